@@ -15,8 +15,9 @@ use crate::math::amm::calculate_quote_asset_amount_swapped;
 use crate::math::amm_spread::{calculate_spread_reserves, get_spread_reserves};
 use crate::math::casting::Cast;
 use crate::math::constants::{
-    CONCENTRATION_PRECISION, FEE_POOL_TO_REVENUE_POOL_THRESHOLD, K_BPS_UPDATE_SCALE,
-    MAX_CONCENTRATION_COEFFICIENT, MAX_K_BPS_INCREASE, MAX_SQRT_K,
+    CONCENTRATION_PRECISION, DEPTH_CONTROLLER_FEE_SURPLUS_BUDGET_DENOMINATOR,
+    FEE_POOL_TO_REVENUE_POOL_THRESHOLD, K_BPS_UPDATE_SCALE, MAX_CONCENTRATION_COEFFICIENT,
+    MAX_K_BPS_INCREASE, MAX_SQRT_K,
 };
 use crate::math::cp_curve::get_update_k_result;
 use crate::math::repeg::get_total_fee_lower_bound;
@@ -29,7 +30,7 @@ use crate::math::{amm, amm_spread, bn, cp_curve, quote_asset::*};
 
 use crate::state::events::CurveRecord;
 use crate::state::oracle::OraclePriceData;
-use crate::state::perp_market::{PerpMarket, AMM};
+use crate::state::perp_market::{PerpMarket, PerpMarketDepthBounds, AMM};
 use crate::state::spot_market::{SpotBalance, SpotBalanceType, SpotMarket};
 use crate::state::user::{SpotPosition, User};
 use crate::validate;
@@ -288,21 +289,38 @@ pub fn update_concentration_coef(market: &mut PerpMarket, scale: u128) -> DriftR
     Ok(())
 }
 
+/// A market's depth bounds must be passed to update its funding rate (and k)
+pub fn select_perp_market_depth_bounds(
+    depth_bounds: Option<&PerpMarketDepthBounds>,
+    market_index: u16,
+    has_depth_bounds: bool,
+) -> DriftResult<Option<&PerpMarketDepthBounds>> {
+    if !has_depth_bounds {
+        return Ok(None);
+    }
+
+    match depth_bounds {
+        Some(depth_bounds) if depth_bounds.market_index == market_index => Ok(Some(depth_bounds)),
+        _ => {
+            msg!("depth bounds for perp market {} not found", market_index);
+            Err(ErrorCode::InvalidPerpMarketDepthBounds)
+        }
+    }
+}
+
+/// Single k update per funding period. The budget combines the funding imbalance budget with the
+/// depth controller budget (if the market has depth bounds), and k is kept within the bounds
 pub fn formulaic_update_k(
     market: &mut PerpMarket,
     _oracle_price_data: &OraclePriceData,
     funding_imbalance_cost: i128,
+    depth_bounds: Option<&PerpMarketDepthBounds>,
     now: i64,
 ) -> DriftResult {
-    let peg_multiplier_before = market.amm.peg_multiplier;
-    let base_asset_reserve_before = market.amm.base_asset_reserve;
-    let quote_asset_reserve_before = market.amm.quote_asset_reserve;
-    let sqrt_k_before = market.amm.sqrt_k;
-
     let funding_imbalance_cost_i64 = funding_imbalance_cost.cast::<i64>()?;
 
     // calculate budget
-    let budget = if funding_imbalance_cost_i64 < 0 {
+    let funding_budget = if funding_imbalance_cost_i64 < 0 {
         // negative cost is period revenue, if spread is low give back half in k increase
         if max(market.amm.long_spread, market.amm.short_spread) <= market.amm.base_spread {
             funding_imbalance_cost_i64.safe_div(2)?.abs()
@@ -318,63 +336,150 @@ pub fn formulaic_update_k(
         0
     };
 
-    if (budget > 0 && market.amm.sqrt_k < MAX_SQRT_K) || (budget < 0 && market.amm.can_lower_k()?) {
-        // single k scale is capped by .1% increase and .1% decrease (regardless of budget)
-        let k_pct_upper_bound = K_BPS_UPDATE_SCALE
-            + MAX_K_BPS_INCREASE * (market.amm.curve_update_intensity as i128) / 100;
-        let k_pct_lower_bound = K_BPS_UPDATE_SCALE
-            - MAX_K_BPS_INCREASE * (market.amm.curve_update_intensity as i128) / 100;
+    let min_lp_sqrt_k = market.amm.user_lp_shares.safe_add(1)?;
+    let (depth_budget, min_sqrt_k, max_sqrt_k) = match depth_bounds {
+        Some(depth_bounds) if depth_bounds.is_enabled() => {
+            let min_sqrt_k = depth_bounds.min_sqrt_k.max(min_lp_sqrt_k);
+            let max_sqrt_k = depth_bounds.max_sqrt_k.min(MAX_SQRT_K);
+            (
+                calculate_depth_controller_budget(market, min_sqrt_k, max_sqrt_k)?,
+                min_sqrt_k,
+                max_sqrt_k,
+            )
+        }
+        _ => (0, min_lp_sqrt_k, u128::MAX),
+    };
 
-        let (k_scale_numerator, k_scale_denominator) = cp_curve::calculate_budgeted_k_scale(
-            market,
-            budget.cast::<i128>()?,
-            k_pct_upper_bound,
-            k_pct_lower_bound,
-        )?;
+    let budget = funding_budget.safe_add(depth_budget)?;
 
-        let new_sqrt_k = bn::U192::from(market.amm.sqrt_k)
-            .safe_mul(bn::U192::from(k_scale_numerator))?
-            .safe_div(bn::U192::from(k_scale_denominator))?
-            .max(bn::U192::from(market.amm.user_lp_shares.safe_add(1)?));
-
-        let update_k_result = get_update_k_result(market, new_sqrt_k, true)?;
-
-        let adjustment_cost = cp_curve::adjust_k_cost(market, &update_k_result)?;
-
-        let cost_applied = apply_cost_to_market(market, adjustment_cost, true)?;
-
-        if cost_applied {
-            cp_curve::update_k(market, &update_k_result)?;
-
-            let peg_multiplier_after = market.amm.peg_multiplier;
-            let base_asset_reserve_after = market.amm.base_asset_reserve;
-            let quote_asset_reserve_after = market.amm.quote_asset_reserve;
-            let sqrt_k_after = market.amm.sqrt_k;
-
-            emit!(CurveRecord {
-                ts: now,
-                record_id: get_then_update_id!(market, next_curve_record_id),
-                market_index: market.market_index,
-                peg_multiplier_before,
-                base_asset_reserve_before,
-                quote_asset_reserve_before,
-                sqrt_k_before,
-                peg_multiplier_after,
-                base_asset_reserve_after,
-                quote_asset_reserve_after,
-                sqrt_k_after,
-                base_asset_amount_long: market.amm.base_asset_amount_long.unsigned_abs(),
-                base_asset_amount_short: market.amm.base_asset_amount_short.unsigned_abs(),
-                base_asset_amount_with_amm: market.amm.base_asset_amount_with_amm,
-                number_of_users: market.number_of_users,
-                adjustment_cost,
-                total_fee: market.amm.total_fee,
-                total_fee_minus_distributions: market.amm.total_fee_minus_distributions,
-                oracle_price: market.amm.historical_oracle_data.last_oracle_price,
-                fill_record: market.next_fill_record_id as u128,
-            });
-        }
+    if (budget > 0 && market.amm.sqrt_k < MAX_SQRT_K.min(max_sqrt_k))
+        || (budget < 0 && market.amm.sqrt_k > min_sqrt_k && market.amm.can_lower_k()?)
+    {
+        update_k_with_budget(market, budget, min_sqrt_k, max_sqrt_k, now)?;
+    }
+
+    Ok(())
+}
+
+/// Scheduled depth controller budget for funding updates.
+/// Raises k when the 24h volume turns over the amm depth and the fee pool is above its lower bound.
+/// Lowers k when the fee pool is drained below its lower bound.
+fn calculate_depth_controller_budget(
+    market: &PerpMarket,
+    min_sqrt_k: u128,
+    max_sqrt_k: u128,
+) -> DriftResult<i64> {
+    let fee_surplus = market
+        .amm
+        .total_fee_minus_distributions
+        .safe_sub(get_total_fee_lower_bound(market)?.cast()?)?;
+
+    let depth_quote = amm::calculate_depth_quote_value(
+        market.amm.sqrt_k,
+        market.amm.historical_oracle_data.last_oracle_price_twap,
+    )?;
+
+    let budget = if fee_surplus > 0
+        && market.amm.volume_24h.cast::<u128>()? >= depth_quote
+        && market.amm.sqrt_k < max_sqrt_k
+    {
+        // spend a slice of the fee surplus on deeper liquidity
+        fee_surplus
+            .safe_div(DEPTH_CONTROLLER_FEE_SURPLUS_BUDGET_DENOMINATOR)?
+            .max(1)
+            .cast::<i64>()?
+    } else if fee_surplus < 0 && market.amm.sqrt_k > min_sqrt_k && market.amm.can_lower_k()? {
+        // recoup a slice of the fee pool deficit with shallower liquidity
+        fee_surplus
+            .safe_div(DEPTH_CONTROLLER_FEE_SURPLUS_BUDGET_DENOMINATOR)?
+            .min(-1)
+            .cast::<i64>()?
+    } else {
+        0
+    };
+
+    if budget != 0 {
+        msg!(
+            "depth controller: fee_surplus={} volume_24h={} depth_quote={} budget={}",
+            fee_surplus,
+            market.amm.volume_24h,
+            depth_quote,
+            budget
+        );
     }
+
+    Ok(budget)
+}
+
+fn update_k_with_budget(
+    market: &mut PerpMarket,
+    budget: i64,
+    min_sqrt_k: u128,
+    max_sqrt_k: u128,
+    now: i64,
+) -> DriftResult {
+    let peg_multiplier_before = market.amm.peg_multiplier;
+    let base_asset_reserve_before = market.amm.base_asset_reserve;
+    let quote_asset_reserve_before = market.amm.quote_asset_reserve;
+    let sqrt_k_before = market.amm.sqrt_k;
+
+    // single k scale is capped by .1% increase and .1% decrease (regardless of budget)
+    let k_pct_upper_bound =
+        K_BPS_UPDATE_SCALE + MAX_K_BPS_INCREASE * (market.amm.curve_update_intensity as i128) / 100;
+    let k_pct_lower_bound =
+        K_BPS_UPDATE_SCALE - MAX_K_BPS_INCREASE * (market.amm.curve_update_intensity as i128) / 100;
+
+    let (k_scale_numerator, k_scale_denominator) = cp_curve::calculate_budgeted_k_scale(
+        market,
+        budget.cast::<i128>()?,
+        k_pct_upper_bound,
+        k_pct_lower_bound,
+    )?;
+
+    let new_sqrt_k = bn::U192::from(market.amm.sqrt_k)
+        .safe_mul(bn::U192::from(k_scale_numerator))?
+        .safe_div(bn::U192::from(k_scale_denominator))?
+        .max(bn::U192::from(min_sqrt_k))
+        .min(bn::U192::from(max_sqrt_k));
+
+    let update_k_result = get_update_k_result(market, new_sqrt_k, true)?;
+
+    let adjustment_cost = cp_curve::adjust_k_cost(market, &update_k_result)?;
+
+    let cost_applied = apply_cost_to_market(market, adjustment_cost, true)?;
+
+    if cost_applied {
+        cp_curve::update_k(market, &update_k_result)?;
+
+        let peg_multiplier_after = market.amm.peg_multiplier;
+        let base_asset_reserve_after = market.amm.base_asset_reserve;
+        let quote_asset_reserve_after = market.amm.quote_asset_reserve;
+        let sqrt_k_after = market.amm.sqrt_k;
+
+        emit!(CurveRecord {
+            ts: now,
+            record_id: get_then_update_id!(market, next_curve_record_id),
+            market_index: market.market_index,
+            peg_multiplier_before,
+            base_asset_reserve_before,
+            quote_asset_reserve_before,
+            sqrt_k_before,
+            peg_multiplier_after,
+            base_asset_reserve_after,
+            quote_asset_reserve_after,
+            sqrt_k_after,
+            base_asset_amount_long: market.amm.base_asset_amount_long.unsigned_abs(),
+            base_asset_amount_short: market.amm.base_asset_amount_short.unsigned_abs(),
+            base_asset_amount_with_amm: market.amm.base_asset_amount_with_amm,
+            number_of_users: market.number_of_users,
+            adjustment_cost,
+            total_fee: market.amm.total_fee,
+            total_fee_minus_distributions: market.amm.total_fee_minus_distributions,
+            oracle_price: market.amm.historical_oracle_data.last_oracle_price,
+            fill_record: market.next_fill_record_id as u128,
+        });
+    }
+
     Ok(())
 }
 
@@ -4,7 +4,8 @@ use crate::math::constants::{
     AMM_RESERVE_PRECISION, MAX_CONCENTRATION_COEFFICIENT, PRICE_PRECISION_I64, QUOTE_PRECISION,
     QUOTE_SPOT_MARKET_INDEX, SPOT_BALANCE_PRECISION, SPOT_CUMULATIVE_INTEREST_PRECISION,
 };
-use crate::state::perp_market::{InsuranceClaim, PoolBalance};
+use crate::state::oracle::HistoricalOracleData;
+use crate::state::perp_market::{InsuranceClaim, PerpMarketDepthBounds, PoolBalance};
 
 #[test]
 fn concentration_coef_tests() {
@@ -137,7 +138,7 @@ fn formualic_k_tests() {
 
     // zero funding cost
     let funding_cost: i128 = 0;
-    formulaic_update_k(&mut market, &oracle_price_data, funding_cost, now).unwrap();
+    formulaic_update_k(&mut market, &oracle_price_data, funding_cost, None, now).unwrap();
     assert_eq!(prev_sqrt_k, market.amm.sqrt_k);
     assert_eq!(
         market.amm.total_fee_minus_distributions,
@@ -146,21 +147,21 @@ fn formualic_k_tests() {
 
     // positive means amm supossedly paid $500 in funding payments for interval
     let funding_cost_2: i128 = (500 * QUOTE_PRECISION) as i128;
-    formulaic_update_k(&mut market, &oracle_price_data, funding_cost_2, now).unwrap();
+    formulaic_update_k(&mut market, &oracle_price_data, funding_cost_2, None, now).unwrap();
     assert_eq!(market.amm.sqrt_k, 499500000000); // max k decrease (.1%)
     assert!(prev_sqrt_k > market.amm.sqrt_k);
     assert_eq!(market.amm.total_fee_minus_distributions, 1000014768); //$.014768 acquired from slippage increase
 
     // negative means amm recieved $500 in funding payments for interval
     let funding_cost_2: i128 = -((500 * QUOTE_PRECISION) as i128);
-    formulaic_update_k(&mut market, &oracle_price_data, funding_cost_2, now).unwrap();
+    formulaic_update_k(&mut market, &oracle_price_data, funding_cost_2, None, now).unwrap();
 
     assert_eq!(market.amm.sqrt_k, 499999500000); // max k increase (.1%)
     assert_eq!(market.amm.total_fee_minus_distributions, 1000000013); //almost full spent from slippage decrease
 
     // negative means amm recieved $.001 in funding payments for interval
     let funding_cost_2: i128 = -((QUOTE_PRECISION / 1000) as i128);
-    formulaic_update_k(&mut market, &oracle_price_data, funding_cost_2, now).unwrap();
+    formulaic_update_k(&mut market, &oracle_price_data, funding_cost_2, None, now).unwrap();
 
     // new numbers bc of increased sqrt_k precision
     assert_eq!(market.amm.sqrt_k, 500015999983); // increase k by 1.000033x
@@ -201,7 +202,7 @@ fn iterative_bounds_formualic_k_tests() {
     while prev_k != new_k && count < 10000 {
         let funding_cost = -(QUOTE_PRECISION as i128);
         prev_k = market.amm.sqrt_k;
-        formulaic_update_k(&mut market, &oracle_price_data, funding_cost, now).unwrap();
+        formulaic_update_k(&mut market, &oracle_price_data, funding_cost, None, now).unwrap();
         new_k = market.amm.sqrt_k;
         count += 1
     }
@@ -245,7 +246,7 @@ fn iterative_no_bounds_formualic_k_tests() {
     while prev_k != new_k && count < 100000 && prev_k < MAX_SQRT_K * 99 / 100 {
         let funding_cost = -((QUOTE_PRECISION * 100000) as i128);
         prev_k = market.amm.sqrt_k;
-        formulaic_update_k(&mut market, &oracle_price_data, funding_cost, now).unwrap();
+        formulaic_update_k(&mut market, &oracle_price_data, funding_cost, None, now).unwrap();
         new_k = market.amm.sqrt_k;
         count += 1
     }
@@ -299,7 +300,7 @@ fn decrease_k_up_to_user_lp_shares() {
     while prev_k != new_k && count < 100000 {
         let funding_cost = (QUOTE_PRECISION * 100000) as i128;
         prev_k = market.amm.sqrt_k;
-        formulaic_update_k(&mut market, &oracle_price_data, funding_cost, now).unwrap();
+        formulaic_update_k(&mut market, &oracle_price_data, funding_cost, None, now).unwrap();
         new_k = market.amm.sqrt_k;
         msg!("quote_asset_reserve:{}", market.amm.quote_asset_reserve);
         msg!("new_k:{}", new_k);
@@ -311,6 +312,126 @@ fn decrease_k_up_to_user_lp_shares() {
     assert_eq!(market.amm.total_fee_minus_distributions, 29796232175);
 }
 
+#[test]
+fn formulaic_update_depth_tests() {
+    let mut market = PerpMarket {
+        amm: AMM {
+            base_asset_reserve: 512295081967,
+            quote_asset_reserve: 488 * AMM_RESERVE_PRECISION,
+            sqrt_k: 500 * AMM_RESERVE_PRECISION,
+            peg_multiplier: 50000000,
+            concentration_coef: MAX_CONCENTRATION_COEFFICIENT,
+            base_asset_amount_with_amm: -12295081967,
+            total_fee_minus_distributions: 1000 * QUOTE_PRECISION as i128,
+            curve_update_intensity: 100,
+            volume_24h: 100_000 * QUOTE_PRECISION as u64,
+            historical_oracle_data: HistoricalOracleData {
+                last_oracle_price_twap: 50 * PRICE_PRECISION_I64,
+                ..HistoricalOracleData::default()
+            },
+            ..AMM::default()
+        },
+        ..PerpMarket::default()
+    };
+    let (new_terminal_quote_reserve, new_terminal_base_reserve) =
+        amm::calculate_terminal_reserves(&market.amm).unwrap();
+    market.amm.terminal_quote_asset_reserve = new_terminal_quote_reserve;
+    let (min_base_asset_reserve, max_base_asset_reserve) =
+        amm::calculate_bid_ask_bounds(market.amm.concentration_coef, new_terminal_base_reserve)
+            .unwrap();
+    market.amm.min_base_asset_reserve = min_base_asset_reserve;
+    market.amm.max_base_asset_reserve = max_base_asset_reserve;
+
+    let now = 10000;
+    let prev_sqrt_k = market.amm.sqrt_k;
+
+    let oracle_price_data = OraclePriceData {
+        price: 50 * PRICE_PRECISION_I64,
+        confidence: 0,
+        delay: 2,
+        has_sufficient_number_of_data_points: true,
+    };
+
+    // no depth bounds, no depth controller
+    formulaic_update_k(&mut market, &oracle_price_data, 0, None, now).unwrap();
+    assert_eq!(market.amm.sqrt_k, prev_sqrt_k);
+
+    // max_sqrt_k = 0 disables the controller
+    let mut depth_bounds = PerpMarketDepthBounds::default();
+    formulaic_update_k(&mut market, &oracle_price_data, 0, Some(&depth_bounds), now).unwrap();
+    assert_eq!(market.amm.sqrt_k, prev_sqrt_k);
+
+    depth_bounds.min_sqrt_k = 499 * AMM_RESERVE_PRECISION;
+    depth_bounds.max_sqrt_k = 501 * AMM_RESERVE_PRECISION;
+
+    // volume below depth ($25k), no increase
+    market.amm.volume_24h = 20_000 * QUOTE_PRECISION as u64;
+    formulaic_update_k(&mut market, &oracle_price_data, 0, Some(&depth_bounds), now).unwrap();
+    assert_eq!(market.amm.sqrt_k, prev_sqrt_k);
+
+    // volume turns over depth and fee pool has surplus, increase up to max_sqrt_k
+    market.amm.volume_24h = 100_000 * QUOTE_PRECISION as u64;
+    formulaic_update_k(&mut market, &oracle_price_data, 0, Some(&depth_bounds), now).unwrap();
+    assert_eq!(market.amm.sqrt_k, 500500000000); // max k increase (.1%)
+    assert!(market.amm.total_fee_minus_distributions < 1000 * QUOTE_PRECISION as i128);
+
+    for _ in 0..100 {
+        formulaic_update_k(&mut market, &oracle_price_data, 0, Some(&depth_bounds), now).unwrap();
+    }
+    assert_eq!(market.amm.sqrt_k, 501 * AMM_RESERVE_PRECISION);
+    assert_eq!(market.amm.total_fee_minus_distributions, 999970553);
+
+    // funding revenue doesnt push k past max_sqrt_k
+    market.amm.base_spread = 1000;
+    formulaic_update_k(
+        &mut market,
+        &oracle_price_data,
+        -100 * QUOTE_PRECISION as i128,
+        Some(&depth_bounds),
+        now,
+    )
+    .unwrap();
+    assert_eq!(market.amm.sqrt_k, 501 * AMM_RESERVE_PRECISION);
+
+    // fee pool in deficit, decrease down to min_sqrt_k
+    market.amm.total_fee_minus_distributions = -100 * QUOTE_PRECISION as i128;
+    market.amm.total_fee_withdrawn = 100 * QUOTE_PRECISION;
+    for _ in 0..100 {
+        formulaic_update_k(&mut market, &oracle_price_data, 0, Some(&depth_bounds), now).unwrap();
+    }
+    assert_eq!(market.amm.sqrt_k, 499 * AMM_RESERVE_PRECISION);
+    assert_eq!(market.amm.total_fee_minus_distributions, -99940988);
+    assert_eq!(market.amm.base_asset_amount_with_amm, -12295081967);
+}
+
+#[test]
+fn select_perp_market_depth_bounds_tests() {
+    let depth_bounds = PerpMarketDepthBounds {
+        market_index: 1,
+        min_sqrt_k: 499 * AMM_RESERVE_PRECISION,
+        max_sqrt_k: 501 * AMM_RESERVE_PRECISION,
+        ..PerpMarketDepthBounds::default()
+    };
+
+    // market without depth bounds ignores the account
+    assert_eq!(
+        select_perp_market_depth_bounds(Some(&depth_bounds), 0, false).unwrap(),
+        None
+    );
+    assert_eq!(
+        select_perp_market_depth_bounds(None, 0, false).unwrap(),
+        None
+    );
+
+    // market with depth bounds requires the account for the market
+    assert_eq!(
+        select_perp_market_depth_bounds(Some(&depth_bounds), 1, true).unwrap(),
+        Some(&depth_bounds)
+    );
+    assert!(select_perp_market_depth_bounds(None, 1, true).is_err());
+    assert!(select_perp_market_depth_bounds(Some(&depth_bounds), 0, true).is_err());
+}
+
 #[test]
 fn update_pool_balances_test_high_util_borrow() {
     let mut market = PerpMarket {
@@ -7,14 +7,20 @@ use crate::controller::amm::formulaic_update_k;
 use crate::controller::position::{
     get_position_index, update_quote_asset_and_break_even_amount, PositionDirection,
 };
-use crate::error::DriftResult;
+use crate::controller::repeg::_update_amm;
+use crate::error::{DriftResult, ErrorCode};
 use crate::get_then_update_id;
 use crate::math::amm;
 use crate::math::casting::Cast;
 use crate::math::constants::{
-    FUNDING_RATE_BUFFER, FUNDING_RATE_OFFSET_DENOMINATOR, ONE_HOUR_I128, TWENTY_FOUR_HOUR,
+    FUNDING_RATE_BUFFER, ONE_HOUR_I128, PREMIUM_INDEX_MAX_SAMPLE_WEIGHT_DENOMINATOR,
+    PRICE_PRECISION_I64, TWENTY_FOUR_HOUR,
+};
+use crate::math::funding::{
+    calculate_funding_interest_rate_offset, calculate_funding_payment,
+    calculate_funding_payment_in_quote_precision, calculate_funding_rate_long_short,
+    calculate_premium_index, calculate_premium_index_sample,
 };
-use crate::math::funding::{calculate_funding_payment, calculate_funding_rate_long_short};
 use crate::math::helpers::on_the_hour_update;
 use crate::math::safe_math::SafeMath;
 use crate::math::stats::calculate_new_twap;
@@ -22,11 +28,14 @@ use crate::math::stats::calculate_new_twap;
 use crate::math::oracle;
 
 use crate::state::events::{FundingPaymentRecord, FundingRateRecord};
+use crate::state::oracle::OraclePriceData;
 use crate::state::oracle_map::OracleMap;
-use crate::state::perp_market::{PerpMarket, AMM};
+use crate::state::paused_operations::PerpOperation;
+use crate::state::perp_market::{MarketStatus, PerpMarket, PerpMarketDepthBounds, AMM};
 use crate::state::perp_market_map::PerpMarketMap;
-use crate::state::state::OracleGuardRails;
-use crate::state::user::User;
+use crate::state::state::{OracleGuardRails, State};
+use crate::state::user::{PerpPosition, User};
+use crate::validate;
 
 pub fn settle_funding_payment(
     user: &mut User,
@@ -152,6 +161,151 @@ pub fn settle_funding_payments(
     Ok(())
 }
 
+/// Samples the premium of the amm mid price over the oracle price, weighted by the time since
+/// the last oracle twap update. A sample's weight is capped at a fraction of the funding period so
+/// a price moved just before an update after a long gap doesn't dominate the index.
+/// Must be called before the oracle twap timestamp is updated.
+pub fn update_premium_index(
+    market: &mut PerpMarket,
+    oracle_price: i64,
+    reserve_price: u64,
+    now: i64,
+) -> DriftResult {
+    let funding_period = max(market.amm.funding_period, 1);
+    let max_sample_weight = max(
+        funding_period.safe_div(PREMIUM_INDEX_MAX_SAMPLE_WEIGHT_DENOMINATOR)?,
+        1,
+    );
+    let since_last = now
+        .safe_sub(market.amm.historical_oracle_data.last_oracle_price_twap_ts)?
+        .clamp(0, max_sample_weight);
+
+    if since_last == 0 {
+        return Ok(());
+    }
+
+    // only keep a funding period worth of samples (e.g. if funding updates were paused)
+    let max_prior_weight = funding_period.safe_sub(since_last)?;
+    let prior_weight = market.amm.premium_index_sample_weight.cast::<i64>()?;
+    if prior_weight > max_prior_weight {
+        market.amm.premium_index_sample_sum = market
+            .amm
+            .premium_index_sample_sum
+            .cast::<i128>()?
+            .safe_mul(max_prior_weight.cast()?)?
+            .safe_div(prior_weight.cast()?)?
+            .cast()?;
+        market.amm.premium_index_sample_weight = max_prior_weight.cast()?;
+    }
+
+    let premium_index_sample = calculate_premium_index_sample(
+        market,
+        market.amm.bid_price(reserve_price)?,
+        market.amm.ask_price(reserve_price)?,
+        oracle_price,
+    )?;
+
+    market.amm.premium_index_sample_sum = market
+        .amm
+        .premium_index_sample_sum
+        .safe_add(premium_index_sample.safe_mul(since_last)?)?;
+
+    market.amm.premium_index_sample_weight = market
+        .amm
+        .premium_index_sample_weight
+        .safe_add(since_last.cast()?)?;
+
+    Ok(())
+}
+
+/// Updates the oracle/mark twaps and premium index and calculates the funding rate for the period
+/// before it is split into long/short rates.
+///
+/// returns (funding_rate, oracle_price_twap, mark_price_twap, premium_index)
+pub fn update_twaps_and_calculate_funding_rate(
+    market: &mut PerpMarket,
+    oracle_price_data: &OraclePriceData,
+    reserve_price: u64,
+    now: UnixTimestamp,
+) -> DriftResult<(i64, i64, u64, Option<i64>)> {
+    let sanitize_clamp_denominator = market.get_sanitize_clamp_denominator()?;
+
+    update_premium_index(market, oracle_price_data.price, reserve_price, now)?;
+
+    let oracle_price_twap = amm::update_oracle_price_twap(
+        &mut market.amm,
+        now,
+        oracle_price_data,
+        Some(reserve_price),
+        sanitize_clamp_denominator,
+    )?;
+
+    // price relates to execution premium / direction
+    let (execution_premium_price, execution_premium_direction) =
+        if market.amm.long_spread > market.amm.short_spread {
+            (
+                market.amm.ask_price(reserve_price)?,
+                Some(PositionDirection::Long),
+            )
+        } else if market.amm.long_spread < market.amm.short_spread {
+            (
+                market.amm.bid_price(reserve_price)?,
+                Some(PositionDirection::Short),
+            )
+        } else {
+            (reserve_price, None)
+        };
+
+    let sanitize_clamp_denominator = market.get_sanitize_clamp_denominator()?;
+    let mid_price_twap = amm::update_mark_twap_from_estimates(
+        &mut market.amm,
+        now,
+        Some(execution_premium_price),
+        execution_premium_direction,
+        sanitize_clamp_denominator,
+    )?;
+
+    let period_adjustment = (24_i128)
+        .safe_mul(ONE_HOUR_I128)?
+        .safe_div(max(ONE_HOUR_I128, market.amm.funding_period as i128))?;
+    // funding period = 1 hour, window = 1 day
+    // low periodicity => quickly updating/settled funding rates => lower funding rate payment per interval
+    // use the premium index sampled over the period, falling back to mark/oracle twap spread if no samples
+    let premium_index = calculate_premium_index(&market.amm)?;
+    let price_spread = match premium_index {
+        Some(premium_index) => premium_index
+            .cast::<i128>()?
+            .safe_mul(oracle_price_twap.cast()?)?
+            .safe_div(PRICE_PRECISION_I64.cast()?)?
+            .cast::<i64>()?,
+        None => mid_price_twap.cast::<i64>()?.safe_sub(oracle_price_twap)?,
+    };
+
+    // add interest rate offset. by default 1/FUNDING_RATE_OFFSET_DENOMINATOR*365 => 7.3% annualized rate
+    let price_spread_with_offset =
+        price_spread.safe_add(calculate_funding_interest_rate_offset(
+            oracle_price_twap,
+            market.funding_interest_rate_adjustment,
+        )?)?;
+
+    // clamp price divergence based on contract tier for funding rate calculation
+    let max_price_spread = market.get_max_price_divergence_for_funding_rate(oracle_price_twap)?;
+    let clamped_price_spread = price_spread_with_offset.clamp(-max_price_spread, max_price_spread);
+
+    let funding_rate = clamped_price_spread
+        .cast::<i128>()?
+        .safe_mul(FUNDING_RATE_BUFFER.cast()?)?
+        .safe_div(period_adjustment.cast()?)?
+        .cast::<i64>()?;
+
+    Ok((
+        funding_rate,
+        oracle_price_twap,
+        mid_price_twap,
+        premium_index,
+    ))
+}
+
 #[allow(clippy::comparison_chain)]
 pub fn update_funding_rate(
     market_index: u16,
@@ -162,7 +316,15 @@ pub fn update_funding_rate(
     guard_rails: &OracleGuardRails,
     funding_paused: bool,
     precomputed_reserve_price: Option<u64>,
+    depth_bounds: Option<&PerpMarketDepthBounds>,
 ) -> DriftResult<bool> {
+    validate!(
+        !market.has_depth_bounds || depth_bounds.is_some(),
+        ErrorCode::InvalidPerpMarketDepthBounds,
+        "depth bounds for perp market {} not found",
+        market_index
+    )?;
+
     let reserve_price = match precomputed_reserve_price {
         Some(reserve_price) => reserve_price,
         None => market.amm.reserve_price()?,
@@ -187,66 +349,8 @@ pub fn update_funding_rate(
 
     if valid_funding_update {
         let oracle_price_data = oracle_map.get_price_data(&market.amm.oracle)?;
-        let sanitize_clamp_denominator = market.get_sanitize_clamp_denominator()?;
-
-        let oracle_price_twap = amm::update_oracle_price_twap(
-            &mut market.amm,
-            now,
-            oracle_price_data,
-            Some(reserve_price),
-            sanitize_clamp_denominator,
-        )?;
-
-        // price relates to execution premium / direction
-        let (execution_premium_price, execution_premium_direction) =
-            if market.amm.long_spread > market.amm.short_spread {
-                (
-                    market.amm.ask_price(reserve_price)?,
-                    Some(PositionDirection::Long),
-                )
-            } else if market.amm.long_spread < market.amm.short_spread {
-                (
-                    market.amm.bid_price(reserve_price)?,
-                    Some(PositionDirection::Short),
-                )
-            } else {
-                (reserve_price, None)
-            };
-
-        let sanitize_clamp_denominator = market.get_sanitize_clamp_denominator()?;
-        let mid_price_twap = amm::update_mark_twap_from_estimates(
-            &mut market.amm,
-            now,
-            Some(execution_premium_price),
-            execution_premium_direction,
-            sanitize_clamp_denominator,
-        )?;
-
-        let period_adjustment = (24_i128)
-            .safe_mul(ONE_HOUR_I128)?
-            .safe_div(max(ONE_HOUR_I128, market.amm.funding_period as i128))?;
-        // funding period = 1 hour, window = 1 day
-        // low periodicity => quickly updating/settled funding rates => lower funding rate payment per interval
-        let price_spread = mid_price_twap.cast::<i64>()?.safe_sub(oracle_price_twap)?;
-
-        // add offset 1/FUNDING_RATE_OFFSET_DENOMINATOR*365. if FUNDING_RATE_OFFSET_DENOMINATOR = 5000 => 7.3% annualized rate
-        let price_spread_with_offset = price_spread.safe_add(
-            oracle_price_twap
-                .abs()
-                .safe_div(FUNDING_RATE_OFFSET_DENOMINATOR)?,
-        )?;
-
-        // clamp price divergence based on contract tier for funding rate calculation
-        let max_price_spread =
-            market.get_max_price_divergence_for_funding_rate(oracle_price_twap)?;
-        let clamped_price_spread =
-            price_spread_with_offset.clamp(-max_price_spread, max_price_spread);
-
-        let funding_rate = clamped_price_spread
-            .cast::<i128>()?
-            .safe_mul(FUNDING_RATE_BUFFER.cast()?)?
-            .safe_div(period_adjustment.cast()?)?
-            .cast::<i64>()?;
+        let (funding_rate, oracle_price_twap, mid_price_twap, premium_index) =
+            update_twaps_and_calculate_funding_rate(market, oracle_price_data, reserve_price, now)?;
 
         let (funding_rate_long, funding_rate_short, funding_imbalance_revenue) =
             calculate_funding_rate_long_short(market, funding_rate.cast()?)?;
@@ -255,7 +359,13 @@ pub fn update_funding_rate(
             // if funding_imbalance_revenue is positive, protocol receives.
             // if funding_imbalance_cost is positive, protocol spends.
             let funding_imbalance_cost = -funding_imbalance_revenue;
-            formulaic_update_k(market, oracle_price_data, funding_imbalance_cost, now)?;
+            formulaic_update_k(
+                market,
+                oracle_price_data,
+                funding_imbalance_cost,
+                depth_bounds,
+                now,
+            )?;
         }
 
         market.amm.cumulative_funding_rate_long = market
@@ -300,12 +410,133 @@ pub fn update_funding_rate(
             period_revenue: market.amm.net_revenue_since_last_funding,
             base_asset_amount_with_amm: market.amm.base_asset_amount_with_amm,
             base_asset_amount_with_unsettled_lp: market.amm.base_asset_amount_with_unsettled_lp,
+            premium_index: premium_index.unwrap_or(0),
+            premium_index_sample_weight: market.amm.premium_index_sample_weight,
         });
 
         market.amm.net_revenue_since_last_funding = 0;
+        market.amm.premium_index_sample_sum = 0;
+        market.amm.premium_index_sample_weight = 0;
     } else {
         return Ok(false);
     }
 
     Ok(true)
 }
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PredictedFundingRate {
+    /// precision: FUNDING_RATE_PRECISION
+    pub funding_rate: i64,
+    /// precision: FUNDING_RATE_PRECISION
+    pub funding_rate_long: i128,
+    /// precision: FUNDING_RATE_PRECISION
+    pub funding_rate_short: i128,
+    /// precision: PRICE_PRECISION
+    pub premium_index: i64,
+    /// precision: PRICE_PRECISION
+    pub oracle_price_twap: i64,
+    /// precision: PRICE_PRECISION
+    pub mark_price_twap: u64,
+    /// seconds until the funding rate can be updated
+    pub time_until_next_update: i64,
+    /// estimated funding payment for the position, positive means the position receives funding
+    /// precision: QUOTE_PRECISION
+    pub funding_payment: i64,
+    /// the oracle blocks the update, so no funding would be paid
+    pub funding_update_blocked: bool,
+}
+
+/// Calculates the funding rate `update_funding_rate` would set if called now.
+/// Runs against a copy of the market so no state is mutated. Errors if the market status or a
+/// pause stops funding updates. If the oracle blocks the update, the predicted rates and payment
+/// are zero.
+pub fn calculate_predicted_funding_rate(
+    market: &PerpMarket,
+    oracle_price_data: &OraclePriceData,
+    state: &State,
+    now: UnixTimestamp,
+    slot: u64,
+    perp_position: Option<&PerpPosition>,
+) -> DriftResult<PredictedFundingRate> {
+    validate!(
+        matches!(
+            market.status,
+            MarketStatus::Active | MarketStatus::ReduceOnly
+        ),
+        ErrorCode::MarketActionPaused,
+        "Market funding is paused",
+    )?;
+
+    validate!(
+        !state.funding_paused()? && !market.is_operation_paused(PerpOperation::UpdateFunding),
+        ErrorCode::MarketActionPaused,
+        "Funding updates are paused",
+    )?;
+
+    let mut market = *market;
+
+    _update_amm(&mut market, oracle_price_data, state, now, slot)?;
+
+    let reserve_price = market.amm.reserve_price()?;
+
+    let funding_update_blocked = oracle::block_operation(
+        &market,
+        oracle_price_data,
+        &state.oracle_guard_rails,
+        reserve_price,
+        slot,
+    )?;
+
+    let (funding_rate, oracle_price_twap, mark_price_twap, premium_index) =
+        update_twaps_and_calculate_funding_rate(
+            &mut market,
+            oracle_price_data,
+            reserve_price,
+            now,
+        )?;
+
+    let funding_rate = if funding_update_blocked {
+        0
+    } else {
+        funding_rate
+    };
+
+    let (funding_rate_long, funding_rate_short, _) =
+        calculate_funding_rate_long_short(&mut market, funding_rate.cast()?)?;
+
+    let time_until_next_update = on_the_hour_update(
+        now,
+        market.amm.last_funding_rate_ts,
+        market.amm.funding_period,
+    )?;
+
+    let funding_payment = match perp_position {
+        Some(perp_position) if perp_position.base_asset_amount != 0 => {
+            let funding_rate = if perp_position.base_asset_amount > 0 {
+                funding_rate_long
+            } else {
+                funding_rate_short
+            };
+
+            calculate_funding_payment_in_quote_precision(
+                funding_rate,
+                perp_position.base_asset_amount.cast()?,
+            )?
+            .cast()?
+        }
+        _ => 0,
+    };
+
+    Ok(PredictedFundingRate {
+        funding_rate,
+        funding_rate_long,
+        funding_rate_short,
+        premium_index: premium_index.unwrap_or(0),
+        oracle_price_twap,
+        mark_price_twap,
+        time_until_next_update,
+        funding_payment,
+        funding_update_blocked,
+    })
+}
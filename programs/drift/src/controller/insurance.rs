@@ -11,27 +11,36 @@ use crate::error::ErrorCode;
 use crate::math::amm::calculate_net_user_pnl;
 use crate::math::casting::Cast;
 use crate::math::constants::{
-    MAX_APR_PER_REVENUE_SETTLE_TO_INSURANCE_FUND_VAULT, ONE_YEAR, PERCENTAGE_PRECISION,
-    SHARE_OF_REVENUE_ALLOCATED_TO_INSURANCE_FUND_VAULT_DENOMINATOR,
+    IF_FACTOR_PRECISION, MAX_APR_PER_REVENUE_SETTLE_TO_INSURANCE_FUND_VAULT, ONE_YEAR,
+    PERCENTAGE_PRECISION, SHARE_OF_REVENUE_ALLOCATED_TO_INSURANCE_FUND_VAULT_DENOMINATOR,
     SHARE_OF_REVENUE_ALLOCATED_TO_INSURANCE_FUND_VAULT_NUMERATOR,
 };
 use crate::math::fuel::calculate_insurance_fuel_bonus;
-use crate::math::helpers::get_proportion_u128;
 use crate::math::helpers::on_the_hour_update;
+use crate::math::helpers::{get_proportion_i128, get_proportion_u128};
 use crate::math::insurance::{
-    calculate_if_shares_lost, calculate_rebase_info, if_shares_to_vault_amount,
-    vault_amount_to_if_shares,
+    calculate_if_shares_lost, calculate_if_value_change, calculate_if_value_per_share,
+    calculate_rebase_info, calculate_revenue_apy, if_shares_to_share_tokens,
+    if_shares_to_vault_amount, share_tokens_to_if_shares, vault_amount_to_if_shares,
 };
+use crate::math::repeg::get_total_fee_lower_bound;
 use crate::math::safe_math::SafeMath;
 use crate::math::spot_balance::get_token_amount;
 use crate::math::spot_withdraw::validate_spot_market_vault_amount;
-use crate::state::events::{InsuranceFundRecord, InsuranceFundStakeRecord, StakeAction};
-use crate::state::insurance_fund_stake::InsuranceFundStake;
+use crate::state::events::{
+    InsuranceFundPerformanceRecord, InsuranceFundRecord, InsuranceFundStakeRecord, StakeAction,
+};
+use crate::state::insurance_fund_stake::{
+    InsuranceFundPerformance, InsuranceFundStake, InsuranceFundTranche, PerpInsuranceFund,
+    SeniorInsuranceFund,
+};
 use crate::state::perp_market::PerpMarket;
-use crate::state::spot_market::{SpotBalanceType, SpotMarket};
+use crate::state::spot_market::{InsuranceFund, SpotBalance, SpotBalanceType, SpotMarket};
 use crate::state::state::State;
 use crate::state::user::UserStats;
-use crate::{emit, validate, FUEL_START_TS, GOV_SPOT_MARKET_INDEX, QUOTE_SPOT_MARKET_INDEX};
+use crate::{
+    emit, load_mut, validate, FUEL_START_TS, GOV_SPOT_MARKET_INDEX, QUOTE_SPOT_MARKET_INDEX,
+};
 
 #[cfg(test)]
 mod tests;
@@ -44,6 +53,12 @@ pub fn update_user_stats_if_stake_amount(
     spot_market: &mut SpotMarket,
     now: i64,
 ) -> DriftResult {
+    // the staked amounts and insurance fuel only track junior stakes. senior and perp insurance
+    // fund stakes would otherwise overwrite them with their own stake's value
+    if insurance_fund_stake.tranche != InsuranceFundTranche::Junior {
+        return Ok(());
+    }
+
     if spot_market.market_index != QUOTE_SPOT_MARKET_INDEX
         && spot_market.market_index != GOV_SPOT_MARKET_INDEX
         && spot_market.fuel_boost_insurance == 0
@@ -123,12 +138,14 @@ pub fn add_insurance_fund_stake(
         insurance_vault_amount,
     )?;
 
-    // reset cost basis if no shares
-    insurance_fund_stake.cost_basis = if if_shares_before == 0 {
-        amount.cast()?
+    // reset cost basis and realized pnl if no shares
+    if if_shares_before == 0 {
+        insurance_fund_stake.cost_basis = amount.cast()?;
+        insurance_fund_stake.realized_pnl = 0;
     } else {
-        insurance_fund_stake.cost_basis.safe_add(amount.cast()?)?
-    };
+        insurance_fund_stake.cost_basis =
+            insurance_fund_stake.cost_basis.safe_add(amount.cast()?)?;
+    }
 
     insurance_fund_stake.increase_if_shares(n_shares, spot_market)?;
 
@@ -442,6 +459,20 @@ pub fn remove_insurance_fund_stake(
 
     let withdraw_amount = amount.min(insurance_fund_stake.last_withdraw_request_value);
 
+    // realize pnl against the removed shares' portion of the remaining cost
+    let cost_removed = get_proportion_i128(
+        insurance_fund_stake
+            .cost_basis
+            .safe_add(insurance_fund_stake.realized_pnl)?
+            .cast()?,
+        n_shares,
+        if_shares_before,
+    )?
+    .cast::<i64>()?;
+    insurance_fund_stake.realized_pnl = insurance_fund_stake
+        .realized_pnl
+        .safe_add(withdraw_amount.cast::<i64>()?.safe_sub(cost_removed)?)?;
+
     insurance_fund_stake.decrease_if_shares(n_shares, spot_market)?;
 
     insurance_fund_stake.cost_basis = insurance_fund_stake
@@ -488,6 +519,172 @@ pub fn remove_insurance_fund_stake(
     Ok(withdraw_amount)
 }
 
+/// Moves n_shares out of the stake and into share tokens the staker can transfer. The shares stay
+/// user shares in the insurance fund, they are just held by whoever owns the tokens.
+/// Returns the number of share tokens to mint
+pub fn tokenize_insurance_fund_stake(
+    n_shares: u128,
+    insurance_vault_amount: u64,
+    insurance_fund_stake: &mut InsuranceFundStake,
+    user_stats: &mut UserStats,
+    spot_market: &mut SpotMarket,
+    now: i64,
+) -> DriftResult<u64> {
+    apply_rebase_to_insurance_fund(insurance_vault_amount, spot_market)?;
+    apply_rebase_to_insurance_fund_stake(insurance_fund_stake, spot_market)?;
+
+    let if_shares_before = insurance_fund_stake.checked_if_shares(spot_market)?;
+    let total_if_shares_before = spot_market.insurance_fund.total_shares;
+    let user_if_shares_before = spot_market.insurance_fund.user_shares;
+
+    validate!(
+        insurance_fund_stake.last_withdraw_request_shares == 0
+            && insurance_fund_stake.last_withdraw_request_value == 0,
+        ErrorCode::IFWithdrawRequestInProgress,
+        "withdraw request in progress"
+    )?;
+
+    validate!(
+        n_shares > 0 && if_shares_before >= n_shares,
+        ErrorCode::InsufficientIFShares,
+        "if_shares_before={} < n_shares={}",
+        if_shares_before,
+        n_shares
+    )?;
+
+    let token_amount = if_shares_to_share_tokens(n_shares, spot_market.insurance_fund.shares_base)?;
+
+    let amount = if_shares_to_vault_amount(
+        n_shares,
+        spot_market.insurance_fund.total_shares,
+        insurance_vault_amount,
+    )?;
+
+    // remaining cost moves with the shares proportionally
+    let cost_basis_delta = get_proportion_i128(
+        insurance_fund_stake
+            .cost_basis
+            .safe_add(insurance_fund_stake.realized_pnl)?
+            .cast()?,
+        n_shares,
+        if_shares_before,
+    )?
+    .cast::<i64>()?;
+    insurance_fund_stake.cost_basis = insurance_fund_stake.cost_basis.safe_sub(cost_basis_delta)?;
+
+    insurance_fund_stake.decrease_if_shares(n_shares, spot_market)?;
+
+    update_user_stats_if_stake_amount(
+        0,
+        insurance_vault_amount,
+        insurance_fund_stake,
+        user_stats,
+        spot_market,
+        now,
+    )?;
+
+    let if_shares_after = insurance_fund_stake.checked_if_shares(spot_market)?;
+
+    emit!(InsuranceFundStakeRecord {
+        ts: now,
+        user_authority: user_stats.authority,
+        action: StakeAction::Tokenize,
+        amount,
+        market_index: spot_market.market_index,
+        insurance_vault_amount_before: insurance_vault_amount,
+        if_shares_before,
+        user_if_shares_before,
+        total_if_shares_before,
+        if_shares_after,
+        total_if_shares_after: spot_market.insurance_fund.total_shares,
+        user_if_shares_after: spot_market.insurance_fund.user_shares,
+    });
+
+    Ok(token_amount)
+}
+
+/// Burns share tokens and credits the shares they represent at the current shares base back to
+/// the stake. Rebases since the tokens were minted are applied through the conversion
+/// Converts share tokens back into shares on the stake. token_amount is rounded down to whole
+/// shares, returns the share tokens to burn
+pub fn redeem_insurance_fund_share_tokens(
+    token_amount: u64,
+    insurance_vault_amount: u64,
+    insurance_fund_stake: &mut InsuranceFundStake,
+    user_stats: &mut UserStats,
+    spot_market: &mut SpotMarket,
+    now: i64,
+) -> DriftResult<u64> {
+    apply_rebase_to_insurance_fund(insurance_vault_amount, spot_market)?;
+    apply_rebase_to_insurance_fund_stake(insurance_fund_stake, spot_market)?;
+
+    let if_shares_before = insurance_fund_stake.checked_if_shares(spot_market)?;
+    let total_if_shares_before = spot_market.insurance_fund.total_shares;
+    let user_if_shares_before = spot_market.insurance_fund.user_shares;
+
+    validate!(
+        insurance_fund_stake.last_withdraw_request_shares == 0
+            && insurance_fund_stake.last_withdraw_request_value == 0,
+        ErrorCode::IFWithdrawRequestInProgress,
+        "withdraw request in progress"
+    )?;
+
+    let n_shares = share_tokens_to_if_shares(token_amount, spot_market.insurance_fund.shares_base)?;
+
+    validate!(
+        n_shares > 0,
+        ErrorCode::InsufficientIFShares,
+        "share token amount {} is worth no shares at shares base {}",
+        token_amount,
+        spot_market.insurance_fund.shares_base
+    )?;
+
+    let amount = if_shares_to_vault_amount(
+        n_shares,
+        spot_market.insurance_fund.total_shares,
+        insurance_vault_amount,
+    )?;
+
+    // reset cost basis and realized pnl if no shares
+    if if_shares_before == 0 {
+        insurance_fund_stake.cost_basis = amount.cast()?;
+        insurance_fund_stake.realized_pnl = 0;
+    } else {
+        insurance_fund_stake.cost_basis =
+            insurance_fund_stake.cost_basis.safe_add(amount.cast()?)?;
+    }
+
+    insurance_fund_stake.increase_if_shares(n_shares, spot_market)?;
+
+    update_user_stats_if_stake_amount(
+        0,
+        insurance_vault_amount,
+        insurance_fund_stake,
+        user_stats,
+        spot_market,
+        now,
+    )?;
+
+    let if_shares_after = insurance_fund_stake.checked_if_shares(spot_market)?;
+
+    emit!(InsuranceFundStakeRecord {
+        ts: now,
+        user_authority: user_stats.authority,
+        action: StakeAction::Redeem,
+        amount,
+        market_index: spot_market.market_index,
+        insurance_vault_amount_before: insurance_vault_amount,
+        if_shares_before,
+        user_if_shares_before,
+        total_if_shares_before,
+        if_shares_after,
+        total_if_shares_after: spot_market.insurance_fund.total_shares,
+        user_if_shares_after: spot_market.insurance_fund.user_shares,
+    });
+
+    if_shares_to_share_tokens(n_shares, spot_market.insurance_fund.shares_base)
+}
+
 pub fn admin_remove_insurance_fund_stake(
     insurance_vault_amount: u64,
     n_shares: u128,
@@ -632,9 +829,16 @@ pub fn transfer_protocol_insurance_fund_stake(
     Ok(withdraw_amount)
 }
 
+/// Settles revenue to the insurance fund if the settle period has passed. The senior tranche must be
+/// passed if the spot market has one so both tranches are settled together
+#[allow(clippy::too_many_arguments)]
 pub fn attempt_settle_revenue_to_insurance_fund<'info>(
     spot_market_vault: &InterfaceAccount<'info, TokenAccount>,
     insurance_fund_vault: &InterfaceAccount<'info, TokenAccount>,
+    senior_insurance_fund: &mut Option<(
+        AccountLoader<'info, SeniorInsuranceFund>,
+        InterfaceAccount<'info, TokenAccount>,
+    )>,
     spot_market: &mut SpotMarket,
     now: i64,
     token_program: &Interface<'info, TokenInterface>,
@@ -642,6 +846,13 @@ pub fn attempt_settle_revenue_to_insurance_fund<'info>(
     state: &State,
     mint: &Option<InterfaceAccount<'info, Mint>>,
 ) -> Result<()> {
+    validate!(
+        senior_insurance_fund.is_some() == spot_market.has_senior_insurance_fund(),
+        ErrorCode::InvalidInsuranceFundTranche,
+        "senior insurance fund must be passed for spot market {}",
+        spot_market.market_index
+    )?;
+
     let valid_revenue_settle_time = if spot_market.insurance_fund.revenue_settle_period > 0 {
         let time_until_next_update = on_the_hour_update(
             now,
@@ -654,18 +865,34 @@ pub fn attempt_settle_revenue_to_insurance_fund<'info>(
         false
     };
 
-    let _token_amount = if valid_revenue_settle_time {
+    if valid_revenue_settle_time {
         // uses proportion of revenue pool allocated to insurance fund
         let spot_market_vault_amount = spot_market_vault.amount;
         let insurance_fund_vault_amount = insurance_fund_vault.amount;
 
-        let token_amount = settle_revenue_to_insurance_fund(
-            spot_market_vault_amount,
-            insurance_fund_vault_amount,
-            spot_market,
-            now,
-            false,
-        )?;
+        let (token_amount, senior_token_amount) = match senior_insurance_fund {
+            Some((senior_insurance_fund, senior_insurance_fund_vault)) => {
+                settle_revenue_to_insurance_fund_tranches(
+                    spot_market_vault_amount,
+                    insurance_fund_vault_amount,
+                    Some((
+                        &mut *load_mut!(senior_insurance_fund)?,
+                        senior_insurance_fund_vault.amount,
+                    )),
+                    spot_market,
+                    now,
+                    false,
+                )?
+            }
+            None => settle_revenue_to_insurance_fund_tranches(
+                spot_market_vault_amount,
+                insurance_fund_vault_amount,
+                None,
+                spot_market,
+                now,
+                false,
+            )?,
+        };
 
         if token_amount > 0 {
             msg!(
@@ -680,17 +907,37 @@ pub fn attempt_settle_revenue_to_insurance_fund<'info>(
                 insurance_fund_vault,
                 drift_signer,
                 state.signer_nonce,
-                token_amount.cast()?,
+                token_amount,
                 mint,
+                None,
             )?;
         }
 
-        spot_market.insurance_fund.last_revenue_settle_ts = now;
+        if let Some((_, senior_insurance_fund_vault)) = senior_insurance_fund {
+            if senior_token_amount > 0 {
+                msg!(
+                    "Spot market_index={} sending {} to senior_insurance_fund_vault",
+                    spot_market.market_index,
+                    senior_token_amount
+                );
+
+                send_from_program_vault(
+                    token_program,
+                    spot_market_vault,
+                    senior_insurance_fund_vault,
+                    drift_signer,
+                    state.signer_nonce,
+                    senior_token_amount,
+                    mint,
+                    None,
+                )?;
+
+                senior_insurance_fund_vault.reload()?;
+            }
+        }
 
-        token_amount
-    } else {
-        0
-    };
+        spot_market.insurance_fund.last_revenue_settle_ts = now;
+    }
 
     Ok(())
 }
@@ -702,11 +949,34 @@ pub fn settle_revenue_to_insurance_fund(
     now: i64,
     check_invariants: bool,
 ) -> DriftResult<u64> {
+    settle_revenue_to_insurance_fund_tranches(
+        spot_market_vault_amount,
+        insurance_vault_amount,
+        None,
+        spot_market,
+        now,
+        check_invariants,
+    )
+    .map(|(junior_token_amount, _)| junior_token_amount)
+}
+
+/// Settles the revenue pool to the insurance fund. If the senior tranche is passed (with its vault
+/// amount), its revenue_share of the settled amount goes to the senior tranche and the protocol's cut
+/// of it is minted with the senior tranche's factors
+/// Returns (junior token amount, senior token amount)
+pub fn settle_revenue_to_insurance_fund_tranches(
+    spot_market_vault_amount: u64,
+    insurance_vault_amount: u64,
+    senior_insurance_fund: Option<(&mut SeniorInsuranceFund, u64)>,
+    spot_market: &mut SpotMarket,
+    now: i64,
+    check_invariants: bool,
+) -> DriftResult<(u64, u64)> {
     update_spot_market_cumulative_interest(spot_market, None, now)?;
 
     if spot_market.insurance_fund.revenue_settle_period == 0 {
         // revenue pool not configured to settle, ending early
-        return Ok(0);
+        return Ok((0, 0));
     }
 
     validate!(
@@ -715,6 +985,23 @@ pub fn settle_revenue_to_insurance_fund(
         "invalid if_factor settings on spot market"
     )?;
 
+    if let Some((senior_insurance_fund, _)) = &senior_insurance_fund {
+        validate!(
+            senior_insurance_fund.market_index == spot_market.market_index,
+            ErrorCode::InvalidInsuranceFundTranche,
+            "senior insurance fund market {} != spot market {}",
+            senior_insurance_fund.market_index,
+            spot_market.market_index
+        )?;
+
+        validate!(
+            senior_insurance_fund.insurance_fund.user_factor
+                <= senior_insurance_fund.insurance_fund.total_factor,
+            ErrorCode::RevenueSettingsCannotSettleToIF,
+            "invalid if_factor settings on senior insurance fund"
+        )?;
+    }
+
     let depositors_claim =
         validate_spot_market_vault_amount(spot_market, spot_market_vault_amount)?;
 
@@ -729,9 +1016,22 @@ pub fn settle_revenue_to_insurance_fund(
         token_amount = depositors_claim.max(0).cast::<u128>()?.safe_div(2)?;
     }
 
-    if spot_market.insurance_fund.user_shares > 0 {
+    // the apr cap applies to both tranches together
+    let (total_insurance_vault_amount, has_user_shares) = match &senior_insurance_fund {
+        Some((senior_insurance_fund, senior_insurance_vault_amount)) => (
+            insurance_vault_amount.safe_add(*senior_insurance_vault_amount)?,
+            spot_market.insurance_fund.user_shares > 0
+                || senior_insurance_fund.insurance_fund.user_shares > 0,
+        ),
+        None => (
+            insurance_vault_amount,
+            spot_market.insurance_fund.user_shares > 0,
+        ),
+    };
+
+    if has_user_shares {
         // only allow MAX_APR_PER_REVENUE_SETTLE_TO_INSURANCE_FUND_VAULT or 1/10th of revenue pool to be settled
-        let capped_apr_amount = insurance_vault_amount
+        let capped_apr_amount = total_insurance_vault_amount
             .cast::<u128>()?
             .safe_mul(MAX_APR_PER_REVENUE_SETTLE_TO_INSURANCE_FUND_VAULT.cast::<u128>()?)?
             .safe_div(PERCENTAGE_PRECISION)?
@@ -759,55 +1059,467 @@ pub fn settle_revenue_to_insurance_fund(
         )?;
     }
 
+    let senior_token_amount = match &senior_insurance_fund {
+        Some((senior_insurance_fund, _)) => insurance_fund_token_amount
+            .cast::<u128>()?
+            .safe_mul(senior_insurance_fund.revenue_share.cast()?)?
+            .safe_div(IF_FACTOR_PRECISION)?
+            .cast::<u64>()?,
+        None => 0,
+    };
+    let junior_token_amount = insurance_fund_token_amount.safe_sub(senior_token_amount)?;
+
     spot_market.insurance_fund.last_revenue_settle_ts = now;
 
-    let protocol_if_factor = spot_market
-        .insurance_fund
+    let total_if_shares_before = mint_protocol_if_shares(
+        &mut spot_market.insurance_fund,
+        junior_token_amount,
+        insurance_vault_amount,
+    )?;
+
+    update_revenue_pool_balances(
+        insurance_fund_token_amount.cast::<u128>()?,
+        &SpotBalanceType::Borrow,
+        spot_market,
+    )?;
+
+    emit!(InsuranceFundRecord {
+        ts: now,
+        spot_market_index: spot_market.market_index,
+        perp_market_index: 0, // todo: make option?
+        amount: junior_token_amount.cast()?,
+
+        user_if_factor: spot_market.insurance_fund.user_factor,
+        total_if_factor: spot_market.insurance_fund.total_factor,
+        vault_amount_before: spot_market_vault_amount,
+        insurance_vault_amount_before: insurance_vault_amount,
+        total_if_shares_before,
+        total_if_shares_after: spot_market.insurance_fund.total_shares,
+        tranche: InsuranceFundTranche::Junior,
+    });
+
+    if let Some((senior_insurance_fund, senior_insurance_vault_amount)) = senior_insurance_fund {
+        let insurance_fund = &mut senior_insurance_fund.insurance_fund;
+        insurance_fund.last_revenue_settle_ts = now;
+
+        let total_if_shares_before = mint_protocol_if_shares(
+            insurance_fund,
+            senior_token_amount,
+            senior_insurance_vault_amount,
+        )?;
+
+        emit!(InsuranceFundRecord {
+            ts: now,
+            spot_market_index: spot_market.market_index,
+            perp_market_index: 0,
+            amount: senior_token_amount.cast()?,
+            user_if_factor: insurance_fund.user_factor,
+            total_if_factor: insurance_fund.total_factor,
+            vault_amount_before: spot_market_vault_amount,
+            insurance_vault_amount_before: senior_insurance_vault_amount,
+            total_if_shares_before,
+            total_if_shares_after: insurance_fund.total_shares,
+            tranche: InsuranceFundTranche::Senior,
+        });
+    }
+
+    Ok((junior_token_amount, senior_token_amount))
+}
+
+/// Gives the protocol its cut of revenue settled to an insurance fund
+/// Returns the insurance fund's total shares after the protocol's cut
+fn mint_protocol_if_shares(
+    insurance_fund: &mut InsuranceFund,
+    token_amount: u64,
+    insurance_vault_amount: u64,
+) -> DriftResult<u128> {
+    let protocol_if_factor = insurance_fund
         .total_factor
-        .safe_sub(spot_market.insurance_fund.user_factor)?;
+        .safe_sub(insurance_fund.user_factor)?;
+
+    if protocol_if_factor > 0 {
+        let n_shares = vault_amount_to_if_shares(
+            token_amount
+                .safe_mul(protocol_if_factor.cast()?)?
+                .safe_div(insurance_fund.total_factor.cast()?)?,
+            insurance_fund.total_shares,
+            insurance_vault_amount,
+        )?;
+
+        insurance_fund.total_shares = insurance_fund.total_shares.safe_add(n_shares)?;
+    }
+
+    Ok(insurance_fund.total_shares)
+}
+
+/// Attributes the change in the insurance fund's value per share since the last update to revenue
+/// or losses. Returns (revenue, losses)
+pub fn update_insurance_fund_performance(
+    insurance_fund_performance: &mut InsuranceFundPerformance,
+    insurance_fund: &InsuranceFund,
+    insurance_vault_amount: u64,
+    now: i64,
+) -> DriftResult<(u64, u64)> {
+    let value_per_share = calculate_if_value_per_share(
+        insurance_fund.total_shares,
+        insurance_fund.shares_base,
+        insurance_vault_amount,
+    )?;
+
+    let (revenue, losses) = calculate_if_value_change(
+        insurance_fund_performance.last_value_per_share,
+        value_per_share,
+        insurance_fund.total_shares,
+        insurance_fund.shares_base,
+    )?;
+
+    insurance_fund_performance.cumulative_revenue = insurance_fund_performance
+        .cumulative_revenue
+        .safe_add(revenue.cast()?)?;
+    insurance_fund_performance.cumulative_losses = insurance_fund_performance
+        .cumulative_losses
+        .safe_add(losses.cast()?)?;
+    insurance_fund_performance.last_value_per_share = value_per_share;
+    insurance_fund_performance.last_update_ts = now;
+
+    Ok((revenue, losses))
+}
+
+/// Records a revenue settlement to an insurance fund tranche. insurance_fund_before and
+/// insurance_vault_amount are the tranche's insurance fund and vault amount before the settlement,
+/// insurance_fund is the tranche's insurance fund after it
+pub fn update_insurance_fund_performance_for_revenue_settle(
+    insurance_fund_performance: &mut InsuranceFundPerformance,
+    insurance_fund_before: &InsuranceFund,
+    insurance_vault_amount: u64,
+    insurance_fund: &InsuranceFund,
+    revenue_settle_period: i64,
+    revenue: u64,
+    now: i64,
+) -> DriftResult {
+    // vault movements since the last update that didn't come from this settlement
+    let (revenue_since_last_update, losses_since_last_update) = update_insurance_fund_performance(
+        insurance_fund_performance,
+        insurance_fund_before,
+        insurance_vault_amount,
+        now,
+    )?;
+
+    let time_since_last_settle = if insurance_fund_performance.last_revenue_settle_ts > 0 {
+        now.safe_sub(insurance_fund_performance.last_revenue_settle_ts)?
+    } else {
+        revenue_settle_period
+    };
+
+    insurance_fund_performance.last_revenue_apy =
+        calculate_revenue_apy(revenue, insurance_vault_amount, time_since_last_settle)?;
+    insurance_fund_performance.last_revenue_settle_ts = now;
+
+    insurance_fund_performance.cumulative_revenue = insurance_fund_performance
+        .cumulative_revenue
+        .safe_add(revenue.cast()?)?;
+    insurance_fund_performance.last_value_per_share = calculate_if_value_per_share(
+        insurance_fund.total_shares,
+        insurance_fund.shares_base,
+        insurance_vault_amount.safe_add(revenue)?,
+    )?;
+
+    emit!(InsuranceFundPerformanceRecord {
+        ts: now,
+        market_index: insurance_fund_performance.market_index,
+        tranche: insurance_fund_performance.tranche,
+        insurance_vault_amount_before: insurance_vault_amount,
+        revenue,
+        loss: 0,
+        revenue_since_last_update,
+        losses_since_last_update,
+        cumulative_revenue: insurance_fund_performance.cumulative_revenue,
+        cumulative_losses: insurance_fund_performance.cumulative_losses,
+        revenue_apy: insurance_fund_performance.last_revenue_apy,
+    });
+
+    Ok(())
+}
+
+/// Records a payment from an insurance fund tranche to cover a loss. insurance_vault_amount is the
+/// tranche's vault amount before the payment
+pub fn update_insurance_fund_performance_for_loss(
+    insurance_fund_performance: &mut InsuranceFundPerformance,
+    insurance_fund: &InsuranceFund,
+    insurance_vault_amount: u64,
+    loss: u64,
+    now: i64,
+) -> DriftResult {
+    // vault movements since the last update that didn't come from this payment
+    let (revenue_since_last_update, losses_since_last_update) = update_insurance_fund_performance(
+        insurance_fund_performance,
+        insurance_fund,
+        insurance_vault_amount,
+        now,
+    )?;
+
+    insurance_fund_performance.cumulative_losses = insurance_fund_performance
+        .cumulative_losses
+        .safe_add(loss.cast()?)?;
+    insurance_fund_performance.last_value_per_share = calculate_if_value_per_share(
+        insurance_fund.total_shares,
+        insurance_fund.shares_base,
+        insurance_vault_amount.safe_sub(loss)?,
+    )?;
+
+    emit!(InsuranceFundPerformanceRecord {
+        ts: now,
+        market_index: insurance_fund_performance.market_index,
+        tranche: insurance_fund_performance.tranche,
+        insurance_vault_amount_before: insurance_vault_amount,
+        revenue: 0,
+        loss,
+        revenue_since_last_update,
+        losses_since_last_update,
+        cumulative_revenue: insurance_fund_performance.cumulative_revenue,
+        cumulative_losses: insurance_fund_performance.cumulative_losses,
+        revenue_apy: insurance_fund_performance.last_revenue_apy,
+    });
+
+    Ok(())
+}
+
+/// Records a payment in the paying tranche's insurance fund performance, if it has one
+pub fn record_insurance_fund_payment(
+    insurance_fund_performance: &Option<AccountLoader<InsuranceFundPerformance>>,
+    insurance_fund: &InsuranceFund,
+    insurance_vault_amount: u64,
+    payment: u64,
+    now: i64,
+) -> Result<()> {
+    if payment == 0 {
+        return Ok(());
+    }
+
+    if let Some(insurance_fund_performance) = insurance_fund_performance {
+        update_insurance_fund_performance_for_loss(
+            &mut *load_mut!(insurance_fund_performance)?,
+            insurance_fund,
+            insurance_vault_amount,
+            payment,
+            now,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InsuranceFundStakePerformance {
+    /// shares at the insurance fund's current shares base
+    pub if_shares: u128,
+    /// what the shares can currently be withdrawn for
+    /// precision: token mint precision
+    pub value: u64,
+    /// precision: token mint precision
+    pub cost_basis: i64,
+    /// precision: token mint precision
+    pub realized_pnl: i64,
+    /// value less the cost remaining in the stake
+    /// precision: token mint precision
+    pub unrealized_pnl: i64,
+    /// net pnl since the stake began if positive
+    /// precision: token mint precision
+    pub earned: u64,
+    /// net pnl since the stake began if negative
+    /// precision: token mint precision
+    pub lost: u64,
+}
+
+pub fn calculate_insurance_fund_stake_performance(
+    insurance_vault_amount: u64,
+    insurance_fund_stake: &InsuranceFundStake,
+    spot_market: &SpotMarket,
+) -> DriftResult<InsuranceFundStakePerformance> {
+    let mut spot_market = *spot_market;
+    let mut insurance_fund_stake = *insurance_fund_stake;
+
+    apply_rebase_to_insurance_fund(insurance_vault_amount, &mut spot_market)?;
+    apply_rebase_to_insurance_fund_stake(&mut insurance_fund_stake, &mut spot_market)?;
+
+    let if_shares = insurance_fund_stake.checked_if_shares(&spot_market)?;
+    let requested_shares = insurance_fund_stake.last_withdraw_request_shares;
+
+    // requested shares can only be withdrawn for at most their value at the time of the request
+    let requested_value = if_shares_to_vault_amount(
+        requested_shares,
+        spot_market.insurance_fund.total_shares,
+        insurance_vault_amount,
+    )?
+    .min(insurance_fund_stake.last_withdraw_request_value);
+
+    let value = if_shares_to_vault_amount(
+        if_shares.safe_sub(requested_shares)?,
+        spot_market.insurance_fund.total_shares,
+        insurance_vault_amount,
+    )?
+    .safe_add(requested_value)?;
+
+    let remaining_cost = insurance_fund_stake
+        .cost_basis
+        .safe_add(insurance_fund_stake.realized_pnl)?;
+    let unrealized_pnl = value.cast::<i64>()?.safe_sub(remaining_cost)?;
+    let pnl = insurance_fund_stake.realized_pnl.safe_add(unrealized_pnl)?;
+
+    Ok(InsuranceFundStakePerformance {
+        if_shares,
+        value,
+        cost_basis: insurance_fund_stake.cost_basis,
+        realized_pnl: insurance_fund_stake.realized_pnl,
+        unrealized_pnl,
+        earned: pnl.max(0).unsigned_abs(),
+        lost: pnl.min(0).unsigned_abs(),
+    })
+}
+
+/// Moves a share of the perp market's fee pool surplus into its isolated insurance fund. The share
+/// is the perp insurance fund's total_factor and is settled before the surplus can flow to the
+/// shared revenue pool
+pub fn settle_revenue_to_perp_insurance_fund(
+    spot_market_vault_amount: u64,
+    insurance_vault_amount: u64,
+    spot_market: &mut SpotMarket,
+    perp_market: &mut PerpMarket,
+    perp_insurance_fund: &mut PerpInsuranceFund,
+    now: i64,
+) -> DriftResult<u64> {
+    validate!(
+        perp_insurance_fund.market_index == perp_market.market_index,
+        ErrorCode::InvalidInsuranceFundTranche,
+        "perp insurance fund market {} != perp market {}",
+        perp_insurance_fund.market_index,
+        perp_market.market_index
+    )?;
+
+    validate!(
+        spot_market.market_index == QUOTE_SPOT_MARKET_INDEX,
+        ErrorCode::InvalidSpotMarketAccount,
+        "perp insurance fund revenue is settled from the quote spot market"
+    )?;
+
+    update_spot_market_cumulative_interest(spot_market, None, now)?;
+
+    let insurance_fund = &mut perp_insurance_fund.insurance_fund;
+
+    validate!(
+        insurance_fund.revenue_settle_period > 0,
+        ErrorCode::RevenueSettingsCannotSettleToIF,
+        "perp insurance fund not configured to settle revenue"
+    )?;
+
+    validate!(
+        insurance_fund.user_factor <= insurance_fund.total_factor,
+        ErrorCode::RevenueSettingsCannotSettleToIF,
+        "invalid if_factor settings on perp insurance fund"
+    )?;
+
+    validate_spot_market_vault_amount(spot_market, spot_market_vault_amount)?;
+
+    let fee_pool_token_amount = get_token_amount(
+        perp_market.amm.fee_pool.balance(),
+        spot_market,
+        perp_market.amm.fee_pool.balance_type(),
+    )?;
+
+    // only the fees the amm doesn't need to keep are available
+    let fee_pool_surplus = perp_market
+        .amm
+        .total_fee_minus_distributions
+        .safe_sub(get_total_fee_lower_bound(perp_market)?.cast()?)?
+        .max(0)
+        .cast::<u128>()?
+        .min(fee_pool_token_amount);
+
+    let mut token_amount = fee_pool_surplus
+        .safe_mul(insurance_fund.total_factor.cast()?)?
+        .safe_div(IF_FACTOR_PRECISION)?;
+
+    if insurance_fund.user_shares > 0 {
+        let capped_apr_amount = insurance_vault_amount
+            .cast::<u128>()?
+            .safe_mul(MAX_APR_PER_REVENUE_SETTLE_TO_INSURANCE_FUND_VAULT.cast::<u128>()?)?
+            .safe_div(PERCENTAGE_PRECISION)?
+            .safe_div(
+                ONE_YEAR
+                    .safe_div(insurance_fund.revenue_settle_period.cast()?)?
+                    .max(1),
+            )?;
+        token_amount = token_amount.min(capped_apr_amount);
+    }
+
+    let token_amount = token_amount.cast::<u64>()?;
+
+    validate!(
+        token_amount != 0,
+        ErrorCode::NoRevenueToSettleToIF,
+        "no amount to settle to perp insurance fund"
+    )?;
+
+    insurance_fund.last_revenue_settle_ts = now;
+
+    let protocol_if_factor = insurance_fund
+        .total_factor
+        .safe_sub(insurance_fund.user_factor)?;
 
     // give protocol its cut
     if protocol_if_factor > 0 {
         let n_shares = vault_amount_to_if_shares(
-            insurance_fund_token_amount
+            token_amount
                 .safe_mul(protocol_if_factor.cast()?)?
-                .safe_div(spot_market.insurance_fund.total_factor.cast()?)?,
-            spot_market.insurance_fund.total_shares,
+                .safe_div(insurance_fund.total_factor.cast()?)?,
+            insurance_fund.total_shares,
             insurance_vault_amount,
         )?;
 
-        spot_market.insurance_fund.total_shares =
-            spot_market.insurance_fund.total_shares.safe_add(n_shares)?;
+        insurance_fund.total_shares = insurance_fund.total_shares.safe_add(n_shares)?;
     }
 
-    let total_if_shares_before = spot_market.insurance_fund.total_shares;
+    let total_if_shares_before = insurance_fund.total_shares;
 
-    update_revenue_pool_balances(
-        insurance_fund_token_amount.cast::<u128>()?,
+    update_spot_balances(
+        token_amount.cast()?,
         &SpotBalanceType::Borrow,
         spot_market,
+        &mut perp_market.amm.fee_pool,
+        false,
     )?;
 
+    perp_market.amm.total_fee_minus_distributions = perp_market
+        .amm
+        .total_fee_minus_distributions
+        .safe_sub(token_amount.cast()?)?;
+
+    perp_market.amm.total_fee_withdrawn = perp_market
+        .amm
+        .total_fee_withdrawn
+        .safe_add(token_amount.cast()?)?;
+
     emit!(InsuranceFundRecord {
         ts: now,
         spot_market_index: spot_market.market_index,
-        perp_market_index: 0, // todo: make option?
-        amount: insurance_fund_token_amount.cast()?,
-
-        user_if_factor: spot_market.insurance_fund.user_factor,
-        total_if_factor: spot_market.insurance_fund.total_factor,
+        perp_market_index: perp_market.market_index,
+        amount: token_amount.cast()?,
+        user_if_factor: insurance_fund.user_factor,
+        total_if_factor: insurance_fund.total_factor,
         vault_amount_before: spot_market_vault_amount,
         insurance_vault_amount_before: insurance_vault_amount,
         total_if_shares_before,
-        total_if_shares_after: spot_market.insurance_fund.total_shares,
+        total_if_shares_after: insurance_fund.total_shares,
+        tranche: InsuranceFundTranche::PerpMarket,
     });
 
-    insurance_fund_token_amount.cast()
+    Ok(token_amount)
 }
 
 pub fn resolve_perp_pnl_deficit(
     vault_amount: u64,
     insurance_vault_amount: u64,
+    perp_insurance_vault_amount: u64,
     spot_market: &mut SpotMarket,
     market: &mut PerpMarket,
     now: i64,
@@ -878,19 +1590,27 @@ pub fn resolve_perp_pnl_deficit(
         .safe_sub(market.insurance_claim.quote_settled_insurance)?
         .cast::<i128>()?;
 
+    // the perp market's isolated insurance fund pays first and doesn't count towards quote_max_insurance
+    let perp_insurance_withdraw = excess_user_pnl_imbalance
+        .min(max_revenue_withdraw_per_period)
+        .min(perp_insurance_vault_amount.saturating_sub(1).cast()?);
+
     validate!(
-        max_insurance_withdraw > 0,
+        max_insurance_withdraw > 0 || perp_insurance_withdraw > 0,
         ErrorCode::MaxIFWithdrawReached,
         "max_insurance_withdraw={}/{} as already been reached",
         market.insurance_claim.quote_settled_insurance,
         market.insurance_claim.quote_max_insurance,
     )?;
 
-    let insurance_withdraw = excess_user_pnl_imbalance
+    let shared_insurance_withdraw = excess_user_pnl_imbalance
         .min(max_revenue_withdraw_per_period)
+        .safe_sub(perp_insurance_withdraw)?
         .min(max_insurance_withdraw)
         .min(insurance_vault_amount.saturating_sub(1).cast()?);
 
+    let insurance_withdraw = perp_insurance_withdraw.safe_add(shared_insurance_withdraw)?;
+
     validate!(
         insurance_withdraw > 0,
         ErrorCode::NoIFWithdrawAvailable,
@@ -912,7 +1632,7 @@ pub fn resolve_perp_pnl_deficit(
     market.insurance_claim.quote_settled_insurance = market
         .insurance_claim
         .quote_settled_insurance
-        .safe_add(insurance_withdraw.cast()?)?;
+        .safe_add(shared_insurance_withdraw.cast()?)?;
 
     validate!(
         market.insurance_claim.quote_settled_insurance
@@ -944,7 +1664,223 @@ pub fn resolve_perp_pnl_deficit(
         insurance_vault_amount_before: insurance_vault_amount,
         total_if_shares_before,
         total_if_shares_after: spot_market.insurance_fund.total_shares,
+        tranche: InsuranceFundTranche::Junior,
     });
 
     insurance_withdraw.cast()
 }
+
+/// Runs insurance fund logic against the senior tranche by swapping its fund into the spot market
+/// for the duration of f. Shares, rebases and revenue settles then all use the senior tranche's state
+pub fn with_senior_insurance_fund<T>(
+    spot_market: &mut SpotMarket,
+    senior_insurance_fund: &mut SeniorInsuranceFund,
+    f: impl FnOnce(&mut SpotMarket) -> DriftResult<T>,
+) -> DriftResult<T> {
+    validate!(
+        senior_insurance_fund.market_index == spot_market.market_index,
+        ErrorCode::InvalidInsuranceFundTranche,
+        "senior insurance fund market {} != spot market {}",
+        senior_insurance_fund.market_index,
+        spot_market.market_index
+    )?;
+
+    with_insurance_fund(spot_market, &mut senior_insurance_fund.insurance_fund, f)
+}
+
+/// Runs insurance fund logic against a perp market's isolated insurance fund by swapping it into
+/// the quote spot market for the duration of f
+pub fn with_perp_insurance_fund<T>(
+    quote_spot_market: &mut SpotMarket,
+    perp_insurance_fund: &mut PerpInsuranceFund,
+    f: impl FnOnce(&mut SpotMarket) -> DriftResult<T>,
+) -> DriftResult<T> {
+    validate!(
+        quote_spot_market.market_index == QUOTE_SPOT_MARKET_INDEX,
+        ErrorCode::InvalidSpotMarketAccount,
+        "perp insurance funds are staked in the quote spot market"
+    )?;
+
+    with_insurance_fund(
+        quote_spot_market,
+        &mut perp_insurance_fund.insurance_fund,
+        f,
+    )
+}
+
+fn with_insurance_fund<T>(
+    spot_market: &mut SpotMarket,
+    insurance_fund: &mut InsuranceFund,
+    f: impl FnOnce(&mut SpotMarket) -> DriftResult<T>,
+) -> DriftResult<T> {
+    std::mem::swap(&mut spot_market.insurance_fund, insurance_fund);
+    let result = f(spot_market);
+    std::mem::swap(&mut spot_market.insurance_fund, insurance_fund);
+
+    result
+}
+
+/// The insurance vault balance available to cover a loss. The senior vault is only included
+/// if passed and, like the junior vault, must keep at least 1 token
+pub fn get_tranched_insurance_vault_amount(
+    junior_vault_amount: u64,
+    senior_vault_amount: Option<u64>,
+) -> DriftResult<u64> {
+    match senior_vault_amount {
+        Some(senior_vault_amount) => {
+            junior_vault_amount.safe_add(senior_vault_amount.saturating_sub(1))
+        }
+        None => Ok(junior_vault_amount),
+    }
+}
+
+/// Splits an insurance payment between the tranches. The junior tranche pays first and the
+/// senior tranche only covers what the junior tranche can't
+/// Returns (junior payment, senior payment)
+pub fn calculate_tranche_payments(
+    payment: u64,
+    junior_vault_amount: u64,
+    senior_vault_amount: Option<u64>,
+) -> DriftResult<(u64, u64)> {
+    let junior_payment = payment.min(junior_vault_amount.saturating_sub(1));
+    let senior_payment = payment.safe_sub(junior_payment)?;
+
+    if senior_payment > 0 {
+        let senior_vault_amount = senior_vault_amount.unwrap_or(0);
+        validate!(
+            senior_payment < senior_vault_amount,
+            ErrorCode::InsufficientCollateral,
+            "Senior Insurance Fund balance InsufficientCollateral for payment: !{} < {}",
+            senior_payment,
+            senior_vault_amount
+        )?;
+    }
+
+    Ok((junior_payment, senior_payment))
+}
+
+/// The part of a perp market's insurance payment covered by its isolated insurance fund. The
+/// isolated vault must keep at least 1 token
+pub fn calculate_perp_insurance_fund_payment(
+    payment: u64,
+    perp_insurance_fund_vault_amount: Option<u64>,
+) -> u64 {
+    payment.min(
+        perp_insurance_fund_vault_amount
+            .unwrap_or(0)
+            .saturating_sub(1),
+    )
+}
+
+/// Sends a perp market's insurance payment to the quote spot market vault. The perp market's
+/// isolated insurance fund pays first and the shared insurance fund tranches cover the rest.
+/// Returns the (perp, junior, senior) insurance fund payments
+#[allow(clippy::too_many_arguments)]
+pub fn send_from_perp_insurance_funds<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    perp_insurance_fund_vault: &Option<InterfaceAccount<'info, TokenAccount>>,
+    insurance_fund_vault: &InterfaceAccount<'info, TokenAccount>,
+    senior_insurance_fund_vault: &Option<InterfaceAccount<'info, TokenAccount>>,
+    spot_market_vault: &InterfaceAccount<'info, TokenAccount>,
+    drift_signer: &AccountInfo<'info>,
+    state: &State,
+    payment: u64,
+    mint: &Option<InterfaceAccount<'info, Mint>>,
+) -> Result<(u64, u64, u64)> {
+    let perp_insurance_fund_payment = calculate_perp_insurance_fund_payment(
+        payment,
+        perp_insurance_fund_vault
+            .as_ref()
+            .map(|perp_insurance_fund_vault| perp_insurance_fund_vault.amount),
+    );
+
+    if let Some(perp_insurance_fund_vault) = perp_insurance_fund_vault {
+        if perp_insurance_fund_payment > 0 {
+            send_from_program_vault(
+                token_program,
+                perp_insurance_fund_vault,
+                spot_market_vault,
+                drift_signer,
+                state.signer_nonce,
+                perp_insurance_fund_payment,
+                mint,
+                None,
+            )?;
+        }
+    }
+
+    let shared_payment = payment.safe_sub(perp_insurance_fund_payment)?;
+    let (junior_payment, senior_payment) = if shared_payment > 0 {
+        send_from_insurance_fund_tranches(
+            token_program,
+            insurance_fund_vault,
+            senior_insurance_fund_vault,
+            spot_market_vault,
+            drift_signer,
+            state,
+            shared_payment,
+            mint,
+        )?
+    } else {
+        (0, 0)
+    };
+
+    Ok((perp_insurance_fund_payment, junior_payment, senior_payment))
+}
+
+/// Sends an insurance payment to the spot market vault, junior tranche first. Returns the
+/// (junior, senior) insurance fund payments
+#[allow(clippy::too_many_arguments)]
+pub fn send_from_insurance_fund_tranches<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    insurance_fund_vault: &InterfaceAccount<'info, TokenAccount>,
+    senior_insurance_fund_vault: &Option<InterfaceAccount<'info, TokenAccount>>,
+    spot_market_vault: &InterfaceAccount<'info, TokenAccount>,
+    drift_signer: &AccountInfo<'info>,
+    state: &State,
+    payment: u64,
+    mint: &Option<InterfaceAccount<'info, Mint>>,
+) -> Result<(u64, u64)> {
+    let (junior_payment, senior_payment) = calculate_tranche_payments(
+        payment,
+        insurance_fund_vault.amount,
+        senior_insurance_fund_vault
+            .as_ref()
+            .map(|senior_insurance_fund_vault| senior_insurance_fund_vault.amount),
+    )?;
+
+    if junior_payment > 0 {
+        send_from_program_vault(
+            token_program,
+            insurance_fund_vault,
+            spot_market_vault,
+            drift_signer,
+            state.signer_nonce,
+            junior_payment,
+            mint,
+            None,
+        )?;
+    }
+
+    if let Some(senior_insurance_fund_vault) = senior_insurance_fund_vault {
+        if senior_payment > 0 {
+            msg!(
+                "junior insurance fund exhausted, senior insurance fund paying {}",
+                senior_payment
+            );
+
+            send_from_program_vault(
+                token_program,
+                senior_insurance_fund_vault,
+                spot_market_vault,
+                drift_signer,
+                state.signer_nonce,
+                senior_payment,
+                mint,
+                None,
+            )?;
+        }
+    }
+
+    Ok((junior_payment, senior_payment))
+}
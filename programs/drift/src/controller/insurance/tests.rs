@@ -4,7 +4,8 @@ use crate::controller::insurance::*;
 use crate::math::constants::{
     QUOTE_PRECISION, SPOT_BALANCE_PRECISION, SPOT_CUMULATIVE_INTEREST_PRECISION,
 };
-use crate::state::perp_market::PoolBalance;
+use crate::state::insurance_fund_stake::{InsuranceFundPerformance, InsuranceFundTranche};
+use crate::state::perp_market::{PoolBalance, AMM};
 use crate::state::spot_market::InsuranceFund;
 use crate::state::user::UserStats;
 #[test]
@@ -1619,3 +1620,631 @@ fn test_transfer_protocol_owned_stake() {
     )
     .is_err());
 }
+
+#[test]
+pub fn tokenize_and_redeem_if_stake_across_rebase() {
+    let mut if_balance = 0;
+
+    let mut if_stake_1 = InsuranceFundStake::new(Pubkey::default(), 0, 0);
+    let mut user_stats_1 = UserStats {
+        number_of_sub_accounts: 0,
+        ..UserStats::default()
+    };
+
+    let mut if_stake_2 = InsuranceFundStake::new(Pubkey::default(), 0, 0);
+    let mut user_stats_2 = UserStats {
+        number_of_sub_accounts: 0,
+        ..UserStats::default()
+    };
+
+    let amount = (QUOTE_PRECISION * 100_000) as u64; // $100k
+    let mut spot_market = SpotMarket {
+        deposit_balance: 0,
+        cumulative_deposit_interest: 1111 * SPOT_CUMULATIVE_INTEREST_PRECISION / 1000,
+        insurance_fund: InsuranceFund {
+            unstaking_period: 0,
+            ..InsuranceFund::default()
+        },
+        ..SpotMarket::default()
+    };
+
+    add_insurance_fund_stake(
+        amount,
+        if_balance,
+        &mut if_stake_1,
+        &mut user_stats_1,
+        &mut spot_market,
+        0,
+    )
+    .unwrap();
+    if_balance = amount;
+
+    let shares = if_stake_1.unchecked_if_shares();
+    assert_eq!(shares, amount as u128);
+
+    let token_amount = tokenize_insurance_fund_stake(
+        shares / 2,
+        if_balance,
+        &mut if_stake_1,
+        &mut user_stats_1,
+        &mut spot_market,
+        0,
+    )
+    .unwrap();
+    assert_eq!(token_amount, amount / 2);
+    assert_eq!(if_stake_1.unchecked_if_shares(), shares / 2);
+    assert_eq!(if_stake_1.cost_basis, (amount / 2) as i64);
+    // tokenized shares are still user shares
+    assert_eq!(spot_market.insurance_fund.user_shares, shares);
+    assert_eq!(user_stats_1.if_staked_quote_asset_amount, amount / 2);
+
+    // cant tokenize more shares than the stake has
+    assert!(tokenize_insurance_fund_stake(
+        shares,
+        if_balance,
+        &mut if_stake_1,
+        &mut user_stats_1,
+        &mut spot_market,
+        0,
+    )
+    .is_err());
+
+    // if gets drained and rebases
+    if_balance = QUOTE_PRECISION as u64;
+
+    // tokens that dont make up a whole share arent burned
+    let burn_amount = redeem_insurance_fund_share_tokens(
+        token_amount + 9_999,
+        if_balance,
+        &mut if_stake_2,
+        &mut user_stats_2,
+        &mut spot_market,
+        0,
+    )
+    .unwrap();
+    assert_eq!(burn_amount, token_amount);
+    assert_eq!(spot_market.insurance_fund.shares_base, 4);
+    assert_eq!(if_stake_2.if_base, 4);
+    let n_shares = shares / 2 / 10_000;
+    assert_eq!(if_stake_2.unchecked_if_shares(), n_shares);
+    assert_eq!(if_stake_2.cost_basis, (if_balance / 2) as i64);
+    assert_eq!(user_stats_2.if_staked_quote_asset_amount, if_balance / 2);
+
+    apply_rebase_to_insurance_fund_stake(&mut if_stake_1, &mut spot_market).unwrap();
+    assert_eq!(
+        if_stake_1.unchecked_if_shares() + if_stake_2.unchecked_if_shares(),
+        spot_market.insurance_fund.user_shares
+    );
+
+    // tokens worth less than a share after the rebase cant be redeemed
+    assert!(redeem_insurance_fund_share_tokens(
+        9_999,
+        if_balance,
+        &mut if_stake_2,
+        &mut user_stats_2,
+        &mut spot_market,
+        0,
+    )
+    .is_err());
+
+    // cant tokenize with a withdraw request in progress
+    request_remove_insurance_fund_stake(
+        1,
+        if_balance,
+        &mut if_stake_2,
+        &mut user_stats_2,
+        &mut spot_market,
+        0,
+    )
+    .unwrap();
+    assert!(tokenize_insurance_fund_stake(
+        1,
+        if_balance,
+        &mut if_stake_2,
+        &mut user_stats_2,
+        &mut spot_market,
+        0,
+    )
+    .is_err());
+}
+
+#[test]
+pub fn tranched_insurance_payments() {
+    let amount = 100 * QUOTE_PRECISION as u64;
+
+    // no senior tranche
+    assert_eq!(
+        get_tranched_insurance_vault_amount(amount, None).unwrap(),
+        amount
+    );
+    assert_eq!(
+        calculate_tranche_payments(amount / 2, amount, None).unwrap(),
+        (amount / 2, 0)
+    );
+    assert!(calculate_tranche_payments(amount, amount, None).is_err());
+
+    // senior tranche only covers what the junior tranche cant
+    assert_eq!(
+        get_tranched_insurance_vault_amount(amount, Some(amount)).unwrap(),
+        2 * amount - 1
+    );
+    assert_eq!(
+        calculate_tranche_payments(amount / 2, amount, Some(amount)).unwrap(),
+        (amount / 2, 0)
+    );
+    assert_eq!(
+        calculate_tranche_payments(amount, amount, Some(amount)).unwrap(),
+        (amount - 1, 1)
+    );
+    assert_eq!(
+        calculate_tranche_payments(2 * amount - 2, amount, Some(amount)).unwrap(),
+        (amount - 1, amount - 1)
+    );
+    // both vaults must keep at least 1 token
+    assert!(calculate_tranche_payments(2 * amount - 1, amount, Some(amount)).is_err());
+}
+
+#[test]
+pub fn senior_insurance_fund_stake() {
+    let amount = QUOTE_PRECISION as u64;
+    let mut spot_market = SpotMarket {
+        market_index: 1,
+        insurance_fund: InsuranceFund {
+            total_shares: 5 * QUOTE_PRECISION,
+            user_shares: 5 * QUOTE_PRECISION,
+            ..InsuranceFund::default()
+        },
+        ..SpotMarket::default()
+    };
+    let mut senior_insurance_fund = SeniorInsuranceFund {
+        market_index: 1,
+        ..SeniorInsuranceFund::default()
+    };
+
+    let mut if_stake = InsuranceFundStake::new(Pubkey::default(), 1, 0);
+    if_stake.tranche = InsuranceFundTranche::Senior;
+    let mut user_stats = UserStats::default();
+
+    with_senior_insurance_fund(
+        &mut spot_market,
+        &mut senior_insurance_fund,
+        |spot_market| {
+            add_insurance_fund_stake(amount, 0, &mut if_stake, &mut user_stats, spot_market, 0)
+        },
+    )
+    .unwrap();
+
+    // stake only touches the senior tranche
+    assert_eq!(
+        senior_insurance_fund.insurance_fund.total_shares,
+        amount as u128
+    );
+    assert_eq!(
+        senior_insurance_fund.insurance_fund.user_shares,
+        amount as u128
+    );
+    assert_eq!(spot_market.insurance_fund.total_shares, 5 * QUOTE_PRECISION);
+    assert_eq!(spot_market.insurance_fund.user_shares, 5 * QUOTE_PRECISION);
+    assert_eq!(if_stake.unchecked_if_shares(), amount as u128);
+
+    assert!(if_stake
+        .validate_tranche(InsuranceFundTranche::Senior)
+        .is_ok());
+    assert!(if_stake
+        .validate_tranche(InsuranceFundTranche::Junior)
+        .is_err());
+
+    // senior fund for another market is rejected
+    senior_insurance_fund.market_index = 2;
+    assert!(
+        with_senior_insurance_fund(&mut spot_market, &mut senior_insurance_fund, |_| Ok(()))
+            .is_err()
+    );
+}
+
+#[test]
+pub fn settle_revenue_to_insurance_fund_tranches_test() {
+    let now = 3600;
+    let mut spot_market = SpotMarket {
+        market_index: 1,
+        decimals: 6,
+        cumulative_deposit_interest: SPOT_CUMULATIVE_INTEREST_PRECISION,
+        deposit_balance: 100 * SPOT_BALANCE_PRECISION,
+        insurance_fund: InsuranceFund {
+            revenue_settle_period: 3600,
+            total_factor: 1_000_000,
+            user_factor: 500_000,
+            ..InsuranceFund::default()
+        },
+        revenue_pool: PoolBalance {
+            market_index: 1,
+            scaled_balance: 100 * SPOT_BALANCE_PRECISION,
+            ..PoolBalance::default()
+        },
+        ..SpotMarket::default()
+    };
+    let mut senior_insurance_fund = SeniorInsuranceFund {
+        insurance_fund: InsuranceFund {
+            total_factor: 1_000_000,
+            user_factor: 0,
+            ..InsuranceFund::default()
+        },
+        revenue_share: 250_000,
+        market_index: 1,
+        ..SeniorInsuranceFund::default()
+    };
+
+    let spot_market_vault_amount = 100 * QUOTE_PRECISION as u64;
+
+    let (junior_token_amount, senior_token_amount) = settle_revenue_to_insurance_fund_tranches(
+        spot_market_vault_amount,
+        0,
+        Some((&mut senior_insurance_fund, 0)),
+        &mut spot_market,
+        now,
+        true,
+    )
+    .unwrap();
+
+    // the senior tranche gets its 25% revenue share of the settled amount
+    assert_eq!(junior_token_amount, 75 * QUOTE_PRECISION as u64);
+    assert_eq!(senior_token_amount, 25 * QUOTE_PRECISION as u64);
+    assert_eq!(spot_market.revenue_pool.scaled_balance, 0);
+    assert_eq!(spot_market.insurance_fund.last_revenue_settle_ts, now);
+    assert_eq!(
+        senior_insurance_fund.insurance_fund.last_revenue_settle_ts,
+        now
+    );
+
+    // protocol shares are minted with each tranche's own factors
+    assert_eq!(
+        spot_market.insurance_fund.total_shares,
+        75 * QUOTE_PRECISION / 2
+    );
+    assert_eq!(
+        senior_insurance_fund.insurance_fund.total_shares,
+        25 * QUOTE_PRECISION
+    );
+
+    // senior fund for another market is rejected
+    senior_insurance_fund.market_index = 2;
+    spot_market.revenue_pool.scaled_balance = 100 * SPOT_BALANCE_PRECISION;
+    assert!(settle_revenue_to_insurance_fund_tranches(
+        spot_market_vault_amount,
+        0,
+        Some((&mut senior_insurance_fund, 0)),
+        &mut spot_market,
+        now,
+        true,
+    )
+    .is_err());
+}
+
+#[test]
+pub fn settle_revenue_to_perp_insurance_fund_test() {
+    let now = 3600;
+    let mut spot_market = SpotMarket {
+        market_index: 0,
+        decimals: 6,
+        cumulative_deposit_interest: SPOT_CUMULATIVE_INTEREST_PRECISION,
+        deposit_balance: 100 * SPOT_BALANCE_PRECISION,
+        ..SpotMarket::default()
+    };
+    let mut perp_market = PerpMarket {
+        market_index: 1,
+        amm: AMM {
+            fee_pool: PoolBalance {
+                scaled_balance: 100 * SPOT_BALANCE_PRECISION,
+                market_index: 0,
+                ..PoolBalance::default()
+            },
+            total_fee_minus_distributions: 100 * QUOTE_PRECISION as i128,
+            total_exchange_fee: 40 * QUOTE_PRECISION,
+            ..AMM::default()
+        },
+        ..PerpMarket::default()
+    };
+    let mut perp_insurance_fund = PerpInsuranceFund {
+        insurance_fund: InsuranceFund {
+            revenue_settle_period: 3600,
+            total_factor: 500_000,
+            user_factor: 250_000,
+            ..InsuranceFund::default()
+        },
+        market_index: 1,
+        ..PerpInsuranceFund::default()
+    };
+
+    let spot_market_vault_amount = 100 * QUOTE_PRECISION as u64;
+
+    let token_amount = settle_revenue_to_perp_insurance_fund(
+        spot_market_vault_amount,
+        0,
+        &mut spot_market,
+        &mut perp_market,
+        &mut perp_insurance_fund,
+        now,
+    )
+    .unwrap();
+
+    // half of the $80 surplus above the $20 fee lower bound
+    assert_eq!(token_amount, 40 * QUOTE_PRECISION as u64);
+    assert_eq!(
+        perp_market.amm.fee_pool.scaled_balance,
+        60 * SPOT_BALANCE_PRECISION
+    );
+    assert_eq!(spot_market.deposit_balance, 60 * SPOT_BALANCE_PRECISION);
+    assert_eq!(
+        perp_market.amm.total_fee_minus_distributions,
+        60 * QUOTE_PRECISION as i128
+    );
+    assert_eq!(perp_market.amm.total_fee_withdrawn, 40 * QUOTE_PRECISION);
+    // protocol gets shares for its half of the settle
+    assert_eq!(
+        perp_insurance_fund.insurance_fund.total_shares,
+        20 * QUOTE_PRECISION
+    );
+    assert_eq!(
+        perp_insurance_fund.insurance_fund.last_revenue_settle_ts,
+        now
+    );
+
+    // perp insurance fund for another market is rejected
+    perp_insurance_fund.market_index = 2;
+    assert!(settle_revenue_to_perp_insurance_fund(
+        spot_market_vault_amount - token_amount,
+        token_amount,
+        &mut spot_market,
+        &mut perp_market,
+        &mut perp_insurance_fund,
+        now,
+    )
+    .is_err());
+}
+
+#[test]
+pub fn perp_insurance_fund_stake_keeps_quote_stake_amount() {
+    let amount = QUOTE_PRECISION as u64;
+    let mut spot_market = SpotMarket {
+        market_index: 0,
+        ..SpotMarket::default()
+    };
+    let mut perp_insurance_fund = PerpInsuranceFund {
+        market_index: 1,
+        ..PerpInsuranceFund::default()
+    };
+
+    let mut if_stake = InsuranceFundStake::new(Pubkey::default(), 1, 0);
+    if_stake.tranche = InsuranceFundTranche::PerpMarket;
+    let mut user_stats = UserStats {
+        if_staked_quote_asset_amount: 5 * amount,
+        ..UserStats::default()
+    };
+
+    with_perp_insurance_fund(&mut spot_market, &mut perp_insurance_fund, |spot_market| {
+        add_insurance_fund_stake(amount, 0, &mut if_stake, &mut user_stats, spot_market, 0)
+    })
+    .unwrap();
+
+    assert_eq!(if_stake.unchecked_if_shares(), amount as u128);
+    // only junior quote stakes are tracked in if_staked_quote_asset_amount
+    assert_eq!(user_stats.if_staked_quote_asset_amount, 5 * amount);
+}
+
+#[test]
+pub fn perp_insurance_fund_payment() {
+    let amount = 100 * QUOTE_PRECISION as u64;
+
+    assert_eq!(calculate_perp_insurance_fund_payment(amount, None), 0);
+    assert_eq!(
+        calculate_perp_insurance_fund_payment(amount, Some(amount + 1)),
+        amount
+    );
+    // perp insurance fund vault keeps at least 1 token
+    assert_eq!(
+        calculate_perp_insurance_fund_payment(amount, Some(amount)),
+        amount - 1
+    );
+}
+
+#[test]
+pub fn insurance_fund_stake_realized_pnl() {
+    let amount = 100 * QUOTE_PRECISION as u64;
+    let mut spot_market = SpotMarket {
+        market_index: 0,
+        ..SpotMarket::default()
+    };
+    let mut if_stake = InsuranceFundStake::new(Pubkey::default(), 0, 0);
+    let mut user_stats = UserStats::default();
+
+    add_insurance_fund_stake(
+        amount,
+        0,
+        &mut if_stake,
+        &mut user_stats,
+        &mut spot_market,
+        0,
+    )
+    .unwrap();
+    assert_eq!(if_stake.cost_basis, amount as i64);
+    assert_eq!(if_stake.realized_pnl, 0);
+
+    // $10 of revenue
+    let if_balance = 110 * QUOTE_PRECISION as u64;
+
+    let performance =
+        calculate_insurance_fund_stake_performance(if_balance, &if_stake, &spot_market).unwrap();
+    assert_eq!(performance.value, 110 * QUOTE_PRECISION as u64);
+    assert_eq!(performance.unrealized_pnl, 10 * QUOTE_PRECISION as i64);
+    assert_eq!(performance.earned, 10 * QUOTE_PRECISION as u64);
+    assert_eq!(performance.lost, 0);
+
+    // remove half
+    request_remove_insurance_fund_stake(
+        if_stake.unchecked_if_shares() / 2,
+        if_balance,
+        &mut if_stake,
+        &mut user_stats,
+        &mut spot_market,
+        0,
+    )
+    .unwrap();
+    let withdraw_amount = remove_insurance_fund_stake(
+        if_balance,
+        &mut if_stake,
+        &mut user_stats,
+        &mut spot_market,
+        0,
+    )
+    .unwrap();
+    assert_eq!(withdraw_amount, 55 * QUOTE_PRECISION as u64);
+    assert_eq!(if_stake.realized_pnl, 5 * QUOTE_PRECISION as i64);
+    assert_eq!(if_stake.cost_basis, 45 * QUOTE_PRECISION as i64);
+
+    let if_balance = if_balance - withdraw_amount;
+
+    // remaining half loses $15
+    let if_balance = if_balance - 15 * QUOTE_PRECISION as u64;
+    let performance =
+        calculate_insurance_fund_stake_performance(if_balance, &if_stake, &spot_market).unwrap();
+    assert_eq!(performance.value, 40 * QUOTE_PRECISION as u64);
+    assert_eq!(performance.realized_pnl, 5 * QUOTE_PRECISION as i64);
+    assert_eq!(performance.unrealized_pnl, -10 * QUOTE_PRECISION as i64);
+    assert_eq!(performance.earned, 0);
+    assert_eq!(performance.lost, 5 * QUOTE_PRECISION as u64);
+
+    // remove the rest
+    request_remove_insurance_fund_stake(
+        if_stake.unchecked_if_shares(),
+        if_balance,
+        &mut if_stake,
+        &mut user_stats,
+        &mut spot_market,
+        0,
+    )
+    .unwrap();
+    let withdraw_amount = remove_insurance_fund_stake(
+        if_balance,
+        &mut if_stake,
+        &mut user_stats,
+        &mut spot_market,
+        0,
+    )
+    .unwrap();
+    assert_eq!(withdraw_amount, 39_999_999);
+    assert_eq!(if_stake.realized_pnl, -5_000_001);
+    assert_eq!(if_stake.unchecked_if_shares(), 0);
+
+    // re-entering from zero shares starts a new stake
+    add_insurance_fund_stake(
+        amount,
+        0,
+        &mut if_stake,
+        &mut user_stats,
+        &mut spot_market,
+        0,
+    )
+    .unwrap();
+    assert_eq!(if_stake.cost_basis, amount as i64);
+    assert_eq!(if_stake.realized_pnl, 0);
+}
+
+#[test]
+pub fn insurance_fund_performance_revenue_settle() {
+    let spot_market = SpotMarket {
+        market_index: 0,
+        insurance_fund: InsuranceFund {
+            total_shares: 1000 * QUOTE_PRECISION,
+            user_shares: 1000 * QUOTE_PRECISION,
+            revenue_settle_period: 3600,
+            ..InsuranceFund::default()
+        },
+        ..SpotMarket::default()
+    };
+    let if_balance = 1000 * QUOTE_PRECISION as u64;
+
+    let mut insurance_fund_performance = InsuranceFundPerformance::default();
+    update_insurance_fund_performance(
+        &mut insurance_fund_performance,
+        &spot_market.insurance_fund,
+        if_balance,
+        0,
+    )
+    .unwrap();
+    assert_eq!(
+        insurance_fund_performance.last_value_per_share,
+        1_000_000_000_000
+    );
+    assert_eq!(insurance_fund_performance.cumulative_revenue, 0);
+
+    // $100 bankruptcy paid out of the fund between settlements
+    let if_balance = 900 * QUOTE_PRECISION as u64;
+    let insurance_fund_before = spot_market.insurance_fund;
+
+    // settle $1 of revenue an hour later, protocol takes no shares
+    let revenue = QUOTE_PRECISION as u64;
+    update_insurance_fund_performance_for_revenue_settle(
+        &mut insurance_fund_performance,
+        &insurance_fund_before,
+        if_balance,
+        &spot_market.insurance_fund,
+        spot_market.insurance_fund.revenue_settle_period,
+        revenue,
+        3600,
+    )
+    .unwrap();
+
+    assert_eq!(
+        insurance_fund_performance.cumulative_losses,
+        100 * QUOTE_PRECISION
+    );
+    assert_eq!(
+        insurance_fund_performance.cumulative_revenue,
+        QUOTE_PRECISION
+    );
+    assert_eq!(insurance_fund_performance.last_revenue_settle_ts, 3600);
+    // $1 an hour on $900 ~= 973% a year
+    assert_eq!(insurance_fund_performance.last_revenue_apy, 9_733_333);
+    assert_eq!(
+        insurance_fund_performance.last_value_per_share,
+        901_000_000_000
+    );
+
+    // $200 bankruptcy paid out of the fund is recorded as a loss right away
+    let if_balance = 901 * QUOTE_PRECISION as u64;
+    update_insurance_fund_performance_for_loss(
+        &mut insurance_fund_performance,
+        &spot_market.insurance_fund,
+        if_balance,
+        200 * QUOTE_PRECISION as u64,
+        7200,
+    )
+    .unwrap();
+
+    assert_eq!(
+        insurance_fund_performance.cumulative_losses,
+        300 * QUOTE_PRECISION
+    );
+    assert_eq!(
+        insurance_fund_performance.cumulative_revenue,
+        QUOTE_PRECISION
+    );
+    assert_eq!(insurance_fund_performance.last_update_ts, 7200);
+    assert_eq!(
+        insurance_fund_performance.last_value_per_share,
+        701_000_000_000
+    );
+
+    // no double counting once the vault reflects the payment
+    update_insurance_fund_performance(
+        &mut insurance_fund_performance,
+        &spot_market.insurance_fund,
+        701 * QUOTE_PRECISION as u64,
+        7200,
+    )
+    .unwrap();
+    assert_eq!(
+        insurance_fund_performance.cumulative_losses,
+        300 * QUOTE_PRECISION
+    );
+}
@@ -56,15 +56,16 @@ use crate::state::events::{
     PerpBankruptcyRecord, SpotBankruptcyRecord,
 };
 use crate::state::fill_mode::FillMode;
+use crate::state::maker_rewards::MakerRewardsProgram;
 use crate::state::margin_calculation::{MarginCalculation, MarginContext, MarketIdentifier};
 use crate::state::oracle_map::OracleMap;
 use crate::state::order_params::PlaceOrderOptions;
 use crate::state::paused_operations::{PerpOperation, SpotOperation};
-use crate::state::perp_market::MarketStatus;
+use crate::state::perp_market::{MarketStatus, PerpMarketDepthBounds};
 use crate::state::perp_market_map::PerpMarketMap;
 use crate::state::spot_market::SpotBalanceType;
 use crate::state::spot_market_map::SpotMarketMap;
-use crate::state::state::State;
+use crate::state::state::{FeeTierQualifications, MarketFeeStructure, State};
 use crate::state::traits::Size;
 use crate::state::user::{MarketType, Order, OrderStatus, OrderType, User, UserStats};
 use crate::state::user_map::{UserMap, UserStatsMap};
@@ -441,6 +442,10 @@ pub fn liquidate_perp(
         base_asset_value,
         now,
     )?;
+    liquidator_stats.update_fuel_keeper_bonus(
+        perp_market_map.get_ref(&market_index)?.fuel_boost_keeper,
+        liquidator_fee.unsigned_abs(),
+    )?;
 
     let user_position_delta = get_position_delta_for_fill(
         base_asset_amount,
@@ -625,6 +630,8 @@ pub fn liquidate_perp(
         maker_order_cumulative_base_asset_amount_filled: Some(base_asset_amount),
         maker_order_cumulative_quote_asset_amount_filled: Some(base_asset_value),
         oracle_price,
+        taker_fee_tier_index: None,
+        maker_fee_tier_index: None,
     };
     emit!(fill_record);
 
@@ -667,6 +674,10 @@ pub fn liquidate_perp_with_fill(
     liquidator_stats_loader: &AccountLoader<UserStats>,
     makers_and_referrer: &UserMap,
     makers_and_referrer_stats: &UserStatsMap,
+    fee_tier_qualifications: Option<&FeeTierQualifications>,
+    market_fee_structure: Option<&MarketFeeStructure>,
+    maker_rewards_program: Option<&mut MakerRewardsProgram>,
+    depth_bounds: Option<&PerpMarketDepthBounds>,
     perp_market_map: &PerpMarketMap,
     spot_market_map: &SpotMarketMap,
     oracle_map: &mut OracleMap,
@@ -1011,6 +1022,11 @@ pub fn liquidate_perp_with_fill(
         makers_and_referrer,
         makers_and_referrer_stats,
         None,
+        None,
+        fee_tier_qualifications,
+        market_fee_structure,
+        maker_rewards_program,
+        depth_bounds,
         clock,
         FillMode::Liquidation,
     )?;
@@ -2625,6 +2641,7 @@ pub fn resolve_perp_bankruptcy(
     oracle_map: &mut OracleMap,
     now: i64,
     insurance_fund_vault_balance: u64,
+    perp_insurance_fund_vault_balance: u64,
 ) -> DriftResult<u64> {
     if !user.is_bankrupt() && is_user_bankrupt(user) {
         user.enter_bankruptcy();
@@ -2701,15 +2718,23 @@ pub fn resolve_perp_bankruptcy(
             .safe_sub(perp_market.insurance_claim.quote_settled_insurance)?
             .cast::<u128>()?;
 
-        let if_payment = loss
+        // the perp market's isolated insurance fund pays first and doesn't count towards quote_max_insurance
+        let perp_if_payment = loss
             .unsigned_abs()
+            .min(perp_insurance_fund_vault_balance.saturating_sub(1).cast()?);
+
+        let shared_if_payment = loss
+            .unsigned_abs()
+            .safe_sub(perp_if_payment)?
             .min(insurance_fund_vault_balance.saturating_sub(1).cast()?)
             .min(max_insurance_withdraw);
 
         perp_market.insurance_claim.quote_settled_insurance = perp_market
             .insurance_claim
             .quote_settled_insurance
-            .safe_add(if_payment.cast()?)?;
+            .safe_add(shared_if_payment.cast()?)?;
+
+        let if_payment = perp_if_payment.safe_add(shared_if_payment)?;
 
         // move if payment to pnl pool
         let spot_market = &mut spot_market_map.get_ref_mut(&QUOTE_SPOT_MARKET_INDEX)?;
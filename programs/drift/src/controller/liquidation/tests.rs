@@ -2417,6 +2417,10 @@ pub mod liquidate_perp_with_fill {
             &liquidator_stats_account_loader,
             &makers_and_referrers,
             &maker_and_referrer_stats,
+            None,
+            None,
+            None,
+            None,
             &perp_market_map,
             &spot_market_map,
             &mut oracle_map,
@@ -2626,6 +2630,10 @@ pub mod liquidate_perp_with_fill {
             &liquidator_stats_account_loader,
             &makers_and_referrers,
             &maker_and_referrer_stats,
+            None,
+            None,
+            None,
+            None,
             &perp_market_map,
             &spot_market_map,
             &mut oracle_map,
@@ -2801,6 +2809,10 @@ pub mod liquidate_perp_with_fill {
             &liquidator_stats_account_loader,
             &UserMap::empty(),
             &UserStatsMap::empty(),
+            None,
+            None,
+            None,
+            None,
             &perp_market_map,
             &spot_market_map,
             &mut oracle_map,
@@ -2972,6 +2984,10 @@ pub mod liquidate_perp_with_fill {
             &liquidator_stats_account_loader,
             &UserMap::empty(),
             &UserStatsMap::empty(),
+            None,
+            None,
+            None,
+            None,
             &perp_market_map,
             &spot_market_map,
             &mut oracle_map,
@@ -3503,6 +3519,7 @@ pub mod liquidate_spot {
         let strict_price_1 = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(oracle_price_data.price / 10),
+            confidence_adjustment: 0,
         };
         let strict_token_value_1 =
             get_strict_token_value(token_amount as i128, 6, &strict_price_1).unwrap();
@@ -3510,6 +3527,7 @@ pub mod liquidate_spot {
         let strict_price_2 = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(oracle_price_data.price * 2),
+            confidence_adjustment: 0,
         };
         let strict_token_value_2 =
             get_strict_token_value(token_amount as i128, 6, &strict_price_2).unwrap();
@@ -3517,6 +3535,7 @@ pub mod liquidate_spot {
         let strict_price_3 = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(oracle_price_data.price * 2),
+            confidence_adjustment: 0,
         };
         let strict_token_value_3 =
             get_strict_token_value(-(token_amount as i128), 6, &strict_price_3).unwrap();
@@ -7268,13 +7287,13 @@ pub mod resolve_perp_bankruptcy {
     use crate::math::constants::{
         AMM_RESERVE_PRECISION, BASE_PRECISION_I128, BASE_PRECISION_I64, BASE_PRECISION_U64,
         FUNDING_RATE_PRECISION_I128, FUNDING_RATE_PRECISION_I64, LIQUIDATION_FEE_PRECISION,
-        PEG_PRECISION, QUOTE_PRECISION_I128, QUOTE_PRECISION_I64, QUOTE_SPOT_MARKET_INDEX,
-        SPOT_BALANCE_PRECISION, SPOT_BALANCE_PRECISION_U64, SPOT_CUMULATIVE_INTEREST_PRECISION,
-        SPOT_WEIGHT_PRECISION,
+        PEG_PRECISION, QUOTE_PRECISION, QUOTE_PRECISION_I128, QUOTE_PRECISION_I64,
+        QUOTE_PRECISION_U64, QUOTE_SPOT_MARKET_INDEX, SPOT_BALANCE_PRECISION,
+        SPOT_BALANCE_PRECISION_U64, SPOT_CUMULATIVE_INTEREST_PRECISION, SPOT_WEIGHT_PRECISION,
     };
     use crate::state::oracle::{HistoricalOracleData, OracleSource};
     use crate::state::oracle_map::OracleMap;
-    use crate::state::perp_market::{MarketStatus, PerpMarket, PoolBalance, AMM};
+    use crate::state::perp_market::{InsuranceClaim, MarketStatus, PerpMarket, PoolBalance, AMM};
     use crate::state::perp_market_map::PerpMarketMap;
     use crate::state::spot_market::{SpotBalanceType, SpotMarket};
     use crate::state::spot_market_map::SpotMarketMap;
@@ -7412,6 +7431,7 @@ pub mod resolve_perp_bankruptcy {
             &mut oracle_map,
             now,
             0,
+            0,
         )
         .unwrap();
 
@@ -7624,6 +7644,7 @@ pub mod resolve_perp_bankruptcy {
             &mut oracle_map,
             now,
             0,
+            0,
         )
         .unwrap();
 
@@ -7707,6 +7728,131 @@ pub mod resolve_perp_bankruptcy {
 
         assert_eq!(expected_affected_short_user, affected_short_user);
     }
+
+    #[test]
+    pub fn successful_resolve_perp_bankruptcy_with_perp_insurance_fund() {
+        let now = 0_i64;
+        let slot = 0_u64;
+
+        let mut oracle_price = get_pyth_price(100, 6);
+        let oracle_price_key =
+            Pubkey::from_str("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix").unwrap();
+        let pyth_program = crate::ids::pyth_program::id();
+        create_account_info!(
+            oracle_price,
+            &oracle_price_key,
+            &pyth_program,
+            oracle_account_info
+        );
+        let mut oracle_map = OracleMap::load_one(&oracle_account_info, slot, None).unwrap();
+
+        let mut market = PerpMarket {
+            amm: AMM {
+                base_asset_reserve: 100 * AMM_RESERVE_PRECISION,
+                quote_asset_reserve: 100 * AMM_RESERVE_PRECISION,
+                bid_base_asset_reserve: 101 * AMM_RESERVE_PRECISION,
+                bid_quote_asset_reserve: 99 * AMM_RESERVE_PRECISION,
+                ask_base_asset_reserve: 99 * AMM_RESERVE_PRECISION,
+                ask_quote_asset_reserve: 101 * AMM_RESERVE_PRECISION,
+                sqrt_k: 100 * AMM_RESERVE_PRECISION,
+                peg_multiplier: 100 * PEG_PRECISION,
+                max_slippage_ratio: 50,
+                max_fill_reserve_fraction: 100,
+                order_step_size: 10000000,
+                quote_asset_amount: -150 * QUOTE_PRECISION_I128,
+                base_asset_amount_long: 5 * BASE_PRECISION_I128,
+                base_asset_amount_short: -5 * BASE_PRECISION_I128,
+                base_asset_amount_with_amm: BASE_PRECISION_I128,
+                oracle: oracle_price_key,
+                cumulative_funding_rate_long: 1000 * FUNDING_RATE_PRECISION_I128,
+                cumulative_funding_rate_short: -1000 * FUNDING_RATE_PRECISION_I128,
+                ..AMM::default()
+            },
+            insurance_claim: InsuranceClaim {
+                quote_max_insurance: 10 * QUOTE_PRECISION_U64,
+                ..InsuranceClaim::default()
+            },
+            margin_ratio_initial: 1000,
+            margin_ratio_maintenance: 500,
+            status: MarketStatus::Initialized,
+            liquidator_fee: LIQUIDATION_FEE_PRECISION / 100,
+            number_of_users: 1,
+            ..PerpMarket::default()
+        };
+        create_anchor_account_info!(market, PerpMarket, market_account_info);
+        let market_map = PerpMarketMap::load_one(&market_account_info, true).unwrap();
+
+        let mut spot_market = SpotMarket {
+            market_index: 0,
+            oracle_source: OracleSource::QuoteAsset,
+            cumulative_deposit_interest: SPOT_CUMULATIVE_INTEREST_PRECISION,
+            decimals: 6,
+            initial_asset_weight: SPOT_WEIGHT_PRECISION,
+            historical_oracle_data: HistoricalOracleData {
+                last_oracle_price_twap: PRICE_PRECISION_I64,
+                last_oracle_price_twap_5min: PRICE_PRECISION_I64,
+                ..HistoricalOracleData::default()
+            },
+            ..SpotMarket::default()
+        };
+        create_anchor_account_info!(spot_market, SpotMarket, spot_market_account_info);
+        let spot_market_map = SpotMarketMap::load_one(&spot_market_account_info, true).unwrap();
+
+        let mut user = User {
+            perp_positions: get_positions(PerpPosition {
+                market_index: 0,
+                base_asset_amount: 0,
+                quote_asset_amount: -100 * QUOTE_PRECISION_I64,
+                quote_entry_amount: -100 * QUOTE_PRECISION_I64,
+                quote_break_even_amount: -100 * QUOTE_PRECISION_I64,
+                ..PerpPosition::default()
+            }),
+            spot_positions: [SpotPosition::default(); 8],
+            status: UserStatus::Bankrupt as u8,
+            next_liquidation_id: 2,
+            ..User::default()
+        };
+
+        let mut liquidator = User {
+            spot_positions: get_spot_positions(SpotPosition {
+                market_index: 0,
+                balance_type: SpotBalanceType::Deposit,
+                scaled_balance: 50 * SPOT_BALANCE_PRECISION_U64,
+                ..SpotPosition::default()
+            }),
+            ..User::default()
+        };
+
+        let user_key = Pubkey::default();
+        let liquidator_key = Pubkey::default();
+
+        // perp insurance fund pays $60, the shared insurance fund pays up to quote_max_insurance
+        let if_payment = resolve_perp_bankruptcy(
+            0,
+            &mut user,
+            &user_key,
+            &mut liquidator,
+            &liquidator_key,
+            &market_map,
+            &spot_market_map,
+            &mut oracle_map,
+            now,
+            1000 * QUOTE_PRECISION_U64,
+            60 * QUOTE_PRECISION_U64 + 1,
+        )
+        .unwrap();
+
+        assert_eq!(if_payment, 70 * QUOTE_PRECISION_U64);
+        assert_eq!(user.perp_positions[0].quote_asset_amount, 0);
+
+        let market = market_map.get_ref(&0).unwrap();
+        // only the shared insurance fund payment counts towards quote_max_insurance
+        assert_eq!(
+            market.insurance_claim.quote_settled_insurance,
+            10 * QUOTE_PRECISION_U64
+        );
+        assert_eq!(market.amm.total_social_loss, 30 * QUOTE_PRECISION);
+    }
 }
 
 pub mod resolve_spot_bankruptcy {
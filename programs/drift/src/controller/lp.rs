@@ -9,7 +9,7 @@ use crate::error::{DriftResult, ErrorCode};
 use crate::get_struct_values;
 use crate::math::casting::Cast;
 use crate::math::cp_curve::{get_update_k_result, update_k};
-use crate::math::lp::calculate_settle_lp_metrics;
+use crate::math::lp::{calculate_settle_lp_metrics, can_amm_absorb_lp_withdraw};
 use crate::math::position::calculate_base_asset_value_with_oracle_price;
 use crate::math::safe_math::SafeMath;
 
@@ -366,10 +366,8 @@ pub fn remove_perp_lp_shares(
 
     let mut market = perp_market_map.get_ref_mut(&market_index)?;
 
-    let time_since_last_add_liquidity = now.safe_sub(user.last_add_perp_lp_shares_ts)?;
-
     validate!(
-        time_since_last_add_liquidity >= state.lp_cooldown_time.cast()?,
+        can_remove_perp_lp_shares(state, user, &market, shares_to_burn, now)?,
         ErrorCode::TryingToRemoveLiquidityTooFast
     )?;
 
@@ -386,6 +384,8 @@ pub fn remove_perp_lp_shares(
     let (position_delta, pnl) =
         burn_lp_shares(position, &mut market, shares_to_burn, oracle_price)?;
 
+    market.record_lp_withdraw(shares_to_burn, now)?;
+
     emit!(LPRecord {
         ts: now,
         action: LPAction::RemoveLiquidity,
@@ -397,5 +397,124 @@ pub fn remove_perp_lp_shares(
         pnl,
     });
 
+    if user.has_lp_withdraw_request(market_index) {
+        let lp_shares = user.perp_positions[position_index].lp_shares;
+        user.lp_withdraw_request_shares = user
+            .lp_withdraw_request_shares
+            .saturating_sub(shares_to_burn)
+            .min(lp_shares);
+    }
+
     Ok(())
 }
+
+pub fn can_remove_perp_lp_shares(
+    state: &State,
+    user: &User,
+    market: &PerpMarket,
+    shares_to_burn: u64,
+    now: i64,
+) -> DriftResult<bool> {
+    let time_since_last_add_liquidity = now.safe_sub(user.last_add_perp_lp_shares_ts)?;
+
+    let cooled_down = time_since_last_add_liquidity >= state.lp_cooldown_time.cast()?
+        || can_amm_absorb_lp_withdraw(&market.amm, shares_to_burn)?;
+
+    Ok(cooled_down && shares_to_burn <= market.get_lp_withdraw_capacity(now)?)
+}
+
+pub fn request_remove_perp_lp_shares(
+    user: &mut User,
+    user_key: Pubkey,
+    shares_to_burn: u64,
+    market_index: u16,
+    now: i64,
+) -> DriftResult<()> {
+    validate!(
+        user.lp_withdraw_request_shares == 0
+            || user.lp_withdraw_request_market_index == market_index,
+        ErrorCode::PendingLpWithdrawRequest,
+        "user has pending lp withdraw request in perp market {}",
+        user.lp_withdraw_request_market_index
+    )?;
+
+    let lp_shares = user.get_perp_position(market_index)?.lp_shares;
+    validate!(
+        shares_to_burn != 0 && shares_to_burn <= lp_shares,
+        ErrorCode::InsufficientLPTokens,
+        "requested {} shares but user has {} lp shares",
+        shares_to_burn,
+        lp_shares
+    )?;
+
+    msg!(
+        "queueing lp withdraw request for {} shares in perp market {}",
+        shares_to_burn,
+        market_index
+    );
+
+    user.lp_withdraw_request_market_index = market_index;
+    user.lp_withdraw_request_shares = shares_to_burn;
+    user.lp_withdraw_request_ts = now.cast()?;
+
+    emit!(LPRecord {
+        ts: now,
+        action: LPAction::RequestRemoveLiquidity,
+        user: user_key,
+        n_shares: shares_to_burn,
+        market_index,
+        delta_base_asset_amount: 0,
+        delta_quote_asset_amount: 0,
+        pnl: 0,
+    });
+
+    Ok(())
+}
+
+pub fn fulfill_perp_lp_withdraw_request(
+    perp_market_map: PerpMarketMap,
+    oracle_map: &mut OracleMap,
+    state: &Account<State>,
+    user: &mut std::cell::RefMut<User>,
+    user_key: Pubkey,
+    now: i64,
+) -> DriftResult<()> {
+    validate!(
+        user.lp_withdraw_request_shares != 0,
+        ErrorCode::NoLpWithdrawRequest
+    )?;
+
+    let market_index = user.lp_withdraw_request_market_index;
+    let lp_shares = user.get_perp_position(market_index)?.lp_shares;
+    let shares_to_burn = user.lp_withdraw_request_shares.min(lp_shares);
+
+    if shares_to_burn == 0 {
+        user.lp_withdraw_request_shares = 0;
+        return Ok(());
+    }
+
+    // requests over the market's hourly withdraw cap are partially fulfilled
+    let lp_withdraw_capacity = perp_market_map
+        .get_ref(&market_index)?
+        .get_lp_withdraw_capacity(now)?;
+
+    validate!(
+        lp_withdraw_capacity > 0,
+        ErrorCode::LpWithdrawCapReached,
+        "lp withdraw cap reached for perp market {}",
+        market_index
+    )?;
+
+    let shares_to_burn = shares_to_burn.min(lp_withdraw_capacity);
+
+    remove_perp_lp_shares(
+        perp_market_map,
+        oracle_map,
+        state,
+        user,
+        user_key,
+        shares_to_burn,
+        market_index,
+        now,
+    )
+}
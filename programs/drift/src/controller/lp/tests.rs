@@ -567,6 +567,7 @@ pub fn test_lp_settle_pnl() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -1815,3 +1816,88 @@ fn test_get_position_update_type_positive_remainder() {
         PositionUpdateType::Flip
     ); // different signum but smaller
 }
+
+#[test]
+fn test_request_remove_perp_lp_shares() {
+    let mut user = User::default();
+    user.perp_positions[0] = PerpPosition {
+        market_index: 1,
+        lp_shares: 100 * BASE_PRECISION_U64,
+        ..PerpPosition::default()
+    };
+    let user_key = Pubkey::default();
+
+    // cant queue more shares than the user has
+    let result =
+        request_remove_perp_lp_shares(&mut user, user_key, 101 * BASE_PRECISION_U64, 1, 10);
+    assert_eq!(result, Err(ErrorCode::InsufficientLPTokens));
+    let result = request_remove_perp_lp_shares(&mut user, user_key, 0, 1, 10);
+    assert_eq!(result, Err(ErrorCode::InsufficientLPTokens));
+
+    request_remove_perp_lp_shares(&mut user, user_key, 100 * BASE_PRECISION_U64, 1, 10).unwrap();
+
+    assert!(user.has_lp_withdraw_request(1));
+    assert!(!user.has_lp_withdraw_request(0));
+    assert_eq!(user.lp_withdraw_request_market_index, 1);
+    assert_eq!(user.lp_withdraw_request_shares, 100 * BASE_PRECISION_U64);
+    assert_eq!(user.lp_withdraw_request_ts, 10);
+
+    // request in same market replaces the pending request
+    request_remove_perp_lp_shares(&mut user, user_key, 50 * BASE_PRECISION_U64, 1, 20).unwrap();
+    assert_eq!(user.lp_withdraw_request_shares, 50 * BASE_PRECISION_U64);
+    assert_eq!(user.lp_withdraw_request_ts, 20);
+
+    // only one market can have a pending request
+    let result = request_remove_perp_lp_shares(&mut user, user_key, 50 * BASE_PRECISION_U64, 0, 20);
+    assert_eq!(result, Err(ErrorCode::PendingLpWithdrawRequest));
+}
+
+#[test]
+fn test_can_remove_perp_lp_shares() {
+    let state = State {
+        lp_cooldown_time: 60,
+        ..State::default()
+    };
+
+    let user = User {
+        last_add_perp_lp_shares_ts: 100,
+        ..User::default()
+    };
+
+    let market = PerpMarket {
+        amm: AMM {
+            user_lp_shares: 100 * AMM_RESERVE_PRECISION,
+            base_asset_amount_with_unsettled_lp: -10 * BASE_PRECISION_I128,
+            order_step_size: BASE_PRECISION_U64 / 10,
+            ..AMM::default()
+        },
+        ..PerpMarket::default()
+    };
+
+    // before cooldown, amm cant absorb the lp inventory leaving
+    assert!(
+        !can_remove_perp_lp_shares(&state, &user, &market, 10 * BASE_PRECISION_U64, 150).unwrap()
+    );
+
+    // before cooldown, amm can absorb small withdraw
+    assert!(
+        can_remove_perp_lp_shares(&state, &user, &market, BASE_PRECISION_U64 / 2, 150).unwrap()
+    );
+
+    // after cooldown
+    assert!(
+        can_remove_perp_lp_shares(&state, &user, &market, 10 * BASE_PRECISION_U64, 160).unwrap()
+    );
+
+    // over the market's hourly withdraw cap
+    let market = PerpMarket {
+        lp_withdraw_cap: 5,
+        ..market
+    };
+    assert!(
+        !can_remove_perp_lp_shares(&state, &user, &market, 10 * BASE_PRECISION_U64, 160).unwrap()
+    );
+    assert!(
+        can_remove_perp_lp_shares(&state, &user, &market, 5 * BASE_PRECISION_U64, 160).unwrap()
+    );
+}
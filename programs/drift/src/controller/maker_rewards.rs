@@ -0,0 +1,245 @@
+use solana_program::msg;
+
+use crate::error::{DriftResult, ErrorCode};
+use crate::load;
+use crate::math::casting::Cast;
+use crate::math::constants::PERCENTAGE_PRECISION_U64;
+use crate::math::safe_math::SafeMath;
+use crate::state::maker_rewards::MakerRewardsProgram;
+use crate::state::user::{MarketType, OrderStatus, User};
+use crate::state::user_map::UserMap;
+use anchor_lang::prelude::Pubkey;
+
+#[cfg(test)]
+mod tests;
+
+pub fn select_maker_rewards_program(
+    maker_rewards_program: Option<&mut MakerRewardsProgram>,
+    market_index: u16,
+    has_maker_rewards_program: bool,
+) -> DriftResult<Option<&mut MakerRewardsProgram>> {
+    if !has_maker_rewards_program {
+        return Ok(None);
+    }
+
+    match maker_rewards_program {
+        Some(maker_rewards_program) if maker_rewards_program.market_index == market_index => {
+            Ok(Some(maker_rewards_program))
+        }
+        _ => {
+            msg!(
+                "maker rewards program for perp market {} not found",
+                market_index
+            );
+            Err(ErrorCode::InvalidMakerRewardsProgram)
+        }
+    }
+}
+
+/// Records the perp position of each program maker passed to the fill, so the filled size can be
+/// measured once the fill is done. Makers that dont cross the taker are still resampled
+pub fn get_maker_base_asset_amounts(
+    maker_rewards_program: &MakerRewardsProgram,
+    makers_and_referrer: &UserMap,
+    taker_key: &Pubkey,
+    market_index: u16,
+) -> DriftResult<Vec<(Pubkey, usize, i64)>> {
+    let mut maker_base_asset_amounts = vec![];
+
+    for (maker_key, maker) in makers_and_referrer.0.iter() {
+        if maker_key == taker_key {
+            continue;
+        }
+
+        let maker_index = match maker_rewards_program.get_maker_index(maker_key) {
+            Some(maker_index) => maker_index,
+            None => continue,
+        };
+
+        let base_asset_amount = get_base_asset_amount(&*load!(maker)?, market_index);
+
+        maker_base_asset_amounts.push((*maker_key, maker_index, base_asset_amount));
+    }
+
+    Ok(maker_base_asset_amounts)
+}
+
+/// Credits each program maker passed to the fill for their depth and filled size. Fills against a
+/// maker with the same authority as the taker dont earn volume rewards
+pub fn update_maker_rewards_after_fill(
+    maker_rewards_program: &mut MakerRewardsProgram,
+    maker_base_asset_amounts_before: &[(Pubkey, usize, i64)],
+    makers_and_referrer: &UserMap,
+    taker_authority: &Pubkey,
+    market_index: u16,
+    oracle_price: i64,
+    slot: u64,
+    tick_size: u64,
+    is_prediction_market: bool,
+    now: i64,
+) -> DriftResult {
+    for (maker_key, maker_index, base_asset_amount_before) in maker_base_asset_amounts_before.iter()
+    {
+        let maker = makers_and_referrer.get_ref(maker_key)?;
+
+        let filled_base_asset_amount = if maker.authority == *taker_authority {
+            0
+        } else {
+            get_base_asset_amount(&maker, market_index)
+                .safe_sub(*base_asset_amount_before)?
+                .unsigned_abs()
+        };
+
+        sample_maker(
+            maker_rewards_program,
+            *maker_index,
+            &maker,
+            filled_base_asset_amount,
+            market_index,
+            oracle_price,
+            slot,
+            tick_size,
+            is_prediction_market,
+            now,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Resamples the depth of each program maker passed, without a fill. Lets anyone credit a maker's
+/// depth up to now, e.g. before the epoch is settled. Users not in the program are skipped
+pub fn update_maker_rewards_depth(
+    maker_rewards_program: &mut MakerRewardsProgram,
+    makers: &UserMap,
+    market_index: u16,
+    oracle_price: i64,
+    slot: u64,
+    tick_size: u64,
+    is_prediction_market: bool,
+    now: i64,
+) -> DriftResult {
+    for (maker_key, maker) in makers.0.iter() {
+        let maker_index = match maker_rewards_program.get_maker_index(maker_key) {
+            Some(maker_index) => maker_index,
+            None => continue,
+        };
+
+        sample_maker(
+            maker_rewards_program,
+            maker_index,
+            &*load!(maker)?,
+            0,
+            market_index,
+            oracle_price,
+            slot,
+            tick_size,
+            is_prediction_market,
+            now,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn sample_maker(
+    maker_rewards_program: &mut MakerRewardsProgram,
+    maker_index: usize,
+    maker: &User,
+    filled_base_asset_amount: u64,
+    market_index: u16,
+    oracle_price: i64,
+    slot: u64,
+    tick_size: u64,
+    is_prediction_market: bool,
+    now: i64,
+) -> DriftResult {
+    let resting_base_asset_amount = calculate_resting_base_asset_amount(
+        maker,
+        market_index,
+        oracle_price,
+        maker_rewards_program.max_oracle_distance,
+        slot,
+        tick_size,
+        is_prediction_market,
+        None,
+    )?;
+
+    let resting_since_last_sample = calculate_resting_base_asset_amount(
+        maker,
+        market_index,
+        oracle_price,
+        maker_rewards_program.max_oracle_distance,
+        slot,
+        tick_size,
+        is_prediction_market,
+        Some(maker_rewards_program.makers[maker_index].last_sample_slot),
+    )?;
+
+    maker_rewards_program.update_maker(
+        maker_index,
+        resting_base_asset_amount,
+        resting_since_last_sample,
+        filled_base_asset_amount,
+        now,
+        slot,
+    )
+}
+
+/// Sums the unfilled size of the user's resting limit orders priced within max_oracle_distance of the oracle.
+/// If placed_by_slot is set, orders placed after that slot are skipped
+pub fn calculate_resting_base_asset_amount(
+    user: &User,
+    market_index: u16,
+    oracle_price: i64,
+    max_oracle_distance: u32,
+    slot: u64,
+    tick_size: u64,
+    is_prediction_market: bool,
+    placed_by_slot: Option<u64>,
+) -> DriftResult<u64> {
+    let oracle_price = oracle_price.unsigned_abs();
+    let max_price_distance = oracle_price
+        .cast::<u128>()?
+        .safe_mul(max_oracle_distance.cast()?)?
+        .safe_div(PERCENTAGE_PRECISION_U64.cast()?)?
+        .cast::<u64>()?;
+
+    let mut resting_base_asset_amount = 0_u64;
+    for order in user.orders.iter() {
+        if order.status != OrderStatus::Open
+            || order.market_type != MarketType::Perp
+            || order.market_index != market_index
+            || (order.must_be_triggered() && !order.triggered())
+            || matches!(placed_by_slot, Some(placed_by_slot) if order.slot > placed_by_slot)
+            || !order.is_resting_limit_order(slot)?
+        {
+            continue;
+        }
+
+        let limit_price = match order.get_limit_price(
+            Some(oracle_price.cast()?),
+            None,
+            slot,
+            tick_size,
+            is_prediction_market,
+        )? {
+            Some(limit_price) => limit_price,
+            None => continue,
+        };
+
+        if limit_price.abs_diff(oracle_price) > max_price_distance {
+            continue;
+        }
+
+        resting_base_asset_amount =
+            resting_base_asset_amount.safe_add(order.get_base_asset_amount_unfilled(None)?)?;
+    }
+
+    Ok(resting_base_asset_amount)
+}
+
+fn get_base_asset_amount(user: &User, market_index: u16) -> i64 {
+    user.get_perp_position(market_index)
+        .map_or(0, |position| position.base_asset_amount)
+}
@@ -0,0 +1,359 @@
+mod select_maker_rewards_program {
+    use crate::controller::maker_rewards::select_maker_rewards_program;
+    use crate::error::ErrorCode;
+    use crate::state::maker_rewards::MakerRewardsProgram;
+
+    #[test]
+    fn no_program_for_market() {
+        let mut program = MakerRewardsProgram {
+            market_index: 1,
+            ..MakerRewardsProgram::default()
+        };
+
+        assert!(select_maker_rewards_program(None, 0, false)
+            .unwrap()
+            .is_none());
+        assert!(select_maker_rewards_program(Some(&mut program), 0, false)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn program_for_market() {
+        let mut program = MakerRewardsProgram {
+            market_index: 1,
+            ..MakerRewardsProgram::default()
+        };
+
+        let selected = select_maker_rewards_program(Some(&mut program), 1, true).unwrap();
+        assert_eq!(selected.unwrap().market_index, 1);
+    }
+
+    #[test]
+    fn missing_or_wrong_program() {
+        let mut program = MakerRewardsProgram {
+            market_index: 1,
+            ..MakerRewardsProgram::default()
+        };
+
+        assert_eq!(
+            select_maker_rewards_program(None, 1, true),
+            Err(ErrorCode::InvalidMakerRewardsProgram)
+        );
+        assert_eq!(
+            select_maker_rewards_program(Some(&mut program), 0, true),
+            Err(ErrorCode::InvalidMakerRewardsProgram)
+        );
+    }
+}
+
+mod calculate_resting_base_asset_amount {
+    use crate::controller::maker_rewards::calculate_resting_base_asset_amount;
+    use crate::controller::position::PositionDirection;
+    use crate::math::constants::{
+        BASE_PRECISION_U64, PERCENTAGE_PRECISION, PRICE_PRECISION_I64, PRICE_PRECISION_U64,
+    };
+    use crate::state::user::{
+        MarketType, Order, OrderStatus, OrderTriggerCondition, OrderType, User,
+    };
+
+    fn resting_order(direction: PositionDirection, price: u64, base_asset_amount: u64) -> Order {
+        Order {
+            status: OrderStatus::Open,
+            order_type: OrderType::Limit,
+            market_type: MarketType::Perp,
+            direction,
+            price,
+            base_asset_amount,
+            post_only: true,
+            ..Order::default()
+        }
+    }
+
+    #[test]
+    fn sums_orders_near_oracle() {
+        let oracle_price = 100 * PRICE_PRECISION_I64;
+        let max_oracle_distance = (PERCENTAGE_PRECISION / 100) as u32; // 1%
+
+        let mut user = User::default();
+        // within 1%
+        user.orders[0] = resting_order(
+            PositionDirection::Long,
+            99 * PRICE_PRECISION_U64,
+            BASE_PRECISION_U64,
+        );
+        user.orders[1] = Order {
+            base_asset_amount_filled: BASE_PRECISION_U64 / 2,
+            ..resting_order(
+                PositionDirection::Short,
+                101 * PRICE_PRECISION_U64,
+                BASE_PRECISION_U64,
+            )
+        };
+        // oracle offset order within 1%
+        user.orders[2] = Order {
+            oracle_price_offset: -(PRICE_PRECISION_I64 as i32 / 2),
+            ..resting_order(PositionDirection::Long, 0, BASE_PRECISION_U64)
+        };
+        // too far from oracle
+        user.orders[3] = resting_order(
+            PositionDirection::Long,
+            98 * PRICE_PRECISION_U64,
+            BASE_PRECISION_U64,
+        );
+        // other market
+        user.orders[4] = Order {
+            market_index: 1,
+            ..resting_order(
+                PositionDirection::Long,
+                100 * PRICE_PRECISION_U64,
+                BASE_PRECISION_U64,
+            )
+        };
+        // spot
+        user.orders[5] = Order {
+            market_type: MarketType::Spot,
+            ..resting_order(
+                PositionDirection::Long,
+                100 * PRICE_PRECISION_U64,
+                BASE_PRECISION_U64,
+            )
+        };
+        // taking order still in auction
+        user.orders[6] = Order {
+            post_only: false,
+            auction_duration: 10,
+            slot: 0,
+            ..resting_order(
+                PositionDirection::Long,
+                100 * PRICE_PRECISION_U64,
+                BASE_PRECISION_U64,
+            )
+        };
+        // untriggered trigger limit
+        user.orders[7] = Order {
+            order_type: OrderType::TriggerLimit,
+            trigger_condition: OrderTriggerCondition::Below,
+            trigger_price: 100 * PRICE_PRECISION_U64,
+            ..resting_order(
+                PositionDirection::Long,
+                100 * PRICE_PRECISION_U64,
+                BASE_PRECISION_U64,
+            )
+        };
+        // canceled
+        user.orders[8] = Order {
+            status: OrderStatus::Canceled,
+            ..resting_order(
+                PositionDirection::Long,
+                100 * PRICE_PRECISION_U64,
+                BASE_PRECISION_U64,
+            )
+        };
+
+        let resting_base_asset_amount = calculate_resting_base_asset_amount(
+            &user,
+            0,
+            oracle_price,
+            max_oracle_distance,
+            5,
+            1,
+            false,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(resting_base_asset_amount, 5 * BASE_PRECISION_U64 / 2);
+    }
+
+    #[test]
+    fn skips_orders_placed_after_slot() {
+        let oracle_price = 100 * PRICE_PRECISION_I64;
+        let max_oracle_distance = (PERCENTAGE_PRECISION / 100) as u32; // 1%
+
+        let mut user = User::default();
+        user.orders[0] = Order {
+            slot: 5,
+            ..resting_order(
+                PositionDirection::Long,
+                100 * PRICE_PRECISION_U64,
+                BASE_PRECISION_U64,
+            )
+        };
+        user.orders[1] = Order {
+            slot: 6,
+            ..resting_order(
+                PositionDirection::Short,
+                100 * PRICE_PRECISION_U64,
+                10 * BASE_PRECISION_U64,
+            )
+        };
+
+        let resting_base_asset_amount = calculate_resting_base_asset_amount(
+            &user,
+            0,
+            oracle_price,
+            max_oracle_distance,
+            10,
+            1,
+            false,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(resting_base_asset_amount, 11 * BASE_PRECISION_U64);
+
+        let resting_since_last_sample = calculate_resting_base_asset_amount(
+            &user,
+            0,
+            oracle_price,
+            max_oracle_distance,
+            10,
+            1,
+            false,
+            Some(5),
+        )
+        .unwrap();
+
+        assert_eq!(resting_since_last_sample, BASE_PRECISION_U64);
+    }
+}
+
+mod update_maker_rewards {
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::Owner;
+
+    use crate::controller::maker_rewards::{
+        update_maker_rewards_after_fill, update_maker_rewards_depth,
+    };
+    use crate::controller::position::PositionDirection;
+    use crate::create_anchor_account_info;
+    use crate::math::constants::{
+        BASE_PRECISION_I64, BASE_PRECISION_U64, PERCENTAGE_PRECISION, PRICE_PRECISION_I64,
+        PRICE_PRECISION_U64,
+    };
+    use crate::state::maker_rewards::MakerRewardsProgram;
+    use crate::state::user::{MarketType, Order, OrderStatus, OrderType, PerpPosition, User};
+    use crate::state::user_map::UserMap;
+    use crate::test_utils::*;
+
+    fn maker_with_resting_order(authority: Pubkey) -> User {
+        let mut maker = User {
+            authority,
+            perp_positions: get_positions(PerpPosition {
+                market_index: 0,
+                base_asset_amount: -BASE_PRECISION_I64,
+                ..PerpPosition::default()
+            }),
+            ..User::default()
+        };
+        maker.orders[0] = Order {
+            status: OrderStatus::Open,
+            order_type: OrderType::Limit,
+            market_type: MarketType::Perp,
+            direction: PositionDirection::Short,
+            price: 100 * PRICE_PRECISION_U64,
+            base_asset_amount: BASE_PRECISION_U64,
+            post_only: true,
+            ..Order::default()
+        };
+        maker
+    }
+
+    fn maker_rewards_program(maker_key: Pubkey) -> MakerRewardsProgram {
+        let mut maker_rewards_program = MakerRewardsProgram {
+            epoch_duration: 3600,
+            max_oracle_distance: (PERCENTAGE_PRECISION / 100) as u32,
+            ..MakerRewardsProgram::default()
+        };
+        maker_rewards_program.add_maker(maker_key, 0, 0, 0).unwrap();
+        maker_rewards_program.makers[0].resting_base_asset_amount = BASE_PRECISION_U64;
+        maker_rewards_program
+    }
+
+    #[test]
+    fn resample_without_fill() {
+        let maker_key = Pubkey::new_unique();
+        let mut maker = maker_with_resting_order(Pubkey::new_unique());
+        create_anchor_account_info!(maker, &maker_key, User, maker_account_info);
+        let makers = UserMap::load_one(&maker_account_info).unwrap();
+
+        let mut maker_rewards_program = maker_rewards_program(maker_key);
+
+        update_maker_rewards_depth(
+            &mut maker_rewards_program,
+            &makers,
+            0,
+            100 * PRICE_PRECISION_I64,
+            10,
+            1,
+            false,
+            100,
+        )
+        .unwrap();
+
+        let maker_entry = &maker_rewards_program.makers[0];
+        assert_eq!(maker_entry.depth_score, 100 * BASE_PRECISION_U64 as u128);
+        assert_eq!(maker_entry.maker_volume, 0);
+        assert_eq!(maker_entry.last_sample_ts, 100);
+        assert_eq!(maker_entry.last_sample_slot, 10);
+        assert_eq!(
+            maker_rewards_program.total_depth_score,
+            100 * BASE_PRECISION_U64 as u128
+        );
+    }
+
+    #[test]
+    fn no_volume_for_taker_with_same_authority() {
+        let maker_key = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let mut maker = maker_with_resting_order(authority);
+        create_anchor_account_info!(maker, &maker_key, User, maker_account_info);
+        let makers = UserMap::load_one(&maker_account_info).unwrap();
+
+        let mut maker_rewards_program = maker_rewards_program(maker_key);
+
+        // maker was flat before the fill
+        let maker_base_asset_amounts_before = [(maker_key, 0_usize, 0_i64)];
+
+        update_maker_rewards_after_fill(
+            &mut maker_rewards_program,
+            &maker_base_asset_amounts_before,
+            &makers,
+            &authority,
+            0,
+            100 * PRICE_PRECISION_I64,
+            10,
+            1,
+            false,
+            100,
+        )
+        .unwrap();
+
+        // depth is still credited
+        let maker_entry = &maker_rewards_program.makers[0];
+        assert_eq!(maker_entry.maker_volume, 0);
+        assert_eq!(maker_entry.depth_score, 100 * BASE_PRECISION_U64 as u128);
+        assert_eq!(maker_rewards_program.total_maker_volume, 0);
+
+        update_maker_rewards_after_fill(
+            &mut maker_rewards_program,
+            &maker_base_asset_amounts_before,
+            &makers,
+            &Pubkey::new_unique(),
+            0,
+            100 * PRICE_PRECISION_I64,
+            10,
+            1,
+            false,
+            100,
+        )
+        .unwrap();
+
+        assert_eq!(
+            maker_rewards_program.makers[0].maker_volume,
+            BASE_PRECISION_U64
+        );
+        assert_eq!(maker_rewards_program.total_maker_volume, BASE_PRECISION_U64);
+    }
+}
@@ -3,6 +3,7 @@ pub mod funding;
 pub mod insurance;
 pub mod liquidation;
 pub mod lp;
+pub mod maker_rewards;
 pub mod orders;
 pub mod pda;
 pub mod pnl;
@@ -10,4 +11,5 @@ pub mod position;
 pub mod repeg;
 pub mod spot_balance;
 pub mod spot_position;
+pub mod term_loan;
 pub mod token;
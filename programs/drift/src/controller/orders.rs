@@ -1,6 +1,6 @@
 use std::cell::RefMut;
 use std::collections::BTreeMap;
-use std::ops::DerefMut;
+use std::ops::{Deref, DerefMut};
 use std::u64;
 
 use anchor_lang::prelude::*;
@@ -30,11 +30,14 @@ use crate::load_mut;
 use crate::math::amm_jit::calculate_amm_jit_liquidity;
 use crate::math::auction::{calculate_auction_params_for_trigger_order, calculate_auction_prices};
 use crate::math::casting::Cast;
-use crate::math::constants::{BASE_PRECISION_U64, PERP_DECIMALS, QUOTE_SPOT_MARKET_INDEX};
+use crate::math::constants::{
+    BASE_PRECISION_U64, FEE_PERCENTAGE_DENOMINATOR, PERP_DECIMALS, QUOTE_SPOT_MARKET_INDEX,
+};
 use crate::math::fees::{determine_user_fee_tier, ExternalFillFees, FillFees};
 use crate::math::fulfillment::{
     determine_perp_fulfillment_methods, determine_spot_fulfillment_methods,
 };
+use crate::math::helpers::get_proportion_u128;
 use crate::math::liquidation::validate_user_not_being_liquidated;
 use crate::math::matching::{
     are_orders_same_market_but_different_sides, calculate_fill_for_matched_orders,
@@ -59,11 +62,15 @@ use crate::state::events::{
 use crate::state::events::{OrderAction, OrderActionExplanation};
 use crate::state::fill_mode::FillMode;
 use crate::state::fulfillment::{PerpFulfillmentMethod, SpotFulfillmentMethod};
+use crate::state::load_ref::load_ref_mut;
+use crate::state::maker_rewards::MakerRewardsProgram;
 use crate::state::margin_calculation::{MarginCalculation, MarginContext};
-use crate::state::oracle::{OraclePriceData, StrictOraclePrice};
+use crate::state::oracle::{OraclePriceData, OracleSource, PrelaunchOracle, StrictOraclePrice};
 use crate::state::oracle_map::OracleMap;
 use crate::state::paused_operations::{PerpOperation, SpotOperation};
-use crate::state::perp_market::{AMMLiquiditySplit, MarketStatus, PerpMarket};
+use crate::state::perp_market::{
+    AMMLiquiditySplit, MarketStatus, PerpMarket, PerpMarketDepthBounds,
+};
 use crate::state::perp_market_map::PerpMarketMap;
 use crate::state::spot_fulfillment_params::{ExternalSpotFill, SpotFulfillmentParams};
 use crate::state::spot_market::{SpotBalanceType, SpotMarket};
@@ -383,6 +390,8 @@ pub fn place_perp_order(
         maker,
         maker_order,
         oracle_map.get_price_data(&market.amm.oracle)?.price,
+        None,
+        None,
     )?;
     emit_stack::<_, { OrderActionRecord::SIZE }>(order_action_record)?;
 
@@ -657,6 +666,8 @@ pub fn cancel_order(
             maker,
             maker_order,
             oracle_map.get_price_data(&oracle)?.price,
+            None,
+            None,
         )?;
         emit_stack::<_, { OrderActionRecord::SIZE }>(order_action_record)?;
     }
@@ -887,6 +898,11 @@ pub fn fill_perp_order(
     makers_and_referrer: &UserMap,
     makers_and_referrer_stats: &UserStatsMap,
     jit_maker_order_id: Option<u32>,
+    referral_tier_config: Option<&ReferralTierConfig>,
+    fee_tier_qualifications: Option<&FeeTierQualifications>,
+    market_fee_structure: Option<&MarketFeeStructure>,
+    maker_rewards_program: Option<&mut MakerRewardsProgram>,
+    depth_bounds: Option<&PerpMarketDepthBounds>,
     clock: &Clock,
     fill_mode: FillMode,
 ) -> DriftResult<(u64, u64)> {
@@ -937,8 +953,25 @@ pub fn fill_perp_order(
         "Market fills paused",
     )?;
 
+    let risk_increasing_fills_paused = market.is_operation_paused(PerpOperation::ReduceOnly);
+    let has_fee_structure_override = market.has_fee_structure_override;
+    let has_maker_rewards_program = market.has_maker_rewards_program;
+    let has_depth_bounds = market.has_depth_bounds;
+
     drop(market);
 
+    let maker_rewards_program = controller::maker_rewards::select_maker_rewards_program(
+        maker_rewards_program,
+        market_index,
+        has_maker_rewards_program,
+    )?;
+
+    let depth_bounds = controller::amm::select_perp_market_depth_bounds(
+        depth_bounds,
+        market_index,
+        has_depth_bounds,
+    )?;
+
     validate!(
         order_status == OrderStatus::Open,
         ErrorCode::OrderNotOpen,
@@ -993,6 +1026,7 @@ pub fn fill_perp_order(
             market.market_index,
             &market.amm.oracle,
             market.amm.historical_oracle_data.last_oracle_price_twap,
+            &market.oracle_validity_guard_rails,
             market.get_max_confidence_interval_multiplier()?,
         )?;
 
@@ -1041,6 +1075,7 @@ pub fn fill_perp_order(
         &user_key,
         &user.orders[order_index],
         &mut filler.as_deref_mut(),
+        &mut filler_stats.as_deref_mut(),
         &filler_key,
         state.perp_fee_structure.flat_filler_fee,
         oracle_price,
@@ -1114,6 +1149,7 @@ pub fn fill_perp_order(
             pay_keeper_flat_reward_for_perps(
                 user,
                 filler.as_deref_mut(),
+                filler_stats.as_deref_mut(),
                 market.deref_mut(),
                 state.perp_fee_structure.flat_filler_fee,
                 slot,
@@ -1144,6 +1180,52 @@ pub fn fill_perp_order(
         return Ok((0, 0));
     }
 
+    if risk_increasing_fills_paused
+        && !user.orders[order_index].reduce_only
+        && !determine_if_user_order_is_position_decreasing(user, market_index, order_index)?
+    {
+        msg!("perp market {} risk increasing fills paused", market_index);
+        return Ok((0, 0));
+    }
+
+    let fee_structure = fees::select_fee_structure(
+        &state.perp_fee_structure,
+        market_fee_structure,
+        MarketType::Perp,
+        market_index,
+        has_fee_structure_override,
+    )?;
+
+    let fee_tier_qualifications =
+        fees::select_fee_tier_qualifications(fee_tier_qualifications, MarketType::Perp);
+
+    let referral_fee_structure = get_referral_fee_structure(
+        &referrer_info,
+        makers_and_referrer_stats,
+        referral_tier_config,
+        fee_structure,
+    )?;
+
+    let referrer_reward_before = match &referrer_info {
+        Some((referrer_authority_key, _)) => {
+            makers_and_referrer_stats
+                .get_ref(referrer_authority_key)?
+                .fees
+                .total_referrer_reward
+        }
+        None => 0,
+    };
+
+    let maker_base_asset_amounts_before = match &maker_rewards_program {
+        Some(maker_rewards_program) => controller::maker_rewards::get_maker_base_asset_amounts(
+            maker_rewards_program,
+            makers_and_referrer,
+            &user_key,
+            market_index,
+        )?,
+        None => vec![],
+    };
+
     let (base_asset_amount, quote_asset_amount) = fulfill_perp_order(
         user,
         order_index,
@@ -1159,7 +1241,8 @@ pub fn fill_perp_order(
         spot_market_map,
         perp_market_map,
         oracle_map,
-        &state.perp_fee_structure,
+        referral_fee_structure.as_ref().unwrap_or(fee_structure),
+        &fee_tier_qualifications,
         reserve_price_before,
         valid_oracle_price,
         now,
@@ -1169,6 +1252,41 @@ pub fn fill_perp_order(
         fill_mode,
     )?;
 
+    update_referrer_after_fill(
+        &referrer_info,
+        referrer_reward_before,
+        quote_asset_amount,
+        &user.authority,
+        makers_and_referrer,
+        makers_and_referrer_stats,
+        filler_stats.as_deref_mut(),
+        referral_tier_config,
+        perp_market_map,
+        market_index,
+        fee_structure.referrer_reward_epoch_upper_bound,
+        now,
+    )?;
+
+    if let Some(maker_rewards_program) = maker_rewards_program {
+        let (tick_size, is_prediction_market) = {
+            let market = perp_market_map.get_ref(&market_index)?;
+            (market.amm.order_tick_size, market.is_prediction_market())
+        };
+
+        controller::maker_rewards::update_maker_rewards_after_fill(
+            maker_rewards_program,
+            &maker_base_asset_amounts_before,
+            makers_and_referrer,
+            &user.authority,
+            market_index,
+            oracle_price,
+            slot,
+            tick_size,
+            is_prediction_market,
+            now,
+        )?;
+    }
+
     if base_asset_amount != 0 {
         let fill_price =
             calculate_fill_price(quote_asset_amount, base_asset_amount, BASE_PRECISION_U64)?;
@@ -1203,6 +1321,7 @@ pub fn fill_perp_order(
             pay_keeper_flat_reward_for_perps(
                 user,
                 filler.as_deref_mut(),
+                filler_stats.as_deref_mut(),
                 market.deref_mut(),
                 state.perp_fee_structure.flat_filler_fee,
                 slot,
@@ -1261,6 +1380,7 @@ pub fn fill_perp_order(
             &state.oracle_guard_rails,
             funding_paused,
             Some(reserve_price_before),
+            depth_bounds,
         )?;
     }
 
@@ -1332,6 +1452,7 @@ fn get_maker_orders_info(
     taker_key: &Pubkey,
     taker_order: &Order,
     filler: &mut Option<&mut User>,
+    filler_stats: &mut Option<&mut UserStats>,
     filler_key: &Pubkey,
     filler_reward: u64,
     oracle_price: i64,
@@ -1376,6 +1497,7 @@ fn get_maker_orders_info(
 
         let initial_margin_ratio = market.margin_ratio_initial;
         let step_size = market.amm.order_step_size;
+        let risk_increasing_fills_paused = market.is_operation_paused(PerpOperation::ReduceOnly);
 
         drop(market);
 
@@ -1399,6 +1521,17 @@ fn get_maker_orders_info(
                 }
             }
 
+            if risk_increasing_fills_paused
+                && !maker_order.reduce_only
+                && !determine_if_user_order_is_position_decreasing(
+                    &maker,
+                    taker_order.market_index,
+                    maker_order_index,
+                )?
+            {
+                continue;
+            }
+
             let breaches_oracle_price_limits = {
                 limit_price_breaches_maker_oracle_price_bands(
                     maker_order_price,
@@ -1429,6 +1562,7 @@ fn get_maker_orders_info(
                     pay_keeper_flat_reward_for_perps(
                         &mut maker,
                         filler.as_deref_mut(),
+                        filler_stats.as_deref_mut(),
                         market.deref_mut(),
                         filler_reward,
                         slot,
@@ -1537,6 +1671,172 @@ fn get_referrer_info(
     Ok(Some((referrer_authority_key, referrer_user_key)))
 }
 
+fn get_referral_fee_structure(
+    referrer_info: &Option<(Pubkey, Pubkey)>,
+    makers_and_referrer_stats: &UserStatsMap,
+    referral_tier_config: Option<&ReferralTierConfig>,
+    perp_fee_structure: &FeeStructure,
+) -> DriftResult<Option<FeeStructure>> {
+    let (referrer_authority_key, referral_tier_config) = match (referrer_info, referral_tier_config)
+    {
+        (Some((referrer_authority_key, _)), Some(referral_tier_config)) => {
+            (referrer_authority_key, referral_tier_config)
+        }
+        _ => return Ok(None),
+    };
+
+    let referee_volume_30d = makers_and_referrer_stats
+        .get_ref(referrer_authority_key)?
+        .referee_volume_30d;
+
+    Ok(referral_tier_config
+        .get_referral_tier(referee_volume_30d)
+        .map(|referral_tier| perp_fee_structure.with_referral_tier(referral_tier)))
+}
+
+/// Tracks the referrer's referee volume and accrues the referrer's own referrer (the sub-affiliate
+/// parent) its share of the referrer reward earned on the fill. The share is taken from the
+/// referrer's position and held in the market's fee pool until the parent claims it from there.
+/// Errors if the parent is owed a share and its stats weren't passed
+fn update_referrer_after_fill(
+    referrer_info: &Option<(Pubkey, Pubkey)>,
+    referrer_reward_before: u64,
+    quote_asset_amount: u64,
+    user_authority: &Pubkey,
+    makers_and_referrer: &UserMap,
+    makers_and_referrer_stats: &UserStatsMap,
+    filler_stats: Option<&mut UserStats>,
+    referral_tier_config: Option<&ReferralTierConfig>,
+    perp_market_map: &PerpMarketMap,
+    market_index: u16,
+    referrer_reward_epoch_upper_bound: u64,
+    now: i64,
+) -> DriftResult {
+    let (referrer_authority_key, referrer_user_key) = match referrer_info {
+        Some(referrer_info) => referrer_info,
+        None => return Ok(()),
+    };
+
+    if quote_asset_amount == 0 {
+        return Ok(());
+    }
+
+    let mut referrer_stats = makers_and_referrer_stats.get_ref_mut(referrer_authority_key)?;
+    referrer_stats.update_referee_volume_30d(quote_asset_amount, now)?;
+
+    let sub_affiliate_reward_numerator = match referral_tier_config {
+        Some(referral_tier_config) => referral_tier_config.sub_affiliate_reward_numerator,
+        None => return Ok(()),
+    };
+
+    let parent_authority_key = referrer_stats.referrer;
+    let referrer_reward = referrer_stats
+        .fees
+        .total_referrer_reward
+        .safe_sub(referrer_reward_before)?;
+
+    if referrer_reward == 0
+        || sub_affiliate_reward_numerator == 0
+        || parent_authority_key == Pubkey::default()
+        || parent_authority_key == *user_authority
+        || parent_authority_key == *referrer_authority_key
+    {
+        return Ok(());
+    }
+
+    // the filler can't decide whether the parent gets paid by leaving out its stats
+    let mut parent_stats_ref;
+    let parent_stats: &mut UserStats = if makers_and_referrer_stats
+        .0
+        .contains_key(&parent_authority_key)
+    {
+        parent_stats_ref = makers_and_referrer_stats.get_ref_mut(&parent_authority_key)?;
+        &mut parent_stats_ref
+    } else {
+        match filler_stats {
+            Some(filler_stats) if filler_stats.authority == parent_authority_key => filler_stats,
+            _ => {
+                msg!(
+                    "sub affiliate stats {} must be passed for referrer {}",
+                    parent_authority_key,
+                    referrer_authority_key
+                );
+                return Err(ErrorCode::ReferrerStatsNotFound);
+            }
+        }
+    };
+
+    let sub_affiliate_reward = get_proportion_u128(
+        referrer_reward.cast()?,
+        sub_affiliate_reward_numerator.cast()?,
+        FEE_PERCENTAGE_DENOMINATOR.cast()?,
+    )?
+    .cast::<u64>()?
+    .min(
+        referrer_reward_epoch_upper_bound
+            .saturating_sub(parent_stats.fees.current_epoch_referrer_reward),
+    );
+
+    if sub_affiliate_reward == 0 {
+        return Ok(());
+    }
+
+    let market = &mut perp_market_map.get_ref_mut(&market_index)?;
+    let mut referrer = makers_and_referrer.get_ref_mut(referrer_user_key)?;
+
+    let referrer_position = referrer.get_perp_position_mut(market_index)?;
+    update_quote_asset_amount(
+        referrer_position,
+        market,
+        -sub_affiliate_reward.cast::<i64>()?,
+    )?;
+    referrer_stats.decrement_total_referrer_reward(sub_affiliate_reward)?;
+
+    market.amm.total_fee_minus_distributions = market
+        .amm
+        .total_fee_minus_distributions
+        .safe_add(sub_affiliate_reward.cast()?)?;
+
+    parent_stats.increment_total_referrer_reward(sub_affiliate_reward, now)?;
+    parent_stats.fees.unclaimed_sub_affiliate_reward = parent_stats
+        .fees
+        .unclaimed_sub_affiliate_reward
+        .safe_add(sub_affiliate_reward)?;
+
+    Ok(())
+}
+
+fn update_fill_twap_oracle(
+    market: &PerpMarket,
+    oracle_map: &OracleMap,
+    base_asset_amount: u64,
+    quote_asset_amount: u64,
+    now: i64,
+) -> DriftResult {
+    if market.amm.oracle_source != OracleSource::FillTwap || base_asset_amount == 0 {
+        return Ok(());
+    }
+
+    let oracle_account_info = oracle_map.get_account_info(&market.amm.oracle)?;
+
+    // fill paths that only pass the oracle as readonly skip the twap update instead of failing
+    if !oracle_account_info.is_writable {
+        msg!(
+            "fill twap oracle {} not writable, skipping update",
+            market.amm.oracle
+        );
+        return Ok(());
+    }
+
+    let mut oracle: RefMut<PrelaunchOracle> =
+        load_ref_mut(&oracle_account_info).or(Err(ErrorCode::UnableToLoadOracle))?;
+
+    let fill_price =
+        calculate_fill_price(quote_asset_amount, base_asset_amount, BASE_PRECISION_U64)?;
+
+    oracle.update_fill_vwap(fill_price, quote_asset_amount, now)
+}
+
 fn fulfill_perp_order(
     user: &mut User,
     user_order_index: usize,
@@ -1553,6 +1853,7 @@ fn fulfill_perp_order(
     perp_market_map: &PerpMarketMap,
     oracle_map: &mut OracleMap,
     fee_structure: &FeeStructure,
+    fee_tier_qualifications: &FeeTierQualifications,
     reserve_price_before: u64,
     valid_oracle_price: Option<i64>,
     now: i64,
@@ -1653,6 +1954,7 @@ fn fulfill_perp_order(
                         &mut referrer.as_deref_mut(),
                         &mut referrer_stats.as_deref_mut(),
                         fee_structure,
+                        fee_tier_qualifications,
                         limit_price,
                         None,
                         *maker_price,
@@ -1699,6 +2001,7 @@ fn fulfill_perp_order(
                         now,
                         slot,
                         fee_structure,
+                        fee_tier_qualifications,
                         oracle_map,
                         fill_mode.is_liquidation(),
                     )?;
@@ -1731,6 +2034,14 @@ fn fulfill_perp_order(
         quote_asset_amount
     )?;
 
+    update_fill_twap_oracle(
+        perp_market_map.get_ref(&market_index)?.deref(),
+        oracle_map,
+        base_asset_amount,
+        quote_asset_amount,
+        now,
+    )?;
+
     let total_maker_fill = maker_fills.values().sum::<i64>();
 
     validate!(
@@ -1769,6 +2080,7 @@ fn fulfill_perp_order(
             taker_margin_calculation.fuel_deposits,
             taker_margin_calculation.fuel_borrows,
             taker_margin_calculation.fuel_positions,
+            taker_margin_calculation.fuel_lp,
             now,
         )?;
 
@@ -1814,6 +2126,7 @@ fn fulfill_perp_order(
                 maker_margin_calculation.fuel_deposits,
                 maker_margin_calculation.fuel_borrows,
                 maker_margin_calculation.fuel_positions,
+                maker_margin_calculation.fuel_lp,
                 now,
             )?;
         }
@@ -1911,6 +2224,7 @@ pub fn fulfill_perp_order_with_amm(
     referrer: &mut Option<&mut User>,
     referrer_stats: &mut Option<&mut UserStats>,
     fee_structure: &FeeStructure,
+    fee_tier_qualifications: &FeeTierQualifications,
     limit_price: Option<u64>,
     override_base_asset_amount: Option<u64>,
     override_fill_price: Option<u64>,
@@ -1926,7 +2240,8 @@ pub fn fulfill_perp_order_with_amm(
             (override_base_asset_amount, limit_price, override_fill_price)
         }
         None => {
-            let fee_tier = determine_user_fee_tier(user_stats, fee_structure, &MarketType::Perp)?;
+            let (_, fee_tier) =
+                determine_user_fee_tier(user_stats, fee_structure, fee_tier_qualifications)?;
             let (base_asset_amount, limit_price) = calculate_base_asset_amount_for_amm_to_fulfill(
                 &user.orders[order_index],
                 market,
@@ -2019,10 +2334,13 @@ pub fn fulfill_perp_order_with_amm(
         referrer_reward,
         fee_to_market_for_lp,
         maker_rebate,
+        taker_fee_tier_index,
+        maker_fee_tier_index,
     } = fees::calculate_fee_for_fulfillment_with_amm(
         user_stats,
         quote_asset_amount,
         fee_structure,
+        fee_tier_qualifications,
         order_slot,
         slot,
         reward_filler,
@@ -2180,6 +2498,8 @@ pub fn fulfill_perp_order_with_amm(
         maker,
         maker_order,
         oracle_map.get_price_data(&market.amm.oracle)?.price,
+        taker_fee_tier_index,
+        maker_fee_tier_index,
     )?;
     emit_stack::<_, { OrderActionRecord::SIZE }>(order_action_record)?;
 
@@ -2213,10 +2533,9 @@ pub fn credit_filler_perp_pnl(
             filler_reward.cast()?,
         )?;
 
-        filler_stats
-            .as_mut()
-            .safe_unwrap()?
-            .update_filler_volume(quote_asset_amount, now)?;
+        let filler_stats = filler_stats.as_mut().safe_unwrap()?;
+        filler_stats.update_filler_volume(quote_asset_amount, now)?;
+        filler_stats.update_fuel_keeper_bonus(market.fuel_boost_keeper, filler_reward)?;
     }
 
     filler.update_last_active_slot(slot);
@@ -2245,6 +2564,7 @@ pub fn fulfill_perp_order_with_match(
     now: i64,
     slot: u64,
     fee_structure: &FeeStructure,
+    fee_tier_qualifications: &FeeTierQualifications,
     oracle_map: &mut OracleMap,
     is_liquidation: bool,
 ) -> DriftResult<(u64, u64, u64)> {
@@ -2357,6 +2677,7 @@ pub fn fulfill_perp_order_with_match(
                 &mut None,
                 &mut None,
                 fee_structure,
+                fee_tier_qualifications,
                 taker_limit_price,
                 Some(jit_base_asset_amount),
                 Some(maker_price), // match the makers price
@@ -2465,18 +2786,20 @@ pub fn fulfill_perp_order_with_match(
         filler_reward,
         referrer_reward,
         referee_discount,
+        taker_fee_tier_index,
+        maker_fee_tier_index,
         ..
     } = fees::calculate_fee_for_fulfillment_with_match(
         taker_stats,
         maker_stats,
         quote_asset_amount,
         fee_structure,
+        fee_tier_qualifications,
         taker.orders[taker_order_index].slot,
         slot,
         filler_multiplier,
         reward_referrer,
         referrer_stats,
-        &MarketType::Perp,
         market.fee_adjustment,
     )?;
 
@@ -2529,10 +2852,9 @@ pub fn fulfill_perp_order_with_match(
                 filler_reward.cast()?,
             )?;
 
-            filler_stats
-                .as_mut()
-                .safe_unwrap()?
-                .update_filler_volume(quote_asset_amount, now)?;
+            let filler_stats = filler_stats.as_mut().safe_unwrap()?;
+            filler_stats.update_filler_volume(quote_asset_amount, now)?;
+            filler_stats.update_fuel_keeper_bonus(market.fuel_boost_keeper, filler_reward)?;
         }
         filler.update_last_active_slot(slot);
     }
@@ -2598,6 +2920,8 @@ pub fn fulfill_perp_order_with_match(
         Some(*maker_key),
         Some(maker.orders[maker_order_index]),
         oracle_map.get_price_data(&market.amm.oracle)?.price,
+        taker_fee_tier_index,
+        maker_fee_tier_index,
     )?;
     emit_stack::<_, { OrderActionRecord::SIZE }>(order_action_record)?;
 
@@ -2660,6 +2984,7 @@ pub fn trigger_order(
     perp_market_map: &PerpMarketMap,
     oracle_map: &mut OracleMap,
     filler: &AccountLoader<User>,
+    filler_stats: &AccountLoader<UserStats>,
     clock: &Clock,
 ) -> DriftResult {
     let now = clock.unix_timestamp;
@@ -2721,6 +3046,7 @@ pub fn trigger_order(
             .amm
             .historical_oracle_data
             .last_oracle_price_twap,
+        &perp_market.oracle_validity_guard_rails,
         perp_market.get_max_confidence_interval_multiplier()?,
     )?;
 
@@ -2780,15 +3106,16 @@ pub fn trigger_order(
     }
 
     let is_filler_taker = user_key == filler_key;
-    let mut filler = if !is_filler_taker {
-        Some(load_mut!(filler)?)
+    let (mut filler, mut filler_stats) = if !is_filler_taker {
+        (Some(load_mut!(filler)?), Some(load_mut!(filler_stats)?))
     } else {
-        None
+        (None, None)
     };
 
     let filler_reward = pay_keeper_flat_reward_for_perps(
         user,
         filler.as_deref_mut(),
+        filler_stats.as_deref_mut(),
         &mut perp_market,
         state.perp_fee_structure.flat_filler_fee,
         slot,
@@ -2814,6 +3141,8 @@ pub fn trigger_order(
         None,
         None,
         oracle_price,
+        None,
+        None,
     )?;
     emit!(order_action_record);
 
@@ -3137,6 +3466,7 @@ pub fn burn_user_lp_shares_for_risk_reduction(
 pub fn pay_keeper_flat_reward_for_perps(
     user: &mut User,
     filler: Option<&mut User>,
+    filler_stats: Option<&mut UserStats>,
     market: &mut PerpMarket,
     filler_reward: u64,
     slot: u64,
@@ -3161,6 +3491,10 @@ pub fn pay_keeper_flat_reward_for_perps(
             filler_reward.cast()?,
         )?;
 
+        if let Some(filler_stats) = filler_stats {
+            filler_stats.update_fuel_keeper_bonus(market.fuel_boost_keeper, filler_reward)?;
+        }
+
         filler_reward
     } else {
         0
@@ -3465,6 +3799,8 @@ pub fn place_spot_order(
         maker,
         maker_order,
         oracle_price_data.price,
+        None,
+        None,
     )?;
     emit_stack::<_, { OrderActionRecord::SIZE }>(order_action_record)?;
 
@@ -3493,6 +3829,8 @@ pub fn fill_spot_order(
     makers_and_referrer: &UserMap,
     makers_and_referrer_stats: &UserStatsMap,
     jit_maker_order_id: Option<u32>,
+    fee_tier_qualifications: Option<&FeeTierQualifications>,
+    market_fee_structure: Option<&MarketFeeStructure>,
     clock: &Clock,
     fulfillment_params: &mut dyn SpotFulfillmentParams,
 ) -> DriftResult<u64> {
@@ -3518,14 +3856,15 @@ pub fn fill_spot_order(
         direction
     );
 
-    {
+    let has_fee_structure_override = {
         let spot_market = spot_market_map.get_ref(&order_market_index)?;
         validate!(
             spot_market.fills_enabled(),
             ErrorCode::MarketFillOrderPaused,
             "Market unavailable for fills"
         )?;
-    }
+        spot_market.has_fee_structure_override()
+    };
 
     validate!(
         order_market_type == MarketType::Spot,
@@ -3678,6 +4017,33 @@ pub fn fill_spot_order(
         return Ok(0);
     }
 
+    let is_risk_increasing_fill_paused = {
+        let spot_market = spot_market_map.get_ref(&order_market_index)?;
+        if spot_market.is_operation_paused(SpotOperation::ReduceOnly)
+            && !user.orders[order_index].reduce_only
+        {
+            let token_amount = user
+                .get_spot_position(order_market_index)?
+                .get_signed_token_amount(&spot_market)?
+                .cast::<i64>()?;
+            !is_order_position_reducing(
+                &user.orders[order_index].direction,
+                user.orders[order_index].get_base_asset_amount_unfilled(Some(token_amount))?,
+                token_amount,
+            )?
+        } else {
+            false
+        }
+    };
+
+    if is_risk_increasing_fill_paused {
+        msg!(
+            "spot market {} risk increasing fills paused",
+            order_market_index
+        );
+        return Ok(0);
+    }
+
     if fulfillment_params.is_external() {
         let exchange_status = state.get_exchange_status()?;
 
@@ -3688,6 +4054,9 @@ pub fn fill_spot_order(
         )?;
     }
 
+    let fee_tier_qualifications =
+        fees::select_fee_tier_qualifications(fee_tier_qualifications, MarketType::Spot);
+
     let (base_asset_amount, quote_asset_amount) = fulfill_spot_order(
         user,
         order_index,
@@ -3704,7 +4073,14 @@ pub fn fill_spot_order(
         oracle_map,
         now,
         slot,
-        &state.spot_fee_structure,
+        fees::select_fee_structure(
+            &state.spot_fee_structure,
+            market_fee_structure,
+            MarketType::Spot,
+            order_market_index,
+            has_fee_structure_override,
+        )?,
+        &fee_tier_qualifications,
         fulfillment_params,
     )?;
 
@@ -3860,6 +4236,8 @@ fn get_spot_maker_orders_info(
             .get_spot_position(taker_order.market_index)?
             .get_signed_token_amount(&market)?;
 
+        let risk_increasing_fills_paused = market.is_operation_paused(SpotOperation::ReduceOnly);
+
         drop(market);
 
         for (maker_order_index, maker_order_price) in maker_order_price_and_indexes.iter() {
@@ -3882,6 +4260,18 @@ fn get_spot_maker_orders_info(
                 }
             }
 
+            if risk_increasing_fills_paused
+                && !maker_order.reduce_only
+                && !is_order_position_reducing(
+                    &maker_order.direction,
+                    maker_order
+                        .get_base_asset_amount_unfilled(Some(existing_base_asset_amount.cast()?))?,
+                    existing_base_asset_amount.cast()?,
+                )?
+            {
+                continue;
+            }
+
             let breaches_oracle_price_limits = {
                 limit_price_breaches_maker_oracle_price_bands(
                     maker_order_price,
@@ -3967,6 +4357,7 @@ fn fulfill_spot_order(
     now: i64,
     slot: u64,
     fee_structure: &FeeStructure,
+    fee_tier_qualifications: &FeeTierQualifications,
     fulfillment_params: &mut dyn SpotFulfillmentParams,
 ) -> DriftResult<(u64, u64)> {
     let base_market_index = user.orders[user_order_index].market_index;
@@ -4081,6 +4472,7 @@ fn fulfill_spot_order(
                     slot,
                     oracle_map,
                     fee_structure,
+                    fee_tier_qualifications,
                 )?;
 
                 if base_filled != 0 {
@@ -4108,6 +4500,7 @@ fn fulfill_spot_order(
                 slot,
                 oracle_map,
                 fee_structure,
+                fee_tier_qualifications,
                 fulfillment_params,
             )?,
         };
@@ -4206,6 +4599,7 @@ fn fulfill_spot_order(
         taker_margin_calculation.fuel_deposits,
         taker_margin_calculation.fuel_borrows,
         taker_margin_calculation.fuel_positions,
+        taker_margin_calculation.fuel_lp,
         now,
     )?;
 
@@ -4298,6 +4692,7 @@ fn fulfill_spot_order(
                 maker_margin_calculation.fuel_deposits,
                 maker_margin_calculation.fuel_borrows,
                 maker_margin_calculation.fuel_positions,
+                maker_margin_calculation.fuel_lp,
                 now,
             )?;
         }
@@ -4334,6 +4729,7 @@ pub fn fulfill_spot_order_with_match(
     slot: u64,
     oracle_map: &mut OracleMap,
     fee_structure: &FeeStructure,
+    fee_tier_qualifications: &FeeTierQualifications,
 ) -> DriftResult<(u64, u64)> {
     if !are_orders_same_market_but_different_sides(
         &maker.orders[maker_order_index],
@@ -4477,18 +4873,20 @@ pub fn fulfill_spot_order_with_match(
         maker_rebate,
         filler_reward,
         fee_to_market,
+        taker_fee_tier_index,
+        maker_fee_tier_index,
         ..
     } = fees::calculate_fee_for_fulfillment_with_match(
         taker_stats,
         maker_stats,
         quote_asset_amount,
         fee_structure,
+        fee_tier_qualifications,
         taker_order_slot,
         slot,
         filler_multiplier,
         false,
         &None,
-        &MarketType::Spot,
         base_market.fee_adjustment,
     )?;
 
@@ -4645,6 +5043,8 @@ pub fn fulfill_spot_order_with_match(
         Some(*maker_key),
         Some(maker.orders[maker_order_index]),
         oracle_map.get_price_data(&base_market.oracle)?.price,
+        taker_fee_tier_index,
+        maker_fee_tier_index,
     )?;
     emit_stack::<_, { OrderActionRecord::SIZE }>(order_action_record)?;
 
@@ -4678,6 +5078,7 @@ pub fn fulfill_spot_order_with_external_market(
     slot: u64,
     oracle_map: &mut OracleMap,
     fee_structure: &FeeStructure,
+    fee_tier_qualifications: &FeeTierQualifications,
     fulfillment_params: &mut dyn SpotFulfillmentParams,
 ) -> DriftResult<(u64, u64)> {
     let oracle_price = oracle_map.get_price_data(&base_market.oracle)?.price;
@@ -4778,10 +5179,12 @@ pub fn fulfill_spot_order_with_external_market(
         fee_to_market,
         fee_pool_delta,
         filler_reward,
+        taker_fee_tier_index,
     } = fees::calculate_fee_for_fulfillment_with_external_market(
         taker_stats,
         quote_asset_amount_filled,
         fee_structure,
+        fee_tier_qualifications,
         taker_order_slot,
         slot,
         filler.is_some(),
@@ -4909,6 +5312,8 @@ pub fn fulfill_spot_order_with_external_market(
         None,
         None,
         oracle_price,
+        Some(taker_fee_tier_index),
+        None,
     )?;
     emit_stack::<_, { OrderActionRecord::SIZE }>(order_action_record)?;
 
@@ -4989,6 +5394,7 @@ pub fn trigger_spot_order(
         spot_market.market_index,
         &spot_market.oracle,
         spot_market.historical_oracle_data.last_oracle_price_twap,
+        &spot_market.oracle_validity_guard_rails,
         spot_market.get_max_confidence_interval_multiplier()?,
     )?;
     let strict_oracle_price = StrictOraclePrice {
@@ -4998,6 +5404,7 @@ pub fn trigger_spot_order(
                 .historical_oracle_data
                 .last_oracle_price_twap_5min,
         ),
+        confidence_adjustment: 0,
     };
 
     validate!(
@@ -5100,6 +5507,8 @@ pub fn trigger_spot_order(
         None,
         None,
         oracle_price,
+        None,
+        None,
     )?;
 
     emit!(order_action_record);
@@ -3,7 +3,7 @@ use anchor_lang::Owner;
 
 use crate::math::constants::ONE_BPS_DENOMINATOR;
 use crate::state::oracle_map::OracleMap;
-use crate::state::state::{FeeStructure, FeeTier};
+use crate::state::state::{FeeStructure, FeeTier, FeeTierQualifications};
 use crate::state::user::{Order, PerpPosition};
 
 fn get_fee_structure() -> FeeStructure {
@@ -33,7 +33,9 @@ pub mod amm_jit {
     use crate::controller::position::PositionDirection;
     use crate::create_account_info;
     use crate::create_anchor_account_info;
-    use crate::math::constants::{PRICE_PRECISION_I64, QUOTE_PRECISION_I64};
+    use crate::math::constants::{
+        PERCENTAGE_PRECISION_U64, PRICE_PRECISION_I64, QUOTE_PRECISION_I64,
+    };
 
     use crate::math::constants::{
         AMM_RESERVE_PRECISION, BASE_PRECISION_I128, BASE_PRECISION_I64, BASE_PRECISION_U64,
@@ -114,6 +116,7 @@ pub mod amm_jit {
             Some(PRICE_PRECISION_I64),
             PositionDirection::Long,
             AMMLiquiditySplit::ProtocolOwned,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
     }
@@ -293,6 +296,7 @@ pub mod amm_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -482,6 +486,7 @@ pub mod amm_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(PRICE_PRECISION_I64),
             now,
@@ -679,6 +684,7 @@ pub mod amm_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(PRICE_PRECISION_I64),
             now,
@@ -875,6 +881,7 @@ pub mod amm_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(200 * PRICE_PRECISION_I64),
             now,
@@ -1073,6 +1080,7 @@ pub mod amm_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -1280,6 +1288,7 @@ pub mod amm_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -1485,6 +1494,7 @@ pub mod amm_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -1713,6 +1723,7 @@ pub mod amm_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -1912,6 +1923,7 @@ pub mod amm_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -2099,6 +2111,7 @@ pub mod amm_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(1),
             now,
@@ -2298,6 +2311,7 @@ pub mod amm_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(200 * PRICE_PRECISION_I64),
             now,
@@ -2548,6 +2562,7 @@ pub mod amm_jit {
                 &market_map,
                 &mut oracle_map,
                 &fee_structure,
+                &FeeTierQualifications::perps_default(),
                 0,
                 Some(1),
                 now,
@@ -2831,6 +2846,7 @@ pub mod amm_jit {
                 &market_map,
                 &mut oracle_map,
                 &fee_structure,
+                &FeeTierQualifications::perps_default(),
                 0,
                 Some(200 * PRICE_PRECISION_I64),
                 now,
@@ -3059,6 +3075,7 @@ pub mod amm_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(1),
             now,
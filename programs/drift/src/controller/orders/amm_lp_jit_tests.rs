@@ -3,7 +3,7 @@ use anchor_lang::Owner;
 
 use crate::math::constants::ONE_BPS_DENOMINATOR;
 use crate::state::oracle_map::OracleMap;
-use crate::state::state::{FeeStructure, FeeTier};
+use crate::state::state::{FeeStructure, FeeTier, FeeTierQualifications};
 use crate::state::user::{Order, PerpPosition};
 
 fn get_fee_structure() -> FeeStructure {
@@ -34,7 +34,8 @@ pub mod amm_lp_jit {
     use crate::create_account_info;
     use crate::create_anchor_account_info;
     use crate::math::constants::{
-        PERCENTAGE_PRECISION_I128, PRICE_PRECISION_I64, QUOTE_PRECISION_I64,
+        PERCENTAGE_PRECISION_I128, PERCENTAGE_PRECISION_U64, PRICE_PRECISION_I64,
+        QUOTE_PRECISION_I64,
     };
 
     use crate::math::amm_jit::calculate_amm_jit_liquidity;
@@ -107,6 +108,7 @@ pub mod amm_lp_jit {
             Some(100 * PRICE_PRECISION_I64),
             PositionDirection::Short,
             AMMLiquiditySplit::Shared,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
         assert_eq!(jit_base_asset_amount, 500000000);
@@ -118,6 +120,7 @@ pub mod amm_lp_jit {
             Some(100 * PRICE_PRECISION_I64),
             PositionDirection::Long,
             AMMLiquiditySplit::Shared,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
         assert_eq!(jit_base_asset_amount, 500000000);
@@ -129,6 +132,7 @@ pub mod amm_lp_jit {
             Some(100 * PRICE_PRECISION_I64),
             PositionDirection::Long,
             AMMLiquiditySplit::Shared,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
         assert_eq!(jit_base_asset_amount, 300000000);
@@ -140,6 +144,7 @@ pub mod amm_lp_jit {
             Some(100 * PRICE_PRECISION_I64),
             PositionDirection::Long,
             AMMLiquiditySplit::Shared,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
         assert_eq!(jit_base_asset_amount, 0);
@@ -154,6 +159,7 @@ pub mod amm_lp_jit {
             Some(100 * PRICE_PRECISION_I64),
             PositionDirection::Long,
             AMMLiquiditySplit::Shared,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
         assert_eq!(jit_base_asset_amount, 45454000);
@@ -165,6 +171,7 @@ pub mod amm_lp_jit {
             Some(100 * PRICE_PRECISION_I64),
             PositionDirection::Short,
             AMMLiquiditySplit::Shared,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
         assert_eq!(jit_base_asset_amount, 45454000);
@@ -176,6 +183,7 @@ pub mod amm_lp_jit {
             Some(100 * PRICE_PRECISION_I64),
             PositionDirection::Short,
             AMMLiquiditySplit::Shared,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
         assert_eq!(jit_base_asset_amount, 0);
@@ -187,6 +195,7 @@ pub mod amm_lp_jit {
             Some(100 * PRICE_PRECISION_I64),
             PositionDirection::Short,
             AMMLiquiditySplit::Shared,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
         assert_eq!(jit_base_asset_amount, 0);
@@ -200,6 +209,7 @@ pub mod amm_lp_jit {
             Some(100 * PRICE_PRECISION_I64),
             PositionDirection::Short,
             AMMLiquiditySplit::Shared,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
         assert_eq!(jit_base_asset_amount, 0);
@@ -211,6 +221,7 @@ pub mod amm_lp_jit {
             Some(100 * PRICE_PRECISION_I64),
             PositionDirection::Short,
             AMMLiquiditySplit::Shared,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
         assert_eq!(jit_base_asset_amount, 0);
@@ -223,6 +234,7 @@ pub mod amm_lp_jit {
             Some(100 * PRICE_PRECISION_I64),
             PositionDirection::Short,
             AMMLiquiditySplit::Shared,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
         assert_eq!(jit_base_asset_amount, 9803000);
@@ -234,6 +246,7 @@ pub mod amm_lp_jit {
             Some(100 * PRICE_PRECISION_I64),
             PositionDirection::Long,
             AMMLiquiditySplit::Shared,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
         assert_eq!(jit_base_asset_amount, 0);
@@ -302,6 +315,7 @@ pub mod amm_lp_jit {
             Some(100 * PRICE_PRECISION_I64),
             PositionDirection::Short,
             AMMLiquiditySplit::Shared,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
         assert_eq!(jit_base_asset_amount, 500000000);
@@ -313,6 +327,7 @@ pub mod amm_lp_jit {
             Some(100 * PRICE_PRECISION_I64),
             PositionDirection::Long,
             AMMLiquiditySplit::Shared,
+            PERCENTAGE_PRECISION_U64,
         )
         .unwrap();
         assert_eq!(jit_base_asset_amount, 500000000);
@@ -642,6 +657,7 @@ pub mod amm_lp_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -844,6 +860,7 @@ pub mod amm_lp_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(PRICE_PRECISION_I64),
             now,
@@ -1053,6 +1070,7 @@ pub mod amm_lp_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(PRICE_PRECISION_I64),
             now,
@@ -1269,6 +1287,7 @@ pub mod amm_lp_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(PRICE_PRECISION_I64),
             now,
@@ -1488,6 +1507,7 @@ pub mod amm_lp_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(200 * PRICE_PRECISION_I64),
             now,
@@ -1689,6 +1709,7 @@ pub mod amm_lp_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -1899,6 +1920,7 @@ pub mod amm_lp_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -2101,6 +2123,7 @@ pub mod amm_lp_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -2291,6 +2314,7 @@ pub mod amm_lp_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(1),
             now,
@@ -2493,6 +2517,7 @@ pub mod amm_lp_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(200 * PRICE_PRECISION_I64),
             now,
@@ -2743,6 +2768,7 @@ pub mod amm_lp_jit {
                 &market_map,
                 &mut oracle_map,
                 &fee_structure,
+                &FeeTierQualifications::perps_default(),
                 0,
                 Some(1),
                 now,
@@ -3026,6 +3052,7 @@ pub mod amm_lp_jit {
                 &market_map,
                 &mut oracle_map,
                 &fee_structure,
+                &FeeTierQualifications::perps_default(),
                 0,
                 Some(200 * PRICE_PRECISION_I64),
                 now,
@@ -3257,6 +3284,7 @@ pub mod amm_lp_jit {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(1),
             now,
@@ -5,7 +5,7 @@ use crate::math::constants::ONE_BPS_DENOMINATOR;
 use crate::math::margin::MarginRequirementType;
 use crate::state::margin_calculation::{MarginCalculation, MarginContext};
 use crate::state::oracle_map::OracleMap;
-use crate::state::state::{FeeStructure, FeeTier};
+use crate::state::state::{FeeStructure, FeeTier, FeeTierQualifications};
 use crate::state::user::{Order, PerpPosition};
 
 fn get_fee_structure() -> FeeStructure {
@@ -237,6 +237,7 @@ pub mod fuel_scoring {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -812,4 +813,81 @@ pub mod fuel_scoring {
         assert_eq!(margin_calc.fuel_deposits, 1000 / 28);
         assert_eq!(taker_stats.fuel_deposits, margin_calc.fuel_deposits);
     }
+
+    #[test]
+    fn lp_fuel() {
+        let market = PerpMarket {
+            fuel_boost_lp: 10,
+            ..PerpMarket::default_test()
+        };
+
+        let mut perp_position = PerpPosition {
+            market_index: 0,
+            lp_shares: 10 * BASE_PRECISION_U64,
+            ..PerpPosition::default()
+        };
+
+        let mut margin_context = MarginContext::standard(MarginRequirementType::Initial);
+        margin_context.fuel_bonus_numerator = crate::FUEL_WINDOW_U128 as i64;
+
+        let mut calculation = MarginCalculation::new(margin_context);
+        calculation
+            .update_fuel_lp_bonus(&market, &perp_position, 100 * PRICE_PRECISION_I64)
+            .unwrap();
+
+        // $1000 of lp shares for a full fuel window
+        assert_eq!(calculation.fuel_lp, 100000);
+        assert_eq!(calculation.fuel_positions, 0);
+
+        perp_position.lp_shares = 0;
+        let mut calculation = MarginCalculation::new(margin_context);
+        calculation
+            .update_fuel_lp_bonus(&market, &perp_position, 100 * PRICE_PRECISION_I64)
+            .unwrap();
+        assert_eq!(calculation.fuel_lp, 0);
+    }
+
+    #[test]
+    fn keeper_fuel() {
+        let mut market = PerpMarket {
+            fuel_boost_keeper: 10,
+            ..PerpMarket::default_test()
+        };
+
+        let mut user = User {
+            perp_positions: get_positions(PerpPosition {
+                market_index: 0,
+                open_orders: 1,
+                ..PerpPosition::default()
+            }),
+            ..User::default()
+        };
+        let mut filler = User::default();
+        let mut filler_stats = UserStats::default();
+
+        let filler_reward = crate::controller::orders::pay_keeper_flat_reward_for_perps(
+            &mut user,
+            Some(&mut filler),
+            Some(&mut filler_stats),
+            &mut market,
+            10_000,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(filler_reward, 10_000);
+        assert_eq!(filler_stats.fuel_keeper, 10);
+
+        // no fuel if filler stats arent passed
+        crate::controller::orders::pay_keeper_flat_reward_for_perps(
+            &mut user,
+            Some(&mut filler),
+            None,
+            &mut market,
+            10_000,
+            0,
+        )
+        .unwrap();
+        assert_eq!(filler_stats.fuel_keeper, 10);
+    }
 }
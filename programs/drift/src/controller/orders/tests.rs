@@ -3,7 +3,7 @@ use anchor_lang::Owner;
 
 use crate::state::oracle_map::OracleMap;
 use crate::state::perp_market::MarketStatus;
-use crate::state::state::{FeeStructure, FeeTier};
+use crate::state::state::{FeeStructure, FeeTier, FeeTierQualifications};
 use crate::state::user::{MarketType, Order, PerpPosition};
 
 fn get_fee_structure() -> FeeStructure {
@@ -129,6 +129,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -147,7 +148,7 @@ pub mod fulfill_order_with_maker_order {
         assert_eq!(taker_stats.fees.total_fee_paid, 50000);
         assert_eq!(taker_stats.taker_volume_30d, 100 * QUOTE_PRECISION_U64);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker.orders[0], Order::default());
 
         let maker_position = &maker.perp_positions[0];
@@ -249,6 +250,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -266,7 +268,7 @@ pub mod fulfill_order_with_maker_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 80000);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 160 * QUOTE_PRECISION_U64);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -369,6 +371,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -383,7 +386,7 @@ pub mod fulfill_order_with_maker_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 90000);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 180 * QUOTE_PRECISION_U64);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -489,6 +492,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -503,7 +507,7 @@ pub mod fulfill_order_with_maker_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 70000);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 140 * QUOTE_PRECISION_U64);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -609,6 +613,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -695,6 +700,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -782,6 +788,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -869,6 +876,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -956,6 +964,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -1063,6 +1072,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -1173,6 +1183,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -1190,7 +1201,7 @@ pub mod fulfill_order_with_maker_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 75000);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 150 * QUOTE_PRECISION_U64);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -1290,6 +1301,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -1318,7 +1330,7 @@ pub mod fulfill_order_with_maker_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 50000);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 100 * QUOTE_PRECISION_U64);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -1408,6 +1420,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -1436,7 +1449,7 @@ pub mod fulfill_order_with_maker_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 50000);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 100 * QUOTE_PRECISION_U64);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -1550,6 +1563,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -1667,6 +1681,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -1745,6 +1760,7 @@ pub mod fulfill_order_with_maker_order {
                 market.market_index,
                 &oracle_price_key,
                 market.amm.historical_oracle_data.last_oracle_price_twap,
+                &market.oracle_validity_guard_rails,
                 market.get_max_confidence_interval_multiplier().unwrap(),
             )
             .unwrap();
@@ -1792,6 +1808,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut oracle_map,
             false,
         )
@@ -1820,7 +1837,7 @@ pub mod fulfill_order_with_maker_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 50000);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 100 * QUOTE_PRECISION_U64);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -1934,6 +1951,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut oracle_map,
             false,
         )
@@ -1962,7 +1980,7 @@ pub mod fulfill_order_with_maker_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 50000);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 100 * QUOTE_PRECISION_U64);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -2074,6 +2092,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut oracle_map,
             false,
         )
@@ -2102,7 +2121,7 @@ pub mod fulfill_order_with_maker_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 50000);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 100 * QUOTE_PRECISION_U64);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -2215,6 +2234,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut oracle_map,
             false,
         )
@@ -2243,7 +2263,7 @@ pub mod fulfill_order_with_maker_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 50000);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 100 * QUOTE_PRECISION_U64);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -2343,6 +2363,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -2371,7 +2392,7 @@ pub mod fulfill_order_with_maker_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 50000);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 100 * QUOTE_PRECISION_U64);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -2470,6 +2491,7 @@ pub mod fulfill_order_with_maker_order {
             now,
             slot,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             &mut get_oracle_map(),
             false,
         )
@@ -2498,7 +2520,7 @@ pub mod fulfill_order_with_maker_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 50000);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 100 * QUOTE_PRECISION_U64);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -2785,6 +2807,7 @@ pub mod fulfill_order {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -2806,7 +2829,7 @@ pub mod fulfill_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 50129);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 100256237);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -3028,6 +3051,7 @@ pub mod fulfill_order {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             100 * PRICE_PRECISION_U64,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -3217,6 +3241,7 @@ pub mod fulfill_order {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -3238,7 +3263,7 @@ pub mod fulfill_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 50142);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 100281362);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -3422,6 +3447,7 @@ pub mod fulfill_order {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             None,
             now,
@@ -3443,7 +3469,7 @@ pub mod fulfill_order {
         assert_eq!(taker_position.open_orders, 1);
         assert_eq!(taker_stats.fees.total_fee_paid, 25000);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 50 * QUOTE_PRECISION_U64);
 
         let maker = makers_and_referrers.get_ref_mut(&maker_key).unwrap();
@@ -3587,6 +3613,7 @@ pub mod fulfill_order {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -3608,7 +3635,7 @@ pub mod fulfill_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 52041);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 104081633);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -3784,6 +3811,7 @@ pub mod fulfill_order {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             100 * PRICE_PRECISION_U64,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -3970,6 +3998,7 @@ pub mod fulfill_order {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             100 * PRICE_PRECISION_U64,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -4109,6 +4138,7 @@ pub mod fulfill_order {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             reserve_price_before,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -4129,7 +4159,7 @@ pub mod fulfill_order {
         assert_eq!(taker_stats.fees.total_fee_paid, 0);
         assert_eq!(taker_stats.fees.total_fee_rebate, 1049);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 0);
         assert_eq!(taker_stats.maker_volume_30d, 3499697);
 
@@ -4275,6 +4305,7 @@ pub mod fulfill_order {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             reserve_price_before,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -4295,7 +4326,7 @@ pub mod fulfill_order {
         assert_eq!(taker_stats.fees.total_fee_paid, 0);
         assert_eq!(taker_stats.fees.total_fee_rebate, 1050);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 0);
         assert_eq!(taker_stats.maker_volume_30d, 3500096);
 
@@ -4681,6 +4712,7 @@ pub mod fulfill_order {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             None,
             now,
@@ -4702,7 +4734,7 @@ pub mod fulfill_order {
         assert_eq!(taker_position.open_orders, 1);
         assert_eq!(taker_stats.fees.total_fee_paid, 25000);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 50 * QUOTE_PRECISION_U64);
 
         let taker_order = &taker.orders[0].clone();
@@ -4924,6 +4956,7 @@ pub mod fulfill_order {
             &market_map,
             &mut oracle_map,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             Some(market.amm.historical_oracle_data.last_oracle_price),
             now,
@@ -4945,7 +4978,7 @@ pub mod fulfill_order {
         assert_eq!(taker_position.open_orders, 0);
         assert_eq!(taker_stats.fees.total_fee_paid, 50129);
         assert_eq!(taker_stats.fees.total_referee_discount, 0);
-        assert_eq!(taker_stats.fees.total_token_discount, 0);
+        assert_eq!(taker_stats.fees.unclaimed_sub_affiliate_reward, 0);
         assert_eq!(taker_stats.taker_volume_30d, 100256237);
         assert_eq!(taker.orders[0], Order::default());
 
@@ -4986,27 +5019,436 @@ pub mod fill_order {
     use crate::state::perp_market_map::PerpMarketMap;
     use crate::state::spot_market::{SpotBalanceType, SpotMarket};
     use crate::state::spot_market_map::SpotMarketMap;
-    use crate::state::state::State;
+    use crate::state::state::{ReferralTier, ReferralTierConfig, State};
     use crate::state::user::{MarketType, OrderStatus, OrderType, SpotPosition, User, UserStats};
     use crate::test_utils::*;
     use crate::test_utils::{
         create_account_info, get_orders, get_positions, get_pyth_price, get_spot_positions,
     };
-    use crate::{create_account_info, QUOTE_PRECISION_I64};
+    use crate::{create_account_info, QUOTE_PRECISION_I64, QUOTE_PRECISION_U64};
+
+    use super::*;
+    use crate::error::ErrorCode;
+    use crate::math::constants::PERCENTAGE_PRECISION_U64;
+    use crate::state::fill_mode::FillMode;
+    use crate::state::maker_rewards::MakerRewardsProgram;
+    use crate::state::user_map::{UserMap, UserStatsMap};
+
+    #[test]
+    fn maker_order_canceled_for_breaching_oracle_price_band() {
+        let clock = Clock {
+            slot: 56,
+            epoch_start_timestamp: 0,
+            epoch: 0,
+            leader_schedule_epoch: 0,
+            unix_timestamp: 0,
+        };
+
+        let mut oracle_price = get_pyth_price(100, 6);
+        let oracle_price_key =
+            Pubkey::from_str("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix").unwrap();
+        let pyth_program = crate::ids::pyth_program::id();
+        create_account_info!(
+            oracle_price,
+            &oracle_price_key,
+            &pyth_program,
+            oracle_account_info
+        );
+        let mut oracle_map = OracleMap::load_one(&oracle_account_info, clock.slot, None).unwrap();
+
+        let mut market = PerpMarket {
+            amm: AMM {
+                base_asset_reserve: 100 * AMM_RESERVE_PRECISION,
+                quote_asset_reserve: 100 * AMM_RESERVE_PRECISION,
+                terminal_quote_asset_reserve: 100 * AMM_RESERVE_PRECISION,
+                sqrt_k: 100 * AMM_RESERVE_PRECISION,
+                peg_multiplier: 100 * PEG_PRECISION,
+                max_slippage_ratio: 100,
+                max_fill_reserve_fraction: 100,
+                order_step_size: 1000,
+                order_tick_size: 1,
+                oracle: oracle_price_key,
+                max_spread: 1000,
+                base_spread: 0,
+                long_spread: 0,
+                short_spread: 0,
+                historical_oracle_data: HistoricalOracleData {
+                    last_oracle_price_twap: oracle_price.twap,
+                    last_oracle_price_twap_5min: oracle_price.twap,
+                    last_oracle_price: oracle_price.agg.price,
+                    ..HistoricalOracleData::default()
+                },
+                ..AMM::default()
+            },
+            margin_ratio_initial: 1000,
+            margin_ratio_maintenance: 500,
+            status: MarketStatus::Initialized,
+            ..PerpMarket::default()
+        };
+        market.status = MarketStatus::Active;
+        market.amm.max_base_asset_reserve = u128::MAX;
+        market.amm.min_base_asset_reserve = 0;
+        let (new_ask_base_asset_reserve, new_ask_quote_asset_reserve) =
+            crate::math::amm_spread::calculate_spread_reserves(&market, PositionDirection::Long)
+                .unwrap();
+        let (new_bid_base_asset_reserve, new_bid_quote_asset_reserve) =
+            crate::math::amm_spread::calculate_spread_reserves(&market, PositionDirection::Short)
+                .unwrap();
+        market.amm.ask_base_asset_reserve = new_ask_base_asset_reserve;
+        market.amm.bid_base_asset_reserve = new_bid_base_asset_reserve;
+        market.amm.ask_quote_asset_reserve = new_ask_quote_asset_reserve;
+        market.amm.bid_quote_asset_reserve = new_bid_quote_asset_reserve;
+        create_anchor_account_info!(market, PerpMarket, market_account_info);
+        let market_map = PerpMarketMap::load_one(&market_account_info, true).unwrap();
+
+        let mut spot_market = SpotMarket {
+            market_index: 0,
+            oracle_source: OracleSource::QuoteAsset,
+            cumulative_deposit_interest: SPOT_CUMULATIVE_INTEREST_PRECISION,
+            decimals: 6,
+            initial_asset_weight: SPOT_WEIGHT_PRECISION,
+            maintenance_asset_weight: SPOT_WEIGHT_PRECISION,
+            historical_oracle_data: HistoricalOracleData::default_price(QUOTE_PRECISION_I64),
+            ..SpotMarket::default()
+        };
+        create_anchor_account_info!(spot_market, SpotMarket, spot_market_account_info);
+        let spot_market_map = SpotMarketMap::load_one(&spot_market_account_info, true).unwrap();
+
+        let mut user = User {
+            authority: Pubkey::from_str("My11111111111111111111111111111111111111111").unwrap(), // different authority than filler
+            orders: get_orders(Order {
+                market_index: 0,
+                order_id: 1,
+                status: OrderStatus::Open,
+                order_type: OrderType::Market,
+                direction: PositionDirection::Long,
+                base_asset_amount: BASE_PRECISION_U64,
+                slot: 0,
+                auction_start_price: 0,
+                auction_end_price: 50 * PRICE_PRECISION_I64,
+                auction_duration: 5,
+                price: 50 * PRICE_PRECISION_U64,
+                ..Order::default()
+            }),
+            perp_positions: get_positions(PerpPosition {
+                market_index: 0,
+                open_orders: 1,
+                open_bids: BASE_PRECISION_I64,
+                ..PerpPosition::default()
+            }),
+            spot_positions: get_spot_positions(SpotPosition {
+                market_index: 0,
+                balance_type: SpotBalanceType::Deposit,
+                scaled_balance: 100 * SPOT_BALANCE_PRECISION_U64,
+                ..SpotPosition::default()
+            }),
+            ..User::default()
+        };
+        create_anchor_account_info!(user, User, user_account_info);
+        let user_account_loader: AccountLoader<User> =
+            AccountLoader::try_from(&user_account_info).unwrap();
+
+        create_anchor_account_info!(UserStats::default(), UserStats, user_stats_account_info);
+        let user_stats_account_loader: AccountLoader<UserStats> =
+            AccountLoader::try_from(&user_stats_account_info).unwrap();
+
+        let maker_key = Pubkey::from_str("My11111111111111111111111111111111111111113").unwrap();
+        let maker_authority =
+            Pubkey::from_str("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix").unwrap();
+        let mut maker = User {
+            authority: maker_authority,
+            orders: get_orders(Order {
+                market_index: 0,
+                order_id: 1,
+                status: OrderStatus::Open,
+                order_type: OrderType::Limit,
+                direction: PositionDirection::Short,
+                base_asset_amount: BASE_PRECISION_U64,
+                slot: 0,
+                price: 50 * PRICE_PRECISION_U64,
+                post_only: true,
+                ..Order::default()
+            }),
+            perp_positions: get_positions(PerpPosition {
+                market_index: 0,
+                open_orders: 1,
+                open_asks: -BASE_PRECISION_I64,
+                ..PerpPosition::default()
+            }),
+            spot_positions: get_spot_positions(SpotPosition {
+                market_index: 0,
+                balance_type: SpotBalanceType::Deposit,
+                scaled_balance: 100 * SPOT_BALANCE_PRECISION_U64,
+                ..SpotPosition::default()
+            }),
+            ..User::default()
+        };
+        create_anchor_account_info!(maker, &maker_key, User, maker_account_info);
+        let makers_and_referrers = UserMap::load_one(&maker_account_info).unwrap();
+
+        let mut maker_stats = UserStats {
+            authority: maker_authority,
+            ..UserStats::default()
+        };
+        create_anchor_account_info!(maker_stats, UserStats, maker_stats_account_info);
+        let maker_and_referrer_stats = UserStatsMap::load_one(&maker_stats_account_info).unwrap();
+
+        let filler_key = Pubkey::from_str("My11111111111111111111111111111111111111111").unwrap();
+        create_anchor_account_info!(User::default(), &filler_key, User, user_account_info);
+        let filler_account_loader: AccountLoader<User> =
+            AccountLoader::try_from(&user_account_info).unwrap();
+
+        create_anchor_account_info!(UserStats::default(), UserStats, filler_stats_account_info);
+        let filler_stats_account_loader: AccountLoader<UserStats> =
+            AccountLoader::try_from(&filler_stats_account_info).unwrap();
+
+        let state = State {
+            min_perp_auction_duration: 1,
+            default_market_order_time_in_force: 10,
+            ..State::default()
+        };
+
+        let (base_asset_amount, _) = fill_perp_order(
+            1,
+            &state,
+            &user_account_loader,
+            &user_stats_account_loader,
+            &spot_market_map,
+            &market_map,
+            &mut oracle_map,
+            &filler_account_loader,
+            &filler_stats_account_loader,
+            &makers_and_referrers,
+            &maker_and_referrer_stats,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            &clock,
+            FillMode::Fill,
+        )
+        .unwrap();
+
+        assert_eq!(base_asset_amount, 0);
+
+        // order canceled
+        let maker = makers_and_referrers.get_ref_mut(&maker_key).unwrap();
+        assert_eq!(maker.orders[0], Order::default());
+    }
+
+    #[test]
+    fn fallback_maker_order_id() {
+        let clock = Clock {
+            slot: 56,
+            epoch_start_timestamp: 0,
+            epoch: 0,
+            leader_schedule_epoch: 0,
+            unix_timestamp: 0,
+        };
+
+        let mut oracle_price = get_pyth_price(100, 6);
+        let oracle_price_key =
+            Pubkey::from_str("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix").unwrap();
+        let pyth_program = crate::ids::pyth_program::id();
+        create_account_info!(
+            oracle_price,
+            &oracle_price_key,
+            &pyth_program,
+            oracle_account_info
+        );
+        let mut oracle_map = OracleMap::load_one(&oracle_account_info, clock.slot, None).unwrap();
+
+        let mut market = PerpMarket {
+            amm: AMM {
+                base_asset_reserve: 100 * AMM_RESERVE_PRECISION,
+                quote_asset_reserve: 100 * AMM_RESERVE_PRECISION,
+                terminal_quote_asset_reserve: 100 * AMM_RESERVE_PRECISION,
+                sqrt_k: 100 * AMM_RESERVE_PRECISION,
+                peg_multiplier: 100 * PEG_PRECISION,
+                max_slippage_ratio: 100,
+                max_fill_reserve_fraction: 100,
+                order_step_size: 1000,
+                order_tick_size: 1,
+                oracle: oracle_price_key,
+                max_spread: 1000,
+                base_spread: 0,
+                long_spread: 0,
+                short_spread: 0,
+                historical_oracle_data: HistoricalOracleData {
+                    last_oracle_price_twap: oracle_price.twap,
+                    last_oracle_price_twap_5min: oracle_price.twap,
+                    last_oracle_price: oracle_price.agg.price,
+                    ..HistoricalOracleData::default()
+                },
+                ..AMM::default()
+            },
+            margin_ratio_initial: 1000,
+            margin_ratio_maintenance: 500,
+            status: MarketStatus::Initialized,
+            ..PerpMarket::default()
+        };
+        market.status = MarketStatus::Active;
+        market.amm.max_base_asset_reserve = u128::MAX;
+        market.amm.min_base_asset_reserve = 0;
+        let (new_ask_base_asset_reserve, new_ask_quote_asset_reserve) =
+            crate::math::amm_spread::calculate_spread_reserves(&market, PositionDirection::Long)
+                .unwrap();
+        let (new_bid_base_asset_reserve, new_bid_quote_asset_reserve) =
+            crate::math::amm_spread::calculate_spread_reserves(&market, PositionDirection::Short)
+                .unwrap();
+        market.amm.ask_base_asset_reserve = new_ask_base_asset_reserve;
+        market.amm.bid_base_asset_reserve = new_bid_base_asset_reserve;
+        market.amm.ask_quote_asset_reserve = new_ask_quote_asset_reserve;
+        market.amm.bid_quote_asset_reserve = new_bid_quote_asset_reserve;
+        create_anchor_account_info!(market, PerpMarket, market_account_info);
+        let market_map = PerpMarketMap::load_one(&market_account_info, true).unwrap();
+
+        let mut spot_market = SpotMarket {
+            market_index: 0,
+            oracle_source: OracleSource::QuoteAsset,
+            cumulative_deposit_interest: SPOT_CUMULATIVE_INTEREST_PRECISION,
+            decimals: 6,
+            initial_asset_weight: SPOT_WEIGHT_PRECISION,
+            maintenance_asset_weight: SPOT_WEIGHT_PRECISION,
+            historical_oracle_data: HistoricalOracleData::default_price(QUOTE_PRECISION_I64),
+            ..SpotMarket::default()
+        };
+        create_anchor_account_info!(spot_market, SpotMarket, spot_market_account_info);
+        let spot_market_map = SpotMarketMap::load_one(&spot_market_account_info, true).unwrap();
+
+        let mut user = User {
+            authority: Pubkey::from_str("My11111111111111111111111111111111111111111").unwrap(), // different authority than filler
+            orders: get_orders(Order {
+                market_index: 0,
+                order_id: 1,
+                status: OrderStatus::Open,
+                order_type: OrderType::Market,
+                direction: PositionDirection::Long,
+                market_type: MarketType::Perp,
+                base_asset_amount: BASE_PRECISION_U64,
+                slot: 0,
+                auction_start_price: 0,
+                auction_end_price: 100 * PRICE_PRECISION_I64,
+                auction_duration: 5,
+                price: 100 * PRICE_PRECISION_U64,
+                ..Order::default()
+            }),
+            perp_positions: get_positions(PerpPosition {
+                market_index: 0,
+                open_orders: 1,
+                open_bids: BASE_PRECISION_I64,
+                ..PerpPosition::default()
+            }),
+            spot_positions: get_spot_positions(SpotPosition {
+                market_index: 0,
+                balance_type: SpotBalanceType::Deposit,
+                scaled_balance: 100 * SPOT_BALANCE_PRECISION_U64,
+                ..SpotPosition::default()
+            }),
+            ..User::default()
+        };
+        create_anchor_account_info!(user, User, user_account_info);
+        let user_account_loader: AccountLoader<User> =
+            AccountLoader::try_from(&user_account_info).unwrap();
+
+        create_anchor_account_info!(UserStats::default(), UserStats, user_stats_account_info);
+        let user_stats_account_loader: AccountLoader<UserStats> =
+            AccountLoader::try_from(&user_stats_account_info).unwrap();
+
+        let maker_key = Pubkey::from_str("My11111111111111111111111111111111111111113").unwrap();
+        let maker_authority =
+            Pubkey::from_str("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix").unwrap();
+        let maker_order_id = 1;
+        let mut maker = User {
+            authority: maker_authority,
+            orders: get_orders(Order {
+                market_index: 0,
+                order_id: maker_order_id,
+                status: OrderStatus::Open,
+                order_type: OrderType::Limit,
+                market_type: MarketType::Perp,
+                direction: PositionDirection::Short,
+                base_asset_amount: BASE_PRECISION_U64,
+                slot: 0,
+                price: 100 * PRICE_PRECISION_U64,
+                post_only: true,
+                ..Order::default()
+            }),
+            perp_positions: get_positions(PerpPosition {
+                market_index: 0,
+                open_orders: 1,
+                open_asks: -BASE_PRECISION_I64,
+                ..PerpPosition::default()
+            }),
+            spot_positions: get_spot_positions(SpotPosition {
+                market_index: 0,
+                balance_type: SpotBalanceType::Deposit,
+                scaled_balance: 100 * SPOT_BALANCE_PRECISION_U64,
+                ..SpotPosition::default()
+            }),
+            ..User::default()
+        };
+        create_anchor_account_info!(maker, &maker_key, User, maker_account_info);
+        let makers_and_referrers = UserMap::load_one(&maker_account_info).unwrap();
+
+        let mut maker_stats = UserStats {
+            authority: maker_authority,
+            ..UserStats::default()
+        };
+        create_anchor_account_info!(maker_stats, UserStats, maker_stats_account_info);
+        let maker_and_referrer_stats = UserStatsMap::load_one(&maker_stats_account_info).unwrap();
+
+        let filler_key = Pubkey::from_str("My11111111111111111111111111111111111111111").unwrap();
+        create_anchor_account_info!(User::default(), &filler_key, User, user_account_info);
+        let filler_account_loader: AccountLoader<User> =
+            AccountLoader::try_from(&user_account_info).unwrap();
 
-    use super::*;
-    use crate::error::ErrorCode;
-    use crate::state::fill_mode::FillMode;
-    use crate::state::user_map::{UserMap, UserStatsMap};
+        create_anchor_account_info!(UserStats::default(), UserStats, filler_stats_account_info);
+        let filler_stats_account_loader: AccountLoader<UserStats> =
+            AccountLoader::try_from(&filler_stats_account_info).unwrap();
+
+        let state = State {
+            min_perp_auction_duration: 1,
+            default_market_order_time_in_force: 10,
+            ..State::default()
+        };
+
+        let (base_asset_amount, _) = fill_perp_order(
+            1,
+            &state,
+            &user_account_loader,
+            &user_stats_account_loader,
+            &spot_market_map,
+            &market_map,
+            &mut oracle_map,
+            &filler_account_loader,
+            &filler_stats_account_loader,
+            &makers_and_referrers,
+            &maker_and_referrer_stats,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            &clock,
+            FillMode::Fill,
+        )
+        .unwrap();
+
+        assert_eq!(base_asset_amount, 1000000000);
+    }
 
     #[test]
-    fn maker_order_canceled_for_breaching_oracle_price_band() {
+    fn maker_rewards_program() {
         let clock = Clock {
             slot: 56,
             epoch_start_timestamp: 0,
             epoch: 0,
             leader_schedule_epoch: 0,
-            unix_timestamp: 0,
+            unix_timestamp: 10,
         };
 
         let mut oracle_price = get_pyth_price(100, 6);
@@ -5048,6 +5490,7 @@ pub mod fill_order {
             margin_ratio_initial: 1000,
             margin_ratio_maintenance: 500,
             status: MarketStatus::Initialized,
+            has_maker_rewards_program: true,
             ..PerpMarket::default()
         };
         market.status = MarketStatus::Active;
@@ -5087,12 +5530,13 @@ pub mod fill_order {
                 status: OrderStatus::Open,
                 order_type: OrderType::Market,
                 direction: PositionDirection::Long,
+                market_type: MarketType::Perp,
                 base_asset_amount: BASE_PRECISION_U64,
                 slot: 0,
                 auction_start_price: 0,
-                auction_end_price: 50 * PRICE_PRECISION_I64,
+                auction_end_price: 100 * PRICE_PRECISION_I64,
                 auction_duration: 5,
-                price: 50 * PRICE_PRECISION_U64,
+                price: 100 * PRICE_PRECISION_U64,
                 ..Order::default()
             }),
             perp_positions: get_positions(PerpPosition {
@@ -5120,24 +5564,42 @@ pub mod fill_order {
         let maker_key = Pubkey::from_str("My11111111111111111111111111111111111111113").unwrap();
         let maker_authority =
             Pubkey::from_str("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix").unwrap();
+        let maker_order_id = 1;
+        let mut maker_orders = [Order::default(); 32];
+        maker_orders[0] = Order {
+            market_index: 0,
+            order_id: maker_order_id,
+            status: OrderStatus::Open,
+            order_type: OrderType::Limit,
+            market_type: MarketType::Perp,
+            direction: PositionDirection::Short,
+            base_asset_amount: BASE_PRECISION_U64,
+            slot: 0,
+            price: 100 * PRICE_PRECISION_U64,
+            post_only: true,
+            ..Order::default()
+        };
+        // resting within max_oracle_distance
+        maker_orders[1] = Order {
+            order_id: 2,
+            base_asset_amount: 2 * BASE_PRECISION_U64,
+            price: 100 * PRICE_PRECISION_U64 + PRICE_PRECISION_U64 / 2,
+            ..maker_orders[0]
+        };
+        // resting too far from oracle
+        maker_orders[2] = Order {
+            order_id: 3,
+            base_asset_amount: BASE_PRECISION_U64,
+            price: 110 * PRICE_PRECISION_U64,
+            ..maker_orders[0]
+        };
         let mut maker = User {
             authority: maker_authority,
-            orders: get_orders(Order {
-                market_index: 0,
-                order_id: 1,
-                status: OrderStatus::Open,
-                order_type: OrderType::Limit,
-                direction: PositionDirection::Short,
-                base_asset_amount: BASE_PRECISION_U64,
-                slot: 0,
-                price: 50 * PRICE_PRECISION_U64,
-                post_only: true,
-                ..Order::default()
-            }),
+            orders: maker_orders,
             perp_positions: get_positions(PerpPosition {
                 market_index: 0,
-                open_orders: 1,
-                open_asks: -BASE_PRECISION_I64,
+                open_orders: 3,
+                open_asks: -4 * BASE_PRECISION_I64,
                 ..PerpPosition::default()
             }),
             spot_positions: get_spot_positions(SpotPosition {
@@ -5173,6 +5635,38 @@ pub mod fill_order {
             ..State::default()
         };
 
+        let mut maker_rewards_program = MakerRewardsProgram {
+            epoch_duration: 3600,
+            max_oracle_distance: PERCENTAGE_PRECISION_U64 as u32 / 100,
+            ..MakerRewardsProgram::default()
+        };
+        maker_rewards_program.add_maker(maker_key, 0, 0, 0).unwrap();
+        maker_rewards_program.makers[0].resting_base_asset_amount = BASE_PRECISION_U64;
+
+        let err = fill_perp_order(
+            1,
+            &state,
+            &user_account_loader,
+            &user_stats_account_loader,
+            &spot_market_map,
+            &market_map,
+            &mut oracle_map,
+            &filler_account_loader,
+            &filler_stats_account_loader,
+            &makers_and_referrers,
+            &maker_and_referrer_stats,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            &clock,
+            FillMode::Fill,
+        );
+
+        assert_eq!(err, Err(ErrorCode::InvalidMakerRewardsProgram));
+
         let (base_asset_amount, _) = fill_perp_order(
             1,
             &state,
@@ -5186,20 +5680,36 @@ pub mod fill_order {
             &makers_and_referrers,
             &maker_and_referrer_stats,
             None,
+            None,
+            None,
+            None,
+            Some(&mut maker_rewards_program),
+            None,
             &clock,
             FillMode::Fill,
         )
         .unwrap();
 
-        assert_eq!(base_asset_amount, 0);
+        assert_eq!(base_asset_amount, BASE_PRECISION_U64);
 
-        // order canceled
-        let maker = makers_and_referrers.get_ref_mut(&maker_key).unwrap();
-        assert_eq!(maker.orders[0], Order::default());
+        let maker_entry = &maker_rewards_program.makers[0];
+        assert_eq!(maker_entry.maker_volume, BASE_PRECISION_U64);
+        assert_eq!(maker_entry.depth_score, 10 * BASE_PRECISION_U64 as u128);
+        assert_eq!(
+            maker_entry.resting_base_asset_amount,
+            2 * BASE_PRECISION_U64
+        );
+        assert_eq!(maker_entry.last_sample_ts, 10);
+        assert_eq!(maker_entry.last_sample_slot, clock.slot);
+        assert_eq!(maker_rewards_program.total_maker_volume, BASE_PRECISION_U64);
+        assert_eq!(
+            maker_rewards_program.total_depth_score,
+            10 * BASE_PRECISION_U64 as u128
+        );
     }
 
     #[test]
-    fn fallback_maker_order_id() {
+    fn referral_tier_and_sub_affiliate_reward() {
         let clock = Clock {
             slot: 56,
             epoch_start_timestamp: 0,
@@ -5313,7 +5823,20 @@ pub mod fill_order {
         let user_account_loader: AccountLoader<User> =
             AccountLoader::try_from(&user_account_info).unwrap();
 
-        create_anchor_account_info!(UserStats::default(), UserStats, user_stats_account_info);
+        let referrer_key = Pubkey::from_str("My11111111111111111111111111111111111111114").unwrap();
+        let referrer_authority =
+            Pubkey::from_str("My11111111111111111111111111111111111111115").unwrap();
+        let parent_authority =
+            Pubkey::from_str("My11111111111111111111111111111111111111117").unwrap();
+
+        create_anchor_account_info!(
+            UserStats {
+                referrer: referrer_authority,
+                ..UserStats::default()
+            },
+            UserStats,
+            user_stats_account_info
+        );
         let user_stats_account_loader: AccountLoader<UserStats> =
             AccountLoader::try_from(&user_stats_account_info).unwrap();
 
@@ -5351,14 +5874,53 @@ pub mod fill_order {
             ..User::default()
         };
         create_anchor_account_info!(maker, &maker_key, User, maker_account_info);
-        let makers_and_referrers = UserMap::load_one(&maker_account_info).unwrap();
+        let mut makers_and_referrers = UserMap::load_one(&maker_account_info).unwrap();
+
+        let mut referrer = User {
+            authority: referrer_authority,
+            ..User::default()
+        };
+        create_anchor_account_info!(referrer, &referrer_key, User, referrer_account_info);
+        makers_and_referrers
+            .insert(
+                referrer_key,
+                AccountLoader::try_from(&referrer_account_info).unwrap(),
+            )
+            .unwrap();
 
         let mut maker_stats = UserStats {
             authority: maker_authority,
             ..UserStats::default()
         };
         create_anchor_account_info!(maker_stats, UserStats, maker_stats_account_info);
-        let maker_and_referrer_stats = UserStatsMap::load_one(&maker_stats_account_info).unwrap();
+        let mut maker_and_referrer_stats =
+            UserStatsMap::load_one(&maker_stats_account_info).unwrap();
+
+        let mut referrer_stats = UserStats {
+            authority: referrer_authority,
+            referrer: parent_authority,
+            referee_volume_30d: 1_000_000 * QUOTE_PRECISION_U64,
+            ..UserStats::default()
+        };
+        create_anchor_account_info!(referrer_stats, UserStats, referrer_stats_account_info);
+        maker_and_referrer_stats
+            .insert(
+                referrer_authority,
+                AccountLoader::try_from(&referrer_stats_account_info).unwrap(),
+            )
+            .unwrap();
+
+        let mut parent_stats = UserStats {
+            authority: parent_authority,
+            ..UserStats::default()
+        };
+        create_anchor_account_info!(parent_stats, UserStats, parent_stats_account_info);
+        maker_and_referrer_stats
+            .insert(
+                parent_authority,
+                AccountLoader::try_from(&parent_stats_account_info).unwrap(),
+            )
+            .unwrap();
 
         let filler_key = Pubkey::from_str("My11111111111111111111111111111111111111111").unwrap();
         create_anchor_account_info!(User::default(), &filler_key, User, user_account_info);
@@ -5372,10 +5934,21 @@ pub mod fill_order {
         let state = State {
             min_perp_auction_duration: 1,
             default_market_order_time_in_force: 10,
+            perp_fee_structure: FeeStructure::perps_default(),
             ..State::default()
         };
 
-        let (base_asset_amount, _) = fill_perp_order(
+        let mut referral_tier_config = ReferralTierConfig {
+            sub_affiliate_reward_numerator: 50,
+            ..ReferralTierConfig::default()
+        };
+        referral_tier_config.tiers[0] = ReferralTier {
+            referee_volume_30d_threshold: 100_000 * QUOTE_PRECISION_U64,
+            referrer_reward_numerator: 20,
+            referee_fee_numerator: 10,
+        };
+
+        let (base_asset_amount, quote_asset_amount) = fill_perp_order(
             1,
             &state,
             &user_account_loader,
@@ -5388,12 +5961,38 @@ pub mod fill_order {
             &makers_and_referrers,
             &maker_and_referrer_stats,
             None,
+            Some(&referral_tier_config),
+            None,
+            None,
+            None,
+            None,
             &clock,
             FillMode::Fill,
         )
         .unwrap();
 
         assert_eq!(base_asset_amount, 1000000000);
+
+        // referral tier discounts the 10bps taker fee by 10% and rewards the referrer 20% of it,
+        // half of which is accrued to the referrer's own referrer
+        let user_stats = user_stats_account_loader.load().unwrap();
+        assert_eq!(user_stats.fees.total_referee_discount, 10000);
+        assert_eq!(user_stats.fees.total_fee_paid, 90000);
+
+        let referrer_stats = maker_and_referrer_stats
+            .get_ref(&referrer_authority)
+            .unwrap();
+        assert_eq!(referrer_stats.fees.total_referrer_reward, 10000);
+        // prior volume decays for one second before the fill is added
+        assert_eq!(quote_asset_amount, 100 * QUOTE_PRECISION_U64);
+        assert_eq!(referrer_stats.referee_volume_30d, 1_000_099_614_197);
+
+        let parent_stats = maker_and_referrer_stats.get_ref(&parent_authority).unwrap();
+        assert_eq!(parent_stats.fees.total_referrer_reward, 10000);
+        assert_eq!(parent_stats.fees.unclaimed_sub_affiliate_reward, 10000);
+
+        let referrer = makers_and_referrers.get_ref(&referrer_key).unwrap();
+        assert_eq!(referrer.perp_positions[0].quote_asset_amount, 10000);
     }
 
     #[test]
@@ -5517,6 +6116,11 @@ pub mod fill_order {
             &UserMap::empty(),
             &UserStatsMap::empty(),
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
             &clock,
             FillMode::Fill,
         )
@@ -5679,6 +6283,11 @@ pub mod fill_order {
             &UserMap::empty(),
             &UserStatsMap::empty(),
             None,
+            None,
+            None,
+            None,
+            None,
+            None,
             &clock,
             FillMode::Fill,
         );
@@ -5796,6 +6405,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -5921,6 +6531,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -6046,6 +6657,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -6171,6 +6783,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -6296,6 +6909,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -6395,6 +7009,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -6494,6 +7109,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -6593,6 +7209,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -6692,6 +7309,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -6829,6 +7447,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -6968,6 +7587,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -7091,6 +7711,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -7214,6 +7835,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -7338,6 +7960,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -7435,6 +8058,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -7535,6 +8159,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -7632,6 +8257,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -7731,6 +8357,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -7830,6 +8457,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -7929,6 +8557,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -8026,6 +8655,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -8125,6 +8755,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -8242,6 +8873,7 @@ pub mod fulfill_spot_order_with_match {
             slot,
             &mut get_oracle_map(),
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
         )
         .unwrap();
 
@@ -8727,6 +9359,8 @@ pub mod fulfill_spot_order {
             &makers_and_referrers,
             &maker_and_referrer_stats,
             None,
+            None,
+            None,
             &clock,
             &mut TestFulfillmentParams {},
         )
@@ -9008,6 +9642,8 @@ pub mod fulfill_spot_order {
             &makers_and_referrers,
             &maker_and_referrer_stats,
             None,
+            None,
+            None,
             &clock,
             &mut TestFulfillmentParams {},
         )
@@ -9185,6 +9821,8 @@ pub mod fulfill_spot_order {
             &makers_and_referrers,
             &maker_and_referrer_stats,
             None,
+            None,
+            None,
             &clock,
             &mut TestFulfillmentParams {},
         );
@@ -9362,6 +10000,8 @@ pub mod fill_spot_order {
             &makers_and_referrers,
             &maker_and_referrer_stats,
             None,
+            None,
+            None,
             &clock,
             &mut TestFulfillmentParams {},
         )
@@ -9868,6 +10508,7 @@ pub mod get_maker_orders_info {
             &taker_key,
             &user.orders[0],
             &mut Some(&mut filler),
+            &mut None,
             &filler_key,
             0,
             oracle_price,
@@ -10057,6 +10698,7 @@ pub mod get_maker_orders_info {
             &taker_key,
             &user.orders[0],
             &mut Some(&mut filler),
+            &mut None,
             &filler_key,
             0,
             oracle_price,
@@ -10235,6 +10877,7 @@ pub mod get_maker_orders_info {
             &taker_key,
             &user.orders[0],
             &mut Some(&mut filler),
+            &mut None,
             &filler_key,
             0,
             oracle_price,
@@ -10476,6 +11119,7 @@ pub mod get_maker_orders_info {
             &taker_key,
             &user.orders[0],
             &mut Some(&mut filler),
+            &mut None,
             &filler_key,
             0,
             oracle_price,
@@ -10672,6 +11316,7 @@ pub mod get_maker_orders_info {
             &taker_key,
             &user.orders[0],
             &mut Some(&mut filler),
+            &mut None,
             &filler_key,
             0,
             oracle_price,
@@ -10890,6 +11535,7 @@ pub mod get_maker_orders_info {
             &taker_key,
             &user.orders[0],
             &mut Some(&mut filler),
+            &mut None,
             &filler_key,
             0,
             oracle_price,
@@ -12094,3 +12740,180 @@ mod update_maker_fills_map {
         assert_eq!(*map.get(&maker_key).unwrap(), -2 * fill as i64);
     }
 }
+
+mod update_fill_twap_oracle {
+    use crate::controller::orders::update_fill_twap_oracle;
+    use crate::create_anchor_account_info;
+    use crate::math::constants::{BASE_PRECISION_U64, PRICE_PRECISION_I64, QUOTE_PRECISION_U64};
+    use crate::state::load_ref::load_ref;
+    use crate::state::oracle::{OracleSource, PrelaunchOracle};
+    use crate::state::oracle_map::OracleMap;
+    use crate::state::perp_market::{PerpMarket, AMM};
+    use crate::test_utils::{create_account_info, get_anchor_account_bytes};
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::Owner;
+    use std::cell::Ref;
+
+    #[test]
+    fn skips_readonly_oracle() {
+        let oracle_key = Pubkey::new_unique();
+        let market = PerpMarket {
+            amm: AMM {
+                oracle: oracle_key,
+                oracle_source: OracleSource::FillTwap,
+                ..AMM::default()
+            },
+            ..PerpMarket::default()
+        };
+
+        let mut oracle = PrelaunchOracle {
+            price: 100 * PRICE_PRECISION_I64,
+            max_price: 1000 * PRICE_PRECISION_I64,
+            ..PrelaunchOracle::default()
+        };
+        let mut lamports = 0;
+        let mut data = get_anchor_account_bytes(&mut oracle);
+        let owner = PrelaunchOracle::owner();
+
+        // readonly oracle, fill doesn't fail and twap isn't updated
+        let oracle_account_info =
+            create_account_info(&oracle_key, false, &mut lamports, &mut data[..], &owner);
+        let oracle_map = OracleMap::load_one(&oracle_account_info, 0, None).unwrap();
+        update_fill_twap_oracle(
+            &market,
+            &oracle_map,
+            BASE_PRECISION_U64,
+            110 * QUOTE_PRECISION_U64,
+            0,
+        )
+        .unwrap();
+        drop(oracle_map);
+        {
+            let oracle: Ref<PrelaunchOracle> = load_ref(&oracle_account_info).unwrap();
+            assert_eq!(oracle.fill_vwap_volume, 0);
+        }
+
+        // writable oracle, twap is updated
+        let mut oracle = PrelaunchOracle {
+            price: 100 * PRICE_PRECISION_I64,
+            max_price: 1000 * PRICE_PRECISION_I64,
+            ..PrelaunchOracle::default()
+        };
+        create_anchor_account_info!(
+            oracle,
+            &oracle_key,
+            PrelaunchOracle,
+            writable_oracle_account_info
+        );
+        let oracle_map = OracleMap::load_one(&writable_oracle_account_info, 0, None).unwrap();
+        update_fill_twap_oracle(
+            &market,
+            &oracle_map,
+            BASE_PRECISION_U64,
+            110 * QUOTE_PRECISION_U64,
+            0,
+        )
+        .unwrap();
+        drop(oracle_map);
+        let oracle: Ref<PrelaunchOracle> = load_ref(&writable_oracle_account_info).unwrap();
+        assert_eq!(oracle.fill_vwap, 110 * PRICE_PRECISION_I64);
+        assert_eq!(oracle.fill_vwap_volume, 110 * QUOTE_PRECISION_U64);
+    }
+}
+
+mod update_referrer_after_fill {
+    use crate::controller::orders::update_referrer_after_fill;
+    use crate::create_anchor_account_info;
+    use crate::error::ErrorCode;
+    use crate::math::constants::QUOTE_PRECISION_U64;
+    use crate::state::perp_market::PerpMarket;
+    use crate::state::perp_market_map::PerpMarketMap;
+    use crate::state::state::ReferralTierConfig;
+    use crate::state::user::{PerpPosition, User, UserStats};
+    use crate::state::user_map::{UserMap, UserStatsMap};
+    use crate::test_utils::*;
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::Owner;
+
+    #[test]
+    fn requires_parent_stats() {
+        let mut market = PerpMarket::default();
+        create_anchor_account_info!(market, PerpMarket, market_account_info);
+        let market_map = PerpMarketMap::load_one(&market_account_info, true).unwrap();
+
+        let referrer_key = Pubkey::new_unique();
+        let referrer_authority = Pubkey::new_unique();
+        let parent_authority = Pubkey::new_unique();
+        let filler_authority = Pubkey::new_unique();
+
+        let mut referrer = User {
+            authority: referrer_authority,
+            perp_positions: get_positions(PerpPosition {
+                market_index: 0,
+                open_orders: 1,
+                ..PerpPosition::default()
+            }),
+            ..User::default()
+        };
+        create_anchor_account_info!(referrer, &referrer_key, User, referrer_account_info);
+        let referrer_map = UserMap::load_one(&referrer_account_info).unwrap();
+
+        let mut referrer_stats = UserStats {
+            authority: referrer_authority,
+            referrer: parent_authority,
+            ..UserStats::default()
+        };
+        referrer_stats.fees.total_referrer_reward = 20000;
+        create_anchor_account_info!(referrer_stats, UserStats, referrer_stats_account_info);
+        let referrer_stats_map = UserStatsMap::load_one(&referrer_stats_account_info).unwrap();
+
+        let referral_tier_config = ReferralTierConfig {
+            sub_affiliate_reward_numerator: 50,
+            ..ReferralTierConfig::default()
+        };
+
+        let mut filler_stats = UserStats {
+            authority: filler_authority,
+            ..UserStats::default()
+        };
+
+        // parent is owed a share and its stats weren't passed
+        let result = update_referrer_after_fill(
+            &Some((referrer_authority, referrer_key)),
+            0,
+            100 * QUOTE_PRECISION_U64,
+            &Pubkey::new_unique(),
+            &referrer_map,
+            &referrer_stats_map,
+            Some(&mut filler_stats),
+            Some(&referral_tier_config),
+            &market_map,
+            0,
+            u64::MAX,
+            0,
+        );
+        assert_eq!(result, Err(ErrorCode::ReferrerStatsNotFound));
+
+        // parent is the filler
+        filler_stats.authority = parent_authority;
+        update_referrer_after_fill(
+            &Some((referrer_authority, referrer_key)),
+            0,
+            100 * QUOTE_PRECISION_U64,
+            &Pubkey::new_unique(),
+            &referrer_map,
+            &referrer_stats_map,
+            Some(&mut filler_stats),
+            Some(&referral_tier_config),
+            &market_map,
+            0,
+            u64::MAX,
+            0,
+        )
+        .unwrap();
+        assert_eq!(filler_stats.fees.unclaimed_sub_affiliate_reward, 10000);
+
+        let market = market_map.get_ref(&0).unwrap();
+        assert_eq!(market.amm.total_fee_minus_distributions, 10000);
+    }
+}
@@ -35,7 +35,7 @@ use crate::state::settle_pnl_mode::SettlePnlMode;
 use crate::state::spot_market::{SpotBalance, SpotBalanceType};
 use crate::state::spot_market_map::SpotMarketMap;
 use crate::state::state::State;
-use crate::state::user::{MarketType, User};
+use crate::state::user::{MarketType, User, UserStats};
 use crate::validate;
 use anchor_lang::prelude::Pubkey;
 use anchor_lang::prelude::*;
@@ -52,6 +52,7 @@ pub fn settle_pnl(
     market_index: u16,
     user: &mut User,
     authority: &Pubkey,
+    keeper_stats: Option<&mut UserStats>,
     user_key: &Pubkey,
     perp_market_map: &PerpMarketMap,
     spot_market_map: &SpotMarketMap,
@@ -164,6 +165,7 @@ pub fn settle_pnl(
                     .amm
                     .historical_oracle_data
                     .last_oracle_price_twap,
+                &perp_market.oracle_validity_guard_rails,
                 perp_market.get_max_confidence_interval_multiplier()?,
             )?;
 
@@ -328,6 +330,18 @@ pub fn settle_pnl(
 
     update_settled_pnl(user, position_index, pnl_to_settle_with_user.cast()?)?;
 
+    // settling pnl for another user is a keeper action. the fuel scales with the pnl settled so
+    // repeatedly settling dust doesn't earn a full filler fee worth each time
+    if let Some(keeper_stats) = keeper_stats {
+        keeper_stats.update_fuel_keeper_bonus(
+            perp_market.fuel_boost_keeper,
+            pnl_to_settle_with_user
+                .unsigned_abs()
+                .min(state.perp_fee_structure.flat_filler_fee.cast()?)
+                .cast()?,
+        )?;
+    }
+
     let quote_asset_amount_after = user.perp_positions[position_index].quote_asset_amount;
     let quote_entry_amount = user.perp_positions[position_index].quote_entry_amount;
 
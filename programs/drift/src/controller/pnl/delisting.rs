@@ -2703,6 +2703,7 @@ pub mod delisting_test {
                 &mut oracle_map,
                 clock.unix_timestamp,
                 0,
+                0,
             )
             .unwrap();
 
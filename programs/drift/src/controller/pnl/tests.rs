@@ -22,7 +22,7 @@ use crate::state::perp_market_map::PerpMarketMap;
 use crate::state::spot_market::{SpotBalanceType, SpotMarket};
 use crate::state::spot_market_map::SpotMarketMap;
 use crate::state::state::{OracleGuardRails, State, ValidityGuardRails};
-use crate::state::user::{PerpPosition, SpotPosition, User};
+use crate::state::user::{PerpPosition, SpotPosition, User, UserStats};
 use crate::test_utils::*;
 use crate::test_utils::{get_positions, get_pyth_price, get_spot_positions};
 use crate::{create_account_info, SettlePnlMode};
@@ -134,6 +134,7 @@ pub fn user_no_position() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -259,6 +260,7 @@ pub fn user_does_not_meet_maintenance_requirement() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -387,6 +389,7 @@ pub fn user_does_not_meet_strict_maintenance_requirement() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -425,7 +428,7 @@ pub fn user_unsettled_negative_pnl() {
         leader_schedule_epoch: 0,
         unix_timestamp: 0,
     };
-    let state = State {
+    let mut state = State {
         oracle_guard_rails: OracleGuardRails {
             validity: ValidityGuardRails {
                 slots_before_stale_for_amm: 10,     // 5s
@@ -437,6 +440,8 @@ pub fn user_unsettled_negative_pnl() {
         },
         ..State::default()
     };
+    // more than the pnl settled, so the keeper fuel is scaled by the pnl instead
+    state.perp_fee_structure.flat_filler_fee = 100_000_000;
     let mut oracle_price = get_pyth_price(100, 6);
     let oracle_price_key =
         Pubkey::from_str("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix").unwrap();
@@ -486,6 +491,7 @@ pub fn user_unsettled_negative_pnl() {
             ..PoolBalance::default()
         },
         unrealized_pnl_maintenance_asset_weight: SPOT_WEIGHT_PRECISION.cast().unwrap(),
+        fuel_boost_keeper: 10,
         ..PerpMarket::default()
     };
     create_anchor_account_info!(market, PerpMarket, market_account_info);
@@ -522,6 +528,7 @@ pub fn user_unsettled_negative_pnl() {
 
     let user_key = Pubkey::default();
     let authority = Pubkey::default();
+    let mut keeper_stats = UserStats::default();
 
     let mut expected_user = user;
     expected_user.perp_positions[0].quote_asset_amount = 0;
@@ -538,6 +545,7 @@ pub fn user_unsettled_negative_pnl() {
         0,
         &mut user,
         &authority,
+        Some(&mut keeper_stats),
         &user_key,
         &market_map,
         &spot_market_map,
@@ -551,6 +559,8 @@ pub fn user_unsettled_negative_pnl() {
 
     assert_eq!(expected_user, user);
     assert_eq!(expected_market, *market_map.get_ref(&0).unwrap());
+    // settled $50 for another user, keeper earns fuel as if paid $50 rather than the flat filler fee
+    assert_eq!(keeper_stats.fuel_keeper, 50_000);
 }
 
 #[test]
@@ -673,6 +683,7 @@ pub fn user_unsettled_positive_pnl_more_than_pool() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -810,6 +821,7 @@ pub fn user_unsettled_positive_pnl_less_than_pool() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -950,6 +962,7 @@ pub fn market_fee_pool_receives_portion() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -1094,6 +1107,7 @@ pub fn market_fee_pool_pays_back_to_pnl_pool() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -1232,6 +1246,7 @@ pub fn user_long_positive_unrealized_pnl_up_to_max_positive_pnl() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -1370,6 +1385,7 @@ pub fn user_long_positive_unrealized_pnl_up_to_max_positive_pnl_price_breached()
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -1505,6 +1521,7 @@ pub fn user_long_negative_unrealized_pnl() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -1643,6 +1660,7 @@ pub fn user_short_positive_unrealized_pnl_up_to_max_positive_pnl() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -1781,6 +1799,7 @@ pub fn user_short_negative_unrealized_pnl() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -1922,6 +1941,7 @@ pub fn user_invalid_oracle_position() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -1948,6 +1968,7 @@ pub fn user_invalid_oracle_position() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -1974,6 +1995,7 @@ pub fn user_invalid_oracle_position() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -1999,6 +2021,7 @@ pub fn user_invalid_oracle_position() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
@@ -2024,6 +2047,7 @@ pub fn user_invalid_oracle_position() {
         0,
         &mut user,
         &authority,
+        None,
         &user_key,
         &market_map,
         &spot_market_map,
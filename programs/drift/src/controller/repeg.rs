@@ -5,6 +5,7 @@ use anchor_lang::prelude::*;
 use solana_program::msg;
 
 use crate::controller::amm::update_spreads;
+use crate::controller::funding::update_premium_index;
 use crate::controller::spot_balance::update_spot_balances;
 use crate::error::ErrorCode;
 use crate::error::*;
@@ -24,6 +25,7 @@ use crate::math::repeg;
 use crate::math::safe_math::SafeMath;
 use crate::math::spot_balance::get_token_amount;
 
+use crate::state::events::OracleCircuitBreakerRecord;
 use crate::state::oracle::OraclePriceData;
 use crate::state::oracle_map::OracleMap;
 use crate::state::perp_market::{MarketStatus, PerpMarket};
@@ -157,6 +159,7 @@ pub fn _update_amm(
         market.amm.historical_oracle_data.last_oracle_price_twap,
         oracle_price_data,
         &state.oracle_guard_rails.validity,
+        &market.oracle_validity_guard_rails,
         market.get_max_confidence_interval_multiplier()?,
         true,
     )?;
@@ -199,9 +202,37 @@ pub fn _update_amm(
 
     let reserve_price_after = market.amm.reserve_price()?;
 
+    if let Some(tripped) = market.oracle_circuit_breaker.update(
+        oracle_price_data.price,
+        market
+            .amm
+            .historical_oracle_data
+            .last_oracle_price_twap_5min,
+        now,
+    )? {
+        msg!(
+            "perp market {} oracle circuit breaker tripped = {}",
+            market.market_index,
+            tripped
+        );
+        emit!(OracleCircuitBreakerRecord {
+            ts: now,
+            market_type: MarketType::Perp,
+            market_index: market.market_index,
+            tripped,
+            oracle_price: oracle_price_data.price,
+            reference_price: market
+                .amm
+                .historical_oracle_data
+                .last_oracle_price_twap_5min,
+        });
+    }
+
     if is_oracle_valid_for_action(oracle_validity, Some(DriftAction::UpdateTwap))? {
         let sanitize_clamp_denominator = market.get_sanitize_clamp_denominator()?;
 
+        update_premium_index(market, oracle_price_data.price, reserve_price_after, now)?;
+
         amm::update_oracle_price_twap(
             &mut market.amm,
             now,
@@ -244,6 +275,7 @@ pub fn update_amm_and_check_validity(
         risk_ema_price,
         oracle_price_data,
         &state.oracle_guard_rails.validity,
+        &market.oracle_validity_guard_rails,
         market.get_max_confidence_interval_multiplier()?,
         false,
     )?;
@@ -20,7 +20,7 @@ use crate::math::stats::{calculate_new_twap, calculate_weighted_average};
 
 use crate::math::oracle::{is_oracle_valid_for_action, DriftAction};
 use crate::math::safe_math::SafeMath;
-use crate::state::events::SpotInterestRecord;
+use crate::state::events::{OracleCircuitBreakerRecord, SpotInterestRecord};
 use crate::state::oracle::OraclePriceData;
 use crate::state::paused_operations::SpotOperation;
 use crate::state::spot_market::{SpotBalance, SpotBalanceType, SpotMarket};
@@ -77,6 +77,30 @@ pub fn update_spot_market_twap_stats(
     .cast()?;
 
     if let Some(oracle_price_data) = oracle_price_data {
+        if let Some(tripped) = spot_market.oracle_circuit_breaker.update(
+            oracle_price_data.price,
+            spot_market
+                .historical_oracle_data
+                .last_oracle_price_twap_5min,
+            now,
+        )? {
+            msg!(
+                "spot market {} oracle circuit breaker tripped = {}",
+                spot_market.market_index,
+                tripped
+            );
+            emit!(OracleCircuitBreakerRecord {
+                ts: now,
+                market_type: MarketType::Spot,
+                market_index: spot_market.market_index,
+                tripped,
+                oracle_price: oracle_price_data.price,
+                reference_price: spot_market
+                    .historical_oracle_data
+                    .last_oracle_price_twap_5min,
+            });
+        }
+
         let sanitize_clamp_denominator = spot_market.get_sanitize_clamp_denominator()?;
 
         let capped_oracle_update_price: i64 = sanitize_new_price(
@@ -132,6 +156,7 @@ pub fn update_spot_market_cumulative_interest(
     let InterestAccumulated {
         deposit_interest,
         borrow_interest,
+        borrow_rate,
     } = calculate_accumulated_interest(spot_market, now)?;
 
     if deposit_interest > 0 && borrow_interest > 1 {
@@ -172,6 +197,7 @@ pub fn update_spot_market_cumulative_interest(
                 optimal_utilization: spot_market.optimal_utilization,
                 optimal_borrow_rate: spot_market.optimal_borrow_rate,
                 max_borrow_rate: spot_market.max_borrow_rate,
+                borrow_rate: borrow_rate.cast()?,
             });
         }
     }
@@ -401,6 +427,7 @@ pub fn update_spot_market_and_check_validity(
         risk_ema_price,
         oracle_price_data,
         validity_guard_rails,
+        &spot_market.oracle_validity_guard_rails,
         spot_market.get_max_confidence_interval_multiplier()?,
         false,
     )?;
@@ -18,6 +18,7 @@ use crate::math::constants::{
 use crate::math::margin::{
     calculate_margin_requirement_and_total_collateral_and_liability_info, MarginRequirementType,
 };
+use crate::math::spot_balance::calculate_borrow_rate;
 use crate::math::spot_withdraw::{
     calculate_max_borrow_token_amount, calculate_min_deposit_token_amount,
     calculate_token_utilization_limits, check_withdraw_limits,
@@ -28,11 +29,12 @@ use crate::state::oracle::{HistoricalOracleData, OracleSource};
 use crate::state::oracle_map::OracleMap;
 use crate::state::perp_market::{MarketStatus, PerpMarket, AMM};
 use crate::state::perp_market_map::PerpMarketMap;
-use crate::state::spot_market::{InsuranceFund, SpotBalanceType, SpotMarket};
+use crate::state::spot_market::{BorrowRateKink, InsuranceFund, SpotBalanceType, SpotMarket};
 use crate::state::spot_market_map::SpotMarketMap;
 use crate::state::user::{Order, PerpPosition, SpotPosition, User};
 use crate::test_utils::*;
 use crate::test_utils::{get_pyth_price, get_spot_positions};
+use crate::validation::spot_market::validate_borrow_rate_kinks;
 
 pub fn check_perp_market_valid(
     perp_market: &PerpMarket,
@@ -1816,3 +1818,83 @@ fn check_spot_market_min_borrow_rate() {
     assert_eq!(accum_interest.borrow_interest, 317107433);
     assert_eq!(accum_interest.deposit_interest, 3171074);
 }
+
+#[test]
+fn check_spot_market_borrow_rate_kinks() {
+    let mut spot_market = SpotMarket {
+        optimal_utilization: SPOT_UTILIZATION_PRECISION_U32 * 8 / 10, // 80%
+        optimal_borrow_rate: SPOT_RATE_PRECISION_U32 / 20,            // 5%
+        max_borrow_rate: SPOT_RATE_PRECISION_U32,                     // 100%
+        ..SpotMarket::default()
+    };
+
+    // no kinks uses the single kink model
+    assert!(!spot_market.has_borrow_rate_kinks());
+    let rate = calculate_borrow_rate(&spot_market, SPOT_UTILIZATION_PRECISION * 9 / 10).unwrap();
+    assert_eq!(rate, 525000); // 52.5%
+
+    // gentle slope until 80%, then steep ramps at 90% and 95%
+    spot_market.borrow_rate_kinks = [
+        BorrowRateKink {
+            utilization: 8000,
+            borrow_rate: 500, // 5%
+        },
+        BorrowRateKink {
+            utilization: 9000,
+            borrow_rate: 1500, // 15%
+        },
+        BorrowRateKink {
+            utilization: 9500,
+            borrow_rate: 5000, // 50%
+        },
+        BorrowRateKink::default(),
+    ];
+    assert!(validate_borrow_rate_kinks(&spot_market.borrow_rate_kinks).is_ok());
+    assert!(spot_market.has_borrow_rate_kinks());
+
+    let rate = calculate_borrow_rate(&spot_market, SPOT_UTILIZATION_PRECISION * 4 / 10).unwrap();
+    assert_eq!(rate, 25000); // 2.5%
+
+    let rate = calculate_borrow_rate(&spot_market, SPOT_UTILIZATION_PRECISION * 8 / 10).unwrap();
+    assert_eq!(rate, 50000); // 5%
+
+    let rate = calculate_borrow_rate(&spot_market, SPOT_UTILIZATION_PRECISION * 85 / 100).unwrap();
+    assert_eq!(rate, 100000); // 10%
+
+    let rate =
+        calculate_borrow_rate(&spot_market, SPOT_UTILIZATION_PRECISION * 925 / 1000).unwrap();
+    assert_eq!(rate, 325000); // 32.5%
+
+    let rate =
+        calculate_borrow_rate(&spot_market, SPOT_UTILIZATION_PRECISION * 975 / 1000).unwrap();
+    assert_eq!(rate, 750000); // 75%
+
+    let rate = calculate_borrow_rate(&spot_market, SPOT_UTILIZATION_PRECISION).unwrap();
+    assert_eq!(rate, 1000000); // 100%
+
+    // min borrow rate still applies
+    spot_market.min_borrow_rate = 10; // 5%
+    let rate = calculate_borrow_rate(&spot_market, SPOT_UTILIZATION_PRECISION / 10).unwrap();
+    assert_eq!(rate, 50000);
+
+    // kinks must be increasing and contiguous
+    let mut invalid_kinks = spot_market.borrow_rate_kinks;
+    invalid_kinks[1].utilization = 7000;
+    assert!(validate_borrow_rate_kinks(&invalid_kinks).is_err());
+
+    let mut invalid_kinks = spot_market.borrow_rate_kinks;
+    invalid_kinks[2] = BorrowRateKink::default();
+    invalid_kinks[3] = spot_market.borrow_rate_kinks[2];
+    assert!(validate_borrow_rate_kinks(&invalid_kinks).is_err());
+
+    let mut invalid_kinks = spot_market.borrow_rate_kinks;
+    invalid_kinks[2].borrow_rate = 1000;
+    assert!(validate_borrow_rate_kinks(&invalid_kinks).is_err());
+
+    let mut invalid_kinks = spot_market.borrow_rate_kinks;
+    invalid_kinks[3] = BorrowRateKink {
+        utilization: 10000,
+        borrow_rate: 10000,
+    };
+    assert!(validate_borrow_rate_kinks(&invalid_kinks).is_err());
+}
@@ -0,0 +1,212 @@
+use anchor_lang::prelude::Pubkey;
+use solana_program::msg;
+
+use crate::controller::spot_balance::update_spot_balances;
+use crate::error::{DriftResult, ErrorCode};
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::state::spot_market::{SpotBalance, SpotBalanceType, SpotMarket};
+use crate::state::term_loan::{TermLoan, TermLoanStatus};
+use crate::state::user::{SpotPosition, User};
+use crate::validate;
+
+#[cfg(test)]
+mod tests;
+
+pub fn take_term_loan_offer(
+    term_loan: &mut TermLoan,
+    borrower_key: Pubkey,
+    lender: &mut User,
+    borrower: &mut User,
+    spot_market: &mut SpotMarket,
+    now: i64,
+) -> DriftResult {
+    validate!(
+        term_loan.is_offered(),
+        ErrorCode::InvalidTermLoan,
+        "term loan status {:?} cant be taken",
+        term_loan.status
+    )?;
+
+    validate!(
+        !term_loan.is_matured(now),
+        ErrorCode::InvalidTermLoan,
+        "term loan offer matured at {}",
+        term_loan.maturity_ts
+    )?;
+
+    validate!(
+        spot_market.market_index == term_loan.market_index,
+        ErrorCode::InvalidTermLoan,
+        "spot market {} does not match term loan market {}",
+        spot_market.market_index,
+        term_loan.market_index
+    )?;
+
+    let principal = term_loan.principal.cast::<u128>()?;
+    let repay_amount = term_loan.calculate_repay_amount(now)?;
+    let maturity_ts = term_loan.maturity_ts.cast::<u32>()?;
+
+    {
+        let lender_spot_position = lender.get_spot_position_mut(spot_market.market_index)?;
+
+        validate!(
+            lender_spot_position.balance_type == SpotBalanceType::Deposit
+                && lender_spot_position.get_token_amount(spot_market)? >= principal,
+            ErrorCode::InsufficientDeposit,
+            "lender deposit insufficient to fund term loan principal {}",
+            principal
+        )?;
+
+        update_spot_balances(
+            principal,
+            &SpotBalanceType::Borrow,
+            spot_market,
+            lender_spot_position,
+            false,
+        )?;
+    }
+
+    let lender_term_position_index = lender.force_get_term_spot_position_index(
+        spot_market.market_index,
+        maturity_ts,
+        SpotBalanceType::Deposit,
+    )?;
+    lender.spot_positions[lender_term_position_index].increase_balance(repay_amount.cast()?)?;
+
+    {
+        let borrower_spot_position_index =
+            borrower.force_get_spot_position_index(spot_market.market_index)?;
+
+        update_spot_balances(
+            principal,
+            &SpotBalanceType::Deposit,
+            spot_market,
+            &mut borrower.spot_positions[borrower_spot_position_index],
+            false,
+        )?;
+    }
+
+    let borrower_term_position_index = borrower.force_get_term_spot_position_index(
+        spot_market.market_index,
+        maturity_ts,
+        SpotBalanceType::Borrow,
+    )?;
+    borrower.spot_positions[borrower_term_position_index].increase_balance(repay_amount.cast()?)?;
+
+    term_loan.borrower = borrower_key;
+    term_loan.repay_amount = repay_amount;
+    term_loan.start_ts = now;
+    term_loan.status = TermLoanStatus::Active;
+
+    Ok(())
+}
+
+/// Closes out both fixed-term positions and moves the repay amount from the borrower's floating
+/// balance to the lender's. If the borrower's deposits don't cover the repay amount, the shortfall
+/// becomes a floating borrow that can be liquidated with liquidate_spot.
+/// Returns the shortfall.
+pub fn settle_term_loan(
+    term_loan: &TermLoan,
+    lender: &mut User,
+    borrower: &mut User,
+    spot_market: &mut SpotMarket,
+) -> DriftResult<u64> {
+    validate!(
+        term_loan.is_active(),
+        ErrorCode::InvalidTermLoan,
+        "term loan status {:?} cant be settled",
+        term_loan.status
+    )?;
+
+    validate!(
+        spot_market.market_index == term_loan.market_index,
+        ErrorCode::InvalidTermLoan,
+        "spot market {} does not match term loan market {}",
+        spot_market.market_index,
+        term_loan.market_index
+    )?;
+
+    let repay_amount = term_loan.repay_amount;
+    let maturity_ts = term_loan.maturity_ts.cast::<u32>()?;
+
+    decrease_term_spot_position(
+        borrower,
+        spot_market.market_index,
+        maturity_ts,
+        SpotBalanceType::Borrow,
+        repay_amount,
+    )?;
+
+    decrease_term_spot_position(
+        lender,
+        spot_market.market_index,
+        maturity_ts,
+        SpotBalanceType::Deposit,
+        repay_amount,
+    )?;
+
+    let shortfall = {
+        let borrower_spot_position_index =
+            borrower.force_get_spot_position_index(spot_market.market_index)?;
+        let borrower_spot_position = &mut borrower.spot_positions[borrower_spot_position_index];
+
+        let deposit_amount = match borrower_spot_position.balance_type {
+            SpotBalanceType::Deposit => borrower_spot_position.get_token_amount(spot_market)?,
+            SpotBalanceType::Borrow => 0,
+        };
+
+        update_spot_balances(
+            repay_amount.cast()?,
+            &SpotBalanceType::Borrow,
+            spot_market,
+            borrower_spot_position,
+            false,
+        )?;
+
+        repay_amount.cast::<u128>()?.saturating_sub(deposit_amount)
+    };
+
+    {
+        let lender_spot_position_index =
+            lender.force_get_spot_position_index(spot_market.market_index)?;
+
+        update_spot_balances(
+            repay_amount.cast()?,
+            &SpotBalanceType::Deposit,
+            spot_market,
+            &mut lender.spot_positions[lender_spot_position_index],
+            false,
+        )?;
+    }
+
+    if shortfall > 0 {
+        msg!(
+            "borrower defaulted on {} of term loan repay amount {}",
+            shortfall,
+            repay_amount
+        );
+    }
+
+    shortfall.cast()
+}
+
+fn decrease_term_spot_position(
+    user: &mut User,
+    market_index: u16,
+    maturity_ts: u32,
+    balance_type: SpotBalanceType,
+    amount: u64,
+) -> DriftResult {
+    let position_index =
+        user.get_term_spot_position_index(market_index, maturity_ts, balance_type)?;
+    let spot_position = &mut user.spot_positions[position_index];
+
+    spot_position.scaled_balance = spot_position.scaled_balance.safe_sub(amount)?;
+
+    if spot_position.scaled_balance == 0 {
+        *spot_position = SpotPosition::default();
+    }
+
+    Ok(())
+}
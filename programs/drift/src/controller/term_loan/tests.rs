@@ -0,0 +1,183 @@
+mod take_and_settle_term_loan {
+    use anchor_lang::prelude::Pubkey;
+
+    use crate::controller::spot_balance::update_spot_balances;
+    use crate::controller::term_loan::{settle_term_loan, take_term_loan_offer};
+    use crate::math::constants::{
+        ONE_YEAR, QUOTE_PRECISION, QUOTE_PRECISION_U64, SPOT_RATE_PRECISION_U32,
+    };
+    use crate::state::spot_market::{SpotBalanceType, SpotMarket};
+    use crate::state::term_loan::{TermLoan, TermLoanStatus};
+    use crate::state::user::User;
+
+    fn setup() -> (TermLoan, User, User, SpotMarket) {
+        let mut spot_market = SpotMarket::default_quote_market();
+        let mut lender = User::default();
+        let borrower = User::default();
+
+        update_spot_balances(
+            2000 * QUOTE_PRECISION,
+            &SpotBalanceType::Deposit,
+            &mut spot_market,
+            lender.get_quote_spot_position_mut(),
+            false,
+        )
+        .unwrap();
+
+        let term_loan = TermLoan {
+            principal: 1000 * QUOTE_PRECISION_U64,
+            rate: SPOT_RATE_PRECISION_U32 / 10, // 10%
+            maturity_ts: ONE_YEAR as i64,
+            ..TermLoan::default()
+        };
+
+        (term_loan, lender, borrower, spot_market)
+    }
+
+    #[test]
+    fn repaid() {
+        let (mut term_loan, mut lender, mut borrower, mut spot_market) = setup();
+        let borrower_key = Pubkey::new_unique();
+
+        take_term_loan_offer(
+            &mut term_loan,
+            borrower_key,
+            &mut lender,
+            &mut borrower,
+            &mut spot_market,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(term_loan.status, TermLoanStatus::Active);
+        assert_eq!(term_loan.borrower, borrower_key);
+        assert_eq!(term_loan.repay_amount, 1100 * QUOTE_PRECISION_U64);
+
+        // principal moves between floating balances
+        assert_eq!(
+            lender
+                .get_quote_spot_position()
+                .get_signed_token_amount(&spot_market)
+                .unwrap(),
+            1000 * QUOTE_PRECISION as i128
+        );
+        assert_eq!(
+            borrower
+                .get_quote_spot_position()
+                .get_signed_token_amount(&spot_market)
+                .unwrap(),
+            1000 * QUOTE_PRECISION as i128
+        );
+
+        // term positions hold the repay amount and are skipped by floating lookups
+        let maturity_ts = ONE_YEAR as u32;
+        let lender_term_index = lender
+            .get_term_spot_position_index(0, maturity_ts, SpotBalanceType::Deposit)
+            .unwrap();
+        assert_eq!(
+            lender.spot_positions[lender_term_index]
+                .get_signed_token_amount(&spot_market)
+                .unwrap(),
+            1100 * QUOTE_PRECISION as i128
+        );
+        let borrower_term_index = borrower
+            .get_term_spot_position_index(0, maturity_ts, SpotBalanceType::Borrow)
+            .unwrap();
+        assert_eq!(
+            borrower.spot_positions[borrower_term_index]
+                .get_signed_token_amount(&spot_market)
+                .unwrap(),
+            -1100 * QUOTE_PRECISION as i128
+        );
+
+        // borrower adds enough to repay
+        update_spot_balances(
+            100 * QUOTE_PRECISION,
+            &SpotBalanceType::Deposit,
+            &mut spot_market,
+            borrower.get_quote_spot_position_mut(),
+            false,
+        )
+        .unwrap();
+
+        let shortfall =
+            settle_term_loan(&term_loan, &mut lender, &mut borrower, &mut spot_market).unwrap();
+        assert_eq!(shortfall, 0);
+
+        assert!(borrower.spot_positions[borrower_term_index].is_available());
+        assert!(lender.spot_positions[lender_term_index].is_available());
+        assert_eq!(
+            borrower
+                .get_quote_spot_position()
+                .get_signed_token_amount(&spot_market)
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            lender
+                .get_quote_spot_position()
+                .get_signed_token_amount(&spot_market)
+                .unwrap(),
+            2100 * QUOTE_PRECISION as i128
+        );
+    }
+
+    #[test]
+    fn default_becomes_floating_borrow() {
+        let (mut term_loan, mut lender, mut borrower, mut spot_market) = setup();
+
+        take_term_loan_offer(
+            &mut term_loan,
+            Pubkey::new_unique(),
+            &mut lender,
+            &mut borrower,
+            &mut spot_market,
+            0,
+        )
+        .unwrap();
+
+        let shortfall =
+            settle_term_loan(&term_loan, &mut lender, &mut borrower, &mut spot_market).unwrap();
+        assert_eq!(shortfall, 100 * QUOTE_PRECISION_U64);
+
+        let borrower_spot_position = borrower.get_quote_spot_position();
+        assert_eq!(borrower_spot_position.balance_type, SpotBalanceType::Borrow);
+        assert_eq!(
+            borrower_spot_position
+                .get_signed_token_amount(&spot_market)
+                .unwrap(),
+            -100 * QUOTE_PRECISION as i128 - 1 // borrow balance rounds up
+        );
+    }
+
+    #[test]
+    fn insufficient_lender_deposit() {
+        let (mut term_loan, mut lender, mut borrower, mut spot_market) = setup();
+        term_loan.principal = 3000 * QUOTE_PRECISION_U64;
+
+        assert!(take_term_loan_offer(
+            &mut term_loan,
+            Pubkey::new_unique(),
+            &mut lender,
+            &mut borrower,
+            &mut spot_market,
+            0,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn cant_take_matured_offer() {
+        let (mut term_loan, mut lender, mut borrower, mut spot_market) = setup();
+
+        assert!(take_term_loan_offer(
+            &mut term_loan,
+            Pubkey::new_unique(),
+            &mut lender,
+            &mut borrower,
+            &mut spot_market,
+            ONE_YEAR as i64,
+        )
+        .is_err());
+    }
+}
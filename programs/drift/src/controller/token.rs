@@ -2,14 +2,18 @@ use crate::error::ErrorCode;
 use crate::signer::get_signer_seeds;
 use crate::validate;
 use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022;
 use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_hook::TransferHook;
 use anchor_spl::token_2022::spl_token_2022::extension::{
     BaseStateWithExtensions, StateWithExtensions,
 };
 use anchor_spl::token_2022::spl_token_2022::state::Mint as MintInner;
 use anchor_spl::token_interface::{
-    self, CloseAccount, Mint, TokenAccount, TokenInterface, Transfer, TransferChecked,
+    self, Burn, CloseAccount, Mint, MintTo, TokenAccount, TokenInterface, Transfer, TransferChecked,
 };
+use std::iter::Peekable;
+use std::slice::Iter;
 
 pub fn send_from_program_vault<'info>(
     token_program: &Interface<'info, TokenInterface>,
@@ -19,6 +23,7 @@ pub fn send_from_program_vault<'info>(
     nonce: u8,
     amount: u64,
     mint: &Option<InterfaceAccount<'info, Mint>>,
+    remaining_accounts: Option<&mut Peekable<Iter<'info, AccountInfo<'info>>>>,
 ) -> Result<()> {
     let signature_seeds = get_signer_seeds(&nonce);
     let signers = &[&signature_seeds[..]];
@@ -26,7 +31,31 @@ pub fn send_from_program_vault<'info>(
     if let Some(mint) = mint {
         let mint_account_info = mint.to_account_info();
 
-        validate_mint_fee(&mint_account_info)?;
+        // transfers without remaining accounts are program-internal: they move exact amounts and
+        // can't pass transfer hook accounts
+        if remaining_accounts.is_none() {
+            validate_mint_fee(&mint_account_info)?;
+        }
+
+        if has_transfer_hook(&mint_account_info)? {
+            let additional_accounts: Vec<AccountInfo<'info>> = remaining_accounts
+                .map(|accounts| accounts.cloned().collect())
+                .unwrap_or_default();
+
+            spl_token_2022::onchain::invoke_transfer_checked(
+                token_program.key,
+                from.to_account_info(),
+                mint_account_info,
+                to.to_account_info(),
+                authority.to_account_info(),
+                &additional_accounts,
+                amount,
+                mint.decimals,
+                signers,
+            )?;
+
+            return Ok(());
+        }
 
         let cpi_accounts = TransferChecked {
             from: from.to_account_info(),
@@ -59,11 +88,36 @@ pub fn receive<'info>(
     authority: &AccountInfo<'info>,
     amount: u64,
     mint: &Option<InterfaceAccount<'info, Mint>>,
+    remaining_accounts: Option<&mut Peekable<Iter<'info, AccountInfo<'info>>>>,
 ) -> Result<()> {
     if let Some(mint) = mint {
         let mint_account_info = mint.to_account_info();
 
-        validate_mint_fee(&mint_account_info)?;
+        // transfers without remaining accounts are program-internal: they move exact amounts and
+        // can't pass transfer hook accounts
+        if remaining_accounts.is_none() {
+            validate_mint_fee(&mint_account_info)?;
+        }
+
+        if has_transfer_hook(&mint_account_info)? {
+            let additional_accounts: Vec<AccountInfo<'info>> = remaining_accounts
+                .map(|accounts| accounts.cloned().collect())
+                .unwrap_or_default();
+
+            spl_token_2022::onchain::invoke_transfer_checked(
+                token_program.key,
+                from.to_account_info(),
+                mint_account_info,
+                to.to_account_info(),
+                authority.to_account_info(),
+                &additional_accounts,
+                amount,
+                mint.decimals,
+                &[],
+            )?;
+
+            return Ok(());
+        }
 
         let cpi_accounts = TransferChecked {
             from: from.to_account_info(),
@@ -87,6 +141,43 @@ pub fn receive<'info>(
     }
 }
 
+pub fn mint_tokens<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    authority: &AccountInfo<'info>,
+    nonce: u8,
+    amount: u64,
+) -> Result<()> {
+    let signature_seeds = get_signer_seeds(&nonce);
+    let signers = &[&signature_seeds[..]];
+    let cpi_accounts = MintTo {
+        mint: mint.to_account_info(),
+        to: to.to_account_info(),
+        authority: authority.to_account_info(),
+    };
+    let cpi_program = token_program.to_account_info();
+    let cpi_context = CpiContext::new_with_signer(cpi_program, cpi_accounts, signers);
+    token_interface::mint_to(cpi_context, amount)
+}
+
+pub fn burn_tokens<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, Mint>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let cpi_accounts = Burn {
+        mint: mint.to_account_info(),
+        from: from.to_account_info(),
+        authority: authority.to_account_info(),
+    };
+    let cpi_program = token_program.to_account_info();
+    let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
+    token_interface::burn(cpi_context, amount)
+}
+
 pub fn close_vault<'info>(
     token_program: &Interface<'info, TokenInterface>,
     account: &InterfaceAccount<'info, TokenAccount>,
@@ -106,6 +197,29 @@ pub fn close_vault<'info>(
     token_interface::close_account(cpi_context)
 }
 
+/// The fee withheld by a token-2022 transfer fee extension when transferring amount.
+/// The destination account receives amount minus the fee
+pub fn calculate_transfer_fee(
+    mint: &Option<InterfaceAccount<'_, Mint>>,
+    amount: u64,
+) -> Result<u64> {
+    let mint = match mint {
+        Some(mint) => mint,
+        None => return Ok(0),
+    };
+
+    let mint_account_info = mint.to_account_info();
+    let mint_data = mint_account_info.try_borrow_data()?;
+    let mint_with_extension = StateWithExtensions::<MintInner>::unpack(&mint_data)?;
+    match mint_with_extension.get_extension::<TransferFeeConfig>() {
+        Ok(fee_config) => Ok(fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(ErrorCode::MathError)?),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Rejects token-2022 mints with a transfer fee or transfer hook
 pub fn validate_mint_fee(account_info: &AccountInfo) -> Result<()> {
     let mint_data = account_info.try_borrow_data()?;
     let mint_with_extension = StateWithExtensions::<MintInner>::unpack(&mint_data)?;
@@ -118,5 +232,21 @@ pub fn validate_mint_fee(account_info: &AccountInfo) -> Result<()> {
         validate!(fee == 0, ErrorCode::NonZeroTransferFee)?
     }
 
+    if let Ok(transfer_hook) = mint_with_extension.get_extension::<TransferHook>() {
+        validate!(
+            Option::<Pubkey>::from(transfer_hook.program_id).is_none(),
+            ErrorCode::TransferHookNotSupported
+        )?
+    }
+
     Ok(())
 }
+
+pub fn has_transfer_hook(account_info: &AccountInfo) -> Result<bool> {
+    let mint_data = account_info.try_borrow_data()?;
+    let mint_with_extension = StateWithExtensions::<MintInner>::unpack(&mint_data)?;
+    match mint_with_extension.get_extension::<TransferHook>() {
+        Ok(transfer_hook) => Ok(Option::<Pubkey>::from(transfer_hook.program_id).is_some()),
+        Err(_) => Ok(false),
+    }
+}
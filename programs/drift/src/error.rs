@@ -575,6 +575,48 @@ pub enum ErrorCode {
     LiquidationOrderFailedToFill,
     #[msg("Invalid prediction market order")]
     InvalidPredictionMarketOrder,
+    #[msg("Pending lp withdraw request")]
+    PendingLpWithdrawRequest,
+    #[msg("No lp withdraw request")]
+    NoLpWithdrawRequest,
+    #[msg("Invalid term loan")]
+    InvalidTermLoan,
+    #[msg("Term loan not matured")]
+    TermLoanNotMatured,
+    #[msg("Invalid insurance fund tranche")]
+    InvalidInsuranceFundTranche,
+    #[msg("Invalid referral tier config")]
+    InvalidReferralTierConfig,
+    #[msg("Invalid market fee structure")]
+    InvalidMarketFeeStructure,
+    #[msg("Invalid fuel season")]
+    InvalidFuelSeason,
+    #[msg("Invalid fuel redemption")]
+    InvalidFuelRedemption,
+    #[msg("Invalid maker rewards program")]
+    InvalidMakerRewardsProgram,
+    #[msg("Maker rewards program full")]
+    MakerRewardsProgramFull,
+    #[msg("Maker rewards epoch not over")]
+    MakerRewardsEpochNotOver,
+    #[msg("Invalid perp market depth bounds")]
+    InvalidPerpMarketDepthBounds,
+    #[msg("Oracle aggregator quorum not met")]
+    OracleAggregatorQuorumNotMet,
+    #[msg("Invalid oracle guard rails")]
+    InvalidOracleGuardRails,
+    #[msg("Invalid oracle circuit breaker window")]
+    InvalidOracleCircuitBreakerWindow,
+    #[msg("Transfer hook not supported")]
+    TransferHookNotSupported,
+    #[msg("No sub affiliate reward to claim")]
+    NoSubAffiliateRewardToClaim,
+    #[msg("Invalid fee tier qualifications")]
+    InvalidFeeTierQualifications,
+    #[msg("Insufficient maker volume to join maker rewards program")]
+    InsufficientMakerVolumeForMakerRewards,
+    #[msg("Lp withdraw cap reached")]
+    LpWithdrawCapReached,
 }
 
 #[macro_export]
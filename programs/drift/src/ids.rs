@@ -26,6 +26,11 @@ pub mod switchboard_on_demand {
     declare_id!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv");
 }
 
+pub mod chainlink_store_program {
+    use solana_program::declare_id;
+    declare_id!("HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny");
+}
+
 pub mod bonk_oracle {
     use solana_program::declare_id;
     #[cfg(feature = "mainnet-beta")]
@@ -18,11 +18,11 @@ use crate::instructions::constraints::*;
 use crate::math::casting::Cast;
 use crate::math::constants::{
     DEFAULT_LIQUIDATION_MARGIN_BUFFER_RATIO, FEE_POOL_TO_REVENUE_POOL_THRESHOLD, FUEL_START_TS,
-    IF_FACTOR_PRECISION, INSURANCE_A_MAX, INSURANCE_B_MAX, INSURANCE_C_MAX,
-    INSURANCE_SPECULATIVE_MAX, LIQUIDATION_FEE_PRECISION, MAX_CONCENTRATION_COEFFICIENT,
-    MAX_SQRT_K, MAX_UPDATE_K_PRICE_CHANGE, PERCENTAGE_PRECISION, QUOTE_SPOT_MARKET_INDEX,
-    SPOT_CUMULATIVE_INTEREST_PRECISION, SPOT_IMF_PRECISION, SPOT_WEIGHT_PRECISION, THIRTEEN_DAY,
-    TWENTY_FOUR_HOUR,
+    FUNDING_INTEREST_RATE_ADJUSTMENT_MAX, IF_FACTOR_PRECISION, INSURANCE_A_MAX, INSURANCE_B_MAX,
+    INSURANCE_C_MAX, INSURANCE_SPECULATIVE_MAX, LIQUIDATION_FEE_PRECISION,
+    MAX_CONCENTRATION_COEFFICIENT, MAX_SQRT_K, MAX_UPDATE_K_PRICE_CHANGE, ONE_BPS_DENOMINATOR,
+    PERCENTAGE_PRECISION, QUOTE_SPOT_MARKET_INDEX, SPOT_CUMULATIVE_INTEREST_PRECISION,
+    SPOT_IMF_PRECISION, SPOT_WEIGHT_PRECISION, THIRTEEN_DAY, TWENTY_FOUR_HOUR,
 };
 use crate::math::cp_curve::get_update_k_result;
 use crate::math::orders::is_multiple_of_step_size;
@@ -33,6 +33,7 @@ use crate::math::spot_withdraw::validate_spot_market_vault_amount;
 use crate::math::{amm, bn};
 use crate::optional_accounts::get_token_mint;
 use crate::state::events::{CurveRecord, SpotMarketVaultDepositRecord};
+use crate::state::fuel_season::FuelSeason;
 use crate::state::fulfillment_params::openbook_v2::{
     OpenbookV2Context, OpenbookV2FulfillmentConfig,
 };
@@ -40,29 +41,45 @@ use crate::state::fulfillment_params::phoenix::PhoenixMarketContext;
 use crate::state::fulfillment_params::phoenix::PhoenixV1FulfillmentConfig;
 use crate::state::fulfillment_params::serum::SerumContext;
 use crate::state::fulfillment_params::serum::SerumV3FulfillmentConfig;
-use crate::state::insurance_fund_stake::ProtocolIfSharesTransferConfig;
+use crate::state::insurance_fund_stake::{
+    InsuranceFundPerformance, InsuranceFundTranche, PerpInsuranceFund,
+    ProtocolIfSharesTransferConfig, SeniorInsuranceFund,
+};
+use crate::state::maker_rewards::MakerRewardsProgram;
 use crate::state::oracle::get_sb_on_demand_price;
 use crate::state::oracle::{
-    get_oracle_price, get_prelaunch_price, get_pyth_price, get_switchboard_price,
-    HistoricalIndexData, HistoricalOracleData, OraclePriceData, OracleSource, PrelaunchOracle,
-    PrelaunchOracleParams,
+    get_chainlink_price, get_oracle_aggregator_price, get_oracle_price, get_prelaunch_price,
+    get_pyth_price, get_signed_price, get_switchboard_price, HistoricalIndexData,
+    HistoricalOracleData, OracleAggregator, OracleAggregatorParams, OracleAggregatorSourceParams,
+    OracleCircuitBreaker, OracleCircuitBreakerWindow, OraclePriceData, OracleSource,
+    PrelaunchOracle, PrelaunchOracleParams, SignedPriceAccount,
 };
 use crate::state::oracle_map::OracleMap;
 use crate::state::paused_operations::{InsuranceFundOperation, PerpOperation, SpotOperation};
 use crate::state::perp_market::{
-    ContractTier, ContractType, InsuranceClaim, MarketStatus, PerpMarket, PoolBalance, AMM,
+    ContractTier, ContractType, InsuranceClaim, MarketStatus, PerpMarket, PerpMarketDepthBounds,
+    PoolBalance, AMM,
 };
 use crate::state::spot_market::{
-    AssetTier, InsuranceFund, SpotBalanceType, SpotFulfillmentConfigStatus, SpotMarket,
+    AssetTier, BorrowRateKink, InsuranceFund, SpotBalanceType, SpotFulfillmentConfigStatus,
+    SpotMarket, SpotMarketFlag,
+};
+use crate::state::state::{
+    ExchangeStatus, FeeStructure, FeeTierQualification, FeeTierQualifications, MarketFeeStructure,
+    OracleGuardRails, ReferralTier, ReferralTierConfig, State, ValidityGuardRailsOverride,
 };
-use crate::state::state::{ExchangeStatus, FeeStructure, OracleGuardRails, State};
 use crate::state::traits::Size;
-use crate::state::user::{User, UserStats};
+use crate::state::user::{MarketType, User, UserStats};
 use crate::validate;
-use crate::validation::fee_structure::validate_fee_structure;
-use crate::validation::margin::{validate_margin, validate_margin_weights};
+use crate::validation::fee_structure::{
+    validate_fee_structure, validate_fee_tier_qualifications, validate_referral_tier_config,
+};
+use crate::validation::margin::{
+    validate_margin, validate_margin_confidence_multiplier, validate_margin_for_oracle_source,
+    validate_margin_weights,
+};
 use crate::validation::perp_market::validate_perp_market;
-use crate::validation::spot_market::validate_borrow_rate;
+use crate::validation::spot_market::{validate_borrow_rate, validate_borrow_rate_kinks};
 use crate::{controller, QUOTE_PRECISION_I64};
 use crate::{get_then_update_id, EPOCH_DURATION};
 use crate::{load, FEE_ADJUSTMENT_MAX};
@@ -99,7 +116,12 @@ pub fn handle_initialize(ctx: Context<Initialize>) -> Result<()> {
         initial_pct_to_liquidate: 0,
         max_number_of_sub_accounts: 0,
         max_initialize_user_fee: 0,
-        padding: [0; 10],
+        fuel_season: 0,
+        has_referral_tier_config: false,
+        has_perp_fee_tier_qualifications: false,
+        has_spot_fee_tier_qualifications: false,
+        fuel_season_start_ts: 0,
+        padding: [0; 1],
     };
 
     Ok(())
@@ -140,7 +162,13 @@ pub fn handle_initialize_spot_market(
         return Err(ErrorCode::InvalidInsuranceFundAuthority.into());
     }
 
-    validate_borrow_rate(optimal_utilization, optimal_borrow_rate, max_borrow_rate, 0)?;
+    validate_borrow_rate(
+        optimal_utilization,
+        optimal_borrow_rate,
+        max_borrow_rate,
+        0,
+        &[BorrowRateKink::default(); 4],
+    )?;
 
     let spot_market_index = get_then_update_id!(state, number_of_spot_markets);
 
@@ -163,6 +191,8 @@ pub fn handle_initialize_spot_market(
         OracleMap::validate_oracle_account_info(&ctx.accounts.oracle)?;
     }
 
+    oracle_source.validate_available()?;
+
     let oracle_price_data = get_oracle_price(
         &oracle_source,
         &ctx.accounts.oracle,
@@ -310,7 +340,14 @@ pub fn handle_initialize_spot_market(
         fuel_boost_maker: 0,
         fuel_boost_insurance: 0,
         token_program,
-        padding: [0; 41],
+        margin_confidence_multiplier: 0,
+        oracle_validity_guard_rails: ValidityGuardRailsOverride::default(),
+        oracle_circuit_breaker: OracleCircuitBreaker::default(),
+        borrow_rate_kinks: [BorrowRateKink::default(); 4],
+        asset_group: 0,
+        asset_group_margin_ratio_initial: 0,
+        asset_group_margin_ratio_maintenance: 0,
+        flags: 0,
         insurance_fund: InsuranceFund {
             vault: *ctx.accounts.insurance_fund_vault.to_account_info().key,
             unstaking_period: THIRTEEN_DAY,
@@ -674,6 +711,7 @@ pub fn handle_initialize_perp_market(
         amm::calculate_bid_ask_bounds(concentration_coef, amm_base_asset_reserve)?;
 
     OracleMap::validate_oracle_account_info(&ctx.accounts.oracle)?;
+    oracle_source.validate_available()?;
 
     // Verify oracle is readable
     let (oracle_price, oracle_delay, last_oracle_price_twap) = match oracle_source {
@@ -734,7 +772,7 @@ pub fn handle_initialize_perp_market(
             msg!("Quote asset oracle cant be used for perp market");
             return Err(ErrorCode::InvalidOracle.into());
         }
-        OracleSource::Prelaunch => {
+        OracleSource::Prelaunch | OracleSource::FillTwap => {
             let OraclePriceData {
                 price: oracle_price,
                 delay: oracle_delay,
@@ -793,6 +831,33 @@ pub fn handle_initialize_perp_market(
                 ..
             } = get_sb_on_demand_price(&ctx.accounts.oracle, clock_slot)?;
 
+            (oracle_price, oracle_delay, oracle_price)
+        }
+        OracleSource::Aggregated => {
+            let OraclePriceData {
+                price: oracle_price,
+                delay: oracle_delay,
+                ..
+            } = get_oracle_aggregator_price(&ctx.accounts.oracle, clock_slot)?;
+
+            (oracle_price, oracle_delay, oracle_price)
+        }
+        OracleSource::Chainlink => {
+            let OraclePriceData {
+                price: oracle_price,
+                delay: oracle_delay,
+                ..
+            } = get_chainlink_price(&ctx.accounts.oracle, clock_slot)?;
+
+            (oracle_price, oracle_delay, oracle_price)
+        }
+        OracleSource::SignedPrice => {
+            let OraclePriceData {
+                price: oracle_price,
+                delay: oracle_delay,
+                ..
+            } = get_signed_price(&ctx.accounts.oracle, clock_slot)?;
+
             (oracle_price, oracle_delay, oracle_price)
         }
     };
@@ -804,6 +869,8 @@ pub fn handle_initialize_perp_market(
         max_spread,
     )?;
 
+    validate_margin_for_oracle_source(oracle_source, margin_ratio_initial)?;
+
     let state = &mut ctx.accounts.state;
     validate!(
         market_index == state.number_of_markets,
@@ -852,7 +919,21 @@ pub fn handle_initialize_perp_market(
         fuel_boost_position: 0,
         fuel_boost_taker: 0,
         fuel_boost_maker: 0,
-        padding: [0; 43],
+        funding_interest_rate_adjustment: 0,
+        dynamic_amm_jit_min_scale: 0,
+        margin_confidence_multiplier: 0,
+        has_depth_bounds: false,
+        has_perp_insurance_fund: false,
+        has_perp_insurance_fund_performance: false,
+        lp_withdraw_cap: 0,
+        lp_withdraw_window_start_ts: 0,
+        lp_shares_withdrawn_in_window: 0,
+        oracle_validity_guard_rails: ValidityGuardRailsOverride::default(),
+        oracle_circuit_breaker: OracleCircuitBreaker::default(),
+        has_fee_structure_override: false,
+        fuel_boost_lp: 0,
+        fuel_boost_keeper: 0,
+        has_maker_rewards_program: false,
         amm: AMM {
             oracle: *ctx.accounts.oracle.key,
             oracle_source,
@@ -947,7 +1028,8 @@ pub fn handle_initialize_perp_market(
             net_unsettled_funding_pnl: 0,
             quote_asset_amount_with_unsettled_lp: 0,
             reference_price_offset: 0,
-            padding: [0; 12],
+            premium_index_sample_weight: 0,
+            premium_index_sample_sum: 0,
         },
     };
 
@@ -1122,6 +1204,8 @@ pub fn handle_update_spot_market_oracle(
         oracle
     )?;
 
+    oracle_source.validate_available()?;
+
     // Verify oracle is readable
     let OraclePriceData {
         price: _oracle_price,
@@ -1254,6 +1338,58 @@ pub fn handle_init_user_fuel(
     Ok(())
 }
 
+pub fn handle_close_fuel_season(ctx: Context<CloseFuelSeason>, season: u16) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let state = &mut ctx.accounts.state;
+
+    validate!(
+        season == state.fuel_season,
+        ErrorCode::InvalidFuelSeason,
+        "season {} is not the open fuel season {}",
+        season,
+        state.fuel_season
+    )?;
+
+    let mut fuel_season = ctx
+        .accounts
+        .fuel_season
+        .load_init()
+        .or(Err(ErrorCode::UnableToLoadAccountLoader))?;
+
+    *fuel_season = FuelSeason {
+        reward_mint: ctx.accounts.reward_mint.key(),
+        start_ts: state.fuel_season_start_ts.cast()?,
+        end_ts: now,
+        season,
+        ..FuelSeason::default()
+    };
+
+    state.fuel_season = season.safe_add(1)?;
+    state.fuel_season_start_ts = now.cast()?;
+
+    msg!("closed fuel season {}", season);
+
+    Ok(())
+}
+
+pub fn handle_update_fuel_season_redemption_rate(
+    ctx: Context<UpdateFuelSeason>,
+    redemption_rate: u64,
+) -> Result<()> {
+    let fuel_season = &mut load_mut!(ctx.accounts.fuel_season)?;
+
+    msg!(
+        "fuel season {} redemption_rate: {} -> {}",
+        fuel_season.season,
+        fuel_season.redemption_rate,
+        redemption_rate
+    );
+
+    fuel_season.redemption_rate = redemption_rate;
+
+    Ok(())
+}
+
 #[access_control(
     perp_market_valid(&ctx.accounts.perp_market)
 )]
@@ -1686,6 +1822,7 @@ pub fn handle_deposit_into_perp_market_fee_pool<'c: 'info, 'info>(
         &ctx.accounts.admin.to_account_info(),
         amount,
         &mint,
+        None,
     )?;
 
     Ok(())
@@ -1756,6 +1893,7 @@ pub fn handle_deposit_into_spot_market_vault<'c: 'info, 'info>(
         &ctx.accounts.admin.to_account_info(),
         amount,
         &mint,
+        None,
     )?;
 
     ctx.accounts.spot_market_vault.reload()?;
@@ -2228,6 +2366,8 @@ pub fn handle_update_perp_market_margin_ratio(
         perp_market.amm.max_spread,
     )?;
 
+    validate_margin_for_oracle_source(perp_market.amm.oracle_source, margin_ratio_initial)?;
+
     msg!(
         "perp_market.margin_ratio_initial: {:?} -> {:?}",
         perp_market.margin_ratio_initial,
@@ -2699,6 +2839,60 @@ pub fn handle_update_spot_market_margin_weights(
     Ok(())
 }
 
+#[access_control(
+    spot_market_valid(&ctx.accounts.spot_market)
+)]
+pub fn handle_update_spot_market_asset_group(
+    ctx: Context<AdminUpdateSpotMarket>,
+    asset_group: u8,
+    asset_group_margin_ratio_initial: u8,
+    asset_group_margin_ratio_maintenance: u8,
+) -> Result<()> {
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    msg!("spot market {}", spot_market.market_index);
+
+    if asset_group == 0 {
+        validate!(
+            asset_group_margin_ratio_initial == 0 && asset_group_margin_ratio_maintenance == 0,
+            ErrorCode::DefaultError,
+            "asset group margin ratios must be 0 when market is not in an asset group"
+        )?;
+    } else {
+        validate!(
+            asset_group_margin_ratio_initial >= asset_group_margin_ratio_maintenance
+                && asset_group_margin_ratio_maintenance > 0,
+            ErrorCode::DefaultError,
+            "asset group margin ratio initial ({}) must be >= maintenance ({}) and maintenance must be > 0",
+            asset_group_margin_ratio_initial,
+            asset_group_margin_ratio_maintenance
+        )?;
+    }
+
+    msg!(
+        "spot_market.asset_group: {:?} -> {:?}",
+        spot_market.asset_group,
+        asset_group
+    );
+
+    msg!(
+        "spot_market.asset_group_margin_ratio_initial: {:?} -> {:?}",
+        spot_market.asset_group_margin_ratio_initial,
+        asset_group_margin_ratio_initial
+    );
+
+    msg!(
+        "spot_market.asset_group_margin_ratio_maintenance: {:?} -> {:?}",
+        spot_market.asset_group_margin_ratio_maintenance,
+        asset_group_margin_ratio_maintenance
+    );
+
+    spot_market.asset_group = asset_group;
+    spot_market.asset_group_margin_ratio_initial = asset_group_margin_ratio_initial;
+    spot_market.asset_group_margin_ratio_maintenance = asset_group_margin_ratio_maintenance;
+
+    Ok(())
+}
+
 #[access_control(
     spot_market_valid(&ctx.accounts.spot_market)
 )]
@@ -2720,6 +2914,7 @@ pub fn handle_update_spot_market_borrow_rate(
             .unwrap_or(spot_market.min_borrow_rate)
             .cast::<u32>()?
             * ((PERCENTAGE_PRECISION / 200) as u32),
+        &spot_market.borrow_rate_kinks,
     )?;
 
     msg!(
@@ -2756,6 +2951,29 @@ pub fn handle_update_spot_market_borrow_rate(
     Ok(())
 }
 
+#[access_control(
+    spot_market_valid(&ctx.accounts.spot_market)
+)]
+pub fn handle_update_spot_market_borrow_rate_kinks(
+    ctx: Context<AdminUpdateSpotMarket>,
+    borrow_rate_kinks: [BorrowRateKink; 4],
+) -> Result<()> {
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    msg!("spot market {}", spot_market.market_index);
+
+    validate_borrow_rate_kinks(&borrow_rate_kinks)?;
+
+    msg!(
+        "spot_market.borrow_rate_kinks: {:?} -> {:?}",
+        spot_market.borrow_rate_kinks,
+        borrow_rate_kinks
+    );
+
+    spot_market.borrow_rate_kinks = borrow_rate_kinks;
+
+    Ok(())
+}
+
 #[access_control(
     spot_market_valid(&ctx.accounts.spot_market)
 )]
@@ -2864,6 +3082,170 @@ pub fn handle_update_spot_market_if_paused_operations(
     Ok(())
 }
 
+#[access_control(
+    spot_market_valid(&ctx.accounts.spot_market)
+)]
+pub fn handle_update_spot_market_margin_confidence_multiplier(
+    ctx: Context<AdminUpdateSpotMarket>,
+    margin_confidence_multiplier: u8,
+) -> Result<()> {
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    msg!("spot market {}", spot_market.market_index);
+
+    validate_margin_confidence_multiplier(margin_confidence_multiplier)?;
+
+    msg!(
+        "spot_market.margin_confidence_multiplier: {} -> {}",
+        spot_market.margin_confidence_multiplier,
+        margin_confidence_multiplier
+    );
+
+    spot_market.margin_confidence_multiplier = margin_confidence_multiplier;
+    Ok(())
+}
+
+#[access_control(
+    spot_market_valid(&ctx.accounts.spot_market)
+)]
+pub fn handle_update_spot_market_oracle_validity_guard_rails(
+    ctx: Context<AdminUpdateSpotMarket>,
+    slots_before_stale_for_amm: u16,
+    slots_before_stale_for_margin: u16,
+    confidence_interval_max_size: u32,
+    too_volatile_ratio: u16,
+) -> Result<()> {
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    msg!("spot market {}", spot_market.market_index);
+
+    let oracle_validity_guard_rails = ValidityGuardRailsOverride {
+        slots_before_stale_for_amm,
+        slots_before_stale_for_margin,
+        confidence_interval_max_size,
+        too_volatile_ratio,
+        ..ValidityGuardRailsOverride::default()
+    };
+
+    oracle_validity_guard_rails
+        .apply(&ctx.accounts.state.oracle_guard_rails.validity)
+        .validate()?;
+
+    msg!(
+        "spot_market.oracle_validity_guard_rails: {:?} -> {:?}",
+        spot_market.oracle_validity_guard_rails,
+        oracle_validity_guard_rails
+    );
+
+    spot_market.oracle_validity_guard_rails = oracle_validity_guard_rails;
+    Ok(())
+}
+
+#[access_control(
+    spot_market_valid(&ctx.accounts.spot_market)
+)]
+pub fn handle_update_spot_market_oracle_circuit_breaker(
+    ctx: Context<AdminUpdateSpotMarket>,
+    threshold: u16,
+    cooldown: u16,
+) -> Result<()> {
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    msg!("spot market {}", spot_market.market_index);
+
+    validate!(
+        threshold <= ONE_BPS_DENOMINATOR.cast()?,
+        ErrorCode::DefaultError,
+        "threshold must be <= ONE_BPS_DENOMINATOR"
+    )?;
+
+    msg!(
+        "spot_market.oracle_circuit_breaker.threshold: {} -> {}",
+        spot_market.oracle_circuit_breaker.threshold,
+        threshold
+    );
+
+    msg!(
+        "spot_market.oracle_circuit_breaker.cooldown: {} -> {}",
+        spot_market.oracle_circuit_breaker.cooldown,
+        cooldown
+    );
+
+    spot_market.oracle_circuit_breaker.threshold = threshold;
+    spot_market.oracle_circuit_breaker.cooldown = cooldown;
+
+    Ok(())
+}
+
+fn validate_oracle_circuit_breaker_window(window_slots: u64) -> Result<()> {
+    validate!(
+        window_slots > 0,
+        ErrorCode::InvalidOracleCircuitBreakerWindow,
+        "window_slots must be > 0",
+    )?;
+
+    Ok(())
+}
+
+#[access_control(
+    perp_market_valid(&ctx.accounts.perp_market)
+)]
+pub fn handle_initialize_perp_market_oracle_circuit_breaker_window(
+    ctx: Context<InitializePerpMarketOracleCircuitBreakerWindow>,
+    market_index: u16,
+    window_slots: u64,
+) -> Result<()> {
+    validate_oracle_circuit_breaker_window(window_slots)?;
+
+    msg!("perp market {}", market_index);
+    msg!("window_slots: {}", window_slots);
+
+    let window = &mut ctx.accounts.oracle_circuit_breaker_window;
+    window.window_slots = window_slots;
+    window.market_index = market_index;
+    window.market_type = MarketType::Perp;
+
+    Ok(())
+}
+
+#[access_control(
+    spot_market_valid(&ctx.accounts.spot_market)
+)]
+pub fn handle_initialize_spot_market_oracle_circuit_breaker_window(
+    ctx: Context<InitializeSpotMarketOracleCircuitBreakerWindow>,
+    market_index: u16,
+    window_slots: u64,
+) -> Result<()> {
+    validate_oracle_circuit_breaker_window(window_slots)?;
+
+    msg!("spot market {}", market_index);
+    msg!("window_slots: {}", window_slots);
+
+    let window = &mut ctx.accounts.oracle_circuit_breaker_window;
+    window.window_slots = window_slots;
+    window.market_index = market_index;
+    window.market_type = MarketType::Spot;
+
+    Ok(())
+}
+
+pub fn handle_update_oracle_circuit_breaker_window(
+    ctx: Context<UpdateOracleCircuitBreakerWindow>,
+    window_slots: u64,
+) -> Result<()> {
+    validate_oracle_circuit_breaker_window(window_slots)?;
+
+    let window = &mut ctx.accounts.oracle_circuit_breaker_window;
+    msg!("{} market {}", window.market_type, window.market_index);
+
+    msg!(
+        "oracle_circuit_breaker_window.window_slots: {} -> {}",
+        window.window_slots,
+        window_slots
+    );
+
+    window.window_slots = window_slots;
+
+    Ok(())
+}
+
 #[access_control(
     perp_market_valid(&ctx.accounts.perp_market)
 )]
@@ -3174,65 +3556,354 @@ pub fn handle_update_spot_fee_structure(
     Ok(())
 }
 
-pub fn handle_update_initial_pct_to_liquidate(
-    ctx: Context<AdminUpdateState>,
-    initial_pct_to_liquidate: u16,
+pub fn handle_initialize_perp_market_fee_structure(
+    ctx: Context<InitializePerpMarketFeeStructure>,
+    market_index: u16,
+    fee_structure: FeeStructure,
 ) -> Result<()> {
-    msg!(
-        "initial_pct_to_liquidate: {} -> {}",
-        ctx.accounts.state.initial_pct_to_liquidate,
-        initial_pct_to_liquidate
-    );
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    msg!("perp market {}", perp_market.market_index);
+
+    validate_fee_structure(&fee_structure)?;
+
+    msg!("fee_structure: {:?}", fee_structure);
+
+    let market_fee_structure = &mut ctx.accounts.market_fee_structure;
+    market_fee_structure.fee_structure = fee_structure;
+    market_fee_structure.market_index = market_index;
+    market_fee_structure.market_type = MarketType::Perp;
+
+    perp_market.has_fee_structure_override = true;
 
-    ctx.accounts.state.initial_pct_to_liquidate = initial_pct_to_liquidate;
     Ok(())
 }
 
-pub fn handle_update_liquidation_duration(
-    ctx: Context<AdminUpdateState>,
-    liquidation_duration: u8,
+pub fn handle_initialize_spot_market_fee_structure(
+    ctx: Context<InitializeSpotMarketFeeStructure>,
+    market_index: u16,
+    fee_structure: FeeStructure,
 ) -> Result<()> {
-    msg!(
-        "liquidation_duration: {} -> {}",
-        ctx.accounts.state.liquidation_duration,
-        liquidation_duration
-    );
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    msg!("spot market {}", spot_market.market_index);
+
+    validate_fee_structure(&fee_structure)?;
+
+    msg!("fee_structure: {:?}", fee_structure);
+
+    let market_fee_structure = &mut ctx.accounts.market_fee_structure;
+    market_fee_structure.fee_structure = fee_structure;
+    market_fee_structure.market_index = market_index;
+    market_fee_structure.market_type = MarketType::Spot;
+
+    spot_market.set_flag(SpotMarketFlag::HasFeeStructureOverride, true);
 
-    ctx.accounts.state.liquidation_duration = liquidation_duration;
     Ok(())
 }
 
-pub fn handle_update_liquidation_margin_buffer_ratio(
-    ctx: Context<AdminUpdateState>,
-    liquidation_margin_buffer_ratio: u32,
+pub fn handle_update_market_fee_structure(
+    ctx: Context<UpdateMarketFeeStructure>,
+    fee_structure: FeeStructure,
 ) -> Result<()> {
+    let market_fee_structure = &mut ctx.accounts.market_fee_structure;
     msg!(
-        "liquidation_margin_buffer_ratio: {} -> {}",
-        ctx.accounts.state.liquidation_margin_buffer_ratio,
-        liquidation_margin_buffer_ratio
+        "{:?} market {}",
+        market_fee_structure.market_type,
+        market_fee_structure.market_index
     );
 
-    ctx.accounts.state.liquidation_margin_buffer_ratio = liquidation_margin_buffer_ratio;
-    Ok(())
-}
+    validate_fee_structure(&fee_structure)?;
 
-pub fn handle_update_oracle_guard_rails(
-    ctx: Context<AdminUpdateState>,
-    oracle_guard_rails: OracleGuardRails,
-) -> Result<()> {
     msg!(
-        "oracle_guard_rails: {:?} -> {:?}",
-        ctx.accounts.state.oracle_guard_rails,
-        oracle_guard_rails
+        "fee_structure: {:?} -> {:?}",
+        market_fee_structure.fee_structure,
+        fee_structure
     );
 
-    ctx.accounts.state.oracle_guard_rails = oracle_guard_rails;
+    market_fee_structure.fee_structure = fee_structure;
     Ok(())
 }
 
-pub fn handle_update_state_settlement_duration(
-    ctx: Context<AdminUpdateState>,
-    settlement_duration: u16,
+pub fn handle_delete_perp_market_fee_structure(
+    ctx: Context<DeletePerpMarketFeeStructure>,
+    _market_index: u16,
+) -> Result<()> {
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    msg!("perp market {}", perp_market.market_index);
+
+    perp_market.has_fee_structure_override = false;
+
+    Ok(())
+}
+
+pub fn handle_delete_spot_market_fee_structure(
+    ctx: Context<DeleteSpotMarketFeeStructure>,
+    _market_index: u16,
+) -> Result<()> {
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    msg!("spot market {}", spot_market.market_index);
+
+    spot_market.set_flag(SpotMarketFlag::HasFeeStructureOverride, false);
+
+    Ok(())
+}
+
+pub fn handle_initialize_perp_fee_tier_qualifications(
+    ctx: Context<InitializePerpFeeTierQualifications>,
+    fee_tier_qualifications: [FeeTierQualification; 10],
+) -> Result<()> {
+    validate_fee_tier_qualifications(&fee_tier_qualifications)?;
+
+    msg!("fee_tier_qualifications: {:?}", fee_tier_qualifications);
+
+    let perp_fee_tier_qualifications = &mut ctx.accounts.fee_tier_qualifications;
+    perp_fee_tier_qualifications.fee_tier_qualifications = fee_tier_qualifications;
+    perp_fee_tier_qualifications.market_type = MarketType::Perp;
+
+    ctx.accounts.state.has_perp_fee_tier_qualifications = true;
+
+    Ok(())
+}
+
+pub fn handle_initialize_spot_fee_tier_qualifications(
+    ctx: Context<InitializeSpotFeeTierQualifications>,
+    fee_tier_qualifications: [FeeTierQualification; 10],
+) -> Result<()> {
+    validate_fee_tier_qualifications(&fee_tier_qualifications)?;
+
+    msg!("fee_tier_qualifications: {:?}", fee_tier_qualifications);
+
+    let spot_fee_tier_qualifications = &mut ctx.accounts.fee_tier_qualifications;
+    spot_fee_tier_qualifications.fee_tier_qualifications = fee_tier_qualifications;
+    spot_fee_tier_qualifications.market_type = MarketType::Spot;
+
+    ctx.accounts.state.has_spot_fee_tier_qualifications = true;
+
+    Ok(())
+}
+
+pub fn handle_update_fee_tier_qualifications(
+    ctx: Context<UpdateFeeTierQualifications>,
+    fee_tier_qualifications: [FeeTierQualification; 10],
+) -> Result<()> {
+    let current_fee_tier_qualifications = &mut ctx.accounts.fee_tier_qualifications;
+    msg!("{:?}", current_fee_tier_qualifications.market_type);
+
+    validate_fee_tier_qualifications(&fee_tier_qualifications)?;
+
+    msg!(
+        "fee_tier_qualifications: {:?} -> {:?}",
+        current_fee_tier_qualifications.fee_tier_qualifications,
+        fee_tier_qualifications
+    );
+
+    current_fee_tier_qualifications.fee_tier_qualifications = fee_tier_qualifications;
+    Ok(())
+}
+
+pub fn handle_initialize_maker_rewards_program(
+    ctx: Context<InitializeMakerRewardsProgram>,
+    market_index: u16,
+    epoch_duration: i64,
+    epoch_rewards: u64,
+    max_oracle_distance: u32,
+    volume_reward_share: u32,
+    min_maker_volume_30d: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    msg!("perp market {}", perp_market.market_index);
+
+    let mut maker_rewards_program = ctx
+        .accounts
+        .maker_rewards_program
+        .load_init()
+        .or(Err(ErrorCode::UnableToLoadAccountLoader))?;
+
+    *maker_rewards_program = MakerRewardsProgram {
+        reward_mint: ctx.accounts.reward_mint.key(),
+        epoch_start_ts: now,
+        epoch_duration,
+        epoch_rewards,
+        max_oracle_distance,
+        volume_reward_share,
+        min_maker_volume_30d,
+        market_index,
+        ..MakerRewardsProgram::default()
+    };
+
+    maker_rewards_program.validate()?;
+
+    perp_market.has_maker_rewards_program = true;
+
+    Ok(())
+}
+
+pub fn handle_update_maker_rewards_program(
+    ctx: Context<UpdateMakerRewardsProgram>,
+    _market_index: u16,
+    epoch_duration: Option<i64>,
+    epoch_rewards: Option<u64>,
+    max_oracle_distance: Option<u32>,
+    volume_reward_share: Option<u32>,
+    min_maker_volume_30d: Option<u64>,
+) -> Result<()> {
+    let maker_rewards_program = &mut load_mut!(ctx.accounts.maker_rewards_program)?;
+    msg!(
+        "maker rewards program {}",
+        maker_rewards_program.market_index
+    );
+
+    if let Some(epoch_duration) = epoch_duration {
+        msg!(
+            "maker_rewards_program.epoch_duration: {:?} -> {:?}",
+            maker_rewards_program.epoch_duration,
+            epoch_duration
+        );
+        maker_rewards_program.epoch_duration = epoch_duration;
+    } else {
+        msg!("maker_rewards_program.epoch_duration: unchanged");
+    }
+
+    if let Some(epoch_rewards) = epoch_rewards {
+        msg!(
+            "maker_rewards_program.epoch_rewards: {:?} -> {:?}",
+            maker_rewards_program.epoch_rewards,
+            epoch_rewards
+        );
+        maker_rewards_program.epoch_rewards = epoch_rewards;
+    } else {
+        msg!("maker_rewards_program.epoch_rewards: unchanged");
+    }
+
+    if let Some(max_oracle_distance) = max_oracle_distance {
+        msg!(
+            "maker_rewards_program.max_oracle_distance: {:?} -> {:?}",
+            maker_rewards_program.max_oracle_distance,
+            max_oracle_distance
+        );
+        maker_rewards_program.max_oracle_distance = max_oracle_distance;
+    } else {
+        msg!("maker_rewards_program.max_oracle_distance: unchanged");
+    }
+
+    if let Some(volume_reward_share) = volume_reward_share {
+        msg!(
+            "maker_rewards_program.volume_reward_share: {:?} -> {:?}",
+            maker_rewards_program.volume_reward_share,
+            volume_reward_share
+        );
+        maker_rewards_program.volume_reward_share = volume_reward_share;
+    } else {
+        msg!("maker_rewards_program.volume_reward_share: unchanged");
+    }
+
+    if let Some(min_maker_volume_30d) = min_maker_volume_30d {
+        msg!(
+            "maker_rewards_program.min_maker_volume_30d: {:?} -> {:?}",
+            maker_rewards_program.min_maker_volume_30d,
+            min_maker_volume_30d
+        );
+        maker_rewards_program.min_maker_volume_30d = min_maker_volume_30d;
+    } else {
+        msg!("maker_rewards_program.min_maker_volume_30d: unchanged");
+    }
+
+    maker_rewards_program.validate()?;
+
+    Ok(())
+}
+
+pub fn handle_remove_maker_from_maker_rewards_program(
+    ctx: Context<UpdateMakerRewardsProgram>,
+    _market_index: u16,
+    user: Pubkey,
+) -> Result<()> {
+    let maker_rewards_program = &mut load_mut!(ctx.accounts.maker_rewards_program)?;
+    msg!(
+        "maker rewards program {}",
+        maker_rewards_program.market_index
+    );
+
+    let maker_index = maker_rewards_program
+        .get_maker_index(&user)
+        .ok_or_else(|| {
+            msg!("user {} not in maker rewards program", user);
+            ErrorCode::InvalidMakerRewardsProgram
+        })?;
+
+    msg!(
+        "removing user {} with depth_score {} and maker_volume {}",
+        user,
+        maker_rewards_program.makers[maker_index].depth_score,
+        maker_rewards_program.makers[maker_index].maker_volume
+    );
+
+    maker_rewards_program.remove_maker(maker_index)?;
+
+    Ok(())
+}
+
+pub fn handle_update_initial_pct_to_liquidate(
+    ctx: Context<AdminUpdateState>,
+    initial_pct_to_liquidate: u16,
+) -> Result<()> {
+    msg!(
+        "initial_pct_to_liquidate: {} -> {}",
+        ctx.accounts.state.initial_pct_to_liquidate,
+        initial_pct_to_liquidate
+    );
+
+    ctx.accounts.state.initial_pct_to_liquidate = initial_pct_to_liquidate;
+    Ok(())
+}
+
+pub fn handle_update_liquidation_duration(
+    ctx: Context<AdminUpdateState>,
+    liquidation_duration: u8,
+) -> Result<()> {
+    msg!(
+        "liquidation_duration: {} -> {}",
+        ctx.accounts.state.liquidation_duration,
+        liquidation_duration
+    );
+
+    ctx.accounts.state.liquidation_duration = liquidation_duration;
+    Ok(())
+}
+
+pub fn handle_update_liquidation_margin_buffer_ratio(
+    ctx: Context<AdminUpdateState>,
+    liquidation_margin_buffer_ratio: u32,
+) -> Result<()> {
+    msg!(
+        "liquidation_margin_buffer_ratio: {} -> {}",
+        ctx.accounts.state.liquidation_margin_buffer_ratio,
+        liquidation_margin_buffer_ratio
+    );
+
+    ctx.accounts.state.liquidation_margin_buffer_ratio = liquidation_margin_buffer_ratio;
+    Ok(())
+}
+
+pub fn handle_update_oracle_guard_rails(
+    ctx: Context<AdminUpdateState>,
+    oracle_guard_rails: OracleGuardRails,
+) -> Result<()> {
+    msg!(
+        "oracle_guard_rails: {:?} -> {:?}",
+        ctx.accounts.state.oracle_guard_rails,
+        oracle_guard_rails
+    );
+
+    oracle_guard_rails.validity.validate()?;
+
+    ctx.accounts.state.oracle_guard_rails = oracle_guard_rails;
+    Ok(())
+}
+
+pub fn handle_update_state_settlement_duration(
+    ctx: Context<AdminUpdateState>,
+    settlement_duration: u16,
 ) -> Result<()> {
     msg!(
         "settlement_duration: {} -> {}",
@@ -3295,6 +3966,8 @@ pub fn handle_update_perp_market_oracle(
         oracle
     )?;
 
+    oracle_source.validate_available()?;
+
     // Verify oracle is readable
     let OraclePriceData {
         price: _oracle_price,
@@ -3302,6 +3975,8 @@ pub fn handle_update_perp_market_oracle(
         ..
     } = get_oracle_price(&oracle_source, &ctx.accounts.oracle, clock.slot)?;
 
+    validate_margin_for_oracle_source(oracle_source, perp_market.margin_ratio_initial)?;
+
     msg!(
         "perp_market.amm.oracle: {:?} -> {:?}",
         perp_market.amm.oracle,
@@ -3384,32 +4059,53 @@ pub fn handle_update_amm_jit_intensity(
 #[access_control(
     perp_market_valid(&ctx.accounts.perp_market)
 )]
-pub fn handle_update_perp_market_max_spread(
+pub fn handle_update_perp_market_dynamic_amm_jit_min_scale(
     ctx: Context<AdminUpdatePerpMarket>,
-    max_spread: u32,
+    dynamic_amm_jit_min_scale: u8,
 ) -> Result<()> {
-    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
-    msg!("perp market {}", perp_market.market_index);
-
     validate!(
-        max_spread >= perp_market.amm.base_spread,
+        dynamic_amm_jit_min_scale <= 100,
         ErrorCode::DefaultError,
-        "invalid max_spread < base_spread",
+        "invalid dynamic_amm_jit_min_scale",
     )?;
 
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    msg!("perp market {}", perp_market.market_index);
+
+    msg!(
+        "perp_market.dynamic_amm_jit_min_scale: {} -> {}",
+        perp_market.dynamic_amm_jit_min_scale,
+        dynamic_amm_jit_min_scale
+    );
+
+    perp_market.dynamic_amm_jit_min_scale = dynamic_amm_jit_min_scale;
+
+    Ok(())
+}
+
+#[access_control(
+    perp_market_valid(&ctx.accounts.perp_market)
+)]
+pub fn handle_update_perp_market_lp_withdraw_cap(
+    ctx: Context<AdminUpdatePerpMarket>,
+    lp_withdraw_cap: u8,
+) -> Result<()> {
     validate!(
-        max_spread <= perp_market.margin_ratio_initial * 100,
+        lp_withdraw_cap <= 100,
         ErrorCode::DefaultError,
-        "invalid max_spread > market.margin_ratio_initial * 100",
+        "invalid lp_withdraw_cap",
     )?;
 
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    msg!("perp market {}", perp_market.market_index);
+
     msg!(
-        "perp_market.amm.max_spread: {:?} -> {:?}",
-        perp_market.amm.max_spread,
-        max_spread
+        "perp_market.lp_withdraw_cap: {} -> {}",
+        perp_market.lp_withdraw_cap,
+        lp_withdraw_cap
     );
 
-    perp_market.amm.max_spread = max_spread;
+    perp_market.lp_withdraw_cap = lp_withdraw_cap;
 
     Ok(())
 }
@@ -3417,105 +4113,314 @@ pub fn handle_update_perp_market_max_spread(
 #[access_control(
     perp_market_valid(&ctx.accounts.perp_market)
 )]
-pub fn handle_update_perp_market_step_size_and_tick_size(
+pub fn handle_update_perp_market_margin_confidence_multiplier(
     ctx: Context<AdminUpdatePerpMarket>,
-    step_size: u64,
-    tick_size: u64,
+    margin_confidence_multiplier: u8,
 ) -> Result<()> {
     let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
     msg!("perp market {}", perp_market.market_index);
 
-    validate!(step_size > 0 && tick_size > 0, ErrorCode::DefaultError)?;
-    validate!(step_size <= 2000000000, ErrorCode::DefaultError)?; // below i32 max for lp's remainder_base_asset
+    validate_margin_confidence_multiplier(margin_confidence_multiplier)?;
 
     msg!(
-        "perp_market.amm.order_step_size: {:?} -> {:?}",
-        perp_market.amm.order_step_size,
-        step_size
+        "perp_market.margin_confidence_multiplier: {} -> {}",
+        perp_market.margin_confidence_multiplier,
+        margin_confidence_multiplier
     );
 
-    msg!(
-        "perp_market.amm.order_tick_size: {:?} -> {:?}",
-        perp_market.amm.order_tick_size,
-        tick_size
-    );
+    perp_market.margin_confidence_multiplier = margin_confidence_multiplier;
 
-    perp_market.amm.order_step_size = step_size;
-    perp_market.amm.order_tick_size = tick_size;
     Ok(())
 }
 
 #[access_control(
     perp_market_valid(&ctx.accounts.perp_market)
 )]
-pub fn handle_update_perp_market_min_order_size(
+pub fn handle_update_perp_market_oracle_validity_guard_rails(
     ctx: Context<AdminUpdatePerpMarket>,
-    order_size: u64,
+    slots_before_stale_for_amm: u16,
+    slots_before_stale_for_margin: u16,
+    confidence_interval_max_size: u32,
+    too_volatile_ratio: u16,
 ) -> Result<()> {
     let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
     msg!("perp market {}", perp_market.market_index);
 
-    validate!(order_size > 0, ErrorCode::DefaultError)?;
+    let oracle_validity_guard_rails = ValidityGuardRailsOverride {
+        slots_before_stale_for_amm,
+        slots_before_stale_for_margin,
+        confidence_interval_max_size,
+        too_volatile_ratio,
+        ..ValidityGuardRailsOverride::default()
+    };
+
+    oracle_validity_guard_rails
+        .apply(&ctx.accounts.state.oracle_guard_rails.validity)
+        .validate()?;
 
     msg!(
-        "perp_market.amm.min_order_size: {:?} -> {:?}",
-        perp_market.amm.min_order_size,
-        order_size
+        "perp_market.oracle_validity_guard_rails: {:?} -> {:?}",
+        perp_market.oracle_validity_guard_rails,
+        oracle_validity_guard_rails
     );
 
-    perp_market.amm.min_order_size = order_size;
+    perp_market.oracle_validity_guard_rails = oracle_validity_guard_rails;
+
     Ok(())
 }
 
 #[access_control(
-    spot_market_valid(&ctx.accounts.spot_market)
+    perp_market_valid(&ctx.accounts.perp_market)
 )]
-pub fn handle_update_spot_market_step_size_and_tick_size(
-    ctx: Context<AdminUpdateSpotMarket>,
-    step_size: u64,
-    tick_size: u64,
+pub fn handle_update_perp_market_oracle_circuit_breaker(
+    ctx: Context<AdminUpdatePerpMarket>,
+    threshold: u16,
+    cooldown: u16,
 ) -> Result<()> {
-    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
-    msg!("spot market {}", spot_market.market_index);
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    msg!("perp market {}", perp_market.market_index);
 
     validate!(
-        spot_market.market_index == 0 || step_size > 0 && tick_size > 0,
-        ErrorCode::DefaultError
+        threshold <= ONE_BPS_DENOMINATOR.cast()?,
+        ErrorCode::DefaultError,
+        "threshold must be <= ONE_BPS_DENOMINATOR"
     )?;
 
     msg!(
-        "spot_market.order_step_size: {:?} -> {:?}",
-        spot_market.order_step_size,
-        step_size
+        "perp_market.oracle_circuit_breaker.threshold: {} -> {}",
+        perp_market.oracle_circuit_breaker.threshold,
+        threshold
     );
 
     msg!(
-        "spot_market.order_tick_size: {:?} -> {:?}",
-        spot_market.order_tick_size,
-        tick_size
+        "perp_market.oracle_circuit_breaker.cooldown: {} -> {}",
+        perp_market.oracle_circuit_breaker.cooldown,
+        cooldown
     );
 
-    spot_market.order_step_size = step_size;
-    spot_market.order_tick_size = tick_size;
+    perp_market.oracle_circuit_breaker.threshold = threshold;
+    perp_market.oracle_circuit_breaker.cooldown = cooldown;
+
     Ok(())
 }
 
-#[access_control(
-    spot_market_valid(&ctx.accounts.spot_market)
-)]
-pub fn handle_update_spot_market_min_order_size(
-    ctx: Context<AdminUpdateSpotMarket>,
-    order_size: u64,
-) -> Result<()> {
-    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
-    msg!("spot market {}", spot_market.market_index);
+fn validate_perp_market_depth_bounds(min_sqrt_k: u128, max_sqrt_k: u128) -> Result<()> {
+    validate!(
+        max_sqrt_k == 0 || min_sqrt_k <= max_sqrt_k,
+        ErrorCode::InvalidPerpMarketDepthBounds,
+        "min_sqrt_k must be <= max_sqrt_k",
+    )?;
 
     validate!(
-        spot_market.market_index == 0 || order_size > 0,
-        ErrorCode::DefaultError
+        max_sqrt_k <= MAX_SQRT_K,
+        ErrorCode::InvalidPerpMarketDepthBounds,
+        "max_sqrt_k must be <= MAX_SQRT_K",
     )?;
 
-    msg!(
+    Ok(())
+}
+
+#[access_control(
+    perp_market_valid(&ctx.accounts.perp_market)
+)]
+pub fn handle_initialize_perp_market_depth_bounds(
+    ctx: Context<InitializePerpMarketDepthBounds>,
+    market_index: u16,
+    min_sqrt_k: u128,
+    max_sqrt_k: u128,
+) -> Result<()> {
+    validate_perp_market_depth_bounds(min_sqrt_k, max_sqrt_k)?;
+
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    msg!("perp market {}", perp_market.market_index);
+
+    msg!("min_sqrt_k: {} max_sqrt_k: {}", min_sqrt_k, max_sqrt_k);
+
+    let depth_bounds = &mut ctx.accounts.depth_bounds;
+    depth_bounds.min_sqrt_k = min_sqrt_k;
+    depth_bounds.max_sqrt_k = max_sqrt_k;
+    depth_bounds.market_index = market_index;
+
+    perp_market.has_depth_bounds = true;
+
+    Ok(())
+}
+
+pub fn handle_update_perp_market_depth_bounds(
+    ctx: Context<UpdatePerpMarketDepthBounds>,
+    min_sqrt_k: u128,
+    max_sqrt_k: u128,
+) -> Result<()> {
+    validate_perp_market_depth_bounds(min_sqrt_k, max_sqrt_k)?;
+
+    let depth_bounds = &mut ctx.accounts.depth_bounds;
+    msg!("perp market {}", depth_bounds.market_index);
+
+    msg!(
+        "depth_bounds.min_sqrt_k: {} -> {}",
+        depth_bounds.min_sqrt_k,
+        min_sqrt_k
+    );
+
+    msg!(
+        "depth_bounds.max_sqrt_k: {} -> {}",
+        depth_bounds.max_sqrt_k,
+        max_sqrt_k
+    );
+
+    depth_bounds.min_sqrt_k = min_sqrt_k;
+    depth_bounds.max_sqrt_k = max_sqrt_k;
+
+    Ok(())
+}
+
+pub fn handle_delete_perp_market_depth_bounds(
+    ctx: Context<DeletePerpMarketDepthBounds>,
+    _market_index: u16,
+) -> Result<()> {
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    msg!("perp market {}", perp_market.market_index);
+
+    perp_market.has_depth_bounds = false;
+
+    Ok(())
+}
+
+#[access_control(
+    perp_market_valid(&ctx.accounts.perp_market)
+)]
+pub fn handle_update_perp_market_max_spread(
+    ctx: Context<AdminUpdatePerpMarket>,
+    max_spread: u32,
+) -> Result<()> {
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    msg!("perp market {}", perp_market.market_index);
+
+    validate!(
+        max_spread >= perp_market.amm.base_spread,
+        ErrorCode::DefaultError,
+        "invalid max_spread < base_spread",
+    )?;
+
+    validate!(
+        max_spread <= perp_market.margin_ratio_initial * 100,
+        ErrorCode::DefaultError,
+        "invalid max_spread > market.margin_ratio_initial * 100",
+    )?;
+
+    msg!(
+        "perp_market.amm.max_spread: {:?} -> {:?}",
+        perp_market.amm.max_spread,
+        max_spread
+    );
+
+    perp_market.amm.max_spread = max_spread;
+
+    Ok(())
+}
+
+#[access_control(
+    perp_market_valid(&ctx.accounts.perp_market)
+)]
+pub fn handle_update_perp_market_step_size_and_tick_size(
+    ctx: Context<AdminUpdatePerpMarket>,
+    step_size: u64,
+    tick_size: u64,
+) -> Result<()> {
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    msg!("perp market {}", perp_market.market_index);
+
+    validate!(step_size > 0 && tick_size > 0, ErrorCode::DefaultError)?;
+    validate!(step_size <= 2000000000, ErrorCode::DefaultError)?; // below i32 max for lp's remainder_base_asset
+
+    msg!(
+        "perp_market.amm.order_step_size: {:?} -> {:?}",
+        perp_market.amm.order_step_size,
+        step_size
+    );
+
+    msg!(
+        "perp_market.amm.order_tick_size: {:?} -> {:?}",
+        perp_market.amm.order_tick_size,
+        tick_size
+    );
+
+    perp_market.amm.order_step_size = step_size;
+    perp_market.amm.order_tick_size = tick_size;
+    Ok(())
+}
+
+#[access_control(
+    perp_market_valid(&ctx.accounts.perp_market)
+)]
+pub fn handle_update_perp_market_min_order_size(
+    ctx: Context<AdminUpdatePerpMarket>,
+    order_size: u64,
+) -> Result<()> {
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    msg!("perp market {}", perp_market.market_index);
+
+    validate!(order_size > 0, ErrorCode::DefaultError)?;
+
+    msg!(
+        "perp_market.amm.min_order_size: {:?} -> {:?}",
+        perp_market.amm.min_order_size,
+        order_size
+    );
+
+    perp_market.amm.min_order_size = order_size;
+    Ok(())
+}
+
+#[access_control(
+    spot_market_valid(&ctx.accounts.spot_market)
+)]
+pub fn handle_update_spot_market_step_size_and_tick_size(
+    ctx: Context<AdminUpdateSpotMarket>,
+    step_size: u64,
+    tick_size: u64,
+) -> Result<()> {
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    msg!("spot market {}", spot_market.market_index);
+
+    validate!(
+        spot_market.market_index == 0 || step_size > 0 && tick_size > 0,
+        ErrorCode::DefaultError
+    )?;
+
+    msg!(
+        "spot_market.order_step_size: {:?} -> {:?}",
+        spot_market.order_step_size,
+        step_size
+    );
+
+    msg!(
+        "spot_market.order_tick_size: {:?} -> {:?}",
+        spot_market.order_tick_size,
+        tick_size
+    );
+
+    spot_market.order_step_size = step_size;
+    spot_market.order_tick_size = tick_size;
+    Ok(())
+}
+
+#[access_control(
+    spot_market_valid(&ctx.accounts.spot_market)
+)]
+pub fn handle_update_spot_market_min_order_size(
+    ctx: Context<AdminUpdateSpotMarket>,
+    order_size: u64,
+) -> Result<()> {
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    msg!("spot market {}", spot_market.market_index);
+
+    validate!(
+        spot_market.market_index == 0 || order_size > 0,
+        ErrorCode::DefaultError
+    )?;
+
+    msg!(
         "spot_market.min_order_size: {:?} -> {:?}",
         spot_market.min_order_size,
         order_size
@@ -3624,6 +4529,35 @@ pub fn handle_update_perp_market_fee_adjustment(
     Ok(())
 }
 
+#[access_control(
+    perp_market_valid(&ctx.accounts.perp_market)
+)]
+pub fn handle_update_perp_market_funding_interest_rate_adjustment(
+    ctx: Context<AdminUpdatePerpMarket>,
+    funding_interest_rate_adjustment: i8,
+) -> Result<()> {
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    msg!("perp market {}", perp_market.market_index);
+
+    validate!(
+        funding_interest_rate_adjustment.cast::<i64>()?.abs()
+            <= FUNDING_INTEREST_RATE_ADJUSTMENT_MAX,
+        ErrorCode::DefaultError,
+        "funding interest rate adjustment {} greater than max {}",
+        funding_interest_rate_adjustment,
+        FUNDING_INTEREST_RATE_ADJUSTMENT_MAX
+    )?;
+
+    msg!(
+        "perp_market.funding_interest_rate_adjustment: {:?} -> {:?}",
+        perp_market.funding_interest_rate_adjustment,
+        funding_interest_rate_adjustment
+    );
+
+    perp_market.funding_interest_rate_adjustment = funding_interest_rate_adjustment;
+    Ok(())
+}
+
 pub fn handle_update_perp_market_number_of_users(
     ctx: Context<AdminUpdatePerpMarket>,
     number_of_users: Option<u32>,
@@ -3668,6 +4602,8 @@ pub fn handle_update_perp_market_fuel(
     fuel_boost_taker: Option<u8>,
     fuel_boost_maker: Option<u8>,
     fuel_boost_position: Option<u8>,
+    fuel_boost_lp: Option<u8>,
+    fuel_boost_keeper: Option<u8>,
 ) -> Result<()> {
     let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
     msg!("perp market {}", perp_market.market_index);
@@ -3705,6 +4641,28 @@ pub fn handle_update_perp_market_fuel(
         msg!("perp_market.fuel_boost_position: unchanged");
     }
 
+    if let Some(fuel_boost_lp) = fuel_boost_lp {
+        msg!(
+            "perp_market.fuel_boost_lp: {:?} -> {:?}",
+            perp_market.fuel_boost_lp,
+            fuel_boost_lp
+        );
+        perp_market.fuel_boost_lp = fuel_boost_lp;
+    } else {
+        msg!("perp_market.fuel_boost_lp: unchanged");
+    }
+
+    if let Some(fuel_boost_keeper) = fuel_boost_keeper {
+        msg!(
+            "perp_market.fuel_boost_keeper: {:?} -> {:?}",
+            perp_market.fuel_boost_keeper,
+            fuel_boost_keeper
+        );
+        perp_market.fuel_boost_keeper = fuel_boost_keeper;
+    } else {
+        msg!("perp_market.fuel_boost_keeper: unchanged");
+    }
+
     Ok(())
 }
 
@@ -3916,52 +4874,390 @@ pub fn handle_initialize_protocol_if_shares_transfer_config(
     Ok(())
 }
 
-pub fn handle_update_protocol_if_shares_transfer_config(
-    ctx: Context<UpdateProtocolIfSharesTransferConfig>,
-    whitelisted_signers: Option<[Pubkey; 4]>,
-    max_transfer_per_epoch: Option<u128>,
+pub fn handle_initialize_insurance_fund_share_mint(
+    ctx: Context<InitializeInsuranceFundShareMint>,
+    market_index: u16,
 ) -> Result<()> {
-    let mut config = ctx.accounts.protocol_if_shares_transfer_config.load_mut()?;
+    msg!(
+        "initialized insurance fund share mint {} for spot market {}",
+        ctx.accounts.insurance_fund_share_mint.key(),
+        market_index
+    );
 
-    if let Some(whitelisted_signers) = whitelisted_signers {
-        msg!(
-            "whitelisted_signers: {:?} -> {:?}",
-            config.whitelisted_signers,
-            whitelisted_signers
-        );
-        config.whitelisted_signers = whitelisted_signers;
-    } else {
-        msg!("whitelisted_signers: unchanged");
-    }
+    Ok(())
+}
 
-    if let Some(max_transfer_per_epoch) = max_transfer_per_epoch {
-        msg!(
-            "max_transfer_per_epoch: {:?} -> {:?}",
-            config.max_transfer_per_epoch,
-            max_transfer_per_epoch
-        );
-        config.max_transfer_per_epoch = max_transfer_per_epoch;
-    } else {
-        msg!("max_transfer_per_epoch: unchanged");
-    }
+pub fn handle_initialize_senior_insurance_fund(
+    ctx: Context<InitializeSeniorInsuranceFund>,
+    market_index: u16,
+) -> Result<()> {
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    let mut senior_insurance_fund = ctx.accounts.senior_insurance_fund.load_init()?;
+
+    msg!(
+        "initializing senior insurance fund for spot market {}",
+        market_index
+    );
+
+    *senior_insurance_fund = SeniorInsuranceFund {
+        insurance_fund: InsuranceFund {
+            vault: ctx.accounts.senior_insurance_fund_vault.key(),
+            unstaking_period: spot_market.insurance_fund.unstaking_period,
+            ..InsuranceFund::default()
+        },
+        revenue_share: 0,
+        market_index,
+        padding: [0; 10],
+    };
+
+    // revenue settles and insurance payments now require the senior insurance fund
+    spot_market.set_flag(SpotMarketFlag::HasSeniorInsuranceFund, true);
 
     Ok(())
 }
 
-pub fn handle_initialize_prelaunch_oracle(
-    ctx: Context<InitializePrelaunchOracle>,
-    params: PrelaunchOracleParams,
+pub fn handle_update_senior_insurance_fund(
+    ctx: Context<UpdateSeniorInsuranceFund>,
+    unstaking_period: i64,
+    revenue_share: u32,
+    user_if_factor: u32,
+    total_if_factor: u32,
 ) -> Result<()> {
-    let mut oracle = ctx.accounts.prelaunch_oracle.load_init()?;
-    msg!("perp market {}", params.perp_market_index);
+    let senior_insurance_fund = &mut load_mut!(ctx.accounts.senior_insurance_fund)?;
 
-    oracle.perp_market_index = params.perp_market_index;
+    msg!(
+        "senior insurance fund for spot market {}",
+        senior_insurance_fund.market_index
+    );
+
+    validate!(
+        revenue_share <= IF_FACTOR_PRECISION.cast()?,
+        ErrorCode::DefaultError,
+        "revenue_share must be <= 100%"
+    )?;
+
+    msg!(
+        "senior_insurance_fund.revenue_share: {:?} -> {:?}",
+        senior_insurance_fund.revenue_share,
+        revenue_share
+    );
+
+    senior_insurance_fund.revenue_share = revenue_share;
+
+    // the senior tranche settles revenue with the junior tranche, so it has no settle period
+    update_insurance_fund_settings(
+        "senior_insurance_fund",
+        &mut senior_insurance_fund.insurance_fund,
+        unstaking_period,
+        0,
+        user_if_factor,
+        total_if_factor,
+    )
+}
+
+pub fn handle_initialize_perp_insurance_fund(
+    ctx: Context<InitializePerpInsuranceFund>,
+    perp_market_index: u16,
+) -> Result<()> {
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    let spot_market = load!(ctx.accounts.spot_market)?;
+    let mut perp_insurance_fund = ctx.accounts.perp_insurance_fund.load_init()?;
+
+    validate!(
+        perp_market.market_index == perp_market_index,
+        ErrorCode::InvalidMarketAccount,
+        "perp_market_index dne perp_market.index"
+    )?;
+
+    msg!(
+        "initializing perp insurance fund for perp market {}",
+        perp_market_index
+    );
+
+    *perp_insurance_fund = PerpInsuranceFund {
+        insurance_fund: InsuranceFund {
+            vault: ctx.accounts.perp_insurance_fund_vault.key(),
+            unstaking_period: spot_market.insurance_fund.unstaking_period,
+            ..InsuranceFund::default()
+        },
+        market_index: perp_market_index,
+        padding: [0; 14],
+    };
+
+    // insurance payments for the perp market now require the perp insurance fund
+    perp_market.has_perp_insurance_fund = true;
+
+    Ok(())
+}
+
+pub fn handle_update_perp_insurance_fund(
+    ctx: Context<UpdatePerpInsuranceFund>,
+    unstaking_period: i64,
+    revenue_settle_period: i64,
+    user_if_factor: u32,
+    total_if_factor: u32,
+) -> Result<()> {
+    let perp_insurance_fund = &mut load_mut!(ctx.accounts.perp_insurance_fund)?;
+
+    msg!(
+        "perp insurance fund for perp market {}",
+        perp_insurance_fund.market_index
+    );
+
+    update_insurance_fund_settings(
+        "perp_insurance_fund",
+        &mut perp_insurance_fund.insurance_fund,
+        unstaking_period,
+        revenue_settle_period,
+        user_if_factor,
+        total_if_factor,
+    )
+}
+
+pub fn handle_initialize_insurance_fund_performance(
+    ctx: Context<InitializeInsuranceFundPerformance>,
+    market_index: u16,
+) -> Result<()> {
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    let mut insurance_fund_performance = ctx.accounts.insurance_fund_performance.load_init()?;
+    let now = Clock::get()?.unix_timestamp;
+
+    msg!(
+        "initializing insurance fund performance for spot market {}",
+        market_index
+    );
+
+    *insurance_fund_performance = InsuranceFundPerformance {
+        market_index,
+        tranche: InsuranceFundTranche::Junior,
+        ..InsuranceFundPerformance::default()
+    };
+
+    // only track revenue and losses from here on
+    controller::insurance::update_insurance_fund_performance(
+        &mut insurance_fund_performance,
+        &spot_market.insurance_fund,
+        ctx.accounts.insurance_fund_vault.amount,
+        now,
+    )?;
+
+    spot_market.set_flag(SpotMarketFlag::HasInsuranceFundPerformance, true);
+
+    Ok(())
+}
+
+pub fn handle_initialize_senior_insurance_fund_performance(
+    ctx: Context<InitializeSeniorInsuranceFundPerformance>,
+    market_index: u16,
+) -> Result<()> {
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    let senior_insurance_fund = load!(ctx.accounts.senior_insurance_fund)?;
+    let mut insurance_fund_performance = ctx.accounts.insurance_fund_performance.load_init()?;
+    let now = Clock::get()?.unix_timestamp;
+
+    msg!(
+        "initializing senior insurance fund performance for spot market {}",
+        market_index
+    );
+
+    *insurance_fund_performance = InsuranceFundPerformance {
+        market_index,
+        tranche: InsuranceFundTranche::Senior,
+        ..InsuranceFundPerformance::default()
+    };
+
+    // only track revenue and losses from here on
+    controller::insurance::update_insurance_fund_performance(
+        &mut insurance_fund_performance,
+        &senior_insurance_fund.insurance_fund,
+        ctx.accounts.senior_insurance_fund_vault.amount,
+        now,
+    )?;
+
+    spot_market.set_flag(SpotMarketFlag::HasSeniorInsuranceFundPerformance, true);
+
+    Ok(())
+}
+
+pub fn handle_initialize_perp_insurance_fund_performance(
+    ctx: Context<InitializePerpInsuranceFundPerformance>,
+    perp_market_index: u16,
+) -> Result<()> {
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    let perp_insurance_fund = load!(ctx.accounts.perp_insurance_fund)?;
+    let mut insurance_fund_performance = ctx.accounts.insurance_fund_performance.load_init()?;
+    let now = Clock::get()?.unix_timestamp;
+
+    msg!(
+        "initializing perp insurance fund performance for perp market {}",
+        perp_market_index
+    );
+
+    *insurance_fund_performance = InsuranceFundPerformance {
+        market_index: perp_market_index,
+        tranche: InsuranceFundTranche::PerpMarket,
+        ..InsuranceFundPerformance::default()
+    };
+
+    // only track revenue and losses from here on
+    controller::insurance::update_insurance_fund_performance(
+        &mut insurance_fund_performance,
+        &perp_insurance_fund.insurance_fund,
+        ctx.accounts.perp_insurance_fund_vault.amount,
+        now,
+    )?;
+
+    perp_market.has_perp_insurance_fund_performance = true;
+
+    Ok(())
+}
+
+fn update_insurance_fund_settings(
+    name: &str,
+    insurance_fund: &mut InsuranceFund,
+    unstaking_period: i64,
+    revenue_settle_period: i64,
+    user_if_factor: u32,
+    total_if_factor: u32,
+) -> Result<()> {
+    validate!(
+        unstaking_period >= 0 && revenue_settle_period >= 0,
+        ErrorCode::DefaultError,
+        "unstaking_period and revenue_settle_period must be >= 0"
+    )?;
+
+    validate!(
+        user_if_factor <= total_if_factor,
+        ErrorCode::DefaultError,
+        "user_if_factor must be <= total_if_factor"
+    )?;
+
+    validate!(
+        total_if_factor <= IF_FACTOR_PRECISION.cast()?,
+        ErrorCode::DefaultError,
+        "total_if_factor must be <= 100%"
+    )?;
+
+    msg!(
+        "{}.unstaking_period: {:?} -> {:?}",
+        name,
+        insurance_fund.unstaking_period,
+        unstaking_period
+    );
+    msg!(
+        "{}.revenue_settle_period: {:?} -> {:?}",
+        name,
+        insurance_fund.revenue_settle_period,
+        revenue_settle_period
+    );
+    msg!(
+        "{}.user_if_factor: {:?} -> {:?}",
+        name,
+        insurance_fund.user_factor,
+        user_if_factor
+    );
+    msg!(
+        "{}.total_if_factor: {:?} -> {:?}",
+        name,
+        insurance_fund.total_factor,
+        total_if_factor
+    );
+
+    insurance_fund.unstaking_period = unstaking_period;
+    insurance_fund.revenue_settle_period = revenue_settle_period;
+    insurance_fund.user_factor = user_if_factor;
+    insurance_fund.total_factor = total_if_factor;
+
+    Ok(())
+}
+
+pub fn handle_update_protocol_if_shares_transfer_config(
+    ctx: Context<UpdateProtocolIfSharesTransferConfig>,
+    whitelisted_signers: Option<[Pubkey; 4]>,
+    max_transfer_per_epoch: Option<u128>,
+) -> Result<()> {
+    let mut config = ctx.accounts.protocol_if_shares_transfer_config.load_mut()?;
+
+    if let Some(whitelisted_signers) = whitelisted_signers {
+        msg!(
+            "whitelisted_signers: {:?} -> {:?}",
+            config.whitelisted_signers,
+            whitelisted_signers
+        );
+        config.whitelisted_signers = whitelisted_signers;
+    } else {
+        msg!("whitelisted_signers: unchanged");
+    }
+
+    if let Some(max_transfer_per_epoch) = max_transfer_per_epoch {
+        msg!(
+            "max_transfer_per_epoch: {:?} -> {:?}",
+            config.max_transfer_per_epoch,
+            max_transfer_per_epoch
+        );
+        config.max_transfer_per_epoch = max_transfer_per_epoch;
+    } else {
+        msg!("max_transfer_per_epoch: unchanged");
+    }
+
+    Ok(())
+}
+
+pub fn handle_initialize_referral_tier_config(
+    ctx: Context<InitializeReferralTierConfig>,
+) -> Result<()> {
+    ctx.accounts.state.has_referral_tier_config = true;
+
+    Ok(())
+}
+
+pub fn handle_update_referral_tier_config(
+    ctx: Context<UpdateReferralTierConfig>,
+    tiers: Option<[ReferralTier; 4]>,
+    sub_affiliate_reward_numerator: Option<u32>,
+) -> Result<()> {
+    let mut config = ctx.accounts.referral_tier_config.load_mut()?;
+
+    if let Some(tiers) = tiers {
+        msg!("tiers: {:?} -> {:?}", config.tiers, tiers);
+        config.tiers = tiers;
+    } else {
+        msg!("tiers: unchanged");
+    }
+
+    if let Some(sub_affiliate_reward_numerator) = sub_affiliate_reward_numerator {
+        msg!(
+            "sub_affiliate_reward_numerator: {:?} -> {:?}",
+            config.sub_affiliate_reward_numerator,
+            sub_affiliate_reward_numerator
+        );
+        config.sub_affiliate_reward_numerator = sub_affiliate_reward_numerator;
+    } else {
+        msg!("sub_affiliate_reward_numerator: unchanged");
+    }
+
+    validate_referral_tier_config(&config, &ctx.accounts.state.perp_fee_structure)?;
+
+    Ok(())
+}
+
+pub fn handle_initialize_prelaunch_oracle(
+    ctx: Context<InitializePrelaunchOracle>,
+    params: PrelaunchOracleParams,
+) -> Result<()> {
+    let mut oracle = ctx.accounts.prelaunch_oracle.load_init()?;
+    msg!("perp market {}", params.perp_market_index);
+
+    oracle.perp_market_index = params.perp_market_index;
     if let Some(price) = params.price {
         oracle.price = price;
     }
     if let Some(max_price) = params.max_price {
         oracle.max_price = max_price;
     }
+    if let Some(max_price_change_per_slot) = params.max_price_change_per_slot {
+        oracle.max_price_change_per_slot = max_price_change_per_slot;
+    }
 
     oracle.validate()?;
 
@@ -4003,6 +5299,17 @@ pub fn handle_update_prelaunch_oracle_params(
         msg!("max price: unchanged")
     }
 
+    if let Some(max_price_change_per_slot) = params.max_price_change_per_slot {
+        msg!(
+            "max price change per slot: {:?} -> {:?}",
+            oracle.max_price_change_per_slot,
+            max_price_change_per_slot
+        );
+        oracle.max_price_change_per_slot = max_price_change_per_slot;
+    } else {
+        msg!("max price change per slot: unchanged")
+    }
+
     oracle.validate()?;
 
     Ok(())
@@ -4024,6 +5331,97 @@ pub fn handle_delete_prelaunch_oracle(
     Ok(())
 }
 
+pub fn handle_initialize_oracle_aggregator<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, InitializeOracleAggregator<'info>>,
+    params: OracleAggregatorParams,
+) -> Result<()> {
+    let mut oracle_aggregator = ctx.accounts.oracle_aggregator.load_init()?;
+    msg!("oracle aggregator {}", params.aggregator_id);
+
+    let clock = Clock::get()?;
+    validate_oracle_aggregator_sources(ctx.remaining_accounts, &params.sources, clock.slot)?;
+
+    oracle_aggregator.aggregator_id = params.aggregator_id;
+    oracle_aggregator.set_sources(&params.sources)?;
+    oracle_aggregator.set_min_valid_sources(params.min_valid_sources)?;
+
+    Ok(())
+}
+
+pub fn handle_update_oracle_aggregator_sources<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, UpdateOracleAggregatorSources<'info>>,
+    params: OracleAggregatorParams,
+) -> Result<()> {
+    let mut oracle_aggregator = ctx.accounts.oracle_aggregator.load_mut()?;
+    msg!("oracle aggregator {}", params.aggregator_id);
+
+    let clock = Clock::get()?;
+    validate_oracle_aggregator_sources(ctx.remaining_accounts, &params.sources, clock.slot)?;
+
+    msg!(
+        "oracle_aggregator.sources: {:?} -> {:?}",
+        oracle_aggregator.get_sources(),
+        params.sources
+    );
+
+    oracle_aggregator.set_sources(&params.sources)?;
+
+    msg!(
+        "oracle_aggregator.min_valid_sources: {} -> {}",
+        oracle_aggregator.min_valid_sources,
+        params.min_valid_sources
+    );
+
+    oracle_aggregator.set_min_valid_sources(params.min_valid_sources)?;
+
+    Ok(())
+}
+
+fn validate_oracle_aggregator_sources(
+    remaining_accounts: &[AccountInfo],
+    sources: &[OracleAggregatorSourceParams],
+    slot: u64,
+) -> Result<()> {
+    validate!(
+        remaining_accounts.len() == sources.len(),
+        ErrorCode::DefaultError,
+        "must pass each source oracle account in remaining accounts"
+    )?;
+
+    for (source, account_info) in sources.iter().zip(remaining_accounts.iter()) {
+        validate!(
+            account_info.key == &source.oracle,
+            ErrorCode::DefaultError,
+            "oracle account info ({:?}) and ix data ({:?}) must match",
+            account_info.key,
+            source.oracle
+        )?;
+
+        OracleMap::validate_oracle_account_info(account_info)?;
+
+        // Verify oracle is readable
+        get_oracle_price(&source.oracle_source, account_info, slot)?;
+    }
+
+    Ok(())
+}
+
+pub fn handle_initialize_signed_price_account(
+    ctx: Context<InitializeSignedPriceAccount>,
+    feed_id: u16,
+    authority: Pubkey,
+) -> Result<()> {
+    OracleSource::SignedPrice.validate_available()?;
+
+    let mut signed_price_account = ctx.accounts.signed_price_account.load_init()?;
+    msg!("signed price feed {} authority {}", feed_id, authority);
+
+    signed_price_account.feed_id = feed_id;
+    signed_price_account.authority = authority;
+
+    Ok(())
+}
+
 pub fn handle_initialize_pyth_pull_oracle(
     ctx: Context<InitPythPullPriceFeed>,
     feed_id: [u8; 32],
@@ -4495,21 +5893,328 @@ pub struct InitUserFuel<'info> {
 }
 
 #[derive(Accounts)]
-pub struct InitializeProtocolIfSharesTransferConfig<'info> {
+#[instruction(season: u16)]
+pub struct CloseFuelSeason<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
     #[account(
         init,
-        seeds = [b"if_shares_transfer_config".as_ref()],
-        space = ProtocolIfSharesTransferConfig::SIZE,
+        seeds = [b"fuel_season".as_ref(), season.to_le_bytes().as_ref()],
+        space = FuelSeason::SIZE,
         bump,
         payer = admin
     )]
-    pub protocol_if_shares_transfer_config: AccountLoader<'info, ProtocolIfSharesTransferConfig>,
+    pub fuel_season: AccountLoader<'info, FuelSeason>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
     #[account(
-        has_one = admin
+        init,
+        seeds = [b"fuel_season_vault".as_ref(), season.to_le_bytes().as_ref()],
+        bump,
+        payer = admin,
+        token::mint = reward_mint,
+        token::authority = drift_signer
     )]
-    pub state: Box<Account<'info, State>>,
+    pub fuel_season_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        constraint = state.signer.eq(&drift_signer.key())
+    )]
+    /// CHECK: program signer
+    pub drift_signer: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFuelSeason<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(mut)]
+    pub fuel_season: AccountLoader<'info, FuelSeason>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProtocolIfSharesTransferConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        seeds = [b"if_shares_transfer_config".as_ref()],
+        space = ProtocolIfSharesTransferConfig::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub protocol_if_shares_transfer_config: AccountLoader<'info, ProtocolIfSharesTransferConfig>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct InitializeInsuranceFundShareMint<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        constraint = spot_market.load()?.mint.eq(&spot_market_mint.key())
+    )]
+    pub spot_market_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        seeds = [b"insurance_fund_share_mint".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+        payer = admin,
+        mint::decimals = spot_market_mint.decimals,
+        mint::authority = drift_signer
+    )]
+    pub insurance_fund_share_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        constraint = state.signer.eq(&drift_signer.key())
+    )]
+    /// CHECK: program signer
+    pub drift_signer: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct InitializeSeniorInsuranceFund<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        constraint = spot_market.load()?.mint.eq(&spot_market_mint.key())
+    )]
+    pub spot_market_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        seeds = [b"senior_insurance_fund".as_ref(), market_index.to_le_bytes().as_ref()],
+        space = SeniorInsuranceFund::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub senior_insurance_fund: AccountLoader<'info, SeniorInsuranceFund>,
+    #[account(
+        init,
+        seeds = [b"senior_insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+        payer = admin,
+        token::mint = spot_market_mint,
+        token::authority = drift_signer
+    )]
+    pub senior_insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        constraint = state.signer.eq(&drift_signer.key())
+    )]
+    /// CHECK: program signer
+    pub drift_signer: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSeniorInsuranceFund<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(mut)]
+    pub senior_insurance_fund: AccountLoader<'info, SeniorInsuranceFund>,
+}
+
+#[derive(Accounts)]
+#[instruction(perp_market_index: u16)]
+pub struct InitializePerpInsuranceFund<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(mut)]
+    pub perp_market: AccountLoader<'info, PerpMarket>,
+    #[account(
+        seeds = [b"spot_market", QUOTE_SPOT_MARKET_INDEX.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        constraint = spot_market.load()?.mint.eq(&spot_market_mint.key())
+    )]
+    pub spot_market_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        seeds = [b"perp_insurance_fund".as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        space = PerpInsuranceFund::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub perp_insurance_fund: AccountLoader<'info, PerpInsuranceFund>,
+    #[account(
+        init,
+        seeds = [b"perp_insurance_fund_vault".as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        bump,
+        payer = admin,
+        token::mint = spot_market_mint,
+        token::authority = drift_signer
+    )]
+    pub perp_insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        constraint = state.signer.eq(&drift_signer.key())
+    )]
+    /// CHECK: program signer
+    pub drift_signer: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePerpInsuranceFund<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(mut)]
+    pub perp_insurance_fund: AccountLoader<'info, PerpInsuranceFund>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct InitializeInsuranceFundPerformance<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        seeds = [b"insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        seeds = [b"insurance_fund_performance".as_ref(), market_index.to_le_bytes().as_ref()],
+        space = InsuranceFundPerformance::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub insurance_fund_performance: AccountLoader<'info, InsuranceFundPerformance>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct InitializeSeniorInsuranceFundPerformance<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        seeds = [b"senior_insurance_fund".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub senior_insurance_fund: AccountLoader<'info, SeniorInsuranceFund>,
+    #[account(
+        seeds = [b"senior_insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub senior_insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        seeds = [b"senior_insurance_fund_performance".as_ref(), market_index.to_le_bytes().as_ref()],
+        space = InsuranceFundPerformance::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub insurance_fund_performance: AccountLoader<'info, InsuranceFundPerformance>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(perp_market_index: u16)]
+pub struct InitializePerpInsuranceFundPerformance<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"perp_market", perp_market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub perp_market: AccountLoader<'info, PerpMarket>,
+    #[account(
+        seeds = [b"perp_insurance_fund".as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub perp_insurance_fund: AccountLoader<'info, PerpInsuranceFund>,
+    #[account(
+        seeds = [b"perp_insurance_fund_vault".as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub perp_insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        seeds = [b"perp_insurance_fund_performance".as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        space = InsuranceFundPerformance::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub insurance_fund_performance: AccountLoader<'info, InsuranceFundPerformance>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
 }
@@ -4530,6 +6235,451 @@ pub struct UpdateProtocolIfSharesTransferConfig<'info> {
     pub state: Box<Account<'info, State>>,
 }
 
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct InitializePerpMarketFeeStructure<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"perp_market", market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub perp_market: AccountLoader<'info, PerpMarket>,
+    #[account(
+        init,
+        seeds = [b"perp_market_fee_structure".as_ref(), market_index.to_le_bytes().as_ref()],
+        space = MarketFeeStructure::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub market_fee_structure: Box<Account<'info, MarketFeeStructure>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct InitializeSpotMarketFeeStructure<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        init,
+        seeds = [b"spot_market_fee_structure".as_ref(), market_index.to_le_bytes().as_ref()],
+        space = MarketFeeStructure::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub market_fee_structure: Box<Account<'info, MarketFeeStructure>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMarketFeeStructure<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(mut)]
+    pub market_fee_structure: Box<Account<'info, MarketFeeStructure>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePerpFeeTierQualifications<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        init,
+        seeds = [b"perp_fee_tier_qualifications".as_ref()],
+        space = FeeTierQualifications::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub fee_tier_qualifications: Box<Account<'info, FeeTierQualifications>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeSpotFeeTierQualifications<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        init,
+        seeds = [b"spot_fee_tier_qualifications".as_ref()],
+        space = FeeTierQualifications::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub fee_tier_qualifications: Box<Account<'info, FeeTierQualifications>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeTierQualifications<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(mut)]
+    pub fee_tier_qualifications: Box<Account<'info, FeeTierQualifications>>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct InitializePerpMarketOracleCircuitBreakerWindow<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        seeds = [b"perp_market", market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub perp_market: AccountLoader<'info, PerpMarket>,
+    #[account(
+        init,
+        seeds = [b"perp_market_oracle_circuit_breaker_window".as_ref(), market_index.to_le_bytes().as_ref()],
+        space = OracleCircuitBreakerWindow::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub oracle_circuit_breaker_window: Box<Account<'info, OracleCircuitBreakerWindow>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct InitializeSpotMarketOracleCircuitBreakerWindow<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        init,
+        seeds = [b"spot_market_oracle_circuit_breaker_window".as_ref(), market_index.to_le_bytes().as_ref()],
+        space = OracleCircuitBreakerWindow::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub oracle_circuit_breaker_window: Box<Account<'info, OracleCircuitBreakerWindow>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOracleCircuitBreakerWindow<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(mut)]
+    pub oracle_circuit_breaker_window: Box<Account<'info, OracleCircuitBreakerWindow>>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct InitializePerpMarketDepthBounds<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"perp_market", market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub perp_market: AccountLoader<'info, PerpMarket>,
+    #[account(
+        init,
+        seeds = [b"perp_market_depth_bounds".as_ref(), market_index.to_le_bytes().as_ref()],
+        space = PerpMarketDepthBounds::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub depth_bounds: Box<Account<'info, PerpMarketDepthBounds>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePerpMarketDepthBounds<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(mut)]
+    pub depth_bounds: Box<Account<'info, PerpMarketDepthBounds>>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct DeletePerpMarketDepthBounds<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"perp_market", market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub perp_market: AccountLoader<'info, PerpMarket>,
+    #[account(
+        mut,
+        seeds = [b"perp_market_depth_bounds".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+        close = admin
+    )]
+    pub depth_bounds: Box<Account<'info, PerpMarketDepthBounds>>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct DeletePerpMarketFeeStructure<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"perp_market", market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub perp_market: AccountLoader<'info, PerpMarket>,
+    #[account(
+        mut,
+        seeds = [b"perp_market_fee_structure".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+        close = admin
+    )]
+    pub market_fee_structure: Box<Account<'info, MarketFeeStructure>>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct DeleteSpotMarketFeeStructure<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        mut,
+        seeds = [b"spot_market_fee_structure".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+        close = admin
+    )]
+    pub market_fee_structure: Box<Account<'info, MarketFeeStructure>>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct InitializeMakerRewardsProgram<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"perp_market", market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub perp_market: AccountLoader<'info, PerpMarket>,
+    #[account(
+        init,
+        seeds = [b"maker_rewards_program".as_ref(), market_index.to_le_bytes().as_ref()],
+        space = MakerRewardsProgram::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub maker_rewards_program: AccountLoader<'info, MakerRewardsProgram>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        seeds = [b"maker_rewards_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+        payer = admin,
+        token::mint = reward_mint,
+        token::authority = drift_signer
+    )]
+    pub maker_rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        constraint = state.signer.eq(&drift_signer.key())
+    )]
+    /// CHECK: program signer
+    pub drift_signer: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct UpdateMakerRewardsProgram<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"maker_rewards_program".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub maker_rewards_program: AccountLoader<'info, MakerRewardsProgram>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeReferralTierConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        seeds = [b"referral_tier_config".as_ref()],
+        space = ReferralTierConfig::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub referral_tier_config: AccountLoader<'info, ReferralTierConfig>,
+    #[account(
+        mut,
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReferralTierConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"referral_tier_config".as_ref()],
+        bump,
+    )]
+    pub referral_tier_config: AccountLoader<'info, ReferralTierConfig>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: OracleAggregatorParams,)]
+pub struct InitializeOracleAggregator<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        seeds = [b"oracle_aggregator".as_ref(), params.aggregator_id.to_le_bytes().as_ref()],
+        space = OracleAggregator::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub oracle_aggregator: AccountLoader<'info, OracleAggregator>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: OracleAggregatorParams,)]
+pub struct UpdateOracleAggregatorSources<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"oracle_aggregator".as_ref(), params.aggregator_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub oracle_aggregator: AccountLoader<'info, OracleAggregator>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+#[derive(Accounts)]
+#[instruction(feed_id: u16,)]
+pub struct InitializeSignedPriceAccount<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        seeds = [b"signed_price_account".as_ref(), feed_id.to_le_bytes().as_ref()],
+        space = SignedPriceAccount::SIZE,
+        bump,
+        payer = admin
+    )]
+    pub signed_price_account: AccountLoader<'info, SignedPriceAccount>,
+    #[account(
+        has_one = admin
+    )]
+    pub state: Box<Account<'info, State>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 #[instruction(params: PrelaunchOracleParams,)]
 pub struct InitializePrelaunchOracle<'info> {
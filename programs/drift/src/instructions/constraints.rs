@@ -2,12 +2,14 @@ use anchor_lang::accounts::account::Account;
 use anchor_lang::accounts::account_loader::AccountLoader;
 use anchor_lang::accounts::signer::Signer;
 use anchor_lang::prelude::{AccountInfo, Pubkey};
+use anchor_lang::Key;
 
 use crate::error::ErrorCode;
 use crate::state::insurance_fund_stake::InsuranceFundStake;
 use crate::state::perp_market::{MarketStatus, PerpMarket};
 use crate::state::spot_market::SpotMarket;
 use crate::state::state::{ExchangeStatus, State};
+use crate::state::term_loan::TermLoan;
 use crate::state::user::{User, UserStats};
 use crate::validate;
 use solana_program::msg;
@@ -37,6 +39,22 @@ pub fn is_stats_for_if_stake(
     Ok(user_stats.authority.eq(&if_stake.authority))
 }
 
+pub fn is_lender_for_term_loan(
+    term_loan: &AccountLoader<TermLoan>,
+    user: &AccountLoader<User>,
+) -> anchor_lang::Result<bool> {
+    let term_loan = term_loan.load()?;
+    Ok(term_loan.lender.eq(&user.key()))
+}
+
+pub fn is_borrower_for_term_loan(
+    term_loan: &AccountLoader<TermLoan>,
+    user: &AccountLoader<User>,
+) -> anchor_lang::Result<bool> {
+    let term_loan = term_loan.load()?;
+    Ok(term_loan.borrower.eq(&user.key()))
+}
+
 pub fn perp_market_valid(market: &AccountLoader<PerpMarket>) -> anchor_lang::Result<()> {
     if market.load()?.status == MarketStatus::Delisted {
         return Err(ErrorCode::MarketDelisted.into());
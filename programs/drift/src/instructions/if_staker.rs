@@ -1,11 +1,17 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{TokenAccount, TokenInterface};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-use crate::controller::insurance::transfer_protocol_insurance_fund_stake;
+use crate::controller::insurance::{
+    transfer_protocol_insurance_fund_stake, InsuranceFundStakePerformance,
+};
 use crate::error::ErrorCode;
 use crate::instructions::constraints::*;
-use crate::optional_accounts::get_token_mint;
-use crate::state::insurance_fund_stake::{InsuranceFundStake, ProtocolIfSharesTransferConfig};
+use crate::math::safe_math::SafeMath;
+use crate::optional_accounts::{get_senior_insurance_fund, get_token_mint};
+use crate::state::insurance_fund_stake::{
+    InsuranceFundStake, InsuranceFundTranche, PerpInsuranceFund, ProtocolIfSharesTransferConfig,
+    SeniorInsuranceFund,
+};
 use crate::state::paused_operations::InsuranceFundOperation;
 use crate::state::perp_market::MarketStatus;
 use crate::state::spot_market::SpotMarket;
@@ -14,7 +20,7 @@ use crate::state::traits::Size;
 use crate::state::user::UserStats;
 use crate::validate;
 use crate::{controller, math};
-use crate::{load_mut, QUOTE_SPOT_MARKET_INDEX};
+use crate::{load, load_mut, QUOTE_SPOT_MARKET_INDEX};
 
 pub fn handle_initialize_insurance_fund_stake(
     ctx: Context<InitializeInsuranceFundStake>,
@@ -59,6 +65,8 @@ pub fn handle_add_insurance_fund_stake<'c: 'info, 'info>(
     let state = &ctx.accounts.state;
 
     let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+    let mut senior_insurance_fund =
+        get_senior_insurance_fund(remaining_accounts_iter, spot_market)?;
     let mint = get_token_mint(remaining_accounts_iter)?;
 
     validate!(
@@ -73,6 +81,8 @@ pub fn handle_add_insurance_fund_stake<'c: 'info, 'info>(
         "insurance_fund_stake does not match market_index"
     )?;
 
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::Junior)?;
+
     validate!(
         spot_market.status != MarketStatus::Initialized,
         ErrorCode::InvalidSpotMarketState,
@@ -91,6 +101,7 @@ pub fn handle_add_insurance_fund_stake<'c: 'info, 'info>(
         controller::insurance::attempt_settle_revenue_to_insurance_fund(
             &ctx.accounts.spot_market_vault,
             &ctx.accounts.insurance_fund_vault,
+            &mut senior_insurance_fund,
             spot_market,
             now,
             &ctx.accounts.token_program,
@@ -108,8 +119,11 @@ pub fn handle_add_insurance_fund_stake<'c: 'info, 'info>(
         )?;
     }
 
+    // shares are only minted for what the vault receives net of any token-2022 transfer fee
+    let transfer_fee = controller::token::calculate_transfer_fee(&mint, amount)?;
+
     controller::insurance::add_insurance_fund_stake(
-        amount,
+        amount.safe_sub(transfer_fee)?,
         ctx.accounts.insurance_fund_vault.amount,
         insurance_fund_stake,
         user_stats,
@@ -124,6 +138,7 @@ pub fn handle_add_insurance_fund_stake<'c: 'info, 'info>(
         &ctx.accounts.authority,
         amount,
         &mint,
+        Some(remaining_accounts_iter),
     )?;
 
     Ok(())
@@ -151,6 +166,8 @@ pub fn handle_request_remove_insurance_fund_stake(
         "insurance_fund_stake does not match market_index"
     )?;
 
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::Junior)?;
+
     validate!(
         insurance_fund_stake.last_withdraw_request_shares == 0,
         ErrorCode::IFWithdrawRequestInProgress,
@@ -200,6 +217,8 @@ pub fn handle_cancel_request_remove_insurance_fund_stake(
         "insurance_fund_stake does not match market_index"
     )?;
 
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::Junior)?;
+
     validate!(
         insurance_fund_stake.last_withdraw_request_shares != 0,
         ErrorCode::NoIFWithdrawRequestInProgress,
@@ -246,6 +265,8 @@ pub fn handle_remove_insurance_fund_stake<'c: 'info, 'info>(
         "insurance_fund_stake does not match market_index"
     )?;
 
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::Junior)?;
+
     // check if spot market is healthy
     validate!(
         spot_market.is_healthy_utilization()?,
@@ -269,6 +290,7 @@ pub fn handle_remove_insurance_fund_stake<'c: 'info, 'info>(
         state.signer_nonce,
         amount,
         &mint,
+        Some(remaining_accounts_iter),
     )?;
 
     ctx.accounts.insurance_fund_vault.reload()?;
@@ -284,186 +306,1306 @@ pub fn handle_remove_insurance_fund_stake<'c: 'info, 'info>(
     Ok(())
 }
 
-pub fn handle_transfer_protocol_if_shares(
-    ctx: Context<TransferProtocolIfShares>,
+pub fn handle_view_insurance_fund_stake_performance(
+    ctx: Context<ViewInsuranceFundStakePerformance>,
+    market_index: u16,
+) -> Result<InsuranceFundStakePerformance> {
+    let insurance_fund_stake = load!(ctx.accounts.insurance_fund_stake)?;
+    let spot_market = load!(ctx.accounts.spot_market)?;
+
+    validate!(
+        insurance_fund_stake.market_index == market_index,
+        ErrorCode::IncorrectSpotMarketAccountPassed,
+        "insurance_fund_stake does not match market_index"
+    )?;
+
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::Junior)?;
+
+    let performance = controller::insurance::calculate_insurance_fund_stake_performance(
+        ctx.accounts.insurance_fund_vault.amount,
+        &insurance_fund_stake,
+        &spot_market,
+    )?;
+
+    Ok(performance)
+}
+
+pub fn handle_tokenize_insurance_fund_stake(
+    ctx: Context<TokenizeInsuranceFundStake>,
     market_index: u16,
     shares: u128,
 ) -> Result<()> {
     let now = Clock::get()?.unix_timestamp;
+    let insurance_fund_stake = &mut load_mut!(ctx.accounts.insurance_fund_stake)?;
+    let user_stats = &mut load_mut!(ctx.accounts.user_stats)?;
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    let state = &ctx.accounts.state;
 
     validate!(
-        market_index == QUOTE_SPOT_MARKET_INDEX,
-        ErrorCode::DefaultError,
-        "must be if for quote spot market"
+        !spot_market.is_insurance_fund_operation_paused(InsuranceFundOperation::Remove),
+        ErrorCode::InsuranceFundOperationPaused,
+        "if staking remove disabled",
     )?;
 
-    let mut transfer_config = ctx.accounts.transfer_config.load_mut()?;
+    validate!(
+        insurance_fund_stake.market_index == market_index,
+        ErrorCode::IncorrectSpotMarketAccountPassed,
+        "insurance_fund_stake does not match market_index"
+    )?;
 
-    transfer_config.validate_signer(ctx.accounts.signer.key)?;
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::Junior)?;
 
-    transfer_config.update_epoch(now)?;
-    transfer_config.validate_transfer(shares)?;
-    transfer_config.current_epoch_transfer += shares;
+    let token_amount = controller::insurance::tokenize_insurance_fund_stake(
+        shares,
+        ctx.accounts.insurance_fund_vault.amount,
+        insurance_fund_stake,
+        user_stats,
+        spot_market,
+        now,
+    )?;
 
-    let mut if_stake = ctx.accounts.insurance_fund_stake.load_mut()?;
-    let mut user_stats = ctx.accounts.user_stats.load_mut()?;
-    let mut spot_market = ctx.accounts.spot_market.load_mut()?;
+    controller::token::mint_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.insurance_fund_share_mint,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.drift_signer,
+        state.signer_nonce,
+        token_amount,
+    )?;
 
-    transfer_protocol_insurance_fund_stake(
+    Ok(())
+}
+
+pub fn handle_redeem_insurance_fund_share_tokens(
+    ctx: Context<RedeemInsuranceFundShareTokens>,
+    market_index: u16,
+    amount: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let insurance_fund_stake = &mut load_mut!(ctx.accounts.insurance_fund_stake)?;
+    let user_stats = &mut load_mut!(ctx.accounts.user_stats)?;
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+
+    validate!(
+        !spot_market.is_insurance_fund_operation_paused(InsuranceFundOperation::Add),
+        ErrorCode::InsuranceFundOperationPaused,
+        "if staking add disabled",
+    )?;
+
+    validate!(
+        insurance_fund_stake.market_index == market_index,
+        ErrorCode::IncorrectSpotMarketAccountPassed,
+        "insurance_fund_stake does not match market_index"
+    )?;
+
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::Junior)?;
+
+    // only whole shares are redeemed, the remainder stays with the user
+    let burn_amount = controller::insurance::redeem_insurance_fund_share_tokens(
+        amount,
         ctx.accounts.insurance_fund_vault.amount,
-        shares,
-        &mut if_stake,
-        &mut user_stats,
-        &mut spot_market,
-        Clock::get()?.unix_timestamp,
-        ctx.accounts.state.signer,
+        insurance_fund_stake,
+        user_stats,
+        spot_market,
+        now,
+    )?;
+
+    controller::token::burn_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.insurance_fund_share_mint,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.authority,
+        burn_amount,
     )?;
 
     Ok(())
 }
 
-#[derive(Accounts)]
-#[instruction(
-    market_index: u16,
+#[access_control(
+    deposit_not_paused(&ctx.accounts.state)
 )]
-pub struct InitializeInsuranceFundStake<'info> {
-    #[account(
-        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub spot_market: AccountLoader<'info, SpotMarket>,
-    #[account(
-        init,
-        seeds = [b"insurance_fund_stake", authority.key.as_ref(), market_index.to_le_bytes().as_ref()],
-        space = InsuranceFundStake::SIZE,
-        bump,
-        payer = payer
-    )]
-    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
-    #[account(
-        mut,
-        has_one = authority
-    )]
-    pub user_stats: AccountLoader<'info, UserStats>,
-    pub state: Box<Account<'info, State>>,
-    pub authority: Signer<'info>,
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    pub rent: Sysvar<'info, Rent>,
-    pub system_program: Program<'info, System>,
-}
+pub fn handle_initialize_senior_insurance_fund_stake(
+    ctx: Context<InitializeSeniorInsuranceFundStake>,
+    market_index: u16,
+) -> Result<()> {
+    let mut if_stake = ctx
+        .accounts
+        .insurance_fund_stake
+        .load_init()
+        .or(Err(ErrorCode::UnableToLoadAccountLoader))?;
 
-#[derive(Accounts)]
-#[instruction(market_index: u16)]
-pub struct AddInsuranceFundStake<'info> {
-    pub state: Box<Account<'info, State>>,
-    #[account(
-        mut,
-        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub spot_market: AccountLoader<'info, SpotMarket>,
-    #[account(
-        mut,
-        has_one = authority,
-    )]
-    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
-    #[account(
-        mut,
-        has_one = authority,
-    )]
-    pub user_stats: AccountLoader<'info, UserStats>,
-    pub authority: Signer<'info>,
-    #[account(
-        mut,
-        seeds = [b"spot_market_vault".as_ref(), market_index.to_le_bytes().as_ref()],
-        bump,
-    )]
-    pub spot_market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
-    #[account(
-        mut,
-        seeds = [b"insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
-        bump,
-    )]
-    pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
 
-    #[account(
-        constraint = state.signer.eq(&drift_signer.key())
-    )]
-    /// CHECK: forced drift_signer
-    pub drift_signer: AccountInfo<'info>,
-    #[account(
-        mut,
-        token::mint = insurance_fund_vault.mint,
-        token::authority = authority
-    )]
-    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
-    pub token_program: Interface<'info, TokenInterface>,
-}
+    *if_stake = InsuranceFundStake::new(*ctx.accounts.authority.key, market_index, now);
+    if_stake.tranche = InsuranceFundTranche::Senior;
+    if_stake.if_base = ctx
+        .accounts
+        .senior_insurance_fund
+        .load()?
+        .insurance_fund
+        .shares_base;
 
-#[derive(Accounts)]
-#[instruction(market_index: u16,)]
-pub struct RequestRemoveInsuranceFundStake<'info> {
-    #[account(
-        mut,
-        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub spot_market: AccountLoader<'info, SpotMarket>,
-    #[account(
-        mut,
-        has_one = authority,
-    )]
-    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
-    #[account(
-        mut,
-        has_one = authority,
-    )]
-    pub user_stats: AccountLoader<'info, UserStats>,
-    pub authority: Signer<'info>,
-    #[account(
-        mut,
-        seeds = [b"insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
-        bump,
-    )]
-    pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    let spot_market = ctx.accounts.spot_market.load()?;
+
+    validate!(
+        !spot_market.is_insurance_fund_operation_paused(InsuranceFundOperation::Init),
+        ErrorCode::InsuranceFundOperationPaused,
+        "if staking init disabled",
+    )?;
+
+    Ok(())
 }
 
-#[derive(Accounts)]
-#[instruction(market_index: u16,)]
-pub struct RemoveInsuranceFundStake<'info> {
-    pub state: Box<Account<'info, State>>,
-    #[account(
-        mut,
-        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub spot_market: AccountLoader<'info, SpotMarket>,
-    #[account(
-        mut,
-        has_one = authority,
-    )]
-    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
-    #[account(
-        mut,
-        has_one = authority,
-    )]
-    pub user_stats: AccountLoader<'info, UserStats>,
-    pub authority: Signer<'info>,
-    #[account(
-        mut,
-        seeds = [b"insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
-        bump,
-    )]
-    pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
-    #[account(
-        constraint = state.signer.eq(&drift_signer.key())
-    )]
-    /// CHECK: forced drift_signer
-    pub drift_signer: AccountInfo<'info>,
-    #[account(
-        mut,
-        token::mint = insurance_fund_vault.mint,
+pub fn handle_add_senior_insurance_fund_stake<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, AddSeniorInsuranceFundStake<'info>>,
+    market_index: u16,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Err(ErrorCode::InsufficientDeposit.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let insurance_fund_stake = &mut load_mut!(ctx.accounts.insurance_fund_stake)?;
+    let user_stats = &mut load_mut!(ctx.accounts.user_stats)?;
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    let senior_insurance_fund = &mut load_mut!(ctx.accounts.senior_insurance_fund)?;
+
+    let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+    let mint = get_token_mint(remaining_accounts_iter)?;
+
+    validate!(
+        !spot_market.is_insurance_fund_operation_paused(InsuranceFundOperation::Add),
+        ErrorCode::InsuranceFundOperationPaused,
+        "if staking add disabled",
+    )?;
+
+    validate!(
+        insurance_fund_stake.market_index == market_index,
+        ErrorCode::IncorrectSpotMarketAccountPassed,
+        "insurance_fund_stake does not match market_index"
+    )?;
+
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::Senior)?;
+
+    validate!(
+        spot_market.status != MarketStatus::Initialized,
+        ErrorCode::InvalidSpotMarketState,
+        "spot market = {} not active for insurance_fund_stake",
+        spot_market.market_index
+    )?;
+
+    validate!(
+        insurance_fund_stake.last_withdraw_request_shares == 0
+            && insurance_fund_stake.last_withdraw_request_value == 0,
+        ErrorCode::IFWithdrawRequestInProgress,
+        "withdraw request in progress"
+    )?;
+
+    // shares are only minted for what the vault receives net of any token-2022 transfer fee
+    let transfer_fee = controller::token::calculate_transfer_fee(&mint, amount)?;
+    let insurance_vault_amount = ctx.accounts.senior_insurance_fund_vault.amount;
+
+    controller::insurance::with_senior_insurance_fund(
+        spot_market,
+        senior_insurance_fund,
+        |spot_market| {
+            controller::insurance::add_insurance_fund_stake(
+                amount.safe_sub(transfer_fee)?,
+                insurance_vault_amount,
+                insurance_fund_stake,
+                user_stats,
+                spot_market,
+                now,
+            )
+        },
+    )?;
+
+    controller::token::receive(
+        &ctx.accounts.token_program,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.senior_insurance_fund_vault,
+        &ctx.accounts.authority,
+        amount,
+        &mint,
+        Some(remaining_accounts_iter),
+    )?;
+
+    Ok(())
+}
+
+pub fn handle_request_remove_senior_insurance_fund_stake(
+    ctx: Context<RequestRemoveSeniorInsuranceFundStake>,
+    market_index: u16,
+    amount: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let insurance_fund_stake = &mut load_mut!(ctx.accounts.insurance_fund_stake)?;
+    let user_stats = &mut load_mut!(ctx.accounts.user_stats)?;
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    let senior_insurance_fund = &mut load_mut!(ctx.accounts.senior_insurance_fund)?;
+
+    validate!(
+        !spot_market.is_insurance_fund_operation_paused(InsuranceFundOperation::RequestRemove),
+        ErrorCode::InsuranceFundOperationPaused,
+        "if staking request remove disabled",
+    )?;
+
+    validate!(
+        insurance_fund_stake.market_index == market_index,
+        ErrorCode::IncorrectSpotMarketAccountPassed,
+        "insurance_fund_stake does not match market_index"
+    )?;
+
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::Senior)?;
+
+    validate!(
+        insurance_fund_stake.last_withdraw_request_shares == 0,
+        ErrorCode::IFWithdrawRequestInProgress,
+        "Withdraw request is already in progress"
+    )?;
+
+    let insurance_vault_amount = ctx.accounts.senior_insurance_fund_vault.amount;
+
+    controller::insurance::with_senior_insurance_fund(
+        spot_market,
+        senior_insurance_fund,
+        |spot_market| {
+            let n_shares = math::insurance::vault_amount_to_if_shares(
+                amount,
+                spot_market.insurance_fund.total_shares,
+                insurance_vault_amount,
+            )?;
+
+            validate!(
+                n_shares > 0,
+                ErrorCode::IFWithdrawRequestTooSmall,
+                "Requested lp_shares = 0"
+            )?;
+
+            let user_if_shares = insurance_fund_stake.checked_if_shares(spot_market)?;
+            validate!(user_if_shares >= n_shares, ErrorCode::InsufficientIFShares)?;
+
+            controller::insurance::request_remove_insurance_fund_stake(
+                n_shares,
+                insurance_vault_amount,
+                insurance_fund_stake,
+                user_stats,
+                spot_market,
+                now,
+            )
+        },
+    )?;
+
+    Ok(())
+}
+
+pub fn handle_cancel_request_remove_senior_insurance_fund_stake(
+    ctx: Context<RequestRemoveSeniorInsuranceFundStake>,
+    market_index: u16,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let insurance_fund_stake = &mut load_mut!(ctx.accounts.insurance_fund_stake)?;
+    let user_stats = &mut load_mut!(ctx.accounts.user_stats)?;
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    let senior_insurance_fund = &mut load_mut!(ctx.accounts.senior_insurance_fund)?;
+
+    validate!(
+        insurance_fund_stake.market_index == market_index,
+        ErrorCode::IncorrectSpotMarketAccountPassed,
+        "insurance_fund_stake does not match market_index"
+    )?;
+
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::Senior)?;
+
+    validate!(
+        insurance_fund_stake.last_withdraw_request_shares != 0,
+        ErrorCode::NoIFWithdrawRequestInProgress,
+        "No withdraw request in progress"
+    )?;
+
+    let insurance_vault_amount = ctx.accounts.senior_insurance_fund_vault.amount;
+
+    controller::insurance::with_senior_insurance_fund(
+        spot_market,
+        senior_insurance_fund,
+        |spot_market| {
+            controller::insurance::cancel_request_remove_insurance_fund_stake(
+                insurance_vault_amount,
+                insurance_fund_stake,
+                user_stats,
+                spot_market,
+                now,
+            )
+        },
+    )?;
+
+    Ok(())
+}
+
+#[access_control(
+    withdraw_not_paused(&ctx.accounts.state)
+)]
+pub fn handle_remove_senior_insurance_fund_stake<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, RemoveSeniorInsuranceFundStake<'info>>,
+    market_index: u16,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let insurance_fund_stake = &mut load_mut!(ctx.accounts.insurance_fund_stake)?;
+    let user_stats = &mut load_mut!(ctx.accounts.user_stats)?;
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    let senior_insurance_fund = &mut load_mut!(ctx.accounts.senior_insurance_fund)?;
+    let state = &ctx.accounts.state;
+
+    let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+    let mint = get_token_mint(remaining_accounts_iter)?;
+
+    validate!(
+        !spot_market.is_insurance_fund_operation_paused(InsuranceFundOperation::Remove),
+        ErrorCode::InsuranceFundOperationPaused,
+        "if staking remove disabled",
+    )?;
+
+    validate!(
+        insurance_fund_stake.market_index == market_index,
+        ErrorCode::IncorrectSpotMarketAccountPassed,
+        "insurance_fund_stake does not match market_index"
+    )?;
+
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::Senior)?;
+
+    let insurance_vault_amount = ctx.accounts.senior_insurance_fund_vault.amount;
+
+    let amount = controller::insurance::with_senior_insurance_fund(
+        spot_market,
+        senior_insurance_fund,
+        |spot_market| {
+            controller::insurance::remove_insurance_fund_stake(
+                insurance_vault_amount,
+                insurance_fund_stake,
+                user_stats,
+                spot_market,
+                now,
+            )
+        },
+    )?;
+
+    controller::token::send_from_program_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.senior_insurance_fund_vault,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.drift_signer,
+        state.signer_nonce,
+        amount,
+        &mint,
+        Some(remaining_accounts_iter),
+    )?;
+
+    ctx.accounts.senior_insurance_fund_vault.reload()?;
+    validate!(
+        ctx.accounts.senior_insurance_fund_vault.amount > 0,
+        ErrorCode::InvalidIFDetected,
+        "senior_insurance_fund_vault.amount must remain > 0"
+    )?;
+
+    Ok(())
+}
+
+pub fn handle_initialize_perp_insurance_fund_stake(
+    ctx: Context<InitializePerpInsuranceFundStake>,
+    perp_market_index: u16,
+) -> Result<()> {
+    let mut if_stake = ctx
+        .accounts
+        .insurance_fund_stake
+        .load_init()
+        .or(Err(ErrorCode::UnableToLoadAccountLoader))?;
+
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    *if_stake = InsuranceFundStake::new(*ctx.accounts.authority.key, perp_market_index, now);
+    if_stake.tranche = InsuranceFundTranche::PerpMarket;
+    if_stake.if_base = ctx
+        .accounts
+        .perp_insurance_fund
+        .load()?
+        .insurance_fund
+        .shares_base;
+
+    let spot_market = ctx.accounts.spot_market.load()?;
+
+    validate!(
+        !spot_market.is_insurance_fund_operation_paused(InsuranceFundOperation::Init),
+        ErrorCode::InsuranceFundOperationPaused,
+        "if staking init disabled",
+    )?;
+
+    Ok(())
+}
+
+#[access_control(
+    deposit_not_paused(&ctx.accounts.state)
+)]
+pub fn handle_add_perp_insurance_fund_stake<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, AddPerpInsuranceFundStake<'info>>,
+    perp_market_index: u16,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Err(ErrorCode::InsufficientDeposit.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let insurance_fund_stake = &mut load_mut!(ctx.accounts.insurance_fund_stake)?;
+    let user_stats = &mut load_mut!(ctx.accounts.user_stats)?;
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    let perp_insurance_fund = &mut load_mut!(ctx.accounts.perp_insurance_fund)?;
+
+    let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+    let mint = get_token_mint(remaining_accounts_iter)?;
+
+    validate!(
+        !spot_market.is_insurance_fund_operation_paused(InsuranceFundOperation::Add),
+        ErrorCode::InsuranceFundOperationPaused,
+        "if staking add disabled",
+    )?;
+
+    validate!(
+        insurance_fund_stake.market_index == perp_market_index,
+        ErrorCode::InvalidMarketAccount,
+        "insurance_fund_stake does not match perp_market_index"
+    )?;
+
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::PerpMarket)?;
+
+    validate!(
+        spot_market.status != MarketStatus::Initialized,
+        ErrorCode::InvalidSpotMarketState,
+        "spot market = {} not active for insurance_fund_stake",
+        spot_market.market_index
+    )?;
+
+    validate!(
+        insurance_fund_stake.last_withdraw_request_shares == 0
+            && insurance_fund_stake.last_withdraw_request_value == 0,
+        ErrorCode::IFWithdrawRequestInProgress,
+        "withdraw request in progress"
+    )?;
+
+    // shares are only minted for what the vault receives net of any token-2022 transfer fee
+    let transfer_fee = controller::token::calculate_transfer_fee(&mint, amount)?;
+    let insurance_vault_amount = ctx.accounts.perp_insurance_fund_vault.amount;
+
+    controller::insurance::with_perp_insurance_fund(
+        spot_market,
+        perp_insurance_fund,
+        |spot_market| {
+            controller::insurance::add_insurance_fund_stake(
+                amount.safe_sub(transfer_fee)?,
+                insurance_vault_amount,
+                insurance_fund_stake,
+                user_stats,
+                spot_market,
+                now,
+            )
+        },
+    )?;
+
+    controller::token::receive(
+        &ctx.accounts.token_program,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.perp_insurance_fund_vault,
+        &ctx.accounts.authority,
+        amount,
+        &mint,
+        Some(remaining_accounts_iter),
+    )?;
+
+    Ok(())
+}
+
+pub fn handle_request_remove_perp_insurance_fund_stake(
+    ctx: Context<RequestRemovePerpInsuranceFundStake>,
+    perp_market_index: u16,
+    amount: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let insurance_fund_stake = &mut load_mut!(ctx.accounts.insurance_fund_stake)?;
+    let user_stats = &mut load_mut!(ctx.accounts.user_stats)?;
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    let perp_insurance_fund = &mut load_mut!(ctx.accounts.perp_insurance_fund)?;
+
+    validate!(
+        !spot_market.is_insurance_fund_operation_paused(InsuranceFundOperation::RequestRemove),
+        ErrorCode::InsuranceFundOperationPaused,
+        "if staking request remove disabled",
+    )?;
+
+    validate!(
+        insurance_fund_stake.market_index == perp_market_index,
+        ErrorCode::InvalidMarketAccount,
+        "insurance_fund_stake does not match perp_market_index"
+    )?;
+
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::PerpMarket)?;
+
+    validate!(
+        insurance_fund_stake.last_withdraw_request_shares == 0,
+        ErrorCode::IFWithdrawRequestInProgress,
+        "Withdraw request is already in progress"
+    )?;
+
+    let insurance_vault_amount = ctx.accounts.perp_insurance_fund_vault.amount;
+
+    controller::insurance::with_perp_insurance_fund(
+        spot_market,
+        perp_insurance_fund,
+        |spot_market| {
+            let n_shares = math::insurance::vault_amount_to_if_shares(
+                amount,
+                spot_market.insurance_fund.total_shares,
+                insurance_vault_amount,
+            )?;
+
+            validate!(
+                n_shares > 0,
+                ErrorCode::IFWithdrawRequestTooSmall,
+                "Requested lp_shares = 0"
+            )?;
+
+            let user_if_shares = insurance_fund_stake.checked_if_shares(spot_market)?;
+            validate!(user_if_shares >= n_shares, ErrorCode::InsufficientIFShares)?;
+
+            controller::insurance::request_remove_insurance_fund_stake(
+                n_shares,
+                insurance_vault_amount,
+                insurance_fund_stake,
+                user_stats,
+                spot_market,
+                now,
+            )
+        },
+    )?;
+
+    Ok(())
+}
+
+pub fn handle_cancel_request_remove_perp_insurance_fund_stake(
+    ctx: Context<RequestRemovePerpInsuranceFundStake>,
+    perp_market_index: u16,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let insurance_fund_stake = &mut load_mut!(ctx.accounts.insurance_fund_stake)?;
+    let user_stats = &mut load_mut!(ctx.accounts.user_stats)?;
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    let perp_insurance_fund = &mut load_mut!(ctx.accounts.perp_insurance_fund)?;
+
+    validate!(
+        insurance_fund_stake.market_index == perp_market_index,
+        ErrorCode::InvalidMarketAccount,
+        "insurance_fund_stake does not match perp_market_index"
+    )?;
+
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::PerpMarket)?;
+
+    validate!(
+        insurance_fund_stake.last_withdraw_request_shares != 0,
+        ErrorCode::NoIFWithdrawRequestInProgress,
+        "No withdraw request in progress"
+    )?;
+
+    let insurance_vault_amount = ctx.accounts.perp_insurance_fund_vault.amount;
+
+    controller::insurance::with_perp_insurance_fund(
+        spot_market,
+        perp_insurance_fund,
+        |spot_market| {
+            controller::insurance::cancel_request_remove_insurance_fund_stake(
+                insurance_vault_amount,
+                insurance_fund_stake,
+                user_stats,
+                spot_market,
+                now,
+            )
+        },
+    )?;
+
+    Ok(())
+}
+
+#[access_control(
+    withdraw_not_paused(&ctx.accounts.state)
+)]
+pub fn handle_remove_perp_insurance_fund_stake<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, RemovePerpInsuranceFundStake<'info>>,
+    perp_market_index: u16,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let insurance_fund_stake = &mut load_mut!(ctx.accounts.insurance_fund_stake)?;
+    let user_stats = &mut load_mut!(ctx.accounts.user_stats)?;
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    let perp_insurance_fund = &mut load_mut!(ctx.accounts.perp_insurance_fund)?;
+    let state = &ctx.accounts.state;
+
+    let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+    let mint = get_token_mint(remaining_accounts_iter)?;
+
+    validate!(
+        !spot_market.is_insurance_fund_operation_paused(InsuranceFundOperation::Remove),
+        ErrorCode::InsuranceFundOperationPaused,
+        "if staking remove disabled",
+    )?;
+
+    validate!(
+        insurance_fund_stake.market_index == perp_market_index,
+        ErrorCode::InvalidMarketAccount,
+        "insurance_fund_stake does not match perp_market_index"
+    )?;
+
+    insurance_fund_stake.validate_tranche(InsuranceFundTranche::PerpMarket)?;
+
+    let insurance_vault_amount = ctx.accounts.perp_insurance_fund_vault.amount;
+
+    let amount = controller::insurance::with_perp_insurance_fund(
+        spot_market,
+        perp_insurance_fund,
+        |spot_market| {
+            controller::insurance::remove_insurance_fund_stake(
+                insurance_vault_amount,
+                insurance_fund_stake,
+                user_stats,
+                spot_market,
+                now,
+            )
+        },
+    )?;
+
+    controller::token::send_from_program_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.perp_insurance_fund_vault,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.drift_signer,
+        state.signer_nonce,
+        amount,
+        &mint,
+        Some(remaining_accounts_iter),
+    )?;
+
+    ctx.accounts.perp_insurance_fund_vault.reload()?;
+    validate!(
+        ctx.accounts.perp_insurance_fund_vault.amount > 0,
+        ErrorCode::InvalidIFDetected,
+        "perp_insurance_fund_vault.amount must remain > 0"
+    )?;
+
+    Ok(())
+}
+
+pub fn handle_transfer_protocol_if_shares(
+    ctx: Context<TransferProtocolIfShares>,
+    market_index: u16,
+    shares: u128,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+
+    validate!(
+        market_index == QUOTE_SPOT_MARKET_INDEX,
+        ErrorCode::DefaultError,
+        "must be if for quote spot market"
+    )?;
+
+    let mut transfer_config = ctx.accounts.transfer_config.load_mut()?;
+
+    transfer_config.validate_signer(ctx.accounts.signer.key)?;
+
+    transfer_config.update_epoch(now)?;
+    transfer_config.validate_transfer(shares)?;
+    transfer_config.current_epoch_transfer += shares;
+
+    let mut if_stake = ctx.accounts.insurance_fund_stake.load_mut()?;
+    let mut user_stats = ctx.accounts.user_stats.load_mut()?;
+    let mut spot_market = ctx.accounts.spot_market.load_mut()?;
+
+    if_stake.validate_tranche(InsuranceFundTranche::Junior)?;
+
+    transfer_protocol_insurance_fund_stake(
+        ctx.accounts.insurance_fund_vault.amount,
+        shares,
+        &mut if_stake,
+        &mut user_stats,
+        &mut spot_market,
+        Clock::get()?.unix_timestamp,
+        ctx.accounts.state.signer,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    market_index: u16,
+)]
+pub struct InitializeInsuranceFundStake<'info> {
+    #[account(
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        init,
+        seeds = [b"insurance_fund_stake", authority.key.as_ref(), market_index.to_le_bytes().as_ref()],
+        space = InsuranceFundStake::SIZE,
+        bump,
+        payer = payer
+    )]
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        mut,
+        has_one = authority
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub state: Box<Account<'info, State>>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct AddInsuranceFundStake<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"spot_market_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub spot_market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        constraint = state.signer.eq(&drift_signer.key())
+    )]
+    /// CHECK: forced drift_signer
+    pub drift_signer: AccountInfo<'info>,
+    #[account(
+        mut,
+        token::mint = insurance_fund_vault.mint,
+        token::authority = authority
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16,)]
+pub struct RequestRemoveInsuranceFundStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16,)]
+pub struct RemoveInsuranceFundStake<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        constraint = state.signer.eq(&drift_signer.key())
+    )]
+    /// CHECK: forced drift_signer
+    pub drift_signer: AccountInfo<'info>,
+    #[account(
+        mut,
+        token::mint = insurance_fund_vault.mint,
+        token::authority = authority
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16,)]
+pub struct ViewInsuranceFundStakePerformance<'info> {
+    #[account(
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        seeds = [b"insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16,)]
+pub struct TokenizeInsuranceFundStake<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"insurance_fund_share_mint".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub insurance_fund_share_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        constraint = state.signer.eq(&drift_signer.key())
+    )]
+    /// CHECK: forced drift_signer
+    pub drift_signer: AccountInfo<'info>,
+    #[account(
+        mut,
+        token::mint = insurance_fund_share_mint,
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16,)]
+pub struct RedeemInsuranceFundShareTokens<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"insurance_fund_share_mint".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub insurance_fund_share_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = insurance_fund_share_mint,
+        token::authority = authority
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16,)]
+pub struct InitializeSeniorInsuranceFundStake<'info> {
+    #[account(
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        seeds = [b"senior_insurance_fund".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub senior_insurance_fund: AccountLoader<'info, SeniorInsuranceFund>,
+    /// Junior stakes predate tranches, so only senior stakes have the tranche in their seeds
+    #[account(
+        init,
+        seeds = [b"insurance_fund_stake", authority.key.as_ref(), market_index.to_le_bytes().as_ref(), &[InsuranceFundTranche::Senior as u8]],
+        space = InsuranceFundStake::SIZE,
+        bump,
+        payer = payer
+    )]
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        mut,
+        has_one = authority
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub state: Box<Account<'info, State>>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16,)]
+pub struct AddSeniorInsuranceFundStake<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        mut,
+        seeds = [b"senior_insurance_fund".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub senior_insurance_fund: AccountLoader<'info, SeniorInsuranceFund>,
+    #[account(
+        mut,
+        seeds = [b"insurance_fund_stake", authority.key.as_ref(), market_index.to_le_bytes().as_ref(), &[InsuranceFundTranche::Senior as u8]],
+        bump,
+        has_one = authority,
+    )]
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"senior_insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub senior_insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = senior_insurance_fund_vault.mint,
+        token::authority = authority
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16,)]
+pub struct RequestRemoveSeniorInsuranceFundStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        mut,
+        seeds = [b"senior_insurance_fund".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub senior_insurance_fund: AccountLoader<'info, SeniorInsuranceFund>,
+    #[account(
+        mut,
+        seeds = [b"insurance_fund_stake", authority.key.as_ref(), market_index.to_le_bytes().as_ref(), &[InsuranceFundTranche::Senior as u8]],
+        bump,
+        has_one = authority,
+    )]
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"senior_insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub senior_insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16,)]
+pub struct RemoveSeniorInsuranceFundStake<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        mut,
+        seeds = [b"senior_insurance_fund".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub senior_insurance_fund: AccountLoader<'info, SeniorInsuranceFund>,
+    #[account(
+        mut,
+        seeds = [b"insurance_fund_stake", authority.key.as_ref(), market_index.to_le_bytes().as_ref(), &[InsuranceFundTranche::Senior as u8]],
+        bump,
+        has_one = authority,
+    )]
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"senior_insurance_fund_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub senior_insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        constraint = state.signer.eq(&drift_signer.key())
+    )]
+    /// CHECK: forced drift_signer
+    pub drift_signer: AccountInfo<'info>,
+    #[account(
+        mut,
+        token::mint = senior_insurance_fund_vault.mint,
+        token::authority = authority
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(perp_market_index: u16,)]
+pub struct InitializePerpInsuranceFundStake<'info> {
+    #[account(
+        seeds = [b"spot_market", QUOTE_SPOT_MARKET_INDEX.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        seeds = [b"perp_insurance_fund".as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub perp_insurance_fund: AccountLoader<'info, PerpInsuranceFund>,
+    #[account(
+        init,
+        seeds = [b"perp_insurance_fund_stake", authority.key.as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        space = InsuranceFundStake::SIZE,
+        bump,
+        payer = payer
+    )]
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        mut,
+        has_one = authority
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub state: Box<Account<'info, State>>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(perp_market_index: u16,)]
+pub struct AddPerpInsuranceFundStake<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", QUOTE_SPOT_MARKET_INDEX.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        mut,
+        seeds = [b"perp_insurance_fund".as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub perp_insurance_fund: AccountLoader<'info, PerpInsuranceFund>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"perp_insurance_fund_vault".as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub perp_insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = perp_insurance_fund_vault.mint,
+        token::authority = authority
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(perp_market_index: u16,)]
+pub struct RequestRemovePerpInsuranceFundStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"spot_market", QUOTE_SPOT_MARKET_INDEX.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        mut,
+        seeds = [b"perp_insurance_fund".as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub perp_insurance_fund: AccountLoader<'info, PerpInsuranceFund>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"perp_insurance_fund_vault".as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub perp_insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+#[instruction(perp_market_index: u16,)]
+pub struct RemovePerpInsuranceFundStake<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", QUOTE_SPOT_MARKET_INDEX.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(
+        mut,
+        seeds = [b"perp_insurance_fund".as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub perp_insurance_fund: AccountLoader<'info, PerpInsuranceFund>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub insurance_fund_stake: AccountLoader<'info, InsuranceFundStake>,
+    #[account(
+        mut,
+        has_one = authority,
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"perp_insurance_fund_vault".as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub perp_insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        constraint = state.signer.eq(&drift_signer.key())
+    )]
+    /// CHECK: forced drift_signer
+    pub drift_signer: AccountInfo<'info>,
+    #[account(
+        mut,
+        token::mint = perp_insurance_fund_vault.mint,
         token::authority = authority
     )]
     pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
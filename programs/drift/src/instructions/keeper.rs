@@ -1,21 +1,45 @@
+use std::iter::Peekable;
+use std::slice::Iter;
+
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{TokenAccount, TokenInterface};
 
+use crate::controller::funding::PredictedFundingRate;
 use crate::controller::insurance::update_user_stats_if_stake_amount;
-use crate::error::ErrorCode;
+use crate::error::{DriftResult, ErrorCode};
 use crate::instructions::constraints::*;
-use crate::instructions::optional_accounts::{load_maps, AccountMaps};
+use crate::instructions::optional_accounts::{
+    get_perp_market_depth_bounds, get_user, load_maps, AccountMaps,
+};
 use crate::math::constants::QUOTE_SPOT_MARKET_INDEX;
-use crate::math::margin::{calculate_user_equity, meets_settle_pnl_maintenance_margin_requirement};
+use crate::math::margin::{
+    calculate_user_equity, meets_maintenance_margin_requirement,
+    meets_settle_pnl_maintenance_margin_requirement, MarginRequirementType,
+};
+use crate::math::oracle::{is_oracle_valid_for_action, DriftAction};
 use crate::math::orders::{estimate_price_from_side, find_bids_and_asks_from_users};
+use crate::math::safe_math::SafeMath;
 use crate::math::spot_withdraw::validate_spot_market_vault_amount;
-use crate::optional_accounts::{get_token_mint, update_prelaunch_oracle};
+use crate::optional_accounts::{
+    get_fee_tier_qualifications, get_insurance_fund_performance, get_keeper_stats,
+    get_maker_rewards_program, get_market_fee_structure, get_perp_insurance_fund,
+    get_referral_tier_config, get_senior_insurance_fund, get_token_mint, update_prelaunch_oracle,
+};
+use crate::state::events::{OracleCircuitBreakerRecord, TermLoanAction, TermLoanRecord};
 use crate::state::fill_mode::FillMode;
+use crate::state::fuel_season::{FuelSeason, UserFuelSeasonRecord};
 use crate::state::fulfillment_params::drift::MatchFulfillmentParams;
 use crate::state::fulfillment_params::openbook_v2::OpenbookV2FulfillmentParams;
 use crate::state::fulfillment_params::phoenix::PhoenixFulfillmentParams;
 use crate::state::fulfillment_params::serum::SerumFulfillmentParams;
-use crate::state::insurance_fund_stake::InsuranceFundStake;
+use crate::state::insurance_fund_stake::{
+    InsuranceFundStake, InsuranceFundTranche, PerpInsuranceFund,
+};
+use crate::state::maker_rewards::MakerRewardsProgram;
+use crate::state::margin_calculation::MarginContext;
+use crate::state::oracle::{
+    OracleAggregator, OracleCircuitBreakerWindow, OracleSource, SignedPriceAccount,
+};
 use crate::state::oracle_map::OracleMap;
 use crate::state::paused_operations::PerpOperation;
 use crate::state::perp_market::{MarketStatus, PerpMarket};
@@ -29,11 +53,13 @@ use crate::state::spot_market::SpotMarket;
 use crate::state::spot_market_map::{
     get_writable_spot_market_set, get_writable_spot_market_set_from_many,
 };
-use crate::state::state::State;
+use crate::state::state::{State, ValidityGuardRailsOverride};
+use crate::state::term_loan::TermLoan;
+use crate::state::traits::Size;
 use crate::state::user::{MarketType, OrderStatus, User, UserStats};
 use crate::state::user_map::{load_user_map, load_user_maps, UserMap, UserStatsMap};
 use crate::validation::user::validate_user_is_idle;
-use crate::{controller, load, math, OracleSource, GOV_SPOT_MARKET_INDEX};
+use crate::{controller, load, math, GOV_SPOT_MARKET_INDEX};
 use crate::{load_mut, QUOTE_PRECISION_U64};
 use crate::{validate, QUOTE_PRECISION_I128};
 
@@ -96,6 +122,25 @@ fn fill_order<'c: 'info, 'info>(
     let (makers_and_referrer, makers_and_referrer_stats) =
         load_user_maps(remaining_accounts_iter, true)?;
 
+    let referral_tier_config = get_referral_tier_config(remaining_accounts_iter, state)?;
+    let referral_tier_config = match &referral_tier_config {
+        Some(referral_tier_config) => Some(load!(referral_tier_config)?),
+        None => None,
+    };
+
+    let fee_tier_qualifications =
+        get_fee_tier_qualifications(remaining_accounts_iter, state, MarketType::Perp)?;
+
+    let market_fee_structure = get_market_fee_structure(remaining_accounts_iter)?;
+
+    let maker_rewards_program = get_maker_rewards_program(remaining_accounts_iter)?;
+    let mut maker_rewards_program = match &maker_rewards_program {
+        Some(maker_rewards_program) => Some(load_mut!(maker_rewards_program)?),
+        None => None,
+    };
+
+    let depth_bounds = get_perp_market_depth_bounds(remaining_accounts_iter)?;
+
     controller::repeg::update_amm(
         market_index,
         &perp_market_map,
@@ -117,6 +162,11 @@ fn fill_order<'c: 'info, 'info>(
         &makers_and_referrer,
         &makers_and_referrer_stats,
         None,
+        referral_tier_config.as_deref(),
+        fee_tier_qualifications.as_deref(),
+        market_fee_structure.as_deref(),
+        maker_rewards_program.as_deref_mut(),
+        depth_bounds.as_deref(),
         clock,
         FillMode::Fill,
     )?;
@@ -213,6 +263,14 @@ fn fill_spot_order<'c: 'info, 'info>(
         _ => (UserMap::empty(), UserStatsMap::empty()),
     };
 
+    let fee_tier_qualifications = get_fee_tier_qualifications(
+        remaining_accounts_iter,
+        &ctx.accounts.state,
+        MarketType::Spot,
+    )?;
+
+    let market_fee_structure = get_market_fee_structure(remaining_accounts_iter)?;
+
     let mut fulfillment_params: Box<dyn SpotFulfillmentParams> = match fulfillment_type {
         SpotFulfillmentType::SerumV3 => {
             let base_market = spot_market_map.get_ref(&market_index)?;
@@ -270,6 +328,8 @@ fn fill_spot_order<'c: 'info, 'info>(
         &makers_and_referrer,
         &makers_and_referrer_stats,
         None,
+        fee_tier_qualifications.as_deref(),
+        market_fee_structure.as_deref(),
         &clock,
         fulfillment_params.as_mut(),
     )?;
@@ -317,6 +377,7 @@ pub fn handle_trigger_order<'c: 'info, 'info>(
             &perp_market_map,
             &mut oracle_map,
             &ctx.accounts.filler,
+            &ctx.accounts.filler_stats,
             &Clock::get()?,
         )?,
         MarketType::Spot => controller::orders::trigger_spot_order(
@@ -426,6 +487,259 @@ pub fn handle_update_user_open_orders_count<'info>(ctx: Context<UpdateUserIdle>)
     Ok(())
 }
 
+/// Creates the record a user's fuel for their current fuel season is swept into
+pub fn handle_initialize_user_fuel_season_record(
+    ctx: Context<InitializeUserFuelSeasonRecord>,
+    season: u16,
+) -> Result<()> {
+    let user_stats = load!(ctx.accounts.user_stats)?;
+
+    validate!(
+        season == user_stats.fuel_season,
+        ErrorCode::InvalidFuelSeason,
+        "cant initialize record for season {} for user stats in season {}",
+        season,
+        user_stats.fuel_season
+    )?;
+
+    let mut user_fuel_season_record = ctx
+        .accounts
+        .user_fuel_season_record
+        .load_init()
+        .or(Err(ErrorCode::UnableToLoadAccountLoader))?;
+
+    *user_fuel_season_record = UserFuelSeasonRecord {
+        authority: user_stats.authority,
+        season,
+        ..UserFuelSeasonRecord::default()
+    };
+
+    Ok(())
+}
+
+/// Sweeps a user's fuel counters into their record for the season they were earned in. While the
+/// season is open this keeps the u32 counters from saturating. Once the season is closed only the
+/// fuel earned before it closed is swept and the user stats are rolled over to the next season, one
+/// season at a time. Fuel accrues into the counters throughout, so a user that snapshots late loses none
+pub fn handle_snapshot_user_fuel_season<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, SnapshotUserFuelSeason<'info>>,
+    season: u16,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let state = &ctx.accounts.state;
+    let user_stats = &mut load_mut!(ctx.accounts.user_stats)?;
+    let user_fuel_season_record = &mut load_mut!(ctx.accounts.user_fuel_season_record)?;
+
+    validate!(
+        season == user_stats.fuel_season && season <= state.fuel_season,
+        ErrorCode::InvalidFuelSeason,
+        "cant snapshot season {} for user stats in season {} (open season {})",
+        season,
+        user_stats.fuel_season,
+        state.fuel_season
+    )?;
+
+    if season < state.fuel_season {
+        // the closed season is passed in the remaining accounts for its end_ts
+        let fuel_season_account_info = ctx
+            .remaining_accounts
+            .first()
+            .ok_or(ErrorCode::InvalidFuelSeason)?;
+        let fuel_season: AccountLoader<FuelSeason> =
+            AccountLoader::try_from(fuel_season_account_info)
+                .or(Err(ErrorCode::UnableToLoadAccountLoader))?;
+        let fuel_season = load!(fuel_season)?;
+
+        validate!(
+            fuel_season.season == season,
+            ErrorCode::InvalidFuelSeason,
+            "fuel season {} passed for season {}",
+            fuel_season.season,
+            season
+        )?;
+
+        user_fuel_season_record.sweep_closed_season(user_stats, &fuel_season, now)?;
+
+        user_stats.fuel_season = season.safe_add(1)?;
+    } else {
+        user_fuel_season_record.sweep(user_stats, now)?;
+    }
+
+    Ok(())
+}
+
+/// Closes the maker rewards epoch once it has run its duration. Rewards are capped by what is in the
+/// vault and last epoch's unclaimed rewards are forfeited
+pub fn handle_settle_maker_rewards_epoch(
+    ctx: Context<SettleMakerRewardsEpoch>,
+    market_index: u16,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let maker_rewards_program = &mut load_mut!(ctx.accounts.maker_rewards_program)?;
+    let vault_amount = ctx.accounts.maker_rewards_vault.amount;
+
+    maker_rewards_program.settle_epoch(vault_amount, now)?;
+
+    msg!(
+        "settled maker rewards epoch {} for perp market {}: depth rewards {} volume rewards {}",
+        maker_rewards_program.epoch.saturating_sub(1),
+        market_index,
+        maker_rewards_program.last_epoch_depth_rewards,
+        maker_rewards_program.last_epoch_volume_rewards
+    );
+
+    Ok(())
+}
+
+/// Permissionless: resamples the depth of the program makers passed in the remaining accounts, so
+/// makers dont rely on being included in fills to have their depth credited before the epoch is
+/// settled
+#[access_control(
+    valid_oracle_for_perp_market(&ctx.accounts.oracle, &ctx.accounts.perp_market)
+)]
+pub fn handle_update_maker_rewards_depth<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, UpdateMakerRewardsDepth<'info>>,
+    market_index: u16,
+) -> Result<()> {
+    let perp_market = load!(ctx.accounts.perp_market)?;
+    let maker_rewards_program = &mut load_mut!(ctx.accounts.maker_rewards_program)?;
+    let state = &ctx.accounts.state;
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let slot = clock.slot;
+
+    let mut oracle_map =
+        OracleMap::load_one(&ctx.accounts.oracle, slot, Some(state.oracle_guard_rails))?;
+
+    let (oracle_price_data, oracle_validity) = oracle_map.get_price_data_and_validity(
+        MarketType::Perp,
+        market_index,
+        &perp_market.amm.oracle,
+        perp_market
+            .amm
+            .historical_oracle_data
+            .last_oracle_price_twap,
+        &perp_market.oracle_validity_guard_rails,
+        perp_market.get_max_confidence_interval_multiplier()?,
+    )?;
+
+    validate!(
+        is_oracle_valid_for_action(oracle_validity, Some(DriftAction::FillOrderMatch))?,
+        ErrorCode::InvalidOracle,
+        "perp market {} oracle invalid ({:?})",
+        market_index,
+        oracle_validity
+    )?;
+
+    let makers = load_user_map(&mut ctx.remaining_accounts.iter().peekable(), false)?;
+
+    controller::maker_rewards::update_maker_rewards_depth(
+        maker_rewards_program,
+        &makers,
+        market_index,
+        oracle_price_data.price,
+        slot,
+        perp_market.amm.order_tick_size,
+        perp_market.is_prediction_market(),
+        now,
+    )?;
+
+    Ok(())
+}
+
+#[access_control(
+    exchange_not_paused(&ctx.accounts.state)
+)]
+pub fn handle_settle_term_loan<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, SettleTermLoan<'info>>,
+) -> Result<()> {
+    let state = &ctx.accounts.state;
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    let lender_key = ctx.accounts.lender.key();
+    let borrower_key = ctx.accounts.borrower.key();
+
+    let term_loan = load!(ctx.accounts.term_loan)?;
+    let lender = &mut load_mut!(ctx.accounts.lender)?;
+    let borrower = &mut load_mut!(ctx.accounts.borrower)?;
+
+    let market_index = term_loan.market_index;
+
+    let AccountMaps {
+        perp_market_map,
+        spot_market_map,
+        mut oracle_map,
+    } = load_maps(
+        &mut ctx.remaining_accounts.iter().peekable(),
+        &MarketSet::new(),
+        &get_writable_spot_market_set(market_index),
+        clock.slot,
+        Some(state.oracle_guard_rails),
+    )?;
+
+    // loans can be settled early if the borrower can be liquidated so the debt can be liquidated with liquidate_spot
+    if !term_loan.is_matured(now) {
+        let meets_maintenance_margin_requirement = meets_maintenance_margin_requirement(
+            borrower,
+            &perp_market_map,
+            &spot_market_map,
+            &mut oracle_map,
+        )?;
+
+        validate!(
+            !meets_maintenance_margin_requirement,
+            ErrorCode::TermLoanNotMatured,
+            "term loan matures at {} (now {})",
+            term_loan.maturity_ts,
+            now
+        )?;
+    }
+
+    let shortfall = {
+        let spot_market = &mut spot_market_map.get_ref_mut(&market_index)?;
+        let oracle_price_data = oracle_map.get_price_data(&spot_market.oracle)?;
+        controller::spot_balance::update_spot_market_cumulative_interest(
+            spot_market,
+            Some(oracle_price_data),
+            now,
+        )?;
+
+        controller::term_loan::settle_term_loan(&term_loan, lender, borrower, spot_market)?
+    };
+
+    emit!(TermLoanRecord {
+        ts: now,
+        action: TermLoanAction::Settle,
+        term_loan: ctx.accounts.term_loan.key(),
+        lender: lender_key,
+        borrower: borrower_key,
+        market_index,
+        principal: term_loan.principal,
+        repay_amount: term_loan.repay_amount,
+        rate: term_loan.rate,
+        maturity_ts: term_loan.maturity_ts,
+        shortfall,
+    });
+
+    Ok(())
+}
+
+/// Settling for another user earns keeper fuel if the keeper passes their user stats in the
+/// remaining accounts. They're ignored when the user or their delegate settles, since they can be
+/// the user's own stats
+fn get_keeper_stats_for_settle<'a>(
+    account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+    user: &User,
+    authority: &Pubkey,
+) -> DriftResult<Option<AccountLoader<'a, UserStats>>> {
+    if user.authority.eq(authority) || user.delegate.eq(authority) {
+        return Ok(None);
+    }
+
+    get_keeper_stats(account_info_iter, authority)
+}
+
 #[access_control(
     settle_pnl_not_paused(&ctx.accounts.state)
 )]
@@ -439,18 +753,26 @@ pub fn handle_settle_pnl<'c: 'info, 'info>(
     let user_key = ctx.accounts.user.key();
     let user = &mut load_mut!(ctx.accounts.user)?;
 
+    let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
     let AccountMaps {
         perp_market_map,
         spot_market_map,
         mut oracle_map,
     } = load_maps(
-        &mut ctx.remaining_accounts.iter().peekable(),
+        remaining_accounts_iter,
         &get_writable_perp_market_set(market_index),
         &get_writable_spot_market_set(QUOTE_SPOT_MARKET_INDEX),
         clock.slot,
         Some(state.oracle_guard_rails),
     )?;
 
+    let keeper_stats =
+        get_keeper_stats_for_settle(remaining_accounts_iter, user, ctx.accounts.authority.key)?;
+    let mut keeper_stats = match &keeper_stats {
+        Some(keeper_stats) => Some(load_mut!(keeper_stats)?),
+        None => None,
+    };
+
     let market_in_settlement =
         perp_market_map.get_ref(&market_index)?.status == MarketStatus::Settlement;
 
@@ -483,6 +805,7 @@ pub fn handle_settle_pnl<'c: 'info, 'info>(
             market_index,
             user,
             ctx.accounts.authority.key,
+            keeper_stats.as_deref_mut(),
             &user_key,
             &perp_market_map,
             &spot_market_map,
@@ -517,18 +840,26 @@ pub fn handle_settle_multiple_pnls<'c: 'info, 'info>(
     let user_key = ctx.accounts.user.key();
     let user = &mut load_mut!(ctx.accounts.user)?;
 
+    let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
     let AccountMaps {
         perp_market_map,
         spot_market_map,
         mut oracle_map,
     } = load_maps(
-        &mut ctx.remaining_accounts.iter().peekable(),
+        remaining_accounts_iter,
         &get_writable_perp_market_set_from_vec(&market_indexes),
         &get_writable_spot_market_set(QUOTE_SPOT_MARKET_INDEX),
         clock.slot,
         Some(state.oracle_guard_rails),
     )?;
 
+    let keeper_stats =
+        get_keeper_stats_for_settle(remaining_accounts_iter, user, ctx.accounts.authority.key)?;
+    let mut keeper_stats = match &keeper_stats {
+        Some(keeper_stats) => Some(load_mut!(keeper_stats)?),
+        None => None,
+    };
+
     let meets_margin_requirement = meets_settle_pnl_maintenance_margin_requirement(
         user,
         &perp_market_map,
@@ -569,6 +900,7 @@ pub fn handle_settle_multiple_pnls<'c: 'info, 'info>(
                 *market_index,
                 user,
                 ctx.accounts.authority.key,
+                keeper_stats.as_deref_mut(),
                 &user_key,
                 &perp_market_map,
                 &spot_market_map,
@@ -626,23 +958,71 @@ pub fn handle_settle_lp<'c: 'info, 'info>(
 ) -> Result<()> {
     let user_key = ctx.accounts.user.key();
     let user = &mut load_mut!(ctx.accounts.user)?;
+    let user_stats = &mut load_mut!(ctx.accounts.user_stats)?;
 
     let state = &ctx.accounts.state;
     let clock = Clock::get()?;
     let now = clock.unix_timestamp;
 
+    let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
     let AccountMaps {
-        perp_market_map, ..
+        perp_market_map,
+        spot_market_map,
+        mut oracle_map,
     } = load_maps(
-        &mut ctx.remaining_accounts.iter().peekable(),
+        remaining_accounts_iter,
         &get_writable_perp_market_set(market_index),
         &MarketSet::new(),
         clock.slot,
         Some(state.oracle_guard_rails),
     )?;
 
-    let market = &mut perp_market_map.get_ref_mut(&market_index)?;
-    controller::lp::settle_funding_payment_then_lp(user, &user_key, market, now)?;
+    let keeper_stats =
+        get_keeper_stats_for_settle(remaining_accounts_iter, user, ctx.accounts.authority.key)?;
+    let mut keeper_stats = match &keeper_stats {
+        Some(keeper_stats) => Some(load_mut!(keeper_stats)?),
+        None => None,
+    };
+
+    {
+        let market = &mut perp_market_map.get_ref_mut(&market_index)?;
+
+        let last_base_asset_amount_per_lp = user
+            .get_perp_position(market_index)
+            .map(|position| position.last_base_asset_amount_per_lp)
+            .ok();
+
+        controller::lp::settle_funding_payment_then_lp(user, &user_key, market, now)?;
+
+        let settled_lp = user
+            .get_perp_position(market_index)
+            .map(|position| {
+                Some(position.last_base_asset_amount_per_lp) != last_base_asset_amount_per_lp
+            })
+            .unwrap_or(false);
+
+        if let Some(keeper_stats) = keeper_stats.as_deref_mut() {
+            if settled_lp {
+                keeper_stats.update_fuel_keeper_bonus(
+                    market.fuel_boost_keeper,
+                    state.perp_fee_structure.flat_filler_fee,
+                )?;
+            }
+        }
+    }
+
+    // credits the lp fuel accrued on the value of the user's lp shares
+    let context =
+        MarginContext::standard(MarginRequirementType::Maintenance).fuel_numerator(user, now);
+    user.calculate_margin_and_increment_fuel_bonus(
+        &perp_market_map,
+        &spot_market_map,
+        &mut oracle_map,
+        context,
+        user_stats,
+        now,
+    )?;
+
     user.update_last_active_slot(clock.slot);
 
     Ok(())
@@ -785,6 +1165,19 @@ pub fn handle_liquidate_perp_with_fill<'c: 'info, 'info>(
     let (makers_and_referrer, makers_and_referrer_stats) =
         load_user_maps(remaining_accounts_iter, true)?;
 
+    let fee_tier_qualifications =
+        get_fee_tier_qualifications(remaining_accounts_iter, state, MarketType::Perp)?;
+
+    let market_fee_structure = get_market_fee_structure(remaining_accounts_iter)?;
+
+    let maker_rewards_program = get_maker_rewards_program(remaining_accounts_iter)?;
+    let mut maker_rewards_program = match &maker_rewards_program {
+        Some(maker_rewards_program) => Some(load_mut!(maker_rewards_program)?),
+        None => None,
+    };
+
+    let depth_bounds = get_perp_market_depth_bounds(remaining_accounts_iter)?;
+
     controller::liquidation::liquidate_perp_with_fill(
         market_index,
         &ctx.accounts.user,
@@ -795,6 +1188,10 @@ pub fn handle_liquidate_perp_with_fill<'c: 'info, 'info>(
         &ctx.accounts.liquidator_stats,
         &makers_and_referrer,
         &makers_and_referrer_stats,
+        fee_tier_qualifications.as_deref(),
+        market_fee_structure.as_deref(),
+        maker_rewards_program.as_deref_mut(),
+        depth_bounds.as_deref(),
         &perp_market_map,
         &spot_market_map,
         &mut oracle_map,
@@ -1045,6 +1442,38 @@ pub fn handle_resolve_perp_pnl_deficit<'c: 'info, 'info>(
         Some(state.oracle_guard_rails),
     )?;
 
+    let insurance_fund_performance = get_insurance_fund_performance(
+        remaining_accounts_iter,
+        spot_market_index,
+        InsuranceFundTranche::Junior,
+        spot_market_map
+            .get_ref(&spot_market_index)?
+            .has_insurance_fund_performance(),
+    )?;
+    let perp_insurance_fund = get_perp_insurance_fund(
+        remaining_accounts_iter,
+        &*perp_market_map.get_ref(&perp_market_index)?,
+    )?;
+    let perp_insurance_fund_performance = get_insurance_fund_performance(
+        remaining_accounts_iter,
+        perp_market_index,
+        InsuranceFundTranche::PerpMarket,
+        perp_market_map
+            .get_ref(&perp_market_index)?
+            .has_perp_insurance_fund_performance,
+    )?;
+    let mut senior_insurance_fund = get_senior_insurance_fund(
+        remaining_accounts_iter,
+        &*spot_market_map.get_ref(&spot_market_index)?,
+    )?;
+    let senior_insurance_fund_performance = get_insurance_fund_performance(
+        remaining_accounts_iter,
+        spot_market_index,
+        InsuranceFundTranche::Senior,
+        spot_market_map
+            .get_ref(&spot_market_index)?
+            .has_senior_insurance_fund_performance(),
+    )?;
     let mint = get_token_mint(remaining_accounts_iter)?;
 
     controller::repeg::update_amm(
@@ -1060,6 +1489,7 @@ pub fn handle_resolve_perp_pnl_deficit<'c: 'info, 'info>(
         controller::insurance::attempt_settle_revenue_to_insurance_fund(
             &ctx.accounts.spot_market_vault,
             &ctx.accounts.insurance_fund_vault,
+            &mut senior_insurance_fund,
             spot_market,
             now,
             &ctx.accounts.token_program,
@@ -1077,7 +1507,33 @@ pub fn handle_resolve_perp_pnl_deficit<'c: 'info, 'info>(
         )?;
     }
 
-    let insurance_vault_amount = ctx.accounts.insurance_fund_vault.amount;
+    let (senior_insurance_fund, senior_insurance_fund_vault) = match senior_insurance_fund {
+        Some((senior_insurance_fund, senior_insurance_fund_vault)) => (
+            Some(senior_insurance_fund),
+            Some(senior_insurance_fund_vault),
+        ),
+        None => (None, None),
+    };
+    let (perp_insurance_fund, perp_insurance_fund_vault) = match perp_insurance_fund {
+        Some((perp_insurance_fund, perp_insurance_fund_vault)) => {
+            (Some(perp_insurance_fund), Some(perp_insurance_fund_vault))
+        }
+        None => (None, None),
+    };
+
+    let junior_insurance_vault_amount = ctx.accounts.insurance_fund_vault.amount;
+    let senior_insurance_vault_amount = senior_insurance_fund_vault
+        .as_ref()
+        .map(|senior_insurance_fund_vault| senior_insurance_fund_vault.amount);
+    let insurance_vault_amount = controller::insurance::get_tranched_insurance_vault_amount(
+        junior_insurance_vault_amount,
+        senior_insurance_vault_amount,
+    )?;
+    let perp_insurance_vault_amount = perp_insurance_fund_vault
+        .as_ref()
+        .map_or(0, |perp_insurance_fund_vault| {
+            perp_insurance_fund_vault.amount
+        });
     let spot_market_vault_amount = ctx.accounts.spot_market_vault.amount;
 
     let pay_from_insurance = {
@@ -1110,6 +1566,7 @@ pub fn handle_resolve_perp_pnl_deficit<'c: 'info, 'info>(
         controller::insurance::resolve_perp_pnl_deficit(
             spot_market_vault_amount,
             insurance_vault_amount,
+            perp_insurance_vault_amount,
             spot_market,
             perp_market,
             clock.unix_timestamp,
@@ -1117,24 +1574,49 @@ pub fn handle_resolve_perp_pnl_deficit<'c: 'info, 'info>(
     };
 
     if pay_from_insurance > 0 {
-        validate!(
-            pay_from_insurance < ctx.accounts.insurance_fund_vault.amount,
-            ErrorCode::InsufficientCollateral,
-            "Insurance Fund balance InsufficientCollateral for payment: !{} < {}",
-            pay_from_insurance,
-            ctx.accounts.insurance_fund_vault.amount
-        )?;
+        let (perp_insurance_fund_payment, insurance_fund_payment, senior_insurance_fund_payment) =
+            controller::insurance::send_from_perp_insurance_funds(
+                &ctx.accounts.token_program,
+                &perp_insurance_fund_vault,
+                &ctx.accounts.insurance_fund_vault,
+                &senior_insurance_fund_vault,
+                &ctx.accounts.spot_market_vault,
+                &ctx.accounts.drift_signer,
+                state,
+                pay_from_insurance,
+                &mint,
+            )?;
 
-        controller::token::send_from_program_vault(
-            &ctx.accounts.token_program,
-            &ctx.accounts.insurance_fund_vault,
-            &ctx.accounts.spot_market_vault,
-            &ctx.accounts.drift_signer,
-            state.signer_nonce,
-            pay_from_insurance,
-            &mint,
+        controller::insurance::record_insurance_fund_payment(
+            &insurance_fund_performance,
+            &spot_market_map.get_ref(&spot_market_index)?.insurance_fund,
+            junior_insurance_vault_amount,
+            insurance_fund_payment,
+            now,
         )?;
 
+        if let Some(perp_insurance_fund) = &perp_insurance_fund {
+            controller::insurance::record_insurance_fund_payment(
+                &perp_insurance_fund_performance,
+                &load!(perp_insurance_fund)?.insurance_fund,
+                perp_insurance_vault_amount,
+                perp_insurance_fund_payment,
+                now,
+            )?;
+        }
+
+        if let (Some(senior_insurance_fund), Some(senior_insurance_vault_amount)) =
+            (&senior_insurance_fund, senior_insurance_vault_amount)
+        {
+            controller::insurance::record_insurance_fund_payment(
+                &senior_insurance_fund_performance,
+                &load!(senior_insurance_fund)?.insurance_fund,
+                senior_insurance_vault_amount,
+                senior_insurance_fund_payment,
+                now,
+            )?;
+        }
+
         validate!(
             ctx.accounts.insurance_fund_vault.amount > 0,
             ErrorCode::InvalidIFDetected,
@@ -1188,6 +1670,38 @@ pub fn handle_resolve_perp_bankruptcy<'c: 'info, 'info>(
         Some(state.oracle_guard_rails),
     )?;
 
+    let insurance_fund_performance = get_insurance_fund_performance(
+        remaining_accounts_iter,
+        quote_spot_market_index,
+        InsuranceFundTranche::Junior,
+        spot_market_map
+            .get_ref(&quote_spot_market_index)?
+            .has_insurance_fund_performance(),
+    )?;
+    let perp_insurance_fund = get_perp_insurance_fund(
+        remaining_accounts_iter,
+        &*perp_market_map.get_ref(&market_index)?,
+    )?;
+    let perp_insurance_fund_performance = get_insurance_fund_performance(
+        remaining_accounts_iter,
+        market_index,
+        InsuranceFundTranche::PerpMarket,
+        perp_market_map
+            .get_ref(&market_index)?
+            .has_perp_insurance_fund_performance,
+    )?;
+    let mut senior_insurance_fund = get_senior_insurance_fund(
+        remaining_accounts_iter,
+        &*spot_market_map.get_ref(&quote_spot_market_index)?,
+    )?;
+    let senior_insurance_fund_performance = get_insurance_fund_performance(
+        remaining_accounts_iter,
+        quote_spot_market_index,
+        InsuranceFundTranche::Senior,
+        spot_market_map
+            .get_ref(&quote_spot_market_index)?
+            .has_senior_insurance_fund_performance(),
+    )?;
     let mint = get_token_mint(remaining_accounts_iter)?;
 
     {
@@ -1195,6 +1709,7 @@ pub fn handle_resolve_perp_bankruptcy<'c: 'info, 'info>(
         controller::insurance::attempt_settle_revenue_to_insurance_fund(
             &ctx.accounts.spot_market_vault,
             &ctx.accounts.insurance_fund_vault,
+            &mut senior_insurance_fund,
             spot_market,
             now,
             &ctx.accounts.token_program,
@@ -1212,6 +1727,30 @@ pub fn handle_resolve_perp_bankruptcy<'c: 'info, 'info>(
         )?;
     }
 
+    let (senior_insurance_fund, senior_insurance_fund_vault) = match senior_insurance_fund {
+        Some((senior_insurance_fund, senior_insurance_fund_vault)) => (
+            Some(senior_insurance_fund),
+            Some(senior_insurance_fund_vault),
+        ),
+        None => (None, None),
+    };
+    let (perp_insurance_fund, perp_insurance_fund_vault) = match perp_insurance_fund {
+        Some((perp_insurance_fund, perp_insurance_fund_vault)) => {
+            (Some(perp_insurance_fund), Some(perp_insurance_fund_vault))
+        }
+        None => (None, None),
+    };
+
+    let junior_insurance_vault_amount = ctx.accounts.insurance_fund_vault.amount;
+    let senior_insurance_vault_amount = senior_insurance_fund_vault
+        .as_ref()
+        .map(|senior_insurance_fund_vault| senior_insurance_fund_vault.amount);
+    let perp_insurance_vault_amount = perp_insurance_fund_vault
+        .as_ref()
+        .map_or(0, |perp_insurance_fund_vault| {
+            perp_insurance_fund_vault.amount
+        });
+
     let pay_from_insurance = controller::liquidation::resolve_perp_bankruptcy(
         market_index,
         user,
@@ -1222,28 +1761,59 @@ pub fn handle_resolve_perp_bankruptcy<'c: 'info, 'info>(
         &spot_market_map,
         &mut oracle_map,
         now,
-        ctx.accounts.insurance_fund_vault.amount,
+        controller::insurance::get_tranched_insurance_vault_amount(
+            junior_insurance_vault_amount,
+            senior_insurance_vault_amount,
+        )?,
+        perp_insurance_vault_amount,
     )?;
 
     if pay_from_insurance > 0 {
-        validate!(
-            pay_from_insurance < ctx.accounts.insurance_fund_vault.amount,
-            ErrorCode::InsufficientCollateral,
-            "Insurance Fund balance InsufficientCollateral for payment: !{} < {}",
-            pay_from_insurance,
-            ctx.accounts.insurance_fund_vault.amount
-        )?;
+        let (perp_insurance_fund_payment, insurance_fund_payment, senior_insurance_fund_payment) =
+            controller::insurance::send_from_perp_insurance_funds(
+                &ctx.accounts.token_program,
+                &perp_insurance_fund_vault,
+                &ctx.accounts.insurance_fund_vault,
+                &senior_insurance_fund_vault,
+                &ctx.accounts.spot_market_vault,
+                &ctx.accounts.drift_signer,
+                state,
+                pay_from_insurance,
+                &mint,
+            )?;
 
-        controller::token::send_from_program_vault(
-            &ctx.accounts.token_program,
-            &ctx.accounts.insurance_fund_vault,
-            &ctx.accounts.spot_market_vault,
-            &ctx.accounts.drift_signer,
-            state.signer_nonce,
-            pay_from_insurance,
-            &mint,
+        controller::insurance::record_insurance_fund_payment(
+            &insurance_fund_performance,
+            &spot_market_map
+                .get_ref(&quote_spot_market_index)?
+                .insurance_fund,
+            junior_insurance_vault_amount,
+            insurance_fund_payment,
+            now,
         )?;
 
+        if let Some(perp_insurance_fund) = &perp_insurance_fund {
+            controller::insurance::record_insurance_fund_payment(
+                &perp_insurance_fund_performance,
+                &load!(perp_insurance_fund)?.insurance_fund,
+                perp_insurance_vault_amount,
+                perp_insurance_fund_payment,
+                now,
+            )?;
+        }
+
+        if let (Some(senior_insurance_fund), Some(senior_insurance_vault_amount)) =
+            (&senior_insurance_fund, senior_insurance_vault_amount)
+        {
+            controller::insurance::record_insurance_fund_payment(
+                &senior_insurance_fund_performance,
+                &load!(senior_insurance_fund)?.insurance_fund,
+                senior_insurance_vault_amount,
+                senior_insurance_fund_payment,
+                now,
+            )?;
+        }
+
         validate!(
             ctx.accounts.insurance_fund_vault.amount > 0,
             ErrorCode::InvalidIFDetected,
@@ -1299,6 +1869,26 @@ pub fn handle_resolve_spot_bankruptcy<'c: 'info, 'info>(
         Some(state.oracle_guard_rails),
     )?;
 
+    let insurance_fund_performance = get_insurance_fund_performance(
+        remaining_accounts_iter,
+        market_index,
+        InsuranceFundTranche::Junior,
+        spot_market_map
+            .get_ref(&market_index)?
+            .has_insurance_fund_performance(),
+    )?;
+    let mut senior_insurance_fund = get_senior_insurance_fund(
+        remaining_accounts_iter,
+        &*spot_market_map.get_ref(&market_index)?,
+    )?;
+    let senior_insurance_fund_performance = get_insurance_fund_performance(
+        remaining_accounts_iter,
+        market_index,
+        InsuranceFundTranche::Senior,
+        spot_market_map
+            .get_ref(&market_index)?
+            .has_senior_insurance_fund_performance(),
+    )?;
     let mint = get_token_mint(remaining_accounts_iter)?;
 
     {
@@ -1306,6 +1896,7 @@ pub fn handle_resolve_spot_bankruptcy<'c: 'info, 'info>(
         controller::insurance::attempt_settle_revenue_to_insurance_fund(
             &ctx.accounts.spot_market_vault,
             &ctx.accounts.insurance_fund_vault,
+            &mut senior_insurance_fund,
             spot_market,
             now,
             &ctx.accounts.token_program,
@@ -1323,6 +1914,19 @@ pub fn handle_resolve_spot_bankruptcy<'c: 'info, 'info>(
         )?;
     }
 
+    let (senior_insurance_fund, senior_insurance_fund_vault) = match senior_insurance_fund {
+        Some((senior_insurance_fund, senior_insurance_fund_vault)) => (
+            Some(senior_insurance_fund),
+            Some(senior_insurance_fund_vault),
+        ),
+        None => (None, None),
+    };
+
+    let junior_insurance_vault_amount = ctx.accounts.insurance_fund_vault.amount;
+    let senior_insurance_vault_amount = senior_insurance_fund_vault
+        .as_ref()
+        .map(|senior_insurance_fund_vault| senior_insurance_fund_vault.amount);
+
     let pay_from_insurance = controller::liquidation::resolve_spot_bankruptcy(
         market_index,
         user,
@@ -1333,20 +1937,45 @@ pub fn handle_resolve_spot_bankruptcy<'c: 'info, 'info>(
         &spot_market_map,
         &mut oracle_map,
         now,
-        ctx.accounts.insurance_fund_vault.amount,
+        controller::insurance::get_tranched_insurance_vault_amount(
+            junior_insurance_vault_amount,
+            senior_insurance_vault_amount,
+        )?,
     )?;
 
     if pay_from_insurance > 0 {
-        controller::token::send_from_program_vault(
-            &ctx.accounts.token_program,
-            &ctx.accounts.insurance_fund_vault,
-            &ctx.accounts.spot_market_vault,
-            &ctx.accounts.drift_signer,
-            ctx.accounts.state.signer_nonce,
-            pay_from_insurance,
-            &mint,
+        let (insurance_fund_payment, senior_insurance_fund_payment) =
+            controller::insurance::send_from_insurance_fund_tranches(
+                &ctx.accounts.token_program,
+                &ctx.accounts.insurance_fund_vault,
+                &senior_insurance_fund_vault,
+                &ctx.accounts.spot_market_vault,
+                &ctx.accounts.drift_signer,
+                &ctx.accounts.state,
+                pay_from_insurance,
+                &mint,
+            )?;
+
+        controller::insurance::record_insurance_fund_payment(
+            &insurance_fund_performance,
+            &spot_market_map.get_ref(&market_index)?.insurance_fund,
+            junior_insurance_vault_amount,
+            insurance_fund_payment,
+            now,
         )?;
 
+        if let (Some(senior_insurance_fund), Some(senior_insurance_vault_amount)) =
+            (&senior_insurance_fund, senior_insurance_vault_amount)
+        {
+            controller::insurance::record_insurance_fund_payment(
+                &senior_insurance_fund_performance,
+                &load!(senior_insurance_fund)?.insurance_fund,
+                senior_insurance_vault_amount,
+                senior_insurance_fund_payment,
+                now,
+            )?;
+        }
+
         validate!(
             ctx.accounts.insurance_fund_vault.amount > 0,
             ErrorCode::InvalidIFDetected,
@@ -1367,13 +1996,52 @@ pub fn handle_resolve_spot_bankruptcy<'c: 'info, 'info>(
     Ok(())
 }
 
+#[access_control(
+    amm_not_paused(&ctx.accounts.state)
+)]
+pub fn handle_fulfill_perp_lp_withdraw_request<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, FulfillPerpLpWithdrawRequest<'info>>,
+) -> Result<()> {
+    let user_key = ctx.accounts.user.key();
+    let user = &mut load_mut!(ctx.accounts.user)?;
+
+    let state = &ctx.accounts.state;
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    let AccountMaps {
+        perp_market_map,
+        mut oracle_map,
+        ..
+    } = load_maps(
+        &mut ctx.remaining_accounts.iter().peekable(),
+        &get_writable_perp_market_set(user.lp_withdraw_request_market_index),
+        &MarketSet::new(),
+        clock.slot,
+        Some(state.oracle_guard_rails),
+    )?;
+
+    controller::lp::fulfill_perp_lp_withdraw_request(
+        perp_market_map,
+        &mut oracle_map,
+        state,
+        user,
+        user_key,
+        now,
+    )?;
+
+    user.update_last_active_slot(clock.slot);
+
+    Ok(())
+}
+
 #[access_control(
     perp_market_valid(&ctx.accounts.perp_market)
     funding_not_paused(&ctx.accounts.state)
     valid_oracle_for_perp_market(&ctx.accounts.oracle, &ctx.accounts.perp_market)
 )]
-pub fn handle_update_funding_rate(
-    ctx: Context<UpdateFundingRate>,
+pub fn handle_update_funding_rate<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, UpdateFundingRate<'info>>,
     perp_market_index: u16,
 ) -> Result<()> {
     let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
@@ -1402,6 +2070,13 @@ pub fn handle_update_funding_rate(
     let funding_paused =
         state.funding_paused()? || perp_market.is_operation_paused(PerpOperation::UpdateFunding);
 
+    let depth_bounds = get_perp_market_depth_bounds(&mut ctx.remaining_accounts.iter().peekable())?;
+    let depth_bounds = controller::amm::select_perp_market_depth_bounds(
+        depth_bounds.as_deref(),
+        perp_market_index,
+        perp_market.has_depth_bounds,
+    )?;
+
     let is_updated = controller::funding::update_funding_rate(
         perp_market_index,
         perp_market,
@@ -1411,6 +2086,7 @@ pub fn handle_update_funding_rate(
         &state.oracle_guard_rails,
         funding_paused,
         None,
+        depth_bounds,
     )?;
 
     if !is_updated {
@@ -1429,6 +2105,61 @@ pub fn handle_update_funding_rate(
     Ok(())
 }
 
+#[access_control(
+    perp_market_valid(&ctx.accounts.perp_market)
+    valid_oracle_for_perp_market(&ctx.accounts.oracle, &ctx.accounts.perp_market)
+)]
+pub fn handle_view_predicted_funding_rate<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, ViewPredictedFundingRate<'info>>,
+    perp_market_index: u16,
+) -> Result<PredictedFundingRate> {
+    let perp_market = &load!(ctx.accounts.perp_market)?;
+    let user = get_user(&mut ctx.remaining_accounts.iter().peekable())?;
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let clock_slot = clock.slot;
+    let state = &ctx.accounts.state;
+    let mut oracle_map = OracleMap::load_one(
+        &ctx.accounts.oracle,
+        clock_slot,
+        Some(state.oracle_guard_rails),
+    )?;
+
+    validate!(
+        perp_market.market_index == perp_market_index,
+        ErrorCode::InvalidMarketAccount,
+        "perp market index {} does not match perp_market_index {}",
+        perp_market.market_index,
+        perp_market_index
+    )?;
+
+    let oracle_price_data = oracle_map.get_price_data(&perp_market.amm.oracle)?;
+
+    let user = match &user {
+        Some(user) => Some(load!(user)?),
+        None => None,
+    };
+
+    let predicted_funding_rate = controller::funding::calculate_predicted_funding_rate(
+        perp_market,
+        oracle_price_data,
+        state,
+        now,
+        clock_slot,
+        user.as_ref()
+            .and_then(|user| user.get_perp_position(perp_market_index).ok()),
+    )?;
+
+    msg!(
+        "predicted funding rate long = {} short = {} payment = {}",
+        predicted_funding_rate.funding_rate_long,
+        predicted_funding_rate.funding_rate_short,
+        predicted_funding_rate.funding_payment
+    );
+
+    Ok(predicted_funding_rate)
+}
+
 #[access_control(
     valid_oracle_for_perp_market(&ctx.accounts.oracle, &ctx.accounts.perp_market)
 )]
@@ -1440,7 +2171,7 @@ pub fn handle_update_prelaunch_oracle(ctx: Context<UpdatePrelaunchOracle>) -> Re
     let perp_market = &load!(ctx.accounts.perp_market)?;
 
     validate!(
-        perp_market.amm.oracle_source == OracleSource::Prelaunch,
+        perp_market.amm.oracle_source.is_prelaunch(),
         ErrorCode::DefaultError,
         "wrong oracle source"
     )?;
@@ -1450,6 +2181,48 @@ pub fn handle_update_prelaunch_oracle(ctx: Context<UpdatePrelaunchOracle>) -> Re
     Ok(())
 }
 
+pub fn handle_post_signed_price(
+    ctx: Context<PostSignedPrice>,
+    price: i64,
+    confidence: u64,
+    exponent: i32,
+) -> Result<()> {
+    OracleSource::SignedPrice.validate_available()?;
+
+    let slot = Clock::get()?.slot;
+    let mut signed_price_account = load_mut!(ctx.accounts.signed_price_account)?;
+
+    signed_price_account.post(price, confidence, exponent, slot)?;
+
+    Ok(())
+}
+
+pub fn handle_update_oracle_aggregator<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, UpdateOracleAggregator<'info>>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let state = &ctx.accounts.state;
+    let oracle_map = OracleMap::load(
+        &mut ctx.remaining_accounts.iter().peekable(),
+        clock.slot,
+        Some(state.oracle_guard_rails),
+    )?;
+
+    let mut oracle_aggregator = load_mut!(ctx.accounts.oracle_aggregator)?;
+    msg!("oracle aggregator {}", oracle_aggregator.aggregator_id);
+
+    let (oracle_price_data, contributing_sources) = oracle_map
+        .get_aggregated_price_data(&oracle_aggregator, &ValidityGuardRailsOverride::default())?
+        .ok_or_else(|| {
+            msg!("must pass every source oracle account");
+            ErrorCode::OracleNotFound
+        })?;
+
+    oracle_aggregator.update(&oracle_price_data, contributing_sources, clock.slot)?;
+
+    Ok(())
+}
+
 #[access_control(
     perp_market_valid(&ctx.accounts.perp_market)
     funding_not_paused(&ctx.accounts.state)
@@ -1488,6 +2261,13 @@ pub fn handle_update_perp_bid_ask_twap<'c: 'info, 'info>(
     let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
     let makers = load_user_map(remaining_accounts_iter, false)?;
 
+    let depth_bounds = get_perp_market_depth_bounds(remaining_accounts_iter)?;
+    let depth_bounds = controller::amm::select_perp_market_depth_bounds(
+        depth_bounds.as_deref(),
+        perp_market.market_index,
+        perp_market.has_depth_bounds,
+    )?;
+
     let depth = perp_market.get_market_depth_for_funding_rate()?;
 
     let (bids, asks) =
@@ -1536,6 +2316,7 @@ pub fn handle_update_perp_bid_ask_twap<'c: 'info, 'info>(
         &state.oracle_guard_rails,
         funding_paused,
         None,
+        depth_bounds,
     )?;
 
     Ok(())
@@ -1552,6 +2333,19 @@ pub fn handle_settle_revenue_to_insurance_fund<'c: 'info, 'info>(
     let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
 
     let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+    let insurance_fund_performance = get_insurance_fund_performance(
+        remaining_accounts_iter,
+        spot_market.market_index,
+        InsuranceFundTranche::Junior,
+        spot_market.has_insurance_fund_performance(),
+    )?;
+    let senior_insurance_fund = get_senior_insurance_fund(remaining_accounts_iter, spot_market)?;
+    let senior_insurance_fund_performance = get_insurance_fund_performance(
+        remaining_accounts_iter,
+        spot_market.market_index,
+        InsuranceFundTranche::Senior,
+        spot_market.has_senior_insurance_fund_performance(),
+    )?;
     let mint = get_token_mint(remaining_accounts_iter)?;
 
     validate!(
@@ -1585,25 +2379,192 @@ pub fn handle_settle_revenue_to_insurance_fund<'c: 'info, 'info>(
         time_until_next_update
     )?;
 
-    // uses proportion of revenue pool allocated to insurance fund
-    let token_amount = controller::insurance::settle_revenue_to_insurance_fund(
-        spot_vault_amount,
-        insurance_vault_amount,
+    let insurance_fund_before = spot_market.insurance_fund;
+    let senior_insurance_fund_before = match &senior_insurance_fund {
+        Some((senior_insurance_fund, _)) => Some(load!(senior_insurance_fund)?.insurance_fund),
+        None => None,
+    };
+
+    // uses proportion of revenue pool allocated to insurance fund, split between the tranches
+    let (token_amount, senior_token_amount) = match &senior_insurance_fund {
+        Some((senior_insurance_fund, senior_insurance_fund_vault)) => {
+            controller::insurance::settle_revenue_to_insurance_fund_tranches(
+                spot_vault_amount,
+                insurance_vault_amount,
+                Some((
+                    &mut *load_mut!(senior_insurance_fund)?,
+                    senior_insurance_fund_vault.amount,
+                )),
+                spot_market,
+                now,
+                true,
+            )?
+        }
+        None => controller::insurance::settle_revenue_to_insurance_fund_tranches(
+            spot_vault_amount,
+            insurance_vault_amount,
+            None,
+            spot_market,
+            now,
+            true,
+        )?,
+    };
+
+    spot_market.insurance_fund.last_revenue_settle_ts = now;
+
+    if let Some(insurance_fund_performance) = insurance_fund_performance {
+        let insurance_fund_performance = &mut load_mut!(insurance_fund_performance)?;
+        controller::insurance::update_insurance_fund_performance_for_revenue_settle(
+            insurance_fund_performance,
+            &insurance_fund_before,
+            insurance_vault_amount,
+            &spot_market.insurance_fund,
+            spot_market.insurance_fund.revenue_settle_period,
+            token_amount,
+            now,
+        )?;
+    }
+
+    if let (
+        Some((senior_insurance_fund, senior_insurance_fund_vault)),
+        Some(senior_insurance_fund_before),
+        Some(senior_insurance_fund_performance),
+    ) = (
+        &senior_insurance_fund,
+        &senior_insurance_fund_before,
+        senior_insurance_fund_performance,
+    ) {
+        let senior_insurance_fund_performance = &mut load_mut!(senior_insurance_fund_performance)?;
+        controller::insurance::update_insurance_fund_performance_for_revenue_settle(
+            senior_insurance_fund_performance,
+            senior_insurance_fund_before,
+            senior_insurance_fund_vault.amount,
+            &load!(senior_insurance_fund)?.insurance_fund,
+            spot_market.insurance_fund.revenue_settle_period,
+            senior_token_amount,
+            now,
+        )?;
+    }
+
+    controller::token::send_from_program_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.spot_market_vault,
+        &ctx.accounts.insurance_fund_vault,
+        &ctx.accounts.drift_signer,
+        state.signer_nonce,
+        token_amount,
+        &mint,
+        None,
+    )?;
+
+    if let Some((_, senior_insurance_fund_vault)) = &senior_insurance_fund {
+        if senior_token_amount > 0 {
+            controller::token::send_from_program_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.spot_market_vault,
+                senior_insurance_fund_vault,
+                &ctx.accounts.drift_signer,
+                state.signer_nonce,
+                senior_token_amount,
+                &mint,
+                None,
+            )?;
+        }
+    }
+
+    // reload the spot market vault balance so it's up-to-date
+    ctx.accounts.spot_market_vault.reload()?;
+    math::spot_withdraw::validate_spot_market_vault_amount(
         spot_market,
+        ctx.accounts.spot_market_vault.amount,
+    )?;
+
+    Ok(())
+}
+
+#[access_control(
+    withdraw_not_paused(&ctx.accounts.state)
+)]
+pub fn handle_settle_revenue_to_perp_insurance_fund<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, SettleRevenueToPerpInsuranceFund<'info>>,
+    perp_market_index: u16,
+) -> Result<()> {
+    let state = &ctx.accounts.state;
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    let perp_insurance_fund = &mut load_mut!(ctx.accounts.perp_insurance_fund)?;
+
+    let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+    let perp_insurance_fund_performance = get_insurance_fund_performance(
+        remaining_accounts_iter,
+        perp_market.market_index,
+        InsuranceFundTranche::PerpMarket,
+        perp_market.has_perp_insurance_fund_performance,
+    )?;
+    let mint = get_token_mint(remaining_accounts_iter)?;
+
+    validate!(
+        perp_market_index == perp_market.market_index,
+        ErrorCode::InvalidMarketAccount,
+        "invalid perp_market passed"
+    )?;
+
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    validate!(
+        !perp_market.is_in_settlement(now),
+        ErrorCode::MarketActionPaused,
+        "Market is in settlement mode",
+    )?;
+
+    let time_until_next_update = math::helpers::on_the_hour_update(
         now,
-        true,
+        perp_insurance_fund.insurance_fund.last_revenue_settle_ts,
+        perp_insurance_fund.insurance_fund.revenue_settle_period,
     )?;
 
-    spot_market.insurance_fund.last_revenue_settle_ts = now;
+    validate!(
+        time_until_next_update == 0,
+        ErrorCode::RevenueSettingsCannotSettleToIF,
+        "Must wait {} seconds until next available settlement time",
+        time_until_next_update
+    )?;
+
+    let perp_insurance_fund_before = perp_insurance_fund.insurance_fund;
+    let perp_insurance_vault_amount = ctx.accounts.perp_insurance_fund_vault.amount;
+
+    let token_amount = controller::insurance::settle_revenue_to_perp_insurance_fund(
+        ctx.accounts.spot_market_vault.amount,
+        perp_insurance_vault_amount,
+        spot_market,
+        perp_market,
+        perp_insurance_fund,
+        now,
+    )?;
+
+    if let Some(perp_insurance_fund_performance) = perp_insurance_fund_performance {
+        let perp_insurance_fund_performance = &mut load_mut!(perp_insurance_fund_performance)?;
+        controller::insurance::update_insurance_fund_performance_for_revenue_settle(
+            perp_insurance_fund_performance,
+            &perp_insurance_fund_before,
+            perp_insurance_vault_amount,
+            &perp_insurance_fund.insurance_fund,
+            perp_insurance_fund.insurance_fund.revenue_settle_period,
+            token_amount,
+            now,
+        )?;
+    }
 
     controller::token::send_from_program_vault(
         &ctx.accounts.token_program,
         &ctx.accounts.spot_market_vault,
-        &ctx.accounts.insurance_fund_vault,
+        &ctx.accounts.perp_insurance_fund_vault,
         &ctx.accounts.drift_signer,
         state.signer_nonce,
         token_amount,
         &mint,
+        None,
     )?;
 
     // reload the spot market vault balance so it's up-to-date
@@ -1661,6 +2622,125 @@ pub fn handle_update_spot_market_cumulative_interest(
     Ok(())
 }
 
+#[access_control(
+    valid_oracle_for_perp_market(&ctx.accounts.oracle, &ctx.accounts.perp_market)
+)]
+pub fn handle_update_perp_market_oracle_circuit_breaker_window(
+    ctx: Context<UpdatePerpMarketOracleCircuitBreakerWindow>,
+) -> Result<()> {
+    let perp_market = &mut load_mut!(ctx.accounts.perp_market)?;
+    let state = &ctx.accounts.state;
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let slot = clock.slot;
+
+    let mut oracle_map =
+        OracleMap::load_one(&ctx.accounts.oracle, slot, Some(state.oracle_guard_rails))?;
+
+    let (oracle_price_data, oracle_validity) = oracle_map.get_price_data_and_validity(
+        MarketType::Perp,
+        perp_market.market_index,
+        &perp_market.amm.oracle,
+        perp_market
+            .amm
+            .historical_oracle_data
+            .last_oracle_price_twap,
+        &perp_market.oracle_validity_guard_rails,
+        perp_market.get_max_confidence_interval_multiplier()?,
+    )?;
+
+    if !is_oracle_valid_for_action(oracle_validity, Some(DriftAction::UpdateTwap))? {
+        msg!(
+            "perp market {} oracle invalid ({:?}), circuit breaker window not updated",
+            perp_market.market_index,
+            oracle_validity
+        );
+        return Ok(());
+    }
+
+    let window = &mut ctx.accounts.oracle_circuit_breaker_window;
+    if let Some(tripped) = window.update(
+        &mut perp_market.oracle_circuit_breaker,
+        oracle_price_data.price,
+        slot,
+        now,
+    )? {
+        msg!(
+            "perp market {} oracle circuit breaker tripped = {}",
+            perp_market.market_index,
+            tripped
+        );
+        emit!(OracleCircuitBreakerRecord {
+            ts: now,
+            market_type: MarketType::Perp,
+            market_index: perp_market.market_index,
+            tripped,
+            oracle_price: oracle_price_data.price,
+            reference_price: window.reference_price,
+        });
+    }
+
+    Ok(())
+}
+
+#[access_control(
+    valid_oracle_for_spot_market(&ctx.accounts.oracle, &ctx.accounts.spot_market)
+)]
+pub fn handle_update_spot_market_oracle_circuit_breaker_window(
+    ctx: Context<UpdateSpotMarketOracleCircuitBreakerWindow>,
+) -> Result<()> {
+    let spot_market = &mut load_mut!(ctx.accounts.spot_market)?;
+    let state = &ctx.accounts.state;
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let slot = clock.slot;
+
+    let mut oracle_map =
+        OracleMap::load_one(&ctx.accounts.oracle, slot, Some(state.oracle_guard_rails))?;
+
+    let (oracle_price_data, oracle_validity) = oracle_map.get_price_data_and_validity(
+        MarketType::Spot,
+        spot_market.market_index,
+        &spot_market.oracle,
+        spot_market.historical_oracle_data.last_oracle_price_twap,
+        &spot_market.oracle_validity_guard_rails,
+        spot_market.get_max_confidence_interval_multiplier()?,
+    )?;
+
+    if !is_oracle_valid_for_action(oracle_validity, Some(DriftAction::UpdateTwap))? {
+        msg!(
+            "spot market {} oracle invalid ({:?}), circuit breaker window not updated",
+            spot_market.market_index,
+            oracle_validity
+        );
+        return Ok(());
+    }
+
+    let window = &mut ctx.accounts.oracle_circuit_breaker_window;
+    if let Some(tripped) = window.update(
+        &mut spot_market.oracle_circuit_breaker,
+        oracle_price_data.price,
+        slot,
+        now,
+    )? {
+        msg!(
+            "spot market {} oracle circuit breaker tripped = {}",
+            spot_market.market_index,
+            tripped
+        );
+        emit!(OracleCircuitBreakerRecord {
+            ts: now,
+            market_type: MarketType::Spot,
+            market_index: spot_market.market_index,
+            tripped,
+            oracle_price: oracle_price_data.price,
+            reference_price: window.reference_price,
+        });
+    }
+
+    Ok(())
+}
+
 #[access_control(
     exchange_not_paused(&ctx.accounts.state)
 )]
@@ -1797,6 +2877,11 @@ pub struct TriggerOrder<'info> {
         constraint = can_sign_for_user(&filler, &authority)?
     )]
     pub filler: AccountLoader<'info, User>,
+    #[account(
+        mut,
+        constraint = is_stats_for_user(&filler, &filler_stats)?
+    )]
+    pub filler_stats: AccountLoader<'info, UserStats>,
     #[account(mut)]
     pub user: AccountLoader<'info, User>,
 }
@@ -1827,6 +2912,104 @@ pub struct UpdateUserIdle<'info> {
     pub user: AccountLoader<'info, User>,
 }
 
+#[derive(Accounts)]
+#[instruction(season: u16)]
+pub struct InitializeUserFuelSeasonRecord<'info> {
+    #[account(has_one = authority)]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    /// CHECK: checked by user_stats has_one
+    pub authority: AccountInfo<'info>,
+    #[account(
+        init,
+        seeds = [b"user_fuel_season_record".as_ref(), authority.key.as_ref(), season.to_le_bytes().as_ref()],
+        space = UserFuelSeasonRecord::SIZE,
+        bump,
+        payer = payer
+    )]
+    pub user_fuel_season_record: AccountLoader<'info, UserFuelSeasonRecord>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(season: u16)]
+pub struct SnapshotUserFuelSeason<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        has_one = authority
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    /// CHECK: checked by user_stats has_one
+    pub authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"user_fuel_season_record".as_ref(), authority.key.as_ref(), season.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub user_fuel_season_record: AccountLoader<'info, UserFuelSeasonRecord>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct SettleMakerRewardsEpoch<'info> {
+    #[account(
+        mut,
+        seeds = [b"maker_rewards_program".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub maker_rewards_program: AccountLoader<'info, MakerRewardsProgram>,
+    #[account(
+        seeds = [b"maker_rewards_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub maker_rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct UpdateMakerRewardsDepth<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        seeds = [b"perp_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub perp_market: AccountLoader<'info, PerpMarket>,
+    /// CHECK: checked in `update_maker_rewards_depth` ix constraint
+    pub oracle: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"maker_rewards_program".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub maker_rewards_program: AccountLoader<'info, MakerRewardsProgram>,
+}
+
+/// Permissionless: anyone can settle a matured loan, or an unmatured one once the borrower can be liquidated
+#[derive(Accounts)]
+pub struct SettleTermLoan<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        constraint = is_lender_for_term_loan(&term_loan, &lender)?,
+        constraint = is_borrower_for_term_loan(&term_loan, &borrower)?,
+        close = lender_authority
+    )]
+    pub term_loan: AccountLoader<'info, TermLoan>,
+    #[account(mut)]
+    pub lender: AccountLoader<'info, User>,
+    #[account(mut)]
+    pub borrower: AccountLoader<'info, User>,
+    /// CHECK: checked against term loan lender authority, receives the account rent
+    #[account(
+        mut,
+        constraint = term_loan.load()?.lender_authority.eq(lender_authority.key)
+    )]
+    pub lender_authority: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 pub struct SettlePNL<'info> {
     pub state: Box<Account<'info, State>>,
@@ -1852,6 +3035,12 @@ pub struct SettleLP<'info> {
     pub state: Box<Account<'info, State>>,
     #[account(mut)]
     pub user: AccountLoader<'info, User>,
+    #[account(
+        mut,
+        constraint = is_stats_for_user(&user, &user_stats)?
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
@@ -2051,6 +3240,44 @@ pub struct SettleRevenueToInsuranceFund<'info> {
     pub token_program: Interface<'info, TokenInterface>,
 }
 
+#[derive(Accounts)]
+#[instruction(perp_market_index: u16,)]
+pub struct SettleRevenueToPerpInsuranceFund<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", QUOTE_SPOT_MARKET_INDEX.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    #[account(mut)]
+    pub perp_market: AccountLoader<'info, PerpMarket>,
+    #[account(
+        mut,
+        seeds = [b"perp_insurance_fund".as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub perp_insurance_fund: AccountLoader<'info, PerpInsuranceFund>,
+    #[account(
+        mut,
+        seeds = [b"spot_market_vault".as_ref(), QUOTE_SPOT_MARKET_INDEX.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub spot_market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        constraint = state.signer.eq(&drift_signer.key())
+    )]
+    /// CHECK: forced drift_signer
+    pub drift_signer: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"perp_insurance_fund_vault".as_ref(), perp_market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub perp_insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateSpotMarketCumulativeInterest<'info> {
     pub state: Box<Account<'info, State>>,
@@ -2065,12 +3292,49 @@ pub struct UpdateSpotMarketCumulativeInterest<'info> {
     pub spot_market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 }
 
+#[derive(Accounts)]
+pub struct UpdatePerpMarketOracleCircuitBreakerWindow<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(mut)]
+    pub perp_market: AccountLoader<'info, PerpMarket>,
+    /// CHECK: checked in `update_perp_market_oracle_circuit_breaker_window` ix constraint
+    pub oracle: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"perp_market_oracle_circuit_breaker_window".as_ref(), perp_market.load()?.market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub oracle_circuit_breaker_window: Box<Account<'info, OracleCircuitBreakerWindow>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSpotMarketOracleCircuitBreakerWindow<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(mut)]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    /// CHECK: checked in `update_spot_market_oracle_circuit_breaker_window` ix constraint
+    pub oracle: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"spot_market_oracle_circuit_breaker_window".as_ref(), spot_market.load()?.market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub oracle_circuit_breaker_window: Box<Account<'info, OracleCircuitBreakerWindow>>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateAMM<'info> {
     pub state: Box<Account<'info, State>>,
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct FulfillPerpLpWithdrawRequest<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(mut)]
+    pub user: AccountLoader<'info, User>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateFundingRate<'info> {
     pub state: Box<Account<'info, State>>,
@@ -2080,6 +3344,14 @@ pub struct UpdateFundingRate<'info> {
     pub oracle: AccountInfo<'info>,
 }
 
+#[derive(Accounts)]
+pub struct ViewPredictedFundingRate<'info> {
+    pub state: Box<Account<'info, State>>,
+    pub perp_market: AccountLoader<'info, PerpMarket>,
+    /// CHECK: checked in `view_predicted_funding_rate` ix constraint
+    pub oracle: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 pub struct UpdatePerpBidAskTwap<'info> {
     pub state: Box<Account<'info, State>>,
@@ -2141,6 +3413,23 @@ pub struct UpdateUserGovTokenInsuranceStake<'info> {
     pub insurance_fund_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 }
 
+#[derive(Accounts)]
+pub struct PostSignedPrice<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority
+    )]
+    pub signed_price_account: AccountLoader<'info, SignedPriceAccount>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOracleAggregator<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(mut)]
+    pub oracle_aggregator: AccountLoader<'info, OracleAggregator>,
+}
+
 #[derive(Accounts)]
 pub struct UpdatePrelaunchOracle<'info> {
     pub state: Box<Account<'info, State>>,
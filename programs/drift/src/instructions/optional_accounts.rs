@@ -4,22 +4,29 @@ use std::convert::TryFrom;
 
 use crate::error::ErrorCode::UnableToLoadOracle;
 use crate::math::safe_unwrap::SafeUnwrap;
+use crate::state::insurance_fund_stake::{
+    InsuranceFundPerformance, InsuranceFundTranche, PerpInsuranceFund, SeniorInsuranceFund,
+};
 use crate::state::load_ref::load_ref_mut;
+use crate::state::maker_rewards::MakerRewardsProgram;
 use crate::state::oracle::PrelaunchOracle;
 use crate::state::oracle_map::OracleMap;
-use crate::state::perp_market::PerpMarket;
+use crate::state::perp_market::{PerpMarket, PerpMarketDepthBounds};
 use crate::state::perp_market_map::{MarketSet, PerpMarketMap};
+use crate::state::spot_market::SpotMarket;
 use crate::state::spot_market_map::SpotMarketMap;
-use crate::state::state::OracleGuardRails;
+use crate::state::state::{
+    FeeTierQualifications, MarketFeeStructure, OracleGuardRails, ReferralTierConfig, State,
+};
 use crate::state::traits::Size;
-use crate::state::user::{User, UserStats};
-use crate::{validate, OracleSource};
+use crate::state::user::{MarketType, User, UserStats};
+use crate::validate;
 use anchor_lang::accounts::account::Account;
-use anchor_lang::prelude::{AccountInfo, Interface};
+use anchor_lang::prelude::{AccountInfo, Interface, Pubkey};
 use anchor_lang::prelude::{AccountLoader, InterfaceAccount};
 use anchor_lang::Discriminator;
 use anchor_spl::token::TokenAccount;
-use anchor_spl::token_interface::{Mint, TokenInterface};
+use anchor_spl::token_interface::{Mint, TokenAccount as TokenAccountInterface, TokenInterface};
 use arrayref::array_ref;
 use solana_program::account_info::next_account_info;
 use solana_program::msg;
@@ -64,7 +71,7 @@ pub fn update_prelaunch_oracle(
     oracle_map: &OracleMap,
     slot: u64,
 ) -> DriftResult {
-    if perp_market.amm.oracle_source != OracleSource::Prelaunch {
+    if !perp_market.amm.oracle_source.is_prelaunch() {
         return Ok(());
     }
 
@@ -235,3 +242,496 @@ pub fn get_token_mint<'a>(
         Err(_) => Ok(None),
     }
 }
+
+/// Loads the perp market's depth bounds if it is the next remaining account
+pub fn get_perp_market_depth_bounds<'a>(
+    account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+) -> DriftResult<Option<Account<'a, PerpMarketDepthBounds>>> {
+    let depth_bounds_account_info = account_info_iter.peek();
+
+    if depth_bounds_account_info.is_none() {
+        return Ok(None);
+    }
+
+    let depth_bounds_account_info = depth_bounds_account_info.safe_unwrap()?;
+    let data = depth_bounds_account_info.try_borrow_data().map_err(|e| {
+        msg!("{:?}", e);
+        ErrorCode::InvalidPerpMarketDepthBounds
+    })?;
+
+    if data.len() < PerpMarketDepthBounds::SIZE {
+        return Ok(None);
+    }
+
+    let depth_bounds_discriminator: [u8; 8] = PerpMarketDepthBounds::discriminator();
+    let account_discriminator = array_ref![data, 0, 8];
+    if account_discriminator != &depth_bounds_discriminator {
+        return Ok(None);
+    }
+
+    drop(data);
+
+    let depth_bounds_account_info = next_account_info(account_info_iter).safe_unwrap()?;
+    let depth_bounds: Account<PerpMarketDepthBounds> = Account::try_from(depth_bounds_account_info)
+        .or(Err(ErrorCode::InvalidPerpMarketDepthBounds))?;
+
+    Ok(Some(depth_bounds))
+}
+
+/// Loads a user if it is the next remaining account. Used by views where the user is optional
+pub fn get_user<'a>(
+    account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+) -> DriftResult<Option<AccountLoader<'a, User>>> {
+    let user_account_info = account_info_iter.peek();
+
+    if user_account_info.is_none() {
+        return Ok(None);
+    }
+
+    let user_account_info = user_account_info.safe_unwrap()?;
+    let data = user_account_info.try_borrow_data().map_err(|e| {
+        msg!("{:?}", e);
+        ErrorCode::CouldNotLoadUserData
+    })?;
+
+    if data.len() < User::SIZE {
+        return Ok(None);
+    }
+
+    let user_discriminator: [u8; 8] = User::discriminator();
+    let account_discriminator = array_ref![data, 0, 8];
+    if account_discriminator != &user_discriminator {
+        return Ok(None);
+    }
+
+    drop(data);
+
+    let user_account_info = next_account_info(account_info_iter).safe_unwrap()?;
+
+    let user: AccountLoader<User> =
+        AccountLoader::try_from(user_account_info).or(Err(ErrorCode::CouldNotLoadUserData))?;
+
+    Ok(Some(user))
+}
+
+/// Loads the keeper's user stats if they are the next remaining account. Keepers pass them to earn
+/// fuel for settling for other users
+pub fn get_keeper_stats<'a>(
+    account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+    authority: &Pubkey,
+) -> DriftResult<Option<AccountLoader<'a, UserStats>>> {
+    let keeper_stats_account_info = account_info_iter.peek();
+
+    if keeper_stats_account_info.is_none() {
+        return Ok(None);
+    }
+
+    let keeper_stats_account_info = keeper_stats_account_info.safe_unwrap()?;
+    let data = keeper_stats_account_info.try_borrow_data().map_err(|e| {
+        msg!("{:?}", e);
+        ErrorCode::CouldNotLoadUserStatsData
+    })?;
+
+    if data.len() < UserStats::SIZE {
+        return Ok(None);
+    }
+
+    let user_stats_discriminator: [u8; 8] = UserStats::discriminator();
+    let account_discriminator = array_ref![data, 0, 8];
+    if account_discriminator != &user_stats_discriminator {
+        return Ok(None);
+    }
+
+    drop(data);
+
+    let keeper_stats_account_info = next_account_info(account_info_iter).safe_unwrap()?;
+
+    validate!(
+        keeper_stats_account_info.is_writable,
+        ErrorCode::InvalidUserStatsAccount,
+        "keeper stats must be writable"
+    )?;
+
+    let keeper_stats: AccountLoader<UserStats> = AccountLoader::try_from(keeper_stats_account_info)
+        .or(Err(ErrorCode::CouldNotLoadUserStatsData))?;
+
+    validate!(
+        keeper_stats
+            .load()
+            .or(Err(ErrorCode::CouldNotLoadUserStatsData))?
+            .authority
+            == *authority,
+        ErrorCode::InvalidUserStatsAccount,
+        "keeper stats authority doesnt match signer"
+    )?;
+
+    Ok(Some(keeper_stats))
+}
+
+/// Loads an insurance fund tranche's performance account. It must be the next remaining account if
+/// the tranche has one
+pub fn get_insurance_fund_performance<'a>(
+    account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+    market_index: u16,
+    tranche: InsuranceFundTranche,
+    has_insurance_fund_performance: bool,
+) -> DriftResult<Option<AccountLoader<'a, InsuranceFundPerformance>>> {
+    if !has_insurance_fund_performance {
+        return Ok(None);
+    }
+
+    let insurance_fund_performance_account_info =
+        next_account_info(account_info_iter).map_err(|_| {
+            msg!(
+                "{:?} insurance fund performance for market {} not passed",
+                tranche,
+                market_index
+            );
+            ErrorCode::InvalidInsuranceFundTranche
+        })?;
+
+    let insurance_fund_performance: AccountLoader<InsuranceFundPerformance> =
+        AccountLoader::try_from(insurance_fund_performance_account_info).map_err(|_| {
+            msg!(
+                "{:?} insurance fund performance for market {} not passed",
+                tranche,
+                market_index
+            );
+            ErrorCode::InvalidInsuranceFundTranche
+        })?;
+
+    {
+        let insurance_fund_performance = insurance_fund_performance
+            .load()
+            .or(Err(ErrorCode::InvalidInsuranceFundTranche))?;
+
+        validate!(
+            insurance_fund_performance.market_index == market_index
+                && insurance_fund_performance.tranche == tranche,
+            ErrorCode::InvalidInsuranceFundTranche,
+            "insurance fund performance {:?} market {} != {:?} market {}",
+            insurance_fund_performance.tranche,
+            insurance_fund_performance.market_index,
+            tranche,
+            market_index
+        )?;
+    }
+
+    Ok(Some(insurance_fund_performance))
+}
+
+/// Loads the referral tier config. It must be the next remaining account, after the makers and
+/// referrer, once the config has been initialized
+pub fn get_referral_tier_config<'a>(
+    account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+    state: &State,
+) -> DriftResult<Option<AccountLoader<'a, ReferralTierConfig>>> {
+    if !state.has_referral_tier_config {
+        return Ok(None);
+    }
+
+    let referral_tier_config_account_info = next_account_info(account_info_iter).map_err(|_| {
+        msg!("referral tier config not passed");
+        ErrorCode::InvalidReferralTierConfig
+    })?;
+
+    let referral_tier_config: AccountLoader<ReferralTierConfig> =
+        AccountLoader::try_from(referral_tier_config_account_info).map_err(|_| {
+            msg!("referral tier config not passed");
+            ErrorCode::InvalidReferralTierConfig
+        })?;
+
+    Ok(Some(referral_tier_config))
+}
+
+/// Loads the fee tier qualifications for the market type if they have been initialized and are
+/// the next remaining account, after the makers and referrer (and referral tier config).
+/// Returns None if they aren't passed, fills then use the default qualifications
+pub fn get_fee_tier_qualifications<'a>(
+    account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+    state: &State,
+    market_type: MarketType,
+) -> DriftResult<Option<Account<'a, FeeTierQualifications>>> {
+    let has_fee_tier_qualifications = match market_type {
+        MarketType::Perp => state.has_perp_fee_tier_qualifications,
+        MarketType::Spot => state.has_spot_fee_tier_qualifications,
+    };
+
+    if !has_fee_tier_qualifications {
+        return Ok(None);
+    }
+
+    let fee_tier_qualifications_account_info = match account_info_iter.peek() {
+        Some(account_info) => account_info,
+        None => return Ok(None),
+    };
+
+    let data = fee_tier_qualifications_account_info
+        .try_borrow_data()
+        .map_err(|e| {
+            msg!("{:?}", e);
+            ErrorCode::InvalidFeeTierQualifications
+        })?;
+
+    if data.len() < FeeTierQualifications::SIZE {
+        return Ok(None);
+    }
+
+    let fee_tier_qualifications_discriminator: [u8; 8] = FeeTierQualifications::discriminator();
+    let account_discriminator = array_ref![data, 0, 8];
+    if account_discriminator != &fee_tier_qualifications_discriminator {
+        return Ok(None);
+    }
+
+    drop(data);
+
+    let fee_tier_qualifications_account_info =
+        next_account_info(account_info_iter).safe_unwrap()?;
+
+    let fee_tier_qualifications: Account<FeeTierQualifications> =
+        Account::try_from(fee_tier_qualifications_account_info)
+            .or(Err(ErrorCode::InvalidFeeTierQualifications))?;
+
+    validate!(
+        fee_tier_qualifications.market_type == market_type,
+        ErrorCode::InvalidFeeTierQualifications,
+        "fee tier qualifications market type {:?} != {:?}",
+        fee_tier_qualifications.market_type,
+        market_type
+    )?;
+
+    Ok(Some(fee_tier_qualifications))
+}
+
+/// Loads the market fee structure override if it is the next remaining account. Must be passed after
+/// the makers and referrer (and referral tier config and fee tier qualifications)
+pub fn get_market_fee_structure<'a>(
+    account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+) -> DriftResult<Option<Account<'a, MarketFeeStructure>>> {
+    let market_fee_structure_account_info = account_info_iter.peek();
+
+    if market_fee_structure_account_info.is_none() {
+        return Ok(None);
+    }
+
+    let market_fee_structure_account_info = market_fee_structure_account_info.safe_unwrap()?;
+    let data = market_fee_structure_account_info
+        .try_borrow_data()
+        .map_err(|e| {
+            msg!("{:?}", e);
+            ErrorCode::InvalidMarketFeeStructure
+        })?;
+
+    if data.len() < MarketFeeStructure::SIZE {
+        return Ok(None);
+    }
+
+    let market_fee_structure_discriminator: [u8; 8] = MarketFeeStructure::discriminator();
+    let account_discriminator = array_ref![data, 0, 8];
+    if account_discriminator != &market_fee_structure_discriminator {
+        return Ok(None);
+    }
+
+    drop(data);
+
+    let market_fee_structure_account_info = next_account_info(account_info_iter).safe_unwrap()?;
+    let market_fee_structure: Account<MarketFeeStructure> =
+        Account::try_from(market_fee_structure_account_info)
+            .or(Err(ErrorCode::InvalidMarketFeeStructure))?;
+
+    Ok(Some(market_fee_structure))
+}
+
+/// Loads the perp market's maker rewards program if it is the next remaining account. Must be passed
+/// after the market fee structure
+pub fn get_maker_rewards_program<'a>(
+    account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+) -> DriftResult<Option<AccountLoader<'a, MakerRewardsProgram>>> {
+    let maker_rewards_program_account_info = account_info_iter.peek();
+
+    if maker_rewards_program_account_info.is_none() {
+        return Ok(None);
+    }
+
+    let maker_rewards_program_account_info = maker_rewards_program_account_info.safe_unwrap()?;
+    let data = maker_rewards_program_account_info
+        .try_borrow_data()
+        .map_err(|e| {
+            msg!("{:?}", e);
+            ErrorCode::InvalidMakerRewardsProgram
+        })?;
+
+    if data.len() < MakerRewardsProgram::SIZE {
+        return Ok(None);
+    }
+
+    let maker_rewards_program_discriminator: [u8; 8] = MakerRewardsProgram::discriminator();
+    let account_discriminator = array_ref![data, 0, 8];
+    if account_discriminator != &maker_rewards_program_discriminator {
+        return Ok(None);
+    }
+
+    drop(data);
+
+    let maker_rewards_program_account_info = next_account_info(account_info_iter).safe_unwrap()?;
+
+    validate!(
+        maker_rewards_program_account_info.is_writable,
+        ErrorCode::InvalidMakerRewardsProgram,
+        "maker rewards program must be writable"
+    )?;
+
+    let maker_rewards_program: AccountLoader<MakerRewardsProgram> =
+        AccountLoader::try_from(maker_rewards_program_account_info)
+            .or(Err(ErrorCode::InvalidMakerRewardsProgram))?;
+
+    Ok(Some(maker_rewards_program))
+}
+
+/// Loads the senior insurance fund and its vault. They must be the next remaining accounts, before
+/// the token mint, if the spot market has a senior insurance fund
+pub fn get_senior_insurance_fund<'a>(
+    account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+    spot_market: &SpotMarket,
+) -> DriftResult<
+    Option<(
+        AccountLoader<'a, SeniorInsuranceFund>,
+        InterfaceAccount<'a, TokenAccountInterface>,
+    )>,
+> {
+    if !spot_market.has_senior_insurance_fund() {
+        return Ok(None);
+    }
+
+    let market_index = spot_market.market_index;
+
+    let senior_insurance_fund_account_info =
+        next_account_info(account_info_iter).map_err(|_| {
+            msg!(
+                "senior insurance fund for spot market {} not passed",
+                market_index
+            );
+            ErrorCode::InvalidInsuranceFundTranche
+        })?;
+
+    let senior_insurance_fund: AccountLoader<SeniorInsuranceFund> =
+        AccountLoader::try_from(senior_insurance_fund_account_info).map_err(|_| {
+            msg!(
+                "senior insurance fund for spot market {} not passed",
+                market_index
+            );
+            ErrorCode::InvalidInsuranceFundTranche
+        })?;
+
+    let senior_insurance_fund_vault_key = {
+        let senior_insurance_fund = senior_insurance_fund
+            .load()
+            .or(Err(ErrorCode::InvalidInsuranceFundTranche))?;
+
+        validate!(
+            senior_insurance_fund.market_index == market_index,
+            ErrorCode::InvalidInsuranceFundTranche,
+            "senior insurance fund market {} != {}",
+            senior_insurance_fund.market_index,
+            market_index
+        )?;
+
+        senior_insurance_fund.insurance_fund.vault
+    };
+
+    let senior_insurance_fund_vault_account_info =
+        next_account_info(account_info_iter).or(Err(ErrorCode::InvalidInsuranceFundTranche))?;
+
+    validate!(
+        senior_insurance_fund_vault_account_info.key == &senior_insurance_fund_vault_key,
+        ErrorCode::InvalidInsuranceFundTranche,
+        "senior insurance fund vault {} != {}",
+        senior_insurance_fund_vault_account_info.key,
+        senior_insurance_fund_vault_key
+    )?;
+
+    let senior_insurance_fund_vault: InterfaceAccount<TokenAccountInterface> =
+        InterfaceAccount::try_from(senior_insurance_fund_vault_account_info)
+            .or(Err(ErrorCode::InvalidInsuranceFundTranche))?;
+
+    Ok(Some((senior_insurance_fund, senior_insurance_fund_vault)))
+}
+
+/// Loads the senior insurance fund vault. The senior insurance fund and its vault must be the next
+/// remaining accounts, before the token mint, if the spot market has a senior insurance fund
+pub fn get_senior_insurance_fund_vault<'a>(
+    account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+    spot_market: &SpotMarket,
+) -> DriftResult<Option<InterfaceAccount<'a, TokenAccountInterface>>> {
+    Ok(get_senior_insurance_fund(account_info_iter, spot_market)?
+        .map(|(_, senior_insurance_fund_vault)| senior_insurance_fund_vault))
+}
+
+/// Loads the perp market's isolated insurance fund and its vault. They must be the next remaining
+/// accounts, before the senior insurance fund and token mint, if the perp market has a perp
+/// insurance fund
+pub fn get_perp_insurance_fund<'a>(
+    account_info_iter: &mut Peekable<Iter<'a, AccountInfo<'a>>>,
+    perp_market: &PerpMarket,
+) -> DriftResult<
+    Option<(
+        AccountLoader<'a, PerpInsuranceFund>,
+        InterfaceAccount<'a, TokenAccountInterface>,
+    )>,
+> {
+    if !perp_market.has_perp_insurance_fund {
+        return Ok(None);
+    }
+
+    let perp_market_index = perp_market.market_index;
+
+    let perp_insurance_fund_account_info = next_account_info(account_info_iter).map_err(|_| {
+        msg!(
+            "perp insurance fund for perp market {} not passed",
+            perp_market_index
+        );
+        ErrorCode::InvalidInsuranceFundTranche
+    })?;
+
+    let perp_insurance_fund: AccountLoader<PerpInsuranceFund> =
+        AccountLoader::try_from(perp_insurance_fund_account_info).map_err(|_| {
+            msg!(
+                "perp insurance fund for perp market {} not passed",
+                perp_market_index
+            );
+            ErrorCode::InvalidInsuranceFundTranche
+        })?;
+
+    let perp_insurance_fund_vault_key = {
+        let perp_insurance_fund = perp_insurance_fund
+            .load()
+            .or(Err(ErrorCode::InvalidInsuranceFundTranche))?;
+
+        validate!(
+            perp_insurance_fund.market_index == perp_market_index,
+            ErrorCode::InvalidInsuranceFundTranche,
+            "perp insurance fund market {} != {}",
+            perp_insurance_fund.market_index,
+            perp_market_index
+        )?;
+
+        perp_insurance_fund.insurance_fund.vault
+    };
+
+    let perp_insurance_fund_vault_account_info =
+        next_account_info(account_info_iter).or(Err(ErrorCode::InvalidInsuranceFundTranche))?;
+
+    validate!(
+        perp_insurance_fund_vault_account_info.key == &perp_insurance_fund_vault_key,
+        ErrorCode::InvalidInsuranceFundTranche,
+        "perp insurance fund vault {} != {}",
+        perp_insurance_fund_vault_account_info.key,
+        perp_insurance_fund_vault_key
+    )?;
+
+    let perp_insurance_fund_vault: InterfaceAccount<TokenAccountInterface> =
+        InterfaceAccount::try_from(perp_insurance_fund_vault_account_info)
+            .or(Err(ErrorCode::InvalidInsuranceFundTranche))?;
+
+    Ok(Some((perp_insurance_fund, perp_insurance_fund_vault)))
+}
@@ -21,14 +21,18 @@ use crate::ids::{
 };
 use crate::instructions::constraints::*;
 use crate::instructions::optional_accounts::{
-    get_referrer_and_referrer_stats, get_whitelist_token, load_maps, AccountMaps,
+    get_fee_tier_qualifications, get_maker_rewards_program, get_market_fee_structure,
+    get_perp_market_depth_bounds, get_referral_tier_config, get_referrer_and_referrer_stats,
+    get_whitelist_token, load_maps, AccountMaps,
 };
 use crate::instructions::SpotFulfillmentType;
 use crate::math::casting::Cast;
+use crate::math::fuel::calculate_fuel_redemption_amount;
 use crate::math::liquidation::is_user_being_liquidated;
 use crate::math::margin::{
-    calculate_max_withdrawable_amount, meets_place_order_margin_requirement,
-    meets_withdraw_margin_requirement, validate_spot_margin_trading, MarginRequirementType,
+    calculate_max_withdrawable_amount, meets_initial_margin_requirement,
+    meets_place_order_margin_requirement, meets_withdraw_margin_requirement,
+    validate_spot_margin_trading, MarginRequirementType,
 };
 use crate::math::safe_math::SafeMath;
 use crate::math::spot_balance::get_token_value;
@@ -41,13 +45,15 @@ use crate::safe_decrement;
 use crate::safe_increment;
 use crate::state::events::{
     DepositDirection, DepositExplanation, DepositRecord, LPAction, LPRecord, NewUserRecord,
-    OrderActionExplanation, SwapRecord,
+    OrderActionExplanation, SwapRecord, TermLoanAction, TermLoanRecord,
 };
 use crate::state::fill_mode::FillMode;
+use crate::state::fuel_season::{FuelSeason, UserFuelSeasonRecord};
 use crate::state::fulfillment_params::drift::MatchFulfillmentParams;
 use crate::state::fulfillment_params::openbook_v2::OpenbookV2FulfillmentParams;
 use crate::state::fulfillment_params::phoenix::PhoenixFulfillmentParams;
 use crate::state::fulfillment_params::serum::SerumFulfillmentParams;
+use crate::state::maker_rewards::MakerRewardsProgram;
 use crate::state::oracle::StrictOraclePrice;
 use crate::state::order_params::{
     ModifyOrderParams, OrderParams, PlaceOrderOptions, PostOnlyParam,
@@ -55,6 +61,7 @@ use crate::state::order_params::{
 use crate::state::paused_operations::{PerpOperation, SpotOperation};
 use crate::state::perp_market::ContractType;
 use crate::state::perp_market::MarketStatus;
+use crate::state::perp_market::PerpMarket;
 use crate::state::perp_market_map::{get_writable_perp_market_set, MarketSet};
 use crate::state::spot_fulfillment_params::SpotFulfillmentParams;
 use crate::state::spot_market::SpotBalanceType;
@@ -63,6 +70,7 @@ use crate::state::spot_market_map::{
     get_writable_spot_market_set, get_writable_spot_market_set_from_many,
 };
 use crate::state::state::State;
+use crate::state::term_loan::{TermLoan, TermLoanStatus};
 use crate::state::traits::Size;
 use crate::state::user::{MarketType, OrderType, ReferrerName, User, UserStats};
 use crate::state::user_map::{load_user_maps, UserMap, UserStatsMap};
@@ -71,7 +79,7 @@ use crate::validation::user::validate_user_deletion;
 use crate::validation::whitelist::validate_whitelist_token;
 use crate::{controller, math};
 use crate::{get_then_update_id, QUOTE_SPOT_MARKET_INDEX};
-use crate::{load, THIRTEEN_DAY};
+use crate::{load, ONE_YEAR, THIRTEEN_DAY};
 use crate::{load_mut, ExchangeStatus};
 use anchor_lang::solana_program::sysvar::instructions;
 use anchor_spl::associated_token::AssociatedToken;
@@ -209,10 +217,11 @@ pub fn handle_initialize_user_stats<'c: 'info, 'info>(
     *user_stats = UserStats {
         authority: ctx.accounts.authority.key(),
         number_of_sub_accounts: 0,
-        last_taker_volume_30d_ts: clock.unix_timestamp,
-        last_maker_volume_30d_ts: clock.unix_timestamp,
+        last_taker_volume_30d_ts: clock.unix_timestamp.cast()?,
+        last_maker_volume_30d_ts: clock.unix_timestamp.cast()?,
         last_filler_volume_30d_ts: clock.unix_timestamp,
         last_fuel_if_bonus_update_ts: clock.unix_timestamp.cast()?,
+        fuel_season: ctx.accounts.state.fuel_season,
         ..UserStats::default()
     };
 
@@ -329,8 +338,20 @@ pub fn handle_deposit<'c: 'info, 'info>(
         amount
     };
 
-    user.increment_total_deposits(
+    // token-2022 transfer fees are withheld from what the vault receives, only credit the net amount
+    let transfer_fee = controller::token::calculate_transfer_fee(&mint, amount)?;
+    let amount_received = amount.safe_sub(transfer_fee)?;
+
+    validate!(
+        amount_received > 0,
+        ErrorCode::InsufficientDeposit,
+        "deposit amount {} does not cover transfer fee {}",
         amount,
+        transfer_fee
+    )?;
+
+    user.increment_total_deposits(
+        amount_received,
         oracle_price_data.price,
         spot_market.get_precision().cast()?,
     )?;
@@ -340,7 +361,7 @@ pub fn handle_deposit<'c: 'info, 'info>(
 
     let spot_position = &mut user.spot_positions[position_index];
     controller::spot_position::update_spot_balances_and_cumulative_deposits(
-        amount as u128,
+        amount_received as u128,
         &SpotBalanceType::Deposit,
         &mut spot_market,
         spot_position,
@@ -387,6 +408,8 @@ pub fn handle_deposit<'c: 'info, 'info>(
 
     let spot_market = &mut spot_market_map.get_ref_mut(&market_index)?;
 
+    let vault_amount_before = ctx.accounts.spot_market_vault.amount;
+
     controller::token::receive(
         &ctx.accounts.token_program,
         &ctx.accounts.user_token_account,
@@ -394,9 +417,25 @@ pub fn handle_deposit<'c: 'info, 'info>(
         &ctx.accounts.authority,
         amount,
         &mint,
+        Some(remaining_accounts_iter),
     )?;
     ctx.accounts.spot_market_vault.reload()?;
 
+    validate!(
+        ctx.accounts
+            .spot_market_vault
+            .amount
+            .safe_sub(vault_amount_before)?
+            == amount_received,
+        ErrorCode::InvalidSpotMarketVault,
+        "spot market vault received {} but expected {}",
+        ctx.accounts
+            .spot_market_vault
+            .amount
+            .safe_sub(vault_amount_before)?,
+        amount_received
+    )?;
+
     let deposit_record_id = get_then_update_id!(spot_market, next_deposit_record_id);
     let oracle_price = oracle_price_data.price;
     let explanation = if is_borrow_before {
@@ -410,7 +449,7 @@ pub fn handle_deposit<'c: 'info, 'info>(
         user_authority: user.authority,
         user: user_key,
         direction: DepositDirection::Deposit,
-        amount,
+        amount: amount_received,
         oracle_price,
         market_deposit_balance: spot_market.deposit_balance,
         market_withdraw_balance: spot_market.borrow_balance,
@@ -421,6 +460,7 @@ pub fn handle_deposit<'c: 'info, 'info>(
         market_index,
         explanation,
         transfer_user: None,
+        transfer_fee,
     };
     emit!(deposit_record);
 
@@ -564,6 +604,9 @@ pub fn handle_withdraw<'c: 'info, 'info>(
         DepositExplanation::None
     };
 
+    // the user token account receives the amount net of any token-2022 transfer fee
+    let transfer_fee = controller::token::calculate_transfer_fee(&mint, amount)?;
+
     let deposit_record_id = get_then_update_id!(spot_market, next_deposit_record_id);
     let deposit_record = DepositRecord {
         ts: now,
@@ -582,6 +625,7 @@ pub fn handle_withdraw<'c: 'info, 'info>(
         total_withdraws_after: user.total_withdraws,
         explanation: deposit_explanation,
         transfer_user: None,
+        transfer_fee,
     };
     emit!(deposit_record);
 
@@ -593,6 +637,7 @@ pub fn handle_withdraw<'c: 'info, 'info>(
         state.signer_nonce,
         amount,
         &mint,
+        Some(remaining_accounts_iter),
     )?;
 
     // reload the spot market vault balance so it's up-to-date
@@ -738,6 +783,7 @@ pub fn handle_transfer_deposit<'c: 'info, 'info>(
             total_withdraws_after: from_user.total_withdraws,
             explanation: DepositExplanation::Transfer,
             transfer_user: Some(to_user_key),
+            transfer_fee: 0,
         };
         emit!(deposit_record);
     }
@@ -794,6 +840,7 @@ pub fn handle_transfer_deposit<'c: 'info, 'info>(
             total_withdraws_after,
             explanation: DepositExplanation::Transfer,
             transfer_user: Some(from_user_key),
+            transfer_fee: 0,
         };
         emit!(deposit_record);
     }
@@ -809,6 +856,248 @@ pub fn handle_transfer_deposit<'c: 'info, 'info>(
     Ok(())
 }
 
+#[access_control(
+    exchange_not_paused(&ctx.accounts.state)
+)]
+pub fn handle_initialize_term_loan_offer(
+    ctx: Context<InitializeTermLoanOffer>,
+    offer_id: u32,
+    market_index: u16,
+    principal: u64,
+    rate: u32,
+    maturity_ts: i64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    let user_key = ctx.accounts.user.key();
+    let user = load!(ctx.accounts.user)?;
+    let spot_market = load!(ctx.accounts.spot_market)?;
+
+    validate!(!user.is_bankrupt(), ErrorCode::UserBankrupt)?;
+
+    validate!(
+        !spot_market.is_reduce_only(),
+        ErrorCode::SpotMarketReduceOnly,
+        "spot market {} in reduce only",
+        market_index
+    )?;
+
+    validate!(
+        principal > 0,
+        ErrorCode::InvalidTermLoan,
+        "term loan principal must be greater than 0"
+    )?;
+
+    validate!(
+        rate <= spot_market.max_borrow_rate,
+        ErrorCode::InvalidTermLoan,
+        "term loan rate {} must be <= max borrow rate {}",
+        rate,
+        spot_market.max_borrow_rate
+    )?;
+
+    validate!(
+        maturity_ts > now && maturity_ts <= now.safe_add(ONE_YEAR.cast()?)?,
+        ErrorCode::InvalidTermLoan,
+        "term loan maturity {} must be in the next year (now {})",
+        maturity_ts,
+        now
+    )?;
+
+    let mut term_loan = ctx
+        .accounts
+        .term_loan
+        .load_init()
+        .or(Err(ErrorCode::UnableToLoadAccountLoader))?;
+
+    *term_loan = TermLoan {
+        lender: user_key,
+        lender_authority: *ctx.accounts.authority.key,
+        principal,
+        maturity_ts,
+        rate,
+        offer_id,
+        market_index,
+        status: TermLoanStatus::Offered,
+        ..TermLoan::default()
+    };
+
+    emit!(TermLoanRecord {
+        ts: now,
+        action: TermLoanAction::Offer,
+        term_loan: ctx.accounts.term_loan.key(),
+        lender: user_key,
+        market_index,
+        principal,
+        rate,
+        maturity_ts,
+        ..TermLoanRecord::default()
+    });
+
+    Ok(())
+}
+
+#[access_control(
+    exchange_not_paused(&ctx.accounts.state)
+)]
+pub fn handle_cancel_term_loan_offer(ctx: Context<CancelTermLoanOffer>) -> Result<()> {
+    let term_loan = load!(ctx.accounts.term_loan)?;
+
+    validate!(
+        term_loan.is_offered(),
+        ErrorCode::InvalidTermLoan,
+        "term loan status {:?} cant be cancelled",
+        term_loan.status
+    )?;
+
+    emit!(TermLoanRecord {
+        ts: Clock::get()?.unix_timestamp,
+        action: TermLoanAction::Cancel,
+        term_loan: ctx.accounts.term_loan.key(),
+        lender: term_loan.lender,
+        market_index: term_loan.market_index,
+        principal: term_loan.principal,
+        rate: term_loan.rate,
+        maturity_ts: term_loan.maturity_ts,
+        ..TermLoanRecord::default()
+    });
+
+    Ok(())
+}
+
+#[access_control(
+    exchange_not_paused(&ctx.accounts.state)
+)]
+pub fn handle_take_term_loan_offer<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, TakeTermLoanOffer<'info>>,
+) -> Result<()> {
+    let state = &ctx.accounts.state;
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    let lender_key = ctx.accounts.lender.key();
+    let borrower_key = ctx.accounts.user.key();
+
+    let term_loan = &mut load_mut!(ctx.accounts.term_loan)?;
+    let lender = &mut load_mut!(ctx.accounts.lender)?;
+    let borrower = &mut load_mut!(ctx.accounts.user)?;
+
+    validate!(
+        lender_key != borrower_key,
+        ErrorCode::InvalidTermLoan,
+        "lender cant take its own term loan offer"
+    )?;
+
+    validate!(
+        !lender.is_bankrupt(),
+        ErrorCode::UserBankrupt,
+        "lender bankrupt"
+    )?;
+    validate!(
+        !lender.is_being_liquidated(),
+        ErrorCode::UserIsBeingLiquidated,
+        "lender being liquidated"
+    )?;
+    validate!(
+        !borrower.is_bankrupt(),
+        ErrorCode::UserBankrupt,
+        "borrower bankrupt"
+    )?;
+    validate!(
+        !borrower.is_being_liquidated(),
+        ErrorCode::UserIsBeingLiquidated,
+        "borrower being liquidated"
+    )?;
+
+    let market_index = term_loan.market_index;
+
+    let AccountMaps {
+        perp_market_map,
+        spot_market_map,
+        mut oracle_map,
+    } = load_maps(
+        &mut ctx.remaining_accounts.iter().peekable(),
+        &MarketSet::new(),
+        &get_writable_spot_market_set(market_index),
+        clock.slot,
+        Some(state.oracle_guard_rails),
+    )?;
+
+    {
+        let spot_market = &mut spot_market_map.get_ref_mut(&market_index)?;
+
+        validate!(
+            !spot_market.is_reduce_only(),
+            ErrorCode::SpotMarketReduceOnly,
+            "spot market {} in reduce only",
+            market_index
+        )?;
+
+        let oracle_price_data = oracle_map.get_price_data(&spot_market.oracle)?;
+        controller::spot_balance::update_spot_market_cumulative_interest(
+            spot_market,
+            Some(oracle_price_data),
+            now,
+        )?;
+
+        controller::term_loan::take_term_loan_offer(
+            term_loan,
+            borrower_key,
+            lender,
+            borrower,
+            spot_market,
+            now,
+        )?;
+    }
+
+    let borrower_meets_initial_margin_requirement = meets_initial_margin_requirement(
+        borrower,
+        &perp_market_map,
+        &spot_market_map,
+        &mut oracle_map,
+    )?;
+
+    validate!(
+        borrower_meets_initial_margin_requirement,
+        ErrorCode::InsufficientCollateral,
+        "borrower does not meet initial margin requirement after taking term loan"
+    )?;
+
+    // the principal moves out of the lender's floating deposit into a term deposit that isn't collateral
+    let lender_meets_initial_margin_requirement = meets_initial_margin_requirement(
+        lender,
+        &perp_market_map,
+        &spot_market_map,
+        &mut oracle_map,
+    )?;
+
+    validate!(
+        lender_meets_initial_margin_requirement,
+        ErrorCode::InsufficientCollateral,
+        "lender does not meet initial margin requirement after term loan is taken"
+    )?;
+
+    lender.update_last_active_slot(clock.slot);
+    borrower.update_last_active_slot(clock.slot);
+
+    emit!(TermLoanRecord {
+        ts: now,
+        action: TermLoanAction::Take,
+        term_loan: ctx.accounts.term_loan.key(),
+        lender: lender_key,
+        borrower: borrower_key,
+        market_index,
+        principal: term_loan.principal,
+        repay_amount: term_loan.repay_amount,
+        rate: term_loan.rate,
+        maturity_ts: term_loan.maturity_ts,
+        shortfall: 0,
+    });
+
+    Ok(())
+}
+
 #[access_control(
     exchange_not_paused(&ctx.accounts.state)
 )]
@@ -1196,6 +1485,25 @@ pub fn handle_place_and_take_perp_order<'c: 'info, 'info>(
     let (makers_and_referrer, makers_and_referrer_stats) =
         load_user_maps(remaining_accounts_iter, true)?;
 
+    let referral_tier_config = get_referral_tier_config(remaining_accounts_iter, state)?;
+    let referral_tier_config = match &referral_tier_config {
+        Some(referral_tier_config) => Some(load!(referral_tier_config)?),
+        None => None,
+    };
+
+    let fee_tier_qualifications =
+        get_fee_tier_qualifications(remaining_accounts_iter, state, MarketType::Perp)?;
+
+    let market_fee_structure = get_market_fee_structure(remaining_accounts_iter)?;
+
+    let maker_rewards_program = get_maker_rewards_program(remaining_accounts_iter)?;
+    let mut maker_rewards_program = match &maker_rewards_program {
+        Some(maker_rewards_program) => Some(load_mut!(maker_rewards_program)?),
+        None => None,
+    };
+
+    let depth_bounds = get_perp_market_depth_bounds(remaining_accounts_iter)?;
+
     let is_immediate_or_cancel = params.immediate_or_cancel;
 
     controller::repeg::update_amm(
@@ -1239,6 +1547,11 @@ pub fn handle_place_and_take_perp_order<'c: 'info, 'info>(
         &makers_and_referrer,
         &makers_and_referrer_stats,
         None,
+        referral_tier_config.as_deref(),
+        fee_tier_qualifications.as_deref(),
+        market_fee_structure.as_deref(),
+        maker_rewards_program.as_deref_mut(),
+        depth_bounds.as_deref(),
         &Clock::get()?,
         FillMode::PlaceAndTake,
     )?;
@@ -1326,6 +1639,25 @@ pub fn handle_place_and_make_perp_order<'c: 'info, 'info>(
     makers_and_referrer.insert(ctx.accounts.user.key(), ctx.accounts.user.clone())?;
     makers_and_referrer_stats.insert(authority, ctx.accounts.user_stats.clone())?;
 
+    let referral_tier_config = get_referral_tier_config(remaining_accounts_iter, state)?;
+    let referral_tier_config = match &referral_tier_config {
+        Some(referral_tier_config) => Some(load!(referral_tier_config)?),
+        None => None,
+    };
+
+    let fee_tier_qualifications =
+        get_fee_tier_qualifications(remaining_accounts_iter, state, MarketType::Perp)?;
+
+    let market_fee_structure = get_market_fee_structure(remaining_accounts_iter)?;
+
+    let maker_rewards_program = get_maker_rewards_program(remaining_accounts_iter)?;
+    let mut maker_rewards_program = match &maker_rewards_program {
+        Some(maker_rewards_program) => Some(load_mut!(maker_rewards_program)?),
+        None => None,
+    };
+
+    let depth_bounds = get_perp_market_depth_bounds(remaining_accounts_iter)?;
+
     controller::orders::fill_perp_order(
         taker_order_id,
         state,
@@ -1339,6 +1671,11 @@ pub fn handle_place_and_make_perp_order<'c: 'info, 'info>(
         &makers_and_referrer,
         &makers_and_referrer_stats,
         Some(order_id),
+        referral_tier_config.as_deref(),
+        fee_tier_qualifications.as_deref(),
+        market_fee_structure.as_deref(),
+        maker_rewards_program.as_deref_mut(),
+        depth_bounds.as_deref(),
         clock,
         FillMode::PlaceAndMake,
     )?;
@@ -1436,6 +1773,14 @@ pub fn handle_place_and_take_spot_order<'c: 'info, 'info>(
         _ => (UserMap::empty(), UserStatsMap::empty()),
     };
 
+    let fee_tier_qualifications = get_fee_tier_qualifications(
+        remaining_accounts_iter,
+        &ctx.accounts.state,
+        MarketType::Spot,
+    )?;
+
+    let market_fee_structure = get_market_fee_structure(remaining_accounts_iter)?;
+
     let is_immediate_or_cancel = params.immediate_or_cancel;
 
     let mut fulfillment_params: Box<dyn SpotFulfillmentParams> = match fulfillment_type {
@@ -1515,6 +1860,8 @@ pub fn handle_place_and_take_spot_order<'c: 'info, 'info>(
         &makers_and_referrer,
         &makers_and_referrer_stats,
         None,
+        fee_tier_qualifications.as_deref(),
+        market_fee_structure.as_deref(),
         &clock,
         fulfillment_params.as_mut(),
     )?;
@@ -1569,6 +1916,14 @@ pub fn handle_place_and_make_spot_order<'c: 'info, 'info>(
 
     let (_referrer, _referrer_stats) = get_referrer_and_referrer_stats(remaining_accounts_iter)?;
 
+    let fee_tier_qualifications = get_fee_tier_qualifications(
+        remaining_accounts_iter,
+        &ctx.accounts.state,
+        MarketType::Spot,
+    )?;
+
+    let market_fee_structure = get_market_fee_structure(remaining_accounts_iter)?;
+
     if !params.immediate_or_cancel
         || params.post_only == PostOnlyParam::None
         || params.order_type != OrderType::Limit
@@ -1661,6 +2016,8 @@ pub fn handle_place_and_make_spot_order<'c: 'info, 'info>(
         &makers_and_referrer,
         &makers_and_referrer_stats,
         Some(order_id),
+        fee_tier_qualifications.as_deref(),
+        market_fee_structure.as_deref(),
         clock,
         fulfillment_params.as_mut(),
     )?;
@@ -1744,6 +2101,12 @@ pub fn handle_add_perp_lp_shares<'c: 'info, 'info>(
             "Market amm fills paused"
         )?;
 
+        validate!(
+            !user.has_lp_withdraw_request(market_index),
+            ErrorCode::PendingLpWithdrawRequest,
+            "cant add lp shares while lp withdraw request is pending"
+        )?;
+
         validate!(
             n_shares >= market.amm.order_step_size,
             ErrorCode::NewLPSizeTooSmall,
@@ -1821,7 +2184,7 @@ pub fn handle_remove_perp_lp_shares_in_expiring_market<'c: 'info, 'info>(
     {
         let market = perp_market_map.get_ref(&market_index)?;
         validate!(
-            market.is_reduce_only()?,
+            market.status == MarketStatus::ReduceOnly,
             ErrorCode::PerpMarketNotInReduceOnly,
             "Can only permissionless burn when market is in reduce only"
         )?;
@@ -1870,16 +2233,32 @@ pub fn handle_remove_perp_lp_shares<'c: 'info, 'info>(
         Some(state.oracle_guard_rails),
     )?;
 
-    controller::lp::remove_perp_lp_shares(
-        perp_market_map,
-        &mut oracle_map,
-        state,
-        user,
-        user_key,
-        shares_to_burn,
-        market_index,
-        now,
-    )?;
+    // withdraws before the cooldown are queued unless the amm can absorb them
+    let can_remove = {
+        let market = perp_market_map.get_ref(&market_index)?;
+        controller::lp::can_remove_perp_lp_shares(state, user, &market, shares_to_burn, now)?
+    };
+
+    if can_remove {
+        controller::lp::remove_perp_lp_shares(
+            perp_market_map,
+            &mut oracle_map,
+            state,
+            user,
+            user_key,
+            shares_to_burn,
+            market_index,
+            now,
+        )?;
+    } else {
+        controller::lp::request_remove_perp_lp_shares(
+            user,
+            user_key,
+            shares_to_burn,
+            market_index,
+            now,
+        )?;
+    }
 
     user.update_last_active_slot(clock.slot);
 
@@ -2053,8 +2432,10 @@ pub fn handle_deposit_into_spot_market_revenue_pool<'c: 'info, 'info>(
         spot_market.market_index
     )?;
 
+    let transfer_fee = controller::token::calculate_transfer_fee(&mint, amount)?;
+
     controller::spot_balance::update_revenue_pool_balances(
-        amount.cast::<u128>()?,
+        amount.safe_sub(transfer_fee)?.cast::<u128>()?,
         &SpotBalanceType::Deposit,
         &mut spot_market,
     )?;
@@ -2066,6 +2447,7 @@ pub fn handle_deposit_into_spot_market_revenue_pool<'c: 'info, 'info>(
         &ctx.accounts.authority,
         amount,
         &mint,
+        Some(remaining_accounts_iter),
     )?;
 
     spot_market.validate_max_token_deposits_and_borrows(false)?;
@@ -2078,6 +2460,219 @@ pub fn handle_deposit_into_spot_market_revenue_pool<'c: 'info, 'info>(
     Ok(())
 }
 
+#[access_control(
+    withdraw_not_paused(&ctx.accounts.state)
+)]
+pub fn handle_claim_sub_affiliate_reward(ctx: Context<ClaimSubAffiliateReward>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let user_key = ctx.accounts.user.key();
+    let mut user = load_mut!(ctx.accounts.user)?;
+    let mut user_stats = load_mut!(ctx.accounts.user_stats)?;
+    let mut perp_market = load_mut!(ctx.accounts.perp_market)?;
+    let mut spot_market = load_mut!(ctx.accounts.quote_spot_market)?;
+
+    controller::spot_balance::update_spot_market_cumulative_interest(&mut spot_market, None, now)?;
+
+    // rewards are held in the perp market fee pools they accrue to, any remainder stays claimable
+    let fee_pool_token_amount =
+        controller::amm::get_fee_pool_tokens(&mut perp_market, &mut spot_market)?;
+    let reward = user_stats
+        .fees
+        .unclaimed_sub_affiliate_reward
+        .cast::<i128>()?
+        .min(fee_pool_token_amount);
+
+    validate!(
+        reward > 0,
+        ErrorCode::NoSubAffiliateRewardToClaim,
+        "unclaimed sub affiliate reward = {} perp market {} fee pool = {}",
+        user_stats.fees.unclaimed_sub_affiliate_reward,
+        perp_market.market_index,
+        fee_pool_token_amount
+    )?;
+
+    let position_index = user.force_get_spot_position_index(QUOTE_SPOT_MARKET_INDEX)?;
+    controller::spot_balance::transfer_spot_balances(
+        reward,
+        &mut spot_market,
+        &mut perp_market.amm.fee_pool,
+        &mut user.spot_positions[position_index],
+    )?;
+
+    perp_market.amm.total_fee_minus_distributions = perp_market
+        .amm
+        .total_fee_minus_distributions
+        .safe_sub(reward)?;
+
+    user_stats.fees.unclaimed_sub_affiliate_reward = user_stats
+        .fees
+        .unclaimed_sub_affiliate_reward
+        .safe_sub(reward.cast()?)?;
+
+    msg!(
+        "claimed sub affiliate reward {} from perp market {} to user {}",
+        reward,
+        perp_market.market_index,
+        user_key
+    );
+
+    Ok(())
+}
+
+pub fn handle_redeem_fuel_season<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, RedeemFuelSeason<'info>>,
+    season: u16,
+) -> Result<()> {
+    let state = &ctx.accounts.state;
+    let fuel_season = &mut load_mut!(ctx.accounts.fuel_season)?;
+    let user_fuel_season_record = &mut load_mut!(ctx.accounts.user_fuel_season_record)?;
+
+    let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+    let mint = get_token_mint(remaining_accounts_iter)?;
+
+    validate!(
+        fuel_season.redemption_rate > 0,
+        ErrorCode::InvalidFuelRedemption,
+        "fuel season {} redemption rate not set",
+        season
+    )?;
+
+    let fuel = user_fuel_season_record.get_unredeemed_fuel()?;
+    let amount = calculate_fuel_redemption_amount(fuel, fuel_season.redemption_rate)?;
+
+    validate!(
+        amount > 0,
+        ErrorCode::InvalidFuelRedemption,
+        "no reward for {} unredeemed fuel",
+        fuel
+    )?;
+
+    user_fuel_season_record.fuel_redeemed = user_fuel_season_record.fuel_redeemed.safe_add(fuel)?;
+    fuel_season.total_fuel_redeemed = fuel_season.total_fuel_redeemed.safe_add(fuel)?;
+    fuel_season.total_rewards_redeemed = fuel_season.total_rewards_redeemed.safe_add(amount)?;
+
+    msg!(
+        "redeemed {} fuel for {} from fuel season {}",
+        fuel,
+        amount,
+        season
+    );
+
+    controller::token::send_from_program_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.fuel_season_vault,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.drift_signer,
+        state.signer_nonce,
+        amount,
+        &mint,
+        Some(remaining_accounts_iter),
+    )?;
+
+    Ok(())
+}
+
+pub fn handle_join_maker_rewards_program(
+    ctx: Context<JoinMakerRewardsProgram>,
+    market_index: u16,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let maker_rewards_program = &mut load_mut!(ctx.accounts.maker_rewards_program)?;
+    let maker_volume_30d = load!(ctx.accounts.user_stats)?.maker_volume_30d;
+    let user_key = ctx.accounts.user.key();
+
+    maker_rewards_program.add_maker(user_key, maker_volume_30d, now, clock.slot)?;
+
+    msg!(
+        "user {} joined maker rewards program for perp market {}",
+        user_key,
+        market_index
+    );
+
+    Ok(())
+}
+
+pub fn handle_leave_maker_rewards_program(
+    ctx: Context<LeaveMakerRewardsProgram>,
+    market_index: u16,
+) -> Result<()> {
+    let maker_rewards_program = &mut load_mut!(ctx.accounts.maker_rewards_program)?;
+    let user_key = ctx.accounts.user.key();
+
+    let maker_index = maker_rewards_program
+        .get_maker_index(&user_key)
+        .ok_or_else(|| {
+            msg!("user {} not in maker rewards program", user_key);
+            ErrorCode::InvalidMakerRewardsProgram
+        })?;
+
+    validate!(
+        maker_rewards_program.calculate_claimable_rewards(maker_index)? == 0,
+        ErrorCode::InvalidMakerRewardsProgram,
+        "claim maker rewards for epoch {} before leaving",
+        maker_rewards_program.epoch.saturating_sub(1)
+    )?;
+
+    maker_rewards_program.remove_maker(maker_index)?;
+
+    msg!(
+        "user {} left maker rewards program for perp market {}",
+        user_key,
+        market_index
+    );
+
+    Ok(())
+}
+
+pub fn handle_claim_maker_rewards<'c: 'info, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, ClaimMakerRewards<'info>>,
+    market_index: u16,
+) -> Result<()> {
+    let state = &ctx.accounts.state;
+    let maker_rewards_program = &mut load_mut!(ctx.accounts.maker_rewards_program)?;
+    let user_key = ctx.accounts.user.key();
+
+    let remaining_accounts_iter = &mut ctx.remaining_accounts.iter().peekable();
+    let mint = get_token_mint(remaining_accounts_iter)?;
+
+    let maker_index = maker_rewards_program
+        .get_maker_index(&user_key)
+        .ok_or_else(|| {
+            msg!("user {} not in maker rewards program", user_key);
+            ErrorCode::InvalidMakerRewardsProgram
+        })?;
+
+    let amount = maker_rewards_program.claim_rewards(maker_index)?;
+
+    validate!(
+        amount > 0,
+        ErrorCode::InvalidMakerRewardsProgram,
+        "no maker rewards to claim for epoch {}",
+        maker_rewards_program.epoch.saturating_sub(1)
+    )?;
+
+    msg!(
+        "user {} claimed {} maker rewards for perp market {}",
+        user_key,
+        amount,
+        market_index
+    );
+
+    controller::token::send_from_program_vault(
+        &ctx.accounts.token_program,
+        &ctx.accounts.maker_rewards_vault,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.drift_signer,
+        state.signer_nonce,
+        amount,
+        &mint,
+        Some(remaining_accounts_iter),
+    )?;
+
+    Ok(())
+}
+
 #[derive(Accounts)]
 #[instruction(
     sub_account_id: u16,
@@ -2206,6 +2801,138 @@ pub struct RevenuePoolDeposit<'info> {
     pub token_program: Interface<'info, TokenInterface>,
 }
 
+#[derive(Accounts)]
+pub struct ClaimSubAffiliateReward<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        constraint = can_sign_for_user(&user, &authority)?
+    )]
+    pub user: AccountLoader<'info, User>,
+    #[account(
+        mut,
+        constraint = is_stats_for_user(&user, &user_stats)?
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub perp_market: AccountLoader<'info, PerpMarket>,
+    #[account(
+        mut,
+        seeds = [b"spot_market", 0_u16.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub quote_spot_market: AccountLoader<'info, SpotMarket>,
+}
+
+#[derive(Accounts)]
+#[instruction(season: u16)]
+pub struct RedeemFuelSeason<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [b"fuel_season".as_ref(), season.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub fuel_season: AccountLoader<'info, FuelSeason>,
+    #[account(
+        mut,
+        seeds = [b"user_fuel_season_record".as_ref(), authority.key.as_ref(), season.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub user_fuel_season_record: AccountLoader<'info, UserFuelSeasonRecord>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"fuel_season_vault".as_ref(), season.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub fuel_season_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        constraint = state.signer.eq(&drift_signer.key())
+    )]
+    /// CHECK: forced drift_signer
+    pub drift_signer: AccountInfo<'info>,
+    #[account(
+        mut,
+        token::mint = fuel_season_vault.mint,
+        token::authority = authority
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct JoinMakerRewardsProgram<'info> {
+    #[account(
+        has_one = authority,
+    )]
+    pub user: AccountLoader<'info, User>,
+    #[account(
+        constraint = is_stats_for_user(&user, &user_stats)?
+    )]
+    pub user_stats: AccountLoader<'info, UserStats>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"maker_rewards_program".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub maker_rewards_program: AccountLoader<'info, MakerRewardsProgram>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct LeaveMakerRewardsProgram<'info> {
+    #[account(
+        has_one = authority,
+    )]
+    pub user: AccountLoader<'info, User>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"maker_rewards_program".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub maker_rewards_program: AccountLoader<'info, MakerRewardsProgram>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_index: u16)]
+pub struct ClaimMakerRewards<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        has_one = authority,
+    )]
+    pub user: AccountLoader<'info, User>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"maker_rewards_program".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub maker_rewards_program: AccountLoader<'info, MakerRewardsProgram>,
+    #[account(
+        mut,
+        seeds = [b"maker_rewards_vault".as_ref(), market_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub maker_rewards_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        constraint = state.signer.eq(&drift_signer.key())
+    )]
+    /// CHECK: forced drift_signer
+    pub drift_signer: AccountInfo<'info>,
+    #[account(
+        mut,
+        token::mint = maker_rewards_vault.mint,
+        token::authority = authority
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
 #[derive(Accounts)]
 #[instruction(market_index: u16,)]
 pub struct Withdraw<'info> {
@@ -2267,6 +2994,71 @@ pub struct TransferDeposit<'info> {
     pub spot_market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 }
 
+#[derive(Accounts)]
+#[instruction(
+    offer_id: u32,
+    market_index: u16,
+)]
+pub struct InitializeTermLoanOffer<'info> {
+    #[account(
+        init,
+        seeds = [b"term_loan", user.key().as_ref(), offer_id.to_le_bytes().as_ref()],
+        space = TermLoan::SIZE,
+        bump,
+        payer = authority
+    )]
+    pub term_loan: AccountLoader<'info, TermLoan>,
+    #[account(
+        has_one = authority,
+    )]
+    pub user: AccountLoader<'info, User>,
+    #[account(
+        seeds = [b"spot_market", market_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spot_market: AccountLoader<'info, SpotMarket>,
+    pub state: Box<Account<'info, State>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTermLoanOffer<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        constraint = is_lender_for_term_loan(&term_loan, &user)?,
+        close = authority
+    )]
+    pub term_loan: AccountLoader<'info, TermLoan>,
+    #[account(
+        has_one = authority,
+    )]
+    pub user: AccountLoader<'info, User>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TakeTermLoanOffer<'info> {
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        constraint = is_lender_for_term_loan(&term_loan, &lender)?
+    )]
+    pub term_loan: AccountLoader<'info, TermLoan>,
+    #[account(mut)]
+    pub lender: AccountLoader<'info, User>,
+    #[account(
+        mut,
+        constraint = can_sign_for_user(&user, &authority)?
+    )]
+    pub user: AccountLoader<'info, User>,
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct PlaceOrder<'info> {
     pub state: Box<Account<'info, State>>,
@@ -2564,6 +3356,7 @@ pub fn handle_begin_swap<'c: 'info, 'info>(
         state.signer_nonce,
         amount_in,
         &mint,
+        None,
     )?;
 
     let ixs = ctx.accounts.instructions.as_ref();
@@ -2792,6 +3585,7 @@ pub fn handle_end_swap<'c: 'info, 'info>(
             &ctx.accounts.authority,
             residual,
             &in_mint,
+            None,
         )?;
         in_token_account.reload()?;
         in_vault.reload()?;
@@ -2872,6 +3666,7 @@ pub fn handle_end_swap<'c: 'info, 'info>(
                 &ctx.accounts.authority,
                 amount_out,
                 &out_mint,
+                None,
             )?;
         } else {
             controller::token::receive(
@@ -2881,6 +3676,7 @@ pub fn handle_end_swap<'c: 'info, 'info>(
                 &ctx.accounts.authority,
                 amount_out,
                 &out_mint,
+                None,
             )?;
         }
 
@@ -10,13 +10,15 @@ use math::amm;
 use math::{bn, constants::*};
 use state::oracle::OracleSource;
 
+use crate::controller::funding::PredictedFundingRate;
+use crate::controller::insurance::InsuranceFundStakePerformance;
 use crate::controller::position::PositionDirection;
-use crate::state::oracle::PrelaunchOracleParams;
+use crate::state::oracle::{OracleAggregatorParams, PrelaunchOracleParams};
 use crate::state::order_params::{ModifyOrderParams, OrderParams};
 use crate::state::perp_market::{ContractTier, MarketStatus};
 use crate::state::settle_pnl_mode::SettlePnlMode;
-use crate::state::spot_market::AssetTier;
 use crate::state::spot_market::SpotFulfillmentConfigStatus;
+use crate::state::spot_market::{AssetTier, BorrowRateKink};
 use crate::state::state::FeeStructure;
 use crate::state::state::*;
 use crate::state::user::MarketType;
@@ -92,6 +94,27 @@ pub mod drift {
         handle_transfer_deposit(ctx, market_index, amount)
     }
 
+    pub fn initialize_term_loan_offer(
+        ctx: Context<InitializeTermLoanOffer>,
+        offer_id: u32,
+        market_index: u16,
+        principal: u64,
+        rate: u32,
+        maturity_ts: i64,
+    ) -> Result<()> {
+        handle_initialize_term_loan_offer(ctx, offer_id, market_index, principal, rate, maturity_ts)
+    }
+
+    pub fn cancel_term_loan_offer(ctx: Context<CancelTermLoanOffer>) -> Result<()> {
+        handle_cancel_term_loan_offer(ctx)
+    }
+
+    pub fn take_term_loan_offer<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, TakeTermLoanOffer<'info>>,
+    ) -> Result<()> {
+        handle_take_term_loan_offer(ctx)
+    }
+
     pub fn place_perp_order<'c: 'info, 'info>(
         ctx: Context<'_, '_, 'c, 'info, PlaceOrder>,
         params: OrderParams,
@@ -356,6 +379,40 @@ pub mod drift {
         handle_update_user_open_orders_count(ctx)
     }
 
+    pub fn initialize_user_fuel_season_record(
+        ctx: Context<InitializeUserFuelSeasonRecord>,
+        season: u16,
+    ) -> Result<()> {
+        handle_initialize_user_fuel_season_record(ctx, season)
+    }
+
+    pub fn snapshot_user_fuel_season<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, SnapshotUserFuelSeason<'info>>,
+        season: u16,
+    ) -> Result<()> {
+        handle_snapshot_user_fuel_season(ctx, season)
+    }
+
+    pub fn settle_maker_rewards_epoch(
+        ctx: Context<SettleMakerRewardsEpoch>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_settle_maker_rewards_epoch(ctx, market_index)
+    }
+
+    pub fn update_maker_rewards_depth<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, UpdateMakerRewardsDepth<'info>>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_update_maker_rewards_depth(ctx, market_index)
+    }
+
+    pub fn settle_term_loan<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, SettleTermLoan<'info>>,
+    ) -> Result<()> {
+        handle_settle_term_loan(ctx)
+    }
+
     pub fn admin_disable_update_perp_bid_ask_twap(
         ctx: Context<AdminDisableBidAskTwapUpdate>,
         disable: bool,
@@ -503,14 +560,52 @@ pub mod drift {
         handle_settle_revenue_to_insurance_fund(ctx, spot_market_index)
     }
 
-    pub fn update_funding_rate(ctx: Context<UpdateFundingRate>, market_index: u16) -> Result<()> {
+    pub fn settle_revenue_to_perp_insurance_fund<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, SettleRevenueToPerpInsuranceFund<'info>>,
+        perp_market_index: u16,
+    ) -> Result<()> {
+        handle_settle_revenue_to_perp_insurance_fund(ctx, perp_market_index)
+    }
+
+    pub fn fulfill_perp_lp_withdraw_request<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, FulfillPerpLpWithdrawRequest<'info>>,
+    ) -> Result<()> {
+        handle_fulfill_perp_lp_withdraw_request(ctx)
+    }
+
+    pub fn update_funding_rate<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, UpdateFundingRate<'info>>,
+        market_index: u16,
+    ) -> Result<()> {
         handle_update_funding_rate(ctx, market_index)
     }
 
+    pub fn view_predicted_funding_rate<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, ViewPredictedFundingRate<'info>>,
+        market_index: u16,
+    ) -> Result<PredictedFundingRate> {
+        handle_view_predicted_funding_rate(ctx, market_index)
+    }
+
     pub fn update_prelaunch_oracle(ctx: Context<UpdatePrelaunchOracle>) -> Result<()> {
         handle_update_prelaunch_oracle(ctx)
     }
 
+    pub fn post_signed_price(
+        ctx: Context<PostSignedPrice>,
+        price: i64,
+        confidence: u64,
+        exponent: i32,
+    ) -> Result<()> {
+        handle_post_signed_price(ctx, price, confidence, exponent)
+    }
+
+    pub fn update_oracle_aggregator<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, UpdateOracleAggregator<'info>>,
+    ) -> Result<()> {
+        handle_update_oracle_aggregator(ctx)
+    }
+
     pub fn update_perp_bid_ask_twap<'c: 'info, 'info>(
         ctx: Context<'_, '_, 'c, 'info, UpdatePerpBidAskTwap<'info>>,
     ) -> Result<()> {
@@ -523,6 +618,18 @@ pub mod drift {
         handle_update_spot_market_cumulative_interest(ctx)
     }
 
+    pub fn update_perp_market_oracle_circuit_breaker_window(
+        ctx: Context<UpdatePerpMarketOracleCircuitBreakerWindow>,
+    ) -> Result<()> {
+        handle_update_perp_market_oracle_circuit_breaker_window(ctx)
+    }
+
+    pub fn update_spot_market_oracle_circuit_breaker_window(
+        ctx: Context<UpdateSpotMarketOracleCircuitBreakerWindow>,
+    ) -> Result<()> {
+        handle_update_spot_market_oracle_circuit_breaker_window(ctx)
+    }
+
     pub fn update_amms<'c: 'info, 'info>(
         ctx: Context<'_, '_, 'c, 'info, UpdateAMM<'info>>,
         market_indexes: [u16; 5],
@@ -588,6 +695,103 @@ pub mod drift {
         handle_remove_insurance_fund_stake(ctx, market_index)
     }
 
+    pub fn view_insurance_fund_stake_performance(
+        ctx: Context<ViewInsuranceFundStakePerformance>,
+        market_index: u16,
+    ) -> Result<InsuranceFundStakePerformance> {
+        handle_view_insurance_fund_stake_performance(ctx, market_index)
+    }
+
+    pub fn tokenize_insurance_fund_stake(
+        ctx: Context<TokenizeInsuranceFundStake>,
+        market_index: u16,
+        shares: u128,
+    ) -> Result<()> {
+        handle_tokenize_insurance_fund_stake(ctx, market_index, shares)
+    }
+
+    pub fn redeem_insurance_fund_share_tokens(
+        ctx: Context<RedeemInsuranceFundShareTokens>,
+        market_index: u16,
+        amount: u64,
+    ) -> Result<()> {
+        handle_redeem_insurance_fund_share_tokens(ctx, market_index, amount)
+    }
+
+    pub fn initialize_senior_insurance_fund_stake(
+        ctx: Context<InitializeSeniorInsuranceFundStake>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_initialize_senior_insurance_fund_stake(ctx, market_index)
+    }
+
+    pub fn add_senior_insurance_fund_stake<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, AddSeniorInsuranceFundStake<'info>>,
+        market_index: u16,
+        amount: u64,
+    ) -> Result<()> {
+        handle_add_senior_insurance_fund_stake(ctx, market_index, amount)
+    }
+
+    pub fn request_remove_senior_insurance_fund_stake(
+        ctx: Context<RequestRemoveSeniorInsuranceFundStake>,
+        market_index: u16,
+        amount: u64,
+    ) -> Result<()> {
+        handle_request_remove_senior_insurance_fund_stake(ctx, market_index, amount)
+    }
+
+    pub fn cancel_request_remove_senior_insurance_fund_stake(
+        ctx: Context<RequestRemoveSeniorInsuranceFundStake>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_cancel_request_remove_senior_insurance_fund_stake(ctx, market_index)
+    }
+
+    pub fn remove_senior_insurance_fund_stake<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, RemoveSeniorInsuranceFundStake<'info>>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_remove_senior_insurance_fund_stake(ctx, market_index)
+    }
+
+    pub fn initialize_perp_insurance_fund_stake(
+        ctx: Context<InitializePerpInsuranceFundStake>,
+        perp_market_index: u16,
+    ) -> Result<()> {
+        handle_initialize_perp_insurance_fund_stake(ctx, perp_market_index)
+    }
+
+    pub fn add_perp_insurance_fund_stake<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, AddPerpInsuranceFundStake<'info>>,
+        perp_market_index: u16,
+        amount: u64,
+    ) -> Result<()> {
+        handle_add_perp_insurance_fund_stake(ctx, perp_market_index, amount)
+    }
+
+    pub fn request_remove_perp_insurance_fund_stake(
+        ctx: Context<RequestRemovePerpInsuranceFundStake>,
+        perp_market_index: u16,
+        amount: u64,
+    ) -> Result<()> {
+        handle_request_remove_perp_insurance_fund_stake(ctx, perp_market_index, amount)
+    }
+
+    pub fn cancel_request_remove_perp_insurance_fund_stake(
+        ctx: Context<RequestRemovePerpInsuranceFundStake>,
+        perp_market_index: u16,
+    ) -> Result<()> {
+        handle_cancel_request_remove_perp_insurance_fund_stake(ctx, perp_market_index)
+    }
+
+    pub fn remove_perp_insurance_fund_stake<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, RemovePerpInsuranceFundStake<'info>>,
+        perp_market_index: u16,
+    ) -> Result<()> {
+        handle_remove_perp_insurance_fund_stake(ctx, perp_market_index)
+    }
+
     pub fn transfer_protocol_if_shares(
         ctx: Context<TransferProtocolIfShares>,
         market_index: u16,
@@ -852,6 +1056,38 @@ pub mod drift {
         handle_deposit_into_spot_market_revenue_pool(ctx, amount)
     }
 
+    pub fn claim_sub_affiliate_reward(ctx: Context<ClaimSubAffiliateReward>) -> Result<()> {
+        handle_claim_sub_affiliate_reward(ctx)
+    }
+
+    pub fn redeem_fuel_season<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, RedeemFuelSeason<'info>>,
+        season: u16,
+    ) -> Result<()> {
+        handle_redeem_fuel_season(ctx, season)
+    }
+
+    pub fn join_maker_rewards_program(
+        ctx: Context<JoinMakerRewardsProgram>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_join_maker_rewards_program(ctx, market_index)
+    }
+
+    pub fn leave_maker_rewards_program(
+        ctx: Context<LeaveMakerRewardsProgram>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_leave_maker_rewards_program(ctx, market_index)
+    }
+
+    pub fn claim_maker_rewards<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, ClaimMakerRewards<'info>>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_claim_maker_rewards(ctx, market_index)
+    }
+
     pub fn repeg_amm_curve(ctx: Context<RepegCurve>, new_peg_candidate: u128) -> Result<()> {
         handle_repeg_amm_curve(ctx, new_peg_candidate)
     }
@@ -982,6 +1218,20 @@ pub mod drift {
         )
     }
 
+    pub fn update_spot_market_asset_group(
+        ctx: Context<AdminUpdateSpotMarket>,
+        asset_group: u8,
+        asset_group_margin_ratio_initial: u8,
+        asset_group_margin_ratio_maintenance: u8,
+    ) -> Result<()> {
+        handle_update_spot_market_asset_group(
+            ctx,
+            asset_group,
+            asset_group_margin_ratio_initial,
+            asset_group_margin_ratio_maintenance,
+        )
+    }
+
     pub fn update_spot_market_borrow_rate(
         ctx: Context<AdminUpdateSpotMarket>,
         optimal_utilization: u32,
@@ -998,6 +1248,13 @@ pub mod drift {
         )
     }
 
+    pub fn update_spot_market_borrow_rate_kinks(
+        ctx: Context<AdminUpdateSpotMarket>,
+        borrow_rate_kinks: [BorrowRateKink; 4],
+    ) -> Result<()> {
+        handle_update_spot_market_borrow_rate_kinks(ctx, borrow_rate_kinks)
+    }
+
     pub fn update_spot_market_max_token_deposits(
         ctx: Context<AdminUpdateSpotMarket>,
         max_token_deposits: u64,
@@ -1059,6 +1316,37 @@ pub mod drift {
         handle_update_spot_market_if_paused_operations(ctx, paused_operations)
     }
 
+    pub fn update_spot_market_margin_confidence_multiplier(
+        ctx: Context<AdminUpdateSpotMarket>,
+        margin_confidence_multiplier: u8,
+    ) -> Result<()> {
+        handle_update_spot_market_margin_confidence_multiplier(ctx, margin_confidence_multiplier)
+    }
+
+    pub fn update_spot_market_oracle_validity_guard_rails(
+        ctx: Context<AdminUpdateSpotMarket>,
+        slots_before_stale_for_amm: u16,
+        slots_before_stale_for_margin: u16,
+        confidence_interval_max_size: u32,
+        too_volatile_ratio: u16,
+    ) -> Result<()> {
+        handle_update_spot_market_oracle_validity_guard_rails(
+            ctx,
+            slots_before_stale_for_amm,
+            slots_before_stale_for_margin,
+            confidence_interval_max_size,
+            too_volatile_ratio,
+        )
+    }
+
+    pub fn update_spot_market_oracle_circuit_breaker(
+        ctx: Context<AdminUpdateSpotMarket>,
+        threshold: u16,
+        cooldown: u16,
+    ) -> Result<()> {
+        handle_update_spot_market_oracle_circuit_breaker(ctx, threshold, cooldown)
+    }
+
     pub fn update_spot_market_name(
         ctx: Context<AdminUpdateSpotMarket>,
         name: [u8; 32],
@@ -1159,6 +1447,112 @@ pub mod drift {
         handle_update_spot_fee_structure(ctx, fee_structure)
     }
 
+    pub fn initialize_perp_market_fee_structure(
+        ctx: Context<InitializePerpMarketFeeStructure>,
+        market_index: u16,
+        fee_structure: FeeStructure,
+    ) -> Result<()> {
+        handle_initialize_perp_market_fee_structure(ctx, market_index, fee_structure)
+    }
+
+    pub fn initialize_spot_market_fee_structure(
+        ctx: Context<InitializeSpotMarketFeeStructure>,
+        market_index: u16,
+        fee_structure: FeeStructure,
+    ) -> Result<()> {
+        handle_initialize_spot_market_fee_structure(ctx, market_index, fee_structure)
+    }
+
+    pub fn update_market_fee_structure(
+        ctx: Context<UpdateMarketFeeStructure>,
+        fee_structure: FeeStructure,
+    ) -> Result<()> {
+        handle_update_market_fee_structure(ctx, fee_structure)
+    }
+
+    pub fn delete_perp_market_fee_structure(
+        ctx: Context<DeletePerpMarketFeeStructure>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_delete_perp_market_fee_structure(ctx, market_index)
+    }
+
+    pub fn delete_spot_market_fee_structure(
+        ctx: Context<DeleteSpotMarketFeeStructure>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_delete_spot_market_fee_structure(ctx, market_index)
+    }
+
+    pub fn initialize_perp_fee_tier_qualifications(
+        ctx: Context<InitializePerpFeeTierQualifications>,
+        fee_tier_qualifications: [FeeTierQualification; 10],
+    ) -> Result<()> {
+        handle_initialize_perp_fee_tier_qualifications(ctx, fee_tier_qualifications)
+    }
+
+    pub fn initialize_spot_fee_tier_qualifications(
+        ctx: Context<InitializeSpotFeeTierQualifications>,
+        fee_tier_qualifications: [FeeTierQualification; 10],
+    ) -> Result<()> {
+        handle_initialize_spot_fee_tier_qualifications(ctx, fee_tier_qualifications)
+    }
+
+    pub fn update_fee_tier_qualifications(
+        ctx: Context<UpdateFeeTierQualifications>,
+        fee_tier_qualifications: [FeeTierQualification; 10],
+    ) -> Result<()> {
+        handle_update_fee_tier_qualifications(ctx, fee_tier_qualifications)
+    }
+
+    pub fn initialize_maker_rewards_program(
+        ctx: Context<InitializeMakerRewardsProgram>,
+        market_index: u16,
+        epoch_duration: i64,
+        epoch_rewards: u64,
+        max_oracle_distance: u32,
+        volume_reward_share: u32,
+        min_maker_volume_30d: u64,
+    ) -> Result<()> {
+        handle_initialize_maker_rewards_program(
+            ctx,
+            market_index,
+            epoch_duration,
+            epoch_rewards,
+            max_oracle_distance,
+            volume_reward_share,
+            min_maker_volume_30d,
+        )
+    }
+
+    pub fn update_maker_rewards_program(
+        ctx: Context<UpdateMakerRewardsProgram>,
+        market_index: u16,
+        epoch_duration: Option<i64>,
+        epoch_rewards: Option<u64>,
+        max_oracle_distance: Option<u32>,
+        volume_reward_share: Option<u32>,
+        min_maker_volume_30d: Option<u64>,
+    ) -> Result<()> {
+        handle_update_maker_rewards_program(
+            ctx,
+            market_index,
+            epoch_duration,
+            epoch_rewards,
+            max_oracle_distance,
+            volume_reward_share,
+            min_maker_volume_30d,
+        )
+    }
+
+    pub fn remove_maker_from_maker_rewards_program(
+        ctx: Context<UpdateMakerRewardsProgram>,
+        market_index: u16,
+        user: Pubkey,
+    ) -> Result<()> {
+        handle_remove_maker_from_maker_rewards_program(ctx, market_index, user)
+    }
+
     pub fn update_initial_pct_to_liquidate(
         ctx: Context<AdminUpdateState>,
         initial_pct_to_liquidate: u16,
@@ -1230,6 +1624,98 @@ pub mod drift {
         handle_update_amm_jit_intensity(ctx, amm_jit_intensity)
     }
 
+    pub fn update_perp_market_dynamic_amm_jit_min_scale(
+        ctx: Context<AdminUpdatePerpMarket>,
+        dynamic_amm_jit_min_scale: u8,
+    ) -> Result<()> {
+        handle_update_perp_market_dynamic_amm_jit_min_scale(ctx, dynamic_amm_jit_min_scale)
+    }
+
+    pub fn update_perp_market_lp_withdraw_cap(
+        ctx: Context<AdminUpdatePerpMarket>,
+        lp_withdraw_cap: u8,
+    ) -> Result<()> {
+        handle_update_perp_market_lp_withdraw_cap(ctx, lp_withdraw_cap)
+    }
+
+    pub fn update_perp_market_margin_confidence_multiplier(
+        ctx: Context<AdminUpdatePerpMarket>,
+        margin_confidence_multiplier: u8,
+    ) -> Result<()> {
+        handle_update_perp_market_margin_confidence_multiplier(ctx, margin_confidence_multiplier)
+    }
+
+    pub fn update_perp_market_oracle_validity_guard_rails(
+        ctx: Context<AdminUpdatePerpMarket>,
+        slots_before_stale_for_amm: u16,
+        slots_before_stale_for_margin: u16,
+        confidence_interval_max_size: u32,
+        too_volatile_ratio: u16,
+    ) -> Result<()> {
+        handle_update_perp_market_oracle_validity_guard_rails(
+            ctx,
+            slots_before_stale_for_amm,
+            slots_before_stale_for_margin,
+            confidence_interval_max_size,
+            too_volatile_ratio,
+        )
+    }
+
+    pub fn update_perp_market_oracle_circuit_breaker(
+        ctx: Context<AdminUpdatePerpMarket>,
+        threshold: u16,
+        cooldown: u16,
+    ) -> Result<()> {
+        handle_update_perp_market_oracle_circuit_breaker(ctx, threshold, cooldown)
+    }
+
+    pub fn initialize_perp_market_oracle_circuit_breaker_window(
+        ctx: Context<InitializePerpMarketOracleCircuitBreakerWindow>,
+        market_index: u16,
+        window_slots: u64,
+    ) -> Result<()> {
+        handle_initialize_perp_market_oracle_circuit_breaker_window(ctx, market_index, window_slots)
+    }
+
+    pub fn initialize_spot_market_oracle_circuit_breaker_window(
+        ctx: Context<InitializeSpotMarketOracleCircuitBreakerWindow>,
+        market_index: u16,
+        window_slots: u64,
+    ) -> Result<()> {
+        handle_initialize_spot_market_oracle_circuit_breaker_window(ctx, market_index, window_slots)
+    }
+
+    pub fn update_oracle_circuit_breaker_window(
+        ctx: Context<UpdateOracleCircuitBreakerWindow>,
+        window_slots: u64,
+    ) -> Result<()> {
+        handle_update_oracle_circuit_breaker_window(ctx, window_slots)
+    }
+
+    pub fn initialize_perp_market_depth_bounds(
+        ctx: Context<InitializePerpMarketDepthBounds>,
+        market_index: u16,
+        min_sqrt_k: u128,
+        max_sqrt_k: u128,
+    ) -> Result<()> {
+        handle_initialize_perp_market_depth_bounds(ctx, market_index, min_sqrt_k, max_sqrt_k)
+    }
+
+    pub fn update_perp_market_depth_bounds(
+        ctx: Context<UpdatePerpMarketDepthBounds>,
+        min_sqrt_k: u128,
+        max_sqrt_k: u128,
+    ) -> Result<()> {
+        handle_update_perp_market_depth_bounds(ctx, min_sqrt_k, max_sqrt_k)
+    }
+
+    pub fn delete_perp_market_depth_bounds(
+        ctx: Context<DeletePerpMarketDepthBounds>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_delete_perp_market_depth_bounds(ctx, market_index)
+    }
+
     pub fn update_perp_market_max_spread(
         ctx: Context<AdminUpdatePerpMarket>,
         max_spread: u32,
@@ -1295,6 +1781,16 @@ pub mod drift {
         handle_update_perp_market_fee_adjustment(ctx, fee_adjustment)
     }
 
+    pub fn update_perp_market_funding_interest_rate_adjustment(
+        ctx: Context<AdminUpdatePerpMarket>,
+        funding_interest_rate_adjustment: i8,
+    ) -> Result<()> {
+        handle_update_perp_market_funding_interest_rate_adjustment(
+            ctx,
+            funding_interest_rate_adjustment,
+        )
+    }
+
     pub fn update_spot_market_fee_adjustment(
         ctx: Context<AdminUpdateSpotMarket>,
         fee_adjustment: i16,
@@ -1307,8 +1803,17 @@ pub mod drift {
         fuel_boost_taker: Option<u8>,
         fuel_boost_maker: Option<u8>,
         fuel_boost_position: Option<u8>,
+        fuel_boost_lp: Option<u8>,
+        fuel_boost_keeper: Option<u8>,
     ) -> Result<()> {
-        handle_update_perp_market_fuel(ctx, fuel_boost_taker, fuel_boost_maker, fuel_boost_position)
+        handle_update_perp_market_fuel(
+            ctx,
+            fuel_boost_taker,
+            fuel_boost_maker,
+            fuel_boost_position,
+            fuel_boost_lp,
+            fuel_boost_keeper,
+        )
     }
 
     pub fn update_spot_market_fuel(
@@ -1347,6 +1852,17 @@ pub mod drift {
         )
     }
 
+    pub fn close_fuel_season(ctx: Context<CloseFuelSeason>, season: u16) -> Result<()> {
+        handle_close_fuel_season(ctx, season)
+    }
+
+    pub fn update_fuel_season_redemption_rate(
+        ctx: Context<UpdateFuelSeason>,
+        redemption_rate: u64,
+    ) -> Result<()> {
+        handle_update_fuel_season_redemption_rate(ctx, redemption_rate)
+    }
+
     pub fn update_admin(ctx: Context<AdminUpdateState>, admin: Pubkey) -> Result<()> {
         handle_update_admin(ctx, admin)
     }
@@ -1392,6 +1908,80 @@ pub mod drift {
         handle_initialize_protocol_if_shares_transfer_config(ctx)
     }
 
+    pub fn initialize_insurance_fund_share_mint(
+        ctx: Context<InitializeInsuranceFundShareMint>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_initialize_insurance_fund_share_mint(ctx, market_index)
+    }
+
+    pub fn initialize_senior_insurance_fund(
+        ctx: Context<InitializeSeniorInsuranceFund>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_initialize_senior_insurance_fund(ctx, market_index)
+    }
+
+    pub fn update_senior_insurance_fund(
+        ctx: Context<UpdateSeniorInsuranceFund>,
+        unstaking_period: i64,
+        revenue_share: u32,
+        user_if_factor: u32,
+        total_if_factor: u32,
+    ) -> Result<()> {
+        handle_update_senior_insurance_fund(
+            ctx,
+            unstaking_period,
+            revenue_share,
+            user_if_factor,
+            total_if_factor,
+        )
+    }
+
+    pub fn initialize_perp_insurance_fund(
+        ctx: Context<InitializePerpInsuranceFund>,
+        perp_market_index: u16,
+    ) -> Result<()> {
+        handle_initialize_perp_insurance_fund(ctx, perp_market_index)
+    }
+
+    pub fn update_perp_insurance_fund(
+        ctx: Context<UpdatePerpInsuranceFund>,
+        unstaking_period: i64,
+        revenue_settle_period: i64,
+        user_if_factor: u32,
+        total_if_factor: u32,
+    ) -> Result<()> {
+        handle_update_perp_insurance_fund(
+            ctx,
+            unstaking_period,
+            revenue_settle_period,
+            user_if_factor,
+            total_if_factor,
+        )
+    }
+
+    pub fn initialize_insurance_fund_performance(
+        ctx: Context<InitializeInsuranceFundPerformance>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_initialize_insurance_fund_performance(ctx, market_index)
+    }
+
+    pub fn initialize_senior_insurance_fund_performance(
+        ctx: Context<InitializeSeniorInsuranceFundPerformance>,
+        market_index: u16,
+    ) -> Result<()> {
+        handle_initialize_senior_insurance_fund_performance(ctx, market_index)
+    }
+
+    pub fn initialize_perp_insurance_fund_performance(
+        ctx: Context<InitializePerpInsuranceFundPerformance>,
+        perp_market_index: u16,
+    ) -> Result<()> {
+        handle_initialize_perp_insurance_fund_performance(ctx, perp_market_index)
+    }
+
     pub fn update_protocol_if_shares_transfer_config(
         ctx: Context<UpdateProtocolIfSharesTransferConfig>,
         whitelisted_signers: Option<[Pubkey; 4]>,
@@ -1404,6 +1994,20 @@ pub mod drift {
         )
     }
 
+    pub fn initialize_referral_tier_config(
+        ctx: Context<InitializeReferralTierConfig>,
+    ) -> Result<()> {
+        handle_initialize_referral_tier_config(ctx)
+    }
+
+    pub fn update_referral_tier_config(
+        ctx: Context<UpdateReferralTierConfig>,
+        tiers: Option<[ReferralTier; 4]>,
+        sub_affiliate_reward_numerator: Option<u32>,
+    ) -> Result<()> {
+        handle_update_referral_tier_config(ctx, tiers, sub_affiliate_reward_numerator)
+    }
+
     pub fn initialize_prelaunch_oracle(
         ctx: Context<InitializePrelaunchOracle>,
         params: PrelaunchOracleParams,
@@ -1425,6 +2029,28 @@ pub mod drift {
         handle_delete_prelaunch_oracle(ctx, perp_market_index)
     }
 
+    pub fn initialize_oracle_aggregator<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, InitializeOracleAggregator<'info>>,
+        params: OracleAggregatorParams,
+    ) -> Result<()> {
+        handle_initialize_oracle_aggregator(ctx, params)
+    }
+
+    pub fn update_oracle_aggregator_sources<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, UpdateOracleAggregatorSources<'info>>,
+        params: OracleAggregatorParams,
+    ) -> Result<()> {
+        handle_update_oracle_aggregator_sources(ctx, params)
+    }
+
+    pub fn initialize_signed_price_account(
+        ctx: Context<InitializeSignedPriceAccount>,
+        feed_id: u16,
+        authority: Pubkey,
+    ) -> Result<()> {
+        handle_initialize_signed_price_account(ctx, feed_id, authority)
+    }
+
     pub fn initialize_pyth_pull_oracle(
         ctx: Context<InitPythPullPriceFeed>,
         feed_id: [u8; 32],
@@ -657,6 +657,14 @@ pub fn calculate_quote_asset_amount_swapped(
     Ok(quote_asset_amount)
 }
 
+/// quote value of the amm's depth (sqrt_k) at the given price
+/// precision: QUOTE_PRECISION
+pub fn calculate_depth_quote_value(sqrt_k: u128, price: i64) -> DriftResult<u128> {
+    sqrt_k
+        .safe_mul(price.max(0).cast()?)?
+        .safe_div(PRICE_TIMES_AMM_TO_QUOTE_PRECISION_RATIO)
+}
+
 pub fn calculate_terminal_reserves(amm: &AMM) -> DriftResult<(u128, u128)> {
     let swap_direction = if amm.base_asset_amount_with_amm > 0 {
         SwapDirection::Add
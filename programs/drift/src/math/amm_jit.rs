@@ -1,7 +1,9 @@
 use crate::controller::position::PositionDirection;
 use crate::error::DriftResult;
 use crate::math::casting::Cast;
-use crate::math::constants::{AMM_RESERVE_PRECISION, PERCENTAGE_PRECISION_U64};
+use crate::math::constants::{
+    AMM_RESERVE_PRECISION, PERCENTAGE_PRECISION, PERCENTAGE_PRECISION_U64,
+};
 use crate::math::orders::standardize_base_asset_amount;
 use crate::math::safe_math::SafeMath;
 use crate::state::perp_market::{AMMLiquiditySplit, PerpMarket};
@@ -17,6 +19,7 @@ pub fn calculate_jit_base_asset_amount(
     valid_oracle_price: Option<i64>,
     taker_direction: PositionDirection,
     liquidity_split: AMMLiquiditySplit,
+    dynamic_scale: u64,
 ) -> DriftResult<u64> {
     // AMM can only take up to 50% of size the maker is offering
     let mut max_jit_amount = maker_base_asset_amount.safe_div(2)?;
@@ -80,19 +83,9 @@ pub fn calculate_jit_base_asset_amount(
     // base @ mim = ratio = 2.5 / 7.5 = 3 == imbalanced
     // ratio >= 3 == imbalanced
 
-    let (max_bids, max_asks) = crate::math::amm::calculate_market_open_bids_asks(&market.amm)?;
-    let (max_bids, max_asks) = (max_bids.unsigned_abs(), max_asks.unsigned_abs());
-
-    let numerator = max_bids.max(max_asks);
-    let denominator = max_bids.min(max_asks);
-    let ratio = numerator
-        .safe_mul(AMM_RESERVE_PRECISION)?
-        .safe_div(denominator)
-        .unwrap_or(u128::MAX);
+    let ratio = calculate_amm_imbalance_ratio(market)?;
 
-    let imbalanced_bound = 15_u128.safe_mul(AMM_RESERVE_PRECISION.safe_div(10)?)?;
-
-    let amm_is_imbalanced = ratio >= imbalanced_bound;
+    let amm_is_imbalanced = ratio >= AMM_IMBALANCED_RATIO_BOUND;
 
     // take more when amm is imbalanced
     let mut jit_base_asset_amount = if amm_is_imbalanced {
@@ -101,6 +94,15 @@ pub fn calculate_jit_base_asset_amount(
         maker_base_asset_amount.safe_div(4)?
     };
 
+    // scale amm_jit_intensity (see calculate_dynamic_amm_jit_scale)
+    if dynamic_scale != PERCENTAGE_PRECISION_U64 {
+        jit_base_asset_amount = jit_base_asset_amount
+            .cast::<u128>()?
+            .safe_mul(dynamic_scale.cast()?)?
+            .safe_div(PERCENTAGE_PRECISION_U64.cast()?)?
+            .cast()?;
+    }
+
     if jit_base_asset_amount == 0 {
         return Ok(0);
     }
@@ -117,6 +119,82 @@ pub fn calculate_jit_base_asset_amount(
     Ok(jit_base_asset_amount)
 }
 
+const AMM_IMBALANCED_RATIO_BOUND: u128 = 15 * AMM_RESERVE_PRECISION / 10;
+
+// ratio of the larger to smaller side of the amm's open bids/asks
+// precision: AMM_RESERVE_PRECISION
+pub fn calculate_amm_imbalance_ratio(market: &PerpMarket) -> DriftResult<u128> {
+    let (max_bids, max_asks) = crate::math::amm::calculate_market_open_bids_asks(&market.amm)?;
+    let (max_bids, max_asks) = (max_bids.unsigned_abs(), max_asks.unsigned_abs());
+
+    let numerator = max_bids.max(max_asks);
+    let denominator = max_bids.min(max_asks);
+    Ok(numerator
+        .safe_mul(AMM_RESERVE_PRECISION)?
+        .safe_div(denominator)
+        .unwrap_or(u128::MAX))
+}
+
+// inventory (as a fraction of sqrt_k) at which the inventory component of the dynamic scale is maxed
+// precision: PERCENTAGE_PRECISION
+const DYNAMIC_AMM_JIT_MAX_INVENTORY: u128 = PERCENTAGE_PRECISION / 20; // 5%
+
+// dynamic scale when the amm holds max inventory and maker liquidity covers the taker order
+// precision: PERCENTAGE_PRECISION
+const DYNAMIC_AMM_JIT_MAX_SCALE: u64 = 2 * PERCENTAGE_PRECISION_U64; // 200%
+
+// scales amm_jit_intensity between dynamic_amm_jit_min_scale and DYNAMIC_AMM_JIT_MAX_SCALE
+// jit flow reduces inventory, so take more the larger the inventory the jit is reducing
+// (the amm's for protocol owned/shared jit, the lps' for lp owned jit) and the more maker
+// liquidity crossed the taker order
+// precision: PERCENTAGE_PRECISION
+pub fn calculate_dynamic_amm_jit_scale(
+    market: &PerpMarket,
+    liquidity_split: AMMLiquiditySplit,
+    maker_base_asset_amount: u64,
+    taker_base_asset_amount: u64,
+) -> DriftResult<u64> {
+    let min_scale = market
+        .dynamic_amm_jit_min_scale
+        .min(100)
+        .cast::<u64>()?
+        .safe_mul(PERCENTAGE_PRECISION_U64 / 100)?;
+
+    let inventory = if liquidity_split != AMMLiquiditySplit::LPOwned {
+        market.amm.base_asset_amount_with_amm
+    } else {
+        market.amm.imbalanced_base_asset_amount_with_lp()?
+    }
+    .unsigned_abs();
+
+    let max_inventory = market
+        .amm
+        .sqrt_k
+        .safe_mul(DYNAMIC_AMM_JIT_MAX_INVENTORY)?
+        .safe_div(PERCENTAGE_PRECISION)?
+        .max(1);
+
+    let inventory_scale = inventory
+        .safe_mul(PERCENTAGE_PRECISION)?
+        .safe_div(max_inventory)?
+        .min(PERCENTAGE_PRECISION);
+
+    let depth_scale = maker_base_asset_amount
+        .cast::<u128>()?
+        .safe_mul(PERCENTAGE_PRECISION)?
+        .safe_div(taker_base_asset_amount.max(1).cast()?)?
+        .min(PERCENTAGE_PRECISION);
+
+    DYNAMIC_AMM_JIT_MAX_SCALE
+        .safe_sub(min_scale)?
+        .cast::<u128>()?
+        .safe_mul(inventory_scale)?
+        .safe_mul(depth_scale)?
+        .safe_div(PERCENTAGE_PRECISION.safe_mul(PERCENTAGE_PRECISION)?)?
+        .cast::<u64>()?
+        .safe_add(min_scale)
+}
+
 // assumption: taker_baa will improve market balance (see orders.rs & amm_wants_to_jit_make)
 // note: we split it into two (calc and clamp) bc its easier to maintain tests
 pub fn calculate_clamped_jit_base_asset_amount(
@@ -189,6 +267,13 @@ pub fn calculate_amm_jit_liquidity(
             AMMLiquiditySplit::ProtocolOwned
         };
 
+        let dynamic_scale = get_dynamic_amm_jit_scale(
+            market,
+            liquidity_split,
+            maker_base_asset_amount,
+            taker_base_asset_amount,
+        )?;
+
         jit_base_asset_amount = calculate_jit_base_asset_amount(
             market,
             base_asset_amount,
@@ -196,10 +281,18 @@ pub fn calculate_amm_jit_liquidity(
             valid_oracle_price,
             taker_direction,
             liquidity_split,
+            dynamic_scale,
         )?;
     } else if split_with_lps {
         liquidity_split = AMMLiquiditySplit::LPOwned;
 
+        let dynamic_scale = get_dynamic_amm_jit_scale(
+            market,
+            liquidity_split,
+            maker_base_asset_amount,
+            taker_base_asset_amount,
+        )?;
+
         jit_base_asset_amount = calculate_jit_base_asset_amount(
             market,
             base_asset_amount,
@@ -207,8 +300,27 @@ pub fn calculate_amm_jit_liquidity(
             valid_oracle_price,
             taker_direction,
             liquidity_split,
+            dynamic_scale,
         )?;
     }
 
     Ok((jit_base_asset_amount, liquidity_split))
 }
+
+fn get_dynamic_amm_jit_scale(
+    market: &PerpMarket,
+    liquidity_split: AMMLiquiditySplit,
+    maker_base_asset_amount: u64,
+    taker_base_asset_amount: u64,
+) -> DriftResult<u64> {
+    if market.dynamic_amm_jit_min_scale == 0 {
+        return Ok(PERCENTAGE_PRECISION_U64);
+    }
+
+    calculate_dynamic_amm_jit_scale(
+        market,
+        liquidity_split,
+        maker_base_asset_amount,
+        taker_base_asset_amount,
+    )
+}
@@ -84,3 +84,124 @@ fn balanced_market_half_intensity() {
     .unwrap();
     assert_eq!(jit_amount, 50);
 }
+
+#[test]
+fn dynamic_amm_jit_scale() {
+    use crate::math::constants::{AMM_RESERVE_PRECISION, BASE_PRECISION_U64};
+
+    let mut market = PerpMarket {
+        amm: AMM {
+            // sqrt_k = 64, so inventory of 3.2 maxes the inventory scale
+            base_asset_amount_with_amm: 0,
+            ..AMM::default_btc_test()
+        },
+        dynamic_amm_jit_min_scale: 50,
+        ..PerpMarket::default()
+    };
+
+    // no inventory takes min scale
+    let scale = calculate_dynamic_amm_jit_scale(
+        &market,
+        AMMLiquiditySplit::ProtocolOwned,
+        BASE_PRECISION_U64,
+        BASE_PRECISION_U64,
+    )
+    .unwrap();
+    assert_eq!(scale, 500_000);
+
+    // half of max inventory
+    market.amm.base_asset_amount_with_amm = -16 * AMM_RESERVE_PRECISION as i128 / 10;
+    let scale = calculate_dynamic_amm_jit_scale(
+        &market,
+        AMMLiquiditySplit::ProtocolOwned,
+        BASE_PRECISION_U64,
+        BASE_PRECISION_U64,
+    )
+    .unwrap();
+    assert_eq!(scale, 1_250_000);
+
+    // maker liquidity only covers half the taker order
+    let scale = calculate_dynamic_amm_jit_scale(
+        &market,
+        AMMLiquiditySplit::ProtocolOwned,
+        BASE_PRECISION_U64 / 2,
+        BASE_PRECISION_U64,
+    )
+    .unwrap();
+    assert_eq!(scale, 875_000);
+
+    // lp owned jit scales off the lps' inventory, which is zero
+    let scale = calculate_dynamic_amm_jit_scale(
+        &market,
+        AMMLiquiditySplit::LPOwned,
+        BASE_PRECISION_U64,
+        BASE_PRECISION_U64,
+    )
+    .unwrap();
+    assert_eq!(scale, 500_000);
+
+    // max inventory takes double intensity
+    market.amm.base_asset_amount_with_amm = 5 * AMM_RESERVE_PRECISION as i128;
+    let scale = calculate_dynamic_amm_jit_scale(
+        &market,
+        AMMLiquiditySplit::Shared,
+        2 * BASE_PRECISION_U64,
+        BASE_PRECISION_U64,
+    )
+    .unwrap();
+    assert_eq!(scale, 2_000_000);
+
+    // min scale of 100 still scales up
+    market.dynamic_amm_jit_min_scale = 100;
+    market.amm.base_asset_amount_with_amm = -16 * AMM_RESERVE_PRECISION as i128 / 10;
+    let scale = calculate_dynamic_amm_jit_scale(
+        &market,
+        AMMLiquiditySplit::ProtocolOwned,
+        BASE_PRECISION_U64,
+        BASE_PRECISION_U64,
+    )
+    .unwrap();
+    assert_eq!(scale, 1_500_000);
+}
+
+#[test]
+fn dynamic_amm_jit_scale_increases_jit_amount() {
+    use crate::controller::position::PositionDirection;
+    use crate::math::constants::{BASE_PRECISION_U64, PERCENTAGE_PRECISION_U64};
+
+    let market = PerpMarket {
+        amm: AMM {
+            amm_jit_intensity: 100,
+            order_step_size: 1,
+            ..AMM::default_btc_test()
+        },
+        ..PerpMarket::default()
+    };
+    let oracle_price = market.amm.historical_oracle_data.last_oracle_price;
+    let auction_price = oracle_price.unsigned_abs();
+
+    let static_jit = calculate_jit_base_asset_amount(
+        &market,
+        BASE_PRECISION_U64,
+        auction_price,
+        Some(oracle_price),
+        PositionDirection::Long,
+        AMMLiquiditySplit::ProtocolOwned,
+        PERCENTAGE_PRECISION_U64,
+    )
+    .unwrap();
+
+    let scaled_jit = calculate_jit_base_asset_amount(
+        &market,
+        BASE_PRECISION_U64,
+        auction_price,
+        Some(oracle_price),
+        PositionDirection::Long,
+        AMMLiquiditySplit::ProtocolOwned,
+        2 * PERCENTAGE_PRECISION_U64,
+    )
+    .unwrap();
+
+    assert!(static_jit > 0);
+    assert_eq!(scaled_jit, 2 * static_jit);
+}
@@ -63,6 +63,7 @@ pub const BID_ASK_SPREAD_PRECISION_I128: i128 = BID_ASK_SPREAD_PRECISION as i128
 
 pub const CONCENTRATION_PRECISION: u128 = PERCENTAGE_PRECISION; // expo 6
 pub const IF_FACTOR_PRECISION: u128 = PERCENTAGE_PRECISION; // expo 6
+pub const IF_VALUE_PER_SHARE_PRECISION: u128 = 1_000_000_000_000; // expo -12
 
 pub const SPOT_UTILIZATION_PRECISION: u128 = PERCENTAGE_PRECISION; // expo = -6
 pub const SPOT_UTILIZATION_PRECISION_U32: u32 = PERCENTAGE_PRECISION as u32; // expo = -6
@@ -166,6 +167,7 @@ pub const MAX_K_BPS_INCREASE: i128 = TEN_BPS;
 pub const MAX_K_BPS_DECREASE: i128 = TWO_PT_TWO_PCT;
 pub const MAX_UPDATE_K_PRICE_CHANGE: u128 = HUNDRENTH_OF_CENT;
 pub const MAX_SQRT_K: u128 = 1000000000000000000000; // 1e21 (count 'em!)
+pub const DEPTH_CONTROLLER_FEE_SURPLUS_BUDGET_DENOMINATOR: i128 = 100; // 1% of fee surplus/deficit per funding period
 pub const MAX_BASE_ASSET_AMOUNT_WITH_AMM: u128 = 100000000000000000; // 1e17 (count 'em!)
 
 pub const MAX_PEG_BPS_INCREASE: u128 = TEN_BPS as u128; // 10 bps increase
@@ -182,6 +184,10 @@ pub const MAX_MARK_TWAP_DIVERGENCE: u128 = 500_000; // expo = -3
 
 pub const MAX_MARGIN_RATIO: u32 = MARGIN_PRECISION; // 1x leverage
 pub const MIN_MARGIN_RATIO: u32 = MARGIN_PRECISION / 50; // 50x leverage
+pub const FILL_TWAP_ORACLE_MIN_MARGIN_RATIO_INITIAL: u32 = MARGIN_PRECISION / 5; // 5x leverage
+pub const MAX_MARGIN_CONFIDENCE_MULTIPLIER: u8 = 50; // 5x oracle confidence, precision: 10
+pub const MIN_ORACLE_AGGREGATION_CONFIDENCE_PCT: u64 = BID_ASK_SPREAD_PRECISION / 1000; // 10 bps of price
+pub const CHAINLINK_MIN_CONFIDENCE_PCT: u64 = BID_ASK_SPREAD_PRECISION / 1000; // 10 bps of price
 
 pub const MAX_BID_ASK_INVENTORY_SKEW_FACTOR: u64 = 10 * BID_ASK_SPREAD_PRECISION;
 
@@ -198,6 +204,8 @@ pub const DEFAULT_QUOTE_ASSET_AMOUNT_TICK_SIZE: u64 =
 
 // FUNDING
 pub const FUNDING_RATE_OFFSET_DENOMINATOR: i64 = 5000; // 5000 => 7.3% annualized rate for hourly funding
+pub const FUNDING_INTEREST_RATE_ADJUSTMENT_MAX: i64 = 100;
+pub const PREMIUM_INDEX_MAX_SAMPLE_WEIGHT_DENOMINATOR: i64 = 60; // a sample counts for at most 1 min of an hourly funding period
 
 // ORDERS
 pub const AUCTION_DERIVE_PRICE_FRACTION: i64 = 200;
@@ -208,6 +216,8 @@ pub const SPOT_MARKET_TOKEN_TWAP_WINDOW: i64 = TWENTY_FOUR_HOUR;
 // FUEL
 pub const FUEL_WINDOW_U128: u128 = EPOCH_DURATION as u128;
 pub const FUEL_START_TS: i64 = 1723147200_i64; // August 8 2024 UTC
+pub const FUEL_REDEMPTION_RATE_PRECISION: u64 = 1_000_000; // expo = -6
+pub const FUEL_REDEMPTION_RATE_PRECISION_U128: u128 = FUEL_REDEMPTION_RATE_PRECISION as u128;
 
 // PREDICTION
 pub const MAX_PREDICTION_MARKET_PRICE: u64 = PRICE_PRECISION_U64;
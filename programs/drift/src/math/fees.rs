@@ -2,21 +2,19 @@ use std::cmp::{max, min};
 
 use num_integer::Roots;
 
-use crate::error::DriftResult;
+use crate::error::{DriftResult, ErrorCode};
 use crate::math::casting::Cast;
 
-use crate::math::constants::{
-    FIFTY_MILLION_QUOTE, FIVE_MILLION_QUOTE, ONE_HUNDRED_MILLION_QUOTE, ONE_HUNDRED_THOUSAND_QUOTE,
-    ONE_MILLION_QUOTE, ONE_THOUSAND_QUOTE, TEN_BPS, TEN_MILLION_QUOTE, TEN_THOUSAND_QUOTE,
-    TWENTY_FIVE_THOUSAND_QUOTE,
-};
+use crate::math::constants::TEN_BPS;
 use crate::math::helpers::get_proportion_u128;
 use crate::math::safe_math::SafeMath;
 
-use crate::state::state::{FeeStructure, FeeTier, OrderFillerRewardStructure};
+use crate::state::state::{
+    FeeStructure, FeeTier, FeeTierQualifications, MarketFeeStructure, OrderFillerRewardStructure,
+};
 use crate::state::user::{MarketType, UserStats};
 
-use crate::{FEE_ADJUSTMENT_MAX, QUOTE_PRECISION_U64};
+use crate::FEE_ADJUSTMENT_MAX;
 use solana_program::msg;
 
 #[cfg(test)]
@@ -30,12 +28,58 @@ pub struct FillFees {
     pub filler_reward: u64,
     pub referrer_reward: u64,
     pub referee_discount: u64,
+    pub taker_fee_tier_index: Option<u8>,
+    pub maker_fee_tier_index: Option<u8>,
+}
+
+/// A market's fee structure override takes precedence over the global fee structure. Markets with
+/// an override can only be filled if the override is passed
+pub fn select_fee_structure<'a>(
+    fee_structure: &'a FeeStructure,
+    market_fee_structure: Option<&'a MarketFeeStructure>,
+    market_type: MarketType,
+    market_index: u16,
+    has_fee_structure_override: bool,
+) -> DriftResult<&'a FeeStructure> {
+    if !has_fee_structure_override {
+        return Ok(fee_structure);
+    }
+
+    match market_fee_structure {
+        Some(market_fee_structure)
+            if market_fee_structure.market_type == market_type
+                && market_fee_structure.market_index == market_index =>
+        {
+            Ok(&market_fee_structure.fee_structure)
+        }
+        _ => {
+            msg!(
+                "fee structure override for {:?} market {} not found",
+                market_type,
+                market_index
+            );
+            Err(ErrorCode::InvalidMarketFeeStructure)
+        }
+    }
+}
+
+/// The fee tier qualifications account replaces the default qualifications for its market type
+/// once it has been initialized
+pub fn select_fee_tier_qualifications(
+    fee_tier_qualifications: Option<&FeeTierQualifications>,
+    market_type: MarketType,
+) -> FeeTierQualifications {
+    match fee_tier_qualifications {
+        Some(fee_tier_qualifications) => fee_tier_qualifications.clone(),
+        None => FeeTierQualifications::default_for(market_type),
+    }
 }
 
 pub fn calculate_fee_for_fulfillment_with_amm(
     user_stats: &UserStats,
     quote_asset_amount: u64,
     fee_structure: &FeeStructure,
+    fee_tier_qualifications: &FeeTierQualifications,
     order_slot: u64,
     clock_slot: u64,
     reward_filler: bool,
@@ -45,7 +89,8 @@ pub fn calculate_fee_for_fulfillment_with_amm(
     is_post_only: bool,
     fee_adjustment: i16,
 ) -> DriftResult<FillFees> {
-    let fee_tier = determine_user_fee_tier(user_stats, fee_structure, &MarketType::Perp)?;
+    let (fee_tier_index, fee_tier) =
+        determine_user_fee_tier(user_stats, fee_structure, fee_tier_qualifications)?;
 
     // if there was a quote_asset_amount_surplus, the order was a maker order and fee_to_market comes from surplus
     if is_post_only {
@@ -86,6 +131,8 @@ pub fn calculate_fee_for_fulfillment_with_amm(
             filler_reward,
             referrer_reward: 0,
             referee_discount: 0,
+            taker_fee_tier_index: None,
+            maker_fee_tier_index: Some(fee_tier_index),
         })
     } else {
         let fee = calculate_taker_fee(quote_asset_amount, fee_tier, fee_adjustment)?;
@@ -130,6 +177,8 @@ pub fn calculate_fee_for_fulfillment_with_amm(
             filler_reward,
             referrer_reward,
             referee_discount,
+            taker_fee_tier_index: Some(fee_tier_index),
+            maker_fee_tier_index: None,
         })
     }
 }
@@ -268,19 +317,20 @@ pub fn calculate_fee_for_fulfillment_with_match(
     maker_stats: &Option<&mut UserStats>,
     quote_asset_amount: u64,
     fee_structure: &FeeStructure,
+    fee_tier_qualifications: &FeeTierQualifications,
     order_slot: u64,
     clock_slot: u64,
     filler_multiplier: u64,
     reward_referrer: bool,
     referrer_stats: &Option<&mut UserStats>,
-    market_type: &MarketType,
     fee_adjustment: i16,
 ) -> DriftResult<FillFees> {
-    let taker_fee_tier = determine_user_fee_tier(taker_stats, fee_structure, market_type)?;
-    let maker_fee_tier = if let Some(maker_stats) = maker_stats {
-        determine_user_fee_tier(maker_stats, fee_structure, market_type)?
+    let (taker_fee_tier_index, taker_fee_tier) =
+        determine_user_fee_tier(taker_stats, fee_structure, fee_tier_qualifications)?;
+    let (maker_fee_tier_index, maker_fee_tier) = if let Some(maker_stats) = maker_stats {
+        determine_user_fee_tier(maker_stats, fee_structure, fee_tier_qualifications)?
     } else {
-        determine_user_fee_tier(taker_stats, fee_structure, market_type)?
+        (taker_fee_tier_index, taker_fee_tier)
     };
 
     let taker_fee = calculate_taker_fee(quote_asset_amount, taker_fee_tier, fee_adjustment)?;
@@ -325,6 +375,8 @@ pub fn calculate_fee_for_fulfillment_with_match(
         referrer_reward,
         fee_to_market_for_lp: 0,
         referee_discount,
+        taker_fee_tier_index: Some(taker_fee_tier_index),
+        maker_fee_tier_index: Some(maker_fee_tier_index),
     })
 }
 
@@ -333,12 +385,14 @@ pub struct ExternalFillFees {
     pub fee_to_market: u64,
     pub fee_pool_delta: i64,
     pub filler_reward: u64,
+    pub taker_fee_tier_index: u8,
 }
 
 pub fn calculate_fee_for_fulfillment_with_external_market(
     user_stats: &UserStats,
     quote_asset_amount: u64,
     fee_structure: &FeeStructure,
+    fee_tier_qualifications: &FeeTierQualifications,
     order_slot: u64,
     clock_slot: u64,
     reward_filler: bool,
@@ -347,7 +401,8 @@ pub fn calculate_fee_for_fulfillment_with_external_market(
     fee_pool_amount: u64,
     fee_adjustment: i16,
 ) -> DriftResult<ExternalFillFees> {
-    let taker_fee_tier = determine_user_fee_tier(user_stats, fee_structure, &MarketType::Spot)?;
+    let (taker_fee_tier_index, taker_fee_tier) =
+        determine_user_fee_tier(user_stats, fee_structure, fee_tier_qualifications)?;
 
     let fee = calculate_taker_fee(quote_asset_amount, taker_fee_tier, fee_adjustment)?;
 
@@ -391,69 +446,34 @@ pub fn calculate_fee_for_fulfillment_with_external_market(
         fee_to_market,
         filler_reward,
         fee_pool_delta,
+        taker_fee_tier_index,
     })
 }
 
+/// Returns the highest fee tier whose qualification the user meets, along with its index
 pub fn determine_user_fee_tier<'a>(
     user_stats: &UserStats,
     fee_structure: &'a FeeStructure,
-    market_type: &MarketType,
-) -> DriftResult<&'a FeeTier> {
-    match market_type {
-        MarketType::Perp => determine_perp_fee_tier(user_stats, fee_structure),
-        MarketType::Spot => determine_spot_fee_tier(user_stats, fee_structure),
-    }
-}
-
-fn determine_perp_fee_tier<'a>(
-    user_stats: &UserStats,
-    fee_structure: &'a FeeStructure,
-) -> DriftResult<&'a FeeTier> {
+    fee_tier_qualifications: &FeeTierQualifications,
+) -> DriftResult<(u8, &'a FeeTier)> {
     let total_30d_volume = user_stats.get_total_30d_volume()?;
     let staked_quote_asset_amount = user_stats.if_staked_quote_asset_amount;
     let staked_gov_token_amount = user_stats.if_staked_gov_token_amount;
 
-    if total_30d_volume >= ONE_HUNDRED_MILLION_QUOTE
-        || staked_quote_asset_amount >= TEN_THOUSAND_QUOTE - QUOTE_PRECISION_U64
-        || staked_gov_token_amount >= ONE_HUNDRED_THOUSAND_QUOTE + 19_500 * QUOTE_PRECISION_U64
-    {
-        return Ok(&fee_structure.fee_tiers[5]);
-    }
-
-    if total_30d_volume >= FIFTY_MILLION_QUOTE
-        || staked_quote_asset_amount >= ONE_THOUSAND_QUOTE * 5 - QUOTE_PRECISION_U64
-        || staked_gov_token_amount >= ONE_HUNDRED_THOUSAND_QUOTE - QUOTE_PRECISION_U64
-    {
-        return Ok(&fee_structure.fee_tiers[4]);
-    }
-
-    if total_30d_volume >= TEN_MILLION_QUOTE
-        || staked_quote_asset_amount >= ONE_THOUSAND_QUOTE * 2 - QUOTE_PRECISION_U64
-        || staked_gov_token_amount >= TWENTY_FIVE_THOUSAND_QUOTE * 2 - QUOTE_PRECISION_U64
-    {
-        return Ok(&fee_structure.fee_tiers[3]);
-    }
-
-    if total_30d_volume >= FIVE_MILLION_QUOTE
-        || staked_quote_asset_amount >= ONE_THOUSAND_QUOTE - QUOTE_PRECISION_U64
-        || staked_gov_token_amount >= TEN_THOUSAND_QUOTE - QUOTE_PRECISION_U64
-    {
-        return Ok(&fee_structure.fee_tiers[2]);
-    }
-
-    if total_30d_volume >= ONE_MILLION_QUOTE
-        || staked_quote_asset_amount >= ONE_THOUSAND_QUOTE / 2 - QUOTE_PRECISION_U64
-        || staked_gov_token_amount >= ONE_THOUSAND_QUOTE - QUOTE_PRECISION_U64
-    {
-        return Ok(&fee_structure.fee_tiers[1]);
-    }
-
-    Ok(&fee_structure.fee_tiers[0])
-}
+    let fee_tier_index = fee_tier_qualifications
+        .fee_tier_qualifications
+        .iter()
+        .rposition(|qualification| {
+            qualification.is_met(
+                total_30d_volume,
+                staked_quote_asset_amount,
+                staked_gov_token_amount,
+            )
+        })
+        .unwrap_or(0);
 
-fn determine_spot_fee_tier<'a>(
-    _user_stats: &UserStats,
-    fee_structure: &'a FeeStructure,
-) -> DriftResult<&'a FeeTier> {
-    Ok(&fee_structure.fee_tiers[0])
+    Ok((
+        fee_tier_index.cast()?,
+        &fee_structure.fee_tiers[fee_tier_index],
+    ))
 }
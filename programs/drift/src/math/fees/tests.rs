@@ -1,8 +1,8 @@
 mod calculate_fee_for_taker_and_maker {
     use crate::math::constants::QUOTE_PRECISION_U64;
     use crate::math::fees::{calculate_fee_for_fulfillment_with_match, FillFees};
-    use crate::state::state::FeeStructure;
-    use crate::state::user::{MarketType, UserStats};
+    use crate::state::state::{FeeStructure, FeeTierQualifications};
+    use crate::state::user::UserStats;
 
     #[test]
     fn no_filler() {
@@ -23,12 +23,12 @@ mod calculate_fee_for_taker_and_maker {
             &Some(&mut maker_stats),
             quote_asset_amount,
             &FeeStructure::test_default(),
+            &FeeTierQualifications::perps_default(),
             0,
             0,
             0,
             false,
             &None,
-            &MarketType::Perp,
             0,
         )
         .unwrap();
@@ -66,12 +66,12 @@ mod calculate_fee_for_taker_and_maker {
             &Some(&mut maker_stats),
             quote_asset_amount,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             0,
             1,
             false,
             &None,
-            &MarketType::Perp,
             0,
         )
         .unwrap();
@@ -108,12 +108,12 @@ mod calculate_fee_for_taker_and_maker {
             &Some(&mut maker_stats),
             quote_asset_amount,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             0,
             1,
             false,
             &None,
-            &MarketType::Perp,
             0,
         )
         .unwrap();
@@ -150,12 +150,12 @@ mod calculate_fee_for_taker_and_maker {
             &Some(&mut maker_stats),
             quote_asset_amount,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             60,
             1,
             false,
             &None,
-            &MarketType::Perp,
             0,
         )
         .unwrap();
@@ -190,12 +190,12 @@ mod calculate_fee_for_taker_and_maker {
             &Some(&mut maker_stats),
             quote_asset_amount,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             0,
             0,
             true,
             &None,
-            &MarketType::Perp,
             0,
         )
         .unwrap();
@@ -227,12 +227,12 @@ mod calculate_fee_for_taker_and_maker {
             &Some(&mut maker_stats),
             quote_asset_amount,
             &FeeStructure::test_default(),
+            &FeeTierQualifications::perps_default(),
             0,
             0,
             0,
             false,
             &None,
-            &MarketType::Perp,
             -50,
         )
         .unwrap();
@@ -257,12 +257,12 @@ mod calculate_fee_for_taker_and_maker {
             &Some(&mut maker_stats),
             quote_asset_amount,
             &FeeStructure::test_default(),
+            &FeeTierQualifications::perps_default(),
             0,
             0,
             0,
             false,
             &None,
-            &MarketType::Perp,
             50,
         )
         .unwrap();
@@ -288,12 +288,12 @@ mod calculate_fee_for_taker_and_maker {
             &Some(&mut maker_stats),
             quote_asset_amount,
             &FeeStructure::test_default(),
+            &FeeTierQualifications::perps_default(),
             0,
             0,
             0,
             true,
             &None,
-            &MarketType::Perp,
             -50,
         )
         .unwrap();
@@ -319,12 +319,12 @@ mod calculate_fee_for_taker_and_maker {
             &Some(&mut maker_stats),
             quote_asset_amount,
             &FeeStructure::test_default(),
+            &FeeTierQualifications::perps_default(),
             0,
             0,
             1,
             true,
             &None,
-            &MarketType::Perp,
             -50,
         )
         .unwrap();
@@ -341,7 +341,7 @@ mod calculate_fee_for_taker_and_maker {
 mod calculate_fee_for_order_fulfill_against_amm {
     use crate::math::constants::QUOTE_PRECISION_U64;
     use crate::math::fees::{calculate_fee_for_fulfillment_with_amm, FillFees};
-    use crate::state::state::FeeStructure;
+    use crate::state::state::{FeeStructure, FeeTierQualifications};
     use crate::state::user::UserStats;
 
     #[test]
@@ -362,6 +362,7 @@ mod calculate_fee_for_order_fulfill_against_amm {
             &taker_stats,
             quote_asset_amount,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             60,
             false,
@@ -398,6 +399,7 @@ mod calculate_fee_for_order_fulfill_against_amm {
             &taker_stats,
             quote_asset_amount,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             60,
             false,
@@ -426,6 +428,7 @@ mod calculate_fee_for_order_fulfill_against_amm {
             &taker_stats,
             quote_asset_amount,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             60,
             false,
@@ -455,6 +458,7 @@ mod calculate_fee_for_order_fulfill_against_amm {
             &taker_stats,
             quote_asset_amount,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             60,
             false,
@@ -484,6 +488,7 @@ mod calculate_fee_for_order_fulfill_against_amm {
             &taker_stats,
             quote_asset_amount,
             &fee_structure,
+            &FeeTierQualifications::perps_default(),
             0,
             60,
             true,
@@ -506,7 +511,7 @@ mod calculate_fee_for_order_fulfill_against_amm {
 mod calculate_fee_for_fulfillment_with_serum {
     use crate::math::constants::QUOTE_PRECISION_U64;
     use crate::math::fees::{calculate_fee_for_fulfillment_with_external_market, ExternalFillFees};
-    use crate::state::state::FeeStructure;
+    use crate::state::state::{FeeStructure, FeeTierQualifications};
     use crate::state::user::UserStats;
 
     #[test]
@@ -527,10 +532,12 @@ mod calculate_fee_for_fulfillment_with_serum {
             fee_to_market,
             fee_pool_delta,
             filler_reward,
+            ..
         } = calculate_fee_for_fulfillment_with_external_market(
             &taker_stats,
             quote_asset_amount,
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
             0,
             0,
             false,
@@ -565,10 +572,12 @@ mod calculate_fee_for_fulfillment_with_serum {
             fee_to_market,
             fee_pool_delta,
             filler_reward,
+            ..
         } = calculate_fee_for_fulfillment_with_external_market(
             &taker_stats,
             quote_asset_amount,
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
             0,
             0,
             true,
@@ -604,10 +613,12 @@ mod calculate_fee_for_fulfillment_with_serum {
             fee_to_market,
             fee_pool_delta,
             filler_reward,
+            ..
         } = calculate_fee_for_fulfillment_with_external_market(
             &user_stats,
             quote_asset_amount,
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
             0,
             0,
             true,
@@ -643,10 +654,12 @@ mod calculate_fee_for_fulfillment_with_serum {
             fee_to_market,
             fee_pool_delta,
             filler_reward,
+            ..
         } = calculate_fee_for_fulfillment_with_external_market(
             &user_stats,
             quote_asset_amount,
             &fee_structure,
+            &FeeTierQualifications::spot_default(),
             0,
             0,
             true,
@@ -663,3 +676,207 @@ mod calculate_fee_for_fulfillment_with_serum {
         assert_eq!(filler_reward, 2000);
     }
 }
+
+mod select_fee_structure {
+    use crate::error::ErrorCode;
+    use crate::math::constants::QUOTE_PRECISION_U64;
+    use crate::math::fees::{calculate_fee_for_fulfillment_with_match, select_fee_structure};
+    use crate::state::state::{FeeStructure, FeeTierQualifications, MarketFeeStructure};
+    use crate::state::user::{MarketType, UserStats};
+
+    #[test]
+    fn market_override() {
+        let fee_structure = FeeStructure::perps_default();
+
+        let mut market_fee_structure = MarketFeeStructure {
+            fee_structure: FeeStructure::perps_default(),
+            market_index: 1,
+            market_type: MarketType::Perp,
+            ..MarketFeeStructure::default()
+        };
+        // zero taker fee for every tier
+        for fee_tier in market_fee_structure.fee_structure.fee_tiers.iter_mut() {
+            fee_tier.fee_numerator = 0;
+            fee_tier.maker_rebate_numerator = 0;
+        }
+
+        // no override
+        let selected =
+            select_fee_structure(&fee_structure, None, MarketType::Perp, 1, false).unwrap();
+        assert_eq!(selected.fee_tiers[0].fee_numerator, 100);
+
+        let selected = select_fee_structure(
+            &fee_structure,
+            Some(&market_fee_structure),
+            MarketType::Perp,
+            1,
+            true,
+        )
+        .unwrap();
+        assert_eq!(selected.fee_tiers[0].fee_numerator, 0);
+
+        let taker_stats = UserStats::default();
+        let fill_fees = calculate_fee_for_fulfillment_with_match(
+            &taker_stats,
+            &Some(&mut UserStats::default()),
+            100 * QUOTE_PRECISION_U64,
+            selected,
+            &FeeTierQualifications::perps_default(),
+            0,
+            0,
+            0,
+            false,
+            &None,
+            0,
+        )
+        .unwrap();
+        assert_eq!(fill_fees.user_fee, 0);
+        assert_eq!(fill_fees.maker_rebate, 0);
+
+        // override required but missing
+        assert_eq!(
+            select_fee_structure(&fee_structure, None, MarketType::Perp, 1, true).unwrap_err(),
+            ErrorCode::InvalidMarketFeeStructure
+        );
+
+        // override for a different market
+        assert_eq!(
+            select_fee_structure(
+                &fee_structure,
+                Some(&market_fee_structure),
+                MarketType::Spot,
+                1,
+                true
+            )
+            .unwrap_err(),
+            ErrorCode::InvalidMarketFeeStructure
+        );
+        assert_eq!(
+            select_fee_structure(
+                &fee_structure,
+                Some(&market_fee_structure),
+                MarketType::Perp,
+                0,
+                true
+            )
+            .unwrap_err(),
+            ErrorCode::InvalidMarketFeeStructure
+        );
+    }
+}
+
+mod determine_user_fee_tier {
+    use crate::math::constants::{
+        ONE_HUNDRED_MILLION_QUOTE, ONE_MILLION_QUOTE, ONE_THOUSAND_QUOTE, QUOTE_PRECISION_U64,
+        TEN_MILLION_QUOTE,
+    };
+    use crate::math::fees::{calculate_fee_for_fulfillment_with_match, determine_user_fee_tier};
+    use crate::state::state::{FeeStructure, FeeTierQualification, FeeTierQualifications};
+    use crate::state::user::UserStats;
+
+    #[test]
+    fn default_qualifications() {
+        let fee_structure = FeeStructure::perps_default();
+        let fee_tier_qualifications = FeeTierQualifications::perps_default();
+
+        let user_stats = UserStats::default();
+        let (fee_tier_index, _) =
+            determine_user_fee_tier(&user_stats, &fee_structure, &fee_tier_qualifications).unwrap();
+        assert_eq!(fee_tier_index, 0);
+
+        let user_stats = UserStats {
+            taker_volume_30d: ONE_MILLION_QUOTE / 2,
+            maker_volume_30d: ONE_MILLION_QUOTE / 2,
+            ..UserStats::default()
+        };
+        let (fee_tier_index, _) =
+            determine_user_fee_tier(&user_stats, &fee_structure, &fee_tier_qualifications).unwrap();
+        assert_eq!(fee_tier_index, 1);
+
+        let user_stats = UserStats {
+            taker_volume_30d: ONE_MILLION_QUOTE,
+            if_staked_quote_asset_amount: ONE_THOUSAND_QUOTE * 2 - QUOTE_PRECISION_U64,
+            ..UserStats::default()
+        };
+        let (fee_tier_index, fee_tier) =
+            determine_user_fee_tier(&user_stats, &fee_structure, &fee_tier_qualifications).unwrap();
+        assert_eq!(fee_tier_index, 3);
+        assert_eq!(fee_tier.fee_numerator, 70);
+
+        let user_stats = UserStats {
+            taker_volume_30d: ONE_HUNDRED_MILLION_QUOTE,
+            ..UserStats::default()
+        };
+        let (fee_tier_index, _) =
+            determine_user_fee_tier(&user_stats, &fee_structure, &fee_tier_qualifications).unwrap();
+        assert_eq!(fee_tier_index, 5);
+
+        // spot has no qualifications by default
+        let (fee_tier_index, _) = determine_user_fee_tier(
+            &user_stats,
+            &FeeStructure::spot_default(),
+            &FeeTierQualifications::spot_default(),
+        )
+        .unwrap();
+        assert_eq!(fee_tier_index, 0);
+    }
+
+    #[test]
+    fn custom_qualifications() {
+        let fee_structure = FeeStructure::perps_default();
+        let mut fee_tier_qualifications = FeeTierQualifications::perps_default();
+        fee_tier_qualifications.fee_tier_qualifications[5] = FeeTierQualification::default();
+        fee_tier_qualifications.fee_tier_qualifications[6] = FeeTierQualification {
+            volume_30d_threshold: TEN_MILLION_QUOTE * 20,
+            ..FeeTierQualification::default()
+        };
+
+        let user_stats = UserStats {
+            taker_volume_30d: ONE_HUNDRED_MILLION_QUOTE,
+            ..UserStats::default()
+        };
+        let (fee_tier_index, _) =
+            determine_user_fee_tier(&user_stats, &fee_structure, &fee_tier_qualifications).unwrap();
+        assert_eq!(fee_tier_index, 4);
+
+        let user_stats = UserStats {
+            taker_volume_30d: ONE_HUNDRED_MILLION_QUOTE * 2,
+            ..UserStats::default()
+        };
+        let (fee_tier_index, _) =
+            determine_user_fee_tier(&user_stats, &fee_structure, &fee_tier_qualifications).unwrap();
+        assert_eq!(fee_tier_index, 6);
+    }
+
+    #[test]
+    fn fill_fees() {
+        let fee_structure = FeeStructure::perps_default();
+        let taker_stats = UserStats {
+            taker_volume_30d: TEN_MILLION_QUOTE,
+            ..UserStats::default()
+        };
+        let mut maker_stats = UserStats {
+            maker_volume_30d: ONE_HUNDRED_MILLION_QUOTE,
+            ..UserStats::default()
+        };
+
+        let fill_fees = calculate_fee_for_fulfillment_with_match(
+            &taker_stats,
+            &Some(&mut maker_stats),
+            100 * QUOTE_PRECISION_U64,
+            &fee_structure,
+            &FeeTierQualifications::perps_default(),
+            0,
+            0,
+            0,
+            false,
+            &None,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(fill_fees.taker_fee_tier_index, Some(3));
+        assert_eq!(fill_fees.maker_fee_tier_index, Some(5));
+        assert_eq!(fill_fees.user_fee, 70_000);
+    }
+}
@@ -3,7 +3,9 @@ use crate::math::casting::Cast;
 use crate::math::safe_math::SafeMath;
 use crate::state::perp_market::PerpMarket;
 use crate::state::spot_market::SpotMarket;
-use crate::{FUEL_WINDOW_U128, QUOTE_PRECISION, QUOTE_PRECISION_U64};
+use crate::{
+    FUEL_REDEMPTION_RATE_PRECISION_U128, FUEL_WINDOW_U128, QUOTE_PRECISION, QUOTE_PRECISION_U64,
+};
 
 #[cfg(test)]
 mod tests;
@@ -28,6 +30,36 @@ pub fn calculate_perp_fuel_bonus(
     Ok(result)
 }
 
+/// Fuel for the value of a user's lp shares, accrued over the same window as position fuel
+pub fn calculate_lp_fuel_bonus(
+    perp_market: &PerpMarket,
+    lp_shares_value: u128,
+    fuel_bonus_numerator: i64,
+) -> DriftResult<u64> {
+    let result: u64 = if lp_shares_value < QUOTE_PRECISION {
+        0_u64
+    } else {
+        lp_shares_value
+            .safe_mul(fuel_bonus_numerator.cast()?)?
+            .safe_mul(perp_market.fuel_boost_lp.cast()?)?
+            .safe_div(FUEL_WINDOW_U128)?
+            .cast::<u64>()?
+            / (QUOTE_PRECISION_U64 / 10)
+    };
+
+    Ok(result)
+}
+
+/// Fuel for performing keeper actions (fills, triggers, expiries and liquidations), credited per
+/// cent of keeper reward earned
+pub fn calculate_keeper_fuel_bonus(fuel_boost_keeper: u8, keeper_reward: u64) -> DriftResult<u64> {
+    keeper_reward
+        .cast::<u128>()?
+        .safe_mul(fuel_boost_keeper.cast()?)?
+        .safe_div(QUOTE_PRECISION / 100)?
+        .cast()
+}
+
 pub fn calculate_spot_fuel_bonus(
     spot_market: &SpotMarket,
     signed_token_value: i128,
@@ -71,3 +103,11 @@ pub fn calculate_insurance_fuel_bonus(
         .cast::<u64>()?
         / (QUOTE_PRECISION_U64 / 10))
 }
+
+/// Reward tokens paid for redeeming fuel from a closed fuel season
+pub fn calculate_fuel_redemption_amount(fuel: u64, redemption_rate: u64) -> DriftResult<u64> {
+    fuel.cast::<u128>()?
+        .safe_mul(redemption_rate.cast()?)?
+        .safe_div(FUEL_REDEMPTION_RATE_PRECISION_U128)?
+        .cast()
+}
@@ -16,6 +16,52 @@ mod calculate_perp_fuel_bonus {
     }
 }
 
+mod calculate_lp_fuel_bonus {
+    use crate::math::fuel::calculate_lp_fuel_bonus;
+    use crate::state::perp_market::PerpMarket;
+    use crate::{FUEL_WINDOW_U128, QUOTE_PRECISION};
+
+    #[test]
+    fn test() {
+        let perp_market = PerpMarket {
+            fuel_boost_lp: 1,
+            ..PerpMarket::default()
+        };
+        let bonus = calculate_lp_fuel_bonus(&perp_market, QUOTE_PRECISION, FUEL_WINDOW_U128 as i64)
+            .unwrap();
+        assert_eq!(bonus, 10);
+
+        // dust lp shares earn no fuel
+        let bonus =
+            calculate_lp_fuel_bonus(&perp_market, QUOTE_PRECISION - 1, FUEL_WINDOW_U128 as i64)
+                .unwrap();
+        assert_eq!(bonus, 0);
+
+        let perp_market = PerpMarket::default();
+        let bonus = calculate_lp_fuel_bonus(&perp_market, QUOTE_PRECISION, FUEL_WINDOW_U128 as i64)
+            .unwrap();
+        assert_eq!(bonus, 0);
+    }
+}
+
+mod calculate_keeper_fuel_bonus {
+    use crate::math::fuel::calculate_keeper_fuel_bonus;
+    use crate::QUOTE_PRECISION_U64;
+
+    #[test]
+    fn test() {
+        // flat filler fee of 1 cent
+        let bonus = calculate_keeper_fuel_bonus(10, QUOTE_PRECISION_U64 / 100).unwrap();
+        assert_eq!(bonus, 10);
+
+        let bonus = calculate_keeper_fuel_bonus(10, QUOTE_PRECISION_U64).unwrap();
+        assert_eq!(bonus, 1000);
+
+        let bonus = calculate_keeper_fuel_bonus(0, QUOTE_PRECISION_U64).unwrap();
+        assert_eq!(bonus, 0);
+    }
+}
+
 mod calculate_spot_fuel_bonus {
     use crate::math::fuel::calculate_spot_fuel_bonus;
 
@@ -64,3 +110,22 @@ mod calculate_insurance_fuel_bonus {
         assert_eq!(bonus, 10);
     }
 }
+
+mod calculate_fuel_redemption_amount {
+    use crate::math::fuel::calculate_fuel_redemption_amount;
+    use crate::FUEL_REDEMPTION_RATE_PRECISION;
+
+    #[test]
+    fn test() {
+        // one reward token unit per fuel
+        let amount =
+            calculate_fuel_redemption_amount(2_500, FUEL_REDEMPTION_RATE_PRECISION).unwrap();
+        assert_eq!(amount, 2_500);
+
+        let amount = calculate_fuel_redemption_amount(u32::MAX as u64 * 6, 1_000_000_000).unwrap();
+        assert_eq!(amount, u32::MAX as u64 * 6_000);
+
+        let amount = calculate_fuel_redemption_amount(1, 999_999).unwrap();
+        assert_eq!(amount, 0);
+    }
+}
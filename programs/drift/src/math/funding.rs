@@ -6,13 +6,14 @@ use crate::error::{DriftResult, ErrorCode};
 use crate::math::bn;
 use crate::math::casting::Cast;
 use crate::math::constants::{
-    AMM_TO_QUOTE_PRECISION_RATIO, AMM_TO_QUOTE_PRECISION_RATIO_I128, FUNDING_RATE_BUFFER,
-    PRICE_PRECISION, QUOTE_TO_BASE_AMT_FUNDING_PRECISION,
+    AMM_TO_QUOTE_PRECISION_RATIO, AMM_TO_QUOTE_PRECISION_RATIO_I128,
+    FUNDING_INTEREST_RATE_ADJUSTMENT_MAX, FUNDING_RATE_BUFFER, FUNDING_RATE_OFFSET_DENOMINATOR,
+    PRICE_PRECISION, PRICE_PRECISION_I64, QUOTE_TO_BASE_AMT_FUNDING_PRECISION,
 };
 use crate::math::repeg::{calculate_fee_pool, get_total_fee_lower_bound};
 use crate::math::safe_math::SafeMath;
 
-use crate::state::perp_market::PerpMarket;
+use crate::state::perp_market::{PerpMarket, AMM};
 use crate::state::user::PerpPosition;
 
 #[cfg(test)]
@@ -212,3 +213,64 @@ pub fn calculate_funding_payment_in_quote_precision(
 
     Ok(funding_payment_collateral)
 }
+
+/// Premium of the amm mid price over the oracle price as a fraction of the oracle price.
+/// Each sample is clamped to the contract tier's max funding divergence so a single
+/// manipulated update can't dominate the index.
+pub fn calculate_premium_index_sample(
+    market: &PerpMarket,
+    bid_price: u64,
+    ask_price: u64,
+    oracle_price: i64,
+) -> DriftResult<i64> {
+    if oracle_price <= 0 {
+        return Ok(0);
+    }
+
+    let mid_price = bid_price.safe_add(ask_price)?.safe_div(2)?;
+
+    let premium = mid_price
+        .cast::<i128>()?
+        .safe_sub(oracle_price.cast()?)?
+        .safe_mul(PRICE_PRECISION_I64.cast()?)?
+        .safe_div(oracle_price.cast()?)?
+        .cast::<i64>()?;
+
+    let max_premium = market.get_max_price_divergence_for_funding_rate(PRICE_PRECISION_I64)?;
+
+    Ok(premium.clamp(-max_premium, max_premium))
+}
+
+/// Time weighted average of the premium index samples taken since the last funding update.
+/// Returns None if no samples have been taken.
+pub fn calculate_premium_index(amm: &AMM) -> DriftResult<Option<i64>> {
+    if amm.premium_index_sample_weight == 0 {
+        return Ok(None);
+    }
+
+    amm.premium_index_sample_sum
+        .safe_div(amm.premium_index_sample_weight.cast()?)
+        .map(Some)
+}
+
+/// The interest rate component of funding, expressed as a price offset.
+/// With no adjustment, the offset is 1/FUNDING_RATE_OFFSET_DENOMINATOR of the oracle twap per day
+pub fn calculate_funding_interest_rate_offset(
+    oracle_price_twap: i64,
+    funding_interest_rate_adjustment: i8,
+) -> DriftResult<i64> {
+    let offset = oracle_price_twap
+        .abs()
+        .safe_div(FUNDING_RATE_OFFSET_DENOMINATOR)?;
+
+    if funding_interest_rate_adjustment == 0 {
+        return Ok(offset);
+    }
+
+    offset
+        .safe_mul(
+            FUNDING_INTEREST_RATE_ADJUSTMENT_MAX
+                .safe_add(funding_interest_rate_adjustment.cast()?)?,
+        )?
+        .safe_div(FUNDING_INTEREST_RATE_ADJUSTMENT_MAX)
+}
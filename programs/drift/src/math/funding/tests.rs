@@ -1,10 +1,13 @@
-use crate::controller::funding::update_funding_rate;
+use crate::controller::funding::{
+    calculate_predicted_funding_rate, update_funding_rate, update_premium_index,
+};
 use crate::controller::repeg::_update_amm;
 use crate::math::helpers::on_the_hour_update;
 use crate::math::oracle::block_operation;
 
 use crate::math::constants::{
-    AMM_RESERVE_PRECISION, ONE_HOUR_I128, PRICE_PRECISION, PRICE_PRECISION_U64, QUOTE_PRECISION,
+    AMM_RESERVE_PRECISION, ONE_HOUR_I128, PEG_PRECISION, PRICE_PRECISION, PRICE_PRECISION_I64,
+    PRICE_PRECISION_U64, QUOTE_PRECISION,
 };
 use crate::math::funding::*;
 use std::cmp::min;
@@ -12,10 +15,13 @@ use std::cmp::min;
 use crate::test_utils::get_pyth_price;
 
 // use crate::create_anchor_account_info;
+use crate::error::ErrorCode;
 use crate::state::oracle::HistoricalOracleData;
 use crate::state::oracle_map::OracleMap;
-use crate::state::perp_market::{ContractTier, PerpMarket, AMM};
+use crate::state::paused_operations::PerpOperation;
+use crate::state::perp_market::{ContractTier, MarketStatus, PerpMarket, AMM};
 use crate::state::state::{OracleGuardRails, State, ValidityGuardRails};
+use crate::state::user::PerpPosition;
 use solana_program::pubkey::Pubkey;
 use std::str::FromStr;
 
@@ -572,6 +578,7 @@ fn max_funding_rates() {
         &state.oracle_guard_rails,
         false,
         None,
+        None,
     )
     .unwrap();
 
@@ -677,7 +684,10 @@ fn unsettled_funding_pnl() {
         market.amm.historical_oracle_data.last_oracle_price_twap,
         51000000
     );
-    // oracle twap > mark, expect negative funding
+    // sample weight capped at 1 min of the hourly funding period
+    assert_eq!(market.amm.premium_index_sample_weight, 60);
+    assert_eq!(market.amm.premium_index_sample_sum, -66101 * 60); // mid ~6.6% below oracle
+                                                                  // oracle twap > mark, expect negative funding
 
     let block_funding_rate_update = block_operation(
         &market,
@@ -699,6 +709,7 @@ fn unsettled_funding_pnl() {
         &state.oracle_guard_rails,
         false,
         None,
+        None,
     )
     .unwrap();
     assert!(did_succeed);
@@ -710,15 +721,325 @@ fn unsettled_funding_pnl() {
         51000000
     );
 
-    assert_eq!(market.amm.cumulative_funding_rate_long, -140002666); // negative funding
-    assert_eq!(market.amm.cumulative_funding_rate_short, -140002666);
-    assert_eq!(market.amm.last_funding_rate, -140002666);
-    assert_eq!(market.amm.last_24h_avg_funding_rate, -140002666 / 24 + 1);
+    assert_eq!(market.amm.cumulative_funding_rate_long, -140039625); // negative funding
+    assert_eq!(market.amm.cumulative_funding_rate_short, -140039625);
+    assert_eq!(market.amm.last_funding_rate, -140039625);
+    assert_eq!(market.amm.last_24h_avg_funding_rate, -140039625 / 24 + 1);
     assert_eq!(market.amm.last_funding_rate_ts, now);
     assert_eq!(market.amm.net_revenue_since_last_funding, 0); // back to 0
-    assert_eq!(market.amm.total_fee_minus_distributions, 100070722677); //71.722677 gain
+    assert_eq!(market.amm.total_fee_minus_distributions, 100070741611); //71.741611 gain
     assert_eq!(market.amm.total_fee, 0);
 
     assert_ne!(market.amm.net_unsettled_funding_pnl, 0); // important: imbalanced market adds funding rev
-    assert_eq!(market.amm.net_unsettled_funding_pnl, -71722677); // users up
+    assert_eq!(market.amm.net_unsettled_funding_pnl, -71741611); // users up
+
+    // premium index samples reset for next funding period
+    assert_eq!(market.amm.premium_index_sample_weight, 0);
+    assert_eq!(market.amm.premium_index_sample_sum, 0);
+}
+
+#[test]
+fn premium_index_sample() {
+    let market = PerpMarket {
+        contract_tier: ContractTier::A,
+        ..PerpMarket::default()
+    };
+
+    // mid above oracle
+    let sample = calculate_premium_index_sample(
+        &market,
+        100_900 * PRICE_PRECISION_U64 / 1000,
+        101_100 * PRICE_PRECISION_U64 / 1000,
+        100 * PRICE_PRECISION_I64,
+    )
+    .unwrap();
+    assert_eq!(sample, 10_000); // 1%
+
+    // mid below oracle
+    let sample = calculate_premium_index_sample(
+        &market,
+        99_400 * PRICE_PRECISION_U64 / 1000,
+        99_600 * PRICE_PRECISION_U64 / 1000,
+        100 * PRICE_PRECISION_I64,
+    )
+    .unwrap();
+    assert_eq!(sample, -5_000); // -.5%
+
+    // clamped to contract tier max divergence
+    let sample = calculate_premium_index_sample(
+        &market,
+        110 * PRICE_PRECISION_U64,
+        110 * PRICE_PRECISION_U64,
+        100 * PRICE_PRECISION_I64,
+    )
+    .unwrap();
+    assert_eq!(sample, 30_303); // 3%
+
+    let market = PerpMarket {
+        contract_tier: ContractTier::Speculative,
+        ..PerpMarket::default()
+    };
+    let sample = calculate_premium_index_sample(
+        &market,
+        110 * PRICE_PRECISION_U64,
+        110 * PRICE_PRECISION_U64,
+        100 * PRICE_PRECISION_I64,
+    )
+    .unwrap();
+    assert_eq!(sample, 100_000); // 10%
+
+    let amm = AMM {
+        premium_index_sample_sum: 10_000 * 1800 - 5_000 * 1800,
+        premium_index_sample_weight: 3600,
+        ..AMM::default()
+    };
+    assert_eq!(calculate_premium_index(&amm).unwrap(), Some(2_500));
+    assert_eq!(calculate_premium_index(&AMM::default()).unwrap(), None);
+}
+
+#[test]
+fn funding_interest_rate_offset() {
+    let oracle_price_twap = 100 * PRICE_PRECISION_I64;
+
+    // default 7.3% annualized
+    let offset = calculate_funding_interest_rate_offset(oracle_price_twap, 0).unwrap();
+    assert_eq!(offset, 20_000);
+
+    let offset = calculate_funding_interest_rate_offset(oracle_price_twap, -50).unwrap();
+    assert_eq!(offset, 10_000);
+
+    let offset = calculate_funding_interest_rate_offset(oracle_price_twap, 100).unwrap();
+    assert_eq!(offset, 40_000);
+
+    let offset = calculate_funding_interest_rate_offset(oracle_price_twap, -100).unwrap();
+    assert_eq!(offset, 0);
+
+    // uses abs of oracle twap
+    let offset = calculate_funding_interest_rate_offset(-oracle_price_twap, 0).unwrap();
+    assert_eq!(offset, 20_000);
+}
+
+#[test]
+fn predicted_funding_rate() {
+    let now = 3600_i64;
+    let slot = 7200_u64;
+
+    let state = State {
+        oracle_guard_rails: OracleGuardRails {
+            validity: ValidityGuardRails {
+                slots_before_stale_for_amm: 10,     // 5s
+                slots_before_stale_for_margin: 120, // 60s
+                confidence_interval_max_size: 1000,
+                too_volatile_ratio: 5,
+            },
+            ..OracleGuardRails::default()
+        },
+        ..State::default()
+    };
+
+    let mut oracle_price = get_pyth_price(51, 6);
+    let oracle_price_key =
+        Pubkey::from_str("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix").unwrap();
+    let pyth_program = crate::ids::pyth_program::id();
+    create_account_info!(
+        oracle_price,
+        &oracle_price_key,
+        &pyth_program,
+        oracle_account_info
+    );
+    let mut oracle_map = OracleMap::load_one(&oracle_account_info, 0, None).unwrap();
+    let mut market = PerpMarket {
+        market_index: 0,
+        status: MarketStatus::Active,
+        amm: AMM {
+            oracle: oracle_price_key,
+
+            base_asset_reserve: 512295081967,
+            quote_asset_reserve: 488 * AMM_RESERVE_PRECISION,
+            sqrt_k: 500 * AMM_RESERVE_PRECISION,
+            peg_multiplier: 50000000,
+            base_asset_amount_with_amm: -12295081967, //~12
+            base_asset_amount_long: 12295081967,
+            base_asset_amount_short: -12295081967 * 2,
+            base_asset_amount_with_unsettled_lp: -((AMM_RESERVE_PRECISION * 500) as i128), //wowsers
+            total_exchange_fee: QUOTE_PRECISION / 2,
+            total_fee_minus_distributions: ((QUOTE_PRECISION * 99999) as i128),
+
+            last_mark_price_twap: 50 * PRICE_PRECISION_U64,
+            historical_oracle_data: HistoricalOracleData {
+                last_oracle_price_twap: (49 * PRICE_PRECISION) as i64,
+
+                ..HistoricalOracleData::default()
+            },
+            funding_period: 3600,
+
+            ..AMM::default()
+        },
+        ..PerpMarket::default()
+    };
+
+    let long_position = PerpPosition {
+        market_index: 0,
+        base_asset_amount: 10 * AMM_RESERVE_PRECISION as i64,
+        ..PerpPosition::default()
+    };
+
+    let market_before = market;
+    let oracle_price_data = oracle_map.get_price_data(&market.amm.oracle).unwrap();
+    let predicted = calculate_predicted_funding_rate(
+        &market,
+        oracle_price_data,
+        &state,
+        now,
+        slot,
+        Some(&long_position),
+    )
+    .unwrap();
+
+    // market untouched
+    assert_eq!(
+        market.amm.last_update_slot,
+        market_before.amm.last_update_slot
+    );
+    assert_eq!(
+        market.amm.total_fee_minus_distributions,
+        market_before.amm.total_fee_minus_distributions
+    );
+    assert_eq!(market.amm.premium_index_sample_weight, 0);
+
+    assert_eq!(predicted.time_until_next_update, 0);
+    assert_eq!(predicted.premium_index, -66101);
+    assert_eq!(predicted.funding_rate, -140039625);
+    assert_eq!(predicted.funding_rate_long, -140039625);
+    assert_eq!(predicted.funding_rate_short, -140039625);
+    // negative funding, longs receive
+    assert_eq!(predicted.funding_payment, 1400396);
+    assert!(!predicted.funding_update_blocked);
+
+    let paused_market = PerpMarket {
+        paused_operations: PerpOperation::UpdateFunding as u8,
+        ..market
+    };
+    assert_eq!(
+        calculate_predicted_funding_rate(
+            &paused_market,
+            oracle_price_data,
+            &state,
+            now,
+            slot,
+            Some(&long_position),
+        ),
+        Err(ErrorCode::MarketActionPaused)
+    );
+
+    let settling_market = PerpMarket {
+        status: MarketStatus::Settlement,
+        ..market
+    };
+    assert_eq!(
+        calculate_predicted_funding_rate(
+            &settling_market,
+            oracle_price_data,
+            &state,
+            now,
+            slot,
+            Some(&long_position),
+        ),
+        Err(ErrorCode::MarketActionPaused)
+    );
+
+    _update_amm(&mut market, oracle_price_data, &state, now, slot).unwrap();
+
+    // markets with depth bounds need them to update funding
+    let mut market_with_depth_bounds = PerpMarket {
+        has_depth_bounds: true,
+        ..market
+    };
+    assert_eq!(
+        update_funding_rate(
+            0,
+            &mut market_with_depth_bounds,
+            &mut oracle_map,
+            now,
+            slot,
+            &state.oracle_guard_rails,
+            false,
+            None,
+            None,
+        ),
+        Err(ErrorCode::InvalidPerpMarketDepthBounds)
+    );
+
+    let did_succeed = update_funding_rate(
+        0,
+        &mut market,
+        &mut oracle_map,
+        now,
+        slot,
+        &state.oracle_guard_rails,
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+    assert!(did_succeed);
+
+    assert_eq!(market.amm.last_funding_rate, predicted.funding_rate);
+    assert_eq!(
+        market.amm.last_funding_rate_long,
+        predicted.funding_rate_long as i64
+    );
+    assert_eq!(
+        market.amm.last_funding_rate_short,
+        predicted.funding_rate_short as i64
+    );
+    assert_eq!(
+        market.amm.historical_oracle_data.last_oracle_price_twap,
+        predicted.oracle_price_twap
+    );
+    assert_eq!(market.amm.last_mark_price_twap, predicted.mark_price_twap);
+
+    let funding_payment =
+        calculate_funding_payment(market.amm.cumulative_funding_rate_long, &long_position).unwrap();
+    assert_eq!(funding_payment, predicted.funding_payment);
+}
+
+#[test]
+fn premium_index_sample_weight_capped() {
+    let mut market = PerpMarket {
+        amm: AMM {
+            base_asset_reserve: 100 * AMM_RESERVE_PRECISION,
+            quote_asset_reserve: 100 * AMM_RESERVE_PRECISION,
+            sqrt_k: 100 * AMM_RESERVE_PRECISION,
+            peg_multiplier: 100 * PEG_PRECISION,
+            funding_period: 3600,
+            historical_oracle_data: HistoricalOracleData {
+                last_oracle_price_twap_ts: 0,
+                ..HistoricalOracleData::default()
+            },
+            ..AMM::default()
+        },
+        contract_tier: ContractTier::Speculative,
+        ..PerpMarket::default()
+    };
+    let reserve_price = market.amm.reserve_price().unwrap();
+
+    // mid at oracle, sampled every 30 seconds for 59 minutes
+    let mut now = 0;
+    for _ in 0..118 {
+        now += 30;
+        update_premium_index(&mut market, 100 * PRICE_PRECISION_I64, reserve_price, now).unwrap();
+        market.amm.historical_oracle_data.last_oracle_price_twap_ts = now;
+    }
+    assert_eq!(market.amm.premium_index_sample_weight, 3540);
+    assert_eq!(market.amm.premium_index_sample_sum, 0);
+
+    // oracle moved 10% right before the only update after a long gap
+    now += 3600;
+    update_premium_index(&mut market, 90 * PRICE_PRECISION_I64, reserve_price, now).unwrap();
+
+    assert_eq!(market.amm.premium_index_sample_weight, 3600);
+    let premium_index = calculate_premium_index(&market.amm).unwrap().unwrap();
+    // the late sample (clamped to the 10% max divergence) only counts for 1 min of the hour
+    assert_eq!(premium_index, 100000 * 60 / 3600);
 }
@@ -2,12 +2,13 @@ use solana_program::msg;
 
 use crate::error::{DriftResult, ErrorCode};
 use crate::math::casting::Cast;
+use crate::math::constants::{IF_VALUE_PER_SHARE_PRECISION, ONE_YEAR, PERCENTAGE_PRECISION};
 use crate::math::helpers::{get_proportion_u128, log10_iter};
 use crate::math::safe_math::SafeMath;
 
 use crate::state::insurance_fund_stake::InsuranceFundStake;
 use crate::state::spot_market::SpotMarket;
-use crate::validate;
+use crate::{math_error, validate};
 
 #[cfg(test)]
 mod tests;
@@ -67,6 +68,99 @@ pub fn if_shares_to_vault_amount(
     Ok(amount)
 }
 
+/// Share tokens are denominated in shares at shares base 0 so they never need to be rebased.
+/// token amount = shares * 10^shares_base
+pub fn if_shares_to_share_tokens(n_shares: u128, shares_base: u128) -> DriftResult<u64> {
+    let multiplier = 10_u128
+        .checked_pow(shares_base.cast()?)
+        .ok_or_else(math_error!())?;
+
+    n_shares.safe_mul(multiplier)?.cast::<u64>()
+}
+
+pub fn share_tokens_to_if_shares(token_amount: u64, shares_base: u128) -> DriftResult<u128> {
+    let divisor = 10_u128
+        .checked_pow(shares_base.cast()?)
+        .ok_or_else(math_error!())?;
+
+    token_amount.cast::<u128>()?.safe_div(divisor)
+}
+
+/// Vault amount per share at shares base 0, so it is comparable across rebases
+pub fn calculate_if_value_per_share(
+    total_if_shares: u128,
+    shares_base: u128,
+    insurance_fund_vault_balance: u64,
+) -> DriftResult<u128> {
+    if total_if_shares == 0 {
+        return Ok(0);
+    }
+
+    let normalized_shares = total_if_shares.safe_mul(
+        10_u128
+            .checked_pow(shares_base.cast()?)
+            .ok_or_else(math_error!())?,
+    )?;
+
+    insurance_fund_vault_balance
+        .cast::<u128>()?
+        .safe_mul(IF_VALUE_PER_SHARE_PRECISION)?
+        .safe_div(normalized_shares)
+}
+
+/// Token amount the change in value per share represents for all shares of the insurance fund.
+/// Returns (gain, loss)
+pub fn calculate_if_value_change(
+    last_value_per_share: u128,
+    value_per_share: u128,
+    total_if_shares: u128,
+    shares_base: u128,
+) -> DriftResult<(u64, u64)> {
+    if last_value_per_share == 0 || value_per_share == 0 {
+        return Ok((0, 0));
+    }
+
+    let normalized_shares = total_if_shares.safe_mul(
+        10_u128
+            .checked_pow(shares_base.cast()?)
+            .ok_or_else(math_error!())?,
+    )?;
+
+    let value_delta = value_per_share.abs_diff(last_value_per_share);
+    let amount = value_delta
+        .safe_mul(normalized_shares)?
+        .safe_div(IF_VALUE_PER_SHARE_PRECISION)?
+        .cast::<u64>()?;
+
+    if value_per_share > last_value_per_share {
+        Ok((amount, 0))
+    } else {
+        Ok((0, amount))
+    }
+}
+
+/// Simple annualized yield of a revenue settlement relative to the vault amount before it
+pub fn calculate_revenue_apy(
+    revenue: u64,
+    insurance_fund_vault_balance: u64,
+    time_since_last_settle: i64,
+) -> DriftResult<u64> {
+    if insurance_fund_vault_balance == 0 || time_since_last_settle <= 0 {
+        return Ok(0);
+    }
+
+    revenue
+        .cast::<u128>()?
+        .safe_mul(PERCENTAGE_PRECISION)?
+        .safe_mul(ONE_YEAR)?
+        .safe_div(
+            insurance_fund_vault_balance
+                .cast::<u128>()?
+                .safe_mul(time_since_last_settle.cast()?)?,
+        )?
+        .cast::<u64>()
+}
+
 pub fn calculate_rebase_info(
     total_if_shares: u128,
     insurance_fund_vault_balance: u64,
@@ -187,3 +187,19 @@ pub fn calculate_lp_shares_to_burn_for_risk_reduction(
 
     Ok((standardized_lp_shares_to_burn, current_base_asset_amount))
 }
+
+/// The amm can absorb an lp withdraw before the lp cooldown if the slice of lp owned inventory
+/// leaving with the shares is less than a step size
+pub fn can_amm_absorb_lp_withdraw(amm: &AMM, shares_to_burn: u64) -> DriftResult<bool> {
+    if amm.user_lp_shares == 0 {
+        return Ok(true);
+    }
+
+    let lp_inventory_withdrawn = amm
+        .base_asset_amount_with_unsettled_lp
+        .unsigned_abs()
+        .safe_mul(shares_to_burn.cast()?)?
+        .safe_div(amm.user_lp_shares)?;
+
+    Ok(lp_inventory_withdrawn < amm.order_step_size.cast()?)
+}
@@ -442,3 +442,39 @@ mod calculate_lp_shares_to_burn_for_risk_reduction {
         assert_eq!(position.lp_shares, 17704500000);
     }
 }
+
+mod can_amm_absorb_lp_withdraw {
+    use crate::math::constants::{AMM_RESERVE_PRECISION, BASE_PRECISION_U64};
+    use crate::math::lp::*;
+    use crate::state::perp_market::AMM;
+
+    #[test]
+    fn test() {
+        let amm = AMM {
+            user_lp_shares: 100 * AMM_RESERVE_PRECISION,
+            order_step_size: BASE_PRECISION_U64 / 10,
+            ..AMM::default()
+        };
+
+        // no lp owned inventory
+        assert!(can_amm_absorb_lp_withdraw(&amm, 50 * BASE_PRECISION_U64).unwrap());
+
+        let amm = AMM {
+            base_asset_amount_with_unsettled_lp: -(AMM_RESERVE_PRECISION as i128),
+            ..amm
+        };
+
+        // 1/100 of the 1 base lp inventory leaves, less than step size
+        assert!(can_amm_absorb_lp_withdraw(&amm, BASE_PRECISION_U64).unwrap());
+
+        // 1/10 of the 1 base lp inventory leaves, equal to step size
+        assert!(!can_amm_absorb_lp_withdraw(&amm, 10 * BASE_PRECISION_U64).unwrap());
+
+        // no lp shares left
+        let amm = AMM {
+            user_lp_shares: 0,
+            ..amm
+        };
+        assert!(can_amm_absorb_lp_withdraw(&amm, 10 * BASE_PRECISION_U64).unwrap());
+    }
+}
@@ -93,6 +93,25 @@ pub fn calculate_size_discount_asset_weight(
     Ok(min_asset_weight)
 }
 
+/// initial margin widens the oracle price by a multiple of its confidence interval
+pub fn calculate_margin_confidence_adjustment(
+    confidence: u64,
+    margin_confidence_multiplier: u8,
+    margin_requirement_type: MarginRequirementType,
+) -> DriftResult<i64> {
+    if margin_requirement_type != MarginRequirementType::Initial
+        || margin_confidence_multiplier == 0
+    {
+        return Ok(0);
+    }
+
+    // margin_confidence_multiplier precision: 10
+    confidence
+        .safe_mul(margin_confidence_multiplier.cast()?)?
+        .safe_div(10)?
+        .cast()
+}
+
 pub fn calculate_perp_position_value_and_pnl(
     market_position: &PerpPosition,
     market: &PerpMarket,
@@ -102,12 +121,33 @@ pub fn calculate_perp_position_value_and_pnl(
     user_custom_margin_ratio: u32,
     track_open_order_fraction: bool,
 ) -> DriftResult<(u128, i128, u128, u128, u128)> {
+    let confidence_adjustment = calculate_margin_confidence_adjustment(
+        oracle_price_data.confidence,
+        market.margin_confidence_multiplier,
+        margin_requirement_type,
+    )?;
+
+    // value the position at the side of the confidence band that hurts it
     let valuation_price = if market.status == MarketStatus::Settlement {
         market.expiry_price
+    } else if market_position.base_asset_amount > 0 {
+        oracle_price_data
+            .price
+            .safe_sub(confidence_adjustment)?
+            .max(0)
+    } else if market_position.base_asset_amount < 0 {
+        oracle_price_data.price.safe_add(confidence_adjustment)?
     } else {
         oracle_price_data.price
     };
 
+    // prediction market short liabilities shrink as price rises, so only widen linear markets
+    let liability_price = if market.is_prediction_market() {
+        oracle_price_data.price
+    } else {
+        oracle_price_data.price.safe_add(confidence_adjustment)?
+    };
+
     // the funding must be calculated before calculated the unrealized pnl w simulated lp position
     let unrealized_funding = calculate_funding_payment(
         if market_position.base_asset_amount > 0 {
@@ -125,8 +165,8 @@ pub fn calculate_perp_position_value_and_pnl(
 
     let total_unrealized_pnl = unrealized_pnl.safe_add(unrealized_funding.cast()?)?;
 
-    let (worst_case_base_asset_amount, worse_case_liability_value) = market_position
-        .worst_case_liability_value(oracle_price_data.price, market.contract_type)?;
+    let (worst_case_base_asset_amount, worse_case_liability_value) =
+        market_position.worst_case_liability_value(liability_price, market.contract_type)?;
 
     // for calculating the perps value, since it's a liability, use the large of twap and quote oracle price
     let worse_case_liability_value = worse_case_liability_value
@@ -230,6 +270,39 @@ pub fn calculate_user_safest_position_tiers(
     Ok((safest_tier_spot_liablity, safest_tier_perp_liablity))
 }
 
+/// Returns the asset group all of the user's spot positions are in, or 0 if the positions span
+/// more than one group or the user has perp positions
+pub fn get_user_asset_group(user: &User, spot_market_map: &SpotMarketMap) -> DriftResult<u8> {
+    if user
+        .perp_positions
+        .iter()
+        .any(|perp_position| !perp_position.is_available())
+    {
+        return Ok(0);
+    }
+
+    let mut asset_group = 0_u8;
+    for spot_position in user.spot_positions.iter() {
+        if spot_position.is_available() {
+            continue;
+        }
+
+        let spot_market_asset_group = spot_market_map
+            .get_ref(&spot_position.market_index)?
+            .asset_group;
+
+        if spot_market_asset_group == 0
+            || (asset_group != 0 && spot_market_asset_group != asset_group)
+        {
+            return Ok(0);
+        }
+
+        asset_group = spot_market_asset_group;
+    }
+
+    Ok(asset_group)
+}
+
 pub fn calculate_margin_requirement_and_total_collateral_and_liability_info(
     user: &User,
     perp_market_map: &PerpMarketMap,
@@ -245,6 +318,9 @@ pub fn calculate_margin_requirement_and_total_collateral_and_liability_info(
         0_u32
     };
 
+    // e-mode: boosted weights when all spot positions are in one correlated asset group
+    let user_asset_group = get_user_asset_group(user, spot_market_map)?;
+
     for spot_position in user.spot_positions.iter() {
         validation::position::validate_spot_position(spot_position)?;
 
@@ -252,12 +328,18 @@ pub fn calculate_margin_requirement_and_total_collateral_and_liability_info(
             continue;
         }
 
+        // term deposits can't be withdrawn or liquidated before the loan settles, so they get no asset weight
+        if spot_position.is_term() && spot_position.balance_type == SpotBalanceType::Deposit {
+            continue;
+        }
+
         let spot_market = spot_market_map.get_ref(&spot_position.market_index)?;
         let (oracle_price_data, oracle_validity) = oracle_map.get_price_data_and_validity(
             MarketType::Spot,
             spot_market.market_index,
             &spot_market.oracle,
             spot_market.historical_oracle_data.last_oracle_price_twap,
+            &spot_market.oracle_validity_guard_rails,
             spot_market.get_max_confidence_interval_multiplier()?,
         )?;
 
@@ -272,7 +354,12 @@ pub fn calculate_margin_requirement_and_total_collateral_and_liability_info(
                 .historical_oracle_data
                 .last_oracle_price_twap_5min,
             calculation.context.strict,
-        );
+        )
+        .with_confidence_adjustment(calculate_margin_confidence_adjustment(
+            oracle_price_data.confidence,
+            spot_market.margin_confidence_multiplier,
+            context.margin_type,
+        )?);
         strict_oracle_price.validate()?;
 
         if spot_market.market_index == 0 {
@@ -331,24 +418,33 @@ pub fn calculate_margin_requirement_and_total_collateral_and_liability_info(
                 &strict_oracle_price,
             )?;
 
+            let worst_case_fill_simulation = spot_position.get_worst_case_fill_simulation(
+                &spot_market,
+                &strict_oracle_price,
+                Some(signed_token_amount),
+                context.margin_type,
+            )?;
+
             let OrderFillSimulation {
                 token_amount: worst_case_token_amount,
                 orders_value: worst_case_orders_value,
                 token_value: worst_case_token_value,
                 weighted_token_value: worst_case_weighted_token_value,
                 ..
-            } = spot_position
-                .get_worst_case_fill_simulation(
+            } = if user_asset_group != 0 {
+                worst_case_fill_simulation.apply_asset_group_weights(
                     &spot_market,
-                    &strict_oracle_price,
-                    Some(signed_token_amount),
+                    strict_oracle_price.current,
                     context.margin_type,
+                    user_custom_margin_ratio,
                 )?
-                .apply_user_custom_margin_ratio(
+            } else {
+                worst_case_fill_simulation.apply_user_custom_margin_ratio(
                     &spot_market,
                     strict_oracle_price.current,
                     user_custom_margin_ratio,
-                )?;
+                )?
+            };
 
             if worst_case_token_amount == 0 {
                 validate!(
@@ -452,6 +548,7 @@ pub fn calculate_margin_requirement_and_total_collateral_and_liability_info(
                 quote_spot_market
                     .historical_oracle_data
                     .last_oracle_price_twap,
+                &quote_spot_market.oracle_validity_guard_rails,
                 quote_spot_market.get_max_confidence_interval_multiplier()?,
             )?;
 
@@ -466,7 +563,12 @@ pub fn calculate_margin_requirement_and_total_collateral_and_liability_info(
                 .historical_oracle_data
                 .last_oracle_price_twap_5min,
             calculation.context.strict,
-        );
+        )
+        .with_confidence_adjustment(calculate_margin_confidence_adjustment(
+            quote_oracle_price_data.confidence,
+            quote_spot_market.margin_confidence_multiplier,
+            context.margin_type,
+        )?);
         drop(quote_spot_market);
 
         let (oracle_price_data, oracle_validity) = oracle_map.get_price_data_and_validity(
@@ -474,6 +576,7 @@ pub fn calculate_margin_requirement_and_total_collateral_and_liability_info(
             market.market_index,
             &market.amm.oracle,
             market.amm.historical_oracle_data.last_oracle_price_twap,
+            &market.oracle_validity_guard_rails,
             market.get_max_confidence_interval_multiplier()?,
         )?;
 
@@ -500,6 +603,8 @@ pub fn calculate_margin_requirement_and_total_collateral_and_liability_info(
             oracle_price_data.price,
         )?;
 
+        calculation.update_fuel_lp_bonus(market, market_position, oracle_price_data.price)?;
+
         calculation.add_margin_requirement(
             perp_margin_requirement,
             worst_case_liability_value,
@@ -845,6 +950,7 @@ pub fn calculate_user_equity(
             spot_market.market_index,
             &spot_market.oracle,
             spot_market.historical_oracle_data.last_oracle_price_twap,
+            &spot_market.oracle_validity_guard_rails,
             spot_market.get_max_confidence_interval_multiplier()?,
         )?;
         all_oracles_valid &=
@@ -874,6 +980,7 @@ pub fn calculate_user_equity(
                     quote_spot_market
                         .historical_oracle_data
                         .last_oracle_price_twap,
+                    &quote_spot_market.oracle_validity_guard_rails,
                     quote_spot_market.get_max_confidence_interval_multiplier()?,
                 )?;
 
@@ -888,6 +995,7 @@ pub fn calculate_user_equity(
             market.market_index,
             &market.amm.oracle,
             market.amm.historical_oracle_data.last_oracle_price_twap,
+            &market.oracle_validity_guard_rails,
             market.get_max_confidence_interval_multiplier()?,
         )?;
 
@@ -5,8 +5,8 @@ mod test {
     use crate::amm::calculate_swap_output;
     use crate::controller::amm::SwapDirection;
     use crate::math::constants::{
-        AMM_RESERVE_PRECISION, PRICE_PRECISION, PRICE_PRECISION_U64, QUOTE_PRECISION,
-        QUOTE_PRECISION_I64, SPOT_IMF_PRECISION,
+        AMM_RESERVE_PRECISION, BASE_PRECISION_I64, PRICE_PRECISION, PRICE_PRECISION_U64,
+        QUOTE_PRECISION, QUOTE_PRECISION_I128, QUOTE_PRECISION_I64, SPOT_IMF_PRECISION,
     };
     use crate::math::margin::{calculate_perp_position_value_and_pnl, MarginRequirementType};
     use crate::math::position::calculate_base_asset_value_and_pnl_with_oracle_price;
@@ -536,6 +536,94 @@ mod test {
         // larger margin req in more unbalanced market
         assert!(pmr2 > pmr)
     }
+
+    #[test]
+    fn confidence_adjusted_perp_position_value() {
+        let market = PerpMarket {
+            market_index: 0,
+            amm: AMM::default_test(),
+            margin_ratio_initial: 1000,
+            margin_ratio_maintenance: 500,
+            unrealized_pnl_initial_asset_weight: 10000,
+            unrealized_pnl_maintenance_asset_weight: 10000,
+            margin_confidence_multiplier: 20, // 2x
+            ..PerpMarket::default()
+        };
+
+        let oracle_price_data = OraclePriceData {
+            price: 100 * PRICE_PRECISION_I64,
+            confidence: PRICE_PRECISION_U64,
+            delay: 2,
+            has_sufficient_number_of_data_points: true,
+        };
+
+        let strict_quote_price = StrictOraclePrice::test(QUOTE_PRECISION_I64);
+
+        let long_position = PerpPosition {
+            base_asset_amount: BASE_PRECISION_I64,
+            quote_asset_amount: -100 * QUOTE_PRECISION_I64,
+            ..PerpPosition::default()
+        };
+
+        // long marked at 98, liability at 102
+        let (margin_requirement, pnl, liability_value, _, _) =
+            calculate_perp_position_value_and_pnl(
+                &long_position,
+                &market,
+                &oracle_price_data,
+                &strict_quote_price,
+                MarginRequirementType::Initial,
+                0,
+                false,
+            )
+            .unwrap();
+        assert_eq!(pnl, -2 * QUOTE_PRECISION_I128);
+        assert_eq!(liability_value, 102 * QUOTE_PRECISION);
+        assert_eq!(margin_requirement, 10_200_000);
+
+        // maintenance ignores the confidence band
+        let (margin_requirement, pnl, liability_value, _, _) =
+            calculate_perp_position_value_and_pnl(
+                &long_position,
+                &market,
+                &oracle_price_data,
+                &strict_quote_price,
+                MarginRequirementType::Maintenance,
+                0,
+                false,
+            )
+            .unwrap();
+        assert_eq!(pnl, 0);
+        assert_eq!(liability_value, 100 * QUOTE_PRECISION);
+        assert_eq!(margin_requirement, 5 * QUOTE_PRECISION);
+
+        let short_position = PerpPosition {
+            base_asset_amount: -BASE_PRECISION_I64,
+            quote_asset_amount: 100 * QUOTE_PRECISION_I64,
+            ..PerpPosition::default()
+        };
+
+        // short marked at 102
+        let (margin_requirement, pnl, liability_value, _, _) =
+            calculate_perp_position_value_and_pnl(
+                &short_position,
+                &market,
+                &oracle_price_data,
+                &strict_quote_price,
+                MarginRequirementType::Initial,
+                0,
+                false,
+            )
+            .unwrap();
+        assert_eq!(pnl, -2 * QUOTE_PRECISION_I128);
+        assert_eq!(liability_value, 102 * QUOTE_PRECISION);
+        assert_eq!(margin_requirement, 10_200_000);
+
+        let strict_price = StrictOraclePrice::new(100 * PRICE_PRECISION_I64, 0, false)
+            .with_confidence_adjustment(2 * PRICE_PRECISION_I64);
+        assert_eq!(strict_price.min(), 98 * PRICE_PRECISION_I64);
+        assert_eq!(strict_price.max(), 102 * PRICE_PRECISION_I64);
+    }
 }
 
 #[cfg(test)]
@@ -1422,6 +1510,197 @@ mod calculate_margin_requirement_and_total_collateral {
         assert_eq!(total_collateral, 9000000000);
         assert_eq!(margin_requirement, 12100000000);
     }
+
+    #[test]
+    pub fn usdc_deposit_and_term_sol_borrow() {
+        let slot = 0_u64;
+
+        let mut sol_oracle_price = get_pyth_price(100, 6);
+        let sol_oracle_price_key =
+            Pubkey::from_str("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix").unwrap();
+        let pyth_program = crate::ids::pyth_program::id();
+        create_account_info!(
+            sol_oracle_price,
+            &sol_oracle_price_key,
+            &pyth_program,
+            oracle_account_info
+        );
+        let mut oracle_map = OracleMap::load_one(&oracle_account_info, slot, None).unwrap();
+
+        let market_map = PerpMarketMap::empty();
+
+        let mut usdc_spot_market = SpotMarket {
+            market_index: 0,
+            oracle_source: OracleSource::QuoteAsset,
+            cumulative_deposit_interest: SPOT_CUMULATIVE_INTEREST_PRECISION,
+            decimals: 6,
+            initial_asset_weight: SPOT_WEIGHT_PRECISION,
+            maintenance_asset_weight: SPOT_WEIGHT_PRECISION,
+            deposit_balance: 10000 * SPOT_BALANCE_PRECISION,
+            liquidator_fee: 0,
+            historical_oracle_data: HistoricalOracleData::default_quote_oracle(),
+            ..SpotMarket::default()
+        };
+        create_anchor_account_info!(usdc_spot_market, SpotMarket, usdc_spot_market_account_info);
+        let mut sol_spot_market = SpotMarket {
+            market_index: 1,
+            oracle_source: OracleSource::Pyth,
+            oracle: sol_oracle_price_key,
+            cumulative_deposit_interest: SPOT_CUMULATIVE_INTEREST_PRECISION,
+            // floating interest doesn't apply to term positions
+            cumulative_borrow_interest: 2 * SPOT_CUMULATIVE_INTEREST_PRECISION,
+            decimals: 9,
+            initial_asset_weight: 8 * SPOT_WEIGHT_PRECISION / 10,
+            maintenance_asset_weight: 9 * SPOT_WEIGHT_PRECISION / 10,
+            initial_liability_weight: 12 * SPOT_WEIGHT_PRECISION / 10,
+            maintenance_liability_weight: 11 * SPOT_WEIGHT_PRECISION / 10,
+            liquidator_fee: LIQUIDATION_FEE_PRECISION / 1000,
+            ..SpotMarket::default()
+        };
+        create_anchor_account_info!(sol_spot_market, SpotMarket, sol_spot_market_account_info);
+        let spot_market_account_infos = Vec::from([
+            &usdc_spot_market_account_info,
+            &sol_spot_market_account_info,
+        ]);
+        let spot_market_map =
+            SpotMarketMap::load_multiple(spot_market_account_infos, true).unwrap();
+
+        let mut spot_positions = [SpotPosition::default(); 8];
+        spot_positions[0] = SpotPosition {
+            market_index: 0,
+            balance_type: SpotBalanceType::Deposit,
+            scaled_balance: 10000 * SPOT_BALANCE_PRECISION_U64,
+            ..SpotPosition::default()
+        };
+        spot_positions[1] = SpotPosition {
+            market_index: 1,
+            balance_type: SpotBalanceType::Borrow,
+            scaled_balance: 10 * 10_u64.pow(9),
+            maturity_ts: 1,
+            ..SpotPosition::default()
+        };
+        let user = User {
+            orders: [Order::default(); 32],
+            perp_positions: [PerpPosition::default(); 8],
+            spot_positions,
+            ..User::default()
+        };
+
+        let MarginCalculation {
+            margin_requirement,
+            total_collateral,
+            ..
+        } = calculate_margin_requirement_and_total_collateral_and_liability_info(
+            &user,
+            &market_map,
+            &spot_market_map,
+            &mut oracle_map,
+            MarginContext::standard(MarginRequirementType::Initial),
+        )
+        .unwrap();
+
+        assert_eq!(margin_requirement, 1200000000);
+        assert_eq!(total_collateral, 10000000000);
+    }
+
+    #[test]
+    pub fn term_usdc_deposit_has_no_asset_weight() {
+        let slot = 0_u64;
+
+        let mut sol_oracle_price = get_pyth_price(100, 6);
+        let sol_oracle_price_key =
+            Pubkey::from_str("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix").unwrap();
+        let pyth_program = crate::ids::pyth_program::id();
+        create_account_info!(
+            sol_oracle_price,
+            &sol_oracle_price_key,
+            &pyth_program,
+            oracle_account_info
+        );
+        let mut oracle_map = OracleMap::load_one(&oracle_account_info, slot, None).unwrap();
+
+        let market_map = PerpMarketMap::empty();
+
+        let mut usdc_spot_market = SpotMarket {
+            market_index: 0,
+            oracle_source: OracleSource::QuoteAsset,
+            cumulative_deposit_interest: SPOT_CUMULATIVE_INTEREST_PRECISION,
+            decimals: 6,
+            initial_asset_weight: SPOT_WEIGHT_PRECISION,
+            maintenance_asset_weight: SPOT_WEIGHT_PRECISION,
+            deposit_balance: 10000 * SPOT_BALANCE_PRECISION,
+            liquidator_fee: 0,
+            historical_oracle_data: HistoricalOracleData::default_quote_oracle(),
+            ..SpotMarket::default()
+        };
+        create_anchor_account_info!(usdc_spot_market, SpotMarket, usdc_spot_market_account_info);
+        let mut sol_spot_market = SpotMarket {
+            market_index: 1,
+            oracle_source: OracleSource::Pyth,
+            oracle: sol_oracle_price_key,
+            cumulative_deposit_interest: SPOT_CUMULATIVE_INTEREST_PRECISION,
+            cumulative_borrow_interest: SPOT_CUMULATIVE_INTEREST_PRECISION,
+            decimals: 9,
+            initial_asset_weight: 8 * SPOT_WEIGHT_PRECISION / 10,
+            maintenance_asset_weight: 9 * SPOT_WEIGHT_PRECISION / 10,
+            initial_liability_weight: 12 * SPOT_WEIGHT_PRECISION / 10,
+            maintenance_liability_weight: 11 * SPOT_WEIGHT_PRECISION / 10,
+            liquidator_fee: LIQUIDATION_FEE_PRECISION / 1000,
+            ..SpotMarket::default()
+        };
+        create_anchor_account_info!(sol_spot_market, SpotMarket, sol_spot_market_account_info);
+        let spot_market_account_infos = Vec::from([
+            &usdc_spot_market_account_info,
+            &sol_spot_market_account_info,
+        ]);
+        let spot_market_map =
+            SpotMarketMap::load_multiple(spot_market_account_infos, true).unwrap();
+
+        let mut spot_positions = [SpotPosition::default(); 8];
+        spot_positions[0] = SpotPosition {
+            market_index: 0,
+            balance_type: SpotBalanceType::Deposit,
+            scaled_balance: 1000 * SPOT_BALANCE_PRECISION_U64,
+            ..SpotPosition::default()
+        };
+        spot_positions[1] = SpotPosition {
+            market_index: 0,
+            balance_type: SpotBalanceType::Deposit,
+            scaled_balance: 10000 * 10_u64.pow(6),
+            maturity_ts: 1,
+            ..SpotPosition::default()
+        };
+        spot_positions[2] = SpotPosition {
+            market_index: 1,
+            balance_type: SpotBalanceType::Borrow,
+            scaled_balance: 10 * SPOT_BALANCE_PRECISION_U64,
+            ..SpotPosition::default()
+        };
+        let user = User {
+            orders: [Order::default(); 32],
+            perp_positions: [PerpPosition::default(); 8],
+            spot_positions,
+            ..User::default()
+        };
+
+        for margin_type in [
+            MarginRequirementType::Initial,
+            MarginRequirementType::Maintenance,
+        ] {
+            let MarginCalculation {
+                total_collateral, ..
+            } = calculate_margin_requirement_and_total_collateral_and_liability_info(
+                &user,
+                &market_map,
+                &spot_market_map,
+                &mut oracle_map,
+                MarginContext::standard(margin_type),
+            )
+            .unwrap();
+
+            assert_eq!(total_collateral, 1000000000);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -6,14 +6,17 @@ use solana_program::msg;
 use crate::error::{DriftResult, ErrorCode};
 use crate::math::amm;
 use crate::math::casting::Cast;
-use crate::math::constants::BID_ASK_SPREAD_PRECISION;
+use crate::math::constants::{
+    BID_ASK_SPREAD_PRECISION, MIN_ORACLE_AGGREGATION_CONFIDENCE_PCT, PRICE_PRECISION,
+};
 use crate::math::safe_math::SafeMath;
 
 use crate::state::oracle::OraclePriceData;
 use crate::state::paused_operations::PerpOperation;
 use crate::state::perp_market::PerpMarket;
-use crate::state::state::{OracleGuardRails, ValidityGuardRails};
+use crate::state::state::{OracleGuardRails, ValidityGuardRails, ValidityGuardRailsOverride};
 use crate::state::user::MarketType;
+use crate::validate;
 use std::fmt;
 
 #[cfg(test)]
@@ -188,6 +191,7 @@ pub fn get_oracle_status(
         market.amm.historical_oracle_data.last_oracle_price_twap,
         oracle_price_data,
         &guard_rails.validity,
+        &market.oracle_validity_guard_rails,
         market.get_max_confidence_interval_multiplier()?,
         false,
     )?;
@@ -212,9 +216,12 @@ pub fn oracle_validity(
     last_oracle_twap: i64,
     oracle_price_data: &OraclePriceData,
     valid_oracle_guard_rails: &ValidityGuardRails,
+    guard_rails_override: &ValidityGuardRailsOverride,
     max_confidence_interval_multiplier: u64,
     log_validity: bool,
 ) -> DriftResult<OracleValidity> {
+    let valid_oracle_guard_rails = &guard_rails_override.apply(valid_oracle_guard_rails);
+
     let OraclePriceData {
         price: oracle_price,
         confidence: oracle_conf,
@@ -306,3 +313,93 @@ pub fn oracle_validity(
 
     Ok(oracle_validity)
 }
+
+fn is_oracle_source_valid_for_aggregation(
+    oracle_price_data: &OraclePriceData,
+    valid_oracle_guard_rails: &ValidityGuardRails,
+) -> DriftResult<bool> {
+    if oracle_price_data.price <= 0
+        || !oracle_price_data.has_sufficient_number_of_data_points
+        || oracle_price_data.delay > valid_oracle_guard_rails.slots_before_stale_for_amm
+    {
+        return Ok(false);
+    }
+
+    let conf_pct_of_price = max(1, oracle_price_data.confidence)
+        .safe_mul(BID_ASK_SPREAD_PRECISION)?
+        .safe_div(oracle_price_data.price.cast()?)?;
+
+    Ok(conf_pct_of_price <= valid_oracle_guard_rails.confidence_interval_max_size)
+}
+
+/// Confidence weighted median of the valid sources (weight = 1 / confidence).
+/// Confidence is floored at MIN_ORACLE_AGGREGATION_CONFIDENCE_PCT of price so a single source
+/// reporting near-zero confidence can't outweigh the others.
+/// Sources that are stale, uncertain or unreadable (None) are skipped. Errors if fewer than
+/// min_valid_sources sources are valid.
+/// Returns the aggregated price data and a bitmask of the contributing sources
+pub fn calculate_aggregated_oracle_price(
+    source_price_data: &[Option<OraclePriceData>],
+    min_valid_sources: u8,
+    valid_oracle_guard_rails: &ValidityGuardRails,
+) -> DriftResult<(OraclePriceData, u8)> {
+    let mut contributors: Vec<(usize, OraclePriceData)> =
+        Vec::with_capacity(source_price_data.len());
+    for (i, oracle_price_data) in source_price_data.iter().enumerate() {
+        if let Some(oracle_price_data) = oracle_price_data {
+            if is_oracle_source_valid_for_aggregation(oracle_price_data, valid_oracle_guard_rails)?
+            {
+                contributors.push((i, *oracle_price_data));
+            }
+        }
+    }
+
+    validate!(
+        !contributors.is_empty() && contributors.len() >= min_valid_sources.cast()?,
+        ErrorCode::OracleAggregatorQuorumNotMet,
+        "{} valid sources for oracle aggregation, min valid sources = {}",
+        contributors.len(),
+        min_valid_sources
+    )?;
+
+    contributors.sort_by_key(|(_, oracle_price_data)| oracle_price_data.price);
+
+    let weights = contributors
+        .iter()
+        .map(|(_, oracle_price_data)| {
+            let min_confidence = oracle_price_data
+                .price
+                .cast::<u64>()?
+                .safe_mul(MIN_ORACLE_AGGREGATION_CONFIDENCE_PCT)?
+                .safe_div(BID_ASK_SPREAD_PRECISION)?;
+
+            PRICE_PRECISION.safe_mul(PRICE_PRECISION)?.safe_div(
+                oracle_price_data
+                    .confidence
+                    .max(min_confidence)
+                    .max(1)
+                    .cast()?,
+            )
+        })
+        .collect::<DriftResult<Vec<u128>>>()?;
+
+    let total_weight = weights
+        .iter()
+        .try_fold(0_u128, |total, weight| total.safe_add(*weight))?;
+
+    let mut cumulative_weight = 0_u128;
+    let mut median_index = contributors.len() - 1;
+    for (i, weight) in weights.iter().enumerate() {
+        cumulative_weight = cumulative_weight.safe_add(*weight)?;
+        if cumulative_weight.safe_mul(2)? >= total_weight {
+            median_index = i;
+            break;
+        }
+    }
+
+    let contributing_sources = contributors
+        .iter()
+        .fold(0_u8, |mask, (i, _)| mask | (1_u8 << i));
+
+    Ok((contributors[median_index].1, contributing_sources))
+}
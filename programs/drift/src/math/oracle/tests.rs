@@ -1,11 +1,15 @@
 use crate::math::amm::update_oracle_price_twap;
 use crate::math::constants::{
-    AMM_RESERVE_PRECISION, PEG_PRECISION, PRICE_PRECISION, PRICE_PRECISION_U64,
+    AMM_RESERVE_PRECISION, PEG_PRECISION, PRICE_PRECISION, PRICE_PRECISION_I64, PRICE_PRECISION_U64,
 };
 use crate::math::oracle::*;
-use crate::state::oracle::HistoricalOracleData;
+use crate::state::oracle::{HistoricalOracleData, OraclePriceData};
 use crate::state::perp_market::{ContractTier, PerpMarket, AMM};
-use crate::state::state::{OracleGuardRails, PriceDivergenceGuardRails, State, ValidityGuardRails};
+use crate::state::state::{
+    OracleGuardRails, PriceDivergenceGuardRails, State, ValidityGuardRails,
+    ValidityGuardRailsOverride,
+};
+use crate::state::user::MarketType;
 
 #[test]
 fn calculate_oracle_valid() {
@@ -131,3 +135,130 @@ fn calculate_oracle_valid() {
     assert!(oracle_status.mark_too_divergent);
     assert!(oracle_status.oracle_validity == OracleValidity::TooUncertain);
 }
+
+#[test]
+fn aggregated_oracle_price() {
+    let validity_guard_rails = OracleGuardRails::default().validity;
+
+    let oracle_price_data = |price: i64, confidence: u64, delay: i64| {
+        Some(OraclePriceData {
+            price: price * PRICE_PRECISION_I64,
+            confidence,
+            delay,
+            has_sufficient_number_of_data_points: true,
+        })
+    };
+
+    // equal confidence, bad print is ignored
+    let sources = [
+        oracle_price_data(100, PRICE_PRECISION_U64 / 100, 0),
+        oracle_price_data(150, PRICE_PRECISION_U64 / 100, 0),
+        oracle_price_data(101, PRICE_PRECISION_U64 / 100, 0),
+    ];
+    let (price_data, contributing_sources) =
+        calculate_aggregated_oracle_price(&sources, 1, &validity_guard_rails).unwrap();
+    assert_eq!(price_data.price, 101 * PRICE_PRECISION_I64);
+    assert_eq!(contributing_sources, 0b111);
+
+    // tighter confidence gets more weight
+    let sources = [
+        oracle_price_data(100, PRICE_PRECISION_U64 / 100, 0),
+        oracle_price_data(101, PRICE_PRECISION_U64 / 2, 0),
+        oracle_price_data(102, PRICE_PRECISION_U64 / 2, 0),
+    ];
+    let (price_data, contributing_sources) =
+        calculate_aggregated_oracle_price(&sources, 1, &validity_guard_rails).unwrap();
+    assert_eq!(price_data.price, 100 * PRICE_PRECISION_I64);
+    assert_eq!(price_data.confidence, PRICE_PRECISION_U64 / 100);
+    assert_eq!(contributing_sources, 0b111);
+
+    // confidence is floored at 10 bps of price, zero confidence doesn't outweigh the others
+    let sources = [
+        oracle_price_data(100, 0, 0),
+        oracle_price_data(101, PRICE_PRECISION_U64 / 10, 0),
+        oracle_price_data(102, PRICE_PRECISION_U64 / 10, 0),
+    ];
+    let (price_data, contributing_sources) =
+        calculate_aggregated_oracle_price(&sources, 1, &validity_guard_rails).unwrap();
+    assert_eq!(price_data.price, 101 * PRICE_PRECISION_I64);
+    assert_eq!(contributing_sources, 0b111);
+
+    // stale, too uncertain and unreadable sources are skipped
+    let sources = [
+        oracle_price_data(100, PRICE_PRECISION_U64 / 100, 11),
+        oracle_price_data(101, 3 * PRICE_PRECISION_U64, 0),
+        None,
+        oracle_price_data(102, PRICE_PRECISION_U64 / 100, 3),
+    ];
+    let (price_data, contributing_sources) =
+        calculate_aggregated_oracle_price(&sources, 1, &validity_guard_rails).unwrap();
+    assert_eq!(price_data.price, 102 * PRICE_PRECISION_I64);
+    assert_eq!(price_data.delay, 3);
+    assert_eq!(contributing_sources, 0b1000);
+
+    // quorum not met
+    assert!(calculate_aggregated_oracle_price(&sources, 2, &validity_guard_rails).is_err());
+
+    // no valid source, no fallback
+    let sources = [
+        None,
+        oracle_price_data(100, PRICE_PRECISION_U64 / 100, 200),
+        oracle_price_data(101, PRICE_PRECISION_U64 / 100, 300),
+    ];
+    assert!(calculate_aggregated_oracle_price(&sources, 1, &validity_guard_rails).is_err());
+
+    assert!(calculate_aggregated_oracle_price(&[None, None], 1, &validity_guard_rails).is_err());
+}
+
+#[test]
+fn oracle_validity_with_market_override() {
+    let guard_rails = OracleGuardRails::default().validity;
+    let oracle_price_data = OraclePriceData {
+        price: 100 * PRICE_PRECISION_I64,
+        confidence: 3 * PRICE_PRECISION_U64, // 3%
+        delay: 50,
+        has_sufficient_number_of_data_points: true,
+    };
+    let twap = 100 * PRICE_PRECISION_I64;
+
+    let validity = |guard_rails_override: &ValidityGuardRailsOverride| {
+        oracle_validity(
+            MarketType::Perp,
+            0,
+            twap,
+            &oracle_price_data,
+            &guard_rails,
+            guard_rails_override,
+            1,
+            false,
+        )
+        .unwrap()
+    };
+
+    // global rails: 2% max confidence
+    assert_eq!(
+        validity(&ValidityGuardRailsOverride::default()),
+        OracleValidity::TooUncertain
+    );
+
+    // long tail market with a slow, wide feed
+    let mut guard_rails_override = ValidityGuardRailsOverride {
+        confidence_interval_max_size: 50_000, // 5%
+        ..ValidityGuardRailsOverride::default()
+    };
+    assert_eq!(validity(&guard_rails_override), OracleValidity::StaleForAMM);
+
+    guard_rails_override.slots_before_stale_for_amm = 60;
+    assert_eq!(validity(&guard_rails_override), OracleValidity::Valid);
+
+    // major with a fast pull oracle
+    guard_rails_override.slots_before_stale_for_margin = 25;
+    assert_eq!(
+        validity(&guard_rails_override),
+        OracleValidity::StaleForMargin
+    );
+
+    let applied = guard_rails_override.apply(&guard_rails);
+    assert_eq!(applied.slots_before_stale_for_margin, 25);
+    assert_eq!(applied.too_volatile_ratio, guard_rails.too_volatile_ratio);
+}
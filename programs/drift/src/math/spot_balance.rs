@@ -1,6 +1,8 @@
 use crate::error::{DriftResult, ErrorCode};
 use crate::math::casting::Cast;
-use crate::math::constants::{ONE_YEAR, SPOT_RATE_PRECISION, SPOT_UTILIZATION_PRECISION};
+use crate::math::constants::{
+    ONE_BPS_DENOMINATOR, ONE_YEAR, SPOT_RATE_PRECISION, SPOT_UTILIZATION_PRECISION,
+};
 use crate::math::safe_math::{SafeDivFloor, SafeMath};
 use crate::state::oracle::{OraclePriceData, StrictOraclePrice};
 use crate::state::spot_market::{SpotBalanceType, SpotMarket};
@@ -82,6 +84,8 @@ pub fn get_interest_token_amount(
 pub struct InterestAccumulated {
     pub borrow_interest: u128,
     pub deposit_interest: u128,
+    /// precision: SPOT_RATE_PRECISION
+    pub borrow_rate: u128,
 }
 
 pub fn calculate_utilization(
@@ -127,6 +131,7 @@ pub fn calculate_accumulated_interest(
         return Ok(InterestAccumulated {
             borrow_interest: 0,
             deposit_interest: 0,
+            borrow_rate: 0,
         });
     }
 
@@ -136,6 +141,7 @@ pub fn calculate_accumulated_interest(
         return Ok(InterestAccumulated {
             borrow_interest: 0,
             deposit_interest: 0,
+            borrow_rate: 0,
         });
     }
 
@@ -170,12 +176,15 @@ pub fn calculate_accumulated_interest(
     Ok(InterestAccumulated {
         borrow_interest,
         deposit_interest,
+        borrow_rate,
     })
 }
 
 #[inline(always)]
 pub fn calculate_borrow_rate(spot_market: &SpotMarket, utilization: u128) -> DriftResult<u128> {
-    let borrow_rate = if utilization > spot_market.optimal_utilization.cast()? {
+    let borrow_rate = if spot_market.has_borrow_rate_kinks() {
+        calculate_kinked_borrow_rate(spot_market, utilization)?
+    } else if utilization > spot_market.optimal_utilization.cast()? {
         let surplus_utilization = utilization.safe_sub(spot_market.optimal_utilization.cast()?)?;
 
         let borrow_rate_slope = spot_market
@@ -208,6 +217,54 @@ pub fn calculate_borrow_rate(spot_market: &SpotMarket, utilization: u128) -> Dri
     Ok(borrow_rate)
 }
 
+fn calculate_kinked_borrow_rate(spot_market: &SpotMarket, utilization: u128) -> DriftResult<u128> {
+    let max_borrow_rate = spot_market.max_borrow_rate.cast::<u128>()?;
+
+    let mut lower_utilization = 0_u128;
+    let mut lower_borrow_rate = 0_u128;
+    let mut upper_utilization = SPOT_UTILIZATION_PRECISION;
+    let mut upper_borrow_rate = max_borrow_rate;
+
+    for kink in spot_market
+        .borrow_rate_kinks
+        .iter()
+        .take_while(|kink| kink.utilization != 0)
+    {
+        let kink_utilization = kink
+            .utilization
+            .cast::<u128>()?
+            .safe_mul(SPOT_UTILIZATION_PRECISION)?
+            .safe_div(ONE_BPS_DENOMINATOR.cast()?)?;
+
+        let kink_borrow_rate = kink
+            .borrow_rate
+            .cast::<u128>()?
+            .safe_mul(max_borrow_rate)?
+            .safe_div(ONE_BPS_DENOMINATOR.cast()?)?;
+
+        if utilization <= kink_utilization {
+            upper_utilization = kink_utilization;
+            upper_borrow_rate = kink_borrow_rate;
+            break;
+        }
+
+        lower_utilization = kink_utilization;
+        lower_borrow_rate = kink_borrow_rate;
+    }
+
+    let borrow_rate_slope = upper_borrow_rate
+        .safe_sub(lower_borrow_rate)?
+        .safe_mul(SPOT_UTILIZATION_PRECISION)?
+        .safe_div(upper_utilization.safe_sub(lower_utilization)?)?;
+
+    lower_borrow_rate.safe_add(
+        utilization
+            .safe_sub(lower_utilization)?
+            .safe_mul(borrow_rate_slope)?
+            .safe_div(SPOT_UTILIZATION_PRECISION)?,
+    )
+}
+
 #[cfg(feature = "drift-rs")]
 pub fn calculate_deposit_rate(
     spot_market: &SpotMarket,
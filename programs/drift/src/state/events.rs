@@ -5,6 +5,7 @@ use crate::controller::position::PositionDirection;
 use crate::error::{DriftResult, ErrorCode::InvalidOrder};
 use crate::math::casting::Cast;
 use crate::math::safe_unwrap::SafeUnwrap;
+use crate::state::insurance_fund_stake::InsuranceFundTranche;
 use crate::state::traits::Size;
 use crate::state::user::{MarketType, Order};
 use anchor_lang::Discriminator;
@@ -50,6 +51,9 @@ pub struct DepositRecord {
     pub total_withdraws_after: u64,
     pub explanation: DepositExplanation,
     pub transfer_user: Option<Pubkey>,
+    /// token-2022 transfer fee withheld from the amount transferred. amount is net of the fee for deposits
+    /// precision: token mint precision
+    pub transfer_fee: u64,
 }
 
 #[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
@@ -79,6 +83,9 @@ pub struct SpotInterestRecord {
     pub optimal_borrow_rate: u32,
     /// precision: PERCENTAGE_PRECISION
     pub max_borrow_rate: u32,
+    /// The borrow rate applied since the last interest update
+    /// precision: SPOT_RATE_PRECISION
+    pub borrow_rate: u32,
 }
 
 #[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
@@ -131,6 +138,11 @@ pub struct FundingRateRecord {
     pub base_asset_amount_with_amm: i128,
     /// precision: BASE_PRECISION
     pub base_asset_amount_with_unsettled_lp: i128,
+    /// time weighted average premium of the amm mid price over the oracle price for the period
+    /// precision: PRICE_PRECISION
+    pub premium_index: i64,
+    /// precision: seconds
+    pub premium_index_sample_weight: u32,
 }
 
 #[event]
@@ -225,6 +237,11 @@ pub struct OrderActionRecord {
 
     /// precision: PRICE_PRECISION
     pub oracle_price: i64,
+
+    /// index into the fee structure's fee tiers
+    pub taker_fee_tier_index: Option<u8>,
+    /// index into the fee structure's fee tiers
+    pub maker_fee_tier_index: Option<u8>,
 }
 
 impl Size for OrderActionRecord {
@@ -251,6 +268,8 @@ pub fn get_order_action_record(
     maker: Option<Pubkey>,
     maker_order: Option<Order>,
     oracle_price: i64,
+    taker_fee_tier_index: Option<u8>,
+    maker_fee_tier_index: Option<u8>,
 ) -> DriftResult<OrderActionRecord> {
     Ok(OrderActionRecord {
         ts,
@@ -298,6 +317,8 @@ pub fn get_order_action_record(
         maker_order_cumulative_quote_asset_amount_filled: maker_order
             .map(|order| order.quote_asset_amount_filled),
         oracle_price,
+        taker_fee_tier_index,
+        maker_fee_tier_index,
     })
 }
 
@@ -360,6 +381,7 @@ pub enum LPAction {
     RemoveLiquidity,
     SettleLiquidity,
     RemoveLiquidityDerisk,
+    RequestRemoveLiquidity,
 }
 
 impl Size for LPRecord {
@@ -505,6 +527,39 @@ pub struct InsuranceFundRecord {
     pub total_if_shares_after: u128,
     /// precision: token mint precision
     pub amount: i64,
+    /// The insurance fund the revenue was settled to or the loss was paid from
+    pub tranche: InsuranceFundTranche,
+}
+
+/// Emitted when revenue is settled to or a loss is paid from an insurance fund tranche with
+/// performance tracking
+#[event]
+#[derive(Default)]
+pub struct InsuranceFundPerformanceRecord {
+    pub ts: i64,
+    /// perp market index for the PerpMarket tranche, spot market index otherwise
+    pub market_index: u16,
+    pub tranche: InsuranceFundTranche,
+    /// precision: token mint precision
+    pub insurance_vault_amount_before: u64,
+    /// revenue from this settlement
+    /// precision: token mint precision
+    pub revenue: u64,
+    /// loss paid out by this payment
+    /// precision: token mint precision
+    pub loss: u64,
+    /// vault gains since the last update not from this settlement or payment
+    /// precision: token mint precision
+    pub revenue_since_last_update: u64,
+    /// vault losses since the last update
+    /// precision: token mint precision
+    pub losses_since_last_update: u64,
+    /// precision: token mint precision
+    pub cumulative_revenue: u128,
+    /// precision: token mint precision
+    pub cumulative_losses: u128,
+    /// precision: PERCENTAGE_PRECISION
+    pub revenue_apy: u64,
 }
 
 #[event]
@@ -536,6 +591,8 @@ pub enum StakeAction {
     Unstake,
     UnstakeTransfer,
     StakeTransfer,
+    Tokenize,
+    Redeem,
 }
 
 #[event]
@@ -570,6 +627,50 @@ pub struct SpotMarketVaultDepositRecord {
     pub amount: u64,
 }
 
+#[event]
+pub struct OracleCircuitBreakerRecord {
+    pub ts: i64,
+    pub market_type: MarketType,
+    pub market_index: u16,
+    /// true if the breaker tripped, false if it reset
+    pub tripped: bool,
+    /// precision: PRICE_PRECISION
+    pub oracle_price: i64,
+    /// 5min oracle twap or the window reference price the oracle was compared against
+    /// precision: PRICE_PRECISION
+    pub reference_price: i64,
+}
+
+#[event]
+#[derive(Default)]
+pub struct TermLoanRecord {
+    pub ts: i64,
+    pub action: TermLoanAction,
+    pub term_loan: Pubkey,
+    pub lender: Pubkey,
+    pub borrower: Pubkey,
+    pub market_index: u16,
+    /// precision: token mint precision
+    pub principal: u64,
+    /// precision: token mint precision
+    pub repay_amount: u64,
+    /// precision: SPOT_RATE_PRECISION
+    pub rate: u32,
+    pub maturity_ts: i64,
+    /// repay amount not covered by the borrower's deposits at settlement
+    /// precision: token mint precision
+    pub shortfall: u64,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
+pub enum TermLoanAction {
+    #[default]
+    Offer,
+    Cancel,
+    Take,
+    Settle,
+}
+
 pub fn emit_stack<T: AnchorSerialize + Discriminator, const N: usize>(event: T) -> DriftResult {
     let mut data_buf = [0u8; N];
     let mut out_buf = [0u8; N];
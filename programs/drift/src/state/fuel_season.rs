@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+
+use crate::error::DriftResult;
+use crate::math::casting::Cast;
+use crate::math::safe_math::SafeMath;
+use crate::state::traits::Size;
+use crate::state::user::UserStats;
+
+#[cfg(test)]
+mod tests;
+
+/// Created when the admin closes a fuel season. Fuel snapshotted for the season can be redeemed
+/// for the reward mint held in the season's vault
+#[account(zero_copy(unsafe))]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct FuelSeason {
+    /// The mint fuel is redeemed for
+    pub reward_mint: Pubkey,
+    /// The unix_timestamp the season started. Zero for the first season
+    pub start_ts: i64,
+    /// The unix_timestamp the season was closed
+    pub end_ts: i64,
+    /// Reward tokens paid per unit of fuel. Redemptions are disabled while zero
+    /// precision: FUEL_REDEMPTION_RATE_PRECISION
+    pub redemption_rate: u64,
+    /// Total fuel redeemed by users
+    pub total_fuel_redeemed: u64,
+    /// Total reward tokens paid to users
+    /// precision: reward mint precision
+    pub total_rewards_redeemed: u64,
+    pub season: u16,
+    pub padding: [u8; 22],
+}
+
+impl Size for FuelSeason {
+    const SIZE: usize = 104;
+}
+
+/// A user's fuel for a fuel season. The user stats u32 counters are swept into it while the season
+/// is open so they don't saturate, and a final time when the user is rolled over to the next season.
+/// Fuel keeps accruing into the counters after the season closes, so the final sweep only moves the
+/// share of the counters earned before the season's end
+#[account(zero_copy(unsafe))]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct UserFuelSeasonRecord {
+    /// The user stats authority
+    pub authority: Pubkey,
+    pub fuel_insurance: u64,
+    pub fuel_deposits: u64,
+    pub fuel_borrows: u64,
+    pub fuel_positions: u64,
+    pub fuel_taker: u64,
+    pub fuel_maker: u64,
+    pub fuel_lp: u64,
+    pub fuel_keeper: u64,
+    /// The unix_timestamp the user stats counters were last swept into the record
+    pub last_sweep_ts: i64,
+    /// Fuel already redeemed for reward tokens
+    pub fuel_redeemed: u64,
+    pub season: u16,
+    pub padding: [u8; 6],
+}
+
+impl Size for UserFuelSeasonRecord {
+    const SIZE: usize = 128;
+}
+
+impl UserFuelSeasonRecord {
+    /// Moves the user stats fuel counters into the record
+    pub fn sweep(&mut self, user_stats: &mut UserStats, now: i64) -> DriftResult {
+        self.sweep_share(user_stats, 1, 1)?;
+        self.last_sweep_ts = now;
+
+        Ok(())
+    }
+
+    /// Moves the share of the user stats fuel counters earned before the season ended into the
+    /// record. The counters are assumed to have accrued evenly since the later of the last sweep
+    /// and the season start; what accrued after the season ended is left for the next season
+    pub fn sweep_closed_season(
+        &mut self,
+        user_stats: &mut UserStats,
+        fuel_season: &FuelSeason,
+        now: i64,
+    ) -> DriftResult {
+        let start_ts = self.last_sweep_ts.max(fuel_season.start_ts);
+
+        if now <= fuel_season.end_ts || start_ts >= now {
+            return self.sweep(user_stats, now);
+        }
+
+        let numerator = fuel_season.end_ts.safe_sub(start_ts)?.max(0).cast()?;
+        let denominator = now.safe_sub(start_ts)?.cast()?;
+
+        self.sweep_share(user_stats, numerator, denominator)?;
+        self.last_sweep_ts = now;
+
+        Ok(())
+    }
+
+    fn sweep_share(
+        &mut self,
+        user_stats: &mut UserStats,
+        numerator: u128,
+        denominator: u128,
+    ) -> DriftResult {
+        let share = |fuel: &mut u32| -> DriftResult<u64> {
+            let swept: u32 = fuel
+                .cast::<u128>()?
+                .safe_mul(numerator)?
+                .safe_div(denominator)?
+                .cast()?;
+            *fuel = fuel.safe_sub(swept)?;
+            swept.cast()
+        };
+
+        self.fuel_insurance = self
+            .fuel_insurance
+            .safe_add(share(&mut user_stats.fuel_insurance)?)?;
+        self.fuel_deposits = self
+            .fuel_deposits
+            .safe_add(share(&mut user_stats.fuel_deposits)?)?;
+        self.fuel_borrows = self
+            .fuel_borrows
+            .safe_add(share(&mut user_stats.fuel_borrows)?)?;
+        self.fuel_positions = self
+            .fuel_positions
+            .safe_add(share(&mut user_stats.fuel_positions)?)?;
+        self.fuel_taker = self
+            .fuel_taker
+            .safe_add(share(&mut user_stats.fuel_taker)?)?;
+        self.fuel_maker = self
+            .fuel_maker
+            .safe_add(share(&mut user_stats.fuel_maker)?)?;
+        self.fuel_lp = self.fuel_lp.safe_add(share(&mut user_stats.fuel_lp)?)?;
+        self.fuel_keeper = self
+            .fuel_keeper
+            .safe_add(share(&mut user_stats.fuel_keeper)?)?;
+
+        Ok(())
+    }
+
+    pub fn get_total_fuel(&self) -> DriftResult<u64> {
+        self.fuel_insurance
+            .safe_add(self.fuel_deposits)?
+            .safe_add(self.fuel_borrows)?
+            .safe_add(self.fuel_positions)?
+            .safe_add(self.fuel_taker)?
+            .safe_add(self.fuel_maker)?
+            .safe_add(self.fuel_lp)?
+            .safe_add(self.fuel_keeper)
+    }
+
+    pub fn get_unredeemed_fuel(&self) -> DriftResult<u64> {
+        self.get_total_fuel()?.safe_sub(self.fuel_redeemed)
+    }
+}
@@ -0,0 +1,148 @@
+mod sweep {
+    use crate::state::fuel_season::{FuelSeason, UserFuelSeasonRecord};
+    use crate::state::user::UserStats;
+    use anchor_lang::prelude::Pubkey;
+
+    #[test]
+    fn resets_user_stats() {
+        let authority = Pubkey::new_unique();
+        let mut user_stats = UserStats {
+            authority,
+            fuel_insurance: 1,
+            fuel_deposits: 2,
+            fuel_borrows: 3,
+            fuel_positions: 4,
+            fuel_taker: 5,
+            fuel_maker: u32::MAX,
+            fuel_lp: 6,
+            fuel_keeper: 7,
+            fuel_season: 2,
+            ..UserStats::default()
+        };
+
+        let mut record = UserFuelSeasonRecord {
+            authority,
+            season: 2,
+            ..UserFuelSeasonRecord::default()
+        };
+
+        record.sweep(&mut user_stats, 100).unwrap();
+
+        assert_eq!(
+            record,
+            UserFuelSeasonRecord {
+                authority,
+                fuel_insurance: 1,
+                fuel_deposits: 2,
+                fuel_borrows: 3,
+                fuel_positions: 4,
+                fuel_taker: 5,
+                fuel_maker: u32::MAX as u64,
+                fuel_lp: 6,
+                fuel_keeper: 7,
+                last_sweep_ts: 100,
+                season: 2,
+                ..UserFuelSeasonRecord::default()
+            }
+        );
+        assert_eq!(record.get_total_fuel().unwrap(), u32::MAX as u64 + 28);
+
+        assert_eq!(user_stats.fuel_insurance, 0);
+        assert_eq!(user_stats.fuel_deposits, 0);
+        assert_eq!(user_stats.fuel_borrows, 0);
+        assert_eq!(user_stats.fuel_positions, 0);
+        assert_eq!(user_stats.fuel_taker, 0);
+        assert_eq!(user_stats.fuel_maker, 0);
+        assert_eq!(user_stats.fuel_lp, 0);
+        assert_eq!(user_stats.fuel_keeper, 0);
+        // the season is advanced by the caller
+        assert_eq!(user_stats.fuel_season, 2);
+
+        // counters swept again keep accumulating past u32::MAX
+        user_stats.fuel_maker = u32::MAX;
+        record.sweep(&mut user_stats, 200).unwrap();
+        assert_eq!(record.fuel_maker, 2 * u32::MAX as u64);
+        assert_eq!(record.last_sweep_ts, 200);
+        assert_eq!(user_stats.fuel_maker, 0);
+    }
+
+    #[test]
+    fn closed_season_keeps_fuel_earned_after_end() {
+        let mut user_stats = UserStats {
+            fuel_deposits: 1000,
+            fuel_taker: 300,
+            fuel_maker: u32::MAX,
+            fuel_season: 1,
+            ..UserStats::default()
+        };
+
+        let fuel_season = FuelSeason {
+            start_ts: 100,
+            end_ts: 400,
+            season: 1,
+            ..FuelSeason::default()
+        };
+
+        // never swept, so the counters are split from the season start
+        let mut record = UserFuelSeasonRecord {
+            season: 1,
+            ..UserFuelSeasonRecord::default()
+        };
+
+        record
+            .sweep_closed_season(&mut user_stats, &fuel_season, 500)
+            .unwrap();
+
+        assert_eq!(record.fuel_deposits, 750);
+        assert_eq!(record.fuel_taker, 225);
+        assert_eq!(record.fuel_maker, u32::MAX as u64 * 3 / 4);
+        assert_eq!(record.last_sweep_ts, 500);
+
+        // fuel earned after the season closed stays for the next season
+        assert_eq!(user_stats.fuel_deposits, 250);
+        assert_eq!(user_stats.fuel_taker, 75);
+        assert_eq!(
+            user_stats.fuel_maker as u64,
+            u32::MAX as u64 - record.fuel_maker
+        );
+
+        // swept during the season, so only the time since then is split
+        let mut user_stats = UserStats {
+            fuel_deposits: 1000,
+            ..UserStats::default()
+        };
+        let mut record = UserFuelSeasonRecord {
+            last_sweep_ts: 300,
+            season: 1,
+            ..UserFuelSeasonRecord::default()
+        };
+
+        record
+            .sweep_closed_season(&mut user_stats, &fuel_season, 500)
+            .unwrap();
+
+        assert_eq!(record.fuel_deposits, 500);
+        assert_eq!(user_stats.fuel_deposits, 500);
+
+        // swept after the season closed, nothing more is credited to it
+        record
+            .sweep_closed_season(&mut user_stats, &fuel_season, 600)
+            .unwrap();
+
+        assert_eq!(record.fuel_deposits, 500);
+        assert_eq!(user_stats.fuel_deposits, 500);
+        assert_eq!(record.last_sweep_ts, 600);
+    }
+
+    #[test]
+    fn unredeemed_fuel() {
+        let record = UserFuelSeasonRecord {
+            fuel_deposits: 100,
+            fuel_taker: 50,
+            fuel_redeemed: 100,
+            ..UserFuelSeasonRecord::default()
+        };
+
+        assert_eq!(record.get_unredeemed_fuel().unwrap(), 50);
+    }
+}
@@ -3,7 +3,7 @@ use crate::error::ErrorCode;
 use crate::math::safe_math::SafeMath;
 use crate::safe_decrement;
 use crate::safe_increment;
-use crate::state::spot_market::SpotMarket;
+use crate::state::spot_market::{InsuranceFund, SpotMarket};
 use crate::state::traits::Size;
 use crate::validate;
 use crate::{math_error, EPOCH_DURATION};
@@ -25,7 +25,13 @@ pub struct InsuranceFundStake {
     pub last_withdraw_request_ts: i64,
     pub cost_basis: i64,
     pub market_index: u16,
-    pub padding: [u8; 14],
+    /// which insurance fund tranche the shares are in
+    pub tranche: InsuranceFundTranche,
+    pub padding: [u8; 5],
+    /// pnl realized by removing stake, measured against cost_basis. resets with cost_basis when
+    /// the stake is re-entered from zero shares
+    /// precision: token mint precision
+    pub realized_pnl: i64,
 }
 
 // implement SIZE const for InsuranceFundStake
@@ -45,7 +51,9 @@ impl InsuranceFundStake {
             if_base: 0,
             last_valid_ts: now,
             if_shares: 0,
-            padding: [0; 14],
+            tranche: InsuranceFundTranche::Junior,
+            padding: [0; 5],
+            realized_pnl: 0,
         }
     }
 
@@ -61,6 +69,18 @@ impl InsuranceFundStake {
         Ok(())
     }
 
+    pub fn validate_tranche(&self, tranche: InsuranceFundTranche) -> DriftResult {
+        validate!(
+            self.tranche == tranche,
+            ErrorCode::InvalidInsuranceFundTranche,
+            "insurance fund stake tranche {:?} != {:?}",
+            self.tranche,
+            tranche
+        )?;
+
+        Ok(())
+    }
+
     pub fn checked_if_shares(&self, spot_market: &SpotMarket) -> DriftResult<u128> {
         self.validate_base(spot_market)?;
         Ok(self.if_shares)
@@ -90,6 +110,83 @@ impl InsuranceFundStake {
     }
 }
 
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Eq, Default)]
+pub enum InsuranceFundTranche {
+    /// Shares in the spot market's insurance fund. Absorbs losses first
+    #[default]
+    Junior,
+    /// Shares in the senior insurance fund. Only absorbs losses once the junior tranche is exhausted
+    Senior,
+    /// Shares in a perp market's isolated insurance fund. market_index is the perp market index
+    PerpMarket,
+}
+
+/// The senior tranche of a spot market's insurance fund. Has its own vault, shares and if factors.
+/// The junior tranche is the spot market's insurance_fund. Revenue is settled to both tranches
+/// together on the junior tranche's settle period
+#[account(zero_copy(unsafe))]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct SeniorInsuranceFund {
+    pub insurance_fund: InsuranceFund,
+    /// The share of revenue settled to the insurance fund that goes to the senior tranche
+    /// precision: IF_FACTOR_PRECISION
+    pub revenue_share: u32,
+    pub market_index: u16,
+    pub padding: [u8; 10],
+}
+
+// implement SIZE const for SeniorInsuranceFund
+impl Size for SeniorInsuranceFund {
+    const SIZE: usize = 136;
+}
+
+/// A perp market's isolated insurance fund. Covers the perp market's bankruptcies and pnl deficits
+/// before the shared quote insurance fund and is paid a share of the perp market's fee pool surplus
+#[account(zero_copy(unsafe))]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct PerpInsuranceFund {
+    pub insurance_fund: InsuranceFund,
+    pub market_index: u16,
+    pub padding: [u8; 14],
+}
+
+// implement SIZE const for PerpInsuranceFund
+impl Size for PerpInsuranceFund {
+    const SIZE: usize = 136;
+}
+
+/// Revenue and loss history for one insurance fund tranche. Updated when revenue is settled to the
+/// tranche and when it pays out; vault movements in between are attributed from the change in
+/// value per share
+#[account(zero_copy(unsafe))]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct InsuranceFundPerformance {
+    /// precision: token mint precision
+    pub cumulative_revenue: u128,
+    /// precision: token mint precision
+    pub cumulative_losses: u128,
+    /// insurance fund vault amount per share normalized to a shares base of 0
+    /// precision: IF_VALUE_PER_SHARE_PRECISION
+    pub last_value_per_share: u128,
+    pub last_update_ts: i64,
+    pub last_revenue_settle_ts: i64,
+    /// last revenue settlement annualized against the vault amount before it
+    /// precision: PERCENTAGE_PRECISION
+    pub last_revenue_apy: u64,
+    /// perp market index for the PerpMarket tranche, spot market index otherwise
+    pub market_index: u16,
+    pub tranche: InsuranceFundTranche,
+    pub padding: [u8; 21],
+}
+
+// implement SIZE const for InsuranceFundPerformance
+impl Size for InsuranceFundPerformance {
+    const SIZE: usize = 104;
+}
+
 #[account(zero_copy(unsafe))]
 #[derive(Default, Eq, PartialEq, Debug)]
 #[repr(C)]
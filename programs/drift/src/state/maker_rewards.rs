@@ -0,0 +1,332 @@
+use anchor_lang::prelude::*;
+
+use crate::error::{DriftResult, ErrorCode};
+use crate::math::casting::Cast;
+use crate::math::constants::PERCENTAGE_PRECISION_U64;
+use crate::math::safe_math::SafeMath;
+use crate::state::traits::Size;
+use crate::validate;
+
+#[cfg(test)]
+mod tests;
+
+/// Pays makers in a perp market for quoting depth near the oracle and for filled maker volume.
+/// Each epoch's rewards are split between the two and paid out pro rata once the epoch is settled
+#[account(zero_copy(unsafe))]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct MakerRewardsProgram {
+    /// Sum of the makers' depth scores for the current epoch
+    /// precision: AMM_RESERVE_PRECISION * seconds
+    pub total_depth_score: u128,
+    /// Sum of the makers' depth scores for the last settled epoch
+    /// precision: AMM_RESERVE_PRECISION * seconds
+    pub last_epoch_total_depth_score: u128,
+    /// The mint rewards are paid in
+    pub reward_mint: Pubkey,
+    pub makers: [MakerRewardsEntry; 16],
+    /// The unix_timestamp the current epoch started
+    pub epoch_start_ts: i64,
+    /// Minimum length of an epoch in seconds
+    pub epoch_duration: i64,
+    /// Reward tokens paid per epoch. Capped by the vault balance when the epoch is settled
+    /// precision: reward mint precision
+    pub epoch_rewards: u64,
+    /// Reward tokens to be claimed for the last settled epoch's depth scores
+    /// precision: reward mint precision
+    pub last_epoch_depth_rewards: u64,
+    /// Reward tokens to be claimed for the last settled epoch's maker volume
+    /// precision: reward mint precision
+    pub last_epoch_volume_rewards: u64,
+    /// Sum of the makers' filled volume for the current epoch
+    /// precision: BASE_PRECISION
+    pub total_maker_volume: u64,
+    /// Sum of the makers' filled volume for the last settled epoch
+    /// precision: BASE_PRECISION
+    pub last_epoch_total_maker_volume: u64,
+    /// Total reward tokens claimed by makers
+    /// precision: reward mint precision
+    pub total_rewards_claimed: u64,
+    /// Rolling 30day maker volume the user's authority needs to join the program
+    /// precision: QUOTE_PRECISION
+    pub min_maker_volume_30d: u64,
+    /// Resting orders priced further than this from the oracle dont count towards depth
+    /// precision: PERCENTAGE_PRECISION
+    pub max_oracle_distance: u32,
+    /// Share of each epoch's rewards paid for filled maker volume, the rest is paid for depth
+    /// precision: PERCENTAGE_PRECISION
+    pub volume_reward_share: u32,
+    pub epoch: u32,
+    pub market_index: u16,
+    pub padding: [u8; 10],
+}
+
+impl Size for MakerRewardsProgram {
+    const SIZE: usize = 1960;
+}
+
+#[zero_copy(unsafe)]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct MakerRewardsEntry {
+    /// Resting size near the oracle integrated over time for the current epoch
+    /// precision: AMM_RESERVE_PRECISION * seconds
+    pub depth_score: u128,
+    /// Depth score for the last settled epoch
+    /// precision: AMM_RESERVE_PRECISION * seconds
+    pub last_epoch_depth_score: u128,
+    /// The maker's user account. Default if the slot is free
+    pub user: Pubkey,
+    /// Filled maker volume for the current epoch
+    /// precision: BASE_PRECISION
+    pub maker_volume: u64,
+    /// Filled maker volume for the last settled epoch
+    /// precision: BASE_PRECISION
+    pub last_epoch_maker_volume: u64,
+    /// Resting size near the oracle when the maker was last sampled
+    /// precision: BASE_PRECISION
+    pub resting_base_asset_amount: u64,
+    /// The unix_timestamp the maker was last sampled
+    pub last_sample_ts: i64,
+    /// The slot the maker was last sampled. Only orders placed by then count towards the next sample
+    pub last_sample_slot: u64,
+    /// Whether the rewards for the last settled epoch have been claimed
+    pub last_epoch_claimed: bool,
+    pub padding: [u8; 7],
+}
+
+impl MakerRewardsEntry {
+    pub fn is_available(&self) -> bool {
+        self.user == Pubkey::default()
+    }
+
+    /// Credits the size that was resting at the last sample and is still resting, so orders
+    /// canceled or placed in between dont earn depth for the whole interval
+    fn accrue_depth_score(
+        &mut self,
+        resting_since_last_sample: u64,
+        epoch_start_ts: i64,
+        now: i64,
+    ) -> DriftResult<u128> {
+        let since_last = now
+            .safe_sub(self.last_sample_ts.max(epoch_start_ts))?
+            .max(0);
+
+        let depth_score = self
+            .resting_base_asset_amount
+            .min(resting_since_last_sample)
+            .cast::<u128>()?
+            .safe_mul(since_last.cast()?)?;
+
+        self.depth_score = self.depth_score.safe_add(depth_score)?;
+        self.last_sample_ts = now;
+
+        Ok(depth_score)
+    }
+}
+
+impl MakerRewardsProgram {
+    pub fn validate(&self) -> DriftResult {
+        validate!(
+            self.epoch_duration > 0,
+            ErrorCode::InvalidMakerRewardsProgram,
+            "epoch_duration must be positive"
+        )?;
+
+        validate!(
+            self.max_oracle_distance.cast::<u64>()? <= PERCENTAGE_PRECISION_U64,
+            ErrorCode::InvalidMakerRewardsProgram,
+            "max_oracle_distance {} must be <= {}",
+            self.max_oracle_distance,
+            PERCENTAGE_PRECISION_U64
+        )?;
+
+        validate!(
+            self.volume_reward_share.cast::<u64>()? <= PERCENTAGE_PRECISION_U64,
+            ErrorCode::InvalidMakerRewardsProgram,
+            "volume_reward_share {} must be <= {}",
+            self.volume_reward_share,
+            PERCENTAGE_PRECISION_U64
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_maker_index(&self, user: &Pubkey) -> Option<usize> {
+        self.makers
+            .iter()
+            .position(|maker| !maker.is_available() && &maker.user == user)
+    }
+
+    pub fn add_maker(
+        &mut self,
+        user: Pubkey,
+        maker_volume_30d: u64,
+        now: i64,
+        slot: u64,
+    ) -> DriftResult {
+        validate!(
+            maker_volume_30d >= self.min_maker_volume_30d,
+            ErrorCode::InsufficientMakerVolumeForMakerRewards,
+            "maker volume {} below program minimum {}",
+            maker_volume_30d,
+            self.min_maker_volume_30d
+        )?;
+
+        validate!(
+            self.get_maker_index(&user).is_none(),
+            ErrorCode::InvalidMakerRewardsProgram,
+            "user {} already in maker rewards program",
+            user
+        )?;
+
+        let maker = self
+            .makers
+            .iter_mut()
+            .find(|maker| maker.is_available())
+            .ok_or(ErrorCode::MakerRewardsProgramFull)?;
+
+        *maker = MakerRewardsEntry {
+            user,
+            last_sample_ts: now,
+            last_sample_slot: slot,
+            // no rewards for epochs settled before joining
+            last_epoch_claimed: true,
+            ..MakerRewardsEntry::default()
+        };
+
+        Ok(())
+    }
+
+    /// Frees the maker's slot. Their scores for the current epoch are taken out of the totals so
+    /// they dont dilute the remaining makers. Unclaimed rewards for the last settled epoch are
+    /// forfeited
+    pub fn remove_maker(&mut self, maker_index: usize) -> DriftResult {
+        let maker = self.makers[maker_index];
+
+        self.total_depth_score = self.total_depth_score.safe_sub(maker.depth_score)?;
+        self.total_maker_volume = self.total_maker_volume.safe_sub(maker.maker_volume)?;
+        self.makers[maker_index] = MakerRewardsEntry::default();
+
+        Ok(())
+    }
+
+    /// Credits the maker's depth since the last sample and starts a new sample.
+    /// resting_since_last_sample only counts orders placed by the last sample's slot
+    pub fn update_maker(
+        &mut self,
+        maker_index: usize,
+        resting_base_asset_amount: u64,
+        resting_since_last_sample: u64,
+        filled_base_asset_amount: u64,
+        now: i64,
+        slot: u64,
+    ) -> DriftResult {
+        let epoch_start_ts = self.epoch_start_ts;
+        let maker = &mut self.makers[maker_index];
+
+        let depth_score =
+            maker.accrue_depth_score(resting_since_last_sample, epoch_start_ts, now)?;
+        maker.resting_base_asset_amount = resting_base_asset_amount;
+        maker.last_sample_slot = slot;
+        maker.maker_volume = maker.maker_volume.safe_add(filled_base_asset_amount)?;
+
+        self.total_depth_score = self.total_depth_score.safe_add(depth_score)?;
+        self.total_maker_volume = self.total_maker_volume.safe_add(filled_base_asset_amount)?;
+
+        Ok(())
+    }
+
+    pub fn is_epoch_over(&self, now: i64) -> DriftResult<bool> {
+        Ok(now >= self.epoch_start_ts.safe_add(self.epoch_duration)?)
+    }
+
+    /// Closes the current epoch, making its scores claimable and forfeiting unclaimed rewards
+    /// from the previous epoch. Depth since a maker's last sample isnt credited, since the orders
+    /// still resting can only be checked when the maker is resampled. Anyone can resample makers
+    /// with update_maker_rewards_depth before settling
+    pub fn settle_epoch(&mut self, vault_amount: u64, now: i64) -> DriftResult {
+        validate!(
+            self.is_epoch_over(now)?,
+            ErrorCode::MakerRewardsEpochNotOver,
+            "epoch {} ends at {}",
+            self.epoch,
+            self.epoch_start_ts.safe_add(self.epoch_duration)?
+        )?;
+
+        for maker in self.makers.iter_mut().filter(|maker| !maker.is_available()) {
+            maker.last_epoch_depth_score = maker.depth_score;
+            maker.last_epoch_maker_volume = maker.maker_volume;
+            maker.last_epoch_claimed = false;
+            maker.depth_score = 0;
+            maker.maker_volume = 0;
+        }
+
+        let epoch_rewards = self.epoch_rewards.min(vault_amount);
+        let volume_rewards = if self.total_maker_volume > 0 {
+            epoch_rewards
+                .cast::<u128>()?
+                .safe_mul(self.volume_reward_share.cast()?)?
+                .safe_div(PERCENTAGE_PRECISION_U64.cast()?)?
+                .cast::<u64>()?
+        } else {
+            0
+        };
+        let depth_rewards = if self.total_depth_score > 0 {
+            epoch_rewards.safe_sub(volume_rewards)?
+        } else {
+            0
+        };
+
+        self.last_epoch_depth_rewards = depth_rewards;
+        self.last_epoch_volume_rewards = volume_rewards;
+        self.last_epoch_total_depth_score = self.total_depth_score;
+        self.last_epoch_total_maker_volume = self.total_maker_volume;
+        self.total_depth_score = 0;
+        self.total_maker_volume = 0;
+
+        self.epoch = self.epoch.safe_add(1)?;
+        self.epoch_start_ts = now;
+
+        Ok(())
+    }
+
+    pub fn calculate_claimable_rewards(&self, maker_index: usize) -> DriftResult<u64> {
+        let maker = &self.makers[maker_index];
+
+        if maker.last_epoch_claimed {
+            return Ok(0);
+        }
+
+        let depth_rewards = if self.last_epoch_total_depth_score > 0 {
+            self.last_epoch_depth_rewards
+                .cast::<u128>()?
+                .safe_mul(maker.last_epoch_depth_score)?
+                .safe_div(self.last_epoch_total_depth_score)?
+                .cast::<u64>()?
+        } else {
+            0
+        };
+
+        let volume_rewards = if self.last_epoch_total_maker_volume > 0 {
+            self.last_epoch_volume_rewards
+                .cast::<u128>()?
+                .safe_mul(maker.last_epoch_maker_volume.cast()?)?
+                .safe_div(self.last_epoch_total_maker_volume.cast()?)?
+                .cast::<u64>()?
+        } else {
+            0
+        };
+
+        depth_rewards.safe_add(volume_rewards)
+    }
+
+    pub fn claim_rewards(&mut self, maker_index: usize) -> DriftResult<u64> {
+        let rewards = self.calculate_claimable_rewards(maker_index)?;
+
+        self.makers[maker_index].last_epoch_claimed = true;
+        self.total_rewards_claimed = self.total_rewards_claimed.safe_add(rewards)?;
+
+        Ok(rewards)
+    }
+}
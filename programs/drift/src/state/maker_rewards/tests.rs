@@ -0,0 +1,427 @@
+mod validate {
+    use crate::error::ErrorCode;
+    use crate::math::constants::PERCENTAGE_PRECISION_U64;
+    use crate::state::maker_rewards::MakerRewardsProgram;
+
+    #[test]
+    fn params() {
+        let program = MakerRewardsProgram {
+            epoch_duration: 3600,
+            max_oracle_distance: PERCENTAGE_PRECISION_U64 as u32 / 100,
+            volume_reward_share: PERCENTAGE_PRECISION_U64 as u32,
+            ..MakerRewardsProgram::default()
+        };
+        assert!(program.validate().is_ok());
+
+        let program = MakerRewardsProgram {
+            epoch_duration: 0,
+            ..program
+        };
+        assert_eq!(
+            program.validate(),
+            Err(ErrorCode::InvalidMakerRewardsProgram)
+        );
+
+        let program = MakerRewardsProgram {
+            epoch_duration: 3600,
+            volume_reward_share: PERCENTAGE_PRECISION_U64 as u32 + 1,
+            ..program
+        };
+        assert_eq!(
+            program.validate(),
+            Err(ErrorCode::InvalidMakerRewardsProgram)
+        );
+    }
+}
+
+mod add_maker {
+    use crate::error::ErrorCode;
+    use crate::math::constants::QUOTE_PRECISION_U64;
+    use crate::state::maker_rewards::{MakerRewardsEntry, MakerRewardsProgram};
+    use anchor_lang::prelude::Pubkey;
+
+    #[test]
+    fn fills_first_free_slot() {
+        let mut program = MakerRewardsProgram::default();
+
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        program.add_maker(first, 0, 10, 1).unwrap();
+        program.add_maker(second, 0, 20, 2).unwrap();
+
+        assert_eq!(program.get_maker_index(&first), Some(0));
+        assert_eq!(program.get_maker_index(&second), Some(1));
+        assert_eq!(program.makers[1].last_sample_ts, 20);
+        assert_eq!(program.makers[1].last_sample_slot, 2);
+        assert!(program.makers[1].last_epoch_claimed);
+        assert_eq!(program.get_maker_index(&Pubkey::default()), None);
+    }
+
+    #[test]
+    fn already_joined() {
+        let mut program = MakerRewardsProgram::default();
+
+        let user = Pubkey::new_unique();
+        program.add_maker(user, 0, 0, 0).unwrap();
+
+        assert_eq!(
+            program.add_maker(user, 0, 0, 0),
+            Err(ErrorCode::InvalidMakerRewardsProgram)
+        );
+    }
+
+    #[test]
+    fn full() {
+        let mut program = MakerRewardsProgram {
+            makers: [MakerRewardsEntry {
+                user: Pubkey::new_unique(),
+                ..MakerRewardsEntry::default()
+            }; 16],
+            ..MakerRewardsProgram::default()
+        };
+
+        assert_eq!(
+            program.add_maker(Pubkey::new_unique(), 0, 0, 0),
+            Err(ErrorCode::MakerRewardsProgramFull)
+        );
+    }
+
+    #[test]
+    fn insufficient_maker_volume() {
+        let mut program = MakerRewardsProgram {
+            min_maker_volume_30d: 1000 * QUOTE_PRECISION_U64,
+            ..MakerRewardsProgram::default()
+        };
+
+        assert_eq!(
+            program.add_maker(Pubkey::new_unique(), 999 * QUOTE_PRECISION_U64, 0, 0),
+            Err(ErrorCode::InsufficientMakerVolumeForMakerRewards)
+        );
+        assert!(program
+            .add_maker(Pubkey::new_unique(), 1000 * QUOTE_PRECISION_U64, 0, 0)
+            .is_ok());
+    }
+}
+
+mod remove_maker {
+    use crate::math::constants::BASE_PRECISION_U64;
+    use crate::state::maker_rewards::{MakerRewardsEntry, MakerRewardsProgram};
+    use anchor_lang::prelude::Pubkey;
+
+    #[test]
+    fn takes_scores_out_of_totals() {
+        let mut program = MakerRewardsProgram {
+            epoch_start_ts: 0,
+            epoch_duration: 100,
+            ..MakerRewardsProgram::default()
+        };
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        program.add_maker(first, 0, 0, 0).unwrap();
+        program.add_maker(second, 0, 0, 0).unwrap();
+
+        program
+            .update_maker(0, BASE_PRECISION_U64, BASE_PRECISION_U64, 0, 0, 0)
+            .unwrap();
+        program
+            .update_maker(1, BASE_PRECISION_U64, BASE_PRECISION_U64, 0, 0, 0)
+            .unwrap();
+        program
+            .update_maker(
+                0,
+                BASE_PRECISION_U64,
+                BASE_PRECISION_U64,
+                BASE_PRECISION_U64,
+                10,
+                1,
+            )
+            .unwrap();
+        program
+            .update_maker(1, BASE_PRECISION_U64, BASE_PRECISION_U64, 0, 20, 2)
+            .unwrap();
+
+        assert_eq!(program.total_depth_score, 30 * BASE_PRECISION_U64 as u128);
+        assert_eq!(program.total_maker_volume, BASE_PRECISION_U64);
+
+        program.remove_maker(0).unwrap();
+
+        assert_eq!(program.makers[0], MakerRewardsEntry::default());
+        assert_eq!(program.get_maker_index(&first), None);
+        assert_eq!(program.get_maker_index(&second), Some(1));
+        assert_eq!(program.total_depth_score, 20 * BASE_PRECISION_U64 as u128);
+        assert_eq!(program.total_maker_volume, 0);
+
+        // slot can be reused
+        let third = Pubkey::new_unique();
+        program.add_maker(third, 0, 30, 3).unwrap();
+        assert_eq!(program.get_maker_index(&third), Some(0));
+    }
+}
+
+mod update_maker {
+    use crate::math::constants::BASE_PRECISION_U64;
+    use crate::state::maker_rewards::MakerRewardsProgram;
+    use anchor_lang::prelude::Pubkey;
+
+    #[test]
+    fn accrues_resting_size_over_time() {
+        let mut program = MakerRewardsProgram {
+            epoch_start_ts: 0,
+            epoch_duration: 100,
+            ..MakerRewardsProgram::default()
+        };
+        program.add_maker(Pubkey::new_unique(), 0, 0, 0).unwrap();
+
+        // nothing resting yet
+        program
+            .update_maker(0, BASE_PRECISION_U64, 0, 0, 10, 1)
+            .unwrap();
+        assert_eq!(program.makers[0].depth_score, 0);
+
+        // 1 base resting for 20 seconds, then 2 base filled
+        program
+            .update_maker(
+                0,
+                3 * BASE_PRECISION_U64,
+                BASE_PRECISION_U64,
+                2 * BASE_PRECISION_U64,
+                30,
+                3,
+            )
+            .unwrap();
+        assert_eq!(
+            program.makers[0].depth_score,
+            20 * BASE_PRECISION_U64 as u128
+        );
+        assert_eq!(program.makers[0].maker_volume, 2 * BASE_PRECISION_U64);
+        assert_eq!(
+            program.makers[0].resting_base_asset_amount,
+            3 * BASE_PRECISION_U64
+        );
+        assert_eq!(program.makers[0].last_sample_ts, 30);
+        assert_eq!(program.makers[0].last_sample_slot, 3);
+
+        assert_eq!(program.total_depth_score, 20 * BASE_PRECISION_U64 as u128);
+        assert_eq!(program.total_maker_volume, 2 * BASE_PRECISION_U64);
+    }
+
+    #[test]
+    fn only_accrues_from_epoch_start() {
+        let mut program = MakerRewardsProgram {
+            epoch_start_ts: 50,
+            epoch_duration: 100,
+            ..MakerRewardsProgram::default()
+        };
+        program.add_maker(Pubkey::new_unique(), 0, 0, 0).unwrap();
+        program.makers[0].resting_base_asset_amount = BASE_PRECISION_U64;
+
+        program
+            .update_maker(0, 0, BASE_PRECISION_U64, 0, 60, 1)
+            .unwrap();
+
+        assert_eq!(
+            program.makers[0].depth_score,
+            10 * BASE_PRECISION_U64 as u128
+        );
+    }
+
+    #[test]
+    fn only_credits_size_resting_through_interval() {
+        let mut program = MakerRewardsProgram {
+            epoch_start_ts: 0,
+            epoch_duration: 100,
+            ..MakerRewardsProgram::default()
+        };
+        program.add_maker(Pubkey::new_unique(), 0, 0, 0).unwrap();
+
+        // sampled with 10 base resting
+        program
+            .update_maker(0, 10 * BASE_PRECISION_U64, 0, 0, 0, 0)
+            .unwrap();
+
+        // orders canceled, then reposted before the next sample
+        program
+            .update_maker(0, 10 * BASE_PRECISION_U64, 0, 0, 50, 5)
+            .unwrap();
+        assert_eq!(program.makers[0].depth_score, 0);
+
+        // 1 base of the reposted orders was filled
+        program
+            .update_maker(
+                0,
+                9 * BASE_PRECISION_U64,
+                9 * BASE_PRECISION_U64,
+                BASE_PRECISION_U64,
+                60,
+                6,
+            )
+            .unwrap();
+        assert_eq!(
+            program.makers[0].depth_score,
+            90 * BASE_PRECISION_U64 as u128
+        );
+    }
+}
+
+mod settle_epoch {
+    use crate::error::ErrorCode;
+    use crate::math::constants::{
+        BASE_PRECISION_U64, PERCENTAGE_PRECISION_U64, QUOTE_PRECISION_U64,
+    };
+    use crate::state::maker_rewards::MakerRewardsProgram;
+    use anchor_lang::prelude::Pubkey;
+
+    fn program_with_two_makers() -> MakerRewardsProgram {
+        let mut program = MakerRewardsProgram {
+            epoch_start_ts: 0,
+            epoch_duration: 100,
+            epoch_rewards: 1000 * QUOTE_PRECISION_U64,
+            volume_reward_share: PERCENTAGE_PRECISION_U64 as u32 / 4,
+            ..MakerRewardsProgram::default()
+        };
+        program.add_maker(Pubkey::new_unique(), 0, 0, 0).unwrap();
+        program.add_maker(Pubkey::new_unique(), 0, 0, 0).unwrap();
+
+        // maker 0 quotes 3 base for the whole epoch, maker 1 quotes 1 base
+        program
+            .update_maker(0, 3 * BASE_PRECISION_U64, 0, 0, 0, 0)
+            .unwrap();
+        program
+            .update_maker(1, BASE_PRECISION_U64, 0, 0, 0, 0)
+            .unwrap();
+        program
+            .update_maker(
+                0,
+                3 * BASE_PRECISION_U64,
+                3 * BASE_PRECISION_U64,
+                0,
+                100,
+                10,
+            )
+            .unwrap();
+
+        // maker 1 does all the volume
+        program
+            .update_maker(
+                1,
+                BASE_PRECISION_U64,
+                BASE_PRECISION_U64,
+                5 * BASE_PRECISION_U64,
+                50,
+                5,
+            )
+            .unwrap();
+        program
+            .update_maker(1, BASE_PRECISION_U64, BASE_PRECISION_U64, 0, 100, 10)
+            .unwrap();
+
+        program
+    }
+
+    #[test]
+    fn epoch_not_over() {
+        let mut program = program_with_two_makers();
+
+        assert_eq!(
+            program.settle_epoch(1000 * QUOTE_PRECISION_U64, 99),
+            Err(ErrorCode::MakerRewardsEpochNotOver)
+        );
+    }
+
+    #[test]
+    fn splits_rewards_pro_rata() {
+        let mut program = program_with_two_makers();
+
+        program
+            .settle_epoch(1000 * QUOTE_PRECISION_U64, 100)
+            .unwrap();
+
+        assert_eq!(program.epoch, 1);
+        assert_eq!(program.epoch_start_ts, 100);
+        assert_eq!(program.last_epoch_volume_rewards, 250 * QUOTE_PRECISION_U64);
+        assert_eq!(program.last_epoch_depth_rewards, 750 * QUOTE_PRECISION_U64);
+        assert_eq!(
+            program.last_epoch_total_depth_score,
+            400 * BASE_PRECISION_U64 as u128
+        );
+        assert_eq!(
+            program.last_epoch_total_maker_volume,
+            5 * BASE_PRECISION_U64
+        );
+        assert_eq!(program.total_depth_score, 0);
+        assert_eq!(program.total_maker_volume, 0);
+        assert_eq!(program.makers[0].depth_score, 0);
+
+        assert_eq!(
+            program.calculate_claimable_rewards(0).unwrap(),
+            562500000 // 3/4 of depth rewards
+        );
+        assert_eq!(
+            program.calculate_claimable_rewards(1).unwrap(),
+            437500000 // 1/4 of depth rewards + all volume rewards
+        );
+
+        assert_eq!(program.claim_rewards(1).unwrap(), 437500000);
+        assert_eq!(program.claim_rewards(1).unwrap(), 0);
+        assert_eq!(program.total_rewards_claimed, 437500000);
+    }
+
+    #[test]
+    fn capped_by_vault() {
+        let mut program = program_with_two_makers();
+
+        program
+            .settle_epoch(100 * QUOTE_PRECISION_U64, 100)
+            .unwrap();
+
+        assert_eq!(program.last_epoch_volume_rewards, 25 * QUOTE_PRECISION_U64);
+        assert_eq!(program.last_epoch_depth_rewards, 75 * QUOTE_PRECISION_U64);
+    }
+
+    #[test]
+    fn no_volume() {
+        let mut program = MakerRewardsProgram {
+            epoch_start_ts: 0,
+            epoch_duration: 100,
+            epoch_rewards: 1000 * QUOTE_PRECISION_U64,
+            volume_reward_share: PERCENTAGE_PRECISION_U64 as u32 / 4,
+            ..MakerRewardsProgram::default()
+        };
+        program.add_maker(Pubkey::new_unique(), 0, 0, 0).unwrap();
+        program
+            .update_maker(0, BASE_PRECISION_U64, 0, 0, 0, 0)
+            .unwrap();
+        program
+            .update_maker(0, BASE_PRECISION_U64, BASE_PRECISION_U64, 0, 100, 10)
+            .unwrap();
+
+        program
+            .settle_epoch(1000 * QUOTE_PRECISION_U64, 100)
+            .unwrap();
+
+        assert_eq!(program.last_epoch_volume_rewards, 0);
+        assert_eq!(program.last_epoch_depth_rewards, 1000 * QUOTE_PRECISION_U64);
+        assert_eq!(
+            program.calculate_claimable_rewards(0).unwrap(),
+            1000 * QUOTE_PRECISION_U64
+        );
+    }
+
+    #[test]
+    fn unclaimed_rewards_forfeited() {
+        let mut program = program_with_two_makers();
+
+        program
+            .settle_epoch(1000 * QUOTE_PRECISION_U64, 100)
+            .unwrap();
+        // no activity in the next epoch
+        program.makers[0].resting_base_asset_amount = 0;
+        program.makers[1].resting_base_asset_amount = 0;
+        program
+            .settle_epoch(1000 * QUOTE_PRECISION_U64, 200)
+            .unwrap();
+
+        assert_eq!(program.last_epoch_depth_rewards, 0);
+        assert_eq!(program.calculate_claimable_rewards(0).unwrap(), 0);
+    }
+}
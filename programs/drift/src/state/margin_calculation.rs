@@ -1,6 +1,8 @@
 use crate::error::{DriftResult, ErrorCode};
 use crate::math::casting::Cast;
-use crate::math::fuel::{calculate_perp_fuel_bonus, calculate_spot_fuel_bonus};
+use crate::math::fuel::{
+    calculate_lp_fuel_bonus, calculate_perp_fuel_bonus, calculate_spot_fuel_bonus,
+};
 use crate::math::margin::MarginRequirementType;
 use crate::math::safe_math::SafeMath;
 use crate::math::spot_balance::get_strict_token_value;
@@ -8,7 +10,9 @@ use crate::state::oracle::StrictOraclePrice;
 use crate::state::perp_market::PerpMarket;
 use crate::state::spot_market::SpotMarket;
 use crate::state::user::{PerpPosition, User};
-use crate::{validate, MarketType, AMM_RESERVE_PRECISION_I128, MARGIN_PRECISION_U128};
+use crate::{
+    validate, MarketType, AMM_RESERVE_PRECISION, AMM_RESERVE_PRECISION_I128, MARGIN_PRECISION_U128,
+};
 use anchor_lang::{prelude::*, solana_program::msg};
 
 #[derive(Clone, Copy, Debug)]
@@ -176,6 +180,7 @@ pub struct MarginCalculation {
     pub fuel_deposits: u32,
     pub fuel_borrows: u32,
     pub fuel_positions: u32,
+    pub fuel_lp: u32,
 }
 
 impl MarginCalculation {
@@ -199,6 +204,7 @@ impl MarginCalculation {
             fuel_deposits: 0,
             fuel_borrows: 0,
             fuel_positions: 0,
+            fuel_lp: 0,
         }
     }
 
@@ -431,6 +437,35 @@ impl MarginCalculation {
         Ok(())
     }
 
+    pub fn update_fuel_lp_bonus(
+        &mut self,
+        perp_market: &PerpMarket,
+        perp_position: &PerpPosition,
+        oracle_price: i64,
+    ) -> DriftResult {
+        if perp_market.fuel_boost_lp == 0 || perp_position.lp_shares == 0 {
+            return Ok(());
+        }
+
+        let lp_shares_value = perp_position
+            .lp_shares
+            .cast::<u128>()?
+            .safe_mul(oracle_price.unsigned_abs().cast()?)?
+            .safe_div(AMM_RESERVE_PRECISION)?;
+
+        let lp_fuel_bonus = calculate_lp_fuel_bonus(
+            perp_market,
+            lp_shares_value,
+            self.context.fuel_bonus_numerator,
+        )?;
+
+        self.fuel_lp = self
+            .fuel_lp
+            .saturating_add(lp_fuel_bonus.cast().unwrap_or(u32::MAX));
+
+        Ok(())
+    }
+
     pub fn update_fuel_spot_bonus(
         &mut self,
         spot_market: &SpotMarket,
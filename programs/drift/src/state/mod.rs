@@ -1,9 +1,11 @@
 pub mod events;
 pub mod fill_mode;
+pub mod fuel_season;
 pub mod fulfillment;
 pub mod fulfillment_params;
 pub mod insurance_fund_stake;
 pub mod load_ref;
+pub mod maker_rewards;
 pub mod margin_calculation;
 pub mod oracle;
 pub mod oracle_map;
@@ -17,6 +19,7 @@ pub mod spot_market;
 pub mod spot_market_map;
 #[allow(clippy::module_inception)]
 pub mod state;
+pub mod term_loan;
 pub mod traits;
 pub mod user;
 pub mod user_map;
@@ -3,17 +3,24 @@ use std::cell::Ref;
 
 use crate::error::{DriftResult, ErrorCode};
 use crate::math::casting::Cast;
-use crate::math::constants::{PRICE_PRECISION, PRICE_PRECISION_I64, PRICE_PRECISION_U64};
+use crate::math::constants::{
+    BID_ASK_SPREAD_PRECISION, CHAINLINK_MIN_CONFIDENCE_PCT, ONE_BPS_DENOMINATOR, ONE_HOUR,
+    PERCENTAGE_PRECISION, PRICE_PRECISION, PRICE_PRECISION_I64, PRICE_PRECISION_U64,
+};
 use crate::math::safe_math::SafeMath;
+use crate::math::stats::{calculate_rolling_sum, calculate_weighted_average};
 use switchboard::{AggregatorAccountData, SwitchboardDecimal};
 use switchboard_on_demand::{PullFeedAccountData, SB_ON_DEMAND_PRECISION};
 
 use crate::error::ErrorCode::{InvalidOracle, UnableToLoadOracle};
 use crate::math::safe_unwrap::SafeUnwrap;
+use crate::math_error;
 use crate::state::load_ref::load_ref;
 use crate::state::perp_market::PerpMarket;
 use crate::state::traits::Size;
+use crate::state::user::MarketType;
 use crate::validate;
+use arrayref::array_ref;
 
 #[cfg(test)]
 mod tests;
@@ -107,6 +114,126 @@ impl HistoricalIndexData {
     }
 }
 
+/// Puts a market into reduce only when the oracle diverges too far from its 5min twap or, for markets
+/// with an OracleCircuitBreakerWindow, from the oracle price at the start of the window
+#[derive(Default, AnchorSerialize, AnchorDeserialize, Clone, Copy, Eq, PartialEq, Debug)]
+pub struct OracleCircuitBreaker {
+    /// Max divergence between oracle price and its reference price before the breaker trips. 0 disables it
+    /// precision: ONE_BPS_DENOMINATOR
+    pub threshold: u16,
+    /// Seconds the oracle must stay within the threshold before the breaker resets
+    pub cooldown: u16,
+    /// unix_timestamp of the last breach while tripped. 0 means not tripped
+    pub tripped_ts: u32,
+}
+
+impl OracleCircuitBreaker {
+    pub fn is_tripped(&self) -> bool {
+        self.tripped_ts != 0
+    }
+
+    fn is_breached(&self, oracle_price: i64, reference_price: i64) -> DriftResult<bool> {
+        let divergence = oracle_price
+            .safe_sub(reference_price)?
+            .unsigned_abs()
+            .cast::<u128>()?
+            .safe_mul(ONE_BPS_DENOMINATOR.cast()?)?
+            .safe_div(reference_price.cast()?)?;
+
+        Ok(divergence > self.threshold.cast()?)
+    }
+
+    /// Returns Some(true) if the breaker wasn't already tripped
+    fn trip(&mut self, now: i64) -> DriftResult<Option<bool>> {
+        let was_tripped = self.is_tripped();
+        self.tripped_ts = now.cast()?;
+        Ok((!was_tripped).then_some(true))
+    }
+
+    /// Returns Some(tripped) if the breaker tripped or reset
+    pub fn update(
+        &mut self,
+        oracle_price: i64,
+        oracle_price_twap_5min: i64,
+        now: i64,
+    ) -> DriftResult<Option<bool>> {
+        if self.threshold == 0 {
+            if self.is_tripped() {
+                self.tripped_ts = 0;
+                return Ok(Some(false));
+            }
+
+            return Ok(None);
+        }
+
+        if oracle_price <= 0 || oracle_price_twap_5min <= 0 {
+            return Ok(None);
+        }
+
+        if self.is_breached(oracle_price, oracle_price_twap_5min)? {
+            return self.trip(now);
+        }
+
+        if self.is_tripped() && now.safe_sub(self.tripped_ts.cast()?)? >= self.cooldown.cast()? {
+            self.tripped_ts = 0;
+            return Ok(Some(false));
+        }
+
+        Ok(None)
+    }
+}
+
+/// Oracle price a market's circuit breaker compares against over a window of slots. The reference
+/// rolls over to the current oracle price once window_slots have passed
+#[account]
+#[derive(Default, Eq, PartialEq, Debug)]
+pub struct OracleCircuitBreakerWindow {
+    /// oracle price at the start of the window
+    /// precision: PRICE_PRECISION
+    pub reference_price: i64,
+    /// slot the window started
+    pub reference_slot: u64,
+    /// length of the window in slots
+    pub window_slots: u64,
+    pub market_index: u16,
+    pub market_type: MarketType,
+    pub padding: [u8; 13],
+}
+
+impl Size for OracleCircuitBreakerWindow {
+    const SIZE: usize = 48;
+}
+
+impl OracleCircuitBreakerWindow {
+    /// Returns Some(true) if the oracle moved more than the breaker's threshold from the reference
+    /// price within the window and the breaker wasn't already tripped
+    pub fn update(
+        &mut self,
+        circuit_breaker: &mut OracleCircuitBreaker,
+        oracle_price: i64,
+        slot: u64,
+        now: i64,
+    ) -> DriftResult<Option<bool>> {
+        if oracle_price <= 0 {
+            return Ok(None);
+        }
+
+        if self.reference_price <= 0 || slot.safe_sub(self.reference_slot)? > self.window_slots {
+            self.reference_price = oracle_price;
+            self.reference_slot = slot;
+            return Ok(None);
+        }
+
+        if circuit_breaker.threshold == 0
+            || !circuit_breaker.is_breached(oracle_price, self.reference_price)?
+        {
+            return Ok(None);
+        }
+
+        circuit_breaker.trip(now)
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
 pub enum OracleSource {
     #[default]
@@ -122,6 +249,31 @@ pub enum OracleSource {
     Pyth1MPull,
     PythStableCoinPull,
     SwitchboardOnDemand,
+    Aggregated,
+    Chainlink,
+    SignedPrice,
+    FillTwap,
+}
+
+impl OracleSource {
+    /// sources backed by a PrelaunchOracle account that's updated from the market's own trading
+    pub fn is_prelaunch(&self) -> bool {
+        matches!(self, OracleSource::Prelaunch | OracleSource::FillTwap)
+    }
+
+    /// keeper pushed signed prices are only available in builds with the signed-price-oracle feature
+    pub fn is_available(&self) -> bool {
+        cfg!(feature = "signed-price-oracle") || *self != OracleSource::SignedPrice
+    }
+
+    pub fn validate_available(&self) -> DriftResult {
+        validate!(
+            self.is_available(),
+            ErrorCode::InvalidOracle,
+            "oracle source {:?} not available",
+            self
+        )
+    }
 }
 
 #[derive(Default, Clone, Copy, Debug)]
@@ -161,13 +313,18 @@ pub fn get_oracle_price(
             delay: 0,
             has_sufficient_number_of_data_points: true,
         }),
-        OracleSource::Prelaunch => get_prelaunch_price(price_oracle, clock_slot),
+        OracleSource::Prelaunch | OracleSource::FillTwap => {
+            get_prelaunch_price(price_oracle, clock_slot)
+        }
         OracleSource::PythPull => get_pyth_price(price_oracle, clock_slot, 1, true),
         OracleSource::Pyth1KPull => get_pyth_price(price_oracle, clock_slot, 1000, true),
         OracleSource::Pyth1MPull => get_pyth_price(price_oracle, clock_slot, 1000000, true),
         OracleSource::PythStableCoinPull => {
             get_pyth_stable_coin_price(price_oracle, clock_slot, true)
         }
+        OracleSource::Aggregated => get_oracle_aggregator_price(price_oracle, clock_slot),
+        OracleSource::Chainlink => get_chainlink_price(price_oracle, clock_slot),
+        OracleSource::SignedPrice => get_signed_price(price_oracle, clock_slot),
     }
 }
 
@@ -378,6 +535,121 @@ fn convert_sb_i128(switchboard_i128: &i128) -> DriftResult<i128> {
     }
 }
 
+/// Scale a value with the given number of decimals to PRICE_PRECISION
+fn convert_to_price_precision(value: i128, decimals: u32) -> DriftResult<i128> {
+    let precision = 10_u128.checked_pow(decimals).ok_or_else(math_error!())?;
+    if precision > PRICE_PRECISION {
+        value.safe_div(precision.safe_div(PRICE_PRECISION)?.cast()?)
+    } else {
+        value.safe_mul(PRICE_PRECISION.safe_div(precision)?.cast()?)
+    }
+}
+
+// chainlink store feed account: 8 byte discriminator, header, then a ring buffer of transmissions
+const CHAINLINK_HEADER_SIZE: usize = 192;
+const CHAINLINK_TRANSMISSION_SIZE: usize = 48;
+
+pub fn get_chainlink_price(
+    price_oracle: &AccountInfo,
+    clock_slot: u64,
+) -> DriftResult<OraclePriceData> {
+    let data = price_oracle.try_borrow_data().or(Err(UnableToLoadOracle))?;
+
+    validate!(
+        data.len() >= 8 + CHAINLINK_HEADER_SIZE,
+        UnableToLoadOracle,
+        "chainlink feed account too small"
+    )?;
+
+    let header = array_ref![data, 8, CHAINLINK_HEADER_SIZE];
+    let decimals = header[130];
+    let latest_round_id = u32::from_le_bytes(*array_ref![header, 135, 4]);
+    let live_length = u32::from_le_bytes(*array_ref![header, 140, 4]);
+    let live_cursor = u32::from_le_bytes(*array_ref![header, 144, 4]);
+
+    validate!(
+        latest_round_id != 0 && live_length != 0,
+        UnableToLoadOracle,
+        "chainlink feed has no transmissions"
+    )?;
+
+    validate!(
+        live_length
+            .cast::<u64>()?
+            .safe_mul(CHAINLINK_TRANSMISSION_SIZE.cast()?)?
+            .safe_add((8 + CHAINLINK_HEADER_SIZE).cast()?)?
+            <= data.len().cast()?,
+        UnableToLoadOracle,
+        "chainlink feed account too small for live length"
+    )?;
+
+    let get_transmission = |index: u32| {
+        let offset = 8 + CHAINLINK_HEADER_SIZE + index as usize * CHAINLINK_TRANSMISSION_SIZE;
+        array_ref![data, offset, CHAINLINK_TRANSMISSION_SIZE]
+    };
+
+    // live_cursor points to the next slot to be written
+    let latest_index = live_cursor.safe_add(live_length)?.safe_sub(1)? % live_length;
+    let transmission = get_transmission(latest_index);
+    let transmission_slot = u64::from_le_bytes(*array_ref![transmission, 0, 8]);
+    let answer = i128::from_le_bytes(*array_ref![transmission, 16, 16]);
+
+    let price = convert_to_price_precision(answer, decimals.cast()?)?.cast::<i64>()?;
+
+    // chainlink doesn't publish a confidence, use the move from the previous round floored at
+    // CHAINLINK_MIN_CONFIDENCE_PCT of price so an unchanged feed doesn't report near-zero confidence.
+    // without a previous round the feed doesn't have enough data points to be valid
+    let has_previous_round = latest_round_id > 1 && live_length > 1;
+    let confidence = if has_previous_round {
+        let previous_index = latest_index.safe_add(live_length)?.safe_sub(1)? % live_length;
+        let previous_answer =
+            i128::from_le_bytes(*array_ref![get_transmission(previous_index), 16, 16]);
+        let previous_price =
+            convert_to_price_precision(previous_answer, decimals.cast()?)?.cast::<i64>()?;
+
+        let min_confidence = price
+            .unsigned_abs()
+            .safe_mul(CHAINLINK_MIN_CONFIDENCE_PCT)?
+            .safe_div(BID_ASK_SPREAD_PRECISION)?;
+
+        price.abs_diff(previous_price).max(min_confidence).max(1)
+    } else {
+        price.unsigned_abs()
+    };
+
+    let delay = clock_slot
+        .cast::<i64>()?
+        .safe_sub(transmission_slot.cast()?)?;
+
+    Ok(OraclePriceData {
+        price,
+        confidence,
+        delay,
+        has_sufficient_number_of_data_points: has_previous_round,
+    })
+}
+
+pub fn get_signed_price(
+    price_oracle: &AccountInfo,
+    clock_slot: u64,
+) -> DriftResult<OraclePriceData> {
+    let oracle: Ref<SignedPriceAccount> = load_ref(price_oracle).or(Err(UnableToLoadOracle))?;
+
+    let decimals = oracle.exponent.unsigned_abs();
+    let price = convert_to_price_precision(oracle.price.cast()?, decimals)?.cast::<i64>()?;
+    let confidence =
+        convert_to_price_precision(oracle.confidence.cast()?, decimals)?.cast::<u64>()?;
+
+    Ok(OraclePriceData {
+        price,
+        confidence,
+        delay: clock_slot
+            .cast::<i64>()?
+            .safe_sub(oracle.posted_slot.cast()?)?,
+        has_sufficient_number_of_data_points: true,
+    })
+}
+
 pub fn get_prelaunch_price(price_oracle: &AccountInfo, slot: u64) -> DriftResult<OraclePriceData> {
     let oracle: Ref<PrelaunchOracle> = load_ref(price_oracle).or(Err(UnableToLoadOracle))?;
 
@@ -389,10 +661,31 @@ pub fn get_prelaunch_price(price_oracle: &AccountInfo, slot: u64) -> DriftResult
     })
 }
 
+/// Last aggregated price stored on the aggregator account. Used when the underlying feeds
+/// aren't passed to the ix (OracleMap aggregates live when they are)
+pub fn get_oracle_aggregator_price(
+    price_oracle: &AccountInfo,
+    slot: u64,
+) -> DriftResult<OraclePriceData> {
+    let oracle: Ref<OracleAggregator> = load_ref(price_oracle).or(Err(UnableToLoadOracle))?;
+
+    Ok(OraclePriceData {
+        price: oracle.price,
+        confidence: oracle.confidence,
+        delay: slot
+            .saturating_sub(oracle.last_update_slot)
+            .cast::<i64>()?
+            .safe_add(oracle.delay)?,
+        has_sufficient_number_of_data_points: true,
+    })
+}
+
 #[derive(Clone, Copy)]
 pub struct StrictOraclePrice {
     pub current: i64,
     pub twap_5min: Option<i64>,
+    /// widens max/min by a multiple of the oracle confidence, for initial margin
+    pub confidence_adjustment: i64,
 }
 
 impl StrictOraclePrice {
@@ -400,21 +693,31 @@ impl StrictOraclePrice {
         Self {
             current: price,
             twap_5min: if enabled { Some(twap_5min) } else { None },
+            confidence_adjustment: 0,
         }
     }
 
+    pub fn with_confidence_adjustment(mut self, confidence_adjustment: i64) -> Self {
+        self.confidence_adjustment = confidence_adjustment;
+        self
+    }
+
     pub fn max(&self) -> i64 {
-        match self.twap_5min {
+        let max = match self.twap_5min {
             Some(twap) => self.current.max(twap),
             None => self.current,
-        }
+        };
+
+        max.saturating_add(self.confidence_adjustment)
     }
 
     pub fn min(&self) -> i64 {
-        match self.twap_5min {
+        let min = match self.twap_5min {
             Some(twap) => self.current.min(twap),
             None => self.current,
-        }
+        };
+
+        min.saturating_sub(self.confidence_adjustment).max(0)
     }
 
     pub fn validate(&self) -> DriftResult {
@@ -444,6 +747,7 @@ impl StrictOraclePrice {
         Self {
             current: price,
             twap_5min: None,
+            confidence_adjustment: 0,
         }
     }
 }
@@ -460,7 +764,18 @@ pub struct PrelaunchOracle {
     // amm.last_update_slot at time oracle was updated
     pub amm_last_update_slot: u64,
     pub perp_market_index: u16,
-    pub padding: [u8; 70],
+    pub padding1: [u8; 2],
+    /// max move in price per slot for fill twap oracles, 0 is unbounded
+    /// precision: PERCENTAGE_PRECISION
+    pub max_price_change_per_slot: u32,
+    /// volume weighted fill price, decays over an hour
+    /// precision: PRICE_PRECISION
+    pub fill_vwap: i64,
+    /// quote volume behind fill_vwap
+    /// precision: QUOTE_PRECISION
+    pub fill_vwap_volume: u64,
+    pub last_fill_ts: i64,
+    pub padding: [u8; 40],
 }
 
 impl Default for PrelaunchOracle {
@@ -472,7 +787,12 @@ impl Default for PrelaunchOracle {
             last_update_slot: 0,
             amm_last_update_slot: 0,
             perp_market_index: 0,
-            padding: [0; 70],
+            padding1: [0; 2],
+            max_price_change_per_slot: 0,
+            fill_vwap: 0,
+            fill_vwap_volume: 0,
+            last_fill_ts: 0,
+            padding: [0; 40],
         }
     }
 }
@@ -483,6 +803,10 @@ impl Size for PrelaunchOracle {
 
 impl PrelaunchOracle {
     pub fn update(&mut self, perp_market: &PerpMarket, slot: u64) -> DriftResult {
+        if perp_market.amm.oracle_source == OracleSource::FillTwap {
+            return self.update_from_fills(perp_market, slot);
+        }
+
         let last_twap = perp_market.amm.last_mark_price_twap.cast::<i64>()?;
         let new_price = if self.max_price <= last_twap {
             msg!(
@@ -520,6 +844,110 @@ impl PrelaunchOracle {
         Ok(())
     }
 
+    /// price is the median of the mark twap, the bid/ask twap midpoint and the fill vwap,
+    /// moving at most max_price_change_per_slot per slot towards it
+    fn update_from_fills(&mut self, perp_market: &PerpMarket, slot: u64) -> DriftResult {
+        let mark_twap = perp_market.amm.last_mark_price_twap.cast::<i64>()?;
+        let bid_twap = perp_market.amm.last_bid_price_twap.cast::<i64>()?;
+        let ask_twap = perp_market.amm.last_ask_price_twap.cast::<i64>()?;
+        let bid_ask_mid_twap = bid_twap.safe_add(ask_twap)?.safe_div(2)?;
+
+        // without recent fills, the mark twap takes the vwap's place in the median
+        let fill_vwap = if self.fill_vwap_volume > 0 && self.fill_vwap > 0 {
+            self.fill_vwap
+        } else {
+            mark_twap
+        };
+
+        let mut prices = [mark_twap, bid_ask_mid_twap, fill_vwap];
+        prices.sort_unstable();
+        let target_price = if self.max_price <= prices[1] {
+            msg!(
+                "target price {} >= max price {}, using max",
+                prices[1],
+                self.max_price
+            );
+            self.max_price
+        } else {
+            prices[1]
+        };
+
+        let new_price = if self.max_price_change_per_slot == 0 || self.price <= 0 {
+            target_price
+        } else {
+            let slots_elapsed = slot.saturating_sub(self.last_update_slot);
+            let max_price_change = self
+                .price
+                .unsigned_abs()
+                .cast::<u128>()?
+                .safe_mul(self.max_price_change_per_slot.cast()?)?
+                .safe_mul(slots_elapsed.cast()?)?
+                .safe_div(PERCENTAGE_PRECISION)?;
+
+            let price_change = target_price
+                .safe_sub(self.price)?
+                .unsigned_abs()
+                .cast::<u128>()?
+                .min(max_price_change)
+                .cast::<i64>()?;
+
+            if target_price > self.price {
+                self.price.safe_add(price_change)?
+            } else {
+                self.price.safe_sub(price_change)?
+            }
+        };
+
+        self.price = new_price;
+
+        let spread_twap = ask_twap.safe_sub(bid_twap)?.unsigned_abs();
+        let fill_divergence = fill_vwap.safe_sub(mark_twap)?.unsigned_abs();
+        let clamped_distance = target_price.safe_sub(new_price)?.unsigned_abs();
+
+        self.confidence = spread_twap
+            .max(perp_market.amm.mark_std)
+            .max(fill_divergence)
+            .max(clamped_distance);
+
+        self.amm_last_update_slot = perp_market.amm.last_update_slot;
+        self.last_update_slot = slot;
+
+        msg!(
+            "setting price = {} confidence = {} target = {}",
+            self.price,
+            self.confidence,
+            target_price
+        );
+
+        Ok(())
+    }
+
+    /// fold a fill into the volume weighted fill price. prior volume decays linearly over an hour
+    pub fn update_fill_vwap(
+        &mut self,
+        fill_price: u64,
+        quote_asset_amount: u64,
+        now: i64,
+    ) -> DriftResult {
+        if quote_asset_amount == 0 {
+            return Ok(());
+        }
+
+        let since_last = now.safe_sub(self.last_fill_ts)?.max(0);
+        let decayed_volume = calculate_rolling_sum(self.fill_vwap_volume, 0, since_last, ONE_HOUR)?;
+
+        self.fill_vwap = calculate_weighted_average(
+            self.fill_vwap,
+            fill_price.cast()?,
+            decayed_volume.cast()?,
+            quote_asset_amount.cast()?,
+        )?;
+        self.fill_vwap_volume = decayed_volume.safe_add(quote_asset_amount)?;
+        self.last_fill_ts = now;
+
+        Ok(())
+    }
+
     pub fn validate(&self) -> DriftResult {
         validate!(self.price != 0, InvalidOracle, "price == 0",)?;
 
@@ -542,4 +970,207 @@ pub struct PrelaunchOracleParams {
     pub perp_market_index: u16,
     pub price: Option<i64>,
     pub max_price: Option<i64>,
+    pub max_price_change_per_slot: Option<u32>,
+}
+
+pub const ORACLE_AGGREGATOR_MAX_SOURCES: usize = 4;
+
+#[zero_copy(unsafe)]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct OracleAggregatorSource {
+    pub oracle: Pubkey,
+    pub oracle_source: OracleSource,
+    pub padding: [u8; 7],
+}
+
+#[account(zero_copy(unsafe))]
+#[derive(Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct OracleAggregator {
+    /// underlying feeds, every source must be passed to aggregate
+    pub sources: [OracleAggregatorSource; 4],
+    /// last aggregated price
+    /// precision: PRICE_PRECISION
+    pub price: i64,
+    /// last aggregated confidence
+    /// precision: PRICE_PRECISION
+    pub confidence: u64,
+    /// slot of the last aggregation
+    pub last_update_slot: u64,
+    /// delay of the median source at the last aggregation
+    pub delay: i64,
+    pub aggregator_id: u16,
+    pub num_sources: u8,
+    /// bitmask of the sources that contributed to the last aggregation
+    pub contributing_sources: u8,
+    /// minimum number of valid sources for an aggregation
+    pub min_valid_sources: u8,
+    pub padding: [u8; 59],
+}
+
+impl Default for OracleAggregator {
+    fn default() -> Self {
+        OracleAggregator {
+            sources: [OracleAggregatorSource::default(); 4],
+            price: 0,
+            confidence: 0,
+            last_update_slot: 0,
+            delay: 0,
+            aggregator_id: 0,
+            num_sources: 0,
+            contributing_sources: 0,
+            min_valid_sources: 0,
+            padding: [0; 59],
+        }
+    }
+}
+
+impl Size for OracleAggregator {
+    const SIZE: usize = 256 + 8;
+}
+
+impl OracleAggregator {
+    pub fn get_sources(&self) -> &[OracleAggregatorSource] {
+        &self.sources[..(self.num_sources as usize).min(ORACLE_AGGREGATOR_MAX_SOURCES)]
+    }
+
+    pub fn set_sources(&mut self, sources: &[OracleAggregatorSourceParams]) -> DriftResult {
+        validate!(
+            !sources.is_empty() && sources.len() <= ORACLE_AGGREGATOR_MAX_SOURCES,
+            InvalidOracle,
+            "number of sources must be in [1, {}]",
+            ORACLE_AGGREGATOR_MAX_SOURCES
+        )?;
+
+        for (i, source) in sources.iter().enumerate() {
+            validate!(
+                !matches!(
+                    source.oracle_source,
+                    OracleSource::Aggregated | OracleSource::QuoteAsset
+                ),
+                InvalidOracle,
+                "invalid oracle source {:?} for aggregator",
+                source.oracle_source
+            )?;
+
+            source.oracle_source.validate_available()?;
+
+            validate!(
+                source.oracle != Pubkey::default(),
+                InvalidOracle,
+                "oracle {} is default pubkey",
+                i
+            )?;
+
+            validate!(
+                !sources[..i].iter().any(|s| s.oracle == source.oracle),
+                InvalidOracle,
+                "duplicate oracle {}",
+                source.oracle
+            )?;
+        }
+
+        self.sources = [OracleAggregatorSource::default(); ORACLE_AGGREGATOR_MAX_SOURCES];
+        for (i, source) in sources.iter().enumerate() {
+            self.sources[i] = OracleAggregatorSource {
+                oracle: source.oracle,
+                oracle_source: source.oracle_source,
+                padding: [0; 7],
+            };
+        }
+        self.num_sources = sources.len().cast()?;
+
+        Ok(())
+    }
+
+    pub fn set_min_valid_sources(&mut self, min_valid_sources: u8) -> DriftResult {
+        validate!(
+            min_valid_sources > 0 && min_valid_sources <= self.num_sources,
+            InvalidOracle,
+            "min valid sources {} must be in [1, {}]",
+            min_valid_sources,
+            self.num_sources
+        )?;
+
+        self.min_valid_sources = min_valid_sources;
+
+        Ok(())
+    }
+
+    pub fn update(
+        &mut self,
+        oracle_price_data: &OraclePriceData,
+        contributing_sources: u8,
+        slot: u64,
+    ) -> DriftResult {
+        self.price = oracle_price_data.price;
+        self.confidence = oracle_price_data.confidence;
+        self.delay = oracle_price_data.delay;
+        self.contributing_sources = contributing_sources;
+        self.last_update_slot = slot;
+
+        msg!(
+            "setting price = {} confidence = {} contributing sources = {:#06b}",
+            self.price,
+            self.confidence,
+            self.contributing_sources
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, AnchorSerialize, AnchorDeserialize, PartialEq, Eq)]
+pub struct OracleAggregatorSourceParams {
+    pub oracle: Pubkey,
+    pub oracle_source: OracleSource,
+}
+
+#[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize, PartialEq, Eq)]
+pub struct OracleAggregatorParams {
+    pub aggregator_id: u16,
+    pub sources: Vec<OracleAggregatorSourceParams>,
+    pub min_valid_sources: u8,
+}
+
+#[account(zero_copy(unsafe))]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct SignedPriceAccount {
+    /// signer allowed to post prices
+    pub authority: Pubkey,
+    /// precision: 10^-exponent
+    pub price: i64,
+    /// precision: 10^-exponent
+    pub confidence: u64,
+    /// slot the last price was posted
+    pub posted_slot: u64,
+    pub exponent: i32,
+    pub feed_id: u16,
+    pub padding: [u8; 2],
+}
+
+impl Size for SignedPriceAccount {
+    const SIZE: usize = 64 + 8;
+}
+
+impl SignedPriceAccount {
+    pub fn post(&mut self, price: i64, confidence: u64, exponent: i32, slot: u64) -> DriftResult {
+        validate!(price > 0, InvalidOracle, "price {} <= 0", price)?;
+
+        validate!(
+            (-12..=0).contains(&exponent),
+            InvalidOracle,
+            "exponent {} must be in [-12, 0]",
+            exponent
+        )?;
+
+        self.price = price;
+        self.confidence = confidence;
+        self.exponent = exponent;
+        self.posted_slot = slot;
+
+        Ok(())
+    }
 }
@@ -1,10 +1,19 @@
 use solana_program::pubkey::Pubkey;
 use std::str::FromStr;
 
-use crate::create_account_info;
-use crate::state::oracle::{get_oracle_price, OracleSource};
-use crate::state::perp_market::AMM;
+use crate::math::constants::PRICE_PRECISION_I64;
+use crate::state::oracle::{
+    get_oracle_price, OracleAggregator, OracleAggregatorSourceParams, OracleCircuitBreaker,
+    OracleCircuitBreakerWindow, OracleSource, PrelaunchOracle, SignedPriceAccount,
+};
+use crate::state::oracle_map::OracleMap;
+use crate::state::paused_operations::PerpOperation;
+use crate::state::perp_market::{PerpMarket, AMM};
+use crate::state::state::{OracleGuardRails, ValidityGuardRailsOverride};
+use crate::state::user::MarketType;
 use crate::test_utils::*;
+use crate::{create_account_info, create_anchor_account_info};
+use anchor_lang::Owner;
 
 #[test]
 fn pyth_1k() {
@@ -126,3 +135,553 @@ fn switchboard_on_demand() {
     let twap = amm.get_oracle_twap(&sol_oracle_info, 0).unwrap();
     assert_eq!(twap, Some(169350629));
 }
+
+#[test]
+fn oracle_aggregator() {
+    let pyth_program = crate::ids::pyth_program::id();
+
+    let oracle_keys = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+
+    let mut oracle_price_1 = get_hardcoded_pyth_price(100_000_000, 6);
+    oracle_price_1.agg.conf = 10_000;
+    oracle_price_1.valid_slot = 20;
+    create_account_info!(
+        oracle_price_1,
+        &oracle_keys[0],
+        &pyth_program,
+        oracle_account_info_1
+    );
+
+    let mut oracle_price_2 = get_hardcoded_pyth_price(101_000_000, 6);
+    oracle_price_2.agg.conf = 10_000;
+    oracle_price_2.valid_slot = 10;
+    create_account_info!(
+        oracle_price_2,
+        &oracle_keys[1],
+        &pyth_program,
+        oracle_account_info_2
+    );
+
+    let mut oracle_price_3 = get_hardcoded_pyth_price(150_000_000, 6);
+    oracle_price_3.agg.conf = 10_000;
+    oracle_price_3.valid_slot = 20;
+    create_account_info!(
+        oracle_price_3,
+        &oracle_keys[2],
+        &pyth_program,
+        oracle_account_info_3
+    );
+
+    let mut oracle_aggregator = OracleAggregator {
+        price: 99_000_000,
+        confidence: 10_000,
+        last_update_slot: 15,
+        delay: 1,
+        ..OracleAggregator::default()
+    };
+    let sources = oracle_keys
+        .iter()
+        .map(|oracle| OracleAggregatorSourceParams {
+            oracle: *oracle,
+            oracle_source: OracleSource::Pyth,
+        })
+        .collect::<Vec<_>>();
+    oracle_aggregator.set_sources(&sources).unwrap();
+    oracle_aggregator.set_min_valid_sources(2).unwrap();
+
+    let mut invalid_oracle_aggregator = OracleAggregator::default();
+    assert!(invalid_oracle_aggregator
+        .set_sources(&[sources[0], sources[0]])
+        .is_err());
+    assert!(invalid_oracle_aggregator
+        .set_sources(&[OracleAggregatorSourceParams {
+            oracle: Pubkey::new_unique(),
+            oracle_source: OracleSource::Aggregated,
+        }])
+        .is_err());
+    assert!(invalid_oracle_aggregator.set_min_valid_sources(1).is_err());
+    invalid_oracle_aggregator.set_sources(&sources).unwrap();
+    assert!(invalid_oracle_aggregator.set_min_valid_sources(0).is_err());
+    assert!(invalid_oracle_aggregator.set_min_valid_sources(4).is_err());
+
+    let oracle_aggregator_key = Pubkey::new_unique();
+    create_anchor_account_info!(
+        oracle_aggregator,
+        &oracle_aggregator_key,
+        OracleAggregator,
+        oracle_aggregator_account_info
+    );
+
+    let account_infos = vec![
+        oracle_aggregator_account_info.clone(),
+        oracle_account_info_1,
+        oracle_account_info_2,
+        oracle_account_info_3,
+    ];
+
+    // all sources valid, median
+    let mut oracle_map = OracleMap::load(
+        &mut account_infos.iter().peekable(),
+        20,
+        Some(OracleGuardRails::default()),
+    )
+    .unwrap();
+    let oracle_price_data = oracle_map.get_price_data(&oracle_aggregator_key).unwrap();
+    assert_eq!(oracle_price_data.price, 101_000_000);
+    assert_eq!(oracle_price_data.delay, 10);
+
+    // median source stale, falls back to the remaining sources
+    let oracle_map = OracleMap::load(
+        &mut account_infos.iter().peekable(),
+        25,
+        Some(OracleGuardRails::default()),
+    )
+    .unwrap();
+    let (oracle_price_data, contributing_sources) = oracle_map
+        .get_aggregated_price_data(&oracle_aggregator, &ValidityGuardRailsOverride::default())
+        .unwrap()
+        .unwrap();
+    assert_eq!(oracle_price_data.price, 100_000_000);
+    assert_eq!(contributing_sources, 0b101);
+
+    // market overriding the staleness guard rails still counts the median source
+    let mut oracle_map = OracleMap::load(
+        &mut account_infos.iter().peekable(),
+        25,
+        Some(OracleGuardRails::default()),
+    )
+    .unwrap();
+    let guard_rails_override = ValidityGuardRailsOverride {
+        slots_before_stale_for_amm: 20,
+        ..ValidityGuardRailsOverride::default()
+    };
+    let (oracle_price_data, _) = oracle_map
+        .get_price_data_and_validity(
+            MarketType::Perp,
+            0,
+            &oracle_aggregator_key,
+            101_000_000,
+            &guard_rails_override,
+            1,
+        )
+        .unwrap();
+    assert_eq!(oracle_price_data.price, 101_000_000);
+    let oracle_price_data = oracle_map.get_price_data(&oracle_aggregator_key).unwrap();
+    assert_eq!(oracle_price_data.price, 101_000_000);
+    let (oracle_price_data, _) = oracle_map
+        .get_price_data_and_validity(
+            MarketType::Perp,
+            1,
+            &oracle_aggregator_key,
+            100_000_000,
+            &ValidityGuardRailsOverride::default(),
+            1,
+        )
+        .unwrap();
+    assert_eq!(oracle_price_data.price, 100_000_000);
+
+    // median source stale and quorum of 3, fails
+    let mut strict_oracle_aggregator = oracle_aggregator;
+    strict_oracle_aggregator.set_min_valid_sources(3).unwrap();
+    assert!(oracle_map
+        .get_aggregated_price_data(
+            &strict_oracle_aggregator,
+            &ValidityGuardRailsOverride::default()
+        )
+        .is_err());
+
+    // a source not passed, fails instead of skipping it
+    let partial_account_infos = [
+        oracle_aggregator_account_info.clone(),
+        account_infos[1].clone(),
+        account_infos[2].clone(),
+    ];
+    let oracle_map = OracleMap::load(
+        &mut partial_account_infos.iter().peekable(),
+        20,
+        Some(OracleGuardRails::default()),
+    )
+    .unwrap();
+    assert!(oracle_map
+        .get_aggregated_price_data(&oracle_aggregator, &ValidityGuardRailsOverride::default())
+        .is_err());
+
+    // underlying sources not passed, uses last stored aggregation
+    let mut oracle_map = OracleMap::load_one(
+        &oracle_aggregator_account_info,
+        25,
+        Some(OracleGuardRails::default()),
+    )
+    .unwrap();
+    let oracle_price_data = oracle_map.get_price_data(&oracle_aggregator_key).unwrap();
+    assert_eq!(oracle_price_data.price, 99_000_000);
+    assert_eq!(oracle_price_data.delay, 11);
+}
+
+#[test]
+fn chainlink() {
+    let mut data = vec![0_u8; 8 + 192 + 3 * 48];
+    let header = 8;
+    data[header + 130] = 8; // decimals
+    data[header + 135..header + 139].copy_from_slice(&5_u32.to_le_bytes()); // latest_round_id
+    data[header + 140..header + 144].copy_from_slice(&3_u32.to_le_bytes()); // live_length
+    data[header + 144..header + 148].copy_from_slice(&1_u32.to_le_bytes()); // live_cursor
+
+    let write_transmission = |data: &mut Vec<u8>, index: usize, slot: u64, answer: i128| {
+        let offset = 8 + 192 + index * 48;
+        data[offset..offset + 8].copy_from_slice(&slot.to_le_bytes());
+        data[offset + 16..offset + 32].copy_from_slice(&answer.to_le_bytes());
+    };
+    // latest transmission is the one before live_cursor
+    write_transmission(&mut data, 0, 100, 2_512_345_678);
+    write_transmission(&mut data, 2, 90, 2_400_000_000);
+
+    let oracle_key = Pubkey::new_unique();
+    let chainlink_program = crate::ids::chainlink_store_program::id();
+    let mut lamports = 0;
+    let oracle_account_info = create_account_info(
+        &oracle_key,
+        true,
+        &mut lamports,
+        &mut data[..],
+        &chainlink_program,
+    );
+
+    let oracle_price_data =
+        get_oracle_price(&OracleSource::Chainlink, &oracle_account_info, 105).unwrap();
+    assert_eq!(oracle_price_data.price, 25_123_456);
+    // move from the previous round
+    assert_eq!(oracle_price_data.confidence, 1_123_456);
+    assert_eq!(oracle_price_data.delay, 5);
+    assert!(oracle_price_data.has_sufficient_number_of_data_points);
+
+    let mut oracle_map = OracleMap::load_one(&oracle_account_info, 105, None).unwrap();
+    let oracle_price_data = oracle_map.get_price_data(&oracle_key).unwrap();
+    assert_eq!(oracle_price_data.price, 25_123_456);
+
+    // price unchanged from the previous round, confidence floored at 10 bps of price
+    write_transmission(&mut data, 2, 90, 2_512_345_678);
+    let oracle_account_info = create_account_info(
+        &oracle_key,
+        true,
+        &mut lamports,
+        &mut data[..],
+        &chainlink_program,
+    );
+    let oracle_price_data =
+        get_oracle_price(&OracleSource::Chainlink, &oracle_account_info, 105).unwrap();
+    assert_eq!(oracle_price_data.confidence, 25_123);
+
+    // first round, no previous round to derive a confidence from
+    let mut data = vec![0_u8; 8 + 192 + 3 * 48];
+    data[header + 130] = 8; // decimals
+    data[header + 135..header + 139].copy_from_slice(&1_u32.to_le_bytes()); // latest_round_id
+    data[header + 140..header + 144].copy_from_slice(&3_u32.to_le_bytes()); // live_length
+    data[header + 144..header + 148].copy_from_slice(&1_u32.to_le_bytes()); // live_cursor
+    write_transmission(&mut data, 0, 100, 2_512_345_678);
+    let oracle_account_info = create_account_info(
+        &oracle_key,
+        true,
+        &mut lamports,
+        &mut data[..],
+        &chainlink_program,
+    );
+    let oracle_price_data =
+        get_oracle_price(&OracleSource::Chainlink, &oracle_account_info, 105).unwrap();
+    assert_eq!(oracle_price_data.price, 25_123_456);
+    assert!(!oracle_price_data.has_sufficient_number_of_data_points);
+
+    // live length larger than the account
+    data[header + 140..header + 144].copy_from_slice(&4_u32.to_le_bytes());
+    let oracle_account_info = create_account_info(
+        &oracle_key,
+        true,
+        &mut lamports,
+        &mut data[..],
+        &chainlink_program,
+    );
+    assert!(get_oracle_price(&OracleSource::Chainlink, &oracle_account_info, 105).is_err());
+
+    // no transmissions yet
+    let mut data = vec![0_u8; 8 + 192 + 3 * 48];
+    let oracle_account_info = create_account_info(
+        &oracle_key,
+        true,
+        &mut lamports,
+        &mut data[..],
+        &chainlink_program,
+    );
+    assert!(get_oracle_price(&OracleSource::Chainlink, &oracle_account_info, 105).is_err());
+}
+
+#[test]
+fn signed_price() {
+    let mut signed_price_account = SignedPriceAccount::default();
+    assert!(signed_price_account.post(0, 1, -2, 10).is_err());
+    assert!(signed_price_account.post(12345, 1, 2, 10).is_err());
+    assert!(signed_price_account.post(12345, 1, -13, 10).is_err());
+    signed_price_account.post(12345, 5, -2, 10).unwrap();
+
+    let oracle_key = Pubkey::new_unique();
+    create_anchor_account_info!(
+        signed_price_account,
+        &oracle_key,
+        SignedPriceAccount,
+        oracle_account_info
+    );
+
+    let oracle_price_data =
+        get_oracle_price(&OracleSource::SignedPrice, &oracle_account_info, 12).unwrap();
+    assert_eq!(oracle_price_data.price, 123_450_000);
+    assert_eq!(oracle_price_data.confidence, 50_000);
+    assert_eq!(oracle_price_data.delay, 2);
+
+    let mut oracle_map = OracleMap::load_one(&oracle_account_info, 12, None).unwrap();
+    let oracle_price_data = oracle_map.get_price_data(&oracle_key).unwrap();
+    assert_eq!(oracle_price_data.price, 123_450_000);
+
+    // not available in mainnet builds
+    assert_eq!(
+        OracleSource::SignedPrice.is_available(),
+        cfg!(feature = "signed-price-oracle")
+    );
+    assert_eq!(
+        OracleSource::SignedPrice.validate_available().is_ok(),
+        cfg!(feature = "signed-price-oracle")
+    );
+    assert!(OracleSource::Chainlink.validate_available().is_ok());
+}
+
+#[test]
+fn fill_twap() {
+    let mut oracle = PrelaunchOracle {
+        price: 100_000_000,
+        max_price: 1_000_000_000,
+        max_price_change_per_slot: 10_000, // 1%
+        last_update_slot: 10,
+        ..PrelaunchOracle::default()
+    };
+
+    oracle
+        .update_fill_vwap(110_000_000, 100_000_000, 0)
+        .unwrap();
+    assert_eq!(oracle.fill_vwap, 110_000_000);
+    assert_eq!(oracle.fill_vwap_volume, 100_000_000);
+
+    // half an hour later, prior volume has decayed by half
+    oracle
+        .update_fill_vwap(120_000_000, 100_000_000, 1800)
+        .unwrap();
+    assert_eq!(oracle.fill_vwap, 116_666_667);
+    assert_eq!(oracle.fill_vwap_volume, 150_000_000);
+    assert_eq!(oracle.last_fill_ts, 1800);
+
+    let perp_market = PerpMarket {
+        amm: AMM {
+            oracle_source: OracleSource::FillTwap,
+            last_mark_price_twap: 115_000_000,
+            last_bid_price_twap: 110_000_000,
+            last_ask_price_twap: 118_000_000,
+            last_update_slot: 12,
+            ..AMM::default()
+        },
+        ..PerpMarket::default()
+    };
+
+    // median is the mark twap, but price can only move 1% per slot
+    oracle.update(&perp_market, 12).unwrap();
+    assert_eq!(oracle.price, 102_000_000);
+    assert_eq!(oracle.confidence, 13_000_000);
+    assert_eq!(oracle.last_update_slot, 12);
+
+    // no movement within the same slot
+    oracle.update(&perp_market, 12).unwrap();
+    assert_eq!(oracle.price, 102_000_000);
+
+    oracle.max_price_change_per_slot = 0;
+    oracle.update(&perp_market, 13).unwrap();
+    assert_eq!(oracle.price, 115_000_000);
+    assert_eq!(oracle.confidence, 8_000_000);
+
+    oracle.max_price = 112_000_000;
+    oracle.update(&perp_market, 14).unwrap();
+    assert_eq!(oracle.price, 112_000_000);
+
+    let oracle_key = Pubkey::new_unique();
+    create_anchor_account_info!(oracle, &oracle_key, PrelaunchOracle, oracle_account_info);
+
+    let oracle_price_data =
+        get_oracle_price(&OracleSource::FillTwap, &oracle_account_info, 14).unwrap();
+    assert_eq!(oracle_price_data.price, 112_000_000);
+}
+
+#[test]
+fn oracle_circuit_breaker() {
+    let mut perp_market = PerpMarket {
+        oracle_circuit_breaker: OracleCircuitBreaker {
+            threshold: 500, // 5%
+            cooldown: 60,
+            ..OracleCircuitBreaker::default()
+        },
+        ..PerpMarket::default()
+    };
+
+    let twap_5min = 100 * PRICE_PRECISION_I64;
+
+    // within threshold
+    let result = perp_market
+        .oracle_circuit_breaker
+        .update(104 * PRICE_PRECISION_I64, twap_5min, 1000)
+        .unwrap();
+    assert_eq!(result, None);
+    assert!(!perp_market.is_reduce_only().unwrap());
+
+    // 6% drop trips the breaker
+    let result = perp_market
+        .oracle_circuit_breaker
+        .update(94 * PRICE_PRECISION_I64, twap_5min, 1001)
+        .unwrap();
+    assert_eq!(result, Some(true));
+    assert_eq!(perp_market.oracle_circuit_breaker.tripped_ts, 1001);
+    assert!(perp_market.is_reduce_only().unwrap());
+    assert!(perp_market.is_operation_paused(PerpOperation::ReduceOnly));
+    assert!(!perp_market.is_operation_paused(PerpOperation::Fill));
+    assert_eq!(perp_market.paused_operations, 0);
+
+    // another breach extends the trip without a new event
+    let result = perp_market
+        .oracle_circuit_breaker
+        .update(107 * PRICE_PRECISION_I64, twap_5min, 1030)
+        .unwrap();
+    assert_eq!(result, None);
+    assert_eq!(perp_market.oracle_circuit_breaker.tripped_ts, 1030);
+
+    // back within threshold but cooldown not over
+    let result = perp_market
+        .oracle_circuit_breaker
+        .update(101 * PRICE_PRECISION_I64, twap_5min, 1089)
+        .unwrap();
+    assert_eq!(result, None);
+    assert!(perp_market.is_reduce_only().unwrap());
+
+    // cooldown over
+    let result = perp_market
+        .oracle_circuit_breaker
+        .update(101 * PRICE_PRECISION_I64, twap_5min, 1090)
+        .unwrap();
+    assert_eq!(result, Some(false));
+    assert!(!perp_market.is_reduce_only().unwrap());
+
+    // disabling resets a tripped breaker
+    perp_market
+        .oracle_circuit_breaker
+        .update(80 * PRICE_PRECISION_I64, twap_5min, 1100)
+        .unwrap();
+    assert!(perp_market.oracle_circuit_breaker.is_tripped());
+    perp_market.oracle_circuit_breaker.threshold = 0;
+    let result = perp_market
+        .oracle_circuit_breaker
+        .update(80 * PRICE_PRECISION_I64, twap_5min, 1101)
+        .unwrap();
+    assert_eq!(result, Some(false));
+    assert!(!perp_market.is_reduce_only().unwrap());
+}
+
+#[test]
+fn oracle_circuit_breaker_window() {
+    let mut perp_market = PerpMarket {
+        oracle_circuit_breaker: OracleCircuitBreaker {
+            threshold: 500, // 5%
+            cooldown: 60,
+            ..OracleCircuitBreaker::default()
+        },
+        ..PerpMarket::default()
+    };
+
+    let mut window = OracleCircuitBreakerWindow {
+        window_slots: 10,
+        market_type: MarketType::Perp,
+        ..OracleCircuitBreakerWindow::default()
+    };
+
+    // first update sets the reference price
+    let result = window
+        .update(
+            &mut perp_market.oracle_circuit_breaker,
+            100 * PRICE_PRECISION_I64,
+            100,
+            1000,
+        )
+        .unwrap();
+    assert_eq!(result, None);
+    assert_eq!(window.reference_price, 100 * PRICE_PRECISION_I64);
+    assert_eq!(window.reference_slot, 100);
+
+    // within threshold of the reference
+    let result = window
+        .update(
+            &mut perp_market.oracle_circuit_breaker,
+            104 * PRICE_PRECISION_I64,
+            105,
+            1002,
+        )
+        .unwrap();
+    assert_eq!(result, None);
+    assert_eq!(window.reference_price, 100 * PRICE_PRECISION_I64);
+
+    // 6% move after the window rolls over the reference instead of tripping
+    let result = window
+        .update(
+            &mut perp_market.oracle_circuit_breaker,
+            106 * PRICE_PRECISION_I64,
+            111,
+            1004,
+        )
+        .unwrap();
+    assert_eq!(result, None);
+    assert!(!perp_market.is_reduce_only().unwrap());
+    assert_eq!(window.reference_price, 106 * PRICE_PRECISION_I64);
+    assert_eq!(window.reference_slot, 111);
+
+    // 6% drop within the window trips the breaker
+    let result = window
+        .update(
+            &mut perp_market.oracle_circuit_breaker,
+            99 * PRICE_PRECISION_I64,
+            121,
+            1008,
+        )
+        .unwrap();
+    assert_eq!(result, Some(true));
+    assert_eq!(perp_market.oracle_circuit_breaker.tripped_ts, 1008);
+    assert!(perp_market.is_reduce_only().unwrap());
+
+    // another breach extends the trip without a new event
+    let result = window
+        .update(
+            &mut perp_market.oracle_circuit_breaker,
+            98 * PRICE_PRECISION_I64,
+            121,
+            1010,
+        )
+        .unwrap();
+    assert_eq!(result, None);
+    assert_eq!(perp_market.oracle_circuit_breaker.tripped_ts, 1010);
+
+    // disabled breaker never trips
+    perp_market.oracle_circuit_breaker = OracleCircuitBreaker::default();
+    let result = window
+        .update(
+            &mut perp_market.oracle_circuit_breaker,
+            50 * PRICE_PRECISION_I64,
+            121,
+            1012,
+        )
+        .unwrap();
+    assert_eq!(result, None);
+    assert!(!perp_market.is_reduce_only().unwrap());
+}
@@ -1,20 +1,25 @@
 use crate::error::ErrorCode::UnableToLoadOracle;
 use crate::error::{DriftResult, ErrorCode};
 use crate::ids::{
-    bonk_oracle, bonk_pull_oracle, drift_oracle_receiver_program, pepe_oracle, pepe_pull_oracle,
-    pyth_program, switchboard_program, usdc_oracle, usdc_pull_oracle, usdt_oracle,
-    usdt_pull_oracle, wen_oracle, wen_pull_oracle,
+    bonk_oracle, bonk_pull_oracle, chainlink_store_program, drift_oracle_receiver_program,
+    pepe_oracle, pepe_pull_oracle, pyth_program, switchboard_program, usdc_oracle,
+    usdc_pull_oracle, usdt_oracle, usdt_pull_oracle, wen_oracle, wen_pull_oracle,
 };
 use crate::math::constants::PRICE_PRECISION_I64;
-use crate::math::oracle::{oracle_validity, OracleValidity};
-use crate::state::oracle::{get_oracle_price, OraclePriceData, OracleSource, PrelaunchOracle};
-use crate::state::state::OracleGuardRails;
+use crate::math::oracle::{calculate_aggregated_oracle_price, oracle_validity, OracleValidity};
+use crate::state::load_ref::load_ref;
+use crate::state::oracle::{
+    get_oracle_price, OracleAggregator, OraclePriceData, OracleSource, PrelaunchOracle,
+    SignedPriceAccount,
+};
+use crate::state::state::{OracleGuardRails, ValidityGuardRailsOverride};
 use crate::state::user::MarketType;
 use anchor_lang::prelude::{AccountInfo, Pubkey};
 use anchor_lang::Discriminator;
 use anchor_lang::Key;
 use arrayref::array_ref;
 use solana_program::msg;
+use std::cell::Ref;
 use std::collections::BTreeMap;
 use std::iter::Peekable;
 use std::slice::Iter;
@@ -43,6 +48,8 @@ pub struct OracleMap<'a> {
     oracles: BTreeMap<Pubkey, AccountInfoAndOracleSource<'a>>,
     price_data: BTreeMap<Pubkey, OraclePriceData>,
     validity: BTreeMap<Pubkey, OracleValidity>,
+    /// guard rails override each cached aggregated price was computed with
+    aggregation_guard_rails: BTreeMap<Pubkey, ValidityGuardRailsOverride>,
     pub slot: u64,
     pub oracle_guard_rails: OracleGuardRails,
     pub quote_asset_price_data: OraclePriceData,
@@ -75,20 +82,7 @@ impl<'a> OracleMap<'a> {
             return self.price_data.get(pubkey).safe_unwrap();
         }
 
-        let (account_info, oracle_source) = match self.oracles.get(pubkey) {
-            Some(AccountInfoAndOracleSource {
-                account_info,
-                oracle_source,
-            }) => (account_info, oracle_source),
-            None => {
-                msg!("oracle pubkey not found in oracle_map: {}", pubkey);
-                return Err(ErrorCode::OracleNotFound);
-            }
-        };
-
-        let price_data = get_oracle_price(oracle_source, account_info, self.slot)?;
-
-        self.price_data.insert(*pubkey, price_data);
+        self.insert_price_data(pubkey, &ValidityGuardRailsOverride::default())?;
 
         self.price_data.get(pubkey).safe_unwrap()
     }
@@ -99,47 +93,44 @@ impl<'a> OracleMap<'a> {
         market_index: u16,
         pubkey: &Pubkey,
         last_oracle_price_twap: i64,
+        guard_rails_override: &ValidityGuardRailsOverride,
         max_confidence_interval_multiplier: u64,
     ) -> DriftResult<(&OraclePriceData, OracleValidity)> {
         if self.should_get_quote_asset_price_data(pubkey) {
             return Ok((&self.quote_asset_price_data, OracleValidity::Valid));
         }
 
+        // validity is cached per oracle, so markets overriding the guard rails always recompute
+        let use_cache = !guard_rails_override.is_set();
+
+        self.clear_stale_aggregated_price_data(pubkey, guard_rails_override);
+
         if self.price_data.contains_key(pubkey) {
             let oracle_price_data = self.price_data.get(pubkey).safe_unwrap()?;
 
-            let oracle_validity = if let Some(oracle_validity) = self.validity.get(pubkey) {
-                *oracle_validity
-            } else {
-                let oracle_validity = oracle_validity(
-                    market_type,
-                    market_index,
-                    last_oracle_price_twap,
-                    oracle_price_data,
-                    &self.oracle_guard_rails.validity,
-                    max_confidence_interval_multiplier,
-                    true,
-                )?;
-                self.validity.insert(*pubkey, oracle_validity);
-                oracle_validity
+            let oracle_validity = match self.validity.get(pubkey) {
+                Some(oracle_validity) if use_cache => *oracle_validity,
+                _ => {
+                    let oracle_validity = oracle_validity(
+                        market_type,
+                        market_index,
+                        last_oracle_price_twap,
+                        oracle_price_data,
+                        &self.oracle_guard_rails.validity,
+                        guard_rails_override,
+                        max_confidence_interval_multiplier,
+                        true,
+                    )?;
+                    if use_cache {
+                        self.validity.insert(*pubkey, oracle_validity);
+                    }
+                    oracle_validity
+                }
             };
             return Ok((oracle_price_data, oracle_validity));
         }
 
-        let (account_info, oracle_source) = match self.oracles.get(pubkey) {
-            Some(AccountInfoAndOracleSource {
-                account_info,
-                oracle_source,
-            }) => (account_info, oracle_source),
-            None => {
-                msg!("oracle pubkey not found in oracle_map: {}", pubkey);
-                return Err(ErrorCode::OracleNotFound);
-            }
-        };
-
-        let price_data = get_oracle_price(oracle_source, account_info, self.slot)?;
-
-        self.price_data.insert(*pubkey, price_data);
+        self.insert_price_data(pubkey, guard_rails_override)?;
 
         let oracle_price_data = self.price_data.get(pubkey).safe_unwrap()?;
         let oracle_validity = oracle_validity(
@@ -148,10 +139,13 @@ impl<'a> OracleMap<'a> {
             last_oracle_price_twap,
             oracle_price_data,
             &self.oracle_guard_rails.validity,
+            guard_rails_override,
             max_confidence_interval_multiplier,
             true,
         )?;
-        self.validity.insert(*pubkey, oracle_validity);
+        if use_cache {
+            self.validity.insert(*pubkey, oracle_validity);
+        }
 
         Ok((oracle_price_data, oracle_validity))
     }
@@ -172,6 +166,58 @@ impl<'a> OracleMap<'a> {
             return Ok((oracle_price_data, validity_guard_rails));
         }
 
+        self.insert_price_data(pubkey, &ValidityGuardRailsOverride::default())?;
+
+        let oracle_price_data = self.price_data.get(pubkey).safe_unwrap()?;
+        let validity_guard_rails = &self.oracle_guard_rails.validity;
+
+        Ok((oracle_price_data, validity_guard_rails))
+    }
+
+    /// Aggregated prices depend on the guard rails used to filter their sources, so a cached
+    /// aggregation computed under a different market override is dropped.
+    /// Lookups without a market (get_price_data) reuse whatever aggregation is cached
+    fn clear_stale_aggregated_price_data(
+        &mut self,
+        pubkey: &Pubkey,
+        guard_rails_override: &ValidityGuardRailsOverride,
+    ) {
+        if matches!(self.aggregation_guard_rails.get(pubkey), Some(aggregation_guard_rails) if aggregation_guard_rails != guard_rails_override)
+        {
+            self.price_data.remove(pubkey);
+            self.validity.remove(pubkey);
+            self.aggregation_guard_rails.remove(pubkey);
+        }
+    }
+
+    fn insert_price_data(
+        &mut self,
+        pubkey: &Pubkey,
+        guard_rails_override: &ValidityGuardRailsOverride,
+    ) -> DriftResult {
+        let price_data = self.get_oracle_price_data(pubkey, guard_rails_override)?;
+
+        self.price_data.insert(*pubkey, price_data);
+
+        if matches!(
+            self.oracles.get(pubkey),
+            Some(AccountInfoAndOracleSource {
+                oracle_source: OracleSource::Aggregated,
+                ..
+            })
+        ) {
+            self.aggregation_guard_rails
+                .insert(*pubkey, *guard_rails_override);
+        }
+
+        Ok(())
+    }
+
+    fn get_oracle_price_data(
+        &self,
+        pubkey: &Pubkey,
+        guard_rails_override: &ValidityGuardRailsOverride,
+    ) -> DriftResult<OraclePriceData> {
         let (account_info, oracle_source) = match self.oracles.get(pubkey) {
             Some(AccountInfoAndOracleSource {
                 account_info,
@@ -183,14 +229,74 @@ impl<'a> OracleMap<'a> {
             }
         };
 
-        let price_data = get_oracle_price(oracle_source, account_info, self.slot)?;
+        if *oracle_source != OracleSource::Aggregated {
+            return get_oracle_price(oracle_source, account_info, self.slot);
+        }
 
-        self.price_data.insert(*pubkey, price_data);
+        let aggregated_price_data = {
+            let aggregator: Ref<OracleAggregator> =
+                load_ref(account_info).or(Err(UnableToLoadOracle))?;
+            self.get_aggregated_price_data(&aggregator, guard_rails_override)?
+        };
 
-        let oracle_price_data = self.price_data.get(pubkey).safe_unwrap()?;
-        let validity_guard_rails = &self.oracle_guard_rails.validity;
+        match aggregated_price_data {
+            Some((price_data, contributing_sources)) => {
+                msg!(
+                    "oracle aggregator {} contributing sources = {:#06b}",
+                    pubkey,
+                    contributing_sources
+                );
+                Ok(price_data)
+            }
+            // underlying feeds not passed, use the last stored aggregation
+            None => get_oracle_price(oracle_source, account_info, self.slot),
+        }
+    }
 
-        Ok((oracle_price_data, validity_guard_rails))
+    /// Aggregates the aggregator's underlying feeds that are loaded in the map, filtering them
+    /// with the global guard rails and the market's override.
+    /// Returns None if none of them are and errors if only some of them are
+    pub fn get_aggregated_price_data(
+        &self,
+        aggregator: &OracleAggregator,
+        guard_rails_override: &ValidityGuardRailsOverride,
+    ) -> DriftResult<Option<(OraclePriceData, u8)>> {
+        let sources = aggregator.get_sources();
+
+        let num_sources_loaded = sources
+            .iter()
+            .filter(|source| self.oracles.contains_key(&source.oracle))
+            .count();
+
+        if num_sources_loaded == 0 {
+            return Ok(None);
+        }
+
+        validate!(
+            num_sources_loaded == sources.len(),
+            ErrorCode::OracleNotFound,
+            "only {} of {} oracle aggregator sources passed",
+            num_sources_loaded,
+            sources.len()
+        )?;
+
+        let source_price_data = sources
+            .iter()
+            .map(|source| {
+                self.oracles.get(&source.oracle).and_then(
+                    |AccountInfoAndOracleSource { account_info, .. }| {
+                        get_oracle_price(&source.oracle_source, account_info, self.slot).ok()
+                    },
+                )
+            })
+            .collect::<Vec<Option<OraclePriceData>>>();
+
+        calculate_aggregated_oracle_price(
+            &source_price_data,
+            aggregator.min_valid_sources,
+            &guard_rails_override.apply(&self.oracle_guard_rails.validity),
+        )
+        .map(Some)
     }
 
     pub fn load<'c>(
@@ -253,15 +359,12 @@ impl<'a> OracleMap<'a> {
                     UnableToLoadOracle
                 })?;
 
-                let expected_data_len = PrelaunchOracle::SIZE;
-                if data.len() < expected_data_len {
-                    break;
-                }
+                let oracle_source = match get_program_oracle_source(&data) {
+                    Some(oracle_source) => oracle_source,
+                    None => break,
+                };
 
-                let account_discriminator = array_ref![data, 0, 8];
-                if account_discriminator != &PrelaunchOracle::discriminator() {
-                    break;
-                }
+                drop(data);
 
                 let account_info = account_info_iter.next().safe_unwrap()?;
                 let pubkey = account_info.key();
@@ -270,7 +373,7 @@ impl<'a> OracleMap<'a> {
                     pubkey,
                     AccountInfoAndOracleSource {
                         account_info: account_info.clone(),
-                        oracle_source: OracleSource::Prelaunch,
+                        oracle_source,
                     },
                 );
 
@@ -300,6 +403,19 @@ impl<'a> OracleMap<'a> {
                     },
                 );
 
+                continue;
+            } else if account_info.owner == &chainlink_store_program::id() {
+                let account_info = account_info_iter.next().safe_unwrap()?;
+                let pubkey = account_info.key();
+
+                oracles.insert(
+                    pubkey,
+                    AccountInfoAndOracleSource {
+                        account_info: account_info.clone(),
+                        oracle_source: OracleSource::Chainlink,
+                    },
+                );
+
                 continue;
             }
 
@@ -316,6 +432,7 @@ impl<'a> OracleMap<'a> {
             oracles,
             price_data: BTreeMap::new(),
             validity: BTreeMap::new(),
+            aggregation_guard_rails: BTreeMap::new(),
             slot,
             oracle_guard_rails: ogr,
             quote_asset_price_data: OraclePriceData {
@@ -380,24 +497,20 @@ impl<'a> OracleMap<'a> {
                 UnableToLoadOracle
             })?;
 
-            let expected_data_len = PrelaunchOracle::SIZE;
-            if data.len() < expected_data_len {
-                msg!("Unexpected account data len loading oracle");
-                return Err(UnableToLoadOracle);
-            }
-
-            let account_discriminator = array_ref![data, 0, 8];
-            if account_discriminator != &PrelaunchOracle::discriminator() {
-                msg!("Unexpected account discriminator");
-                return Err(UnableToLoadOracle);
-            }
+            let oracle_source = match get_program_oracle_source(&data) {
+                Some(oracle_source) => oracle_source,
+                None => {
+                    msg!("Unexpected account data loading oracle");
+                    return Err(UnableToLoadOracle);
+                }
+            };
 
             let pubkey = account_info.key();
             oracles.insert(
                 pubkey,
                 AccountInfoAndOracleSource {
                     account_info: account_info.clone(),
-                    oracle_source: OracleSource::Prelaunch,
+                    oracle_source,
                 },
             );
         } else if account_info.owner == &switchboard_program::id() {
@@ -418,6 +531,15 @@ impl<'a> OracleMap<'a> {
                     oracle_source: OracleSource::SwitchboardOnDemand,
                 },
             );
+        } else if account_info.owner == &chainlink_store_program::id() {
+            let pubkey = account_info.key();
+            oracles.insert(
+                pubkey,
+                AccountInfoAndOracleSource {
+                    account_info: account_info.clone(),
+                    oracle_source: OracleSource::Chainlink,
+                },
+            );
         } else if account_info.key() != Pubkey::default() {
             return Err(ErrorCode::InvalidOracle);
         }
@@ -432,6 +554,7 @@ impl<'a> OracleMap<'a> {
             oracles,
             price_data: BTreeMap::new(),
             validity: BTreeMap::new(),
+            aggregation_guard_rails: BTreeMap::new(),
             slot,
             oracle_guard_rails: ogr,
             quote_asset_price_data: OraclePriceData {
@@ -456,6 +579,30 @@ impl<'a> OracleMap<'a> {
     }
 }
 
+/// oracle source for an oracle account owned by this program, None if it isn't one
+fn get_program_oracle_source(data: &[u8]) -> Option<OracleSource> {
+    if data.len() < 8 {
+        return None;
+    }
+
+    let account_discriminator = array_ref![data, 0, 8];
+    if data.len() >= PrelaunchOracle::SIZE
+        && account_discriminator == &PrelaunchOracle::discriminator()
+    {
+        Some(OracleSource::Prelaunch)
+    } else if data.len() >= OracleAggregator::SIZE
+        && account_discriminator == &OracleAggregator::discriminator()
+    {
+        Some(OracleSource::Aggregated)
+    } else if data.len() >= SignedPriceAccount::SIZE
+        && account_discriminator == &SignedPriceAccount::discriminator()
+    {
+        Some(OracleSource::SignedPrice)
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 impl<'a> OracleMap<'a> {
     pub fn empty() -> OracleMap<'a> {
@@ -463,6 +610,7 @@ impl<'a> OracleMap<'a> {
             oracles: BTreeMap::new(),
             validity: BTreeMap::new(),
             price_data: BTreeMap::new(),
+            aggregation_guard_rails: BTreeMap::new(),
             slot: 0,
             oracle_guard_rails: OracleGuardRails::default(),
             quote_asset_price_data: OraclePriceData {
@@ -11,15 +11,17 @@ pub enum PerpOperation {
     SettlePnl = 0b00001000,
     SettlePnlWithPosition = 0b00010000,
     Liquidation = 0b00100000,
+    ReduceOnly = 0b01000000,
 }
 
-const ALL_PERP_OPERATIONS: [PerpOperation; 6] = [
+const ALL_PERP_OPERATIONS: [PerpOperation; 7] = [
     PerpOperation::UpdateFunding,
     PerpOperation::AmmFill,
     PerpOperation::Fill,
     PerpOperation::SettlePnl,
     PerpOperation::SettlePnlWithPosition,
     PerpOperation::Liquidation,
+    PerpOperation::ReduceOnly,
 ];
 
 impl PerpOperation {
@@ -43,14 +45,16 @@ pub enum SpotOperation {
     Deposit = 0b00000100,
     Withdraw = 0b00001000,
     Liquidation = 0b00010000,
+    ReduceOnly = 0b00100000,
 }
 
-const ALL_SPOT_OPERATIONS: [SpotOperation; 5] = [
+const ALL_SPOT_OPERATIONS: [SpotOperation; 6] = [
     SpotOperation::UpdateCumulativeInterest,
     SpotOperation::Fill,
     SpotOperation::Deposit,
     SpotOperation::Withdraw,
     SpotOperation::Liquidation,
+    SpotOperation::ReduceOnly,
 ];
 
 impl SpotOperation {
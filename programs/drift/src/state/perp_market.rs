@@ -8,7 +8,7 @@ use crate::math::amm;
 use crate::math::casting::Cast;
 #[cfg(test)]
 use crate::math::constants::{
-    AMM_RESERVE_PRECISION, MAX_CONCENTRATION_COEFFICIENT, PRICE_PRECISION_I64,
+    AMM_RESERVE_PRECISION, MAX_CONCENTRATION_COEFFICIENT, ONE_HOUR, PRICE_PRECISION_I64,
 };
 use crate::math::constants::{
     AMM_RESERVE_PRECISION_I128, AMM_TO_QUOTE_PRECISION_RATIO, BID_ASK_SPREAD_PRECISION,
@@ -29,10 +29,12 @@ use crate::state::events::OrderActionExplanation;
 use num_integer::Roots;
 
 use crate::state::oracle::{
-    get_prelaunch_price, get_sb_on_demand_price, get_switchboard_price, HistoricalOracleData,
+    get_chainlink_price, get_oracle_aggregator_price, get_prelaunch_price, get_sb_on_demand_price,
+    get_signed_price, get_switchboard_price, HistoricalOracleData, OracleCircuitBreaker,
     OracleSource,
 };
 use crate::state::spot_market::{AssetTier, SpotBalance, SpotBalanceType};
+use crate::state::state::ValidityGuardRailsOverride;
 use crate::state::traits::{MarketIndexOffset, Size};
 use borsh::{BorshDeserialize, BorshSerialize};
 
@@ -233,7 +235,46 @@ pub struct PerpMarket {
     /// fuel multiplier for perp maker
     /// precision: 10
     pub fuel_boost_maker: u8,
-    pub padding: [u8; 43],
+    /// Between -100 and 100, represents what % to increase/decrease the funding interest rate by
+    /// E.g. if this is -50 the interest rate component of funding goes from 7.3% to 3.65% annualized
+    pub funding_interest_rate_adjustment: i8,
+    /// If non zero, amm jit intensity scales with amm inventory and the maker liquidity crossing the taker
+    /// This is the min % of amm_jit_intensity used when the amm has no inventory or little maker liquidity crossed
+    /// Scales up to 200% of amm_jit_intensity at max inventory. 0 means amm jit intensity is static. (0, 100]
+    pub dynamic_amm_jit_min_scale: u8,
+    /// Initial margin values the position at oracle price -/+ this multiple of the oracle confidence
+    /// 0 means the confidence interval is ignored
+    /// precision: 10
+    pub margin_confidence_multiplier: u8,
+    /// Whether fills and funding updates must pass the market's PerpMarketDepthBounds to run the depth controller
+    pub has_depth_bounds: bool,
+    /// Whether the market has a PerpInsuranceFund. It must be passed to cover the market's
+    /// bankruptcies and pnl deficits
+    pub has_perp_insurance_fund: bool,
+    /// Whether the market's PerpInsuranceFund has an InsuranceFundPerformance. It must be passed
+    /// after the perp insurance fund vault
+    pub has_perp_insurance_fund_performance: bool,
+    /// Max % of the market's lp shares that can be withdrawn per hour. 0 means no cap
+    pub lp_withdraw_cap: u8,
+    /// The unix_timestamp the current hourly lp withdraw window started
+    pub lp_withdraw_window_start_ts: u32,
+    /// Lp shares withdrawn in the current hourly window
+    /// precision: AMM_RESERVE_PRECISION
+    pub lp_shares_withdrawn_in_window: u64,
+    /// Overrides State::oracle_guard_rails.validity for this market, zero fields use the global value
+    pub oracle_validity_guard_rails: ValidityGuardRailsOverride,
+    /// Pauses risk increasing fills when the oracle moves too far from its 5min twap
+    pub oracle_circuit_breaker: OracleCircuitBreaker,
+    /// Whether fills use the market's MarketFeeStructure instead of State::perp_fee_structure
+    pub has_fee_structure_override: bool,
+    /// fuel multiplier for the value of perp lp shares
+    /// precision: 10
+    pub fuel_boost_lp: u8,
+    /// fuel multiplier for keeper rewards (filler rewards, keeper fees and liquidator fees)
+    /// precision: 10
+    pub fuel_boost_keeper: u8,
+    /// Whether fills must pass the market's MakerRewardsProgram to sample maker depth and volume
+    pub has_maker_rewards_program: bool,
 }
 
 impl Default for PerpMarket {
@@ -270,7 +311,21 @@ impl Default for PerpMarket {
             fuel_boost_position: 0,
             fuel_boost_taker: 0,
             fuel_boost_maker: 0,
-            padding: [0; 43],
+            funding_interest_rate_adjustment: 0,
+            dynamic_amm_jit_min_scale: 0,
+            margin_confidence_multiplier: 0,
+            has_depth_bounds: false,
+            has_perp_insurance_fund: false,
+            has_perp_insurance_fund_performance: false,
+            lp_withdraw_cap: 0,
+            lp_withdraw_window_start_ts: 0,
+            lp_shares_withdrawn_in_window: 0,
+            oracle_validity_guard_rails: ValidityGuardRailsOverride::default(),
+            oracle_circuit_breaker: OracleCircuitBreaker::default(),
+            has_fee_structure_override: false,
+            fuel_boost_lp: 0,
+            fuel_boost_keeper: 0,
+            has_maker_rewards_program: false,
         }
     }
 }
@@ -283,6 +338,31 @@ impl MarketIndexOffset for PerpMarket {
     const MARKET_INDEX_OFFSET: usize = 1160;
 }
 
+/// Bounds for the depth controller's automatic k updates on funding.
+/// The market's has_depth_bounds flag is set while the bounds exist
+#[account]
+#[derive(Default, Eq, PartialEq, Debug)]
+pub struct PerpMarketDepthBounds {
+    /// The lower bound for automatic k (depth) updates on funding
+    /// precision: AMM_RESERVE_PRECISION
+    pub min_sqrt_k: u128,
+    /// The upper bound for automatic k (depth) updates on funding. 0 means the depth controller is disabled
+    /// precision: AMM_RESERVE_PRECISION
+    pub max_sqrt_k: u128,
+    pub market_index: u16,
+    pub padding: [u8; 14],
+}
+
+impl Size for PerpMarketDepthBounds {
+    const SIZE: usize = 56;
+}
+
+impl PerpMarketDepthBounds {
+    pub fn is_enabled(&self) -> bool {
+        self.max_sqrt_k != 0
+    }
+}
+
 impl PerpMarket {
     pub fn is_in_settlement(&self, now: i64) -> bool {
         let in_settlement = matches!(
@@ -294,11 +374,63 @@ impl PerpMarket {
     }
 
     pub fn is_reduce_only(&self) -> DriftResult<bool> {
-        Ok(self.status == MarketStatus::ReduceOnly)
+        Ok(self.status == MarketStatus::ReduceOnly
+            || self.is_operation_paused(PerpOperation::ReduceOnly))
+    }
+
+    pub fn get_paused_operations(&self) -> u8 {
+        if self.oracle_circuit_breaker.is_tripped() {
+            self.paused_operations | PerpOperation::ReduceOnly as u8
+        } else {
+            self.paused_operations
+        }
     }
 
     pub fn is_operation_paused(&self, operation: PerpOperation) -> bool {
-        PerpOperation::is_operation_paused(self.paused_operations, operation)
+        PerpOperation::is_operation_paused(self.get_paused_operations(), operation)
+    }
+
+    fn get_lp_shares_withdrawn_in_window(&self, now: i64) -> DriftResult<u64> {
+        let window_start_ts = self.lp_withdraw_window_start_ts.cast::<i64>()?;
+        if now.safe_sub(window_start_ts)? >= ONE_HOUR {
+            Ok(0)
+        } else {
+            Ok(self.lp_shares_withdrawn_in_window)
+        }
+    }
+
+    /// Lp shares that can still be withdrawn in the current hourly window. The cap is a % of the
+    /// lp shares at the start of the window
+    pub fn get_lp_withdraw_capacity(&self, now: i64) -> DriftResult<u64> {
+        if self.lp_withdraw_cap == 0 {
+            return Ok(u64::MAX);
+        }
+
+        let shares_withdrawn = self.get_lp_shares_withdrawn_in_window(now)?;
+
+        let max_shares_withdrawn = self
+            .amm
+            .user_lp_shares
+            .safe_add(shares_withdrawn.cast()?)?
+            .safe_mul(self.lp_withdraw_cap.cast()?)?
+            .safe_div(100)?
+            .cast::<u64>()?;
+
+        Ok(max_shares_withdrawn.saturating_sub(shares_withdrawn))
+    }
+
+    pub fn record_lp_withdraw(&mut self, shares_withdrawn: u64, now: i64) -> DriftResult {
+        let window_start_ts = self.lp_withdraw_window_start_ts.cast::<i64>()?;
+        if now.safe_sub(window_start_ts)? >= ONE_HOUR {
+            self.lp_withdraw_window_start_ts = now.cast()?;
+            self.lp_shares_withdrawn_in_window = 0;
+        }
+
+        self.lp_shares_withdrawn_in_window = self
+            .lp_shares_withdrawn_in_window
+            .safe_add(shares_withdrawn)?;
+
+        Ok(())
     }
 
     pub fn has_too_much_drawdown(&self) -> DriftResult<bool> {
@@ -593,7 +725,7 @@ impl PerpMarket {
     }
 
     pub fn can_sanitize_market_order_auctions(&self) -> bool {
-        self.amm.oracle_source != OracleSource::Prelaunch
+        !self.amm.oracle_source.is_prelaunch()
     }
 
     pub fn is_prediction_market(&self) -> bool {
@@ -953,7 +1085,12 @@ pub struct AMM {
     pub net_unsettled_funding_pnl: i64,
     pub quote_asset_amount_with_unsettled_lp: i64,
     pub reference_price_offset: i32,
-    pub padding: [u8; 12],
+    /// the total time weight of premium index samples since the last funding rate update
+    /// precision: seconds
+    pub premium_index_sample_weight: u32,
+    /// the time weighted sum of premium index samples since the last funding rate update
+    /// precision: PRICE_PRECISION * seconds
+    pub premium_index_sample_sum: i64,
 }
 
 impl Default for AMM {
@@ -1042,7 +1179,8 @@ impl Default for AMM {
             net_unsettled_funding_pnl: 0,
             quote_asset_amount_with_unsettled_lp: 0,
             reference_price_offset: 0,
-            padding: [0; 12],
+            premium_index_sample_weight: 0,
+            premium_index_sample_sum: 0,
         }
     }
 }
@@ -1370,7 +1508,9 @@ impl AMM {
                 msg!("Can't get oracle twap for quote asset");
                 Err(ErrorCode::DefaultError)
             }
-            OracleSource::Prelaunch => Ok(Some(get_prelaunch_price(price_oracle, slot)?.price)),
+            OracleSource::Prelaunch | OracleSource::FillTwap => {
+                Ok(Some(get_prelaunch_price(price_oracle, slot)?.price))
+            }
             OracleSource::PythPull | OracleSource::PythStableCoinPull => {
                 Ok(Some(self.get_pyth_twap(price_oracle, 1, true)?))
             }
@@ -1378,6 +1518,11 @@ impl AMM {
             OracleSource::Pyth1MPull => {
                 Ok(Some(self.get_pyth_twap(price_oracle, 1000000, true)?))
             }
+            OracleSource::Aggregated => {
+                Ok(Some(get_oracle_aggregator_price(price_oracle, slot)?.price))
+            }
+            OracleSource::Chainlink => Ok(Some(get_chainlink_price(price_oracle, slot)?.price)),
+            OracleSource::SignedPrice => Ok(Some(get_signed_price(price_oracle, slot)?.price)),
         }
     }
 
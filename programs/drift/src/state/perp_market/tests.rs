@@ -40,3 +40,53 @@ mod amm {
         assert_eq!(discount, 10000000); // $1
     }
 }
+
+mod lp_withdraw_capacity {
+    use crate::math::constants::AMM_RESERVE_PRECISION;
+    use crate::state::perp_market::{PerpMarket, AMM};
+
+    #[test]
+    fn capped_per_hour() {
+        let mut market = PerpMarket {
+            amm: AMM {
+                user_lp_shares: 100 * AMM_RESERVE_PRECISION,
+                ..AMM::default()
+            },
+            ..PerpMarket::default()
+        };
+
+        // no cap
+        assert_eq!(market.get_lp_withdraw_capacity(0).unwrap(), u64::MAX);
+
+        market.lp_withdraw_cap = 10; // 10% per hour
+        assert_eq!(
+            market.get_lp_withdraw_capacity(0).unwrap(),
+            10 * AMM_RESERVE_PRECISION as u64
+        );
+
+        // 6 shares withdrawn, cap is still based on the shares at the start of the window
+        market.amm.user_lp_shares = 94 * AMM_RESERVE_PRECISION;
+        market
+            .record_lp_withdraw(6 * AMM_RESERVE_PRECISION as u64, 4000)
+            .unwrap();
+        assert_eq!(market.lp_withdraw_window_start_ts, 4000);
+        assert_eq!(
+            market.get_lp_withdraw_capacity(4100).unwrap(),
+            4 * AMM_RESERVE_PRECISION as u64
+        );
+
+        // window rolls over after an hour
+        assert_eq!(
+            market.get_lp_withdraw_capacity(7600).unwrap(),
+            94 * AMM_RESERVE_PRECISION as u64 / 10
+        );
+        market
+            .record_lp_withdraw(AMM_RESERVE_PRECISION as u64, 7600)
+            .unwrap();
+        assert_eq!(market.lp_withdraw_window_start_ts, 7600);
+        assert_eq!(
+            market.lp_shares_withdrawn_in_window,
+            AMM_RESERVE_PRECISION as u64
+        );
+    }
+}
@@ -7,7 +7,8 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use crate::error::{DriftResult, ErrorCode};
 use crate::math::casting::Cast;
 use crate::math::constants::{
-    AMM_RESERVE_PRECISION, FIVE_MINUTE, MARGIN_PRECISION, ONE_HOUR, SPOT_WEIGHT_PRECISION_U128,
+    AMM_RESERVE_PRECISION, FIVE_MINUTE, MARGIN_PRECISION, ONE_HOUR, SPOT_WEIGHT_PRECISION,
+    SPOT_WEIGHT_PRECISION_U128,
 };
 #[cfg(test)]
 use crate::math::constants::{PRICE_PRECISION_I64, SPOT_CUMULATIVE_INTEREST_PRECISION};
@@ -19,12 +20,29 @@ use crate::math::safe_math::SafeMath;
 use crate::math::spot_balance::{calculate_utilization, get_token_amount, get_token_value};
 
 use crate::math::stats::calculate_new_twap;
-use crate::state::oracle::{HistoricalIndexData, HistoricalOracleData, OracleSource};
+use crate::state::oracle::{
+    HistoricalIndexData, HistoricalOracleData, OracleCircuitBreaker, OracleSource,
+};
 use crate::state::paused_operations::{InsuranceFundOperation, SpotOperation};
 use crate::state::perp_market::{MarketStatus, PoolBalance};
+use crate::state::state::ValidityGuardRailsOverride;
 use crate::state::traits::{MarketIndexOffset, Size};
 use crate::{validate, PERCENTAGE_PRECISION};
 
+#[derive(Clone, Copy, PartialEq, Debug, Eq)]
+pub enum SpotMarketFlag {
+    /// Fills use the market's MarketFeeStructure instead of State::spot_fee_structure
+    HasFeeStructureOverride = 0b00000001,
+    /// The market has a SeniorInsuranceFund. It must be passed to settle revenue and cover losses
+    HasSeniorInsuranceFund = 0b00000010,
+    /// The market's insurance fund has an InsuranceFundPerformance. It must be passed to settle
+    /// revenue and cover losses
+    HasInsuranceFundPerformance = 0b00000100,
+    /// The market's SeniorInsuranceFund has an InsuranceFundPerformance. It must be passed after
+    /// the senior insurance fund vault
+    HasSeniorInsuranceFundPerformance = 0b00001000,
+}
+
 #[account(zero_copy(unsafe))]
 #[derive(PartialEq, Eq, Debug)]
 #[repr(C)]
@@ -201,7 +219,30 @@ pub struct SpotMarket {
     /// precision: 10
     pub fuel_boost_insurance: u8,
     pub token_program: u8,
-    pub padding: [u8; 41],
+    /// Initial margin values deposits at oracle price - this multiple of the oracle confidence
+    /// and borrows at oracle price + this multiple. 0 means the confidence interval is ignored
+    /// precision: 10
+    pub margin_confidence_multiplier: u8,
+    /// Overrides State::oracle_guard_rails.validity for this market, zero fields use the global value
+    pub oracle_validity_guard_rails: ValidityGuardRailsOverride,
+    /// Blocks borrows and risk increasing fills when the oracle moves too far from its 5min twap
+    pub oracle_circuit_breaker: OracleCircuitBreaker,
+    /// Optional piecewise linear borrow rate curve. Unused kinks have zero utilization
+    /// If no kinks are set, optimal_utilization/optimal_borrow_rate is used as a single kink
+    pub borrow_rate_kinks: [BorrowRateKink; 4],
+    /// The e-mode asset group the market belongs to (e.g. SOL and its LSTs). 0 means no group
+    /// The group margin ratios apply when all of a user's spot positions are in the same group
+    pub asset_group: u8,
+    /// Initial margin ratio used in the asset group. asset weight = 1 - ratio, liability weight = 1 + ratio
+    /// 1 => 1/1000 => .1%
+    /// precision: X/1000
+    pub asset_group_margin_ratio_initial: u8,
+    /// Maintenance margin ratio used in the asset group. asset weight = 1 - ratio, liability weight = 1 + ratio
+    /// 1 => 1/1000 => .1%
+    /// precision: X/1000
+    pub asset_group_margin_ratio_maintenance: u8,
+    /// Bitflags for the market's optional accounts. See SpotMarketFlag
+    pub flags: u8,
 }
 
 impl Default for SpotMarket {
@@ -269,7 +310,14 @@ impl Default for SpotMarket {
             fuel_boost_maker: 0,
             fuel_boost_insurance: 0,
             token_program: 0,
-            padding: [0; 41],
+            margin_confidence_multiplier: 0,
+            oracle_validity_guard_rails: ValidityGuardRailsOverride::default(),
+            oracle_circuit_breaker: OracleCircuitBreaker::default(),
+            borrow_rate_kinks: [BorrowRateKink::default(); 4],
+            asset_group: 0,
+            asset_group_margin_ratio_initial: 0,
+            asset_group_margin_ratio_maintenance: 0,
+            flags: 0,
         }
     }
 }
@@ -294,16 +342,57 @@ impl SpotMarket {
 
     pub fn is_reduce_only(&self) -> bool {
         self.status == MarketStatus::ReduceOnly
+            || self.is_operation_paused(SpotOperation::ReduceOnly)
+    }
+
+    pub fn get_paused_operations(&self) -> u8 {
+        if self.oracle_circuit_breaker.is_tripped() {
+            self.paused_operations | SpotOperation::ReduceOnly as u8
+        } else {
+            self.paused_operations
+        }
     }
 
     pub fn is_operation_paused(&self, operation: SpotOperation) -> bool {
-        SpotOperation::is_operation_paused(self.paused_operations, operation)
+        SpotOperation::is_operation_paused(self.get_paused_operations(), operation)
     }
 
     pub fn is_insurance_fund_operation_paused(&self, operation: InsuranceFundOperation) -> bool {
         InsuranceFundOperation::is_operation_paused(self.if_paused_operations, operation)
     }
 
+    pub fn has_borrow_rate_kinks(&self) -> bool {
+        self.borrow_rate_kinks[0].utilization != 0
+    }
+
+    pub fn has_flag(&self, flag: SpotMarketFlag) -> bool {
+        self.flags & (flag as u8) > 0
+    }
+
+    pub fn set_flag(&mut self, flag: SpotMarketFlag, value: bool) {
+        if value {
+            self.flags |= flag as u8;
+        } else {
+            self.flags &= !(flag as u8);
+        }
+    }
+
+    pub fn has_fee_structure_override(&self) -> bool {
+        self.has_flag(SpotMarketFlag::HasFeeStructureOverride)
+    }
+
+    pub fn has_senior_insurance_fund(&self) -> bool {
+        self.has_flag(SpotMarketFlag::HasSeniorInsuranceFund)
+    }
+
+    pub fn has_insurance_fund_performance(&self) -> bool {
+        self.has_flag(SpotMarketFlag::HasInsuranceFundPerformance)
+    }
+
+    pub fn has_senior_insurance_fund_performance(&self) -> bool {
+        self.has_flag(SpotMarketFlag::HasSeniorInsuranceFundPerformance)
+    }
+
     pub fn fills_enabled(&self) -> bool {
         matches!(self.status, MarketStatus::Active | MarketStatus::ReduceOnly)
             && !self.is_operation_paused(SpotOperation::Fill)
@@ -423,6 +512,62 @@ impl SpotMarket {
         Ok(liability_weight)
     }
 
+    pub fn is_in_asset_group(&self) -> bool {
+        self.asset_group != 0
+    }
+
+    /// precision: SPOT_WEIGHT_PRECISION
+    pub fn get_asset_group_margin_ratio(
+        &self,
+        margin_requirement_type: &MarginRequirementType,
+    ) -> DriftResult<u32> {
+        let margin_ratio = match margin_requirement_type {
+            MarginRequirementType::Initial => {
+                self.asset_group_margin_ratio_initial.cast::<u32>()?
+            }
+            MarginRequirementType::Fill => {
+                self.asset_group_margin_ratio_initial
+                    .cast::<u32>()?
+                    .safe_add(self.asset_group_margin_ratio_maintenance.cast()?)?
+                    / 2
+            }
+            MarginRequirementType::Maintenance => {
+                self.asset_group_margin_ratio_maintenance.cast::<u32>()?
+            }
+        };
+
+        margin_ratio.safe_mul(SPOT_WEIGHT_PRECISION / 1000)
+    }
+
+    /// asset weight when all of a user's spot positions are in the market's asset group.
+    /// never lower than the standard asset weight
+    pub fn get_asset_group_asset_weight(
+        &self,
+        size: u128,
+        oracle_price: i64,
+        margin_requirement_type: &MarginRequirementType,
+    ) -> DriftResult<u32> {
+        let asset_weight = self.get_asset_weight(size, oracle_price, margin_requirement_type)?;
+        let asset_group_asset_weight = SPOT_WEIGHT_PRECISION
+            .safe_sub(self.get_asset_group_margin_ratio(margin_requirement_type)?)?;
+
+        Ok(asset_weight.max(asset_group_asset_weight))
+    }
+
+    /// liability weight when all of a user's spot positions are in the market's asset group.
+    /// never higher than the standard liability weight
+    pub fn get_asset_group_liability_weight(
+        &self,
+        size: u128,
+        margin_requirement_type: &MarginRequirementType,
+    ) -> DriftResult<u32> {
+        let liability_weight = self.get_liability_weight(size, margin_requirement_type)?;
+        let asset_group_liability_weight = SPOT_WEIGHT_PRECISION
+            .safe_add(self.get_asset_group_margin_ratio(margin_requirement_type)?)?;
+
+        Ok(liability_weight.min(asset_group_liability_weight))
+    }
+
     // get liability weight as if it were perp market margin requirement
     pub fn get_margin_ratio(
         &self,
@@ -656,6 +801,15 @@ pub enum AssetTier {
     Unlisted,
 }
 
+#[derive(Default, AnchorSerialize, AnchorDeserialize, Clone, Copy, Eq, PartialEq, Debug)]
+pub struct BorrowRateKink {
+    /// precision: ONE_BPS_DENOMINATOR
+    pub utilization: u16,
+    /// The borrow rate at this utilization as a fraction of max_borrow_rate
+    /// precision: ONE_BPS_DENOMINATOR
+    pub borrow_rate: u16,
+}
+
 #[zero_copy(unsafe)]
 #[derive(Default, Eq, PartialEq, Debug)]
 #[repr(C)]
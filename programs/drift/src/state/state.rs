@@ -1,13 +1,18 @@
 use anchor_lang::prelude::*;
 use enumflags2::BitFlags;
 
-use crate::error::DriftResult;
+use crate::error::{DriftResult, ErrorCode};
 use crate::math::constants::{
-    FEE_DENOMINATOR, FEE_PERCENTAGE_DENOMINATOR, MAX_REFERRER_REWARD_EPOCH_UPPER_BOUND,
+    BID_ASK_SPREAD_PRECISION, FEE_DENOMINATOR, FEE_PERCENTAGE_DENOMINATOR, FIFTY_MILLION_QUOTE,
+    FIVE_MILLION_QUOTE, MAX_REFERRER_REWARD_EPOCH_UPPER_BOUND, ONE_HUNDRED_MILLION_QUOTE,
+    ONE_HUNDRED_THOUSAND_QUOTE, ONE_MILLION_QUOTE, ONE_THOUSAND_QUOTE, QUOTE_PRECISION_U64,
+    TEN_MILLION_QUOTE, TEN_THOUSAND_QUOTE, TWENTY_FIVE_THOUSAND_QUOTE,
 };
 use crate::math::safe_math::SafeMath;
 use crate::math::safe_unwrap::SafeUnwrap;
 use crate::state::traits::Size;
+use crate::state::user::MarketType;
+use crate::validate;
 use crate::{LAMPORTS_PER_SOL_U64, PERCENTAGE_PRECISION_U64};
 
 #[cfg(test)]
@@ -41,7 +46,17 @@ pub struct State {
     pub initial_pct_to_liquidate: u16,
     pub max_number_of_sub_accounts: u16,
     pub max_initialize_user_fee: u16,
-    pub padding: [u8; 10],
+    /// The open fuel season. Incremented when the admin closes a season
+    pub fuel_season: u16,
+    /// Set once the referral tier config is initialized. Fills must then pass it
+    pub has_referral_tier_config: bool,
+    /// Set once the perp fee tier qualifications are initialized. Perp fills that don't pass them use the defaults
+    pub has_perp_fee_tier_qualifications: bool,
+    /// Set once the spot fee tier qualifications are initialized. Spot fills that don't pass them use the defaults
+    pub has_spot_fee_tier_qualifications: bool,
+    /// The unix_timestamp the open fuel season started. Zero until the first season is closed
+    pub fuel_season_start_ts: u32,
+    pub padding: [u8; 1],
 }
 
 #[derive(BitFlags, Clone, Copy, PartialEq, Debug, Eq)]
@@ -165,6 +180,87 @@ pub struct ValidityGuardRails {
     pub too_volatile_ratio: i64,
 }
 
+impl ValidityGuardRails {
+    pub fn validate(&self) -> DriftResult {
+        validate!(
+            self.slots_before_stale_for_amm > 0,
+            ErrorCode::InvalidOracleGuardRails,
+            "slots_before_stale_for_amm ({}) must be > 0",
+            self.slots_before_stale_for_amm
+        )?;
+
+        validate!(
+            self.slots_before_stale_for_margin >= self.slots_before_stale_for_amm,
+            ErrorCode::InvalidOracleGuardRails,
+            "slots_before_stale_for_margin ({}) must be >= slots_before_stale_for_amm ({})",
+            self.slots_before_stale_for_margin,
+            self.slots_before_stale_for_amm
+        )?;
+
+        validate!(
+            self.confidence_interval_max_size > 0
+                && self.confidence_interval_max_size <= BID_ASK_SPREAD_PRECISION,
+            ErrorCode::InvalidOracleGuardRails,
+            "confidence_interval_max_size ({}) must be in (0, {}]",
+            self.confidence_interval_max_size,
+            BID_ASK_SPREAD_PRECISION
+        )?;
+
+        validate!(
+            self.too_volatile_ratio > 1,
+            ErrorCode::InvalidOracleGuardRails,
+            "too_volatile_ratio ({}) must be > 1",
+            self.too_volatile_ratio
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Per market overrides of the global ValidityGuardRails. Zero falls back to the global value
+#[zero_copy(unsafe)]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct ValidityGuardRailsOverride {
+    /// precision: BID_ASK_SPREAD_PRECISION
+    pub confidence_interval_max_size: u32,
+    pub slots_before_stale_for_amm: u16,
+    pub slots_before_stale_for_margin: u16,
+    pub too_volatile_ratio: u16,
+    pub padding: [u8; 2],
+}
+
+impl ValidityGuardRailsOverride {
+    pub fn is_set(&self) -> bool {
+        self.confidence_interval_max_size != 0
+            || self.slots_before_stale_for_amm != 0
+            || self.slots_before_stale_for_margin != 0
+            || self.too_volatile_ratio != 0
+    }
+
+    pub fn apply(&self, guard_rails: &ValidityGuardRails) -> ValidityGuardRails {
+        let mut validity = *guard_rails;
+
+        if self.slots_before_stale_for_amm != 0 {
+            validity.slots_before_stale_for_amm = self.slots_before_stale_for_amm.into();
+        }
+
+        if self.slots_before_stale_for_margin != 0 {
+            validity.slots_before_stale_for_margin = self.slots_before_stale_for_margin.into();
+        }
+
+        if self.confidence_interval_max_size != 0 {
+            validity.confidence_interval_max_size = self.confidence_interval_max_size.into();
+        }
+
+        if self.too_volatile_ratio != 0 {
+            validity.too_volatile_ratio = self.too_volatile_ratio.into();
+        }
+
+        validity
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct FeeStructure {
     pub fee_tiers: [FeeTier; 10],
@@ -206,6 +302,39 @@ impl Default for FeeTier {
     }
 }
 
+/// A user qualifies for a fee tier by meeting any of its non-zero thresholds. A tier with only
+/// zero thresholds can't be reached
+#[derive(AnchorSerialize, AnchorDeserialize, Default, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FeeTierQualification {
+    /// precision: QUOTE_PRECISION
+    pub volume_30d_threshold: u64,
+    /// precision: QUOTE_PRECISION
+    pub if_staked_quote_asset_amount_threshold: u64,
+    /// precision: QUOTE_PRECISION
+    pub if_staked_gov_token_amount_threshold: u64,
+}
+
+impl FeeTierQualification {
+    pub fn is_enabled(&self) -> bool {
+        self.volume_30d_threshold != 0
+            || self.if_staked_quote_asset_amount_threshold != 0
+            || self.if_staked_gov_token_amount_threshold != 0
+    }
+
+    pub fn is_met(
+        &self,
+        volume_30d: u64,
+        if_staked_quote_asset_amount: u64,
+        if_staked_gov_token_amount: u64,
+    ) -> bool {
+        (self.volume_30d_threshold != 0 && volume_30d >= self.volume_30d_threshold)
+            || (self.if_staked_quote_asset_amount_threshold != 0
+                && if_staked_quote_asset_amount >= self.if_staked_quote_asset_amount_threshold)
+            || (self.if_staked_gov_token_amount_threshold != 0
+                && if_staked_gov_token_amount >= self.if_staked_gov_token_amount_threshold)
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Default, Clone, Debug)]
 pub struct OrderFillerRewardStructure {
     pub reward_numerator: u32,
@@ -313,6 +442,145 @@ impl FeeStructure {
     }
 }
 
+impl FeeStructure {
+    /// Fee structure with every fee tier's referrer reward and referee discount replaced by the
+    /// referral tier's
+    pub fn with_referral_tier(&self, referral_tier: &ReferralTier) -> Self {
+        let mut fee_structure = self.clone();
+        for fee_tier in fee_structure.fee_tiers.iter_mut() {
+            fee_tier.referrer_reward_numerator = referral_tier.referrer_reward_numerator;
+            fee_tier.referrer_reward_denominator = FEE_PERCENTAGE_DENOMINATOR;
+            fee_tier.referee_fee_numerator = referral_tier.referee_fee_numerator;
+            fee_tier.referee_fee_denominator = FEE_PERCENTAGE_DENOMINATOR;
+        }
+        fee_structure
+    }
+}
+
+/// Referral tiers replace the perp fee tiers' referrer reward and referee discount once the
+/// referrer's referees have traded enough over the last 30 days
+#[account(zero_copy(unsafe))]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct ReferralTierConfig {
+    /// ordered by increasing referee volume threshold. tiers with a zero threshold are disabled
+    pub tiers: [ReferralTier; 4],
+    /// share of a referrer's reward paid to the referrer's own referrer
+    /// precision: FEE_PERCENTAGE_DENOMINATOR
+    pub sub_affiliate_reward_numerator: u32,
+    pub padding: [u8; 28],
+}
+
+impl Size for ReferralTierConfig {
+    const SIZE: usize = 104;
+}
+
+impl ReferralTierConfig {
+    pub fn get_referral_tier(&self, referee_volume_30d: u64) -> Option<&ReferralTier> {
+        self.tiers.iter().rev().find(|tier| {
+            tier.referee_volume_30d_threshold != 0
+                && referee_volume_30d >= tier.referee_volume_30d_threshold
+        })
+    }
+}
+
+#[zero_copy(unsafe)]
+#[repr(C)]
+#[derive(AnchorSerialize, AnchorDeserialize, Default, PartialEq, Debug, Eq)]
+pub struct ReferralTier {
+    /// 30 day volume of the referrer's referees needed to qualify
+    /// precision: QUOTE_PRECISION
+    pub referee_volume_30d_threshold: u64,
+    /// precision: FEE_PERCENTAGE_DENOMINATOR
+    pub referrer_reward_numerator: u32,
+    /// precision: FEE_PERCENTAGE_DENOMINATOR
+    pub referee_fee_numerator: u32,
+}
+
+/// Overrides State::perp_fee_structure / State::spot_fee_structure for fills in a single market.
+/// The market's has_fee_structure_override flag is set while the override exists
+#[account]
+#[derive(Default, Debug)]
+pub struct MarketFeeStructure {
+    pub fee_structure: FeeStructure,
+    pub market_index: u16,
+    pub market_type: MarketType,
+    pub padding: [u8; 29],
+}
+
+impl Size for MarketFeeStructure {
+    const SIZE: usize = 400;
+}
+
+/// Requirements to qualify for the fee tier at the same index, for fills in all markets of one
+/// market type. Until it is initialized, perp fills use the default thresholds and spot fills
+/// always use fee tier 0. State::has_perp_fee_tier_qualifications /
+/// State::has_spot_fee_tier_qualifications are set once it exists
+#[account]
+#[derive(Default, Debug)]
+pub struct FeeTierQualifications {
+    /// tier 0 has no requirements
+    pub fee_tier_qualifications: [FeeTierQualification; 10],
+    pub market_type: MarketType,
+    pub padding: [u8; 31],
+}
+
+impl Size for FeeTierQualifications {
+    const SIZE: usize = 280;
+}
+
+impl FeeTierQualifications {
+    pub fn default_for(market_type: MarketType) -> Self {
+        match market_type {
+            MarketType::Perp => FeeTierQualifications::perps_default(),
+            MarketType::Spot => FeeTierQualifications::spot_default(),
+        }
+    }
+
+    pub fn perps_default() -> Self {
+        let mut fee_tier_qualifications = [FeeTierQualification::default(); 10];
+        fee_tier_qualifications[1] = FeeTierQualification {
+            volume_30d_threshold: ONE_MILLION_QUOTE,
+            if_staked_quote_asset_amount_threshold: ONE_THOUSAND_QUOTE / 2 - QUOTE_PRECISION_U64,
+            if_staked_gov_token_amount_threshold: ONE_THOUSAND_QUOTE - QUOTE_PRECISION_U64,
+        };
+        fee_tier_qualifications[2] = FeeTierQualification {
+            volume_30d_threshold: FIVE_MILLION_QUOTE,
+            if_staked_quote_asset_amount_threshold: ONE_THOUSAND_QUOTE - QUOTE_PRECISION_U64,
+            if_staked_gov_token_amount_threshold: TEN_THOUSAND_QUOTE - QUOTE_PRECISION_U64,
+        };
+        fee_tier_qualifications[3] = FeeTierQualification {
+            volume_30d_threshold: TEN_MILLION_QUOTE,
+            if_staked_quote_asset_amount_threshold: ONE_THOUSAND_QUOTE * 2 - QUOTE_PRECISION_U64,
+            if_staked_gov_token_amount_threshold: TWENTY_FIVE_THOUSAND_QUOTE * 2
+                - QUOTE_PRECISION_U64,
+        };
+        fee_tier_qualifications[4] = FeeTierQualification {
+            volume_30d_threshold: FIFTY_MILLION_QUOTE,
+            if_staked_quote_asset_amount_threshold: ONE_THOUSAND_QUOTE * 5 - QUOTE_PRECISION_U64,
+            if_staked_gov_token_amount_threshold: ONE_HUNDRED_THOUSAND_QUOTE - QUOTE_PRECISION_U64,
+        };
+        fee_tier_qualifications[5] = FeeTierQualification {
+            volume_30d_threshold: ONE_HUNDRED_MILLION_QUOTE,
+            if_staked_quote_asset_amount_threshold: TEN_THOUSAND_QUOTE - QUOTE_PRECISION_U64,
+            if_staked_gov_token_amount_threshold: ONE_HUNDRED_THOUSAND_QUOTE
+                + 19_500 * QUOTE_PRECISION_U64,
+        };
+        FeeTierQualifications {
+            fee_tier_qualifications,
+            market_type: MarketType::Perp,
+            padding: [0; 31],
+        }
+    }
+
+    pub fn spot_default() -> Self {
+        FeeTierQualifications {
+            market_type: MarketType::Spot,
+            ..FeeTierQualifications::default()
+        }
+    }
+}
+
 #[cfg(test)]
 impl FeeStructure {
     pub fn test_default() -> Self {
@@ -51,3 +51,151 @@ mod get_init_user_fee {
         assert_eq!(init_user_fee, 1000000000);
     }
 }
+
+mod referral_tier_config {
+    use crate::math::constants::{FEE_PERCENTAGE_DENOMINATOR, QUOTE_PRECISION_U64};
+    use crate::state::state::{FeeStructure, ReferralTier, ReferralTierConfig};
+
+    #[test]
+    fn get_referral_tier() {
+        let mut config = ReferralTierConfig::default();
+        assert_eq!(config.get_referral_tier(u64::MAX), None);
+
+        config.tiers[0] = ReferralTier {
+            referee_volume_30d_threshold: 100_000 * QUOTE_PRECISION_U64,
+            referrer_reward_numerator: 17,
+            referee_fee_numerator: 6,
+        };
+        config.tiers[1] = ReferralTier {
+            referee_volume_30d_threshold: 1_000_000 * QUOTE_PRECISION_U64,
+            referrer_reward_numerator: 20,
+            referee_fee_numerator: 8,
+        };
+
+        assert_eq!(
+            config.get_referral_tier(100_000 * QUOTE_PRECISION_U64 - 1),
+            None
+        );
+        assert_eq!(
+            config.get_referral_tier(100_000 * QUOTE_PRECISION_U64),
+            Some(&config.tiers[0])
+        );
+        assert_eq!(
+            config.get_referral_tier(1_000_000 * QUOTE_PRECISION_U64),
+            Some(&config.tiers[1])
+        );
+        // disabled tiers are skipped
+        assert_eq!(config.get_referral_tier(u64::MAX), Some(&config.tiers[1]));
+    }
+
+    #[test]
+    fn with_referral_tier() {
+        let fee_structure = FeeStructure::perps_default();
+        let referral_fee_structure = fee_structure.with_referral_tier(&ReferralTier {
+            referee_volume_30d_threshold: 100_000 * QUOTE_PRECISION_U64,
+            referrer_reward_numerator: 20,
+            referee_fee_numerator: 10,
+        });
+
+        for (fee_tier, referral_fee_tier) in fee_structure
+            .fee_tiers
+            .iter()
+            .zip(referral_fee_structure.fee_tiers.iter())
+        {
+            assert_eq!(referral_fee_tier.fee_numerator, fee_tier.fee_numerator);
+            assert_eq!(
+                referral_fee_tier.maker_rebate_numerator,
+                fee_tier.maker_rebate_numerator
+            );
+            assert_eq!(referral_fee_tier.referrer_reward_numerator, 20);
+            assert_eq!(
+                referral_fee_tier.referrer_reward_denominator,
+                FEE_PERCENTAGE_DENOMINATOR
+            );
+            assert_eq!(referral_fee_tier.referee_fee_numerator, 10);
+            assert_eq!(
+                referral_fee_tier.referee_fee_denominator,
+                FEE_PERCENTAGE_DENOMINATOR
+            );
+        }
+    }
+}
+
+mod market_fee_structure {
+    use crate::state::state::MarketFeeStructure;
+    use crate::state::traits::Size;
+    use anchor_lang::AnchorSerialize;
+
+    #[test]
+    fn size() {
+        let market_fee_structure = MarketFeeStructure::default();
+        assert_eq!(
+            market_fee_structure.try_to_vec().unwrap().len() + 8,
+            MarketFeeStructure::SIZE
+        );
+    }
+}
+
+mod fee_tier_qualifications {
+    use crate::state::state::FeeTierQualifications;
+    use crate::state::traits::Size;
+    use anchor_lang::AnchorSerialize;
+
+    #[test]
+    fn size() {
+        let fee_tier_qualifications = FeeTierQualifications::default();
+        assert_eq!(
+            fee_tier_qualifications.try_to_vec().unwrap().len() + 8,
+            FeeTierQualifications::SIZE
+        );
+    }
+}
+
+mod validity_guard_rails {
+    use crate::state::state::{OracleGuardRails, ValidityGuardRails, ValidityGuardRailsOverride};
+
+    #[test]
+    fn validate() {
+        let validity = OracleGuardRails::default().validity;
+        validity.validate().unwrap();
+
+        let invalid = ValidityGuardRails {
+            slots_before_stale_for_amm: 0,
+            ..validity
+        };
+        assert!(invalid.validate().is_err());
+
+        let invalid = ValidityGuardRails {
+            slots_before_stale_for_margin: 5,
+            ..validity
+        };
+        assert!(invalid.validate().is_err());
+
+        let invalid = ValidityGuardRails {
+            confidence_interval_max_size: 2_000_000,
+            ..validity
+        };
+        assert!(invalid.validate().is_err());
+
+        let invalid = ValidityGuardRails {
+            too_volatile_ratio: 1,
+            ..validity
+        };
+        assert!(invalid.validate().is_err());
+
+        // overrides are validated after being applied to the global guard rails
+        let market_override = ValidityGuardRailsOverride {
+            slots_before_stale_for_amm: 200,
+            ..ValidityGuardRailsOverride::default()
+        };
+        assert!(market_override.apply(&validity).validate().is_err());
+
+        let market_override = ValidityGuardRailsOverride {
+            slots_before_stale_for_amm: 200,
+            slots_before_stale_for_margin: 400,
+            confidence_interval_max_size: 50_000,
+            ..ValidityGuardRailsOverride::default()
+        };
+        market_override.apply(&validity).validate().unwrap();
+    }
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+
+use crate::error::DriftResult;
+use crate::math::casting::Cast;
+use crate::math::constants::{ONE_YEAR, SPOT_RATE_PRECISION};
+use crate::math::safe_math::SafeMath;
+use crate::state::traits::Size;
+
+#[cfg(test)]
+mod tests;
+
+#[account(zero_copy(unsafe))]
+#[derive(Default, Eq, PartialEq, Debug)]
+#[repr(C)]
+pub struct TermLoan {
+    /// The lender's user account
+    pub lender: Pubkey,
+    /// The lender's authority. Receives the rent when the loan is closed
+    pub lender_authority: Pubkey,
+    /// The borrower's user account. Default until the offer is taken
+    pub borrower: Pubkey,
+    /// The amount lent to the borrower
+    /// precision: token mint precision
+    pub principal: u64,
+    /// The amount the borrower owes the lender at maturity. Set when the offer is taken
+    /// precision: token mint precision
+    pub repay_amount: u64,
+    /// The unix_timestamp the loan matures at
+    pub maturity_ts: i64,
+    /// The unix_timestamp the offer was taken
+    pub start_ts: i64,
+    /// The fixed annualized interest rate
+    /// precision: SPOT_RATE_PRECISION
+    pub rate: u32,
+    /// Id chosen by the lender to derive the account address
+    pub offer_id: u32,
+    pub market_index: u16,
+    pub status: TermLoanStatus,
+    pub padding: [u8; 5],
+}
+
+impl Size for TermLoan {
+    const SIZE: usize = 152;
+}
+
+impl TermLoan {
+    pub fn is_offered(&self) -> bool {
+        self.status == TermLoanStatus::Offered
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status == TermLoanStatus::Active
+    }
+
+    pub fn is_matured(&self, now: i64) -> bool {
+        now >= self.maturity_ts
+    }
+
+    /// principal plus the fixed interest accrued from now until maturity
+    pub fn calculate_repay_amount(&self, now: i64) -> DriftResult<u64> {
+        let time_to_maturity = self.maturity_ts.safe_sub(now)?.max(0).cast::<u128>()?;
+
+        let interest = self
+            .principal
+            .cast::<u128>()?
+            .safe_mul(self.rate.cast()?)?
+            .safe_mul(time_to_maturity)?
+            .safe_div_ceil(ONE_YEAR.safe_mul(SPOT_RATE_PRECISION)?)?;
+
+        self.principal.safe_add(interest.cast()?)
+    }
+}
+
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Eq, Default)]
+pub enum TermLoanStatus {
+    /// The lender has posted the offer and is waiting for a borrower
+    #[default]
+    Offered,
+    /// A borrower has taken the offer and owes the repay amount at maturity
+    Active,
+}
@@ -0,0 +1,41 @@
+mod calculate_repay_amount {
+    use crate::math::constants::{ONE_YEAR, QUOTE_PRECISION_U64, SPOT_RATE_PRECISION_U32};
+    use crate::state::term_loan::TermLoan;
+
+    #[test]
+    fn one_year() {
+        let term_loan = TermLoan {
+            principal: 1000 * QUOTE_PRECISION_U64,
+            rate: SPOT_RATE_PRECISION_U32 / 10, // 10%
+            maturity_ts: ONE_YEAR as i64,
+            ..TermLoan::default()
+        };
+
+        let repay_amount = term_loan.calculate_repay_amount(0).unwrap();
+        assert_eq!(repay_amount, 1100 * QUOTE_PRECISION_U64);
+
+        let repay_amount = term_loan
+            .calculate_repay_amount(ONE_YEAR as i64 / 2)
+            .unwrap();
+        assert_eq!(repay_amount, 1050 * QUOTE_PRECISION_U64);
+
+        // no interest once matured
+        let repay_amount = term_loan
+            .calculate_repay_amount(ONE_YEAR as i64 + 1)
+            .unwrap();
+        assert_eq!(repay_amount, 1000 * QUOTE_PRECISION_U64);
+    }
+
+    #[test]
+    fn rounds_up() {
+        let term_loan = TermLoan {
+            principal: 1,
+            rate: 1,
+            maturity_ts: 1,
+            ..TermLoan::default()
+        };
+
+        let repay_amount = term_loan.calculate_repay_amount(0).unwrap();
+        assert_eq!(repay_amount, 2);
+    }
+}
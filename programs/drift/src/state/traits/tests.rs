@@ -1,7 +1,9 @@
 mod size {
     use crate::state::events::OrderActionRecord;
+    use crate::state::fuel_season::{FuelSeason, UserFuelSeasonRecord};
     use crate::state::fulfillment_params::serum::SerumV3FulfillmentConfig;
     use crate::state::insurance_fund_stake::InsuranceFundStake;
+    use crate::state::maker_rewards::MakerRewardsProgram;
     use crate::state::perp_market::PerpMarket;
     use crate::state::spot_market::SpotMarket;
     use crate::state::state::State;
@@ -63,6 +65,24 @@ mod size {
         let actual_size = InsuranceFundStake::SIZE;
         assert_eq!(actual_size, expected_size);
     }
+
+    #[test]
+    fn fuel_season() {
+        let expected_size = std::mem::size_of::<FuelSeason>() + 8;
+        let actual_size = FuelSeason::SIZE;
+        assert_eq!(actual_size, expected_size);
+
+        let expected_size = std::mem::size_of::<UserFuelSeasonRecord>() + 8;
+        let actual_size = UserFuelSeasonRecord::SIZE;
+        assert_eq!(actual_size, expected_size);
+    }
+
+    #[test]
+    fn maker_rewards_program() {
+        let expected_size = std::mem::size_of::<MakerRewardsProgram>() + 8;
+        let actual_size = MakerRewardsProgram::SIZE;
+        assert_eq!(actual_size, expected_size);
+    }
 }
 
 mod market_index_offset {
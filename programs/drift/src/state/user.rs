@@ -7,6 +7,7 @@ use crate::math::constants::{
     EPOCH_DURATION, FUEL_START_TS, OPEN_ORDER_MARGIN_REQUIREMENT,
     PRICE_TIMES_AMM_TO_QUOTE_PRECISION_RATIO, QUOTE_PRECISION, QUOTE_SPOT_MARKET_INDEX, THIRTY_DAY,
 };
+use crate::math::fuel::calculate_keeper_fuel_bonus;
 use crate::math::lp::{calculate_lp_open_bids_asks, calculate_settle_lp_metrics};
 use crate::math::margin::MarginRequirementType;
 use crate::math::orders::{standardize_base_asset_amount, standardize_price};
@@ -126,9 +127,16 @@ pub struct User {
     pub open_auctions: u8,
     /// Whether or not user has open order with auction
     pub has_open_auction: bool,
-    pub padding1: [u8; 5],
+    pub padding1: [u8; 1],
+    /// The perp market index of the pending lp withdraw request
+    pub lp_withdraw_request_market_index: u16,
+    pub padding2: [u8; 2],
     pub last_fuel_bonus_update_ts: u32,
-    pub padding: [u8; 12],
+    /// The last time the user queued an lp withdraw request
+    pub lp_withdraw_request_ts: u32,
+    /// The lp shares queued to be removed once the lp cooldown has passed. 0 if there is no pending request
+    /// precision: AMM_RESERVE_PRECISION
+    pub lp_withdraw_request_shares: u64,
 }
 
 impl User {
@@ -148,6 +156,11 @@ impl User {
         self.status & (UserStatus::AdvancedLp as u8) > 0
     }
 
+    pub fn has_lp_withdraw_request(&self, market_index: u16) -> bool {
+        self.lp_withdraw_request_shares != 0
+            && self.lp_withdraw_request_market_index == market_index
+    }
+
     pub fn add_user_status(&mut self, status: UserStatus) {
         self.status |= status as u8;
     }
@@ -169,10 +182,49 @@ impl User {
 
         self.spot_positions
             .iter()
-            .position(|spot_position| spot_position.market_index == market_index)
+            .position(|spot_position| {
+                spot_position.market_index == market_index && !spot_position.is_term()
+            })
+            .ok_or(ErrorCode::CouldNotFindSpotPosition)
+    }
+
+    pub fn get_term_spot_position_index(
+        &self,
+        market_index: u16,
+        maturity_ts: u32,
+        balance_type: SpotBalanceType,
+    ) -> DriftResult<usize> {
+        self.spot_positions
+            .iter()
+            .position(|spot_position| {
+                spot_position.market_index == market_index
+                    && spot_position.maturity_ts == maturity_ts
+                    && spot_position.balance_type == balance_type
+                    && !spot_position.is_available()
+            })
             .ok_or(ErrorCode::CouldNotFindSpotPosition)
     }
 
+    pub fn force_get_term_spot_position_index(
+        &mut self,
+        market_index: u16,
+        maturity_ts: u32,
+        balance_type: SpotBalanceType,
+    ) -> DriftResult<usize> {
+        validate!(
+            maturity_ts != 0,
+            ErrorCode::InvalidSpotPosition,
+            "term spot position must have a maturity"
+        )?;
+
+        self.get_term_spot_position_index(market_index, maturity_ts, balance_type)
+            .or_else(|_| {
+                let position_index = self.add_spot_position(market_index, balance_type)?;
+                self.spot_positions[position_index].maturity_ts = maturity_ts;
+                Ok(position_index)
+            })
+    }
+
     pub fn get_spot_position(&self, market_index: u16) -> DriftResult<&SpotPosition> {
         self.get_spot_position_index(market_index)
             .map(|market_index| &self.spot_positions[market_index])
@@ -480,6 +532,7 @@ impl User {
             margin_calculation.fuel_deposits,
             margin_calculation.fuel_borrows,
             margin_calculation.fuel_positions,
+            margin_calculation.fuel_lp,
             now,
         )?;
 
@@ -534,6 +587,7 @@ impl User {
             calculation.fuel_deposits,
             calculation.fuel_borrows,
             calculation.fuel_positions,
+            calculation.fuel_lp,
             now,
         )?;
 
@@ -551,9 +605,9 @@ pub struct UserFees {
     /// Total maker fee rebate
     /// precision: QUOTE_PRECISION
     pub total_fee_rebate: u64,
-    /// Total discount from holding token
+    /// Sub-affiliate rewards earned as a referrer's referrer that have not been claimed yet
     /// precision: QUOTE_PRECISION
-    pub total_token_discount: u64,
+    pub unclaimed_sub_affiliate_reward: u64,
     /// Total discount from being referred
     /// precision: QUOTE_PRECISION
     pub total_referee_discount: u64,
@@ -570,8 +624,8 @@ pub struct UserFees {
 #[repr(C)]
 pub struct SpotPosition {
     /// The scaled balance of the position. To get the token amount, multiply by the cumulative deposit/borrow
-    /// interest of corresponding market.
-    /// precision: SPOT_BALANCE_PRECISION
+    /// interest of corresponding market. For fixed-term positions, this is the token amount due at maturity.
+    /// precision: SPOT_BALANCE_PRECISION (token mint precision for fixed-term positions)
     pub scaled_balance: u64,
     /// How many spot bids the user has open
     /// precision: token mint precision
@@ -588,7 +642,8 @@ pub struct SpotPosition {
     pub balance_type: SpotBalanceType,
     /// Number of open orders
     pub open_orders: u8,
-    pub padding: [u8; 4],
+    /// The unix_timestamp a fixed-term position matures at. Zero for floating positions
+    pub maturity_ts: u32,
 }
 
 impl SpotBalance for SpotPosition {
@@ -684,6 +739,45 @@ impl OrderFillSimulation {
 
         Ok(self)
     }
+
+    /// reweights the simulation with the market's asset group weights. the user custom margin ratio
+    /// still caps the weights
+    pub fn apply_asset_group_weights(
+        mut self,
+        spot_market: &SpotMarket,
+        oracle_price: i64,
+        margin_type: MarginRequirementType,
+        user_custom_margin_ratio: u32,
+    ) -> DriftResult<Self> {
+        if self.token_value < 0 {
+            let liability_weight = spot_market
+                .get_asset_group_liability_weight(self.token_amount.unsigned_abs(), &margin_type)?
+                .max(user_custom_margin_ratio.safe_add(SPOT_WEIGHT_PRECISION)?);
+
+            self.weighted_token_value = self
+                .token_value
+                .safe_mul(liability_weight.cast()?)?
+                .safe_div(SPOT_WEIGHT_PRECISION_I128)?;
+        } else if self.token_value > 0 {
+            let asset_weight = spot_market
+                .get_asset_group_asset_weight(
+                    self.token_amount.unsigned_abs(),
+                    oracle_price,
+                    &margin_type,
+                )?
+                .min(SPOT_WEIGHT_PRECISION.saturating_sub(user_custom_margin_ratio));
+
+            self.weighted_token_value = self
+                .token_value
+                .safe_mul(asset_weight.cast()?)?
+                .safe_div(SPOT_WEIGHT_PRECISION_I128)?;
+        }
+
+        self.free_collateral_contribution =
+            self.weighted_token_value.safe_add(self.orders_value)?;
+
+        Ok(self)
+    }
 }
 
 impl SpotPosition {
@@ -701,15 +795,21 @@ impl SpotPosition {
             .safe_mul(OPEN_ORDER_MARGIN_REQUIREMENT)
     }
 
+    pub fn is_term(&self) -> bool {
+        self.maturity_ts != 0
+    }
+
     pub fn get_token_amount(&self, spot_market: &SpotMarket) -> DriftResult<u128> {
+        // fixed-term positions don't accrue floating interest
+        if self.is_term() {
+            return self.scaled_balance.cast();
+        }
+
         get_token_amount(self.scaled_balance.cast()?, spot_market, &self.balance_type)
     }
 
     pub fn get_signed_token_amount(&self, spot_market: &SpotMarket) -> DriftResult<i128> {
-        get_signed_token_amount(
-            get_token_amount(self.scaled_balance.cast()?, spot_market, &self.balance_type)?,
-            &self.balance_type,
-        )
+        get_signed_token_amount(self.get_token_amount(spot_market)?, &self.balance_type)
     }
 
     pub fn get_worst_case_fill_simulation(
@@ -1557,10 +1657,14 @@ pub struct UserStats {
     /// Rolling 30day filler volume for user
     /// precision: QUOTE_PRECISION
     pub filler_volume_30d: u64,
-    /// last time the maker volume was updated
-    pub last_maker_volume_30d_ts: i64,
-    /// last time the taker volume was updated
-    pub last_taker_volume_30d_ts: i64,
+    /// last time the maker volume was updated (u32 to save space)
+    pub last_maker_volume_30d_ts: u32,
+    /// accumulated fuel for the value of perp lp shares
+    pub fuel_lp: u32,
+    /// last time the taker volume was updated (u32 to save space)
+    pub last_taker_volume_30d_ts: u32,
+    /// accumulated fuel for keeper actions (fills, triggers, liquidations and settlements)
+    pub fuel_keeper: u32,
     /// last time the filler volume was updated
     pub last_filler_volume_30d_ts: i64,
 
@@ -1574,7 +1678,8 @@ pub struct UserStats {
     /// Whether the user is a referrer. Sub account 0 can not be deleted if user is a referrer
     pub is_referrer: bool,
     pub disable_update_perp_bid_ask_twap: bool,
-    pub padding1: [u8; 2],
+    /// The earliest fuel season the fuel counters may hold fuel for. Advanced as closed seasons are snapshotted
+    pub fuel_season: u16,
     /// accumulated fuel for token amounts of insurance
     pub fuel_insurance: u32,
     /// accumulated fuel for notional of deposits
@@ -1593,8 +1698,11 @@ pub struct UserStats {
 
     /// last unix ts user stats data was used to update if fuel (u32 to save space)
     pub last_fuel_if_bonus_update_ts: u32,
-
-    pub padding: [u8; 12],
+    /// last time the referee volume was updated (u32 to save space)
+    pub last_referee_volume_30d_ts: u32,
+    /// Rolling 30day taker volume of the users this user referred
+    /// precision: QUOTE_PRECISION
+    pub referee_volume_30d: u64,
 }
 
 impl Size for UserStats {
@@ -1628,12 +1736,14 @@ impl UserStats {
         fuel_deposits: u32,
         fuel_borrows: u32,
         fuel_positions: u32,
+        fuel_lp: u32,
         now: i64,
     ) -> DriftResult {
         if user.last_fuel_bonus_update_ts != 0 || now > FUEL_START_TS {
             self.fuel_deposits = self.fuel_deposits.saturating_add(fuel_deposits);
             self.fuel_borrows = self.fuel_borrows.saturating_add(fuel_borrows);
             self.fuel_positions = self.fuel_positions.saturating_add(fuel_positions);
+            self.fuel_lp = self.fuel_lp.saturating_add(fuel_lp);
 
             user.last_fuel_bonus_update_ts = now.cast()?;
         }
@@ -1675,13 +1785,24 @@ impl UserStats {
         Ok(())
     }
 
+    pub fn update_fuel_keeper_bonus(&mut self, fuel_boost: u8, keeper_reward: u64) -> DriftResult {
+        if fuel_boost > 0 {
+            self.fuel_keeper = self.fuel_keeper.saturating_add(
+                calculate_keeper_fuel_bonus(fuel_boost, keeper_reward)?
+                    .cast::<u32>()
+                    .unwrap_or(u32::MAX),
+            );
+        }
+        Ok(())
+    }
+
     pub fn update_maker_volume_30d(
         &mut self,
         fuel_boost: u8,
         quote_asset_amount: u64,
         now: i64,
     ) -> DriftResult {
-        let since_last = max(1_i64, now.safe_sub(self.last_maker_volume_30d_ts)?);
+        let since_last = max(1_i64, now.safe_sub(self.last_maker_volume_30d_ts.cast()?)?);
 
         self.update_fuel_maker_bonus(fuel_boost, quote_asset_amount)?;
 
@@ -1691,7 +1812,24 @@ impl UserStats {
             since_last,
             THIRTY_DAY,
         )?;
-        self.last_maker_volume_30d_ts = now;
+        self.last_maker_volume_30d_ts = now.cast()?;
+
+        Ok(())
+    }
+
+    pub fn update_referee_volume_30d(&mut self, quote_asset_amount: u64, now: i64) -> DriftResult {
+        let since_last = max(
+            1_i64,
+            now.safe_sub(self.last_referee_volume_30d_ts.cast()?)?,
+        );
+
+        self.referee_volume_30d = calculate_rolling_sum(
+            self.referee_volume_30d,
+            quote_asset_amount,
+            since_last,
+            THIRTY_DAY,
+        )?;
+        self.last_referee_volume_30d_ts = now.cast()?;
 
         Ok(())
     }
@@ -1702,7 +1840,7 @@ impl UserStats {
         quote_asset_amount: u64,
         now: i64,
     ) -> DriftResult {
-        let since_last = max(1_i64, now.safe_sub(self.last_taker_volume_30d_ts)?);
+        let since_last = max(1_i64, now.safe_sub(self.last_taker_volume_30d_ts.cast()?)?);
 
         self.update_fuel_taker_bonus(fuel_boost, quote_asset_amount)?;
 
@@ -1712,7 +1850,7 @@ impl UserStats {
             since_last,
             THIRTY_DAY,
         )?;
-        self.last_taker_volume_30d_ts = now;
+        self.last_taker_volume_30d_ts = now.cast()?;
 
         Ok(())
     }
@@ -1744,6 +1882,12 @@ impl UserStats {
         Ok(())
     }
 
+    pub fn decrement_total_referrer_reward(&mut self, reward: u64) -> DriftResult {
+        self.fees.total_referrer_reward = self.fees.total_referrer_reward.safe_sub(reward)?;
+
+        Ok(())
+    }
+
     pub fn increment_total_referrer_reward(&mut self, reward: u64, now: i64) -> DriftResult {
         self.fees.total_referrer_reward = self.fees.total_referrer_reward.safe_add(reward)?;
 
@@ -1784,8 +1928,8 @@ impl UserStats {
         // upper bound of age of the user stats account
         let min_action_ts: i64 = self
             .last_filler_volume_30d_ts
-            .min(self.last_maker_volume_30d_ts)
-            .min(self.last_taker_volume_30d_ts);
+            .min(self.last_maker_volume_30d_ts.cast().unwrap_or(i64::MAX))
+            .min(self.last_taker_volume_30d_ts.cast().unwrap_or(i64::MAX));
         now.saturating_sub(min_action_ts).max(0)
     }
 }
@@ -626,6 +626,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: None,
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -651,6 +652,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(110 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -676,6 +678,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(90 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -723,6 +726,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: None,
+            confidence_adjustment: 0,
         };
 
         let OrderFillSimulation {
@@ -749,6 +753,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(110 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
 
         let OrderFillSimulation {
@@ -775,6 +780,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(90 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
 
         let OrderFillSimulation {
@@ -823,6 +829,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: None,
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -848,6 +855,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(110 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -873,6 +881,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(90 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -920,6 +929,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: None,
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -945,6 +955,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(110 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -970,6 +981,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(90 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -1017,6 +1029,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: None,
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -1042,6 +1055,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(110 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -1067,6 +1081,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(90 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -1114,6 +1129,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: None,
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -1139,6 +1155,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(110 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -1164,6 +1181,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(90 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -1211,6 +1229,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: None,
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -1236,6 +1255,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(110 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -1261,6 +1281,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(90 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -1308,6 +1329,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: None,
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -1333,6 +1355,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(110 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -1358,6 +1381,7 @@ mod get_worst_case_fill_simulation {
         let strict_price = StrictOraclePrice {
             current: oracle_price_data.price,
             twap_5min: Some(90 * PRICE_PRECISION_I64),
+            confidence_adjustment: 0,
         };
         let OrderFillSimulation {
             token_amount: worst_case_token_amount,
@@ -1495,6 +1519,106 @@ mod apply_user_custom_margin_ratio {
     }
 }
 
+mod apply_asset_group_weights {
+    use crate::math::constants::{PRICE_PRECISION_I64, QUOTE_PRECISION_I128};
+    use crate::math::margin::MarginRequirementType;
+    use crate::state::spot_market::SpotMarket;
+    use crate::state::user::OrderFillSimulation;
+    use crate::MARGIN_PRECISION;
+
+    #[test]
+    fn test() {
+        let sol = SpotMarket {
+            asset_group: 1,
+            asset_group_margin_ratio_initial: 50,     // 5%
+            asset_group_margin_ratio_maintenance: 30, // 3%
+            ..SpotMarket::default_base_market()
+        };
+        let oracle_price = 100 * PRICE_PRECISION_I64;
+
+        let deposit = OrderFillSimulation {
+            token_value: 100 * QUOTE_PRECISION_I128,
+            weighted_token_value: 80 * QUOTE_PRECISION_I128,
+            free_collateral_contribution: 80 * QUOTE_PRECISION_I128,
+            ..OrderFillSimulation::default()
+        };
+
+        let actual = deposit
+            .apply_asset_group_weights(&sol, oracle_price, MarginRequirementType::Initial, 0)
+            .unwrap();
+        assert_eq!(actual.weighted_token_value, 95 * QUOTE_PRECISION_I128);
+        assert_eq!(
+            actual.free_collateral_contribution,
+            95 * QUOTE_PRECISION_I128
+        );
+
+        let actual = deposit
+            .apply_asset_group_weights(&sol, oracle_price, MarginRequirementType::Maintenance, 0)
+            .unwrap();
+        assert_eq!(actual.weighted_token_value, 97 * QUOTE_PRECISION_I128);
+
+        let borrow = OrderFillSimulation {
+            token_value: -100 * QUOTE_PRECISION_I128,
+            weighted_token_value: -120 * QUOTE_PRECISION_I128,
+            orders_value: 100 * QUOTE_PRECISION_I128,
+            free_collateral_contribution: -20 * QUOTE_PRECISION_I128,
+            ..OrderFillSimulation::default()
+        };
+
+        let actual = borrow
+            .apply_asset_group_weights(&sol, oracle_price, MarginRequirementType::Initial, 0)
+            .unwrap();
+        assert_eq!(actual.weighted_token_value, -105 * QUOTE_PRECISION_I128);
+        assert_eq!(
+            actual.free_collateral_contribution,
+            -5 * QUOTE_PRECISION_I128
+        );
+
+        let actual = borrow
+            .apply_asset_group_weights(&sol, oracle_price, MarginRequirementType::Maintenance, 0)
+            .unwrap();
+        assert_eq!(actual.weighted_token_value, -103 * QUOTE_PRECISION_I128);
+
+        // user custom margin ratio still applies
+        let custom_margin_ratio = MARGIN_PRECISION / 2; // 2x
+        let actual = deposit
+            .apply_asset_group_weights(
+                &sol,
+                oracle_price,
+                MarginRequirementType::Initial,
+                custom_margin_ratio,
+            )
+            .unwrap();
+        assert_eq!(actual.weighted_token_value, 50 * QUOTE_PRECISION_I128);
+
+        let actual = borrow
+            .apply_asset_group_weights(
+                &sol,
+                oracle_price,
+                MarginRequirementType::Initial,
+                custom_margin_ratio,
+            )
+            .unwrap();
+        assert_eq!(actual.weighted_token_value, -150 * QUOTE_PRECISION_I128);
+
+        // group weights never worse than the standard weights
+        let sol = SpotMarket {
+            asset_group_margin_ratio_initial: 250,
+            asset_group_margin_ratio_maintenance: 250,
+            ..sol
+        };
+        let actual = deposit
+            .apply_asset_group_weights(&sol, oracle_price, MarginRequirementType::Initial, 0)
+            .unwrap();
+        assert_eq!(actual.weighted_token_value, 80 * QUOTE_PRECISION_I128);
+
+        let actual = borrow
+            .apply_asset_group_weights(&sol, oracle_price, MarginRequirementType::Initial, 0)
+            .unwrap();
+        assert_eq!(actual.weighted_token_value, -120 * QUOTE_PRECISION_I128);
+    }
+}
+
 mod get_base_asset_amount_unfilled {
     use crate::controller::position::PositionDirection;
     use crate::state::user::Order;
@@ -1939,6 +2063,28 @@ mod get_user_stats_age_ts {
         let age = user_stats.get_age_ts(now);
         assert_eq!(age, 0);
     }
+
+    #[test]
+    fn volume_ts_written_as_i64() {
+        // the maker and taker volume timestamps used to be i64. the high bytes of a timestamp are
+        // zero, so accounts written before the split read the same timestamp and no lp or keeper
+        // fuel
+        let mut user_stats = UserStats::default();
+        let ts: i64 = 1_700_000_000;
+
+        let base = &user_stats as *const UserStats as usize;
+        let maker_offset = &user_stats.last_maker_volume_30d_ts as *const u32 as usize - base;
+        let taker_offset = &user_stats.last_taker_volume_30d_ts as *const u32 as usize - base;
+
+        let bytes = bytemuck::bytes_of_mut(&mut user_stats);
+        bytes[maker_offset..maker_offset + 8].copy_from_slice(&ts.to_le_bytes());
+        bytes[taker_offset..taker_offset + 8].copy_from_slice(&ts.to_le_bytes());
+
+        assert_eq!(user_stats.last_maker_volume_30d_ts, ts as u32);
+        assert_eq!(user_stats.fuel_lp, 0);
+        assert_eq!(user_stats.last_taker_volume_30d_ts, ts as u32);
+        assert_eq!(user_stats.fuel_keeper, 0);
+    }
 }
 
 mod fuel {
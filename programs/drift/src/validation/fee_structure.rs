@@ -4,7 +4,7 @@ use crate::error::{DriftResult, ErrorCode};
 use crate::math::constants::{
     FEE_DENOMINATOR, FEE_PERCENTAGE_DENOMINATOR, OPEN_ORDER_MARGIN_REQUIREMENT,
 };
-use crate::state::state::{FeeStructure, FeeTier};
+use crate::state::state::{FeeStructure, FeeTier, FeeTierQualification, ReferralTierConfig};
 use crate::validate;
 
 #[cfg(test)]
@@ -103,3 +103,85 @@ pub fn validate_fee_tier(
 
     Ok(())
 }
+
+pub fn validate_fee_tier_qualifications(
+    fee_tier_qualifications: &[FeeTierQualification],
+) -> DriftResult {
+    validate!(
+        !fee_tier_qualifications[0].is_enabled(),
+        ErrorCode::InvalidFeeStructure,
+        "fee tier 0 can not have qualification thresholds"
+    )?;
+
+    // each threshold must increase with the fee tier, ignoring tiers where it is disabled
+    let mut last_qualification = FeeTierQualification::default();
+    for (i, qualification) in fee_tier_qualifications.iter().enumerate().skip(1) {
+        for (name, threshold, last_threshold) in [
+            (
+                "volume 30d",
+                qualification.volume_30d_threshold,
+                &mut last_qualification.volume_30d_threshold,
+            ),
+            (
+                "if staked quote asset amount",
+                qualification.if_staked_quote_asset_amount_threshold,
+                &mut last_qualification.if_staked_quote_asset_amount_threshold,
+            ),
+            (
+                "if staked gov token amount",
+                qualification.if_staked_gov_token_amount_threshold,
+                &mut last_qualification.if_staked_gov_token_amount_threshold,
+            ),
+        ] {
+            if threshold == 0 {
+                continue;
+            }
+
+            validate!(
+                threshold > *last_threshold,
+                ErrorCode::InvalidFeeStructure,
+                "fee tier ({}) {} threshold ({}) must be greater than lower tier's ({})",
+                i,
+                name,
+                threshold,
+                last_threshold
+            )?;
+            *last_threshold = threshold;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_referral_tier_config(
+    referral_tier_config: &ReferralTierConfig,
+    perp_fee_structure: &FeeStructure,
+) -> DriftResult {
+    let mut last_threshold = 0_u64;
+    for (i, referral_tier) in referral_tier_config.tiers.iter().enumerate() {
+        if referral_tier.referee_volume_30d_threshold == 0 {
+            continue;
+        }
+
+        validate!(
+            referral_tier.referee_volume_30d_threshold > last_threshold,
+            ErrorCode::InvalidReferralTierConfig,
+            "referral tier ({}) threshold ({}) must be greater than previous tier's ({})",
+            i,
+            referral_tier.referee_volume_30d_threshold,
+            last_threshold
+        )?;
+        last_threshold = referral_tier.referee_volume_30d_threshold;
+
+        validate_fee_structure(&perp_fee_structure.with_referral_tier(referral_tier))?;
+    }
+
+    validate!(
+        referral_tier_config.sub_affiliate_reward_numerator <= 50, // <= 50%
+        ErrorCode::InvalidReferralTierConfig,
+        "invalid sub affiliate reward numerator ({})",
+        referral_tier_config.sub_affiliate_reward_numerator
+    )?;
+
+    Ok(())
+}
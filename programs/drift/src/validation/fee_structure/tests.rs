@@ -1,5 +1,10 @@
-use crate::state::state::FeeStructure;
-use crate::validation::fee_structure::validate_fee_structure;
+use crate::math::constants::QUOTE_PRECISION_U64;
+use crate::state::state::{
+    FeeStructure, FeeTierQualification, FeeTierQualifications, ReferralTier, ReferralTierConfig,
+};
+use crate::validation::fee_structure::{
+    validate_fee_structure, validate_fee_tier_qualifications, validate_referral_tier_config,
+};
 
 #[test]
 fn default_fee_structures() {
@@ -11,3 +16,74 @@ fn default_fee_structures() {
     default_spot_fees.flat_filler_fee = 3333;
     validate_fee_structure(&default_spot_fees).unwrap();
 }
+
+#[test]
+fn referral_tier_config() {
+    let mut default_perp_fees = FeeStructure::perps_default();
+    default_perp_fees.flat_filler_fee = 3333;
+
+    let mut config = ReferralTierConfig::default();
+    validate_referral_tier_config(&config, &default_perp_fees).unwrap();
+
+    config.tiers[0] = ReferralTier {
+        referee_volume_30d_threshold: 100_000 * QUOTE_PRECISION_U64,
+        referrer_reward_numerator: 20,
+        referee_fee_numerator: 10,
+    };
+    config.tiers[2] = ReferralTier {
+        referee_volume_30d_threshold: 1_000_000 * QUOTE_PRECISION_U64,
+        referrer_reward_numerator: 20,
+        referee_fee_numerator: 20,
+    };
+    config.sub_affiliate_reward_numerator = 50;
+    validate_referral_tier_config(&config, &default_perp_fees).unwrap();
+
+    // thresholds must increase
+    config.tiers[2].referee_volume_30d_threshold = 100_000 * QUOTE_PRECISION_U64;
+    assert!(validate_referral_tier_config(&config, &default_perp_fees).is_err());
+    config.tiers[2].referee_volume_30d_threshold = 1_000_000 * QUOTE_PRECISION_U64;
+
+    // tier must produce a valid fee structure
+    config.tiers[2].referrer_reward_numerator = 21;
+    assert!(validate_referral_tier_config(&config, &default_perp_fees).is_err());
+    config.tiers[2].referrer_reward_numerator = 20;
+
+    config.sub_affiliate_reward_numerator = 51;
+    assert!(validate_referral_tier_config(&config, &default_perp_fees).is_err());
+}
+
+#[test]
+fn fee_tier_qualifications() {
+    let fee_tier_qualifications = FeeTierQualifications::perps_default().fee_tier_qualifications;
+    validate_fee_tier_qualifications(&fee_tier_qualifications).unwrap();
+    validate_fee_tier_qualifications(
+        &FeeTierQualifications::spot_default().fee_tier_qualifications,
+    )
+    .unwrap();
+
+    // tier 0 can't have requirements
+    let mut invalid_fee_tier_qualifications = fee_tier_qualifications;
+    invalid_fee_tier_qualifications[0].volume_30d_threshold = QUOTE_PRECISION_U64;
+    assert!(validate_fee_tier_qualifications(&invalid_fee_tier_qualifications).is_err());
+
+    // thresholds must increase with the tier
+    let mut invalid_fee_tier_qualifications = fee_tier_qualifications;
+    invalid_fee_tier_qualifications[4].if_staked_gov_token_amount_threshold =
+        invalid_fee_tier_qualifications[3].if_staked_gov_token_amount_threshold;
+    assert!(validate_fee_tier_qualifications(&invalid_fee_tier_qualifications).is_err());
+
+    // disabled thresholds are skipped
+    let mut valid_fee_tier_qualifications = fee_tier_qualifications;
+    valid_fee_tier_qualifications[3].volume_30d_threshold = 0;
+    valid_fee_tier_qualifications[6] = FeeTierQualification {
+        volume_30d_threshold: fee_tier_qualifications[5].volume_30d_threshold * 2,
+        ..FeeTierQualification::default()
+    };
+    validate_fee_tier_qualifications(&valid_fee_tier_qualifications).unwrap();
+
+    valid_fee_tier_qualifications[7] = FeeTierQualification {
+        volume_30d_threshold: fee_tier_qualifications[5].volume_30d_threshold,
+        ..FeeTierQualification::default()
+    };
+    assert!(validate_fee_tier_qualifications(&valid_fee_tier_qualifications).is_err());
+}
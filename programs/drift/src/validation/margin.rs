@@ -1,8 +1,10 @@
 use crate::error::{DriftResult, ErrorCode};
 use crate::math::constants::{
-    LIQUIDATION_FEE_TO_MARGIN_PRECISION_RATIO, MAX_MARGIN_RATIO, MIN_MARGIN_RATIO,
-    SPOT_IMF_PRECISION, SPOT_WEIGHT_PRECISION,
+    FILL_TWAP_ORACLE_MIN_MARGIN_RATIO_INITIAL, LIQUIDATION_FEE_TO_MARGIN_PRECISION_RATIO,
+    MAX_MARGIN_CONFIDENCE_MULTIPLIER, MAX_MARGIN_RATIO, MIN_MARGIN_RATIO, SPOT_IMF_PRECISION,
+    SPOT_WEIGHT_PRECISION,
 };
+use crate::state::oracle::OracleSource;
 use crate::validate;
 use solana_program::msg;
 
@@ -41,6 +43,36 @@ pub fn validate_margin(
     Ok(())
 }
 
+/// markets priced off their own fills can't offer more than 5x leverage
+pub fn validate_margin_for_oracle_source(
+    oracle_source: OracleSource,
+    margin_ratio_initial: u32,
+) -> DriftResult {
+    if oracle_source == OracleSource::FillTwap {
+        validate!(
+            margin_ratio_initial >= FILL_TWAP_ORACLE_MIN_MARGIN_RATIO_INITIAL,
+            ErrorCode::InvalidMarginRatio,
+            "margin_ratio_initial ({}) must be >= {} for fill twap oracle",
+            margin_ratio_initial,
+            FILL_TWAP_ORACLE_MIN_MARGIN_RATIO_INITIAL
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn validate_margin_confidence_multiplier(margin_confidence_multiplier: u8) -> DriftResult {
+    validate!(
+        margin_confidence_multiplier <= MAX_MARGIN_CONFIDENCE_MULTIPLIER,
+        ErrorCode::DefaultError,
+        "margin_confidence_multiplier ({}) must be <= {}",
+        margin_confidence_multiplier,
+        MAX_MARGIN_CONFIDENCE_MULTIPLIER
+    )?;
+
+    Ok(())
+}
+
 pub fn validate_margin_weights(
     spot_market_index: u16,
     initial_asset_weight: u32,
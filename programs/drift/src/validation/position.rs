@@ -63,5 +63,12 @@ pub fn validate_spot_position(position: &SpotPosition) -> DriftResult {
         position.open_asks,
     )?;
 
+    validate!(
+        !position.is_term() || !position.has_open_order(),
+        ErrorCode::InvalidSpotPositionDetected,
+        "user spot={} term position has open orders",
+        position.market_index,
+    )?;
+
     Ok(())
 }
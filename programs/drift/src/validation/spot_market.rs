@@ -1,5 +1,7 @@
 use crate::error::{DriftResult, ErrorCode};
-use crate::math::constants::SPOT_UTILIZATION_PRECISION_U32;
+use crate::math::casting::Cast;
+use crate::math::constants::{ONE_BPS_DENOMINATOR, SPOT_UTILIZATION_PRECISION_U32};
+use crate::state::spot_market::BorrowRateKink;
 use crate::validate;
 use solana_program::msg;
 
@@ -8,6 +10,7 @@ pub fn validate_borrow_rate(
     optimal_borrow_rate: u32,
     max_borrow_rate: u32,
     min_borrow_rate: u32,
+    borrow_rate_kinks: &[BorrowRateKink],
 ) -> DriftResult {
     validate!(
         optimal_utilization <= SPOT_UTILIZATION_PRECISION_U32,
@@ -32,5 +35,55 @@ pub fn validate_borrow_rate(
         min_borrow_rate
     )?;
 
+    validate_borrow_rate_kinks(borrow_rate_kinks)?;
+
+    Ok(())
+}
+
+pub fn validate_borrow_rate_kinks(borrow_rate_kinks: &[BorrowRateKink]) -> DriftResult {
+    let mut previous_kink = BorrowRateKink::default();
+    let mut kinks_ended = false;
+
+    for kink in borrow_rate_kinks.iter() {
+        if kink.utilization == 0 {
+            validate!(
+                kink.borrow_rate == 0,
+                ErrorCode::InvalidSpotMarketInitialization,
+                "For spot market, unused borrow rate kink must have zero borrow rate"
+            )?;
+
+            kinks_ended = true;
+            continue;
+        }
+
+        validate!(
+            !kinks_ended,
+            ErrorCode::InvalidSpotMarketInitialization,
+            "For spot market, borrow rate kinks must be set before unused kinks"
+        )?;
+
+        validate!(
+            kink.utilization > previous_kink.utilization
+                && kink.utilization.cast::<u32>()? < ONE_BPS_DENOMINATOR,
+            ErrorCode::InvalidSpotMarketInitialization,
+            "For spot market, borrow rate kink utilization ({}) must be > previous kink ({}) and < {}",
+            kink.utilization,
+            previous_kink.utilization,
+            ONE_BPS_DENOMINATOR
+        )?;
+
+        validate!(
+            kink.borrow_rate >= previous_kink.borrow_rate
+                && kink.borrow_rate.cast::<u32>()? <= ONE_BPS_DENOMINATOR,
+            ErrorCode::InvalidSpotMarketInitialization,
+            "For spot market, borrow rate kink borrow rate ({}) must be >= previous kink ({}) and <= {}",
+            kink.borrow_rate,
+            previous_kink.borrow_rate,
+            ONE_BPS_DENOMINATOR
+        )?;
+
+        previous_kink = *kink;
+    }
+
     Ok(())
 }
@@ -132,6 +132,129 @@ export async function getInsuranceFundVaultPublicKey(
 	)[0];
 }
 
+export function getInsuranceFundShareMintPublicKey(
+	programId: PublicKey,
+	marketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('insurance_fund_share_mint')),
+			new anchor.BN(marketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getSeniorInsuranceFundPublicKey(
+	programId: PublicKey,
+	marketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('senior_insurance_fund')),
+			new anchor.BN(marketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getSeniorInsuranceFundVaultPublicKey(
+	programId: PublicKey,
+	marketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('senior_insurance_fund_vault')),
+			new anchor.BN(marketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getInsuranceFundPerformancePublicKey(
+	programId: PublicKey,
+	marketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('insurance_fund_performance')),
+			new anchor.BN(marketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getSeniorInsuranceFundPerformancePublicKey(
+	programId: PublicKey,
+	marketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(
+				anchor.utils.bytes.utf8.encode('senior_insurance_fund_performance')
+			),
+			new anchor.BN(marketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getPerpInsuranceFundPerformancePublicKey(
+	programId: PublicKey,
+	perpMarketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(
+				anchor.utils.bytes.utf8.encode('perp_insurance_fund_performance')
+			),
+			new anchor.BN(perpMarketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getPerpInsuranceFundPublicKey(
+	programId: PublicKey,
+	perpMarketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('perp_insurance_fund')),
+			new anchor.BN(perpMarketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getPerpInsuranceFundVaultPublicKey(
+	programId: PublicKey,
+	perpMarketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('perp_insurance_fund_vault')),
+			new anchor.BN(perpMarketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getPerpInsuranceFundStakeAccountPublicKey(
+	programId: PublicKey,
+	authority: PublicKey,
+	perpMarketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('perp_insurance_fund_stake')),
+			authority.toBuffer(),
+			new anchor.BN(perpMarketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
 export function getInsuranceFundStakeAccountPublicKey(
 	programId: PublicKey,
 	authority: PublicKey,
@@ -147,6 +270,104 @@ export function getInsuranceFundStakeAccountPublicKey(
 	)[0];
 }
 
+export function getSeniorInsuranceFundStakeAccountPublicKey(
+	programId: PublicKey,
+	authority: PublicKey,
+	marketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('insurance_fund_stake')),
+			authority.toBuffer(),
+			new anchor.BN(marketIndex).toArrayLike(Buffer, 'le', 2),
+			Buffer.from([1]), // InsuranceFundTranche::Senior
+		],
+		programId
+	)[0];
+}
+
+export function getTermLoanAccountPublicKey(
+	programId: PublicKey,
+	lenderUser: PublicKey,
+	offerId: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('term_loan')),
+			lenderUser.toBuffer(),
+			new anchor.BN(offerId).toArrayLike(Buffer, 'le', 4),
+		],
+		programId
+	)[0];
+}
+
+export function getFuelSeasonPublicKey(
+	programId: PublicKey,
+	season: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('fuel_season')),
+			new anchor.BN(season).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getFuelSeasonVaultPublicKey(
+	programId: PublicKey,
+	season: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('fuel_season_vault')),
+			new anchor.BN(season).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getUserFuelSeasonRecordPublicKey(
+	programId: PublicKey,
+	authority: PublicKey,
+	season: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('user_fuel_season_record')),
+			authority.toBuffer(),
+			new anchor.BN(season).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getMakerRewardsProgramPublicKey(
+	programId: PublicKey,
+	marketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('maker_rewards_program')),
+			new anchor.BN(marketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getMakerRewardsVaultPublicKey(
+	programId: PublicKey,
+	marketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('maker_rewards_vault')),
+			new anchor.BN(marketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
 export function getDriftSignerPublicKey(programId: PublicKey): PublicKey {
 	return PublicKey.findProgramAddressSync(
 		[Buffer.from(anchor.utils.bytes.utf8.encode('drift_signer'))],
@@ -241,6 +462,114 @@ export function getProtocolIfSharesTransferConfigPublicKey(
 	)[0];
 }
 
+export function getPerpMarketFeeStructurePublicKey(
+	programId: PublicKey,
+	marketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('perp_market_fee_structure')),
+			new anchor.BN(marketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getPerpMarketDepthBoundsPublicKey(
+	programId: PublicKey,
+	marketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('perp_market_depth_bounds')),
+			new anchor.BN(marketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getPerpMarketOracleCircuitBreakerWindowPublicKey(
+	programId: PublicKey,
+	marketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(
+				anchor.utils.bytes.utf8.encode(
+					'perp_market_oracle_circuit_breaker_window'
+				)
+			),
+			new anchor.BN(marketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getSpotMarketOracleCircuitBreakerWindowPublicKey(
+	programId: PublicKey,
+	marketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(
+				anchor.utils.bytes.utf8.encode(
+					'spot_market_oracle_circuit_breaker_window'
+				)
+			),
+			new anchor.BN(marketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getSpotMarketFeeStructurePublicKey(
+	programId: PublicKey,
+	marketIndex: number
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(anchor.utils.bytes.utf8.encode('spot_market_fee_structure')),
+			new anchor.BN(marketIndex).toArrayLike(Buffer, 'le', 2),
+		],
+		programId
+	)[0];
+}
+
+export function getPerpFeeTierQualificationsPublicKey(
+	programId: PublicKey
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(
+				anchor.utils.bytes.utf8.encode('perp_fee_tier_qualifications')
+			),
+		],
+		programId
+	)[0];
+}
+
+export function getSpotFeeTierQualificationsPublicKey(
+	programId: PublicKey
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[
+			Buffer.from(
+				anchor.utils.bytes.utf8.encode('spot_fee_tier_qualifications')
+			),
+		],
+		programId
+	)[0];
+}
+
+export function getReferralTierConfigPublicKey(
+	programId: PublicKey
+): PublicKey {
+	return PublicKey.findProgramAddressSync(
+		[Buffer.from(anchor.utils.bytes.utf8.encode('referral_tier_config'))],
+		programId
+	)[0];
+}
+
 export function getPrelaunchOraclePublicKey(
 	programId: PublicKey,
 	marketIndex: number
@@ -7,6 +7,9 @@ import {
 } from '@solana/web3.js';
 import {
 	FeeStructure,
+	FeeTierQualification,
+	isVariant,
+	MarketType,
 	OracleGuardRails,
 	OracleSource,
 	ExchangeStatus,
@@ -32,6 +35,10 @@ import {
 	getOpenbookV2FulfillmentConfigPublicKey,
 	getPythPullOraclePublicKey,
 	getUserStatsAccountPublicKey,
+	getFuelSeasonPublicKey,
+	getFuelSeasonVaultPublicKey,
+	getMakerRewardsProgramPublicKey,
+	getMakerRewardsVaultPublicKey,
 } from './addresses/pda';
 import { squareRootBN } from './math/utils';
 import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
@@ -1382,6 +1389,44 @@ export class AdminClient extends DriftClient {
 		);
 	}
 
+	public async updatePerpMarketLpWithdrawCap(
+		perpMarketIndex: number,
+		lpWithdrawCap: number
+	): Promise<TransactionSignature> {
+		const updatePerpMarketLpWithdrawCapIx =
+			await this.getUpdatePerpMarketLpWithdrawCapIx(
+				perpMarketIndex,
+				lpWithdrawCap
+			);
+
+		const tx = await this.buildTransaction(updatePerpMarketLpWithdrawCapIx);
+
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+
+		return txSig;
+	}
+
+	public async getUpdatePerpMarketLpWithdrawCapIx(
+		perpMarketIndex: number,
+		lpWithdrawCap: number
+	): Promise<TransactionInstruction> {
+		return await this.program.instruction.updatePerpMarketLpWithdrawCap(
+			lpWithdrawCap,
+			{
+				accounts: {
+					admin: this.isSubscribed
+						? this.getStateAccount().admin
+						: this.wallet.publicKey,
+					state: await this.getStatePublicKey(),
+					perpMarket: await getPerpMarketPublicKey(
+						this.program.programId,
+						perpMarketIndex
+					),
+				},
+			}
+		);
+	}
+
 	public async updatePerpMarketName(
 		perpMarketIndex: number,
 		name: string
@@ -1574,6 +1619,86 @@ export class AdminClient extends DriftClient {
 		});
 	}
 
+	public async initializeFeeTierQualifications(
+		marketType: MarketType,
+		feeTierQualifications: FeeTierQualification[]
+	): Promise<TransactionSignature> {
+		const initializeFeeTierQualificationsIx =
+			await this.getInitializeFeeTierQualificationsIx(
+				marketType,
+				feeTierQualifications
+			);
+
+		const tx = await this.buildTransaction(initializeFeeTierQualificationsIx);
+
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+
+		return txSig;
+	}
+
+	public async getInitializeFeeTierQualificationsIx(
+		marketType: MarketType,
+		feeTierQualifications: FeeTierQualification[]
+	): Promise<TransactionInstruction> {
+		const accounts = {
+			admin: this.isSubscribed
+				? this.getStateAccount().admin
+				: this.wallet.publicKey,
+			state: await this.getStatePublicKey(),
+			feeTierQualifications: this.getFeeTierQualificationsPublicKey(marketType),
+			rent: SYSVAR_RENT_PUBKEY,
+			systemProgram: anchor.web3.SystemProgram.programId,
+		};
+
+		if (isVariant(marketType, 'perp')) {
+			return await this.program.instruction.initializePerpFeeTierQualifications(
+				feeTierQualifications,
+				{ accounts }
+			);
+		} else {
+			return await this.program.instruction.initializeSpotFeeTierQualifications(
+				feeTierQualifications,
+				{ accounts }
+			);
+		}
+	}
+
+	public async updateFeeTierQualifications(
+		marketType: MarketType,
+		feeTierQualifications: FeeTierQualification[]
+	): Promise<TransactionSignature> {
+		const updateFeeTierQualificationsIx =
+			await this.getUpdateFeeTierQualificationsIx(
+				marketType,
+				feeTierQualifications
+			);
+
+		const tx = await this.buildTransaction(updateFeeTierQualificationsIx);
+
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+
+		return txSig;
+	}
+
+	public async getUpdateFeeTierQualificationsIx(
+		marketType: MarketType,
+		feeTierQualifications: FeeTierQualification[]
+	): Promise<TransactionInstruction> {
+		return await this.program.instruction.updateFeeTierQualifications(
+			feeTierQualifications,
+			{
+				accounts: {
+					admin: this.isSubscribed
+						? this.getStateAccount().admin
+						: this.wallet.publicKey,
+					state: await this.getStatePublicKey(),
+					feeTierQualifications:
+						this.getFeeTierQualificationsPublicKey(marketType),
+				},
+			}
+		);
+	}
+
 	public async updateInitialPctToLiquidate(
 		initialPctToLiquidate: number
 	): Promise<TransactionSignature> {
@@ -3299,6 +3424,46 @@ export class AdminClient extends DriftClient {
 		);
 	}
 
+	public async updatePerpMarketFundingInterestRateAdjustment(
+		perpMarketIndex: number,
+		fundingInterestRateAdjustment: number
+	): Promise<TransactionSignature> {
+		const updatePerpMarketFundingInterestRateAdjustmentIx =
+			await this.getUpdatePerpMarketFundingInterestRateAdjustmentIx(
+				perpMarketIndex,
+				fundingInterestRateAdjustment
+			);
+
+		const tx = await this.buildTransaction(
+			updatePerpMarketFundingInterestRateAdjustmentIx
+		);
+
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+
+		return txSig;
+	}
+
+	public async getUpdatePerpMarketFundingInterestRateAdjustmentIx(
+		perpMarketIndex: number,
+		fundingInterestRateAdjustment: number
+	): Promise<TransactionInstruction> {
+		return await this.program.instruction.updatePerpMarketFundingInterestRateAdjustment(
+			fundingInterestRateAdjustment,
+			{
+				accounts: {
+					admin: this.isSubscribed
+						? this.getStateAccount().admin
+						: this.wallet.publicKey,
+					state: await this.getStatePublicKey(),
+					perpMarket: await getPerpMarketPublicKey(
+						this.program.programId,
+						perpMarketIndex
+					),
+				},
+			}
+		);
+	}
+
 	public async updateSpotMarketFeeAdjustment(
 		perpMarketIndex: number,
 		feeAdjustment: number
@@ -3519,13 +3684,15 @@ export class AdminClient extends DriftClient {
 	public async initializePrelaunchOracle(
 		perpMarketIndex: number,
 		price?: BN,
-		maxPrice?: BN
+		maxPrice?: BN,
+		maxPriceChangePerSlot?: number
 	): Promise<TransactionSignature> {
 		const initializePrelaunchOracleIx =
 			await this.getInitializePrelaunchOracleIx(
 				perpMarketIndex,
 				price,
-				maxPrice
+				maxPrice,
+				maxPriceChangePerSlot
 			);
 
 		const tx = await this.buildTransaction(initializePrelaunchOracleIx);
@@ -3538,12 +3705,14 @@ export class AdminClient extends DriftClient {
 	public async getInitializePrelaunchOracleIx(
 		perpMarketIndex: number,
 		price?: BN,
-		maxPrice?: BN
+		maxPrice?: BN,
+		maxPriceChangePerSlot?: number
 	): Promise<TransactionInstruction> {
 		const params = {
 			perpMarketIndex,
 			price: price || null,
 			maxPrice: maxPrice || null,
+			maxPriceChangePerSlot: maxPriceChangePerSlot ?? null,
 		};
 
 		return await this.program.instruction.initializePrelaunchOracle(params, {
@@ -3565,13 +3734,15 @@ export class AdminClient extends DriftClient {
 	public async updatePrelaunchOracleParams(
 		perpMarketIndex: number,
 		price?: BN,
-		maxPrice?: BN
+		maxPrice?: BN,
+		maxPriceChangePerSlot?: number
 	): Promise<TransactionSignature> {
 		const updatePrelaunchOracleParamsIx =
 			await this.getUpdatePrelaunchOracleParamsIx(
 				perpMarketIndex,
 				price,
-				maxPrice
+				maxPrice,
+				maxPriceChangePerSlot
 			);
 
 		const tx = await this.buildTransaction(updatePrelaunchOracleParamsIx);
@@ -3584,12 +3755,14 @@ export class AdminClient extends DriftClient {
 	public async getUpdatePrelaunchOracleParamsIx(
 		perpMarketIndex: number,
 		price?: BN,
-		maxPrice?: BN
+		maxPrice?: BN,
+		maxPriceChangePerSlot?: number
 	): Promise<TransactionInstruction> {
 		const params = {
 			perpMarketIndex,
 			price: price || null,
 			maxPrice: maxPrice || null,
+			maxPriceChangePerSlot: maxPriceChangePerSlot ?? null,
 		};
 
 		const perpMarketPublicKey = await getPerpMarketPublicKey(
@@ -3715,13 +3888,17 @@ export class AdminClient extends DriftClient {
 		perpMarketIndex: number,
 		fuelBoostTaker?: number,
 		fuelBoostMaker?: number,
-		fuelBoostPosition?: number
+		fuelBoostPosition?: number,
+		fuelBoostLp?: number,
+		fuelBoostKeeper?: number
 	): Promise<TransactionSignature> {
 		const updatePerpMarketFuelIx = await this.getUpdatePerpMarketFuelIx(
 			perpMarketIndex,
 			fuelBoostTaker || null,
 			fuelBoostMaker || null,
-			fuelBoostPosition || null
+			fuelBoostPosition || null,
+			fuelBoostLp || null,
+			fuelBoostKeeper || null
 		);
 
 		const tx = await this.buildTransaction(updatePerpMarketFuelIx);
@@ -3734,7 +3911,9 @@ export class AdminClient extends DriftClient {
 		perpMarketIndex: number,
 		fuelBoostTaker?: number,
 		fuelBoostMaker?: number,
-		fuelBoostPosition?: number
+		fuelBoostPosition?: number,
+		fuelBoostLp?: number,
+		fuelBoostKeeper?: number
 	): Promise<TransactionInstruction> {
 		const perpMarketPublicKey = await getPerpMarketPublicKey(
 			this.program.programId,
@@ -3745,6 +3924,8 @@ export class AdminClient extends DriftClient {
 			fuelBoostTaker || null,
 			fuelBoostMaker || null,
 			fuelBoostPosition || null,
+			fuelBoostLp || null,
+			fuelBoostKeeper || null,
 			{
 				accounts: {
 					admin: this.isSubscribed
@@ -3813,6 +3994,243 @@ export class AdminClient extends DriftClient {
 		);
 	}
 
+	public async closeFuelSeason(
+		season: number,
+		rewardMint: PublicKey,
+		tokenProgram = TOKEN_PROGRAM_ID
+	): Promise<TransactionSignature> {
+		const closeFuelSeasonIx = await this.getCloseFuelSeasonIx(
+			season,
+			rewardMint,
+			tokenProgram
+		);
+
+		const tx = await this.buildTransaction(closeFuelSeasonIx);
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+
+		return txSig;
+	}
+
+	public async getCloseFuelSeasonIx(
+		season: number,
+		rewardMint: PublicKey,
+		tokenProgram = TOKEN_PROGRAM_ID
+	): Promise<TransactionInstruction> {
+		return await this.program.instruction.closeFuelSeason(season, {
+			accounts: {
+				admin: this.isSubscribed
+					? this.getStateAccount().admin
+					: this.wallet.publicKey,
+				state: await this.getStatePublicKey(),
+				fuelSeason: getFuelSeasonPublicKey(this.program.programId, season),
+				rewardMint,
+				fuelSeasonVault: getFuelSeasonVaultPublicKey(
+					this.program.programId,
+					season
+				),
+				driftSigner: this.getSignerPublicKey(),
+				rent: SYSVAR_RENT_PUBKEY,
+				systemProgram: anchor.web3.SystemProgram.programId,
+				tokenProgram,
+			},
+		});
+	}
+
+	public async updateFuelSeasonRedemptionRate(
+		season: number,
+		redemptionRate: BN
+	): Promise<TransactionSignature> {
+		const updateFuelSeasonRedemptionRateIx =
+			await this.getUpdateFuelSeasonRedemptionRateIx(season, redemptionRate);
+
+		const tx = await this.buildTransaction(updateFuelSeasonRedemptionRateIx);
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+
+		return txSig;
+	}
+
+	public async getUpdateFuelSeasonRedemptionRateIx(
+		season: number,
+		redemptionRate: BN
+	): Promise<TransactionInstruction> {
+		return await this.program.instruction.updateFuelSeasonRedemptionRate(
+			redemptionRate,
+			{
+				accounts: {
+					admin: this.isSubscribed
+						? this.getStateAccount().admin
+						: this.wallet.publicKey,
+					state: await this.getStatePublicKey(),
+					fuelSeason: getFuelSeasonPublicKey(this.program.programId, season),
+				},
+			}
+		);
+	}
+
+	public async initializeMakerRewardsProgram(
+		marketIndex: number,
+		rewardMint: PublicKey,
+		epochDuration: BN,
+		epochRewards: BN,
+		maxOracleDistance: number,
+		volumeRewardShare: number,
+		minMakerVolume30d: BN,
+		tokenProgram = TOKEN_PROGRAM_ID
+	): Promise<TransactionSignature> {
+		const initializeMakerRewardsProgramIx =
+			await this.getInitializeMakerRewardsProgramIx(
+				marketIndex,
+				rewardMint,
+				epochDuration,
+				epochRewards,
+				maxOracleDistance,
+				volumeRewardShare,
+				minMakerVolume30d,
+				tokenProgram
+			);
+
+		const tx = await this.buildTransaction(initializeMakerRewardsProgramIx);
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+
+		return txSig;
+	}
+
+	public async getInitializeMakerRewardsProgramIx(
+		marketIndex: number,
+		rewardMint: PublicKey,
+		epochDuration: BN,
+		epochRewards: BN,
+		maxOracleDistance: number,
+		volumeRewardShare: number,
+		minMakerVolume30d: BN,
+		tokenProgram = TOKEN_PROGRAM_ID
+	): Promise<TransactionInstruction> {
+		return await this.program.instruction.initializeMakerRewardsProgram(
+			marketIndex,
+			epochDuration,
+			epochRewards,
+			maxOracleDistance,
+			volumeRewardShare,
+			minMakerVolume30d,
+			{
+				accounts: {
+					admin: this.isSubscribed
+						? this.getStateAccount().admin
+						: this.wallet.publicKey,
+					state: await this.getStatePublicKey(),
+					perpMarket: await getPerpMarketPublicKey(
+						this.program.programId,
+						marketIndex
+					),
+					makerRewardsProgram: getMakerRewardsProgramPublicKey(
+						this.program.programId,
+						marketIndex
+					),
+					rewardMint,
+					makerRewardsVault: getMakerRewardsVaultPublicKey(
+						this.program.programId,
+						marketIndex
+					),
+					driftSigner: this.getSignerPublicKey(),
+					rent: SYSVAR_RENT_PUBKEY,
+					systemProgram: anchor.web3.SystemProgram.programId,
+					tokenProgram,
+				},
+			}
+		);
+	}
+
+	public async updateMakerRewardsProgram(
+		marketIndex: number,
+		epochDuration?: BN,
+		epochRewards?: BN,
+		maxOracleDistance?: number,
+		volumeRewardShare?: number,
+		minMakerVolume30d?: BN
+	): Promise<TransactionSignature> {
+		const updateMakerRewardsProgramIx =
+			await this.getUpdateMakerRewardsProgramIx(
+				marketIndex,
+				epochDuration,
+				epochRewards,
+				maxOracleDistance,
+				volumeRewardShare,
+				minMakerVolume30d
+			);
+
+		const tx = await this.buildTransaction(updateMakerRewardsProgramIx);
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+
+		return txSig;
+	}
+
+	public async getUpdateMakerRewardsProgramIx(
+		marketIndex: number,
+		epochDuration?: BN,
+		epochRewards?: BN,
+		maxOracleDistance?: number,
+		volumeRewardShare?: number,
+		minMakerVolume30d?: BN
+	): Promise<TransactionInstruction> {
+		return await this.program.instruction.updateMakerRewardsProgram(
+			marketIndex,
+			epochDuration || null,
+			epochRewards || null,
+			maxOracleDistance ?? null,
+			volumeRewardShare ?? null,
+			minMakerVolume30d ?? null,
+			{
+				accounts: {
+					admin: this.isSubscribed
+						? this.getStateAccount().admin
+						: this.wallet.publicKey,
+					state: await this.getStatePublicKey(),
+					makerRewardsProgram: getMakerRewardsProgramPublicKey(
+						this.program.programId,
+						marketIndex
+					),
+				},
+			}
+		);
+	}
+
+	public async removeMakerFromMakerRewardsProgram(
+		marketIndex: number,
+		user: PublicKey
+	): Promise<TransactionSignature> {
+		const removeMakerFromMakerRewardsProgramIx =
+			await this.getRemoveMakerFromMakerRewardsProgramIx(marketIndex, user);
+
+		const tx = await this.buildTransaction(
+			removeMakerFromMakerRewardsProgramIx
+		);
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+
+		return txSig;
+	}
+
+	public async getRemoveMakerFromMakerRewardsProgramIx(
+		marketIndex: number,
+		user: PublicKey
+	): Promise<TransactionInstruction> {
+		return await this.program.instruction.removeMakerFromMakerRewardsProgram(
+			marketIndex,
+			user,
+			{
+				accounts: {
+					admin: this.isSubscribed
+						? this.getStateAccount().admin
+						: this.wallet.publicKey,
+					state: await this.getStatePublicKey(),
+					makerRewardsProgram: getMakerRewardsProgramPublicKey(
+						this.program.programId,
+						marketIndex
+					),
+				},
+			}
+		);
+	}
+
 	public async initializePythPullOracle(
 		feedId: string
 	): Promise<TransactionSignature> {
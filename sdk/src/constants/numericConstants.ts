@@ -109,5 +109,6 @@ export const DUST_POSITION_SIZE = QUOTE_PRECISION.divn(100); // Dust position is
 
 export const FUEL_WINDOW = new BN(60 * 60 * 24 * 28); // 28 days
 export const FUEL_START_TS = new BN(1723147200); // unix timestamp
+export const FUEL_REDEMPTION_RATE_PRECISION = new BN(10).pow(new BN(6));
 
 export const MAX_PREDICTION_PRICE = PRICE_PRECISION;
@@ -48,6 +48,7 @@ import {
 	SignedTxData,
 	MappedRecord,
 	OpenbookV2FulfillmentConfigAccount,
+	SpotMarketFlag,
 } from './types';
 import * as anchor from '@coral-xyz/anchor';
 import driftIDL from './idl/drift.json';
@@ -78,12 +79,29 @@ import {
 	getDriftStateAccountPublicKey,
 	getInsuranceFundStakeAccountPublicKey,
 	getOpenbookV2FulfillmentConfigPublicKey,
+	getPerpInsuranceFundPublicKey,
+	getPerpInsuranceFundVaultPublicKey,
+	getInsuranceFundPerformancePublicKey,
+	getSeniorInsuranceFundPerformancePublicKey,
+	getPerpInsuranceFundPerformancePublicKey,
+	getPerpFeeTierQualificationsPublicKey,
+	getPerpMarketFeeStructurePublicKey,
+	getPerpMarketDepthBoundsPublicKey,
+	getPerpMarketOracleCircuitBreakerWindowPublicKey,
+	getSpotMarketOracleCircuitBreakerWindowPublicKey,
+	getMakerRewardsProgramPublicKey,
+	getMakerRewardsVaultPublicKey,
 	getPerpMarketPublicKey,
 	getPhoenixFulfillmentConfigPublicKey,
 	getPythPullOraclePublicKey,
+	getReferralTierConfigPublicKey,
 	getReferrerNamePublicKeySync,
 	getSerumFulfillmentConfigPublicKey,
 	getSerumSignerPublicKey,
+	getSeniorInsuranceFundPublicKey,
+	getSeniorInsuranceFundVaultPublicKey,
+	getSpotFeeTierQualificationsPublicKey,
+	getSpotMarketFeeStructurePublicKey,
 	getSpotMarketPublicKey,
 	getUserAccountPublicKey,
 	getUserAccountPublicKeySync,
@@ -1482,6 +1500,24 @@ export class DriftClient {
 		)) as ReferrerNameAccount;
 	}
 
+	/**
+	 * Rolling 30 day volume of the referrer's referees, which determines the
+	 * referrer's referral tier
+	 */
+	public async fetchReferrerRefereeVolume30D(
+		name: string
+	): Promise<BN | undefined> {
+		const referrerNameAccount = await this.fetchReferrerNameAccount(name);
+		if (!referrerNameAccount) {
+			return undefined;
+		}
+
+		const referrerStats = (await this.program.account.userStats.fetch(
+			referrerNameAccount.userStats
+		)) as UserStatsAccount;
+		return referrerStats.refereeVolume30D;
+	}
+
 	userStatsAccountPublicKey: PublicKey;
 	public getUserStatsAccountPublicKey(): PublicKey {
 		if (this.userStatsAccountPublicKey) {
@@ -1753,7 +1789,9 @@ export class DriftClient {
 			isWritable: writable,
 		});
 		const oracleWritable =
-			writable && isVariant(perpMarketAccount.amm.oracleSource, 'prelaunch');
+			writable &&
+			(isVariant(perpMarketAccount.amm.oracleSource, 'prelaunch') ||
+				isVariant(perpMarketAccount.amm.oracleSource, 'fillTwap'));
 		oracleAccountMap.set(perpMarketAccount.amm.oracle.toString(), {
 			pubkey: perpMarketAccount.amm.oracle,
 			isSigner: false,
@@ -2135,6 +2173,231 @@ export class DriftClient {
 		}
 	}
 
+	public addPerpInsuranceFundToRemainingAccounts(
+		perpMarketAccount: PerpMarketAccount,
+		remainingAccounts: AccountMeta[]
+	) {
+		if (perpMarketAccount.hasPerpInsuranceFund) {
+			remainingAccounts.push({
+				pubkey: getPerpInsuranceFundPublicKey(
+					this.program.programId,
+					perpMarketAccount.marketIndex
+				),
+				isSigner: false,
+				isWritable: false,
+			});
+			remainingAccounts.push({
+				pubkey: getPerpInsuranceFundVaultPublicKey(
+					this.program.programId,
+					perpMarketAccount.marketIndex
+				),
+				isSigner: false,
+				isWritable: true,
+			});
+		}
+	}
+
+	public addSeniorInsuranceFundToRemainingAccounts(
+		spotMarketAccount: SpotMarketAccount,
+		remainingAccounts: AccountMeta[]
+	) {
+		if (
+			(spotMarketAccount.flags & SpotMarketFlag.HAS_SENIOR_INSURANCE_FUND) >
+			0
+		) {
+			remainingAccounts.push({
+				pubkey: getSeniorInsuranceFundPublicKey(
+					this.program.programId,
+					spotMarketAccount.marketIndex
+				),
+				isSigner: false,
+				isWritable: true,
+			});
+			remainingAccounts.push({
+				pubkey: getSeniorInsuranceFundVaultPublicKey(
+					this.program.programId,
+					spotMarketAccount.marketIndex
+				),
+				isSigner: false,
+				isWritable: true,
+			});
+		}
+	}
+
+	public addInsuranceFundPerformanceToRemainingAccounts(
+		spotMarketAccount: SpotMarketAccount,
+		remainingAccounts: AccountMeta[]
+	) {
+		if (
+			(spotMarketAccount.flags &
+				SpotMarketFlag.HAS_INSURANCE_FUND_PERFORMANCE) >
+			0
+		) {
+			remainingAccounts.push({
+				pubkey: getInsuranceFundPerformancePublicKey(
+					this.program.programId,
+					spotMarketAccount.marketIndex
+				),
+				isSigner: false,
+				isWritable: true,
+			});
+		}
+	}
+
+	public addSeniorInsuranceFundPerformanceToRemainingAccounts(
+		spotMarketAccount: SpotMarketAccount,
+		remainingAccounts: AccountMeta[]
+	) {
+		if (
+			(spotMarketAccount.flags &
+				SpotMarketFlag.HAS_SENIOR_INSURANCE_FUND_PERFORMANCE) >
+			0
+		) {
+			remainingAccounts.push({
+				pubkey: getSeniorInsuranceFundPerformancePublicKey(
+					this.program.programId,
+					spotMarketAccount.marketIndex
+				),
+				isSigner: false,
+				isWritable: true,
+			});
+		}
+	}
+
+	public addPerpInsuranceFundPerformanceToRemainingAccounts(
+		perpMarketAccount: PerpMarketAccount,
+		remainingAccounts: AccountMeta[]
+	) {
+		if (perpMarketAccount.hasPerpInsuranceFundPerformance) {
+			remainingAccounts.push({
+				pubkey: getPerpInsuranceFundPerformancePublicKey(
+					this.program.programId,
+					perpMarketAccount.marketIndex
+				),
+				isSigner: false,
+				isWritable: true,
+			});
+		}
+	}
+
+	public addReferralTierConfigToRemainingAccounts(
+		remainingAccounts: AccountMeta[]
+	) {
+		if (this.getStateAccount().hasReferralTierConfig) {
+			remainingAccounts.push({
+				pubkey: getReferralTierConfigPublicKey(this.program.programId),
+				isSigner: false,
+				isWritable: false,
+			});
+		}
+	}
+
+	public getFeeTierQualificationsPublicKey(marketType: MarketType): PublicKey {
+		return isVariant(marketType, 'perp')
+			? getPerpFeeTierQualificationsPublicKey(this.program.programId)
+			: getSpotFeeTierQualificationsPublicKey(this.program.programId);
+	}
+
+	public addFeeTierQualificationsToRemainingAccounts(
+		marketType: MarketType,
+		remainingAccounts: AccountMeta[]
+	) {
+		const state = this.getStateAccount();
+		const hasFeeTierQualifications = isVariant(marketType, 'perp')
+			? state.hasPerpFeeTierQualifications
+			: state.hasSpotFeeTierQualifications;
+		if (hasFeeTierQualifications) {
+			remainingAccounts.push({
+				pubkey: this.getFeeTierQualificationsPublicKey(marketType),
+				isSigner: false,
+				isWritable: false,
+			});
+		}
+	}
+
+	public addPerpMarketFeeStructureToRemainingAccounts(
+		perpMarketAccount: PerpMarketAccount,
+		remainingAccounts: AccountMeta[]
+	) {
+		if (perpMarketAccount.hasFeeStructureOverride) {
+			remainingAccounts.push({
+				pubkey: getPerpMarketFeeStructurePublicKey(
+					this.program.programId,
+					perpMarketAccount.marketIndex
+				),
+				isSigner: false,
+				isWritable: false,
+			});
+		}
+	}
+
+	public addPerpMarketDepthBoundsToRemainingAccounts(
+		perpMarketAccount: PerpMarketAccount,
+		remainingAccounts: AccountMeta[]
+	) {
+		if (perpMarketAccount.hasDepthBounds) {
+			remainingAccounts.push({
+				pubkey: getPerpMarketDepthBoundsPublicKey(
+					this.program.programId,
+					perpMarketAccount.marketIndex
+				),
+				isSigner: false,
+				isWritable: false,
+			});
+		}
+	}
+
+	public addPerpMarketMakerRewardsProgramToRemainingAccounts(
+		perpMarketAccount: PerpMarketAccount,
+		remainingAccounts: AccountMeta[]
+	) {
+		if (perpMarketAccount.hasMakerRewardsProgram) {
+			remainingAccounts.push({
+				pubkey: getMakerRewardsProgramPublicKey(
+					this.program.programId,
+					perpMarketAccount.marketIndex
+				),
+				isSigner: false,
+				isWritable: true,
+			});
+		}
+	}
+
+	public addKeeperStatsToRemainingAccounts(
+		settleeUserAccount: UserAccount,
+		remainingAccounts: AccountMeta[]
+	) {
+		if (
+			!settleeUserAccount.authority.equals(this.wallet.publicKey) &&
+			!settleeUserAccount.delegate.equals(this.wallet.publicKey)
+		) {
+			remainingAccounts.push({
+				pubkey: this.getUserStatsAccountPublicKey(),
+				isSigner: false,
+				isWritable: true,
+			});
+		}
+	}
+
+	public addSpotMarketFeeStructureToRemainingAccounts(
+		spotMarketAccount: SpotMarketAccount,
+		remainingAccounts: AccountMeta[]
+	) {
+		if (
+			(spotMarketAccount.flags & SpotMarketFlag.HAS_FEE_STRUCTURE_OVERRIDE) >
+			0
+		) {
+			remainingAccounts.push({
+				pubkey: getSpotMarketFeeStructurePublicKey(
+					this.program.programId,
+					spotMarketAccount.marketIndex
+				),
+				isSigner: false,
+				isWritable: false,
+			});
+		}
+	}
+
 	public getAssociatedTokenAccountCreationIx(
 		tokenMintAddress: PublicKey,
 		associatedTokenAddress: PublicKey,
@@ -2699,6 +2962,80 @@ export class DriftClient {
 		});
 	}
 
+	public async updatePerpMarketOracleCircuitBreakerWindow(
+		marketIndex: number,
+		txParams?: TxParams
+	): Promise<TransactionSignature> {
+		const { txSig } = await this.sendTransaction(
+			await this.buildTransaction(
+				await this.updatePerpMarketOracleCircuitBreakerWindowIx(
+					marketIndex
+				),
+				txParams
+			),
+			[],
+			this.opts
+		);
+		return txSig;
+	}
+
+	public async updatePerpMarketOracleCircuitBreakerWindowIx(
+		marketIndex: number
+	): Promise<TransactionInstruction> {
+		const perpMarket = this.getPerpMarketAccount(marketIndex);
+		return await this.program.instruction.updatePerpMarketOracleCircuitBreakerWindow(
+			{
+				accounts: {
+					state: await this.getStatePublicKey(),
+					perpMarket: perpMarket.pubkey,
+					oracle: perpMarket.amm.oracle,
+					oracleCircuitBreakerWindow:
+						getPerpMarketOracleCircuitBreakerWindowPublicKey(
+							this.program.programId,
+							marketIndex
+						),
+				},
+			}
+		);
+	}
+
+	public async updateSpotMarketOracleCircuitBreakerWindow(
+		marketIndex: number,
+		txParams?: TxParams
+	): Promise<TransactionSignature> {
+		const { txSig } = await this.sendTransaction(
+			await this.buildTransaction(
+				await this.updateSpotMarketOracleCircuitBreakerWindowIx(
+					marketIndex
+				),
+				txParams
+			),
+			[],
+			this.opts
+		);
+		return txSig;
+	}
+
+	public async updateSpotMarketOracleCircuitBreakerWindowIx(
+		marketIndex: number
+	): Promise<TransactionInstruction> {
+		const spotMarket = this.getSpotMarketAccount(marketIndex);
+		return await this.program.instruction.updateSpotMarketOracleCircuitBreakerWindow(
+			{
+				accounts: {
+					state: await this.getStatePublicKey(),
+					spotMarket: spotMarket.pubkey,
+					oracle: spotMarket.oracle,
+					oracleCircuitBreakerWindow:
+						getSpotMarketOracleCircuitBreakerWindowPublicKey(
+							this.program.programId,
+							marketIndex
+						),
+				},
+			}
+		);
+	}
+
 	public async settleLP(
 		settleeUserAccountPublicKey: PublicKey,
 		marketIndex: number,
@@ -2727,11 +3064,20 @@ export class DriftClient {
 			userAccounts: [settleeUserAccount],
 			writablePerpMarketIndexes: [marketIndex],
 		});
+		this.addKeeperStatsToRemainingAccounts(
+			settleeUserAccount,
+			remainingAccounts
+		);
 
 		return this.program.instruction.settleLp(marketIndex, {
 			accounts: {
 				state: await this.getStatePublicKey(),
 				user: settleeUserAccountPublicKey,
+				userStats: getUserStatsAccountPublicKey(
+					this.program.programId,
+					settleeUserAccount.authority
+				),
+				authority: this.wallet.publicKey,
 			},
 			remainingAccounts: remainingAccounts,
 		});
@@ -3631,8 +3977,34 @@ export class DriftClient {
 					isSigner: false,
 				});
 			}
+
+			if (referrerInfo.subAffiliateStats) {
+				remainingAccounts.push({
+					pubkey: referrerInfo.subAffiliateStats,
+					isWritable: true,
+					isSigner: false,
+				});
+			}
 		}
 
+		this.addReferralTierConfigToRemainingAccounts(remainingAccounts);
+		this.addFeeTierQualificationsToRemainingAccounts(
+			MarketType.PERP,
+			remainingAccounts
+		);
+		this.addPerpMarketFeeStructureToRemainingAccounts(
+			this.getPerpMarketAccount(marketIndex),
+			remainingAccounts
+		);
+		this.addPerpMarketMakerRewardsProgramToRemainingAccounts(
+			this.getPerpMarketAccount(marketIndex),
+			remainingAccounts
+		);
+		this.addPerpMarketDepthBoundsToRemainingAccounts(
+			this.getPerpMarketAccount(marketIndex),
+			remainingAccounts
+		);
+
 		const orderId = order.orderId;
 		return await this.program.instruction.fillPerpOrder(orderId, null, {
 			accounts: {
@@ -3810,6 +4182,15 @@ export class DriftClient {
 
 		const orderId = order.orderId;
 
+		this.addFeeTierQualificationsToRemainingAccounts(
+			MarketType.SPOT,
+			remainingAccounts
+		);
+		this.addSpotMarketFeeStructureToRemainingAccounts(
+			this.getSpotMarketAccount(marketIndex),
+			remainingAccounts
+		);
+
 		this.addSpotFulfillmentAccounts(
 			marketIndex,
 			remainingAccounts,
@@ -4734,6 +5115,7 @@ export class DriftClient {
 			accounts: {
 				state: await this.getStatePublicKey(),
 				filler,
+				fillerStats: this.getUserStatsAccountPublicKey(),
 				user: userAccountPublicKey,
 				authority: this.wallet.publicKey,
 			},
@@ -5172,8 +5554,34 @@ export class DriftClient {
 					isSigner: false,
 				});
 			}
+
+			if (referrerInfo.subAffiliateStats) {
+				remainingAccounts.push({
+					pubkey: referrerInfo.subAffiliateStats,
+					isWritable: true,
+					isSigner: false,
+				});
+			}
 		}
 
+		this.addReferralTierConfigToRemainingAccounts(remainingAccounts);
+		this.addFeeTierQualificationsToRemainingAccounts(
+			MarketType.PERP,
+			remainingAccounts
+		);
+		this.addPerpMarketFeeStructureToRemainingAccounts(
+			this.getPerpMarketAccount(orderParams.marketIndex),
+			remainingAccounts
+		);
+		this.addPerpMarketMakerRewardsProgramToRemainingAccounts(
+			this.getPerpMarketAccount(orderParams.marketIndex),
+			remainingAccounts
+		);
+		this.addPerpMarketDepthBoundsToRemainingAccounts(
+			this.getPerpMarketAccount(orderParams.marketIndex),
+			remainingAccounts
+		);
+
 		return await this.program.instruction.placeAndTakePerpOrder(
 			orderParams,
 			null,
@@ -5245,8 +5653,34 @@ export class DriftClient {
 				isWritable: true,
 				isSigner: false,
 			});
+
+			if (referrerInfo.subAffiliateStats) {
+				remainingAccounts.push({
+					pubkey: referrerInfo.subAffiliateStats,
+					isWritable: true,
+					isSigner: false,
+				});
+			}
 		}
 
+		this.addReferralTierConfigToRemainingAccounts(remainingAccounts);
+		this.addFeeTierQualificationsToRemainingAccounts(
+			MarketType.PERP,
+			remainingAccounts
+		);
+		this.addPerpMarketFeeStructureToRemainingAccounts(
+			this.getPerpMarketAccount(orderParams.marketIndex),
+			remainingAccounts
+		);
+		this.addPerpMarketMakerRewardsProgramToRemainingAccounts(
+			this.getPerpMarketAccount(orderParams.marketIndex),
+			remainingAccounts
+		);
+		this.addPerpMarketDepthBoundsToRemainingAccounts(
+			this.getPerpMarketAccount(orderParams.marketIndex),
+			remainingAccounts
+		);
+
 		const takerOrderId = takerInfo.order.orderId;
 		return await this.program.instruction.placeAndMakePerpOrder(
 			orderParams,
@@ -5369,6 +5803,15 @@ export class DriftClient {
 			});
 		}
 
+		this.addFeeTierQualificationsToRemainingAccounts(
+			MarketType.SPOT,
+			remainingAccounts
+		);
+		this.addSpotMarketFeeStructureToRemainingAccounts(
+			this.getSpotMarketAccount(orderParams.marketIndex),
+			remainingAccounts
+		);
+
 		this.addSpotFulfillmentAccounts(
 			orderParams.marketIndex,
 			remainingAccounts,
@@ -5454,6 +5897,15 @@ export class DriftClient {
 			});
 		}
 
+		this.addFeeTierQualificationsToRemainingAccounts(
+			MarketType.SPOT,
+			remainingAccounts
+		);
+		this.addSpotMarketFeeStructureToRemainingAccounts(
+			this.getSpotMarketAccount(orderParams.marketIndex),
+			remainingAccounts
+		);
+
 		this.addSpotFulfillmentAccounts(
 			orderParams.marketIndex,
 			remainingAccounts,
@@ -5910,6 +6362,10 @@ export class DriftClient {
 			writablePerpMarketIndexes: [marketIndex],
 			writableSpotMarketIndexes: [QUOTE_SPOT_MARKET_INDEX],
 		});
+		this.addKeeperStatsToRemainingAccounts(
+			settleeUserAccount,
+			remainingAccounts
+		);
 
 		return await this.program.instruction.settlePnl(marketIndex, {
 			accounts: {
@@ -5956,6 +6412,10 @@ export class DriftClient {
 			writablePerpMarketIndexes: marketIndexes,
 			writableSpotMarketIndexes: [QUOTE_SPOT_MARKET_INDEX],
 		});
+		this.addKeeperStatsToRemainingAccounts(
+			settleeUserAccount,
+			remainingAccounts
+		);
 
 		return await this.program.instruction.settleMultiplePnls(
 			marketIndexes,
@@ -6138,6 +6598,23 @@ export class DriftClient {
 			});
 		}
 
+		this.addFeeTierQualificationsToRemainingAccounts(
+			MarketType.PERP,
+			remainingAccounts
+		);
+		this.addPerpMarketFeeStructureToRemainingAccounts(
+			this.getPerpMarketAccount(marketIndex),
+			remainingAccounts
+		);
+		this.addPerpMarketMakerRewardsProgramToRemainingAccounts(
+			this.getPerpMarketAccount(marketIndex),
+			remainingAccounts
+		);
+		this.addPerpMarketDepthBoundsToRemainingAccounts(
+			this.getPerpMarketAccount(marketIndex),
+			remainingAccounts
+		);
+
 		return await this.program.instruction.liquidatePerpWithFill(marketIndex, {
 			accounts: {
 				state: await this.getStatePublicKey(),
@@ -6422,6 +6899,25 @@ export class DriftClient {
 		});
 
 		const spotMarket = this.getQuoteSpotMarketAccount();
+		const perpMarket = this.getPerpMarketAccount(marketIndex);
+
+		this.addInsuranceFundPerformanceToRemainingAccounts(
+			spotMarket,
+			remainingAccounts
+		);
+		this.addPerpInsuranceFundToRemainingAccounts(perpMarket, remainingAccounts);
+		this.addPerpInsuranceFundPerformanceToRemainingAccounts(
+			perpMarket,
+			remainingAccounts
+		);
+		this.addSeniorInsuranceFundToRemainingAccounts(
+			spotMarket,
+			remainingAccounts
+		);
+		this.addSeniorInsuranceFundPerformanceToRemainingAccounts(
+			spotMarket,
+			remainingAccounts
+		);
 
 		return await this.program.instruction.resolvePerpBankruptcy(
 			QUOTE_SPOT_MARKET_INDEX,
@@ -6490,6 +6986,18 @@ export class DriftClient {
 
 		const spotMarket = this.getSpotMarketAccount(marketIndex);
 
+		this.addInsuranceFundPerformanceToRemainingAccounts(
+			spotMarket,
+			remainingAccounts
+		);
+		this.addSeniorInsuranceFundToRemainingAccounts(
+			spotMarket,
+			remainingAccounts
+		);
+		this.addSeniorInsuranceFundPerformanceToRemainingAccounts(
+			spotMarket,
+			remainingAccounts
+		);
 		this.addTokenMintToRemainingAccounts(spotMarket, remainingAccounts);
 
 		return await this.program.instruction.resolveSpotBankruptcy(marketIndex, {
@@ -6533,12 +7041,18 @@ export class DriftClient {
 			this.program.programId,
 			perpMarketIndex
 		);
+		const remainingAccounts = [];
+		this.addPerpMarketDepthBoundsToRemainingAccounts(
+			this.getPerpMarketAccount(perpMarketIndex),
+			remainingAccounts
+		);
 		return await this.program.instruction.updateFundingRate(perpMarketIndex, {
 			accounts: {
 				state: await this.getStatePublicKey(),
 				perpMarket: perpMarketPublicKey,
 				oracle: oracle,
 			},
+			remainingAccounts,
 		});
 	}
 
@@ -6562,7 +7076,10 @@ export class DriftClient {
 	): Promise<TransactionInstruction> {
 		const perpMarket = this.getPerpMarketAccount(perpMarketIndex);
 
-		if (!isVariant(perpMarket.amm.oracleSource, 'prelaunch')) {
+		if (
+			!isVariant(perpMarket.amm.oracleSource, 'prelaunch') &&
+			!isVariant(perpMarket.amm.oracleSource, 'fillTwap')
+		) {
 			throw new Error(`Wrong oracle source ${perpMarket.amm.oracleSource}`);
 		}
 
@@ -6610,6 +7127,10 @@ export class DriftClient {
 				isSigner: false,
 			});
 		}
+		this.addPerpMarketDepthBoundsToRemainingAccounts(
+			perpMarket,
+			remainingAccounts
+		);
 
 		return await this.program.instruction.updatePerpBidAskTwap({
 			accounts: {
@@ -6736,6 +7257,10 @@ export class DriftClient {
 		);
 
 		const remainingAccounts = [];
+		this.addSeniorInsuranceFundToRemainingAccounts(
+			spotMarket,
+			remainingAccounts
+		);
 		this.addTokenMintToRemainingAccounts(spotMarket, remainingAccounts);
 		const tokenProgram = this.getTokenProgramForSpotMarket(spotMarket);
 		const ix = this.program.instruction.addInsuranceFundStake(
@@ -7117,6 +7642,18 @@ export class DriftClient {
 	): Promise<TransactionInstruction> {
 		const spotMarketAccount = this.getSpotMarketAccount(spotMarketIndex);
 		const remainingAccounts = [];
+		this.addInsuranceFundPerformanceToRemainingAccounts(
+			spotMarketAccount,
+			remainingAccounts
+		);
+		this.addSeniorInsuranceFundToRemainingAccounts(
+			spotMarketAccount,
+			remainingAccounts
+		);
+		this.addSeniorInsuranceFundPerformanceToRemainingAccounts(
+			spotMarketAccount,
+			remainingAccounts
+		);
 		this.addTokenMintToRemainingAccounts(spotMarketAccount, remainingAccounts);
 		const ix = await this.program.instruction.settleRevenueToInsuranceFund(
 			spotMarketIndex,
@@ -7135,6 +7672,220 @@ export class DriftClient {
 		return ix;
 	}
 
+	/**
+	 * Claims unclaimed sub-affiliate rewards out of a perp market's fee pool
+	 * @param marketIndex perp market whose fee pool pays the reward
+	 */
+	public async claimSubAffiliateReward(
+		marketIndex: number,
+		subAccountId?: number,
+		txParams?: TxParams
+	): Promise<TransactionSignature> {
+		const tx = await this.buildTransaction(
+			await this.getClaimSubAffiliateRewardIx(marketIndex, subAccountId),
+			txParams
+		);
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+		return txSig;
+	}
+
+	public async getClaimSubAffiliateRewardIx(
+		marketIndex: number,
+		subAccountId?: number
+	): Promise<TransactionInstruction> {
+		return await this.program.instruction.claimSubAffiliateReward({
+			accounts: {
+				state: await this.getStatePublicKey(),
+				user: await this.getUserAccountPublicKey(subAccountId),
+				userStats: this.getUserStatsAccountPublicKey(),
+				authority: this.wallet.publicKey,
+				perpMarket: this.getPerpMarketAccount(marketIndex).pubkey,
+				quoteSpotMarket: this.getQuoteSpotMarketAccount().pubkey,
+			},
+		});
+	}
+
+	public async joinMakerRewardsProgram(
+		marketIndex: number,
+		subAccountId?: number,
+		txParams?: TxParams
+	): Promise<TransactionSignature> {
+		const tx = await this.buildTransaction(
+			await this.getJoinMakerRewardsProgramIx(marketIndex, subAccountId),
+			txParams
+		);
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+		return txSig;
+	}
+
+	public async getJoinMakerRewardsProgramIx(
+		marketIndex: number,
+		subAccountId?: number
+	): Promise<TransactionInstruction> {
+		return await this.program.instruction.joinMakerRewardsProgram(marketIndex, {
+			accounts: {
+				user: await this.getUserAccountPublicKey(subAccountId),
+				userStats: this.getUserStatsAccountPublicKey(),
+				authority: this.wallet.publicKey,
+				makerRewardsProgram: getMakerRewardsProgramPublicKey(
+					this.program.programId,
+					marketIndex
+				),
+			},
+		});
+	}
+
+	public async leaveMakerRewardsProgram(
+		marketIndex: number,
+		subAccountId?: number,
+		txParams?: TxParams
+	): Promise<TransactionSignature> {
+		const tx = await this.buildTransaction(
+			await this.getLeaveMakerRewardsProgramIx(marketIndex, subAccountId),
+			txParams
+		);
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+		return txSig;
+	}
+
+	public async getLeaveMakerRewardsProgramIx(
+		marketIndex: number,
+		subAccountId?: number
+	): Promise<TransactionInstruction> {
+		return await this.program.instruction.leaveMakerRewardsProgram(
+			marketIndex,
+			{
+				accounts: {
+					user: await this.getUserAccountPublicKey(subAccountId),
+					authority: this.wallet.publicKey,
+					makerRewardsProgram: getMakerRewardsProgramPublicKey(
+						this.program.programId,
+						marketIndex
+					),
+				},
+			}
+		);
+	}
+
+	public async claimMakerRewards(
+		marketIndex: number,
+		userTokenAccount: PublicKey,
+		subAccountId?: number,
+		tokenProgram = TOKEN_PROGRAM_ID,
+		txParams?: TxParams
+	): Promise<TransactionSignature> {
+		const tx = await this.buildTransaction(
+			await this.getClaimMakerRewardsIx(
+				marketIndex,
+				userTokenAccount,
+				subAccountId,
+				tokenProgram
+			),
+			txParams
+		);
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+		return txSig;
+	}
+
+	public async getClaimMakerRewardsIx(
+		marketIndex: number,
+		userTokenAccount: PublicKey,
+		subAccountId?: number,
+		tokenProgram = TOKEN_PROGRAM_ID
+	): Promise<TransactionInstruction> {
+		return await this.program.instruction.claimMakerRewards(marketIndex, {
+			accounts: {
+				state: await this.getStatePublicKey(),
+				user: await this.getUserAccountPublicKey(subAccountId),
+				authority: this.wallet.publicKey,
+				makerRewardsProgram: getMakerRewardsProgramPublicKey(
+					this.program.programId,
+					marketIndex
+				),
+				makerRewardsVault: getMakerRewardsVaultPublicKey(
+					this.program.programId,
+					marketIndex
+				),
+				driftSigner: this.getSignerPublicKey(),
+				userTokenAccount,
+				tokenProgram,
+			},
+		});
+	}
+
+	public async settleMakerRewardsEpoch(
+		marketIndex: number,
+		txParams?: TxParams
+	): Promise<TransactionSignature> {
+		const tx = await this.buildTransaction(
+			await this.getSettleMakerRewardsEpochIx(marketIndex),
+			txParams
+		);
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+		return txSig;
+	}
+
+	public async getSettleMakerRewardsEpochIx(
+		marketIndex: number
+	): Promise<TransactionInstruction> {
+		return await this.program.instruction.settleMakerRewardsEpoch(marketIndex, {
+			accounts: {
+				makerRewardsProgram: getMakerRewardsProgramPublicKey(
+					this.program.programId,
+					marketIndex
+				),
+				makerRewardsVault: getMakerRewardsVaultPublicKey(
+					this.program.programId,
+					marketIndex
+				),
+			},
+		});
+	}
+
+	public async updateMakerRewardsDepth(
+		marketIndex: number,
+		makers: PublicKey[],
+		txParams?: TxParams
+	): Promise<TransactionSignature> {
+		const tx = await this.buildTransaction(
+			await this.getUpdateMakerRewardsDepthIx(marketIndex, makers),
+			txParams
+		);
+		const { txSig } = await this.sendTransaction(tx, [], this.opts);
+		return txSig;
+	}
+
+	public async getUpdateMakerRewardsDepthIx(
+		marketIndex: number,
+		makers: PublicKey[]
+	): Promise<TransactionInstruction> {
+		const perpMarket = this.getPerpMarketAccount(marketIndex);
+
+		const remainingAccounts = makers.map((maker) => {
+			return {
+				pubkey: maker,
+				isWritable: false,
+				isSigner: false,
+			};
+		});
+
+		return await this.program.instruction.updateMakerRewardsDepth(
+			marketIndex,
+			{
+				accounts: {
+					state: await this.getStatePublicKey(),
+					perpMarket: perpMarket.pubkey,
+					oracle: perpMarket.amm.oracle,
+					makerRewardsProgram: getMakerRewardsProgramPublicKey(
+						this.program.programId,
+						marketIndex
+					),
+				},
+				remainingAccounts,
+			}
+		);
+	}
+
 	public async resolvePerpPnlDeficit(
 		spotMarketIndex: number,
 		perpMarketIndex: number,
@@ -7163,6 +7914,25 @@ export class DriftClient {
 		});
 
 		const spotMarket = this.getSpotMarketAccount(spotMarketIndex);
+		const perpMarket = this.getPerpMarketAccount(perpMarketIndex);
+
+		this.addInsuranceFundPerformanceToRemainingAccounts(
+			spotMarket,
+			remainingAccounts
+		);
+		this.addPerpInsuranceFundToRemainingAccounts(perpMarket, remainingAccounts);
+		this.addPerpInsuranceFundPerformanceToRemainingAccounts(
+			perpMarket,
+			remainingAccounts
+		);
+		this.addSeniorInsuranceFundToRemainingAccounts(
+			spotMarket,
+			remainingAccounts
+		);
+		this.addSeniorInsuranceFundPerformanceToRemainingAccounts(
+			spotMarket,
+			remainingAccounts
+		);
 
 		return await this.program.instruction.resolvePerpPnlDeficit(
 			spotMarketIndex,
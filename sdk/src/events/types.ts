@@ -10,11 +10,14 @@ import {
 	SettlePnlRecord,
 	LPRecord,
 	InsuranceFundRecord,
+	InsuranceFundPerformanceRecord,
 	SpotInterestRecord,
 	InsuranceFundStakeRecord,
 	CurveRecord,
 	SwapRecord,
 	SpotMarketVaultDepositRecord,
+	OracleCircuitBreakerRecord,
+	TermLoanRecord,
 } from '../index';
 import { EventEmitter } from 'events';
 
@@ -44,11 +47,14 @@ export const DefaultEventSubscriptionOptions: EventSubscriptionOptions = {
 		'SettlePnlRecord',
 		'LPRecord',
 		'InsuranceFundRecord',
+		'InsuranceFundPerformanceRecord',
 		'SpotInterestRecord',
 		'InsuranceFundStakeRecord',
 		'CurveRecord',
 		'SwapRecord',
 		'SpotMarketVaultDepositRecord',
+		'OracleCircuitBreakerRecord',
+		'TermLoanRecord',
 	],
 	maxEventsPerType: 4096,
 	orderBy: 'blockchain',
@@ -87,11 +93,14 @@ export type EventMap = {
 	NewUserRecord: Event<NewUserRecord>;
 	LPRecord: Event<LPRecord>;
 	InsuranceFundRecord: Event<InsuranceFundRecord>;
+	InsuranceFundPerformanceRecord: Event<InsuranceFundPerformanceRecord>;
 	SpotInterestRecord: Event<SpotInterestRecord>;
 	InsuranceFundStakeRecord: Event<InsuranceFundStakeRecord>;
 	CurveRecord: Event<CurveRecord>;
 	SwapRecord: Event<SwapRecord>;
 	SpotMarketVaultDepositRecord: Event<SpotMarketVaultDepositRecord>;
+	OracleCircuitBreakerRecord: Event<OracleCircuitBreakerRecord>;
+	TermLoanRecord: Event<TermLoanRecord>;
 };
 
 export type EventType = keyof EventMap;
@@ -107,11 +116,14 @@ export type DriftEvent =
 	| Event<NewUserRecord>
 	| Event<LPRecord>
 	| Event<InsuranceFundRecord>
+	| Event<InsuranceFundPerformanceRecord>
 	| Event<SpotInterestRecord>
 	| Event<InsuranceFundStakeRecord>
 	| Event<CurveRecord>
 	| Event<SwapRecord>
-	| Event<SpotMarketVaultDepositRecord>;
+	| Event<SpotMarketVaultDepositRecord>
+	| Event<OracleCircuitBreakerRecord>
+	| Event<TermLoanRecord>;
 
 export interface EventSubscriberEvents {
 	newEvent: (event: WrappedEvent<EventType>) => void;
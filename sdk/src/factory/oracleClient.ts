@@ -9,6 +9,9 @@ import { PrelaunchOracleClient } from '../oracles/prelaunchOracleClient';
 import { SwitchboardClient } from '../oracles/switchboardClient';
 import { PythPullClient } from '../oracles/pythPullClient';
 import { SwitchboardOnDemandClient } from '../oracles/switchboardOnDemandClient';
+import { OracleAggregatorClient } from '../oracles/oracleAggregatorClient';
+import { ChainlinkClient } from '../oracles/chainlinkClient';
+import { SignedPriceClient } from '../oracles/signedPriceClient';
 
 export function getOracleClient(
 	oracleSource: OracleSource,
@@ -51,7 +54,10 @@ export function getOracleClient(
 		return new SwitchboardClient(connection);
 	}
 
-	if (isVariant(oracleSource, 'prelaunch')) {
+	if (
+		isVariant(oracleSource, 'prelaunch') ||
+		isVariant(oracleSource, 'fillTwap')
+	) {
 		return new PrelaunchOracleClient(connection, program);
 	}
 
@@ -63,5 +69,17 @@ export function getOracleClient(
 		return new SwitchboardOnDemandClient(connection);
 	}
 
+	if (isVariant(oracleSource, 'aggregated')) {
+		return new OracleAggregatorClient(connection, program);
+	}
+
+	if (isVariant(oracleSource, 'chainlink')) {
+		return new ChainlinkClient(connection);
+	}
+
+	if (isVariant(oracleSource, 'signedPrice')) {
+		return new SignedPriceClient(connection, program);
+	}
+
 	throw new Error(`Unknown oracle source ${oracleSource}`);
 }
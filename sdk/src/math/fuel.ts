@@ -68,3 +68,23 @@ export function calculatePerpFuelBonus(
 
 	return result;
 }
+
+export function calculateLpFuelBonus(
+	perpMarket: PerpMarketAccount,
+	lpSharesValue: BN,
+	fuelBonusNumerator: BN
+): BN {
+	let result: BN;
+
+	if (lpSharesValue.lt(QUOTE_PRECISION)) {
+		result = new BN(0);
+	} else {
+		result = lpSharesValue
+			.mul(fuelBonusNumerator)
+			.mul(new BN(perpMarket.fuelBoostLp))
+			.div(FUEL_WINDOW)
+			.div(QUOTE_PRECISION.div(new BN(10)));
+	}
+
+	return result;
+}
@@ -383,7 +383,9 @@ export function calculateInterestRate(
 ): BN {
 	const utilization = calculateUtilization(bank, delta);
 	let interestRate: BN;
-	if (utilization.gt(new BN(bank.optimalUtilization))) {
+	if (bank.borrowRateKinks?.[0]?.utilization > 0) {
+		interestRate = calculateKinkedBorrowRate(bank, utilization);
+	} else if (utilization.gt(new BN(bank.optimalUtilization))) {
 		const surplusUtilization = utilization.sub(new BN(bank.optimalUtilization));
 		const borrowRateSlope = new BN(bank.maxBorrowRate - bank.optimalBorrowRate)
 			.mul(SPOT_MARKET_UTILIZATION_PRECISION)
@@ -412,6 +414,53 @@ export function calculateInterestRate(
 	);
 }
 
+function calculateKinkedBorrowRate(
+	bank: SpotMarketAccount,
+	utilization: BN
+): BN {
+	const bpsDenominator = new BN(10000);
+	const maxBorrowRate = new BN(bank.maxBorrowRate);
+
+	let lowerUtilization = ZERO;
+	let lowerBorrowRate = ZERO;
+	let upperUtilization = SPOT_MARKET_UTILIZATION_PRECISION;
+	let upperBorrowRate = maxBorrowRate;
+
+	for (const kink of bank.borrowRateKinks) {
+		if (kink.utilization === 0) {
+			break;
+		}
+
+		const kinkUtilization = new BN(kink.utilization)
+			.mul(SPOT_MARKET_UTILIZATION_PRECISION)
+			.div(bpsDenominator);
+		const kinkBorrowRate = new BN(kink.borrowRate)
+			.mul(maxBorrowRate)
+			.div(bpsDenominator);
+
+		if (utilization.lte(kinkUtilization)) {
+			upperUtilization = kinkUtilization;
+			upperBorrowRate = kinkBorrowRate;
+			break;
+		}
+
+		lowerUtilization = kinkUtilization;
+		lowerBorrowRate = kinkBorrowRate;
+	}
+
+	const borrowRateSlope = upperBorrowRate
+		.sub(lowerBorrowRate)
+		.mul(SPOT_MARKET_UTILIZATION_PRECISION)
+		.div(upperUtilization.sub(lowerUtilization));
+
+	return lowerBorrowRate.add(
+		utilization
+			.sub(lowerUtilization)
+			.mul(borrowRateSlope)
+			.div(SPOT_MARKET_UTILIZATION_PRECISION)
+	);
+}
+
 export function calculateDepositRate(
 	bank: SpotMarketAccount,
 	delta = ZERO
@@ -972,11 +972,11 @@ export function getUser30dRollingVolumeEstimate(
 ) {
 	now = now || new BN(new Date().getTime() / 1000);
 	const sinceLastTaker = BN.max(
-		now.sub(userStatsAccount.lastTakerVolume30DTs),
+		now.sub(new BN(userStatsAccount.lastTakerVolume30DTs)),
 		ZERO
 	);
 	const sinceLastMaker = BN.max(
-		now.sub(userStatsAccount.lastMakerVolume30DTs),
+		now.sub(new BN(userStatsAccount.lastMakerVolume30DTs)),
 		ZERO
 	);
 	const thirtyDaysInSeconds = new BN(60 * 60 * 24 * 30);
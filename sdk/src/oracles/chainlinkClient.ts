@@ -0,0 +1,49 @@
+import { Connection, PublicKey } from '@solana/web3.js';
+import { OracleClient, OraclePriceData } from './types';
+import { BN } from '@coral-xyz/anchor';
+import { PRICE_PRECISION, TEN } from '../constants/numericConstants';
+
+// chainlink store feed account: 8 byte discriminator, header, then a ring buffer of transmissions
+const HEADER_OFFSET = 8;
+const TRANSMISSIONS_OFFSET = HEADER_OFFSET + 192;
+const TRANSMISSION_SIZE = 48;
+
+export class ChainlinkClient implements OracleClient {
+	private connection: Connection;
+
+	public constructor(connection: Connection) {
+		this.connection = connection;
+	}
+
+	public async getOraclePriceData(
+		pricePublicKey: PublicKey
+	): Promise<OraclePriceData> {
+		const accountInfo = await this.connection.getAccountInfo(pricePublicKey);
+		return this.getOraclePriceDataFromBuffer(accountInfo.data);
+	}
+
+	public getOraclePriceDataFromBuffer(buffer: Buffer): OraclePriceData {
+		const decimals = buffer.readUInt8(HEADER_OFFSET + 130);
+		const liveLength = buffer.readUInt32LE(HEADER_OFFSET + 140);
+		const liveCursor = buffer.readUInt32LE(HEADER_OFFSET + 144);
+
+		const latestIndex = (liveCursor + liveLength - 1) % liveLength;
+		const offset = TRANSMISSIONS_OFFSET + latestIndex * TRANSMISSION_SIZE;
+
+		const slot = new BN(buffer.subarray(offset, offset + 8), 'le');
+		const answer = new BN(
+			buffer.subarray(offset + 16, offset + 32),
+			'le'
+		).fromTwos(128);
+
+		const price = answer.mul(PRICE_PRECISION).div(TEN.pow(new BN(decimals)));
+
+		return {
+			price,
+			slot,
+			// chainlink doesn't publish a confidence, use 10bps of price
+			confidence: price.abs().divn(1000),
+			hasSufficientNumberOfDataPoints: true,
+		};
+	}
+}
@@ -0,0 +1,36 @@
+import { Connection, PublicKey } from '@solana/web3.js';
+import { OracleClient, OraclePriceData } from './types';
+import { Program } from '@coral-xyz/anchor';
+import { OracleAggregator } from '../types';
+
+export class OracleAggregatorClient implements OracleClient {
+	private connection: Connection;
+	private program: Program;
+
+	public constructor(connection: Connection, program: Program) {
+		this.connection = connection;
+		this.program = program;
+	}
+
+	public async getOraclePriceData(
+		pricePublicKey: PublicKey
+	): Promise<OraclePriceData> {
+		const accountInfo = await this.connection.getAccountInfo(pricePublicKey);
+		return this.getOraclePriceDataFromBuffer(accountInfo.data);
+	}
+
+	public getOraclePriceDataFromBuffer(buffer: Buffer): OraclePriceData {
+		const oracleAggregator =
+			this.program.account.oracleAggregator.coder.accounts.decodeUnchecked(
+				'OracleAggregator',
+				buffer
+			) as OracleAggregator;
+
+		return {
+			price: oracleAggregator.price,
+			slot: oracleAggregator.lastUpdateSlot,
+			confidence: oracleAggregator.confidence,
+			hasSufficientNumberOfDataPoints: true,
+		};
+	}
+}
@@ -0,0 +1,41 @@
+import { Connection, PublicKey } from '@solana/web3.js';
+import { OracleClient, OraclePriceData } from './types';
+import { BN, Program } from '@coral-xyz/anchor';
+import { PRICE_PRECISION, TEN } from '../constants/numericConstants';
+import { SignedPriceAccount } from '../types';
+
+export class SignedPriceClient implements OracleClient {
+	private connection: Connection;
+	private program: Program;
+
+	public constructor(connection: Connection, program: Program) {
+		this.connection = connection;
+		this.program = program;
+	}
+
+	public async getOraclePriceData(
+		pricePublicKey: PublicKey
+	): Promise<OraclePriceData> {
+		const accountInfo = await this.connection.getAccountInfo(pricePublicKey);
+		return this.getOraclePriceDataFromBuffer(accountInfo.data);
+	}
+
+	public getOraclePriceDataFromBuffer(buffer: Buffer): OraclePriceData {
+		const signedPriceAccount =
+			this.program.account.signedPriceAccount.coder.accounts.decodeUnchecked(
+				'SignedPriceAccount',
+				buffer
+			) as SignedPriceAccount;
+
+		const precision = TEN.pow(new BN(Math.abs(signedPriceAccount.exponent)));
+
+		return {
+			price: signedPriceAccount.price.mul(PRICE_PRECISION).div(precision),
+			slot: signedPriceAccount.postedSlot,
+			confidence: signedPriceAccount.confidence
+				.mul(PRICE_PRECISION)
+				.div(precision),
+			hasSufficientNumberOfDataPoints: true,
+		};
+	}
+}
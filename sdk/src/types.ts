@@ -39,6 +39,7 @@ export enum PerpOperation {
 	SETTLE_PNL = 8,
 	SETTLE_PNL_WITH_POSITION = 16,
 	LIQUIDATION = 32,
+	REDUCE_ONLY = 64,
 }
 
 export enum SpotOperation {
@@ -47,6 +48,7 @@ export enum SpotOperation {
 	DEPOSIT = 4,
 	WITHDRAW = 8,
 	LIQUIDATION = 16,
+	REDUCE_ONLY = 32,
 }
 
 export enum InsuranceFundOperation {
@@ -56,6 +58,13 @@ export enum InsuranceFundOperation {
 	REMOVE = 8,
 }
 
+export enum SpotMarketFlag {
+	HAS_FEE_STRUCTURE_OVERRIDE = 1,
+	HAS_SENIOR_INSURANCE_FUND = 2,
+	HAS_INSURANCE_FUND_PERFORMANCE = 4,
+	HAS_SENIOR_INSURANCE_FUND_PERFORMANCE = 8,
+}
+
 export enum UserStatus {
 	BEING_LIQUIDATED = 1,
 	BANKRUPT = 2,
@@ -119,6 +128,10 @@ export class OracleSource {
 	static readonly PYTH_STABLE_COIN_PULL = { pythStableCoinPull: {} };
 	static readonly Prelaunch = { prelaunch: {} };
 	static readonly SWITCHBOARD_ON_DEMAND = { switchboardOnDemand: {} };
+	static readonly AGGREGATED = { aggregated: {} };
+	static readonly CHAINLINK = { chainlink: {} };
+	static readonly SIGNED_PRICE = { signedPrice: {} };
+	static readonly FILL_TWAP = { fillTwap: {} };
 }
 
 export class OrderType {
@@ -244,6 +257,14 @@ export class StakeAction {
 	static readonly UNSTAKE = { unstake: {} };
 	static readonly UNSTAKE_TRANSFER = { unstakeTransfer: {} };
 	static readonly STAKE_TRANSFER = { stakeTransfer: {} };
+	static readonly TOKENIZE = { tokenize: {} };
+	static readonly REDEEM = { redeem: {} };
+}
+
+export class InsuranceFundTranche {
+	static readonly JUNIOR = { junior: {} };
+	static readonly SENIOR = { senior: {} };
+	static readonly PERP_MARKET = { perpMarket: {} };
 }
 
 export class SettlePnlMode {
@@ -310,6 +331,7 @@ export type DepositRecord = {
 	depositRecordId: BN;
 	explanation: DepositExplanation;
 	transferUser?: PublicKey;
+	transferFee: BN;
 };
 
 export type SpotInterestRecord = {
@@ -322,6 +344,7 @@ export type SpotInterestRecord = {
 	optimalUtilization: number;
 	optimalBorrowRate: number;
 	maxBorrowRate: number;
+	borrowRate: number;
 };
 
 export type CurveRecord = {
@@ -358,6 +381,21 @@ export declare type InsuranceFundRecord = {
 	totalIfSharesBefore: BN;
 	totalIfSharesAfter: BN;
 	amount: BN;
+	tranche: InsuranceFundTranche;
+};
+
+export declare type InsuranceFundPerformanceRecord = {
+	ts: BN;
+	marketIndex: number;
+	tranche: InsuranceFundTranche;
+	insuranceVaultAmountBefore: BN;
+	revenue: BN;
+	loss: BN;
+	revenueSinceLastUpdate: BN;
+	lossesSinceLastUpdate: BN;
+	cumulativeRevenue: BN;
+	cumulativeLosses: BN;
+	revenueApy: BN;
 };
 
 export declare type InsuranceFundStakeRecord = {
@@ -391,6 +429,7 @@ export class LPAction {
 	static readonly REMOVE_LIQUIDITY = { removeLiquidity: {} };
 	static readonly SETTLE_LIQUIDITY = { settleLiquidity: {} };
 	static readonly REMOVE_LIQUIDITY_DERISK = { removeLiquidityDerisk: {} };
+	static readonly REQUEST_REMOVE_LIQUIDITY = { requestRemoveLiquidity: {} };
 }
 
 export type FundingRateRecord = {
@@ -407,6 +446,8 @@ export type FundingRateRecord = {
 	periodRevenue: BN;
 	baseAssetAmountWithAmm: BN;
 	baseAssetAmountWithUnsettledLp: BN;
+	premiumIndex: BN;
+	premiumIndexSampleWeight: number;
 };
 
 export type FundingPaymentRecord = {
@@ -563,6 +604,8 @@ export type OrderActionRecord = {
 	makerOrderCumulativeBaseAssetAmountFilled: BN | null;
 	makerOrderCumulativeQuoteAssetAmountFilled: BN | null;
 	oraclePrice: BN;
+	takerFeeTierIndex: number | null;
+	makerFeeTierIndex: number | null;
 };
 
 export type SwapRecord = {
@@ -587,6 +630,55 @@ export type SpotMarketVaultDepositRecord = {
 	amount: BN;
 };
 
+export type OracleCircuitBreakerRecord = {
+	ts: BN;
+	marketType: MarketType;
+	marketIndex: number;
+	tripped: boolean;
+	oraclePrice: BN;
+	referencePrice: BN;
+};
+
+export type TermLoanRecord = {
+	ts: BN;
+	action: TermLoanAction;
+	termLoan: PublicKey;
+	lender: PublicKey;
+	borrower: PublicKey;
+	marketIndex: number;
+	principal: BN;
+	repayAmount: BN;
+	rate: number;
+	maturityTs: BN;
+	shortfall: BN;
+};
+
+export class TermLoanAction {
+	static readonly OFFER = { offer: {} };
+	static readonly CANCEL = { cancel: {} };
+	static readonly TAKE = { take: {} };
+	static readonly SETTLE = { settle: {} };
+}
+
+export class TermLoanStatus {
+	static readonly OFFERED = { offered: {} };
+	static readonly ACTIVE = { active: {} };
+}
+
+export type TermLoanAccount = {
+	lender: PublicKey;
+	lenderAuthority: PublicKey;
+	borrower: PublicKey;
+	principal: BN;
+	repayAmount: BN;
+	maturityTs: BN;
+	startTs: BN;
+	rate: number;
+	offerId: number;
+	marketIndex: number;
+	status: TermLoanStatus;
+};
+
 export type StateAccount = {
 	admin: PublicKey;
 	exchangeStatus: number;
@@ -612,6 +704,11 @@ export type StateAccount = {
 	initialPctToLiquidate: number;
 	liquidationDuration: number;
 	maxInitializeUserFee: number;
+	fuelSeason: number;
+	hasReferralTierConfig: boolean;
+	hasPerpFeeTierQualifications: boolean;
+	hasSpotFeeTierQualifications: boolean;
+	fuelSeasonStartTs: number;
 };
 
 export type PerpMarketAccount = {
@@ -653,6 +750,22 @@ export type PerpMarketAccount = {
 	fuelBoostTaker: number;
 	fuelBoostMaker: number;
 	fuelBoostPosition: number;
+
+	fundingInterestRateAdjustment: number;
+	dynamicAmmJitMinScale: number;
+	marginConfidenceMultiplier: number;
+	hasDepthBounds: boolean;
+	hasPerpInsuranceFund: boolean;
+	hasPerpInsuranceFundPerformance: boolean;
+	lpWithdrawCap: number;
+	lpWithdrawWindowStartTs: number;
+	lpSharesWithdrawnInWindow: BN;
+	oracleValidityGuardRails: ValidityGuardRailsOverride;
+	oracleCircuitBreaker: OracleCircuitBreaker;
+	hasFeeStructureOverride: boolean;
+	fuelBoostLp: number;
+	fuelBoostKeeper: number;
+	hasMakerRewardsProgram: boolean;
 };
 
 export type HistoricalOracleData = {
@@ -759,6 +872,19 @@ export type SpotMarketAccount = {
 	fuelBoostInsurance: number;
 
 	tokenProgram: number;
+	marginConfidenceMultiplier: number;
+	oracleValidityGuardRails: ValidityGuardRailsOverride;
+	oracleCircuitBreaker: OracleCircuitBreaker;
+	borrowRateKinks: BorrowRateKink[];
+	assetGroup: number;
+	assetGroupMarginRatioInitial: number;
+	assetGroupMarginRatioMaintenance: number;
+	flags: number;
+};
+
+export type BorrowRateKink = {
+	utilization: number;
+	borrowRate: number;
 };
 
 export type PoolBalance = {
@@ -859,6 +985,8 @@ export type AMM = {
 	netUnsettledFundingPnl: BN;
 	quoteAssetAmountWithUnsettledLp: BN;
 	referencePriceOffset: number;
+	premiumIndexSampleWeight: number;
+	premiumIndexSampleSum: BN;
 };
 
 // # User Account Types
@@ -886,13 +1014,13 @@ export type UserStatsAccount = {
 	makerVolume30D: BN;
 	takerVolume30D: BN;
 	fillerVolume30D: BN;
-	lastMakerVolume30DTs: BN;
-	lastTakerVolume30DTs: BN;
+	lastMakerVolume30DTs: number; // u32 onchain
+	lastTakerVolume30DTs: number; // u32 onchain
 	lastFillerVolume30DTs: BN;
 	fees: {
 		totalFeePaid: BN;
 		totalFeeRebate: BN;
-		totalTokenDiscount: BN;
+		unclaimedSubAffiliateReward: BN;
 		totalRefereeDiscount: BN;
 		totalReferrerReward: BN;
 		current_epoch_referrer_reward: BN;
@@ -910,8 +1038,14 @@ export type UserStatsAccount = {
 	fuelPositions: number;
 	fuelTaker: number;
 	fuelMaker: number;
+	fuelLp: number;
+	fuelKeeper: number;
+	fuelSeason: number;
 
 	ifStakedGovTokenAmount: BN;
+
+	lastRefereeVolume30DTs: number; // u32 onchain
+	refereeVolume30D: BN;
 };
 
 export type UserAccount = {
@@ -942,6 +1076,9 @@ export type UserAccount = {
 	openAuctions: number;
 	hasOpenAuction: boolean;
 	lastFuelBonusUpdateTs: number;
+	lpWithdrawRequestMarketIndex: number;
+	lpWithdrawRequestTs: number;
+	lpWithdrawRequestShares: BN;
 };
 
 export type SpotPosition = {
@@ -952,6 +1089,7 @@ export type SpotPosition = {
 	openBids: BN;
 	openAsks: BN;
 	cumulativeDeposits: BN;
+	maturityTs: number;
 };
 
 export type Order = {
@@ -1065,6 +1203,9 @@ export type TakerInfo = {
 export type ReferrerInfo = {
 	referrer: PublicKey;
 	referrerStats: PublicKey;
+	// stats of the referrer's own referrer, accrued the sub-affiliate share of referral tier rewards.
+	// required for perp fills when the referrer has a referrer and the share is enabled
+	subAffiliateStats?: PublicKey;
 };
 
 type ExactType<T> = Pick<T, keyof T>;
@@ -1123,6 +1264,48 @@ export type FeeTier = {
 	refereeFeeDenominator: number;
 };
 
+export type FeeTierQualification = {
+	volume30DThreshold: BN;
+	ifStakedQuoteAssetAmountThreshold: BN;
+	ifStakedGovTokenAmountThreshold: BN;
+};
+
+export type FeeTierQualifications = {
+	feeTierQualifications: FeeTierQualification[];
+	marketType: MarketType;
+};
+
+export type MarketFeeStructure = {
+	feeStructure: FeeStructure;
+	marketIndex: number;
+	marketType: MarketType;
+};
+
+export type PerpMarketDepthBounds = {
+	minSqrtK: BN;
+	maxSqrtK: BN;
+	marketIndex: number;
+};
+
+export type OracleCircuitBreakerWindow = {
+	referencePrice: BN;
+	referenceSlot: BN;
+	windowSlots: BN;
+	marketIndex: number;
+	marketType: MarketType;
+};
+
+export type ReferralTierConfig = {
+	tiers: ReferralTier[];
+	subAffiliateRewardNumerator: number;
+};
+
+export type ReferralTier = {
+	refereeVolume30DThreshold: BN;
+	referrerRewardNumerator: number;
+	refereeFeeNumerator: number;
+};
+
 export type OrderFillerRewardStructure = {
 	rewardNumerator: BN;
 	rewardDenominator: BN;
@@ -1142,6 +1325,19 @@ export type OracleGuardRails = {
 	};
 };
 
+export type ValidityGuardRailsOverride = {
+	confidenceIntervalMaxSize: number;
+	slotsBeforeStaleForAmm: number;
+	slotsBeforeStaleForMargin: number;
+	tooVolatileRatio: number;
+};
+
+export type OracleCircuitBreaker = {
+	threshold: number;
+	cooldown: number;
+	trippedTs: number;
+};
+
 export type PrelaunchOracle = {
 	price: BN;
 	maxPrice: BN;
@@ -1149,6 +1345,36 @@ export type PrelaunchOracle = {
 	ammLastUpdateSlot: BN;
 	lastUpdateSlot: BN;
 	perpMarketIndex: number;
+	maxPriceChangePerSlot: number;
+	fillVwap: BN;
+	fillVwapVolume: BN;
+	lastFillTs: BN;
+};
+
+export type OracleAggregatorSource = {
+	oracle: PublicKey;
+	oracleSource: OracleSource;
+};
+
+export type OracleAggregator = {
+	sources: OracleAggregatorSource[];
+	price: BN;
+	confidence: BN;
+	lastUpdateSlot: BN;
+	delay: BN;
+	aggregatorId: number;
+	numSources: number;
+	contributingSources: number;
+	minValidSources: number;
+};
+
+export type SignedPriceAccount = {
+	authority: PublicKey;
+	price: BN;
+	confidence: BN;
+	postedSlot: BN;
+	exponent: number;
+	feedId: number;
 };
 
 export type MarginCategory = 'Initial' | 'Maintenance';
@@ -1165,6 +1391,118 @@ export type InsuranceFundStake = {
 	lastWithdrawRequestShares: BN;
 	lastWithdrawRequestValue: BN;
 	lastWithdrawRequestTs: BN;
+
+	tranche: InsuranceFundTranche;
+	realizedPnl: BN;
+};
+
+export type InsuranceFundPerformance = {
+	cumulativeRevenue: BN;
+	cumulativeLosses: BN;
+	lastValuePerShare: BN;
+	lastUpdateTs: BN;
+	lastRevenueSettleTs: BN;
+	lastRevenueApy: BN;
+	marketIndex: number;
+	tranche: InsuranceFundTranche;
+};
+
+export type FuelSeason = {
+	rewardMint: PublicKey;
+	startTs: BN;
+	endTs: BN;
+	redemptionRate: BN;
+	totalFuelRedeemed: BN;
+	totalRewardsRedeemed: BN;
+	season: number;
+};
+
+export type UserFuelSeasonRecord = {
+	authority: PublicKey;
+	fuelInsurance: BN;
+	fuelDeposits: BN;
+	fuelBorrows: BN;
+	fuelPositions: BN;
+	fuelTaker: BN;
+	fuelMaker: BN;
+	fuelLp: BN;
+	fuelKeeper: BN;
+	lastSweepTs: BN;
+	fuelRedeemed: BN;
+	season: number;
+};
+
+export type MakerRewardsEntry = {
+	depthScore: BN;
+	lastEpochDepthScore: BN;
+	user: PublicKey;
+	makerVolume: BN;
+	lastEpochMakerVolume: BN;
+	restingBaseAssetAmount: BN;
+	lastSampleTs: BN;
+	lastSampleSlot: BN;
+	lastEpochClaimed: boolean;
+};
+
+export type MakerRewardsProgram = {
+	totalDepthScore: BN;
+	lastEpochTotalDepthScore: BN;
+	rewardMint: PublicKey;
+	makers: MakerRewardsEntry[];
+	epochStartTs: BN;
+	epochDuration: BN;
+	epochRewards: BN;
+	lastEpochDepthRewards: BN;
+	lastEpochVolumeRewards: BN;
+	totalMakerVolume: BN;
+	lastEpochTotalMakerVolume: BN;
+	totalRewardsClaimed: BN;
+	minMakerVolume30d: BN;
+	maxOracleDistance: number;
+	volumeRewardShare: number;
+	epoch: number;
+	marketIndex: number;
+};
+
+export type InsuranceFundStakePerformance = {
+	ifShares: BN;
+	value: BN;
+	costBasis: BN;
+	realizedPnl: BN;
+	unrealizedPnl: BN;
+	earned: BN;
+	lost: BN;
+};
+
+export type SeniorInsuranceFund = {
+	insuranceFund: {
+		vault: PublicKey;
+		totalShares: BN;
+		userShares: BN;
+		sharesBase: BN;
+		unstakingPeriod: BN;
+		lastRevenueSettleTs: BN;
+		revenueSettlePeriod: BN;
+		totalFactor: number;
+		userFactor: number;
+	};
+	revenueShare: number;
+	marketIndex: number;
+};
+
+export type PerpInsuranceFund = {
+	insuranceFund: {
+		vault: PublicKey;
+		totalShares: BN;
+		userShares: BN;
+		sharesBase: BN;
+		unstakingPeriod: BN;
+		lastRevenueSettleTs: BN;
+		revenueSettlePeriod: BN;
+		totalFactor: number;
+		userFactor: number;
+	};
+	marketIndex: number;
 };
 
 export type SerumV3FulfillmentConfigAccount = {
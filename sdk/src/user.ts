@@ -3,6 +3,8 @@ import { EventEmitter } from 'events';
 import StrictEventEmitter from 'strict-event-emitter-types';
 import { DriftClient } from './driftClient';
 import {
+	FeeTierQualification,
+	FeeTierQualifications,
 	HealthComponent,
 	HealthComponents,
 	isVariant,
@@ -97,8 +99,32 @@ import {
 	calculateSpotFuelBonus,
 	calculatePerpFuelBonus,
 	calculateInsuranceFuelBonus,
+	calculateLpFuelBonus,
 } from './math/fuel';
 
+function getDefaultFeeTierQualifications(
+	marketType: MarketType
+): FeeTierQualification[] {
+	if (!isVariant(marketType, 'perp')) {
+		return [];
+	}
+
+	// mirrors FeeTierQualifications::perps_default in the program
+	const thresholds = [
+		[0, 0, 0],
+		[1_000_000, 499, 999],
+		[5_000_000, 999, 9_999],
+		[10_000_000, 1_999, 49_999],
+		[50_000_000, 4_999, 99_999],
+		[100_000_000, 9_999, 119_500],
+	];
+	return thresholds.map(([volume, stakedQuote, stakedGovToken]) => ({
+		volume30DThreshold: new BN(volume).mul(QUOTE_PRECISION),
+		ifStakedQuoteAssetAmountThreshold: new BN(stakedQuote).mul(QUOTE_PRECISION),
+		ifStakedGovTokenAmountThreshold: new BN(stakedGovToken).mul(QUOTE_PRECISION),
+	}));
+}
+
 export class User {
 	driftClient: DriftClient;
 	userAccountPublicKey: PublicKey;
@@ -214,7 +240,8 @@ export class User {
 		marketIndex: number
 	): SpotPosition | undefined {
 		return userAccount.spotPositions.find(
-			(position) => position.marketIndex === marketIndex
+			(position) =>
+				position.marketIndex === marketIndex && position.maturityTs === 0
 		);
 	}
 
@@ -902,6 +929,8 @@ export class User {
 		takerFuel: BN;
 		makerFuel: BN;
 		insuranceFuel: BN;
+		lpFuel: BN;
+		keeperFuel: BN;
 	} {
 		const userAccount: UserAccount = this.getUserAccount();
 
@@ -912,6 +941,8 @@ export class User {
 			depositFuel: ZERO,
 			borrowFuel: ZERO,
 			positionFuel: ZERO,
+			lpFuel: ZERO,
+			keeperFuel: ZERO,
 		};
 
 		if (includeSettled) {
@@ -930,6 +961,8 @@ export class User {
 			result.positionFuel = result.positionFuel.add(
 				new BN(userStats.fuelPositions)
 			);
+			result.lpFuel = result.lpFuel.add(new BN(userStats.fuelLp));
+			result.keeperFuel = result.keeperFuel.add(new BN(userStats.fuelKeeper));
 		}
 
 		if (includeUnsettled) {
@@ -1008,6 +1041,20 @@ export class User {
 							fuelBonusNumerator
 						)
 					);
+
+					if (perpPosition.lpShares.gt(ZERO)) {
+						const lpSharesValue = perpPosition.lpShares
+							.mul(oraclePriceData.price.abs())
+							.div(AMM_RESERVE_PRECISION);
+
+						result.lpFuel = result.lpFuel.add(
+							calculateLpFuelBonus(
+								perpMarketAccount,
+								lpSharesValue,
+								fuelBonusNumerator
+							)
+						);
+					}
 				}
 			}
 
@@ -3408,50 +3455,57 @@ export class User {
 		return newLeverage;
 	}
 
-	public getUserFeeTier(marketType: MarketType, now?: BN) {
+	/**
+	 * @param feeTierQualifications - the program's fee tier qualifications account for the market type. Falls back to the program defaults if not passed
+	 */
+	public getUserFeeTier(
+		marketType: MarketType,
+		now?: BN,
+		feeTierQualifications?: FeeTierQualifications
+	) {
 		const state = this.driftClient.getStateAccount();
+		const feeStructure = isVariant(marketType, 'perp')
+			? state.perpFeeStructure
+			: state.spotFeeStructure;
 
-		let feeTierIndex = 0;
-		if (isVariant(marketType, 'perp')) {
-			const userStatsAccount: UserStatsAccount = this.driftClient
-				.getUserStats()
-				.getAccount();
+		const userStatsAccount: UserStatsAccount = this.driftClient
+			.getUserStats()
+			.getAccount();
 
-			const total30dVolume = getUser30dRollingVolumeEstimate(
-				userStatsAccount,
-				now
-			);
+		const total30dVolume = getUser30dRollingVolumeEstimate(
+			userStatsAccount,
+			now
+		);
+		const stakedQuoteAssetAmount = userStatsAccount.ifStakedQuoteAssetAmount;
+		const stakedGovTokenAmount = userStatsAccount.ifStakedGovTokenAmount;
 
-			const stakedQuoteAssetAmount = userStatsAccount.ifStakedQuoteAssetAmount;
-			const volumeTiers = [
-				new BN(100_000_000).mul(QUOTE_PRECISION),
-				new BN(50_000_000).mul(QUOTE_PRECISION),
-				new BN(10_000_000).mul(QUOTE_PRECISION),
-				new BN(5_000_000).mul(QUOTE_PRECISION),
-				new BN(1_000_000).mul(QUOTE_PRECISION),
-			];
-			const stakedTiers = [
-				new BN(10000).mul(QUOTE_PRECISION),
-				new BN(5000).mul(QUOTE_PRECISION),
-				new BN(2000).mul(QUOTE_PRECISION),
-				new BN(1000).mul(QUOTE_PRECISION),
-				new BN(500).mul(QUOTE_PRECISION),
-			];
-
-			for (let i = 0; i < volumeTiers.length; i++) {
-				if (
-					total30dVolume.gte(volumeTiers[i]) ||
-					stakedQuoteAssetAmount.gte(stakedTiers[i])
-				) {
-					feeTierIndex = 5 - i;
-					break;
-				}
-			}
+		const meetsThreshold = (amount: BN, threshold: BN) =>
+			!threshold.eq(ZERO) && amount.gte(threshold);
+
+		const qualifications =
+			feeTierQualifications?.feeTierQualifications ??
+			getDefaultFeeTierQualifications(marketType);
 
-			return state.perpFeeStructure.feeTiers[feeTierIndex];
+		let feeTierIndex = 0;
+		for (let i = qualifications.length - 1; i > 0; i--) {
+			const qualification = qualifications[i];
+			if (
+				meetsThreshold(total30dVolume, qualification.volume30DThreshold) ||
+				meetsThreshold(
+					stakedQuoteAssetAmount,
+					qualification.ifStakedQuoteAssetAmountThreshold
+				) ||
+				meetsThreshold(
+					stakedGovTokenAmount,
+					qualification.ifStakedGovTokenAmountThreshold
+				)
+			) {
+				feeTierIndex = i;
+				break;
+			}
 		}
 
-		return state.spotFeeStructure.feeTiers[feeTierIndex];
+		return feeStructure.feeTiers[feeTierIndex];
 	}
 
 	/**
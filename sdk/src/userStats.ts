@@ -89,8 +89,8 @@ export class UserStats {
 	public static getOldestActionTs(account: UserStatsAccount): number {
 		return Math.min(
 			account.lastFillerVolume30DTs.toNumber(),
-			account.lastMakerVolume30DTs.toNumber(),
-			account.lastTakerVolume30DTs.toNumber()
+			account.lastMakerVolume30DTs,
+			account.lastTakerVolume30DTs
 		);
 	}
 }
@@ -190,6 +190,9 @@ export const mockPerpMarkets: Array<PerpMarketAccount> = [
 		fuelBoostPosition: 0,
 		fuelBoostMaker: 0,
 		fuelBoostTaker: 0,
+		fuelBoostLp: 0,
+		fuelBoostKeeper: 0,
+		hasMakerRewardsProgram: false,
 	},
 	{
 		status: MarketStatus.INITIALIZED,
@@ -232,6 +235,9 @@ export const mockPerpMarkets: Array<PerpMarketAccount> = [
 		fuelBoostPosition: 0,
 		fuelBoostMaker: 0,
 		fuelBoostTaker: 0,
+		fuelBoostLp: 0,
+		fuelBoostKeeper: 0,
+		hasMakerRewardsProgram: false,
 	},
 	{
 		status: MarketStatus.INITIALIZED,
@@ -274,6 +280,9 @@ export const mockPerpMarkets: Array<PerpMarketAccount> = [
 		fuelBoostPosition: 0,
 		fuelBoostMaker: 0,
 		fuelBoostTaker: 0,
+		fuelBoostLp: 0,
+		fuelBoostKeeper: 0,
+		hasMakerRewardsProgram: false,
 	},
 ];
 
@@ -629,6 +638,10 @@ export const mockStateAccount: StateAccount = {
 	whitelistMint: PublicKey.default,
 	maxNumberOfSubAccounts: 0,
 	maxInitializeUserFee: 0,
+	fuelSeason: 0,
+	hasReferralTierConfig: false,
+	hasPerpFeeTierQualifications: false,
+	hasSpotFeeTierQualifications: false,
 };
 
 export class MockUserMap implements UserMapInterface {